@@ -9,8 +9,8 @@ use std::{
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use tracing_subscriber::{
-    fmt::format::FmtSpan, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt,
-    Layer,
+    filter::EnvFilter, fmt::format::FmtSpan, layer::SubscriberExt, registry::LookupSpan, reload,
+    util::SubscriberInitExt, Layer, Registry,
 };
 
 // SAFETY: Configuration file needs many bools.
@@ -85,7 +85,12 @@ impl LogConfig {
     #[must_use]
     pub const fn default_show_fn_latency() -> bool { false }
 
-    pub fn registry(&self) {
+    pub fn registry(&self) { let _handle = self.reloadable_registry(); }
+
+    /// Same as [`registry`](Self::registry), returning a handle to replace the
+    /// log filters later
+    #[must_use]
+    pub fn reloadable_registry(&self) -> LogFilterHandle {
         let Self {
             emit_journald,
             file_path,
@@ -96,7 +101,7 @@ impl LogConfig {
             show_fn_latency,
         } = self;
 
-        let filter_layer = tracing_subscriber::filter::EnvFilter::new(log_filters.as_str());
+        let (filter_layer, handle) = reload::Layer::new(EnvFilter::new(log_filters.as_str()));
 
         // Display function latency in logs, for example:
         // `XXX_FUNCTION close, time.busy: 37.5µs, time.idle: 2.01s`.
@@ -113,6 +118,26 @@ impl LogConfig {
             .with(emit_stdout.then(|| LogDriver::Stdout(formatter.clone()).layer(fmt_span.clone())))
             .with(emit_stderr.then(|| LogDriver::Stderr(formatter.clone()).layer(fmt_span)))
             .init();
+
+        LogFilterHandle { handle }
+    }
+}
+
+/// Replaces the filters of the registry installed by
+/// [`LogConfig::reloadable_registry`]
+#[derive(Clone, Debug)]
+pub struct LogFilterHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl LogFilterHandle {
+    /// Filter events with `log_filters`, e.g. `info,sqlx=warn`, from now on
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the registry has been dropped
+    pub fn reload(&self, log_filters: &str) -> Result<(), reload::Error> {
+        self.handle.reload(EnvFilter::new(log_filters))
     }
 }
 
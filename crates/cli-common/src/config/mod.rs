@@ -1,3 +1,3 @@
 mod log;
 
-pub use self::log::{LogConfig, LogFormatter};
+pub use self::log::{LogConfig, LogFilterHandle, LogFormatter};
@@ -0,0 +1,40 @@
+use snafu::Snafu;
+
+/// Errors that can occur in the events crate.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    /// Failed to connect to the NATS server.
+    #[snafu(display("Failed to connect to NATS server {url}: {source}"))]
+    ConnectNats {
+        /// The URL of the NATS server.
+        url: String,
+        /// The underlying NATS error.
+        source: async_nats::ConnectError,
+    },
+
+    /// JetStream did not acknowledge an event.
+    #[snafu(display("Failed to publish event to NATS subject `{subject}`: {source}"))]
+    PublishNats {
+        /// The subject the event was published to.
+        subject: String,
+        /// The underlying JetStream error.
+        source: async_nats::jetstream::context::PublishError,
+    },
+
+    /// Failed to create the Kafka producer.
+    #[snafu(display("Failed to create Kafka producer: {source}"))]
+    CreateKafkaProducer {
+        /// The underlying Kafka error.
+        source: rdkafka::error::KafkaError,
+    },
+
+    /// Kafka did not acknowledge an event.
+    #[snafu(display("Failed to publish event to Kafka topic `{topic}`: {source}"))]
+    PublishKafka {
+        /// The topic the event was published to.
+        topic: String,
+        /// The underlying Kafka error.
+        source: rdkafka::error::KafkaError,
+    },
+}
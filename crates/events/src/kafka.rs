@@ -0,0 +1,99 @@
+//! Publisher shipping events to a Kafka topic.
+
+use async_trait::async_trait;
+use rdkafka::{
+    message::{Header, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+    ClientConfig,
+};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{error, Error, EventPublisher, Message};
+
+/// Configuration for the Kafka publisher.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// Comma-separated `host:port` list of the bootstrap brokers.
+    pub brokers: String,
+
+    /// Topic the events are published to.
+    pub topic: String,
+
+    /// How long the producer retries an event before giving up on it, in
+    /// milliseconds.
+    #[serde(default = "Config::default_message_timeout_milliseconds")]
+    pub message_timeout_milliseconds: u64,
+}
+
+impl Config {
+    /// Thirty seconds.
+    #[must_use]
+    pub const fn default_message_timeout_milliseconds() -> u64 { 30_000 }
+}
+
+/// Publisher shipping events to Kafka.
+///
+/// An event counts as published once all in-sync replicas acknowledged it.
+/// Events are keyed, so the events of one entity land on one partition in
+/// order, and carry their ID in the `event_id` header for consumers to drop
+/// redeliveries.
+#[derive(Clone)]
+pub struct Publisher {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl Publisher {
+    /// Creates a new Kafka publisher, the brokers are connected lazily.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the producer configuration is rejected.
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .set("message.timeout.ms", config.message_timeout_milliseconds.to_string())
+            .create()
+            .context(error::CreateKafkaProducerSnafu)?;
+
+        Ok(Self { producer, topic: config.topic.clone() })
+    }
+}
+
+#[async_trait]
+impl EventPublisher for Publisher {
+    async fn publish(&self, message: &Message<'_>) -> Result<(), Error> {
+        let headers = OwnedHeaders::new()
+            .insert(Header { key: "event_id", value: Some(message.id) })
+            .insert(Header { key: "event_kind", value: Some(message.kind) });
+        let record = FutureRecord::to(&self.topic)
+            .key(message.key)
+            .payload(message.payload)
+            .headers(headers);
+
+        let (partition, offset) =
+            self.producer.send(record, Timeout::Never).await.map_err(|(source, _message)| {
+                Error::PublishKafka { topic: self.topic.clone(), source }
+            })?;
+
+        tracing::debug!(id = message.id, partition, offset, "Event published to Kafka");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: Config =
+            serde_yaml::from_str("brokers: localhost:9092\ntopic: mpc-events\n").unwrap();
+        assert_eq!(config.topic, "mpc-events");
+        assert_eq!(config.message_timeout_milliseconds, 30_000);
+    }
+}
@@ -0,0 +1,45 @@
+//! # Events Crate
+//!
+//! This crate ships domain events to a message broker. The events themselves
+//! are produced and stored by the application, a publisher only delivers
+//! them and reports whether the broker accepted each one.
+//!
+//! ## Features
+//!
+//! - NATS JetStream publisher, deduplicating redeliveries by event ID
+//! - Kafka publisher with idempotent production, keyed for per-entity ordering
+//! - Logging publisher, for local development without a broker
+
+mod error;
+pub mod kafka;
+pub mod log;
+pub mod nats;
+
+use async_trait::async_trait;
+pub use error::Error;
+
+/// An event as shipped to the broker.
+#[derive(Clone, Copy, Debug)]
+pub struct Message<'a> {
+    /// Unique ID of the event, brokers use it to recognize redeliveries.
+    pub id: &'a str,
+    /// Kind of the event, e.g. `user_created`.
+    pub kind: &'a str,
+    /// Key the broker keeps the events in order by, e.g. the ID of a user.
+    pub key: &'a str,
+    /// The JSON encoded event.
+    pub payload: &'a [u8],
+}
+
+/// Trait for publishers shipping events to a broker.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    /// Publishes an event, returning once the broker has acknowledged it.
+    ///
+    /// Publishing the same event again is allowed, delivery is at least once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the broker does not acknowledge the event.
+    async fn publish(&self, message: &Message<'_>) -> Result<(), Error>;
+}
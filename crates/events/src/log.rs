@@ -0,0 +1,30 @@
+//! Publisher that only logs events, for local development where no broker is
+//! running.
+
+use async_trait::async_trait;
+
+use crate::{Error, EventPublisher, Message};
+
+/// Publisher logging events instead of shipping them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Publisher;
+
+impl Publisher {
+    /// Creates a new logging publisher.
+    #[must_use]
+    pub const fn new() -> Self { Self }
+}
+
+#[async_trait]
+impl EventPublisher for Publisher {
+    async fn publish(&self, message: &Message<'_>) -> Result<(), Error> {
+        tracing::info!(
+            id = message.id,
+            kind = message.kind,
+            key = message.key,
+            payload = %String::from_utf8_lossy(message.payload),
+            "Event not published, no broker is configured"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! Publisher shipping events to a NATS JetStream stream.
+
+use async_nats::{jetstream, HeaderMap};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{error, Error, EventPublisher, Message};
+
+/// Header JetStream drops duplicate messages by.
+const MESSAGE_ID_HEADER: &str = "Nats-Msg-Id";
+
+/// Configuration for the NATS publisher.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// URL of the NATS server, e.g. `nats://localhost:4222`.
+    pub url: String,
+
+    /// Subject prefix, events are published to `<subject>.<kind>`. A JetStream
+    /// stream must capture the subjects, e.g. `<subject>.>`.
+    pub subject: String,
+}
+
+/// Publisher shipping events to NATS JetStream.
+///
+/// An event counts as published once the stream acknowledged it. The event ID
+/// is sent as `Nats-Msg-Id`, so the stream drops an event published again
+/// within its duplicate window.
+#[derive(Clone, Debug)]
+pub struct Publisher {
+    jetstream: jetstream::Context,
+    subject: String,
+}
+
+impl Publisher {
+    /// Connects to the NATS server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the NATS server cannot be reached.
+    pub async fn connect(config: &Config) -> Result<Self, Error> {
+        let client = async_nats::connect(config.url.as_str())
+            .await
+            .context(error::ConnectNatsSnafu { url: config.url.clone() })?;
+
+        Ok(Self { jetstream: jetstream::new(client), subject: config.subject.clone() })
+    }
+}
+
+#[async_trait]
+impl EventPublisher for Publisher {
+    async fn publish(&self, message: &Message<'_>) -> Result<(), Error> {
+        let subject = format!("{}.{}", self.subject, message.kind);
+        let mut headers = HeaderMap::new();
+        headers.insert(MESSAGE_ID_HEADER, message.id);
+
+        let ack = self
+            .jetstream
+            .publish_with_headers(subject.clone(), headers, Bytes::copy_from_slice(message.payload))
+            .await
+            .context(error::PublishNatsSnafu { subject: subject.clone() })?
+            .await
+            .context(error::PublishNatsSnafu { subject })?;
+
+        tracing::debug!(
+            id = message.id,
+            stream = %ack.stream,
+            sequence = ack.sequence,
+            duplicate = ack.duplicate,
+            "Event published to NATS"
+        );
+        Ok(())
+    }
+}
@@ -8,9 +8,24 @@ pub enum Error {
     #[snafu(display("Could not setup metrics, error: {source}"))]
     SetupMetrics { source: prometheus::Error, backtrace: Backtrace },
 
+    #[snafu(display("Metric `{name}` already exists with another type or labels"))]
+    ConflictingMetric { name: String },
+
     #[snafu(display("Error occurs while binding metrics server, error: {source}"))]
     BindMetricsServer { source: std::io::Error },
 
     #[snafu(display("Error occurs while serving metrics server, error: {message}"))]
     ServeMetricsServer { message: String },
+
+    #[snafu(display("Invalid Pushgateway endpoint `{endpoint}`"))]
+    InvalidPushGatewayEndpoint { endpoint: String },
+
+    #[snafu(display("Could not create the Pushgateway client, error: {source}"))]
+    BuildPushGatewayClient { source: reqwest::Error },
+
+    #[snafu(display("Could not encode metrics, error: {source}"))]
+    EncodeMetrics { source: prometheus::Error },
+
+    #[snafu(display("Could not push metrics to {url}, error: {source}"))]
+    PushMetrics { url: reqwest::Url, source: reqwest::Error },
 }
@@ -0,0 +1,176 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use prometheus::{
+    core::Collector, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+};
+use snafu::ResultExt;
+
+use crate::error::{self, Error};
+
+/// Handle for creating application metrics in a shared registry
+///
+/// The constructors return the metric already created under the same name, so
+/// a module may ask for its metrics whenever it records them. Clones share the
+/// registry.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsHandle {
+    registry: Registry,
+    created: Arc<Mutex<HashMap<String, Created>>>,
+}
+
+/// Metric created through a [`MetricsHandle`], with the labels it was created
+/// with
+#[derive(Clone, Debug)]
+enum Created {
+    Counter(IntCounterVec, Vec<String>),
+    Gauge(IntGaugeVec, Vec<String>),
+    Histogram(HistogramVec, Vec<String>),
+}
+
+impl MetricsHandle {
+    /// Handle creating metrics in `registry`, the default creates them in a
+    /// registry of its own
+    #[must_use]
+    pub fn new(registry: Registry) -> Self { Self { registry, created: Arc::default() } }
+
+    /// Counter `name` partitioned by `labels`, use no labels for a single
+    /// counter
+    ///
+    /// # Errors
+    ///
+    /// * if `name` or `labels` are invalid
+    /// * if a metric named `name` was created with another type or labels
+    pub fn counter(&self, name: &str, help: &str, labels: &[&str]) -> Result<IntCounterVec, Error> {
+        self.get_or_create(
+            name,
+            labels,
+            |created| match created {
+                Created::Counter(counter, _) => Some(counter.clone()),
+                _ => None,
+            },
+            || {
+                let counter = IntCounterVec::new(Opts::new(name, help), labels)
+                    .context(error::SetupMetricsSnafu)?;
+                Ok((counter.clone(), Created::Counter(counter, to_owned(labels))))
+            },
+        )
+    }
+
+    /// Gauge `name` partitioned by `labels`, use no labels for a single gauge
+    ///
+    /// # Errors
+    ///
+    /// * if `name` or `labels` are invalid
+    /// * if a metric named `name` was created with another type or labels
+    pub fn gauge(&self, name: &str, help: &str, labels: &[&str]) -> Result<IntGaugeVec, Error> {
+        self.get_or_create(
+            name,
+            labels,
+            |created| match created {
+                Created::Gauge(gauge, _) => Some(gauge.clone()),
+                _ => None,
+            },
+            || {
+                let gauge = IntGaugeVec::new(Opts::new(name, help), labels)
+                    .context(error::SetupMetricsSnafu)?;
+                Ok((gauge.clone(), Created::Gauge(gauge, to_owned(labels))))
+            },
+        )
+    }
+
+    /// Histogram `name` with the default buckets, partitioned by `labels`
+    ///
+    /// # Errors
+    ///
+    /// * if `name` or `labels` are invalid
+    /// * if a metric named `name` was created with another type or labels
+    pub fn histogram(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> Result<HistogramVec, Error> {
+        self.get_or_create(
+            name,
+            labels,
+            |created| match created {
+                Created::Histogram(histogram, _) => Some(histogram.clone()),
+                _ => None,
+            },
+            || {
+                let histogram = HistogramVec::new(HistogramOpts::new(name, help), labels)
+                    .context(error::SetupMetricsSnafu)?;
+                Ok((histogram.clone(), Created::Histogram(histogram, to_owned(labels))))
+            },
+        )
+    }
+
+    fn get_or_create<M>(
+        &self,
+        name: &str,
+        labels: &[&str],
+        get: impl FnOnce(&Created) -> Option<M>,
+        create: impl FnOnce() -> Result<(M, Created), Error>,
+    ) -> Result<M, Error> {
+        let mut created = self.created.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(existing) = created.get(name) {
+            return get(existing)
+                .filter(|_| existing.labels() == labels)
+                .ok_or_else(|| error::ConflictingMetricSnafu { name }.build());
+        }
+
+        let (metric, entry) = create()?;
+        self.registry.register(entry.collector()).context(error::SetupMetricsSnafu)?;
+        let _previous = created.insert(name.to_string(), entry);
+
+        Ok(metric)
+    }
+}
+
+impl Created {
+    fn labels(&self) -> &[String] {
+        match self {
+            Self::Counter(_, labels) | Self::Gauge(_, labels) | Self::Histogram(_, labels) => {
+                labels
+            }
+        }
+    }
+
+    fn collector(&self) -> Box<dyn Collector> {
+        match self {
+            Self::Counter(counter, _) => Box::new(counter.clone()),
+            Self::Gauge(gauge, _) => Box::new(gauge.clone()),
+            Self::Histogram(histogram, _) => Box::new(histogram.clone()),
+        }
+    }
+}
+
+fn to_owned(labels: &[&str]) -> Vec<String> { labels.iter().map(ToString::to_string).collect() }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::MetricsHandle;
+
+    #[test]
+    fn test_get_or_create() {
+        let registry = Registry::new();
+        let handle = MetricsHandle::new(registry.clone());
+
+        let counter = handle.counter("users_created_total", "Users created", &[]).unwrap();
+        counter.with_label_values(&[]).inc();
+
+        // asking again returns the same counter
+        let again = handle.counter("users_created_total", "Users created", &[]).unwrap();
+        assert_eq!(again.with_label_values(&[]).get(), 1);
+        assert_eq!(registry.gather().len(), 1);
+
+        assert!(handle.gauge("users_created_total", "Users created", &[]).is_err());
+        assert!(handle.counter("users_created_total", "Users created", &["kind"]).is_err());
+    }
+}
@@ -1,32 +1,95 @@
 pub mod error;
+mod handle;
+mod openmetrics;
+mod push;
 mod server;
 mod traits;
 
-pub use self::{error::Error, server::start_metrics_server, traits::Metrics};
+use snafu::ResultExt;
 
+pub use self::{
+    error::Error,
+    handle::MetricsHandle,
+    openmetrics::{encode_openmetrics, Exemplars},
+    push::{start_push_gateway, PushGatewayConfig},
+    server::start_metrics_server,
+    traits::Metrics,
+};
+
+/// Registry exported by the metrics server, clones share it
 #[derive(Clone, Debug)]
 pub struct DefaultMetrics {
     registry: prometheus::Registry,
+
+    handle: MetricsHandle,
+
+    exemplars: Exemplars,
 }
 
 impl DefaultMetrics {
-    // FIXME: we have to check the result in the near future
-    #[allow(clippy::unnecessary_wraps, clippy::missing_errors_doc)]
-    pub fn new() -> Result<Self, Error> {
+    #[must_use]
+    pub fn new() -> Self {
         let registry = prometheus::Registry::new();
+        let handle = MetricsHandle::new(registry.clone());
+
+        Self { registry, handle, exemplars: Exemplars::default() }
+    }
 
-        Ok(Self { registry })
+    /// Register a collector, its metrics are exported with the default ones.
+    ///
+    /// # Errors
+    ///
+    /// * if a collector with the same descriptors is already registered
+    pub fn register(&self, collector: Box<dyn prometheus::core::Collector>) -> Result<(), Error> {
+        self.registry.register(collector).context(error::SetupMetricsSnafu)
     }
+
+    /// Handle creating application metrics in this registry
+    #[must_use]
+    pub fn handle(&self) -> MetricsHandle { self.handle.clone() }
+}
+
+impl Default for DefaultMetrics {
+    fn default() -> Self { Self::new() }
 }
 
 impl Metrics for DefaultMetrics {
     fn gather(&self) -> Vec<prometheus::proto::MetricFamily> { self.registry.gather() }
+
+    fn exemplars(&self) -> Option<Exemplars> { Some(self.exemplars.clone()) }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::DefaultMetrics;
+    use crate::{DefaultMetrics, Metrics};
 
     #[test]
-    fn test_new() { drop(DefaultMetrics::new().unwrap()); }
+    fn test_new() {
+        assert!(DefaultMetrics::new().gather().is_empty());
+    }
+
+    #[test]
+    fn test_register() {
+        let metrics = DefaultMetrics::new();
+        let counter = prometheus::IntCounter::new("test_total", "test counter").unwrap();
+        counter.inc();
+
+        metrics.register(Box::new(counter.clone())).unwrap();
+        assert!(metrics.register(Box::new(counter)).is_err());
+
+        let families = metrics.gather();
+        assert_eq!(families.len(), 1);
+        assert_eq!(families[0].get_name(), "test_total");
+    }
+
+    #[test]
+    fn test_handle_shares_registry() {
+        let metrics = DefaultMetrics::new();
+        let gauge = metrics.handle().gauge("outbox_depth", "outbox depth", &[]).unwrap();
+        gauge.with_label_values(&[]).set(3);
+
+        let families = metrics.gather();
+        assert_eq!(families.len(), 1);
+        assert_eq!(families[0].get_name(), "outbox_depth");
+    }
 }
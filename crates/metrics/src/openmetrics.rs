@@ -0,0 +1,279 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+
+/// Label pairs of a series, sorted by name
+type SeriesLabels = Vec<(String, String)>;
+
+/// Exemplars by bucket index, per series
+type ExemplarMap = HashMap<(String, SeriesLabels), BTreeMap<usize, Exemplar>>;
+
+/// Exemplars of the histogram buckets, exported in the OpenMetrics format
+///
+/// Each bucket of a series keeps the last exemplar observed in it, clones
+/// share the exemplars.
+#[derive(Clone, Debug, Default)]
+pub struct Exemplars {
+    inner: Arc<Mutex<ExemplarMap>>,
+}
+
+/// Observation linking a bucket to the trace it was recorded in
+#[derive(Clone, Debug)]
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    timestamp: SystemTime,
+}
+
+impl Exemplars {
+    /// Record `value` observed by histogram `name` with `buckets` in the trace
+    /// `trace_id`, replacing the exemplar of its bucket
+    pub fn observe(
+        &self,
+        name: &str,
+        labels: &[(&str, &str)],
+        buckets: &[f64],
+        value: f64,
+        trace_id: &str,
+    ) {
+        // the `+Inf` bucket follows the last upper bound
+        let bucket = buckets.iter().position(|upper_bound| value <= *upper_bound);
+        let exemplar =
+            Exemplar { trace_id: trace_id.to_string(), value, timestamp: SystemTime::now() };
+
+        let labels = sorted(labels.iter().map(|(name, value)| (*name, *value)));
+        let _previous = self
+            .lock()
+            .entry((name.to_string(), labels))
+            .or_default()
+            .insert(bucket.unwrap_or(buckets.len()), exemplar);
+    }
+
+    /// Exemplars of the series by bucket index
+    fn series(&self, name: &str, labels: &[LabelPair]) -> BTreeMap<usize, Exemplar> {
+        let labels = sorted(labels.iter().map(|label| (label.get_name(), label.get_value())));
+        self.lock().get(&(name.to_string(), labels)).cloned().unwrap_or_default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ExemplarMap> {
+        // exemplars are replaced whole, a poisoned lock is still usable
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn sorted<'a>(labels: impl Iterator<Item = (&'a str, &'a str)>) -> SeriesLabels {
+    let mut labels =
+        labels.map(|(name, value)| (name.to_string(), value.to_string())).collect::<Vec<_>>();
+    labels.sort();
+    labels
+}
+
+/// Encode `families` in the OpenMetrics text format, with the exemplars of the
+/// histogram buckets
+///
+/// Counters are exported as `<name>_total` with `<name>` as the family name, as
+/// OpenMetrics requires.
+#[must_use]
+pub fn encode_openmetrics(families: &[MetricFamily], exemplars: Option<&Exemplars>) -> String {
+    let mut buffer = String::new();
+    write_families(&mut buffer, families, exemplars)
+        .expect("writing to a `String` should not fail; qed");
+    buffer
+}
+
+fn write_families(
+    writer: &mut String,
+    families: &[MetricFamily],
+    exemplars: Option<&Exemplars>,
+) -> fmt::Result {
+    for family in families {
+        let name = family.get_name();
+        let (family_name, metric_type) = match family.get_field_type() {
+            MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::SUMMARY => (name, "summary"),
+            MetricType::UNTYPED => (name, "unknown"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+        };
+
+        writeln!(writer, "# TYPE {family_name} {metric_type}")?;
+        if !family.get_help().is_empty() {
+            writeln!(writer, "# HELP {family_name} {}", escape(family.get_help()))?;
+        }
+
+        for metric in family.get_metric() {
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    let value = float(metric.get_counter().get_value());
+                    write_sample(writer, family_name, "_total", metric, None, &value)?;
+                }
+                MetricType::GAUGE => {
+                    let value = float(metric.get_gauge().get_value());
+                    write_sample(writer, name, "", metric, None, &value)?;
+                }
+                MetricType::UNTYPED => {
+                    let value = float(metric.get_untyped().get_value());
+                    write_sample(writer, name, "", metric, None, &value)?;
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        let label = ("quantile", float(quantile.get_quantile()));
+                        let value = float(quantile.get_value());
+                        write_sample(writer, name, "", metric, Some(label), &value)?;
+                    }
+                    let sum = float(summary.get_sample_sum());
+                    write_sample(writer, name, "_sum", metric, None, &sum)?;
+                    let count = summary.get_sample_count().to_string();
+                    write_sample(writer, name, "_count", metric, None, &count)?;
+                }
+                MetricType::HISTOGRAM => write_histogram(writer, name, metric, exemplars)?,
+            }
+        }
+    }
+
+    writeln!(writer, "# EOF")
+}
+
+fn write_histogram(
+    writer: &mut String,
+    name: &str,
+    metric: &Metric,
+    exemplars: Option<&Exemplars>,
+) -> fmt::Result {
+    let histogram = metric.get_histogram();
+    let exemplars =
+        exemplars.map(|exemplars| exemplars.series(name, metric.get_label())).unwrap_or_default();
+
+    let buckets = histogram
+        .get_bucket()
+        .iter()
+        .filter(|bucket| bucket.get_upper_bound().is_finite())
+        .map(|bucket| (float(bucket.get_upper_bound()), bucket.get_cumulative_count()))
+        .chain([("+Inf".to_string(), histogram.get_sample_count())]);
+    for (index, (upper_bound, count)) in buckets.enumerate() {
+        let mut value = count.to_string();
+        if let Some(exemplar) = exemplars.get(&index) {
+            write_exemplar(&mut value, exemplar)?;
+        }
+        write_sample(writer, name, "_bucket", metric, Some(("le", upper_bound)), &value)?;
+    }
+
+    let count = histogram.get_sample_count().to_string();
+    write_sample(writer, name, "_count", metric, None, &count)?;
+    let sum = float(histogram.get_sample_sum());
+    write_sample(writer, name, "_sum", metric, None, &sum)
+}
+
+/// Append ` # {trace_id="…"} <value> <timestamp>` to a sample value
+fn write_exemplar(writer: &mut String, exemplar: &Exemplar) -> fmt::Result {
+    let timestamp =
+        exemplar.timestamp.duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64());
+    write!(
+        writer,
+        " # {{trace_id=\"{}\"}} {} {timestamp:.3}",
+        escape(&exemplar.trace_id),
+        float(exemplar.value),
+    )
+}
+
+fn write_sample(
+    writer: &mut String,
+    name: &str,
+    suffix: &str,
+    metric: &Metric,
+    extra_label: Option<(&str, String)>,
+    value: &str,
+) -> fmt::Result {
+    write!(writer, "{name}{suffix}")?;
+
+    let labels = metric
+        .get_label()
+        .iter()
+        .map(|label| (label.get_name(), label.get_value().to_string()))
+        .chain(extra_label)
+        .collect::<Vec<_>>();
+    if !labels.is_empty() {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        write!(writer, "{{{labels}}}")?;
+    }
+
+    writeln!(writer, " {value}")
+}
+
+fn float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value.is_sign_positive() { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        // the debug representation keeps the fraction, e.g. `1.0`
+        format!("{value:?}")
+    }
+}
+
+/// Escape a label value or help text
+fn escape(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', r#"\""#).replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{HistogramOpts, HistogramVec, IntCounter, Registry};
+
+    use super::*;
+
+    #[test]
+    fn test_encode_counter() {
+        let registry = Registry::new();
+        let counter = IntCounter::new("users_created_total", "Users \"created\"").unwrap();
+        counter.inc();
+        registry.register(Box::new(counter)).unwrap();
+
+        assert_eq!(
+            encode_openmetrics(&registry.gather(), None),
+            "# TYPE users_created counter\n# HELP users_created Users \
+             \\\"created\\\"\nusers_created_total 1.0\n# EOF\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_histogram_with_exemplars() {
+        let registry = Registry::new();
+        let histogram = HistogramVec::new(
+            HistogramOpts::new("latency_seconds", "Latency").buckets(vec![0.1, 1.0]),
+            &["route"],
+        )
+        .unwrap();
+        histogram.with_label_values(&["/users"]).observe(0.5);
+        histogram.with_label_values(&["/users"]).observe(2.0);
+        registry.register(Box::new(histogram)).unwrap();
+
+        let exemplars = Exemplars::default();
+        exemplars.observe("latency_seconds", &[("route", "/users")], &[0.1, 1.0], 0.5, "abc");
+        exemplars.observe("latency_seconds", &[("route", "/users")], &[0.1, 1.0], 2.0, "def");
+
+        let encoded = encode_openmetrics(&registry.gather(), Some(&exemplars));
+        let lines = encoded.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "# TYPE latency_seconds histogram");
+        assert_eq!(lines[2], "latency_seconds_bucket{route=\"/users\",le=\"0.1\"} 0");
+        assert!(lines[3].starts_with(
+            "latency_seconds_bucket{route=\"/users\",le=\"1.0\"} 1 # {trace_id=\"abc\"} 0.5 "
+        ));
+        assert!(lines[4].starts_with(
+            "latency_seconds_bucket{route=\"/users\",le=\"+Inf\"} 2 # {trace_id=\"def\"} 2.0 "
+        ));
+        assert_eq!(lines[5], "latency_seconds_count{route=\"/users\"} 2");
+        assert_eq!(lines[6], "latency_seconds_sum{route=\"/users\"} 2.5");
+        assert_eq!(lines[7], "# EOF");
+    }
+}
@@ -0,0 +1,140 @@
+use std::{future::Future, time::Duration};
+
+use prometheus::{Encoder, TextEncoder};
+use reqwest::{header, Url};
+use snafu::ResultExt;
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    error::{self, Error},
+    traits,
+};
+
+/// Timeout of a single push, shorter than any sensible push interval
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prometheus Pushgateway the metrics are pushed to, for environments which
+/// cannot be scraped
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PushGatewayConfig {
+    /// Base URL of the Pushgateway, e.g. `http://pushgateway:9091`
+    pub endpoint: String,
+
+    /// Value of the `job` grouping label
+    pub job: String,
+
+    /// Time between two pushes
+    pub interval: Duration,
+}
+
+impl PushGatewayConfig {
+    /// URL of the metric group of `job`
+    fn group_url(&self) -> Result<Url, Error> {
+        let invalid = || Error::InvalidPushGatewayEndpoint { endpoint: self.endpoint.clone() };
+
+        let mut url = Url::parse(&self.endpoint).map_err(|_| invalid())?;
+        {
+            let mut segments = url.path_segments_mut().map_err(|()| invalid())?;
+            let _segments = segments.pop_if_empty().extend(["metrics", "job", self.job.as_str()]);
+        }
+
+        Ok(url)
+    }
+}
+
+/// Push the metrics every `interval` until `shutdown_signal`, then push them
+/// once more
+///
+/// A failed push is logged and the metrics are pushed again on the next tick,
+/// each push replaces the metrics of the previous one.
+///
+/// # Errors
+///
+/// * if the endpoint is not a valid base URL
+/// * if the HTTP client cannot be created
+pub async fn start_push_gateway<Metrics, ShutdownSignal>(
+    config: PushGatewayConfig,
+    metrics: Metrics,
+    shutdown_signal: ShutdownSignal,
+) -> Result<(), Error>
+where
+    Metrics: traits::Metrics + 'static,
+    ShutdownSignal: Future<Output = ()> + Send + 'static,
+{
+    let url = config.group_url()?;
+    let client = reqwest::Client::builder()
+        .timeout(PUSH_TIMEOUT)
+        .build()
+        .context(error::BuildPushGatewayClientSnafu)?;
+
+    let mut ticks = tokio::time::interval(config.interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tokio::pin!(shutdown_signal);
+
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => push_logged(&client, &url, &metrics).await,
+            () = &mut shutdown_signal => {
+                // the last values would be lost to the gateway otherwise
+                push_logged(&client, &url, &metrics).await;
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn push_logged<Metrics>(client: &reqwest::Client, url: &Url, metrics: &Metrics)
+where
+    Metrics: traits::Metrics,
+{
+    if let Err(err) = push(client, url, metrics).await {
+        tracing::warn!("{err}");
+    }
+}
+
+async fn push<Metrics>(client: &reqwest::Client, url: &Url, metrics: &Metrics) -> Result<(), Error>
+where
+    Metrics: traits::Metrics,
+{
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    encoder.encode(&metrics.gather(), &mut body).context(error::EncodeMetricsSnafu)?;
+
+    let _response = client
+        .put(url.clone())
+        .header(header::CONTENT_TYPE, encoder.format_type())
+        .body(body)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(error::PushMetricsSnafu { url: url.clone() })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(endpoint: &str, job: &str) -> PushGatewayConfig {
+        PushGatewayConfig {
+            endpoint: endpoint.to_string(),
+            job: job.to_string(),
+            interval: Duration::from_secs(15),
+        }
+    }
+
+    #[test]
+    fn test_group_url() {
+        for endpoint in ["http://pushgateway:9091", "http://pushgateway:9091/"] {
+            let url = config(endpoint, "mpc-backend-mock").group_url().unwrap();
+            assert_eq!(url.as_str(), "http://pushgateway:9091/metrics/job/mpc-backend-mock");
+        }
+
+        let url = config("https://example.com/gateway", "a/b").group_url().unwrap();
+        assert_eq!(url.as_str(), "https://example.com/gateway/metrics/job/a%2Fb");
+
+        assert!(config("not a url", "job").group_url().is_err());
+        assert!(config("mailto:ops@example.com", "job").group_url().is_err());
+    }
+}
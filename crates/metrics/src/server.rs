@@ -3,7 +3,7 @@ use std::{future::Future, net::SocketAddr, str::FromStr, sync::LazyLock};
 use axum::{
     body::Body,
     extract::Extension,
-    http::{header, HeaderValue},
+    http::{header, HeaderMap, HeaderValue},
     response::Response,
     routing, Router,
 };
@@ -15,31 +15,86 @@ use tokio::net::TcpListener;
 
 use crate::{
     error::{self, Error},
-    traits,
+    openmetrics, traits,
 };
 
-// FIXME: use `OPENMETRICS_TEXT`
-#[allow(dead_code)]
 static OPENMETRICS_TEXT: LazyLock<Mime> = LazyLock::new(|| {
     Mime::from_str("application/openmetrics-text; version=1.0.0; charset=utf-8")
         .expect("is valid mime type; qed")
 });
 static ENCODER: LazyLock<TextEncoder> = LazyLock::new(TextEncoder::new);
 
-async fn metrics<Metrics>(Extension(metrics): Extension<Metrics>) -> Response<Body>
+/// Exposition formats of the metrics endpoint
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    /// Prometheus text format `0.0.4`
+    Text,
+
+    /// OpenMetrics text format `1.0.0`, the only one carrying exemplars
+    OpenMetrics,
+}
+
+impl Format {
+    /// Format preferred by the `Accept` header, the Prometheus text format when
+    /// both are as good or none is given
+    fn negotiate(headers: &HeaderMap) -> Self {
+        let mut openmetrics_quality = 0.0_f64;
+        let mut text_quality = 0.0_f64;
+
+        let accepted = headers.get_all(header::ACCEPT).iter().filter_map(|v| v.to_str().ok());
+        for media_range in accepted.flat_map(|value| value.split(',')) {
+            let Ok(media_range) = media_range.trim().parse::<Mime>() else {
+                continue;
+            };
+            let quality = media_range
+                .get_param("q")
+                .and_then(|quality| quality.as_str().parse::<f64>().ok())
+                .unwrap_or(1.0);
+
+            match (media_range.type_().as_str(), media_range.subtype().as_str()) {
+                ("application", "openmetrics-text") => {
+                    openmetrics_quality = openmetrics_quality.max(quality);
+                }
+                ("text", "plain" | "*") | ("*", "*") => text_quality = text_quality.max(quality),
+                _ => {}
+            }
+        }
+
+        if openmetrics_quality > 0.0 && openmetrics_quality > text_quality {
+            Self::OpenMetrics
+        } else {
+            Self::Text
+        }
+    }
+}
+
+async fn metrics<Metrics>(
+    Extension(metrics): Extension<Metrics>,
+    headers: HeaderMap,
+) -> Response<Body>
 where
     Metrics: traits::Metrics + 'static,
 {
-    let mut buffer = BytesMut::new().writer();
-    ENCODER
-        .encode(&metrics.gather(), &mut buffer)
-        .expect("`Writer<BytesMut>` should not encounter io error; qed");
-
-    let mut res = Response::new(Body::from(buffer.into_inner().freeze()));
-    drop(
-        res.headers_mut()
-            .insert(header::CONTENT_TYPE, HeaderValue::from_static(ENCODER.format_type())),
-    );
+    let (body, content_type) = match Format::negotiate(&headers) {
+        Format::Text => {
+            let mut buffer = BytesMut::new().writer();
+            ENCODER
+                .encode(&metrics.gather(), &mut buffer)
+                .expect("`Writer<BytesMut>` should not encounter io error; qed");
+            (buffer.into_inner().freeze(), HeaderValue::from_static(ENCODER.format_type()))
+        }
+        Format::OpenMetrics => {
+            let body =
+                openmetrics::encode_openmetrics(&metrics.gather(), metrics.exemplars().as_ref());
+            let content_type = HeaderValue::from_str(OPENMETRICS_TEXT.as_ref())
+                .expect("is valid header value; qed");
+            (body.into(), content_type)
+        }
+    };
+
+    let mut res = Response::new(Body::from(body));
+    drop(res.headers_mut().insert(header::CONTENT_TYPE, content_type));
+    drop(res.headers_mut().insert(header::VARY, HeaderValue::from_static("accept")));
     res
 }
 
@@ -81,7 +136,9 @@ where
 mod tests {
     use std::sync::LazyLock;
 
-    use crate::server::{ENCODER, OPENMETRICS_TEXT};
+    use axum::http::{header, HeaderMap, HeaderValue};
+
+    use crate::server::{Format, ENCODER, OPENMETRICS_TEXT};
 
     #[test]
     fn test_once_cell_lazy() {
@@ -97,4 +154,32 @@ mod tests {
         assert_eq!(OPENMETRICS_TEXT.get_param("charset").unwrap(), "utf-8");
         assert_eq!(OPENMETRICS_TEXT.get_param("version").unwrap(), "1.0.0");
     }
+
+    #[test]
+    fn test_negotiate_format() {
+        let negotiate = |accept: Option<&'static str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(accept) = accept {
+                drop(headers.insert(header::ACCEPT, HeaderValue::from_static(accept)));
+            }
+            Format::negotiate(&headers)
+        };
+
+        assert_eq!(negotiate(None), Format::Text);
+        assert_eq!(negotiate(Some("*/*")), Format::Text);
+        assert_eq!(negotiate(Some("text/plain;version=0.0.4")), Format::Text);
+        assert_eq!(negotiate(Some("application/openmetrics-text")), Format::OpenMetrics);
+        assert_eq!(
+            negotiate(Some(
+                "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5,*/*;\
+                 q=0.1"
+            )),
+            Format::OpenMetrics
+        );
+        assert_eq!(
+            negotiate(Some("application/openmetrics-text;q=0.3,text/plain;q=0.5")),
+            Format::Text
+        );
+        assert_eq!(negotiate(Some("application/openmetrics-text;q=0")), Format::Text);
+    }
 }
@@ -1,3 +1,9 @@
+use crate::Exemplars;
+
 pub trait Metrics: Clone + Send + Sync {
     fn gather(&self) -> Vec<prometheus::proto::MetricFamily>;
+
+    /// Exemplars exported with the histograms in the OpenMetrics format, none
+    /// by default
+    fn exemplars(&self) -> Option<Exemplars> { None }
 }
@@ -16,7 +16,7 @@
 
 use notification::{
     gmail::{Client, Config},
-    Notification, NotificationClient,
+    Notification, NotificationClient, Recipients,
 };
 
 #[tokio::main]
@@ -33,15 +33,17 @@ async fn main() -> Result<(), notification::Error> {
 
     // Configure the Gmail client
     // Replace with your actual domain email
-    let config = Config { impersonate_user: "noreply@yourdomain.com".to_string() };
+    let config =
+        Config { impersonate_user: "noreply@yourdomain.com".to_string(), template_directory: None };
 
     tracing::info!("Creating Gmail client with domain-wide delegation");
     let client = Client::new(config).await?;
 
     // Create an activation email notification
     let notification = Notification::ActivationEmail {
-        to: "user@example.com".to_string(),
+        recipients: Recipients::new("user@example.com"),
         link: "https://yourdomain.com/activate?token=abc123def456".to_string(),
+        locale: None,
     };
 
     tracing::info!("Sending activation email");
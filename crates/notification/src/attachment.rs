@@ -0,0 +1,88 @@
+//! Files attached to notifications.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A file attached to an email.
+///
+/// The content serializes as base64, so attachments can be stored with the
+/// notification they belong to.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// The file name shown to the recipients, e.g. `receipt.pdf`.
+    pub filename: String,
+    /// The MIME type of the content, e.g. `application/pdf`.
+    pub content_type: String,
+    /// The file content.
+    #[serde(serialize_with = "serialize_base64", deserialize_with = "deserialize_base64")]
+    pub content: Vec<u8>,
+}
+
+impl Attachment {
+    /// Creates a new attachment.
+    #[must_use]
+    pub fn new<F, C>(filename: F, content_type: C, content: Vec<u8>) -> Self
+    where
+        F: Into<String>,
+        C: Into<String>,
+    {
+        Self { filename: filename.into(), content_type: content_type.into(), content }
+    }
+
+    /// Returns the size of the content in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize { self.content.len() }
+}
+
+/// The content is left out, it may be large and is rarely readable.
+impl std::fmt::Debug for Attachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Attachment")
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+fn serialize_base64<S>(content: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&STANDARD.encode(content))
+}
+
+fn deserialize_base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let encoded = String::deserialize(deserializer)?;
+    STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_serialization() {
+        let attachment =
+            Attachment::new("users.csv", "text/csv", b"email\nuser@example.com\n".to_vec());
+
+        let value = serde_json::to_value(&attachment).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "filename": "users.csv",
+                "content_type": "text/csv",
+                "content": "ZW1haWwKdXNlckBleGFtcGxlLmNvbQo=",
+            })
+        );
+
+        assert_eq!(serde_json::from_value::<Attachment>(value).unwrap(), attachment);
+        assert_eq!(
+            format!("{attachment:?}"),
+            r#"Attachment { filename: "users.csv", content_type: "text/csv", size: 23 }"#
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Client that renders emails and records them in a store instead of sending
+//! them, so staging environments and tests can inspect what would have been
+//! sent.
+
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Notification, NotificationClient, RenderedEmail, TemplateStore};
+
+/// Default number of emails kept by a [`MemoryStore`].
+pub const DEFAULT_MEMORY_CAPACITY: usize = 1000;
+
+/// Configuration for the capture client.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Directory containing template overrides. Templates missing from the
+    /// directory fall back to the built-in defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_directory: Option<PathBuf>,
+}
+
+/// An email the capture client would have sent.
+#[derive(Clone, Debug)]
+pub struct CapturedEmail {
+    /// The notification the email was rendered from.
+    pub notification: Notification,
+    /// The rendered subject and body.
+    pub rendered: RenderedEmail,
+}
+
+/// Store recording captured emails.
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Records a captured email.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the email cannot be recorded.
+    async fn record(&self, email: &CapturedEmail) -> Result<(), Error>;
+}
+
+/// Store keeping the latest captured emails in memory.
+///
+/// The oldest email is dropped once the capacity is reached.
+#[derive(Debug)]
+pub struct MemoryStore {
+    emails: Mutex<VecDeque<CapturedEmail>>,
+    capacity: usize,
+}
+
+impl MemoryStore {
+    /// Creates a store keeping at most `capacity` emails.
+    #[must_use]
+    pub const fn new(capacity: usize) -> Self {
+        Self { emails: Mutex::new(VecDeque::new()), capacity }
+    }
+
+    /// Returns the captured emails, oldest first.
+    #[must_use]
+    pub fn emails(&self) -> Vec<CapturedEmail> { self.lock().iter().cloned().collect() }
+
+    /// Drops all captured emails.
+    pub fn clear(&self) { self.lock().clear(); }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<CapturedEmail>> {
+        // the queue stays consistent even if a holder of the lock panicked
+        self.emails.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self { Self::new(DEFAULT_MEMORY_CAPACITY) }
+}
+
+#[async_trait]
+impl Store for MemoryStore {
+    async fn record(&self, email: &CapturedEmail) -> Result<(), Error> {
+        let mut emails = self.lock();
+        if emails.len() >= self.capacity {
+            drop(emails.pop_front());
+        }
+        emails.push_back(email.clone());
+        drop(emails);
+
+        Ok(())
+    }
+}
+
+/// Client recording rendered emails in a [`Store`] instead of sending them.
+///
+/// Notifications are rendered like the Gmail client renders them, so a broken
+/// template fails here as well.
+#[derive(Clone)]
+pub struct Client {
+    templates: Arc<TemplateStore>,
+    store: Arc<dyn Store>,
+}
+
+impl Client {
+    /// Creates a new capture client recording into `store`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a template in the template directory cannot be
+    /// loaded.
+    pub fn new(config: Config, store: Arc<dyn Store>) -> Result<Self, Error> {
+        let templates = match config.template_directory {
+            Some(ref directory) => TemplateStore::from_directory(directory)?,
+            None => TemplateStore::new()?,
+        };
+
+        Ok(Self { templates: Arc::new(templates), store })
+    }
+}
+
+#[async_trait]
+impl NotificationClient for Client {
+    async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let rendered = self.templates.render(notification)?;
+
+        self.store.record(&CapturedEmail { notification: notification.clone(), rendered }).await?;
+
+        tracing::debug!(
+            to = %notification.recipients(),
+            template = notification.template_name(),
+            "Email not sent, captured"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Recipients;
+
+    fn activation_email(to: &str) -> Notification {
+        Notification::ActivationEmail {
+            recipients: Recipients::new(to),
+            link: "https://example.com/activate?token=abc123".to_string(),
+            locale: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_notification_records_rendered_email() {
+        let store = Arc::new(MemoryStore::default());
+        let client = Client::new(Config::default(), Arc::clone(&store) as Arc<dyn Store>).unwrap();
+
+        client.send_notification(&activation_email("user@example.com")).await.unwrap();
+
+        let emails = store.emails();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].notification.recipients().to, ["user@example.com"]);
+        assert_eq!(emails[0].rendered.subject, "Activate your Account");
+        assert!(emails[0].rendered.html_body.contains("Welcome to Zionx!"));
+
+        store.clear();
+        assert!(store.emails().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_drops_oldest_email() {
+        let store = Arc::new(MemoryStore::new(2));
+        let client = Client::new(Config::default(), Arc::clone(&store) as Arc<dyn Store>).unwrap();
+
+        for to in ["first@example.com", "second@example.com", "third@example.com"] {
+            client.send_notification(&activation_email(to)).await.unwrap();
+        }
+
+        let recipients: Vec<_> = store
+            .emails()
+            .iter()
+            .map(|email| email.notification.recipients().to_string())
+            .collect();
+        assert_eq!(recipients, ["second@example.com", "third@example.com"]);
+    }
+}
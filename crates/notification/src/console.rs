@@ -0,0 +1,148 @@
+//! Client that renders emails and prints them to the tracing output, for
+//! local development where no mail provider should ever be contacted.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Notification, NotificationClient, TemplateStore};
+
+/// Configuration for the console client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// Directory the rendered HTML bodies are written to.
+    #[serde(default = "Config::default_output_directory")]
+    pub output_directory: PathBuf,
+
+    /// Directory containing template overrides. Templates missing from the
+    /// directory fall back to the built-in defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_directory: Option<PathBuf>,
+}
+
+impl Config {
+    /// `notifications` in the system temporary directory.
+    #[must_use]
+    pub fn default_output_directory() -> PathBuf { std::env::temp_dir().join("notifications") }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { output_directory: Self::default_output_directory(), template_directory: None }
+    }
+}
+
+/// Client printing rendered emails instead of sending them.
+///
+/// The subject and recipient are printed, the HTML body is written to a file
+/// in the output directory so it can be opened in a browser.
+#[derive(Clone, Debug)]
+pub struct Client {
+    output_directory: PathBuf,
+    templates: Arc<TemplateStore>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl Client {
+    /// Creates a new console client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The output directory cannot be created
+    /// - A template in the template directory cannot be loaded
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let templates = match config.template_directory {
+            Some(ref directory) => TemplateStore::from_directory(directory)?,
+            None => TemplateStore::new()?,
+        };
+
+        std::fs::create_dir_all(&config.output_directory).map_err(|source| Error::WriteEmail {
+            path: config.output_directory.clone(),
+            source,
+        })?;
+
+        Ok(Self {
+            output_directory: config.output_directory,
+            templates: Arc::new(templates),
+            sequence: Arc::default(),
+        })
+    }
+
+    /// Path of the file the next email body is written to, unique within the
+    /// output directory.
+    fn next_body_path(&self, notification: &Notification) -> PathBuf {
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis());
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+
+        self.output_directory
+            .join(format!("{timestamp}-{sequence}-{}.html", notification.template_name()))
+    }
+}
+
+#[async_trait]
+impl NotificationClient for Client {
+    async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        let rendered = self.templates.render(notification)?;
+
+        let path = self.next_body_path(notification);
+        std::fs::write(&path, &rendered.html_body)
+            .map_err(|source| Error::WriteEmail { path: path.clone(), source })?;
+
+        let attachments = notification
+            .attachments()
+            .iter()
+            .map(|attachment| format!("{} ({} bytes)", attachment.filename, attachment.size()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        tracing::info!(
+            "Email not sent, printed to console\n  To:          {}\n  Subject:     {}\n  Body:        \
+             {}\n  Attachments: {attachments}",
+            notification.recipients(),
+            rendered.subject,
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Recipients;
+
+    #[tokio::test]
+    async fn test_send_notification_writes_body() {
+        let output_directory = tempfile::tempdir().unwrap();
+        let client = Client::new(Config {
+            output_directory: output_directory.path().join("emails"),
+            template_directory: None,
+        })
+        .unwrap();
+
+        let notification = Notification::ActivationEmail {
+            recipients: Recipients::new("user@example.com"),
+            link: "https://example.com/activate?token=abc123".to_string(),
+            locale: None,
+        };
+        client.send_notification(&notification).await.unwrap();
+        client.send_notification(&notification).await.unwrap();
+
+        let bodies: Vec<_> = std::fs::read_dir(output_directory.path().join("emails"))
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies.iter().all(|body| body.contains("Welcome to Zionx!")));
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use snafu::Snafu;
 
 /// Errors that can occur in the notification crate.
@@ -12,6 +14,29 @@ pub enum Error {
     #[snafu(display("Failed to build email message"))]
     BuildEmail,
 
+    /// An email address cannot be parsed.
+    #[snafu(display("Invalid email address `{address}`"))]
+    InvalidAddress {
+        /// The invalid address.
+        address: String,
+    },
+
+    /// An attachment's MIME type cannot be parsed.
+    #[snafu(display("Invalid attachment content type `{content_type}`"))]
+    InvalidContentType {
+        /// The invalid MIME type.
+        content_type: String,
+    },
+
+    /// An email exceeds the provider's size limit.
+    #[snafu(display("Email of {size} bytes exceeds the limit of {limit} bytes"))]
+    EmailTooLarge {
+        /// The size of the formatted email in bytes.
+        size: usize,
+        /// The largest email the provider sends, in bytes.
+        limit: usize,
+    },
+
     /// Failed to send email.
     #[snafu(display("Failed to send email"))]
     SendEmail,
@@ -22,4 +47,47 @@ pub enum Error {
         /// The underlying reqwest error.
         source: reqwest::Error,
     },
+
+    /// Failed to write a rendered email to a file.
+    #[snafu(display("Failed to write email to {}: {source}", path.display()))]
+    WriteEmail {
+        /// The file or directory path.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Failed to record a captured email.
+    #[snafu(display("Failed to record captured email: {message}"))]
+    RecordEmail {
+        /// Why the store failed.
+        message: String,
+    },
+
+    /// Failed to read a template file.
+    #[snafu(display("Failed to read template {}: {source}", path.display()))]
+    ReadTemplate {
+        /// The template file path.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Failed to compile a template.
+    #[snafu(display("Failed to compile template `{name}`: {source}"))]
+    CompileTemplate {
+        /// The template name.
+        name: String,
+        /// The underlying template error.
+        source: handlebars::TemplateError,
+    },
+
+    /// Failed to render a template.
+    #[snafu(display("Failed to render template `{name}`: {source}"))]
+    RenderTemplate {
+        /// The template name.
+        name: String,
+        /// The underlying render error.
+        source: handlebars::RenderError,
+    },
 }
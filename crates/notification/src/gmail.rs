@@ -1,31 +1,48 @@
 //! Gmail API client implementation for sending emails via domain-wide
 //! delegation.
 
+use std::{path::PathBuf, sync::Arc};
+
 use async_trait::async_trait;
 use base64::{engine::general_purpose::URL_SAFE, Engine as _};
 use google_cloud_token::TokenSourceProvider;
-use lettre::Message;
+use lettre::{
+    message::{header::ContentType, Attachment as AttachmentPart, Mailbox, MultiPart, SinglePart},
+    Message,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::{Error, Notification, NotificationClient};
+use crate::{
+    Attachment, Error, Notification, NotificationClient, Recipients, RenderedEmail, TemplateStore,
+};
 
 /// Gmail API scopes required for sending emails.
 const SCOPES: [&str; 1] = ["https://www.googleapis.com/auth/gmail.send"];
 
+/// Largest message Gmail sends, attachments included after their base64
+/// encoding.
+const MAX_MESSAGE_SIZE: usize = 25 * 1024 * 1024;
+
 /// Configuration for the Gmail client.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
     /// Google Workspace user to impersonate for domain-wide delegation.
     /// This will also be used as the sender address.
     pub impersonate_user: String,
+
+    /// Directory containing template overrides. Templates missing from the
+    /// directory fall back to the built-in defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_directory: Option<PathBuf>,
 }
 
 /// Gmail API client for sending emails.
 #[derive(Clone)]
 pub struct Client {
     http: reqwest::Client,
-    token_source: std::sync::Arc<dyn google_cloud_token::TokenSource>,
+    token_source: Arc<dyn google_cloud_token::TokenSource>,
     from_address: String,
+    templates: Arc<TemplateStore>,
 }
 
 impl Client {
@@ -36,6 +53,7 @@ impl Client {
     /// Returns an error if:
     /// - Failed to create token source provider
     /// - Authentication configuration is invalid
+    /// - A template in the template directory cannot be loaded
     ///
     /// # Example
     ///
@@ -45,6 +63,7 @@ impl Client {
     /// # async fn example() -> Result<(), notification::Error> {
     /// let config = Config {
     ///     impersonate_user: "sender@example.com".to_string(),
+    ///     template_directory: None,
     /// };
     ///
     /// let client = Client::new(config).await?;
@@ -54,6 +73,11 @@ impl Client {
     pub async fn new(config: Config) -> Result<Self, Error> {
         tracing::info!(impersonate_user = %config.impersonate_user, "Using domain-wide delegation for Gmail API");
 
+        let templates = match config.template_directory {
+            Some(ref directory) => TemplateStore::from_directory(directory)?,
+            None => TemplateStore::new()?,
+        };
+
         let auth_config = google_cloud_auth::project::Config::default()
             .with_scopes(&SCOPES)
             .with_sub(&config.impersonate_user);
@@ -70,6 +94,7 @@ impl Client {
             http: reqwest::Client::new(),
             token_source: token_source_provider.token_source(),
             from_address: config.impersonate_user,
+            templates: Arc::new(templates),
         })
     }
 }
@@ -77,10 +102,14 @@ impl Client {
 #[async_trait]
 impl NotificationClient for Client {
     async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
-        let Notification::ActivationEmail { to, link } = notification;
+        let recipients = notification.recipients();
+        let rendered = self.templates.render(notification)?;
 
-        let email = build_activation_email(&self.from_address, to, link)?;
-        let encoded_email = URL_SAFE.encode(email.formatted());
+        let email =
+            build_email(&self.from_address, recipients, &rendered, notification.attachments())?;
+        let formatted = email.formatted();
+        check_message_size(&formatted)?;
+        let encoded_email = URL_SAFE.encode(formatted);
 
         let token = self.token_source.token().await.map_err(|e| {
             tracing::error!(error = ?e, "Failed to get access token");
@@ -107,42 +136,105 @@ impl NotificationClient for Client {
             return Err(Error::SendEmail);
         }
 
-        tracing::info!(to = %to, "Successfully sent activation email");
+        tracing::info!(
+            to = %recipients,
+            template = notification.template_name(),
+            "Successfully sent email"
+        );
         Ok(())
     }
 }
 
-/// Builds an activation email message.
+/// Builds an HTML email message from a rendered template.
+///
+/// Messages with attachments are `multipart/mixed`, the HTML body being the
+/// first part. The `Bcc` header is kept in the message, Gmail reads the blind
+/// carbon copy recipients from it and removes it before delivery.
 ///
 /// # Errors
 ///
 /// Returns an error if the email addresses are invalid or the message cannot be
 /// built.
-fn build_activation_email(from: &str, to: &str, link: &str) -> Result<Message, Error> {
-    let body = format!(
-        "<h1>Welcome to Zionx!</h1><p>Please click the link below to activate your account:</p><a \
-         href=\"{link}\">{link}</a>"
-    );
-
-    Message::builder()
-        .from(from.parse().map_err(|_| Error::BuildEmail)?)
-        .to(to.parse().map_err(|_| Error::BuildEmail)?)
-        .subject("Activate your Account")
-        .header(lettre::message::header::ContentType::TEXT_HTML)
-        .body(body)
-        .map_err(|_| Error::BuildEmail)
+fn build_email(
+    from: &str,
+    recipients: &Recipients,
+    rendered: &RenderedEmail,
+    attachments: &[Attachment],
+) -> Result<Message, Error> {
+    let mut builder = Message::builder().from(parse_mailbox(from)?);
+    for to in &recipients.to {
+        builder = builder.to(parse_mailbox(to)?);
+    }
+    for cc in &recipients.cc {
+        builder = builder.cc(parse_mailbox(cc)?);
+    }
+    for bcc in &recipients.bcc {
+        builder = builder.bcc(parse_mailbox(bcc)?);
+    }
+    if let Some(reply_to) = &recipients.reply_to {
+        builder = builder.reply_to(parse_mailbox(reply_to)?);
+    }
+
+    let builder = builder.keep_bcc().subject(rendered.subject.as_str());
+    if attachments.is_empty() {
+        return builder
+            .header(ContentType::TEXT_HTML)
+            .body(rendered.html_body.clone())
+            .map_err(|_| Error::BuildEmail);
+    }
+
+    let mut multipart = MultiPart::mixed().singlepart(SinglePart::html(rendered.html_body.clone()));
+    for attachment in attachments {
+        let content_type = ContentType::parse(&attachment.content_type).map_err(|_| {
+            Error::InvalidContentType { content_type: attachment.content_type.clone() }
+        })?;
+        multipart = multipart.singlepart(
+            AttachmentPart::new(attachment.filename.clone())
+                .body(attachment.content.clone(), content_type),
+        );
+    }
+
+    builder.multipart(multipart).map_err(|_| Error::BuildEmail)
+}
+
+/// Checks that a formatted message is within Gmail's size limit.
+///
+/// # Errors
+///
+/// Returns an error if the message is larger than [`MAX_MESSAGE_SIZE`].
+fn check_message_size(formatted: &[u8]) -> Result<(), Error> {
+    if formatted.len() > MAX_MESSAGE_SIZE {
+        return Err(Error::EmailTooLarge { size: formatted.len(), limit: MAX_MESSAGE_SIZE });
+    }
+    Ok(())
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, Error> {
+    address.parse().map_err(|_| Error::InvalidAddress { address: address.to_string() })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn rendered_activation_email() -> RenderedEmail {
+        TemplateStore::new()
+            .unwrap()
+            .render(&Notification::ActivationEmail {
+                recipients: Recipients::new("recipient@example.com"),
+                link: "https://example.com/activate?token=abc123".to_string(),
+                locale: None,
+            })
+            .unwrap()
+    }
+
     #[test]
     fn test_build_activation_email() {
-        let result = build_activation_email(
+        let result = build_email(
             "sender@example.com",
-            "recipient@example.com",
-            "https://example.com/activate?token=abc123",
+            &Recipients::new("recipient@example.com"),
+            &rendered_activation_email(),
+            &[],
         );
 
         assert!(result.is_ok());
@@ -159,23 +251,120 @@ mod tests {
 
     #[test]
     fn test_build_activation_email_invalid_from() {
-        let result = build_activation_email(
+        let result = build_email(
             "invalid-email",
-            "recipient@example.com",
-            "https://example.com/activate",
+            &Recipients::new("recipient@example.com"),
+            &rendered_activation_email(),
+            &[],
         );
 
-        assert!(result.is_err());
+        assert!(
+            matches!(result, Err(Error::InvalidAddress { address }) if address == "invalid-email")
+        );
     }
 
     #[test]
     fn test_build_activation_email_invalid_to() {
-        let result = build_activation_email(
+        let result = build_email(
             "sender@example.com",
-            "invalid-email",
-            "https://example.com/activate",
+            &Recipients::new("invalid-email"),
+            &rendered_activation_email(),
+            &[],
         );
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_build_activation_email_with_multiple_recipients() {
+        let recipients = Recipients::new("first@example.com")
+            .with_to("second@example.com")
+            .with_cc("cc@example.com")
+            .with_bcc("bcc@example.com")
+            .with_reply_to("support@example.com");
+
+        let message =
+            build_email("sender@example.com", &recipients, &rendered_activation_email(), &[])
+                .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("To: first@example.com, second@example.com\r\n"));
+        assert!(formatted.contains("Cc: cc@example.com\r\n"));
+        assert!(formatted.contains("Bcc: bcc@example.com\r\n"));
+        assert!(formatted.contains("Reply-To: support@example.com\r\n"));
+
+        let envelope: Vec<_> = message.envelope().to().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            envelope,
+            ["first@example.com", "second@example.com", "cc@example.com", "bcc@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_build_activation_email_without_recipients() {
+        let result = build_email(
+            "sender@example.com",
+            &Recipients::default(),
+            &rendered_activation_email(),
+            &[],
+        );
+
+        assert!(matches!(result, Err(Error::BuildEmail)));
+    }
+
+    #[test]
+    fn test_build_email_with_attachments() {
+        let attachments = [
+            Attachment::new("receipt.pdf", "application/pdf", b"%PDF-1.7".to_vec()),
+            Attachment::new("users.csv", "text/csv", b"email\n".to_vec()),
+        ];
+
+        let message = build_email(
+            "sender@example.com",
+            &Recipients::new("recipient@example.com"),
+            &rendered_activation_email(),
+            &attachments,
+        )
+        .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("Content-Type: multipart/mixed;"));
+        assert!(formatted.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(formatted.contains("Welcome to Zionx!"));
+        assert!(formatted.contains("Content-Disposition: attachment; filename=\"receipt.pdf\""));
+        assert!(formatted.contains("Content-Type: application/pdf"));
+        assert!(formatted.contains("Content-Disposition: attachment; filename=\"users.csv\""));
+        assert!(check_message_size(formatted.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_build_email_with_invalid_attachment_content_type() {
+        let result = build_email(
+            "sender@example.com",
+            &Recipients::new("recipient@example.com"),
+            &rendered_activation_email(),
+            &[Attachment::new("receipt.pdf", "pdf", b"%PDF-1.7".to_vec())],
+        );
+
+        assert!(
+            matches!(result, Err(Error::InvalidContentType { content_type }) if content_type == "pdf")
+        );
+    }
+
+    #[test]
+    fn test_check_message_size_with_large_attachment() {
+        // 20 MiB fit the limit but not once base64-encoded
+        let message = build_email(
+            "sender@example.com",
+            &Recipients::new("recipient@example.com"),
+            &rendered_activation_email(),
+            &[Attachment::new("export.bin", "application/octet-stream", vec![0; 20 * 1024 * 1024])],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            check_message_size(&message.formatted()),
+            Err(Error::EmailTooLarge { limit: MAX_MESSAGE_SIZE, .. })
+        ));
+    }
 }
@@ -7,26 +7,235 @@
 //! ## Features
 //!
 //! - Gmail API integration with domain-wide delegation
+//! - Console client printing rendered emails, for local development
+//! - Logging client for setups without a mail provider
+//! - No-op and capturing clients, so staging never emails real users and tests
+//!   can inspect what would have been sent
 //! - HTML email support
-//! - Activation email templates
+//! - File attachments
+//! - Multiple recipients, CC, BCC and Reply-To addresses
+//! - Activation and email change verification templates, overridable from a
+//!   template directory
+//! - Localized templates with fallback to less specific languages
 //! - Async/await support
 
+mod attachment;
+pub mod capture;
+pub mod console;
 mod error;
 pub mod gmail;
+pub mod log;
+pub mod noop;
+pub mod template;
+
+use std::fmt;
 
 use async_trait::async_trait;
+pub use attachment::Attachment;
 pub use error::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+pub use template::{RenderedEmail, TemplateStore};
+
+/// Addresses a notification is sent to.
+///
+/// `to` is also read from the single address string notifications were stored
+/// with before multiple recipients were supported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recipients {
+    /// The primary recipients' email addresses.
+    #[serde(deserialize_with = "one_or_many")]
+    pub to: Vec<String>,
+    /// The carbon copy recipients' email addresses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>,
+    /// The blind carbon copy recipients' email addresses, never shown to the
+    /// other recipients.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bcc: Vec<String>,
+    /// The address replies are sent to instead of the sender.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+
+impl Recipients {
+    /// Creates recipients with a single primary recipient.
+    #[must_use]
+    pub fn new<S: Into<String>>(to: S) -> Self { Self { to: vec![to.into()], ..Self::default() } }
+
+    /// Adds a primary recipient.
+    #[must_use]
+    pub fn with_to<S: Into<String>>(mut self, to: S) -> Self {
+        self.to.push(to.into());
+        self
+    }
+
+    /// Adds a carbon copy recipient.
+    #[must_use]
+    pub fn with_cc<S: Into<String>>(mut self, cc: S) -> Self {
+        self.cc.push(cc.into());
+        self
+    }
+
+    /// Adds a blind carbon copy recipient.
+    #[must_use]
+    pub fn with_bcc<S: Into<String>>(mut self, bcc: S) -> Self {
+        self.bcc.push(bcc.into());
+        self
+    }
+
+    /// Sets the address replies are sent to.
+    #[must_use]
+    pub fn with_reply_to<S: Into<String>>(mut self, reply_to: S) -> Self {
+        self.reply_to = Some(reply_to.into());
+        self
+    }
+}
+
+/// Formats the primary recipients as a comma-separated list.
+impl fmt::Display for Recipients {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.to.join(", ")) }
+}
 
 /// Represents different types of notifications that can be sent.
-#[derive(Debug, Clone)]
+///
+/// Notifications serialize with their template name as `type`, so they can be
+/// stored and sent later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Notification {
     /// An activation email with a link for account activation.
     ActivationEmail {
-        /// The recipient's email address.
-        to: String,
+        /// The recipients' email addresses.
+        #[serde(flatten)]
+        recipients: Recipients,
         /// The activation link URL.
         link: String,
+        /// BCP 47 language tag of the recipient's language, English without.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        locale: Option<String>,
     },
+
+    /// An email to a user's new address with a link confirming the change.
+    EmailChangeVerification {
+        /// The recipients' email addresses, the new address of the user.
+        #[serde(flatten)]
+        recipients: Recipients,
+        /// The confirmation link URL.
+        link: String,
+        /// BCP 47 language tag of the recipient's language, English without.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        locale: Option<String>,
+    },
+
+    /// A plain email with files attached, e.g. receipts or exports.
+    EmailWithAttachments {
+        /// The recipients' email addresses.
+        #[serde(flatten)]
+        recipients: Recipients,
+        /// The subject line.
+        subject: String,
+        /// The body text, HTML-escaped by the template.
+        body: String,
+        /// The attached files.
+        attachments: Vec<Attachment>,
+        /// BCP 47 language tag of the recipient's language, English without.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        locale: Option<String>,
+    },
+}
+
+impl Notification {
+    /// Returns the name of the template used to render this notification.
+    #[must_use]
+    pub const fn template_name(&self) -> &'static str {
+        match self {
+            Self::ActivationEmail { .. } => "activation_email",
+            Self::EmailChangeVerification { .. } => "email_change_verification",
+            Self::EmailWithAttachments { .. } => "email_with_attachments",
+        }
+    }
+
+    /// Returns the recipients' email addresses.
+    #[must_use]
+    pub const fn recipients(&self) -> &Recipients {
+        match self {
+            Self::ActivationEmail { recipients, .. }
+            | Self::EmailChangeVerification { recipients, .. }
+            | Self::EmailWithAttachments { recipients, .. } => recipients,
+        }
+    }
+
+    /// Returns the recipients' email addresses for changing them.
+    pub const fn recipients_mut(&mut self) -> &mut Recipients {
+        match self {
+            Self::ActivationEmail { recipients, .. }
+            | Self::EmailChangeVerification { recipients, .. }
+            | Self::EmailWithAttachments { recipients, .. } => recipients,
+        }
+    }
+
+    /// Sets the language tag of the recipient's language, `None` for English.
+    pub fn set_locale(&mut self, locale: Option<String>) {
+        match self {
+            Self::ActivationEmail { locale: current, .. }
+            | Self::EmailChangeVerification { locale: current, .. }
+            | Self::EmailWithAttachments { locale: current, .. } => *current = locale,
+        }
+    }
+
+    /// Returns the language tag of the recipient's language, `None` for
+    /// English.
+    #[must_use]
+    pub fn locale(&self) -> Option<&str> {
+        match self {
+            Self::ActivationEmail { locale, .. }
+            | Self::EmailChangeVerification { locale, .. }
+            | Self::EmailWithAttachments { locale, .. } => locale.as_deref(),
+        }
+    }
+
+    /// Returns the files attached to the notification.
+    #[must_use]
+    pub const fn attachments(&self) -> &[Attachment] {
+        match self {
+            Self::ActivationEmail { .. } | Self::EmailChangeVerification { .. } => &[],
+            Self::EmailWithAttachments { attachments, .. } => attachments.as_slice(),
+        }
+    }
+
+    /// Returns the variables exposed to the notification's templates.
+    ///
+    /// `to` is the comma-separated list of primary recipients.
+    #[must_use]
+    pub fn template_data(&self) -> serde_json::Value {
+        match self {
+            Self::ActivationEmail { recipients, link, .. }
+            | Self::EmailChangeVerification { recipients, link, .. } => {
+                serde_json::json!({ "to": recipients.to_string(), "link": link })
+            }
+            Self::EmailWithAttachments { recipients, subject, body, .. } => {
+                serde_json::json!({ "to": recipients.to_string(), "subject": subject, "body": body })
+            }
+        }
+    }
+}
+
+/// Deserializes a single address or a list of addresses.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(address) => vec![address],
+        OneOrMany::Many(addresses) => addresses,
+    })
 }
 
 /// Trait for notification clients that can send notifications.
@@ -39,3 +248,77 @@ pub trait NotificationClient: Send + Sync {
     /// Returns an error if the notification fails to send.
     async fn send_notification(&self, notification: &Notification) -> Result<(), Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_serialization() {
+        let notification = Notification::ActivationEmail {
+            recipients: Recipients::new("user@example.com")
+                .with_bcc("audit@example.com")
+                .with_reply_to("support@example.com"),
+            link: "https://example.com/activate?token=abc".to_string(),
+            locale: Some("zh-TW".to_string()),
+        };
+
+        // the format is stored, changing it breaks notifications queued before
+        let value = serde_json::to_value(&notification).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "type": "activation_email",
+                "to": ["user@example.com"],
+                "bcc": ["audit@example.com"],
+                "reply_to": "support@example.com",
+                "link": "https://example.com/activate?token=abc",
+                "locale": "zh-TW",
+            })
+        );
+        assert_eq!(value["type"], notification.template_name());
+
+        let Notification::ActivationEmail { recipients, link, locale } =
+            serde_json::from_value(value).unwrap()
+        else {
+            panic!("expected an activation email");
+        };
+        assert_eq!(recipients, *notification.recipients());
+        assert_eq!(link, "https://example.com/activate?token=abc");
+        assert_eq!(locale.as_deref(), Some("zh-TW"));
+    }
+
+    #[test]
+    fn test_notification_deserialization_with_single_recipient() {
+        // format of notifications queued before multiple recipients
+        let value = serde_json::json!({
+            "type": "activation_email",
+            "to": "user@example.com",
+            "link": "https://example.com/activate?token=abc",
+        });
+
+        let notification: Notification = serde_json::from_value(value).unwrap();
+        assert_eq!(*notification.recipients(), Recipients::new("user@example.com"));
+        assert_eq!(notification.locale(), None);
+        assert_eq!(notification.template_data()["to"], "user@example.com");
+    }
+
+    #[test]
+    fn test_email_with_attachments_serialization() {
+        let notification = Notification::EmailWithAttachments {
+            recipients: Recipients::new("user@example.com"),
+            subject: "Your receipt".to_string(),
+            body: "Thanks for your order".to_string(),
+            attachments: vec![Attachment::new("receipt.pdf", "application/pdf", b"%PDF".to_vec())],
+            locale: None,
+        };
+
+        let value = serde_json::to_value(&notification).unwrap();
+        assert_eq!(value["type"], "email_with_attachments");
+        assert_eq!(value["attachments"][0]["content"], "JVBERg==");
+
+        let notification: Notification = serde_json::from_value(value).unwrap();
+        assert_eq!(notification.attachments()[0].content, b"%PDF");
+        assert_eq!(notification.template_data()["subject"], "Your receipt");
+    }
+}
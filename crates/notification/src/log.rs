@@ -0,0 +1,33 @@
+//! Client that only logs notifications, for local development and tests where
+//! no mail provider is configured.
+
+use async_trait::async_trait;
+
+use crate::{Error, Notification, NotificationClient};
+
+/// Client logging notifications instead of sending them.
+///
+/// The rendered templates are not involved, the notification itself is logged
+/// so e.g. activation links can be copied from the logs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Client;
+
+impl Client {
+    /// Creates a new logging client.
+    #[must_use]
+    pub const fn new() -> Self { Self }
+}
+
+#[async_trait]
+impl NotificationClient for Client {
+    async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        tracing::info!(
+            to = %notification.recipients(),
+            template = notification.template_name(),
+            data = %notification.template_data(),
+            attachments = notification.attachments().len(),
+            "Notification not sent, no mail provider is configured"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+//! Client that drops notifications, for environments such as staging where no
+//! email may ever reach a real user.
+
+use async_trait::async_trait;
+
+use crate::{Error, Notification, NotificationClient};
+
+/// Client discarding notifications without sending them.
+///
+/// Unlike the logging client, the notification data is not logged, so
+/// activation links of real users do not end up in the logs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Client;
+
+impl Client {
+    /// Creates a new no-op client.
+    #[must_use]
+    pub const fn new() -> Self { Self }
+}
+
+#[async_trait]
+impl NotificationClient for Client {
+    async fn send_notification(&self, notification: &Notification) -> Result<(), Error> {
+        tracing::debug!(
+            template = notification.template_name(),
+            "Notification dropped, the no-op provider is configured"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+//! Template store used to render notification subjects and bodies.
+//!
+//! Every notification type has a subject template (`<name>.subject.hbs`) and
+//! an HTML body template (`<name>.html.hbs`). Built-in defaults are compiled
+//! into the crate; a template directory can be supplied to override any of
+//! them without rebuilding.
+//!
+//! Templates are localized by a BCP 47 language tag before the suffix, e.g.
+//! `activation_email.zh-TW.html.hbs`. A notification with a locale is rendered
+//! with the most specific template of its language, `zh-Hant-TW` tries
+//! `zh-Hant-TW`, `zh-Hant` and `zh` before the unlocalized template, which is
+//! English. Subjects and bodies fall back independently.
+
+use std::{ffi::OsStr, path::Path};
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::{Error, Notification};
+
+/// Built-in templates.
+///
+/// Each entry is `(name, locale, subject, html body)`, the unlocalized
+/// templates have no locale.
+const BUILT_IN_TEMPLATES: [(&str, Option<&str>, &str, &str); 5] = [
+    (
+        "activation_email",
+        None,
+        include_str!("../templates/activation_email.subject.hbs"),
+        include_str!("../templates/activation_email.html.hbs"),
+    ),
+    (
+        "activation_email",
+        Some("zh-TW"),
+        include_str!("../templates/activation_email.zh-TW.subject.hbs"),
+        include_str!("../templates/activation_email.zh-TW.html.hbs"),
+    ),
+    (
+        "email_change_verification",
+        None,
+        include_str!("../templates/email_change_verification.subject.hbs"),
+        include_str!("../templates/email_change_verification.html.hbs"),
+    ),
+    (
+        "email_change_verification",
+        Some("zh-TW"),
+        include_str!("../templates/email_change_verification.zh-TW.subject.hbs"),
+        include_str!("../templates/email_change_verification.zh-TW.html.hbs"),
+    ),
+    (
+        "email_with_attachments",
+        None,
+        include_str!("../templates/email_with_attachments.subject.hbs"),
+        include_str!("../templates/email_with_attachments.html.hbs"),
+    ),
+];
+
+/// Suffix of subject template files in a template directory.
+const SUBJECT_SUFFIX: &str = "subject.hbs";
+
+/// Suffix of HTML body template files in a template directory.
+const HTML_SUFFIX: &str = "html.hbs";
+
+/// A rendered email, ready to be wrapped into a MIME message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedEmail {
+    /// The email subject line.
+    pub subject: String,
+    /// The HTML body.
+    pub html_body: String,
+}
+
+/// Part of an email a template renders.
+#[derive(Clone, Copy, Debug)]
+enum TemplatePart {
+    Subject,
+    Body,
+}
+
+/// Store of subject and body templates for every notification type.
+///
+/// Templates are keyed by their name, followed by their lowercase locale for
+/// localized ones, e.g. `activation_email.zh-tw`.
+#[derive(Clone, Debug)]
+pub struct TemplateStore {
+    /// Subject templates, rendered without HTML escaping.
+    subjects: Handlebars<'static>,
+    /// HTML body templates, rendered with HTML escaping.
+    bodies: Handlebars<'static>,
+}
+
+impl TemplateStore {
+    /// Creates a store with only the built-in templates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a built-in template fails to compile.
+    pub fn new() -> Result<Self, Error> {
+        let mut subjects = Handlebars::new();
+        subjects.set_strict_mode(true);
+        subjects.register_escape_fn(handlebars::no_escape);
+
+        let mut bodies = Handlebars::new();
+        bodies.set_strict_mode(true);
+
+        let mut store = Self { subjects, bodies };
+        for (name, locale, subject, html_body) in BUILT_IN_TEMPLATES {
+            let key = template_key(name, locale);
+            store.register(TemplatePart::Subject, &key, subject)?;
+            store.register(TemplatePart::Body, &key, html_body)?;
+        }
+
+        Ok(store)
+    }
+
+    /// Creates a store that loads templates from `directory`, falling back to
+    /// the built-in defaults for any template file that does not exist.
+    ///
+    /// Files named after a built-in template, localized or not, override or
+    /// add to the built-in templates; other files are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or a template file exists but cannot
+    /// be read, or a template fails to compile.
+    pub fn from_directory<P: AsRef<Path>>(directory: P) -> Result<Self, Error> {
+        let directory = directory.as_ref();
+        let mut store = Self::new()?;
+
+        let read_error = |source| Error::ReadTemplate { path: directory.to_path_buf(), source };
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(store),
+            Err(source) => return Err(read_error(source)),
+        };
+        // sorted, so that files differing only in the case of their locale
+        // override each other in the same order everywhere
+        let mut paths = entries
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>, std::io::Error>>()
+            .map_err(read_error)?;
+        paths.sort();
+
+        for path in paths {
+            let Some((key, part)) =
+                path.file_name().and_then(OsStr::to_str).and_then(parse_file_name)
+            else {
+                continue;
+            };
+
+            let template = std::fs::read_to_string(&path)
+                .map_err(|source| Error::ReadTemplate { path: path.clone(), source })?;
+            store.register(part, &key, &template)?;
+
+            tracing::info!(template = %key, file = %path.display(), "Loaded template override");
+        }
+
+        Ok(store)
+    }
+
+    /// Renders the subject and body for `notification`, in its locale if
+    /// templates of its language exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template references data the notification does
+    /// not provide.
+    pub fn render(&self, notification: &Notification) -> Result<RenderedEmail, Error> {
+        let name = notification.template_name();
+        let locale = notification.locale();
+        let data = notification.template_data();
+
+        let subject_key = resolve(&self.subjects, name, locale);
+        let body_key = resolve(&self.bodies, name, locale);
+
+        Ok(RenderedEmail {
+            subject: render(&self.subjects, &subject_key, &data)?.trim().to_string(),
+            html_body: render(&self.bodies, &body_key, &data)?,
+        })
+    }
+
+    fn register(&mut self, part: TemplatePart, key: &str, template: &str) -> Result<(), Error> {
+        let registry = match part {
+            TemplatePart::Subject => &mut self.subjects,
+            TemplatePart::Body => &mut self.bodies,
+        };
+        registry
+            .register_template_string(key, template)
+            .map_err(|source| Error::CompileTemplate { name: key.to_string(), source })
+    }
+}
+
+fn template_key(name: &str, locale: Option<&str>) -> String {
+    match locale {
+        Some(locale) => format!("{name}.{}", locale.to_ascii_lowercase()),
+        None => name.to_string(),
+    }
+}
+
+/// Key and part of a template file named `<name>[.<locale>].<suffix>`, `None`
+/// if it is not named after a built-in template.
+fn parse_file_name(file_name: &str) -> Option<(String, TemplatePart)> {
+    let (stem, part) = if let Some(stem) = file_name.strip_suffix(SUBJECT_SUFFIX) {
+        (stem, TemplatePart::Subject)
+    } else {
+        (file_name.strip_suffix(HTML_SUFFIX)?, TemplatePart::Body)
+    };
+
+    let stem = stem.strip_suffix('.')?;
+    let (name, locale) = match stem.split_once('.') {
+        Some((name, locale)) => (name, Some(locale)),
+        None => (stem, None),
+    };
+
+    let is_built_in = BUILT_IN_TEMPLATES.iter().any(|(built_in, ..)| *built_in == name);
+    let locale_ok = locale.is_none_or(|locale| !locale.is_empty() && !locale.contains('.'));
+    (is_built_in && locale_ok).then(|| (template_key(name, locale), part))
+}
+
+/// Key of the most specific template of `locale` in `registry`, the
+/// unlocalized template if there is none.
+fn resolve(registry: &Handlebars<'static>, name: &str, locale: Option<&str>) -> String {
+    let mut tag = locale.unwrap_or_default();
+    while !tag.is_empty() {
+        let key = template_key(name, Some(tag));
+        if registry.has_template(&key) {
+            return key;
+        }
+        tag = tag.rsplit_once('-').map_or("", |(parent, _)| parent);
+    }
+
+    name.to_string()
+}
+
+fn render<T: Serialize>(
+    registry: &Handlebars<'static>,
+    name: &str,
+    data: &T,
+) -> Result<String, Error> {
+    registry
+        .render(name, data)
+        .map_err(|source| Error::RenderTemplate { name: name.to_string(), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Recipients;
+
+    fn activation_email() -> Notification {
+        Notification::ActivationEmail {
+            recipients: Recipients::new("recipient@example.com"),
+            link: "https://example.com/activate?token=abc123".to_string(),
+            locale: None,
+        }
+    }
+
+    fn activation_email_in(locale: &str) -> Notification {
+        Notification::ActivationEmail {
+            recipients: Recipients::new("recipient@example.com"),
+            link: "https://example.com/activate?token=abc123".to_string(),
+            locale: Some(locale.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render_built_in_activation_email() {
+        let store = TemplateStore::new().unwrap();
+        let rendered = store.render(&activation_email()).unwrap();
+
+        assert_eq!(rendered.subject, "Activate your Account");
+        assert!(rendered.html_body.contains("Welcome to Zionx!"));
+        assert!(rendered.html_body.contains("https://example.com/activate?token&#x3D;abc123"));
+    }
+
+    #[test]
+    fn test_render_built_in_email_with_attachments() {
+        let store = TemplateStore::new().unwrap();
+        let rendered = store
+            .render(&Notification::EmailWithAttachments {
+                recipients: Recipients::new("recipient@example.com"),
+                subject: "Users & wallets".to_string(),
+                body: "See <attached> export".to_string(),
+                attachments: Vec::new(),
+                locale: None,
+            })
+            .unwrap();
+
+        // subjects are not escaped, bodies are
+        assert_eq!(rendered.subject, "Users & wallets");
+        assert_eq!(rendered.html_body, "<p>See &lt;attached&gt; export</p>");
+    }
+
+    #[test]
+    fn test_render_built_in_email_change_verification() {
+        let store = TemplateStore::new().unwrap();
+        let mut notification = Notification::EmailChangeVerification {
+            recipients: Recipients::new("new@example.com"),
+            link: "https://example.com/confirm-email?token=abc123".to_string(),
+            locale: None,
+        };
+
+        let rendered = store.render(&notification).unwrap();
+        assert_eq!(rendered.subject, "Confirm your new email address");
+        assert!(rendered.html_body.contains("https://example.com/confirm-email?token&#x3D;abc123"));
+
+        notification.set_locale(Some("zh-TW".to_string()));
+        let rendered = store.render(&notification).unwrap();
+        assert_eq!(rendered.subject, "確認您的新電子郵件地址");
+    }
+
+    #[test]
+    fn test_render_built_in_localized_activation_email() {
+        let store = TemplateStore::new().unwrap();
+
+        for locale in ["zh-TW", "zh-tw", "zh-TW-x-private"] {
+            let rendered = store.render(&activation_email_in(locale)).unwrap();
+            assert_eq!(rendered.subject, "啟用您的帳戶");
+            assert!(rendered.html_body.contains("歡迎使用 Zionx！"));
+        }
+
+        // languages without templates are sent in English
+        for locale in ["en", "fr-FR", "zh"] {
+            let rendered = store.render(&activation_email_in(locale)).unwrap();
+            assert_eq!(rendered.subject, "Activate your Account");
+        }
+    }
+
+    #[test]
+    fn test_directory_override_with_localized_templates() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("activation_email.zh.subject.hbs"), "啟用帳戶")
+            .unwrap();
+        std::fs::write(
+            directory.path().join("activation_email.zh-Hant.html.hbs"),
+            "<p>{{link}}</p>",
+        )
+        .unwrap();
+        std::fs::write(directory.path().join("activation_email.fr.txt"), "ignored").unwrap();
+        std::fs::write(directory.path().join("unknown.fr.subject.hbs"), "ignored").unwrap();
+
+        let store = TemplateStore::from_directory(directory.path()).unwrap();
+
+        // the subject falls back to `zh`, the body to `zh-Hant`
+        let rendered = store.render(&activation_email_in("zh-Hant-TW")).unwrap();
+        assert_eq!(rendered.subject, "啟用帳戶");
+        assert_eq!(rendered.html_body, "<p>https://example.com/activate?token&#x3D;abc123</p>");
+
+        // the built-in `zh-TW` templates are more specific than `zh`
+        let rendered = store.render(&activation_email_in("zh-TW")).unwrap();
+        assert_eq!(rendered.subject, "啟用您的帳戶");
+    }
+
+    #[test]
+    fn test_directory_override_falls_back_to_built_in() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(
+            directory.path().join("activation_email.html.hbs"),
+            "<p>Activate {{to}}: {{link}}</p>",
+        )
+        .unwrap();
+
+        let store = TemplateStore::from_directory(directory.path()).unwrap();
+        let rendered = store.render(&activation_email()).unwrap();
+
+        // subject is not overridden, body is
+        assert_eq!(rendered.subject, "Activate your Account");
+        assert!(rendered.html_body.starts_with("<p>Activate recipient@example.com:"));
+    }
+
+    #[test]
+    fn test_directory_override_with_invalid_template() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("activation_email.subject.hbs"), "{{#if}}").unwrap();
+
+        assert!(matches!(
+            TemplateStore::from_directory(directory.path()),
+            Err(Error::CompileTemplate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_with_unknown_variable() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("activation_email.subject.hbs"), "Hi {{name}}")
+            .unwrap();
+
+        let store = TemplateStore::from_directory(directory.path()).unwrap();
+
+        assert!(matches!(store.render(&activation_email()), Err(Error::RenderTemplate { .. })));
+    }
+}
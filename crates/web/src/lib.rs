@@ -4,6 +4,7 @@ use std::{
 };
 
 use http::HeaderMap;
+use ipnet::IpNet;
 
 pub mod response;
 
@@ -22,3 +23,103 @@ pub fn get_request_ip(headers: &HeaderMap, addr: &SocketAddr) -> IpAddr {
 
     x_forwarded_for.unwrap_or_else(|| x_real_ip.unwrap_or_else(|| addr.ip()))
 }
+
+/// Resolve the client IP address of a request, honoring `X-Forwarded-For`
+/// and `X-Real-IP` only when the direct peer is one of `trusted_proxies`.
+///
+/// `X-Forwarded-For` is walked from right to left and the first address that
+/// is not a trusted proxy is returned, so clients cannot spoof their address
+/// by prepending entries to the header.
+#[must_use]
+pub fn get_trusted_request_ip(
+    headers: &HeaderMap,
+    addr: &SocketAddr,
+    trusted_proxies: &[IpNet],
+) -> IpAddr {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+
+    let peer_ip = addr.ip().to_canonical();
+    if !is_trusted(&peer_ip) {
+        return peer_ip;
+    }
+
+    let x_forwarded_for: Vec<IpAddr> = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|s| s.split(','))
+        .filter_map(|ip| IpAddr::from_str(ip.trim()).ok())
+        .map(|ip| ip.to_canonical())
+        .collect();
+    let x_real_ip = headers
+        .get("X-Real-IP")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|s| IpAddr::from_str(s.trim()).ok())
+        .map(|ip| ip.to_canonical());
+
+    tracing::debug!(?x_forwarded_for, ?x_real_ip, ip_address = ?peer_ip);
+
+    x_forwarded_for
+        .iter()
+        .rev()
+        .find(|ip| !is_trusted(ip))
+        .or_else(|| x_forwarded_for.first())
+        .copied()
+        .or(x_real_ip)
+        .unwrap_or(peer_ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    fn trusted_proxies() -> Vec<IpNet> { vec!["10.0.0.0/8".parse().unwrap()] }
+
+    fn headers(x_forwarded_for: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        drop(headers.insert("X-Forwarded-For", HeaderValue::from_static(x_forwarded_for)));
+        headers
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_forwarded_headers() {
+        let addr = SocketAddr::from(([203, 0, 113, 7], 443));
+
+        let ip = get_trusted_request_ip(&headers("198.51.100.1"), &addr, &trusted_proxies());
+
+        assert_eq!(ip, IpAddr::from([203, 0, 113, 7]));
+    }
+
+    #[test]
+    fn test_trusted_peer_uses_rightmost_untrusted_forwarded_address() {
+        let addr = SocketAddr::from(([10, 0, 0, 2], 443));
+
+        let ip = get_trusted_request_ip(
+            &headers("1.2.3.4, 198.51.100.1, 10.0.0.3"),
+            &addr,
+            &trusted_proxies(),
+        );
+
+        assert_eq!(ip, IpAddr::from([198, 51, 100, 1]));
+    }
+
+    #[test]
+    fn test_trusted_peer_without_forwarded_headers() {
+        let addr = SocketAddr::from(([10, 0, 0, 2], 443));
+
+        let ip = get_trusted_request_ip(&HeaderMap::new(), &addr, &trusted_proxies());
+
+        assert_eq!(ip, IpAddr::from([10, 0, 0, 2]));
+    }
+
+    #[test]
+    fn test_ipv4_mapped_peer_is_canonicalized() {
+        let addr: SocketAddr = "[::ffff:203.0.113.7]:443".parse().unwrap();
+
+        let ip = get_trusted_request_ip(&HeaderMap::new(), &addr, &[]);
+
+        assert_eq!(ip, IpAddr::from([203, 0, 113, 7]));
+    }
+}
@@ -2,7 +2,9 @@ use std::fmt::Debug;
 
 use axum::{
     body,
-    http::{header, StatusCode},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
     response::{IntoResponse, Response},
 };
 use indexmap::IndexMap;
@@ -231,17 +233,109 @@ impl<T, M> EncapsulatedJson<T, M> {
     }
 }
 
+/// Media type of MessagePack bodies
+pub const APPLICATION_MSGPACK: &str = "application/msgpack";
+
+tokio::task_local! {
+    /// Format of the request being handled, set by [`negotiate_format`]
+    static RESPONSE_FORMAT: ResponseFormat;
+}
+
+/// Format [`EncapsulatedJson`] bodies are serialized in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+
+    /// MessagePack with the field names kept, so the body has the same shape
+    /// as the JSON one
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// Format asked for by the `Accept` header: MessagePack if it accepts
+    /// `application/msgpack` (or `application/x-msgpack`) with at least the
+    /// quality of `application/json`, JSON otherwise
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut msgpack_quality = 0.0_f32;
+        let mut json_quality = 0.0_f32;
+        for media_range in headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+        {
+            let mut params = media_range.split(';').map(str::trim);
+            let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = params
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            match media_type.as_str() {
+                APPLICATION_MSGPACK | "application/x-msgpack" => {
+                    msgpack_quality = msgpack_quality.max(quality);
+                }
+                "application/json" => json_quality = json_quality.max(quality),
+                _ => {}
+            }
+        }
+
+        if msgpack_quality > 0.0 && msgpack_quality >= json_quality {
+            Self::MessagePack
+        } else {
+            Self::Json
+        }
+    }
+
+    /// Format negotiated for the request being handled, JSON outside of
+    /// [`negotiate_format`]
+    #[must_use]
+    pub fn current() -> Self { RESPONSE_FORMAT.try_with(|format| *format).unwrap_or_default() }
+
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => APPLICATION_MSGPACK,
+        }
+    }
+
+    fn to_vec<T: Serialize>(self, value: &T) -> Vec<u8> {
+        match self {
+            Self::Json => serde_json::to_vec(value).expect("value is serializable"),
+            Self::MessagePack => rmp_serde::to_vec_named(value).expect("value is serializable"),
+        }
+    }
+}
+
+/// Middleware serializing the [`EncapsulatedJson`] bodies of the responses in
+/// the format the `Accept` header of the request asks for, see
+/// [`ResponseFormat::from_headers`]
+///
+/// Error bodies are always JSON, so that layers amending them keep working.
+pub async fn negotiate_format(request: Request, next: Next) -> Response {
+    let format = ResponseFormat::from_headers(request.headers());
+    let mut response = RESPONSE_FORMAT.scope(format, next.run(request)).await;
+
+    // the body depends on `Accept`, caches must not mix the formats up
+    let _appended = response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
 impl<T, M> IntoResponse for EncapsulatedJson<T, M>
 where
     T: Serialize,
     M: Serialize,
 {
     fn into_response(self) -> Response {
-        let body = body::Body::from(serde_json::to_vec(&self.0).expect("self.0 is serializable"));
+        let format = ResponseFormat::current();
+        let body = body::Body::from(format.to_vec(&self.0));
 
         Response::builder()
             .status(self.0.status_code)
-            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(header::CONTENT_TYPE, format.content_type())
             .body(body)
             .expect("Build `Axum` response successfully; qed")
     }
@@ -265,17 +359,50 @@ where
     }
 }
 
+/// `_metadata` of a paginated response
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginationMetadata {
+    /// Number of items matching the query across all pages
+    pub total_count: u64,
+
+    /// Current page, starting from 1
+    pub page: u32,
+
+    /// Maximum number of items per page
+    pub limit: u32,
+}
+
+impl PaginationMetadata {
+    #[inline]
+    #[must_use]
+    pub const fn new(total_count: u64, page: u32, limit: u32) -> Self {
+        Self { total_count, page, limit }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Error {
     #[serde(rename = "type")]
     pub type_: ErrorType,
 
+    /// Stable machine-readable code, see [`ErrorCode`]
+    pub code: String,
+
     pub message: String,
 
     #[serde(flatten, skip_serializing_if = "IndexMap::is_empty")]
     pub additional_fields: IndexMap<String, serde_json::Value>,
 }
 
+/// Stable machine-readable code of an error, returned as `code` in
+/// [`Error`] so clients can branch on it instead of parsing `message`
+pub trait ErrorCode {
+    /// Code in `SCREAMING_SNAKE_CASE`, e.g. `USER_ALREADY_EXISTS`, it must not
+    /// change once clients depend on it
+    fn error_code(&self) -> &'static str;
+}
+
 #[macro_export]
 macro_rules! json_response {
     (status: $status:expr,data: $data:expr) => {
@@ -331,3 +458,53 @@ pub enum ErrorType {
     UnprocessableEntity,
     UnavailableForLegalReasons,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        drop(headers.insert(header::ACCEPT, HeaderValue::from_static(value)));
+        headers
+    }
+
+    #[test]
+    fn test_response_format_from_accept() {
+        assert_eq!(ResponseFormat::from_headers(&HeaderMap::new()), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::from_headers(&accept("*/*")), ResponseFormat::Json);
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("application/msgpack")),
+            ResponseFormat::MessagePack
+        );
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("application/json, application/x-msgpack")),
+            ResponseFormat::MessagePack
+        );
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("application/json, application/msgpack;q=0.5")),
+            ResponseFormat::Json
+        );
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("application/msgpack;q=0")),
+            ResponseFormat::Json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encapsulated_json_is_serialized_in_negotiated_format() {
+        let response = RESPONSE_FORMAT
+            .scope(ResponseFormat::MessagePack, async {
+                EncapsulatedJson::ok(serde_json::json!({ "id": 1 })).into_response()
+            })
+            .await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], APPLICATION_MSGPACK);
+
+        let bytes = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let decoded: serde_json::Value = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, serde_json::json!({ "_status": 200, "data": { "id": 1 } }));
+
+        let response = EncapsulatedJson::ok(1).into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+}
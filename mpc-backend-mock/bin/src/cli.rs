@@ -2,10 +2,15 @@ use std::{io, io::Write, path::PathBuf};
 
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
-use mpc_backend_mock_server::ApiDoc;
-use utoipa::OpenApi;
+use mpc_backend_mock_server::{CassetteMode, CassetteOptions, MigrateOptions};
 
-use crate::{command::run_server, config::Config, error, shadow};
+use crate::{
+    command::{
+        run_check_config, run_migrate, run_openapi, run_seed, run_server, run_user, OpenApiFormat,
+    },
+    config::Config,
+    error, shadow,
+};
 
 #[derive(Debug, Parser)]
 #[command(author,
@@ -39,10 +44,82 @@ pub enum Command {
 
     #[clap(about = "Run server")]
     #[command(visible_alias = "run")]
-    Server,
+    Server {
+        #[clap(
+            long,
+            value_name = "DIR",
+            conflicts_with = "replay",
+            help = "Record the responses of Bitcoin, Solana and the Keycloak user directory to \
+                    cassettes in `DIR`"
+        )]
+        record: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "DIR",
+            help = "Answer Bitcoin, Solana and the Keycloak user directory from the cassettes in \
+                    `DIR`, logins and token validation still use Keycloak"
+        )]
+        replay: Option<PathBuf>,
+    },
 
     #[clap(about = "Output `OpenApi` document")]
-    OpenApi,
+    OpenApi {
+        #[clap(long, value_enum, default_value_t, help = "Format of the document")]
+        format: OpenApiFormat,
+
+        #[clap(
+            long = "ts-client",
+            value_name = "DIR",
+            help = "Also write the TypeScript types of the API to `DIR/api.d.ts`"
+        )]
+        ts_client: Option<PathBuf>,
+    },
+
+    #[clap(about = "Validate the configuration file")]
+    CheckConfig {
+        #[clap(long, help = "Also connect to Postgres, Keycloak and Bitcoin")]
+        probe: bool,
+    },
+
+    #[clap(about = "Run the pending database migrations without starting the server")]
+    Migrate {
+        #[clap(long, help = "Only print the pending migrations")]
+        dry_run: bool,
+
+        #[clap(long = "to", value_name = "VERSION", help = "Last migration version to apply")]
+        target_version: Option<i64>,
+    },
+
+    #[clap(about = "Manage users without going through the HTTP API")]
+    User {
+        #[command(subcommand)]
+        command: UserCommand,
+    },
+
+    #[clap(about = "Create users, Bitcoin addresses and transactions from a YAML fixture")]
+    Seed {
+        #[clap(long, value_name = "FILE", help = "Fixture file to seed")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UserCommand {
+    #[clap(about = "Create a user and send its activation email")]
+    Create {
+        #[clap(long, help = "Email of the user")]
+        email: String,
+
+        #[clap(long, help = "Language of the user, a BCP 47 tag such as zh-TW")]
+        locale: Option<String>,
+    },
+
+    #[clap(about = "Soft delete a user")]
+    Delete {
+        #[clap(long, help = "Email of the user")]
+        email: String,
+    },
 }
 
 impl Cli {
@@ -63,19 +140,35 @@ impl Cli {
                     serde_yaml::to_string(&Config::default()).expect("`Config` is serializable");
                 io::stdout().write_all(config_text.as_bytes()).expect("failed to write to stdout");
             }
-            Command::Server => {
+            Command::Server { ref record, ref replay } => {
                 let config = self.load_config()?;
-                run_server(config)?;
+                let cassette = match (record, replay) {
+                    (Some(dir), _) => {
+                        Some(CassetteOptions { mode: CassetteMode::Record, dir: dir.clone() })
+                    }
+                    (None, Some(dir)) => {
+                        Some(CassetteOptions { mode: CassetteMode::Replay, dir: dir.clone() })
+                    }
+                    (None, None) => None,
+                };
+                run_server(config, self.config_file_path(), cassette)?;
             }
-            Command::OpenApi => {
-                io::stdout()
-                    .write_all(
-                        ApiDoc::openapi()
-                            .to_yaml()
-                            .expect("ApiDoc should be valid yaml")
-                            .as_bytes(),
-                    )
-                    .expect("failed to write to stdout");
+            Command::OpenApi { format, ts_client } => run_openapi(format, ts_client)?,
+            Command::CheckConfig { probe } => {
+                let config = self.load_config()?;
+                run_check_config(config, probe)?;
+            }
+            Command::Migrate { dry_run, target_version } => {
+                let config = self.load_config()?;
+                run_migrate(config, MigrateOptions { dry_run, target_version })?;
+            }
+            Command::User { ref command } => {
+                let config = self.load_config()?;
+                run_user(config, command)?;
+            }
+            Command::Seed { ref file } => {
+                let config = self.load_config()?;
+                run_seed(config, file)?;
             }
         }
 
@@ -84,7 +177,10 @@ impl Cli {
 
     #[allow(clippy::result_large_err)]
     fn load_config(&self) -> Result<Config, error::Error> {
-        let config_file_path = &self.config_file_path.clone().unwrap_or_else(Config::default_path);
-        Ok(Config::load(config_file_path)?)
+        Ok(Config::load(self.config_file_path())?)
+    }
+
+    fn config_file_path(&self) -> PathBuf {
+        self.config_file_path.clone().unwrap_or_else(Config::default_path)
     }
 }
@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+
+use snafu::ResultExt;
+use tokio::runtime::Runtime;
+
+use crate::{
+    config::{load_server_config, Config},
+    error,
+    error::{Error, Result},
+};
+
+/// Validate the configuration and optionally probe the upstreams it points
+/// to, printing every issue and probe
+#[allow(clippy::result_large_err)]
+pub fn run_check_config(config: Config, probe: bool) -> Result<()> {
+    let report = config.validate();
+
+    let mut stdout = io::stdout().lock();
+    for issue in &report.issues {
+        writeln!(stdout, "{issue}").expect("failed to write to stdout");
+    }
+    let errors = report.errors().count();
+    writeln!(stdout, "Configuration: {errors} error(s), {} warning(s)", report.warnings().count())
+        .expect("failed to write to stdout");
+
+    // probing with an invalid configuration would only repeat its errors
+    if errors > 0 {
+        return Err(Error::CheckConfig { errors, failed_probes: 0 });
+    }
+    if !probe {
+        return Ok(());
+    }
+
+    let runtime = Runtime::new().context(error::InitializeTokioRuntimeSnafu)?;
+    let probes = runtime.block_on(async move {
+        let config = load_server_config(config).await?;
+        Ok::<_, Error>(mpc_backend_mock_server::probe(&config).await)
+    })?;
+
+    for probe in &probes {
+        let line = match &probe.error {
+            None => format!("ok: {} ({:?})", probe.name, probe.elapsed),
+            Some(error) => format!("failed: {} ({:?}): {error}", probe.name, probe.elapsed),
+        };
+        writeln!(stdout, "{line}").expect("failed to write to stdout");
+    }
+
+    let failed_probes = probes.iter().filter(|probe| probe.error.is_some()).count();
+    if failed_probes > 0 {
+        Err(Error::CheckConfig { errors, failed_probes })
+    } else {
+        Ok(())
+    }
+}
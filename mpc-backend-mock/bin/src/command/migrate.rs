@@ -0,0 +1,38 @@
+use std::io::{self, Write};
+
+use mpc_backend_mock_server::{MigrateOptions, MigrationReport};
+use snafu::ResultExt;
+use tokio::runtime::Runtime;
+
+use crate::{
+    config::{load_server_config, Config},
+    error,
+    error::{Error, Result},
+};
+
+/// Run the pending database migrations without starting the server, printing
+/// each migration which is applied, or would be on a dry run
+#[allow(clippy::result_large_err)]
+pub fn run_migrate(config: Config, options: MigrateOptions) -> Result<()> {
+    config.log.registry();
+
+    let runtime = Runtime::new().context(error::InitializeTokioRuntimeSnafu)?;
+    let migrations = runtime.block_on(async move {
+        let config = load_server_config(config).await?;
+        mpc_backend_mock_server::migrate(&config.postgres, options).await.map_err(Error::from)
+    })?;
+
+    let mut stdout = io::stdout().lock();
+    if migrations.is_empty() {
+        writeln!(stdout, "No pending migrations").expect("failed to write to stdout");
+    }
+    for MigrationReport { version, description, elapsed } in migrations {
+        let line = match elapsed {
+            Some(elapsed) => format!("Applied {version}/{description} ({elapsed:?})"),
+            None => format!("Pending {version}/{description}"),
+        };
+        writeln!(stdout, "{line}").expect("failed to write to stdout");
+    }
+
+    Ok(())
+}
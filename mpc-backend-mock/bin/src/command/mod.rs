@@ -1,3 +1,15 @@
+mod check_config;
+mod migrate;
+mod openapi;
+mod seed;
 mod server;
+mod user;
 
-pub use self::server::run_server;
+pub use self::{
+    check_config::run_check_config,
+    migrate::run_migrate,
+    openapi::{run_openapi, OpenApiFormat},
+    seed::run_seed,
+    server::run_server,
+    user::run_user,
+};
@@ -0,0 +1,50 @@
+mod typescript;
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::ValueEnum;
+use mpc_backend_mock_server::ApiDoc;
+use snafu::ResultExt;
+use utoipa::OpenApi;
+
+use crate::error::{self, Result};
+
+/// File the TypeScript types are written to, in the `--ts-client` directory
+const TYPESCRIPT_FILE_NAME: &str = "api.d.ts";
+
+/// Serialization of the OpenAPI document
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum OpenApiFormat {
+    Json,
+
+    #[default]
+    Yaml,
+}
+
+/// Print the OpenAPI document in `format`, and write the TypeScript types of
+/// the API into `ts_client` when given
+#[allow(clippy::result_large_err)]
+pub fn run_openapi(format: OpenApiFormat, ts_client: Option<PathBuf>) -> Result<()> {
+    let openapi = ApiDoc::openapi();
+
+    if let Some(directory) = ts_client {
+        let document = serde_json::to_value(&openapi).expect("ApiDoc should be valid json");
+        let path = directory.join(TYPESCRIPT_FILE_NAME);
+        fs::create_dir_all(&directory)
+            .and_then(|()| fs::write(&path, typescript::generate(&document)))
+            .context(error::WriteTypeScriptClientSnafu { path: path.clone() })?;
+        eprintln!("Wrote TypeScript types to {}", path.display());
+    }
+
+    let document = match format {
+        OpenApiFormat::Json => openapi.to_pretty_json().expect("ApiDoc should be valid json"),
+        OpenApiFormat::Yaml => openapi.to_yaml().expect("ApiDoc should be valid yaml"),
+    };
+    io::stdout().write_all(document.as_bytes()).expect("failed to write to stdout");
+
+    Ok(())
+}
@@ -0,0 +1,246 @@
+//! TypeScript declarations of the OpenAPI document.
+//!
+//! Every component schema becomes an exported type of the same name and the
+//! operations are described by the `Paths` interface, keyed by path and HTTP
+//! method. Only the types are emitted, frontends keep their own HTTP client.
+
+use serde_json::{Map, Value};
+
+const HEADER: &str = "// Generated by `mpc-backend-mock openapi --ts-client`, do not edit.\n";
+
+const METHODS: [&str; 5] = ["get", "put", "post", "delete", "patch"];
+
+/// TypeScript declarations of the OpenAPI `document`
+pub fn generate(document: &Value) -> String {
+    let mut output = String::from(HEADER);
+
+    let schemas = document.pointer("/components/schemas").and_then(Value::as_object);
+    for (name, schema) in schemas.into_iter().flatten() {
+        output.push('\n');
+        output.push_str(&doc_comment(schema, ""));
+        if has_properties(schema) {
+            output.push_str(&format!("export interface {name} {}\n", object(schema, "")));
+        } else {
+            output.push_str(&format!("export type {name} = {};\n", type_of(schema, "")));
+        }
+    }
+
+    output.push_str("\nexport interface Paths {\n");
+    let paths = document.get("paths").and_then(Value::as_object);
+    for (path, item) in paths.into_iter().flatten() {
+        output.push_str(&format!("  {}: {{\n", quote(path)));
+        for method in METHODS {
+            if let Some(operation) = item.get(method) {
+                output.push_str(&operation_type(method, operation));
+            }
+        }
+        output.push_str("  };\n");
+    }
+    output.push_str("}\n");
+
+    output
+}
+
+/// Member of a path in `Paths`, indented by four spaces
+fn operation_type(method: &str, operation: &Value) -> String {
+    const INDENT: &str = "      ";
+
+    let mut members = Vec::new();
+
+    let parameters = operation.get("parameters").and_then(Value::as_array);
+    let mut locations = Map::new();
+    for parameter in parameters.into_iter().flatten() {
+        let (Some(name), Some(location)) = (
+            parameter.get("name").and_then(Value::as_str),
+            parameter.get("in").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let fields = locations
+            .entry(location.to_string())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .expect("is an object; qed");
+        let _previous = fields.insert(name.to_string(), parameter.clone());
+    }
+    if !locations.is_empty() {
+        let mut parameters = String::from("{\n");
+        for (location, fields) in &locations {
+            parameters.push_str(&format!("{INDENT}  {location}: {{\n"));
+            for (name, parameter) in fields.as_object().into_iter().flatten() {
+                let optional =
+                    if parameter.get("required") == Some(&Value::Bool(true)) { "" } else { "?" };
+                let schema = parameter.get("schema").unwrap_or(&Value::Null);
+                parameters.push_str(&doc_comment(parameter, &format!("{INDENT}    ")));
+                parameters.push_str(&format!(
+                    "{INDENT}    {}{optional}: {};\n",
+                    property_name(name),
+                    type_of(schema, &format!("{INDENT}    "))
+                ));
+            }
+            parameters.push_str(&format!("{INDENT}  }};\n"));
+        }
+        parameters.push_str(&format!("{INDENT}}}"));
+        members.push(format!("parameters: {parameters}"));
+    }
+
+    if let Some(body) = operation.get("requestBody") {
+        let optional = if body.get("required") == Some(&Value::Bool(true)) { "" } else { "?" };
+        members.push(format!("requestBody{optional}: {}", content_type(body, INDENT)));
+    }
+
+    let responses = operation.get("responses").and_then(Value::as_object);
+    let mut response_types = String::from("{\n");
+    for (status, response) in responses.into_iter().flatten() {
+        response_types.push_str(&doc_comment(response, &format!("{INDENT}  ")));
+        response_types.push_str(&format!(
+            "{INDENT}  {}: {};\n",
+            property_name(status),
+            content_type(response, &format!("{INDENT}  "))
+        ));
+    }
+    response_types.push_str(&format!("{INDENT}}}"));
+    members.push(format!("responses: {response_types}"));
+
+    let mut output = doc_comment(operation, "    ");
+    output.push_str(&format!("    {method}: {{\n"));
+    for member in members {
+        output.push_str(&format!("{INDENT}{member};\n"));
+    }
+    output.push_str("    };\n");
+    output
+}
+
+/// Type of the content of a request body or response, JSON preferred
+fn content_type(body: &Value, indent: &str) -> String {
+    let Some(content) = body.get("content").and_then(Value::as_object) else {
+        return "void".to_string();
+    };
+
+    content
+        .get("application/json")
+        .or_else(|| content.values().next())
+        .and_then(|media_type| media_type.get("schema"))
+        .map_or_else(|| "string".to_string(), |schema| type_of(schema, indent))
+}
+
+fn type_of(schema: &Value, indent: &str) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference.rsplit('/').next().unwrap_or(reference).to_string();
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values.iter().map(Value::to_string).collect::<Vec<_>>().join(" | ");
+    }
+    for (key, separator) in [("allOf", " & "), ("oneOf", " | "), ("anyOf", " | ")] {
+        if let Some(schemas) = schema.get(key).and_then(Value::as_array) {
+            let types = schemas.iter().map(|schema| type_of(schema, indent)).collect::<Vec<_>>();
+            return format!("({})", types.join(separator));
+        }
+    }
+
+    match schema.get("type") {
+        Some(Value::String(kind)) => type_of_kind(schema, kind, indent),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|kind| type_of_kind(schema, kind, indent))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        _ if has_properties(schema) => object(schema, indent),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn type_of_kind(schema: &Value, kind: &str, indent: &str) -> String {
+    match kind {
+        "string" => "string".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => {
+            let items = schema
+                .get("items")
+                .map_or_else(|| "unknown".to_string(), |items| type_of(items, indent));
+            format!("Array<{items}>")
+        }
+        "object" => object(schema, indent),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Object literal type of `schema`, its closing brace indented by `indent`
+fn object(schema: &Value, indent: &str) -> String {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let Some(properties) = properties.filter(|properties| !properties.is_empty()) else {
+        return match schema.get("additionalProperties") {
+            Some(Value::Bool(false)) => "Record<string, never>".to_string(),
+            Some(additional) if additional.is_object() => {
+                format!("Record<string, {}>", type_of(additional, indent))
+            }
+            _ => "Record<string, unknown>".to_string(),
+        };
+    };
+
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let inner = format!("{indent}  ");
+
+    let mut output = String::from("{\n");
+    for (name, property) in properties {
+        let optional = if required.contains(&name.as_str()) { "" } else { "?" };
+        output.push_str(&doc_comment(property, &inner));
+        output.push_str(&format!(
+            "{inner}{}{optional}: {};\n",
+            property_name(name),
+            type_of(property, &inner)
+        ));
+    }
+    output.push_str(indent);
+    output.push('}');
+    output
+}
+
+fn has_properties(schema: &Value) -> bool {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|properties| !properties.is_empty())
+}
+
+/// `/** … */` of the `description` of `schema`, empty when it has none
+fn doc_comment(schema: &Value, indent: &str) -> String {
+    let Some(description) = schema.get("description").and_then(Value::as_str) else {
+        return String::new();
+    };
+    let description = description.trim().replace("*/", "*\\/");
+    if description.is_empty() {
+        return String::new();
+    }
+
+    if description.contains('\n') {
+        let lines = description
+            .lines()
+            .map(|line| format!("{indent} * {line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("{indent}/**\n{lines}\n{indent} */\n")
+    } else {
+        format!("{indent}/** {description} */\n")
+    }
+}
+
+/// Property key, quoted unless it is an identifier
+fn property_name(name: &str) -> String {
+    let is_identifier = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+fn quote(value: &str) -> String { Value::from(value).to_string() }
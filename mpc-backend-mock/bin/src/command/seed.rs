@@ -0,0 +1,51 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use mpc_backend_mock_server::{Fixtures, SeedReport};
+use snafu::ResultExt;
+use tokio::runtime::Runtime;
+
+use crate::{
+    config::{load_server_config, Config},
+    error,
+    error::Result,
+};
+
+/// Seed the fixtures of `file` with the server configuration, printing what
+/// was created
+#[allow(clippy::result_large_err)]
+pub fn run_seed(config: Config, file: &Path) -> Result<()> {
+    let fixtures = std::fs::read_to_string(file)
+        .context(error::ReadFixturesSnafu { path: file.to_path_buf() })?;
+    let fixtures: Fixtures = serde_yaml::from_str(&fixtures)
+        .context(error::ParseFixturesSnafu { path: file.to_path_buf() })?;
+
+    config.log.registry();
+
+    let runtime = Runtime::new().context(error::InitializeTokioRuntimeSnafu)?;
+    let report = runtime.block_on(async {
+        let config = load_server_config(config).await?;
+        Ok::<_, error::Error>(mpc_backend_mock_server::seed(config, &fixtures).await?)
+    })?;
+
+    let SeedReport {
+        users_created,
+        users_skipped,
+        wallets_created,
+        wallets_skipped,
+        transactions_created,
+        transactions_skipped,
+    } = report;
+    writeln!(
+        io::stdout(),
+        "Seeded {users_created} user(s), {wallets_created} Bitcoin address(es) and \
+         {transactions_created} transaction(s), skipped {users_skipped} user(s), \
+         {wallets_skipped} Bitcoin address(es) and {transactions_skipped} transaction(s) which \
+         already exist"
+    )
+    .expect("failed to write to stdout");
+
+    Ok(())
+}
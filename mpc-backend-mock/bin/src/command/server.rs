@@ -1,23 +1,41 @@
-use std::process;
+use std::{path::PathBuf, process, sync::Arc};
 
+use async_trait::async_trait;
 use chrono::Utc;
-use mpc_backend_mock_core::{ServerInfo, PROGRAM_NAME};
+use mpc_backend_mock_core::{ServerInfo, PROGRAM_NAME, PROJECT_NAME_WITH_INITIAL_CAPITAL};
+use mpc_backend_mock_server::{CassetteOptions, ConfigSource};
 use snafu::ResultExt;
 use tokio::runtime::Runtime;
+use zeus_cli_common::config::LogFilterHandle;
 
 use crate::{
-    config::{load_server_config, Config},
+    config::{self, load_server_config, Config},
     error,
     error::{Error, Result},
     shadow::{BRANCH, PKG_VERSION, SHORT_COMMIT},
 };
 
-/// Run the server
+/// Run the server, reloading `config_file_path` on `SIGHUP` and recording or
+/// replaying the upstream responses with `cassette`
 #[allow(clippy::cognitive_complexity, clippy::result_large_err)]
-pub fn run_server(config: Config) -> Result<()> {
+pub fn run_server(
+    config: Config,
+    config_file_path: PathBuf,
+    cassette: Option<CassetteOptions>,
+) -> Result<()> {
     let Config { ref log, ref bitcoin, ref solana, .. } = config;
 
-    log.registry();
+    let log_filter = log.reloadable_registry();
+
+    let report = config.validate();
+    for warning in report.warnings() {
+        tracing::warn!("{warning}");
+    }
+    if report.has_errors() {
+        let error = Error::from(config::Error::InvalidConfig { report });
+        tracing::error!(%error);
+        return Err(error);
+    }
 
     let server_info = ServerInfo {
         version: PKG_VERSION.to_string(),
@@ -26,10 +44,11 @@ pub fn run_server(config: Config) -> Result<()> {
         branch: BRANCH.to_string(),
         solana_cluster: solana.endpoint.cluster.to_string(),
         start_time: Utc::now(),
+        capabilities: config.capabilities(),
     };
 
     tracing::info!("{PROGRAM_NAME} is initializing, pid: {}", process::id());
-    tracing::info!("Server info: {server_info:?}");
+    log_banner(&server_info);
 
     tracing::info!("Initializing Tokio runtime");
 
@@ -37,10 +56,16 @@ pub fn run_server(config: Config) -> Result<()> {
         Ok(runtime) => runtime.block_on({
             async move {
                 let config = load_server_config(config).await?;
+                let config_source = Arc::new(ConfigFile { path: config_file_path, log_filter });
 
-                mpc_backend_mock_server::serve_with_shutdown(config, server_info)
-                    .await
-                    .map_err(Error::from)
+                mpc_backend_mock_server::serve_with_shutdown(
+                    config,
+                    server_info,
+                    config_source,
+                    cassette,
+                )
+                .await
+                .map_err(Error::from)
             }
         }),
 
@@ -54,3 +79,42 @@ pub fn run_server(config: Config) -> Result<()> {
     tracing::info!("{PROGRAM_NAME} is shutdown");
     exit_status
 }
+
+/// Configuration file the server was started with, read again on reload
+struct ConfigFile {
+    path: PathBuf,
+    log_filter: LogFilterHandle,
+}
+
+#[async_trait]
+impl ConfigSource for ConfigFile {
+    async fn load(&self) -> std::result::Result<mpc_backend_mock_core::config::Config, String> {
+        let config = Config::load(&self.path).map_err(|err| err.to_string())?;
+
+        let report = config.validate();
+        if report.has_errors() {
+            return Err(config::Error::InvalidConfig { report }.to_string());
+        }
+
+        load_server_config(config).await.map_err(|err| err.to_string())
+    }
+
+    fn reload_log_filters(&self, log_filters: &str) -> std::result::Result<(), String> {
+        self.log_filter.reload(log_filters).map_err(|err| err.to_string())
+    }
+}
+
+fn log_banner(server_info: &ServerInfo) {
+    tracing::info!(
+        "{PROJECT_NAME_WITH_INITIAL_CAPITAL} v{} ({}@{})",
+        server_info.version,
+        server_info.branch,
+        server_info.commit_hash
+    );
+    tracing::info!(
+        "Bitcoin network: {}, Solana cluster: {}",
+        server_info.bitcoin_network,
+        server_info.solana_cluster
+    );
+    tracing::info!("Capabilities: {}", server_info.capabilities);
+}
@@ -0,0 +1,41 @@
+use std::io::{self, Write};
+
+use snafu::ResultExt;
+use tokio::runtime::Runtime;
+
+use crate::{
+    cli::UserCommand,
+    config::{load_server_config, Config},
+    error,
+    error::Result,
+};
+
+/// Create or delete a user with the server configuration, printing the user
+#[allow(clippy::result_large_err)]
+pub fn run_user(config: Config, command: &UserCommand) -> Result<()> {
+    config.log.registry();
+
+    let runtime = Runtime::new().context(error::InitializeTokioRuntimeSnafu)?;
+    let line = runtime.block_on(manage_user(config, command))?;
+
+    writeln!(io::stdout(), "{line}").expect("failed to write to stdout");
+
+    Ok(())
+}
+
+#[allow(clippy::result_large_err)]
+async fn manage_user(config: Config, command: &UserCommand) -> Result<String> {
+    let config = load_server_config(config).await?;
+
+    match command {
+        UserCommand::Create { email, locale } => {
+            let user =
+                mpc_backend_mock_server::create_user(config, email, locale.as_deref()).await?;
+            Ok(format!("Created user {} <{}>, activation email sent", user.id, user.email))
+        }
+        UserCommand::Delete { email } => {
+            let user_id = mpc_backend_mock_server::delete_user(config, email).await?;
+            Ok(format!("Deleted user {user_id} <{email}>"))
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ActivationConfig {
+    /// Page the activation link in the activation email points to
+    #[serde(default = "ActivationConfig::default_url")]
+    pub url: String,
+
+    /// Page the link in the email verifying a new email address points to
+    #[serde(default = "ActivationConfig::default_email_change_url")]
+    pub email_change_url: String,
+
+    /// How long an activation or email change token can be used, in seconds
+    #[serde(default = "ActivationConfig::default_token_ttl_seconds")]
+    pub token_ttl_seconds: u64,
+}
+
+impl ActivationConfig {
+    #[inline]
+    pub fn default_url() -> String { "http://localhost:3000/activate".to_string() }
+
+    #[inline]
+    pub fn default_email_change_url() -> String {
+        "http://localhost:3000/confirm-email".to_string()
+    }
+
+    /// One day
+    #[inline]
+    pub const fn default_token_ttl_seconds() -> u64 { 24 * 60 * 60 }
+}
+
+impl Default for ActivationConfig {
+    fn default() -> Self {
+        Self {
+            url: Self::default_url(),
+            email_change_url: Self::default_email_change_url(),
+            token_ttl_seconds: Self::default_token_ttl_seconds(),
+        }
+    }
+}
+
+impl From<ActivationConfig> for mpc_backend_mock_core::config::ActivationConfig {
+    fn from(config: ActivationConfig) -> Self {
+        Self {
+            url: config.url,
+            email_change_url: config.email_change_url,
+            token_ttl: Duration::from_secs(config.token_ttl_seconds),
+        }
+    }
+}
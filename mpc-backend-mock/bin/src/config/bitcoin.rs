@@ -1,11 +1,14 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use eris_bitcoin_ext::WellKnownNetwork as BitcoinNetwork;
 use eris_bitcoin_rpc_client::Authentication as BitcoinRpcAuthentication;
 use serde::{Deserialize, Serialize};
 use zpl_bitcoin_spv::constant::BLOCK_CONFIRMATION_COUNT;
 
-use crate::config::error::Error;
+use crate::{
+    config::{error::Error, Secret},
+    kms_client::KeyManagementServiceClient,
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BitcoinConfig {
@@ -16,33 +19,51 @@ pub struct BitcoinConfig {
 
     /// username and password for authentication, separating by `:`, for
     /// example: "USER:PASSWORD"
-    pub rpc_authentication: Option<String>,
+    pub rpc_authentication: Option<Secret>,
 
     #[serde(with = "http_serde::option::uri")]
     pub indexer_endpoint: Option<http::Uri>,
 
     pub support_quicknode_blockbook: bool,
-}
 
-impl TryFrom<BitcoinConfig> for mpc_backend_mock_core::config::BitcoinConfig {
-    type Error = Error;
+    /// How long fee estimates and mempool information are served from the
+    /// cache, in seconds, so that polling clients do not hammer the node
+    #[serde(default = "BitcoinConfig::default_fee_cache_ttl_seconds")]
+    pub fee_cache_ttl_seconds: u64,
+
+    /// Serve a canned chain in process instead of connecting to the RPC and
+    /// indexer endpoints
+    #[serde(default)]
+    pub mock: bool,
+}
 
-    fn try_from(source: BitcoinConfig) -> Result<Self, Self::Error> {
-        let BitcoinConfig {
+impl BitcoinConfig {
+    /// Convert into the server configuration, decrypting the RPC
+    /// authentication with `kms` if it is encrypted
+    pub async fn into_core(
+        self,
+        kms: Option<&dyn KeyManagementServiceClient>,
+    ) -> Result<mpc_backend_mock_core::config::BitcoinConfig, Error> {
+        let Self {
             network,
             rpc_endpoint,
             rpc_authentication,
             indexer_endpoint,
             support_quicknode_blockbook,
-        } = source;
+            fee_cache_ttl_seconds,
+            mock,
+        } = self;
         let network = BitcoinNetwork::from_str(&network)
             .map_err(|_| Error::ParseBitcoinNetwork { value: network })?;
-        let authentication = rpc_authentication
-            .map(|auth| BitcoinRpcAuthentication::from_str(&auth).unwrap_or_default())
-            .unwrap_or_default();
+        let authentication = match rpc_authentication {
+            Some(auth) => {
+                BitcoinRpcAuthentication::from_str(&auth.reveal(kms).await?).unwrap_or_default()
+            }
+            None => BitcoinRpcAuthentication::default(),
+        };
 
         let block_number_to_confirm = u64::try_from(BLOCK_CONFIRMATION_COUNT).unwrap_or(6);
-        Ok(Self {
+        Ok(mpc_backend_mock_core::config::BitcoinConfig {
             endpoint: eris_bitcoin_rpc_client::RpcEndpoint {
                 endpoint: rpc_endpoint,
                 indexer_endpoint,
@@ -51,11 +72,14 @@ impl TryFrom<BitcoinConfig> for mpc_backend_mock_core::config::BitcoinConfig {
                 network,
             },
             block_number_to_confirm,
+            fee_cache_ttl: Duration::from_secs(fee_cache_ttl_seconds),
+            mock,
         })
     }
-}
 
-impl BitcoinConfig {
+    #[inline]
+    pub const fn default_fee_cache_ttl_seconds() -> u64 { 5 }
+
     pub fn devnet() -> Self {
         Self {
             network: "regtest".to_string(),
@@ -63,6 +87,8 @@ impl BitcoinConfig {
             rpc_authentication: None,
             indexer_endpoint: Some(http::Uri::from_static("http://127.0.0.1:50001")),
             support_quicknode_blockbook: false,
+            fee_cache_ttl_seconds: Self::default_fee_cache_ttl_seconds(),
+            mock: false,
         }
     }
 }
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChainStateConfig {
+    /// Time between two reads of the Bitcoin block height and the Solana slot
+    /// served from the cache, in seconds
+    #[serde(default = "ChainStateConfig::default_refresh_interval_seconds")]
+    pub refresh_interval_seconds: u64,
+}
+
+impl ChainStateConfig {
+    #[inline]
+    pub const fn default_refresh_interval_seconds() -> u64 { 10 }
+}
+
+impl Default for ChainStateConfig {
+    fn default() -> Self {
+        Self { refresh_interval_seconds: Self::default_refresh_interval_seconds() }
+    }
+}
+
+impl From<ChainStateConfig> for mpc_backend_mock_core::config::ChainStateConfig {
+    fn from(config: ChainStateConfig) -> Self {
+        Self { refresh_interval: Duration::from_secs(config.refresh_interval_seconds) }
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Circuit breakers in front of Keycloak, the Bitcoin RPC endpoint and the
+/// JWKS endpoint, each opened on its own
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed calls after which a dependency is considered down
+    #[serde(default = "CircuitBreakerConfig::default_failure_threshold")]
+    pub failure_threshold: u32,
+
+    /// Seconds calls are rejected with `503` before a trial call is let
+    /// through
+    #[serde(default = "CircuitBreakerConfig::default_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+impl CircuitBreakerConfig {
+    #[inline]
+    pub const fn default_failure_threshold() -> u32 { 5 }
+
+    #[inline]
+    pub const fn default_cooldown_seconds() -> u64 { 30 }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: Self::default_failure_threshold(),
+            cooldown_seconds: Self::default_cooldown_seconds(),
+        }
+    }
+}
+
+impl From<CircuitBreakerConfig> for mpc_backend_mock_core::config::CircuitBreakerConfig {
+    fn from(
+        CircuitBreakerConfig { failure_threshold, cooldown_seconds }: CircuitBreakerConfig,
+    ) -> Self {
+        Self { failure_threshold, cooldown: Duration::from_secs(cooldown_seconds) }
+    }
+}
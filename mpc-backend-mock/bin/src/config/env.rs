@@ -0,0 +1,76 @@
+use serde_yaml::{Mapping, Value};
+
+use crate::config::{Config, Error};
+
+/// Prefix of the environment variables overriding configuration fields,
+/// nested fields are separated by [`SEPARATOR`], e.g.
+/// `MPC_BACKEND_MOCK__POSTGRES__PASSWORD` overrides `postgres.password`
+pub const ENV_PREFIX: &str = "MPC_BACKEND_MOCK__";
+
+const SEPARATOR: &str = "__";
+
+/// Override the fields of a parsed configuration file with the matching
+/// environment variables
+///
+/// A value replaces a string field as is, and is parsed as YAML otherwise, so
+/// that numbers, booleans and lists such as `[10.0.0.0/8]` can be set too.
+pub fn apply_env_overrides(
+    config: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), Error> {
+    let defaults = serde_yaml::to_value(Config::default()).expect("`Config` is serializable");
+
+    let mut overrides = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let path = name.strip_prefix(ENV_PREFIX)?.split(SEPARATOR).map(str::to_lowercase);
+            Some((path.collect::<Vec<_>>(), name, value))
+        })
+        .collect::<Vec<_>>();
+    // parents before their fields, so that a field wins over a whole section
+    overrides.sort();
+
+    for (path, name, value) in overrides {
+        if path.iter().any(String::is_empty) {
+            return Err(Error::InvalidEnvOverride { name });
+        }
+
+        let is_string = matches!(
+            lookup(config, &path).or_else(|| lookup(&defaults, &path)),
+            Some(Value::String(_))
+        );
+        let value = if is_string {
+            Value::String(value)
+        } else {
+            serde_yaml::from_str(&value).unwrap_or_else(|_| Value::String(value))
+        };
+
+        if !insert(config, &path, value) {
+            return Err(Error::InvalidEnvOverride { name });
+        }
+    }
+
+    Ok(())
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| value.get(key.as_str()))
+}
+
+/// Set the field at `path`, creating the missing sections, fails if a parent
+/// is not a section
+fn insert(value: &mut Value, path: &[String], field: Value) -> bool {
+    let mut current = value;
+    for key in path {
+        if current.is_null() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let Value::Mapping(mapping) = current else {
+            return false;
+        };
+        current = mapping.entry(Value::String(key.clone())).or_insert(Value::Null);
+    }
+
+    *current = field;
+    true
+}
@@ -13,6 +13,12 @@ pub enum Error {
     #[snafu(display("Count not parse config from {}, error: {source}", filename.display()))]
     ParseConfig { filename: PathBuf, source: serde_yaml::Error },
 
+    #[snafu(display(
+        "Environment variable `{name}` does not name a configuration field, nested fields are \
+         separated by `__`"
+    ))]
+    InvalidEnvOverride { name: String },
+
     #[snafu(display("Could not resolve file path {}, error: {source}", file_path.display()))]
     ResolveFilePath { file_path: PathBuf, source: std::io::Error },
 
@@ -25,6 +31,15 @@ pub enum Error {
     #[snafu(display("Failed to restore solana private key, error: {source}"))]
     RestoreSolanaKeypair { source: Box<dyn std::error::Error> },
 
+    #[snafu(display("Notification provider `{provider}` is selected but not configured"))]
+    MissingNotificationProviderConfig { provider: String },
+
+    #[snafu(display("Event publisher `{publisher}` is selected but not configured"))]
+    MissingEventPublisherConfig { publisher: String },
+
+    #[snafu(display("Invalid configuration, {report}"))]
+    InvalidConfig { report: crate::config::ValidationReport },
+
     #[snafu(display("Key Management Service client is required"))]
     KmsClientRequired,
 
@@ -33,9 +48,14 @@ pub enum Error {
     ))]
     InitializeGcpKms { source: kms_client::Error },
 
+    #[snafu(display("Vault token is neither configured nor set in `VAULT_TOKEN`"))]
+    MissingVaultToken,
+
     #[snafu(display(
-        "Failed to decrypt value: {value} from Google Cloud Platform Key Management Service, \
-         error: {source}"
+        "Failed to decrypt value: {value} with the Key Management Service, error: {source}"
     ))]
-    GcpKmsDecrypt { value: String, source: kms_client::Error },
+    KmsDecrypt { value: String, source: kms_client::Error },
+
+    #[snafu(display("Value decrypted from {value} is not UTF-8"))]
+    NonUtf8Secret { value: String },
 }
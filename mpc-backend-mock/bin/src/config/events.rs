@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Error;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EventsConfig {
+    /// Publisher shipping the domain events, `nats` or `kafka` when their
+    /// section is set and `disabled` otherwise. Events are only written to the
+    /// outbox while a publisher is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<EventPublisher>,
+
+    /// Publish to NATS JetStream
+    pub nats: Option<events::nats::Config>,
+
+    /// Publish to a Kafka topic
+    pub kafka: Option<events::kafka::Config>,
+
+    /// Events published per run of the publisher
+    #[serde(default = "EventsConfig::default_batch_size")]
+    pub batch_size: u32,
+}
+
+impl EventsConfig {
+    #[inline]
+    pub const fn default_batch_size() -> u32 { 100 }
+
+    /// Publisher which is selected or implied by the configured sections
+    #[must_use]
+    pub const fn publisher(&self) -> EventPublisher {
+        match (self.publisher, &self.nats, &self.kafka) {
+            (Some(publisher), ..) => publisher,
+            (None, Some(_), _) => EventPublisher::Nats,
+            (None, None, Some(_)) => EventPublisher::Kafka,
+            (None, None, None) => EventPublisher::Disabled,
+        }
+    }
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self { publisher: None, nats: None, kafka: None, batch_size: Self::default_batch_size() }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventPublisher {
+    Disabled,
+    Log,
+    Nats,
+    Kafka,
+}
+
+impl TryFrom<EventsConfig> for mpc_backend_mock_core::config::EventsConfig {
+    type Error = Error;
+
+    fn try_from(config: EventsConfig) -> Result<Self, Self::Error> {
+        use mpc_backend_mock_core::config::EventPublisherConfig;
+
+        let publisher = match config.publisher() {
+            EventPublisher::Disabled => EventPublisherConfig::Disabled,
+            EventPublisher::Log => EventPublisherConfig::Log,
+            EventPublisher::Nats => config
+                .nats
+                .map(EventPublisherConfig::Nats)
+                .ok_or(Error::MissingEventPublisherConfig { publisher: "nats".to_string() })?,
+            EventPublisher::Kafka => config
+                .kafka
+                .map(EventPublisherConfig::Kafka)
+                .ok_or(Error::MissingEventPublisherConfig { publisher: "kafka".to_string() })?,
+        };
+
+        Ok(Self { publisher, batch_size: config.batch_size })
+    }
+}
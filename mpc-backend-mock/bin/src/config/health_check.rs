@@ -1,6 +1,36 @@
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{
+    config::{error, Error, Secret},
+    kms_client::KeyManagementServiceClient,
+};
+
+/// Dependency checked by the gRPC health check
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheckComponent {
+    Postgres,
+    Bitcoin,
+    Keycloak,
+    Solana,
+}
+
+impl From<HealthCheckComponent> for mpc_backend_mock_core::config::HealthCheckComponent {
+    fn from(component: HealthCheckComponent) -> Self {
+        match component {
+            HealthCheckComponent::Postgres => Self::Postgres,
+            HealthCheckComponent::Bitcoin => Self::Bitcoin,
+            HealthCheckComponent::Keycloak => Self::Keycloak,
+            HealthCheckComponent::Solana => Self::Solana,
+        }
+    }
+}
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct HealthCheckConfig {
@@ -9,9 +39,57 @@ pub struct HealthCheckConfig {
 
     #[serde(default = "HealthCheckConfig::default_port")]
     pub port: u16,
+
+    /// Dependencies the health check reports on, each one can also be
+    /// checked on its own by its name
+    #[serde(default = "HealthCheckConfig::default_components")]
+    pub components: Vec<HealthCheckComponent>,
+
+    /// Serve over TLS, plain text when unset
+    #[serde(default)]
+    pub tls: Option<HealthCheckTlsConfig>,
+
+    /// Token callers must send as `authorization: Bearer <token>`, anyone may
+    /// call when unset
+    #[serde(default)]
+    pub bearer_token: Option<Secret>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HealthCheckTlsConfig {
+    /// PEM encoded certificate chain of the server
+    pub certificate_file: PathBuf,
+
+    /// PEM encoded private key of the server
+    pub private_key_file: PathBuf,
+
+    /// PEM encoded CA certificates, clients must present a certificate
+    /// signed by one of them when set
+    #[serde(default)]
+    pub client_ca_file: Option<PathBuf>,
 }
 
 impl HealthCheckConfig {
+    pub async fn into_core(
+        self,
+        kms: Option<&dyn KeyManagementServiceClient>,
+    ) -> Result<mpc_backend_mock_core::config::HealthCheckConfig, Error> {
+        let listen_address = self.socket_address();
+        let Self { components, tls, bearer_token, .. } = self;
+        let tls = tls.map(HealthCheckTlsConfig::load).transpose()?;
+        let bearer_token = match bearer_token {
+            Some(token) => Some(token.reveal(kms).await?),
+            None => None,
+        };
+
+        Ok(mpc_backend_mock_core::config::HealthCheckConfig {
+            listen_address,
+            components: components.into_iter().map(Into::into).collect(),
+            tls,
+            bearer_token,
+        })
+    }
+
     #[inline]
     pub const fn socket_address(&self) -> SocketAddr { SocketAddr::new(self.host, self.port) }
 
@@ -20,8 +98,42 @@ impl HealthCheckConfig {
 
     #[inline]
     pub const fn default_port() -> u16 { mpc_backend_mock_core::DEFAULT_HEALTH_CHECK_PORT }
+
+    #[inline]
+    pub fn default_components() -> Vec<HealthCheckComponent> {
+        vec![
+            HealthCheckComponent::Postgres,
+            HealthCheckComponent::Bitcoin,
+            HealthCheckComponent::Keycloak,
+            HealthCheckComponent::Solana,
+        ]
+    }
 }
 
 impl Default for HealthCheckConfig {
-    fn default() -> Self { Self { host: Self::default_host(), port: Self::default_port() } }
+    fn default() -> Self {
+        Self {
+            host: Self::default_host(),
+            port: Self::default_port(),
+            components: Self::default_components(),
+            tls: None,
+            bearer_token: None,
+        }
+    }
+}
+
+impl HealthCheckTlsConfig {
+    fn load(self) -> Result<mpc_backend_mock_core::config::HealthCheckTlsConfig, Error> {
+        let Self { certificate_file, private_key_file, client_ca_file } = self;
+
+        Ok(mpc_backend_mock_core::config::HealthCheckTlsConfig {
+            certificate: read_file(&certificate_file)?,
+            private_key: read_file(&private_key_file)?,
+            client_ca_certificate: client_ca_file.as_deref().map(read_file).transpose()?,
+        })
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    std::fs::read(path).with_context(|_| error::ReadFileSnafu { path: path.to_path_buf() })
 }
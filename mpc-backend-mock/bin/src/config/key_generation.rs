@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Simulation of the key generation of `POST /api/v1/keys`
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct KeyGenerationConfig {
+    /// Delay before a key is generated, in milliseconds
+    #[serde(default)]
+    pub simulate_delay_ms: u64,
+
+    /// Fail every key generation with `503`
+    #[serde(default)]
+    pub simulate_failure: bool,
+}
+
+impl From<KeyGenerationConfig> for mpc_backend_mock_core::config::KeyGenerationConfig {
+    fn from(
+        KeyGenerationConfig { simulate_delay_ms, simulate_failure }: KeyGenerationConfig,
+    ) -> Self {
+        Self { simulate_delay: Duration::from_millis(simulate_delay_ms), simulate_failure }
+    }
+}
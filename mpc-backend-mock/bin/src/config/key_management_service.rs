@@ -8,6 +8,10 @@ use crate::{
     kms_client,
 };
 
+/// Environment variable holding the Vault token when the configuration has
+/// none
+const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum KeyManagementService {
     GoogleCloudPlatform {
@@ -16,9 +20,41 @@ pub enum KeyManagementService {
         key_ring: String,
         crypto_key: String,
     },
+
+    /// Credentials come from the default AWS provider chain
+    AmazonWebServices {
+        region: String,
+
+        /// Only needed for ciphertexts of asymmetric keys
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key_id: Option<String>,
+    },
+
+    /// Transit secrets engine of HashiCorp Vault
+    HashiCorpVault {
+        /// e.g. `https://vault.example.com:8200`
+        address: String,
+
+        /// Read from `VAULT_TOKEN` when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+
+        /// Path the transit engine is mounted at
+        #[serde(default = "KeyManagementService::default_vault_mount")]
+        mount: String,
+
+        /// Name of the encryption key
+        key: String,
+    },
 }
 
 impl KeyManagementService {
+    #[inline]
+    pub fn default_vault_mount() -> String { "transit".to_string() }
+
     pub async fn load(&self) -> Result<Arc<dyn kms_client::KeyManagementServiceClient>, Error> {
         match self {
             Self::GoogleCloudPlatform { project_id, location, key_ring, crypto_key } => {
@@ -31,6 +67,26 @@ impl KeyManagementService {
                 .await
                 .context(error::InitializeGcpKmsSnafu)?;
 
+                Ok(Arc::new(client))
+            }
+            Self::AmazonWebServices { region, key_id } => {
+                let client = kms_client::aws::Client::new(region.clone(), key_id.clone()).await;
+
+                Ok(Arc::new(client))
+            }
+            Self::HashiCorpVault { address, token, namespace, mount, key } => {
+                let token = match token {
+                    Some(token) => token.clone(),
+                    None => std::env::var(VAULT_TOKEN_ENV).map_err(|_| Error::MissingVaultToken)?,
+                };
+                let client = kms_client::vault::Client::new(
+                    address.clone(),
+                    token,
+                    namespace.clone(),
+                    mount.clone(),
+                    key.clone(),
+                );
+
                 Ok(Arc::new(client))
             }
         }
@@ -1,5 +1,12 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    config::{Error, Secret},
+    kms_client::KeyManagementServiceClient,
+};
+
 /// JWT validation method
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -27,7 +34,13 @@ pub struct KeycloakConfig {
 
     /// Client secret for backend service
     #[serde(default = "KeycloakConfig::default_client_secret")]
-    pub client_secret: String,
+    pub client_secret: Secret,
+
+    /// `iss` claim of the tokens of the realm, `{server_url}/realms/{realm}`
+    /// if unset, set it when clients reach Keycloak under another URL than
+    /// the backend
+    #[serde(default)]
+    pub issuer: Option<String>,
 
     /// Enable TLS certificate verification
     #[serde(default = "KeycloakConfig::default_verify_ssl")]
@@ -36,9 +49,95 @@ pub struct KeycloakConfig {
     /// JWT validation method
     #[serde(default)]
     pub jwt_validation_method: JwtValidationMethod,
+
+    /// How long the claims of an active token are reused instead of
+    /// introspecting it again, in seconds, `0` disables the cache
+    #[serde(default = "KeycloakConfig::default_introspection_cache_ttl_seconds")]
+    pub introspection_cache_ttl_seconds: u64,
+
+    /// How long fetched JWKS are served from the cache, in seconds
+    #[serde(default = "KeycloakConfig::default_jwks_cache_ttl_seconds")]
+    pub jwks_cache_ttl_seconds: u64,
+
+    /// Time between two background JWKS refreshes, in seconds, should be
+    /// shorter than `jwks_cache_ttl_seconds`
+    #[serde(default = "KeycloakConfig::default_jwks_refresh_interval_seconds")]
+    pub jwks_refresh_interval_seconds: u64,
+
+    /// Further realms of the same server whose tokens are accepted, their
+    /// users are kept apart from those of `realm`
+    #[serde(default)]
+    pub realms: Vec<KeycloakRealmConfig>,
+}
+
+/// Keycloak realm whose tokens are accepted besides `keycloak.realm`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeycloakRealmConfig {
+    /// Keycloak realm name (e.g., "partner")
+    pub realm: String,
+
+    /// Client ID of the backend in the realm, introspects its tokens
+    #[serde(default = "KeycloakConfig::default_client_id")]
+    pub client_id: String,
+
+    /// Client secret of the backend in the realm
+    #[serde(default = "KeycloakConfig::default_client_secret")]
+    pub client_secret: Secret,
+
+    /// `iss` claim of the tokens of the realm, `{server_url}/realms/{realm}`
+    /// if unset
+    #[serde(default)]
+    pub issuer: Option<String>,
 }
 
 impl KeycloakConfig {
+    /// Convert into the server configuration, decrypting the client secrets
+    /// with `kms` if they are encrypted
+    pub async fn into_core(
+        self,
+        kms: Option<&dyn KeyManagementServiceClient>,
+    ) -> Result<mpc_backend_mock_core::config::KeycloakConfig, Error> {
+        let mut additional_realms = Vec::with_capacity(self.realms.len());
+        for realm in &self.realms {
+            additional_realms.push(mpc_backend_mock_core::config::KeycloakRealmConfig {
+                realm: realm.realm.clone(),
+                client_id: realm.client_id.clone(),
+                client_secret: realm.client_secret.clone().reveal(kms).await?,
+                issuer: realm.issuer.clone().unwrap_or_else(|| self.default_issuer(&realm.realm)),
+            });
+        }
+        let issuer = self.issuer.clone().unwrap_or_else(|| self.default_issuer(&self.realm));
+
+        Ok(mpc_backend_mock_core::config::KeycloakConfig {
+            server_url: self.server_url,
+            realm: self.realm,
+            client_id: self.client_id,
+            client_secret: self.client_secret.reveal(kms).await?,
+            issuer,
+            verify_ssl: self.verify_ssl,
+            jwt_validation_method: match self.jwt_validation_method {
+                JwtValidationMethod::Jwks => {
+                    // Map to core config enum
+                    mpc_backend_mock_core::config::JwtValidationMethod::Jwks
+                }
+                JwtValidationMethod::Introspection => {
+                    // Map to core config enum
+                    mpc_backend_mock_core::config::JwtValidationMethod::Introspection
+                }
+            },
+            introspection_cache_ttl: Duration::from_secs(self.introspection_cache_ttl_seconds),
+            jwks_cache_ttl: Duration::from_secs(self.jwks_cache_ttl_seconds),
+            jwks_refresh_interval: Duration::from_secs(self.jwks_refresh_interval_seconds),
+            additional_realms,
+        })
+    }
+
+    /// `iss` claim of the tokens of `realm`, unless configured otherwise
+    #[must_use]
+    pub fn default_issuer(&self, realm: &str) -> String {
+        format!("{}/realms/{realm}", self.server_url.trim_end_matches('/'))
+    }
+
     #[inline]
     pub fn default_server_url() -> String { "http://localhost:8080".to_string() }
 
@@ -49,10 +148,19 @@ impl KeycloakConfig {
     pub fn default_client_id() -> String { "mpc-backend".to_string() }
 
     #[inline]
-    pub fn default_client_secret() -> String { "changeme".to_string() }
+    pub fn default_client_secret() -> Secret { Secret::plain("changeme") }
 
     #[inline]
     pub const fn default_verify_ssl() -> bool { true }
+
+    #[inline]
+    pub const fn default_introspection_cache_ttl_seconds() -> u64 { 30 }
+
+    #[inline]
+    pub const fn default_jwks_cache_ttl_seconds() -> u64 { 300 }
+
+    #[inline]
+    pub const fn default_jwks_refresh_interval_seconds() -> u64 { 240 }
 }
 
 impl Default for KeycloakConfig {
@@ -62,8 +170,13 @@ impl Default for KeycloakConfig {
             realm: Self::default_realm(),
             client_id: Self::default_client_id(),
             client_secret: Self::default_client_secret(),
+            issuer: None,
             verify_ssl: Self::default_verify_ssl(),
             jwt_validation_method: JwtValidationMethod::default(),
+            introspection_cache_ttl_seconds: Self::default_introspection_cache_ttl_seconds(),
+            jwks_cache_ttl_seconds: Self::default_jwks_cache_ttl_seconds(),
+            jwks_refresh_interval_seconds: Self::default_jwks_refresh_interval_seconds(),
+            realms: Vec::new(),
         }
     }
 }
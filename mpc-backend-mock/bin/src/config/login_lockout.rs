@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LoginLockoutConfig {
+    /// Lock emails after repeated failed logins
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Failed logins within the failure window which lock an email
+    #[serde(default = "LoginLockoutConfig::default_max_failed_attempts")]
+    pub max_failed_attempts: u32,
+
+    /// How long failed logins are counted, in seconds
+    #[serde(default = "LoginLockoutConfig::default_failure_window_seconds")]
+    pub failure_window_seconds: u64,
+
+    /// How long an email stays locked, in seconds, `0` until an admin
+    /// unlocks it
+    #[serde(default = "LoginLockoutConfig::default_lockout_seconds")]
+    pub lockout_seconds: u64,
+}
+
+impl LoginLockoutConfig {
+    #[inline]
+    pub const fn default_max_failed_attempts() -> u32 { 5 }
+
+    /// Fifteen minutes
+    #[inline]
+    pub const fn default_failure_window_seconds() -> u64 { 15 * 60 }
+
+    /// Fifteen minutes
+    #[inline]
+    pub const fn default_lockout_seconds() -> u64 { 15 * 60 }
+}
+
+impl Default for LoginLockoutConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_failed_attempts: Self::default_max_failed_attempts(),
+            failure_window_seconds: Self::default_failure_window_seconds(),
+            lockout_seconds: Self::default_lockout_seconds(),
+        }
+    }
+}
+
+impl From<LoginLockoutConfig> for mpc_backend_mock_core::config::LoginLockoutConfig {
+    fn from(config: LoginLockoutConfig) -> Self {
+        Self {
+            enable: config.enable,
+            max_failed_attempts: config.max_failed_attempts,
+            failure_window: Duration::from_secs(config.failure_window_seconds),
+            lockout_duration: (config.lockout_seconds > 0)
+                .then(|| Duration::from_secs(config.lockout_seconds)),
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +15,11 @@ pub struct MetricsConfig {
 
     #[serde(default = "MetricsConfig::default_port")]
     pub port: u16,
+
+    /// Pushgateway the metrics are pushed to, alongside the metrics endpoint
+    /// or instead of it when `enable` is false
+    #[serde(default)]
+    pub push_gateway: Option<PushGatewayConfig>,
 }
 
 impl MetricsConfig {
@@ -34,12 +42,45 @@ impl Default for MetricsConfig {
             enable: Self::default_enable(),
             host: Self::default_host(),
             port: Self::default_port(),
+            push_gateway: None,
         }
     }
 }
 
 impl From<MetricsConfig> for mpc_backend_mock_core::config::MetricsConfig {
     fn from(config: MetricsConfig) -> Self {
-        Self { enable: config.enable, listen_address: config.socket_address() }
+        Self {
+            enable: config.enable,
+            listen_address: config.socket_address(),
+            push_gateway: config.push_gateway.map(Into::into),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PushGatewayConfig {
+    /// Base URL of the Pushgateway, e.g. `http://pushgateway:9091`
+    pub endpoint: String,
+
+    /// Value of the `job` grouping label
+    #[serde(default = "PushGatewayConfig::default_job")]
+    pub job: String,
+
+    /// Time between two pushes, in seconds
+    #[serde(default = "PushGatewayConfig::default_interval_seconds")]
+    pub interval_seconds: u64,
+}
+
+impl PushGatewayConfig {
+    #[inline]
+    pub fn default_job() -> String { mpc_backend_mock_core::PROJECT_NAME.to_string() }
+
+    #[inline]
+    pub const fn default_interval_seconds() -> u64 { 15 }
+}
+
+impl From<PushGatewayConfig> for mpc_backend_mock_core::config::MetricsPushGatewayConfig {
+    fn from(PushGatewayConfig { endpoint, job, interval_seconds }: PushGatewayConfig) -> Self {
+        Self { endpoint, job, interval: Duration::from_secs(interval_seconds) }
     }
 }
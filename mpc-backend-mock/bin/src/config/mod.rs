@@ -1,15 +1,31 @@
+mod activation;
 mod bitcoin;
+mod chain_state;
+mod circuit_breaker;
+mod env;
 mod error;
+mod events;
 mod health_check;
+mod key_generation;
 mod key_management_service;
 mod keycloak;
+mod login_lockout;
 mod metrics;
+mod notification;
 mod postgres;
+mod rate_limit;
+mod redis;
+mod secret;
+mod session;
+mod shutdown;
+mod signing;
 mod solana;
+mod validation;
 mod web;
 
 use std::path::{Path, PathBuf};
 
+use mpc_backend_mock_core::{Capabilities, Capability};
 use resolve_path::PathResolveExt;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
@@ -17,18 +33,36 @@ use zeus_cli_common::config::LogConfig;
 
 use self::key_management_service::KeyManagementService;
 pub use self::{
+    activation::ActivationConfig,
     bitcoin::BitcoinConfig,
+    chain_state::ChainStateConfig,
+    circuit_breaker::CircuitBreakerConfig,
     error::Error,
+    events::EventsConfig,
     health_check::HealthCheckConfig,
+    key_generation::KeyGenerationConfig,
     keycloak::{JwtValidationMethod, KeycloakConfig},
+    login_lockout::LoginLockoutConfig,
     metrics::MetricsConfig,
+    notification::NotificationConfig,
     postgres::PostgresConfig,
+    rate_limit::RateLimitConfig,
+    redis::RedisConfig,
+    secret::Secret,
+    session::SessionConfig,
+    shutdown::ShutdownConfig,
+    signing::SigningConfig,
     solana::SolanaConfig,
-    web::WebConfig,
+    validation::{Issue, Severity, ValidationReport},
+    web::{DebugHttpConfig, FaultConfig, FaultInjectionConfig, WebConfig},
 };
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Reject development defaults, such as the default secrets, on startup
+    #[serde(default)]
+    pub production: bool,
+
     #[serde(default)]
     pub log: LogConfig,
 
@@ -48,15 +82,52 @@ pub struct Config {
 
     pub solana: SolanaConfig,
 
+    #[serde(default)]
+    pub chain_state: ChainStateConfig,
+
     pub key_management_service: Option<KeyManagementService>,
 
     #[serde(default)]
     pub keycloak: KeycloakConfig,
+
+    #[serde(default)]
+    pub notification: NotificationConfig,
+
+    #[serde(default)]
+    pub events: EventsConfig,
+
+    #[serde(default)]
+    pub activation: ActivationConfig,
+
+    #[serde(default)]
+    pub session: SessionConfig,
+
+    #[serde(default)]
+    pub login_lockout: LoginLockoutConfig,
+
+    #[serde(default)]
+    pub signing: SigningConfig,
+
+    #[serde(default)]
+    pub key_generation: KeyGenerationConfig,
+
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+
+    /// Caches and rate limits are kept in process when unset
+    pub redis: Option<RedisConfig>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            production: false,
             log: LogConfig::default(),
             web: WebConfig::default(),
             postgres: PostgresConfig::default(),
@@ -64,13 +135,35 @@ impl Default for Config {
             metrics: MetricsConfig::default(),
             bitcoin: BitcoinConfig::devnet(),
             solana: SolanaConfig::devnet(),
+            chain_state: ChainStateConfig::default(),
             key_management_service: None,
             keycloak: KeycloakConfig::default(),
+            notification: NotificationConfig::default(),
+            events: EventsConfig::default(),
+            activation: ActivationConfig::default(),
+            session: SessionConfig::default(),
+            login_lockout: LoginLockoutConfig::default(),
+            signing: SigningConfig::default(),
+            key_generation: KeyGenerationConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            redis: None,
         }
     }
 }
 
 impl Config {
+    /// Subsystems enabled by this configuration
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::new()
+            .with(Capability::Bitcoin, true)
+            .with(Capability::Solana, true)
+            .with(Capability::Kms, self.key_management_service.is_some())
+            .with(Capability::Sessions, self.session.enable)
+    }
+
     #[inline]
     pub fn default_path() -> PathBuf {
         [
@@ -81,13 +174,24 @@ impl Config {
         .collect()
     }
 
+    /// Load the configuration file, overridden by the environment variables
+    /// prefixed with [`env::ENV_PREFIX`]
     #[inline]
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let mut config: Self = {
             let data = std::fs::read_to_string(&path)
                 .context(error::OpenConfigSnafu { filename: path.as_ref().to_path_buf() })?;
 
-            serde_yaml::from_str(&data)
+            let mut value: serde_yaml::Value = serde_yaml::from_str(&data)
+                .context(error::ParseConfigSnafu { filename: path.as_ref().to_path_buf() })?;
+            env::apply_env_overrides(
+                &mut value,
+                std::env::vars_os().filter_map(|(name, value)| {
+                    Some((name.into_string().ok()?, value.into_string().ok()?))
+                }),
+            )?;
+
+            serde_yaml::from_value(value)
                 .context(error::ParseConfigSnafu { filename: path.as_ref().to_path_buf() })?
         };
 
@@ -114,42 +218,58 @@ pub async fn load_server_config(
         health_check,
         bitcoin,
         solana,
+        chain_state,
         keycloak,
+        notification,
+        events,
+        activation,
+        session,
+        login_lockout,
+        signing,
+        key_generation,
+        rate_limit,
+        circuit_breaker,
+        shutdown,
+        redis,
         key_management_service: kms,
+        log,
         ..
     }: Config,
 ) -> Result<mpc_backend_mock_core::config::Config, Error> {
-    let _kms = if let Some(kms) = kms {
+    let kms = if let Some(kms) = kms {
         tracing::info!("Load KMS client");
         Some(kms.load().await?)
     } else {
         None
     };
-    let bitcoin = bitcoin.try_into()?;
+    let kms = kms.as_deref();
+    let postgres = postgres.into_core(kms).await?;
+    let bitcoin = bitcoin.into_core(kms).await?;
+    let health_check = health_check.into_core(kms).await?;
+    let keycloak = keycloak.into_core(kms).await?;
+    let notification = notification.try_into()?;
+    let events = events.try_into()?;
 
     Ok(mpc_backend_mock_core::config::Config {
+        log_filters: log.log_filters,
         web: web.into(),
-        postgres: postgres.into(),
+        postgres,
         metrics: metrics.into(),
-        health_check_listen_address: health_check.socket_address(),
+        health_check,
         bitcoin,
         solana: solana.into(),
-        keycloak: mpc_backend_mock_core::config::KeycloakConfig {
-            server_url: keycloak.server_url,
-            realm: keycloak.realm,
-            client_id: keycloak.client_id,
-            client_secret: keycloak.client_secret,
-            verify_ssl: keycloak.verify_ssl,
-            jwt_validation_method: match keycloak.jwt_validation_method {
-                JwtValidationMethod::Jwks => {
-                    // Map to core config enum
-                    mpc_backend_mock_core::config::JwtValidationMethod::Jwks
-                }
-                JwtValidationMethod::Introspection => {
-                    // Map to core config enum
-                    mpc_backend_mock_core::config::JwtValidationMethod::Introspection
-                }
-            },
-        },
+        chain_state: chain_state.into(),
+        keycloak,
+        notification,
+        events,
+        activation: activation.into(),
+        session: session.into(),
+        login_lockout: login_lockout.into(),
+        signing: signing.into(),
+        key_generation: key_generation.into(),
+        rate_limit: rate_limit.into(),
+        circuit_breaker: circuit_breaker.into(),
+        shutdown: shutdown.into(),
+        redis: redis.map(Into::into),
     })
 }
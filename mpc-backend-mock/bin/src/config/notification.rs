@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Error;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct NotificationConfig {
+    /// Provider sending the notifications, `gmail` when the `gmail` section is
+    /// set and `console` otherwise. `noop` and `capture` never send an email,
+    /// e.g. for staging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<NotificationProvider>,
+
+    /// Send emails via the Gmail API
+    pub gmail: Option<notification::gmail::Config>,
+
+    /// Print emails to the console and write their bodies to files
+    #[serde(default)]
+    pub console: notification::console::Config,
+
+    /// Record rendered emails in the database instead of sending them
+    #[serde(default)]
+    pub capture: notification::capture::Config,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationProvider {
+    Gmail,
+    Console,
+    Log,
+    Noop,
+    Capture,
+}
+
+impl TryFrom<NotificationConfig> for mpc_backend_mock_core::config::NotificationConfig {
+    type Error = Error;
+
+    fn try_from(
+        NotificationConfig { provider, gmail, console, capture }: NotificationConfig,
+    ) -> Result<Self, Self::Error> {
+        let provider = provider.unwrap_or(if gmail.is_some() {
+            NotificationProvider::Gmail
+        } else {
+            NotificationProvider::Console
+        });
+
+        match provider {
+            NotificationProvider::Gmail => gmail
+                .map(Self::Gmail)
+                .ok_or(Error::MissingNotificationProviderConfig { provider: "gmail".to_string() }),
+            NotificationProvider::Console => Ok(Self::Console(console)),
+            NotificationProvider::Log => Ok(Self::Log),
+            NotificationProvider::Noop => Ok(Self::Noop),
+            NotificationProvider::Capture => Ok(Self::Capture(capture)),
+        }
+    }
+}
@@ -1,8 +1,13 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::postgres::PgSslMode;
 
+use crate::{
+    config::{Error, Secret},
+    kms_client::KeyManagementServiceClient,
+};
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PostgresConfig {
     #[serde(default = "PostgresConfig::default_host")]
@@ -18,7 +23,7 @@ pub struct PostgresConfig {
     pub username: String,
 
     #[serde(default = "PostgresConfig::default_password")]
-    pub password: String,
+    pub password: Secret,
 
     #[serde(default = "PostgresConfig::default_role")]
     pub role: Option<String>,
@@ -35,6 +40,43 @@ pub struct PostgresConfig {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub application_name: Option<String>,
+
+    /// How long to wait for other replicas to finish migrating and for the
+    /// migrations to run, in seconds
+    #[serde(default = "PostgresConfig::default_migration_timeout_seconds")]
+    pub migration_timeout_seconds: u64,
+
+    /// Statements running longer are cancelled by Postgres, in milliseconds,
+    /// `0` keeps the `statement_timeout` of the server
+    #[serde(default = "PostgresConfig::default_statement_timeout_milliseconds")]
+    pub statement_timeout_milliseconds: u64,
+
+    /// User queries running longer are logged and counted as slow, in
+    /// milliseconds, `0` disables the log
+    #[serde(default = "PostgresConfig::default_slow_query_threshold_milliseconds")]
+    pub slow_query_threshold_milliseconds: u64,
+
+    /// Retries of transactions aborted by a serialization failure or a
+    /// deadlock
+    #[serde(default)]
+    pub transaction_retry: TransactionRetryConfig,
+
+    /// Hosts of the read replicas serving user lookups and listings, with the
+    /// port and the credentials of the primary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_replicas: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionRetryConfig {
+    /// Attempts of a transaction, the first one included
+    #[serde(default = "TransactionRetryConfig::default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, in milliseconds, doubled for each
+    /// further retry and jittered
+    #[serde(default = "TransactionRetryConfig::default_base_delay_milliseconds")]
+    pub base_delay_milliseconds: u64,
 }
 
 impl PostgresConfig {
@@ -51,7 +93,7 @@ impl PostgresConfig {
     pub fn default_username() -> String { "postgres".to_string() }
 
     #[inline]
-    pub fn default_password() -> String { "postgres".to_string() }
+    pub fn default_password() -> Secret { Secret::plain("postgres") }
 
     #[inline]
     pub const fn default_role() -> Option<String> { None }
@@ -61,6 +103,16 @@ impl PostgresConfig {
 
     #[inline]
     pub const fn default_max_connections() -> u32 { 100 }
+
+    /// Five minutes
+    #[inline]
+    pub const fn default_migration_timeout_seconds() -> u64 { 5 * 60 }
+
+    #[inline]
+    pub const fn default_statement_timeout_milliseconds() -> u64 { 0 }
+
+    #[inline]
+    pub const fn default_slow_query_threshold_milliseconds() -> u64 { 1000 }
 }
 
 impl Default for PostgresConfig {
@@ -75,13 +127,48 @@ impl Default for PostgresConfig {
             ssl_mode: Self::default_ssl_mode(),
             max_connections: Self::default_max_connections(),
             application_name: None,
+            migration_timeout_seconds: Self::default_migration_timeout_seconds(),
+            statement_timeout_milliseconds: Self::default_statement_timeout_milliseconds(),
+            slow_query_threshold_milliseconds: Self::default_slow_query_threshold_milliseconds(),
+            transaction_retry: TransactionRetryConfig::default(),
+            read_replicas: Vec::new(),
         }
     }
 }
 
-impl From<PostgresConfig> for mpc_backend_mock_core::config::PostgresConfig {
+impl TransactionRetryConfig {
+    #[inline]
+    pub const fn default_max_attempts() -> u32 { 3 }
+
+    #[inline]
+    pub const fn default_base_delay_milliseconds() -> u64 { 50 }
+}
+
+impl Default for TransactionRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: Self::default_max_attempts(),
+            base_delay_milliseconds: Self::default_base_delay_milliseconds(),
+        }
+    }
+}
+
+impl From<TransactionRetryConfig> for mpc_backend_mock_core::config::TransactionRetryConfig {
     fn from(
-        PostgresConfig {
+        TransactionRetryConfig { max_attempts, base_delay_milliseconds }: TransactionRetryConfig,
+    ) -> Self {
+        Self { max_attempts, base_delay: Duration::from_millis(base_delay_milliseconds) }
+    }
+}
+
+impl PostgresConfig {
+    /// Convert into the server configuration, decrypting the password with
+    /// `kms` if it is encrypted
+    pub async fn into_core(
+        self,
+        kms: Option<&dyn KeyManagementServiceClient>,
+    ) -> Result<mpc_backend_mock_core::config::PostgresConfig, Error> {
+        let Self {
             host,
             port,
             database,
@@ -91,19 +178,31 @@ impl From<PostgresConfig> for mpc_backend_mock_core::config::PostgresConfig {
             ssl_mode,
             max_connections,
             application_name,
-        }: PostgresConfig,
-    ) -> Self {
-        Self {
+            migration_timeout_seconds,
+            statement_timeout_milliseconds,
+            slow_query_threshold_milliseconds,
+            transaction_retry,
+            read_replicas,
+        } = self;
+
+        Ok(mpc_backend_mock_core::config::PostgresConfig {
             host,
             port,
             database,
             username,
-            password,
+            password: password.reveal(kms).await?,
             role,
             ssl_mode,
             max_connections,
             application_name,
-        }
+            migration_timeout: Duration::from_secs(migration_timeout_seconds),
+            statement_timeout: (statement_timeout_milliseconds > 0)
+                .then(|| Duration::from_millis(statement_timeout_milliseconds)),
+            slow_query_threshold: (slow_query_threshold_milliseconds > 0)
+                .then(|| Duration::from_millis(slow_query_threshold_milliseconds)),
+            transaction_retry: transaction_retry.into(),
+            read_replicas,
+        })
     }
 }
 
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RateLimitConfig {
+    #[serde(default = "RateLimitConfig::default_enable")]
+    pub enable: bool,
+
+    /// Limit per client IP, applied to every `/api` route
+    #[serde(default = "RateLimitConfig::default_per_ip")]
+    pub per_ip: RateLimit,
+
+    /// Limit per authenticated user, applied to protected routes
+    #[serde(default = "RateLimitConfig::default_per_user")]
+    pub per_user: RateLimit,
+
+    /// Limit of `POST /api/v1/auth/login` per client IP, throttles password
+    /// guessing on top of `per_ip`
+    #[serde(default = "RateLimitConfig::default_login_per_ip")]
+    pub login_per_ip: RateLimit,
+}
+
+/// Token bucket refilled at `requests_per_minute`, holding at most `burst`
+/// requests
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RateLimit {
+    /// `0` disables the limit
+    pub requests_per_minute: u32,
+
+    pub burst: u32,
+}
+
+impl RateLimitConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+
+    #[inline]
+    pub const fn default_per_ip() -> RateLimit {
+        RateLimit { requests_per_minute: 600, burst: 100 }
+    }
+
+    #[inline]
+    pub const fn default_per_user() -> RateLimit {
+        RateLimit { requests_per_minute: 300, burst: 50 }
+    }
+
+    #[inline]
+    pub const fn default_login_per_ip() -> RateLimit {
+        RateLimit { requests_per_minute: 20, burst: 10 }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enable: Self::default_enable(),
+            per_ip: Self::default_per_ip(),
+            per_user: Self::default_per_user(),
+            login_per_ip: Self::default_login_per_ip(),
+        }
+    }
+}
+
+impl From<RateLimit> for mpc_backend_mock_core::config::RateLimit {
+    fn from(RateLimit { requests_per_minute, burst }: RateLimit) -> Self {
+        Self { requests_per_minute, burst }
+    }
+}
+
+impl From<RateLimitConfig> for mpc_backend_mock_core::config::RateLimitConfig {
+    fn from(config: RateLimitConfig) -> Self {
+        Self {
+            enable: config.enable,
+            per_ip: config.per_ip.into(),
+            per_user: config.per_user.into(),
+            login_per_ip: config.login_per_ip.into(),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RedisConfig {
+    /// `redis://[:<password>@]<host>[:<port>][/<database>]`
+    pub url: String,
+
+    /// Prepended to every key, so that deployments can share a Redis
+    #[serde(default = "RedisConfig::default_key_prefix")]
+    pub key_prefix: String,
+}
+
+impl RedisConfig {
+    #[inline]
+    pub fn default_key_prefix() -> String { mpc_backend_mock_core::PROJECT_NAME.to_string() }
+}
+
+impl From<RedisConfig> for mpc_backend_mock_core::config::RedisConfig {
+    fn from(RedisConfig { url, key_prefix }: RedisConfig) -> Self { Self { url, key_prefix } }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{
+    config::{error, error::Error},
+    kms_client::KeyManagementServiceClient,
+};
+
+/// Secret given in plain text, or encrypted with the Key Management Service
+/// as `{ kmsCiphertext: "<base64>" }`
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Secret {
+    Plain(String),
+
+    Kms(KmsSecret),
+}
+
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KmsSecret {
+    /// Ciphertext returned by the Key Management Service, base64 encoded
+    #[serde(rename = "kmsCiphertext")]
+    pub kms_ciphertext: String,
+}
+
+impl Secret {
+    #[inline]
+    pub fn plain(secret: impl Into<String>) -> Self { Self::Plain(secret.into()) }
+
+    /// Plain text of the secret, `None` while it is encrypted
+    #[inline]
+    pub fn as_plain(&self) -> Option<&str> {
+        match self {
+            Self::Plain(secret) => Some(secret),
+            Self::Kms(_) => None,
+        }
+    }
+
+    /// Decrypt the secret with `kms` if it is encrypted
+    pub async fn reveal(
+        self,
+        kms: Option<&dyn KeyManagementServiceClient>,
+    ) -> Result<String, Error> {
+        match self {
+            Self::Plain(secret) => Ok(secret),
+            Self::Kms(KmsSecret { kms_ciphertext }) => {
+                let kms = kms.ok_or(Error::KmsClientRequired)?;
+                let plaintext = kms
+                    .decrypt(&kms_ciphertext)
+                    .await
+                    .with_context(|_| error::KmsDecryptSnafu { value: kms_ciphertext.clone() })?;
+
+                String::from_utf8(plaintext)
+                    .map_err(|_| Error::NonUtf8Secret { value: kms_ciphertext })
+            }
+        }
+    }
+}
+
+/// Secrets are never printed, even in debug logs
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plain(_) => f.write_str("Secret(***)"),
+            Self::Kms(KmsSecret { kms_ciphertext }) => {
+                f.debug_struct("Secret").field("kms_ciphertext", kms_ciphertext).finish()
+            }
+        }
+    }
+}
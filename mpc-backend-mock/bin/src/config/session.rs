@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionConfig {
+    /// Let `POST /api/v1/auth/login` set a session cookie on request
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Name of the session cookie
+    #[serde(default = "SessionConfig::default_cookie_name")]
+    pub cookie_name: String,
+
+    /// How long a session lasts after the login, in seconds
+    #[serde(default = "SessionConfig::default_ttl_seconds")]
+    pub ttl_seconds: u64,
+
+    /// Send the cookies over HTTPS only, unset it to test over plain HTTP
+    #[serde(default = "SessionConfig::default_secure")]
+    pub secure: bool,
+}
+
+impl SessionConfig {
+    #[inline]
+    pub fn default_cookie_name() -> String { "mpc_session".to_string() }
+
+    /// Eight hours
+    #[inline]
+    pub const fn default_ttl_seconds() -> u64 { 8 * 60 * 60 }
+
+    #[inline]
+    pub const fn default_secure() -> bool { true }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            cookie_name: Self::default_cookie_name(),
+            ttl_seconds: Self::default_ttl_seconds(),
+            secure: Self::default_secure(),
+        }
+    }
+}
+
+impl From<SessionConfig> for mpc_backend_mock_core::config::SessionConfig {
+    fn from(config: SessionConfig) -> Self {
+        Self {
+            enable: config.enable,
+            cookie_name: config.cookie_name,
+            ttl: Duration::from_secs(config.ttl_seconds),
+            secure: config.secure,
+        }
+    }
+}
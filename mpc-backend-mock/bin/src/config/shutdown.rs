@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShutdownConfig {
+    /// How long in-flight HTTP requests and the notification outbox flush
+    /// may take on shutdown, in seconds
+    #[serde(default = "ShutdownConfig::default_grace_period_seconds")]
+    pub grace_period_seconds: u64,
+}
+
+impl ShutdownConfig {
+    #[inline]
+    pub const fn default_grace_period_seconds() -> u64 { 30 }
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self { Self { grace_period_seconds: Self::default_grace_period_seconds() } }
+}
+
+impl From<ShutdownConfig> for mpc_backend_mock_core::config::ShutdownConfig {
+    fn from(config: ShutdownConfig) -> Self {
+        Self { grace_period: Duration::from_secs(config.grace_period_seconds) }
+    }
+}
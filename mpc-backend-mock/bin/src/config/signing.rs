@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Simulation of the mock MPC signing sessions
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SigningConfig {
+    /// Duration of each simulated signing round, in milliseconds
+    #[serde(default = "SigningConfig::default_round_duration_milliseconds")]
+    pub round_duration_milliseconds: u64,
+
+    /// Share of the sessions failing in a random round, between `0` and `1`
+    #[serde(default)]
+    pub failure_rate: f64,
+}
+
+impl SigningConfig {
+    #[inline]
+    pub const fn default_round_duration_milliseconds() -> u64 { 2_000 }
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self {
+        Self {
+            round_duration_milliseconds: Self::default_round_duration_milliseconds(),
+            failure_rate: 0.0,
+        }
+    }
+}
+
+impl From<SigningConfig> for mpc_backend_mock_core::config::SigningConfig {
+    fn from(SigningConfig { round_duration_milliseconds, failure_rate }: SigningConfig) -> Self {
+        Self { round_duration: Duration::from_millis(round_duration_milliseconds), failure_rate }
+    }
+}
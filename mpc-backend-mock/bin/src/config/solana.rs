@@ -4,16 +4,21 @@ use zpl_rpc_client::Endpoint as SolanaEndpoint;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SolanaConfig {
     pub endpoint: SolanaEndpoint,
+
+    /// Serve a canned chain in process instead of connecting to the RPC
+    /// endpoint
+    #[serde(default)]
+    pub mock: bool,
 }
 
 impl SolanaConfig {
-    pub fn mainnet() -> Self { Self { endpoint: SolanaEndpoint::mainnet() } }
+    pub fn mainnet() -> Self { Self { endpoint: SolanaEndpoint::mainnet(), mock: false } }
 
-    pub fn testnet() -> Self { Self { endpoint: SolanaEndpoint::testnet() } }
+    pub fn testnet() -> Self { Self { endpoint: SolanaEndpoint::testnet(), mock: false } }
 
-    pub fn devnet() -> Self { Self { endpoint: SolanaEndpoint::devnet() } }
+    pub fn devnet() -> Self { Self { endpoint: SolanaEndpoint::devnet(), mock: false } }
 }
 
 impl From<SolanaConfig> for mpc_backend_mock_core::config::SolanaConfig {
-    fn from(source: SolanaConfig) -> Self { Self { endpoint: source.endpoint } }
+    fn from(SolanaConfig { endpoint, mock }: SolanaConfig) -> Self { Self { endpoint, mock } }
 }
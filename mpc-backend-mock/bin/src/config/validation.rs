@@ -0,0 +1,530 @@
+use std::{fmt, net::SocketAddr, str::FromStr};
+
+use eris_bitcoin_ext::WellKnownNetwork as BitcoinNetwork;
+
+use crate::config::{
+    events::EventPublisher, notification::NotificationProvider, CircuitBreakerConfig, Config,
+    DebugHttpConfig, FaultConfig, FaultInjectionConfig, HealthCheckConfig, KeycloakConfig,
+    LoginLockoutConfig, PostgresConfig, RateLimitConfig, Secret, SessionConfig,
+};
+
+/// Problems found in a configuration by [`Config::validate`]
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+
+    /// Path of the field in the configuration file, e.g. `keycloak.realm`
+    pub field: &'static str,
+
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    /// The server would misbehave or refuse to start
+    Error,
+
+    /// Likely a mistake, the server runs anyway
+    Warning,
+}
+
+impl ValidationReport {
+    #[inline]
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error)
+    }
+
+    #[inline]
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn has_errors(&self) -> bool { self.errors().next().is_some() }
+
+    fn error(&mut self, field: &'static str, message: impl Into<String>) {
+        self.issues.push(Issue { severity: Severity::Error, field, message: message.into() });
+    }
+
+    fn warning(&mut self, field: &'static str, message: impl Into<String>) {
+        self.issues.push(Issue { severity: Severity::Warning, field, message: message.into() });
+    }
+
+    fn check_url(&mut self, field: &'static str, url: &str, schemes: &[&str]) {
+        let valid = url.parse::<http::Uri>().is_ok_and(|uri| {
+            uri.host().is_some() && uri.scheme_str().is_some_and(|scheme| schemes.contains(&scheme))
+        });
+        if !valid {
+            self.error(field, format!("`{url}` is not a {} URL", schemes.join(" or ")));
+        }
+    }
+
+    fn check_port(&mut self, field: &'static str, port: u16) {
+        if port == 0 {
+            self.error(field, "port must not be 0");
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}: {}", self.field, self.message)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.errors().map(ToString::to_string).collect::<Vec<_>>();
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+impl Config {
+    /// Check field constraints without connecting to anything
+    ///
+    /// In production mode development defaults, such as the default secrets,
+    /// are errors too.
+    #[must_use]
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        self.validate_listeners(&mut report);
+        for origin in &self.web.cors_allowed_origins {
+            report.check_url("web.cors_allowed_origins", origin, &["http", "https"]);
+        }
+        validate_fault_injection(&self.web.fault_injection, self.production, &mut report);
+        validate_debug_http(&self.web.debug_http, self.production, &mut report);
+        validate_postgres(&self.postgres, self.production, &mut report);
+        validate_keycloak(&self.keycloak, self.production, &mut report);
+        validate_rate_limit(&self.rate_limit, &mut report);
+        validate_circuit_breaker(&self.circuit_breaker, &mut report);
+        validate_session(
+            &self.session,
+            &self.web.cors_allowed_origins,
+            self.production,
+            &mut report,
+        );
+        validate_login_lockout(&self.login_lockout, &mut report);
+
+        validate_health_check(&self.health_check, &mut report);
+
+        if BitcoinNetwork::from_str(&self.bitcoin.network).is_err() {
+            report.error(
+                "bitcoin.network",
+                format!("unknown Bitcoin network `{}`", self.bitcoin.network),
+            );
+        }
+        report.check_url(
+            "bitcoin.rpc_endpoint",
+            &self.bitcoin.rpc_endpoint.to_string(),
+            &["http", "https"],
+        );
+        if let Some(indexer_endpoint) = &self.bitcoin.indexer_endpoint {
+            report.check_url(
+                "bitcoin.indexer_endpoint",
+                &indexer_endpoint.to_string(),
+                &["http", "https"],
+            );
+        }
+        if self
+            .bitcoin
+            .rpc_authentication
+            .as_ref()
+            .and_then(Secret::as_plain)
+            .is_some_and(|auth| !auth.contains(':'))
+        {
+            report.error("bitcoin.rpc_authentication", "expected `USER:PASSWORD`");
+        }
+        if self.production && self.bitcoin.mock {
+            report.error("bitcoin.mock", "the mock chain is not allowed in production mode");
+        }
+        if self.production && self.solana.mock {
+            report.error("solana.mock", "the mock chain is not allowed in production mode");
+        }
+
+        report.check_url("activation.url", &self.activation.url, &["http", "https"]);
+        report.check_url(
+            "activation.email_change_url",
+            &self.activation.email_change_url,
+            &["http", "https"],
+        );
+        if self.activation.token_ttl_seconds == 0 {
+            report.error("activation.token_ttl_seconds", "must be greater than 0");
+        }
+
+        if !(0.0..=1.0).contains(&self.signing.failure_rate) {
+            report.error("signing.failure_rate", "must be between 0 and 1");
+        }
+
+        if self.production && self.key_generation.simulate_failure {
+            report.error(
+                "key_generation.simulate_failure",
+                "simulated failures are not allowed in production mode",
+            );
+        }
+
+        if self.chain_state.refresh_interval_seconds == 0 {
+            report.error("chain_state.refresh_interval_seconds", "must be greater than 0");
+        }
+
+        if self.shutdown.grace_period_seconds == 0 {
+            report.error("shutdown.grace_period_seconds", "must be greater than 0");
+        }
+
+        if let Some(push_gateway) = &self.metrics.push_gateway {
+            report.check_url(
+                "metrics.push_gateway.endpoint",
+                &push_gateway.endpoint,
+                &["http", "https"],
+            );
+            if push_gateway.job.is_empty() {
+                report.error("metrics.push_gateway.job", "must not be empty");
+            }
+            if push_gateway.interval_seconds == 0 {
+                report.error("metrics.push_gateway.interval_seconds", "must be greater than 0");
+            }
+        }
+
+        if let Some(redis) = &self.redis {
+            report.check_url("redis.url", &redis.url, &["redis", "rediss"]);
+        }
+
+        let provider = self.notification.provider.unwrap_or(if self.notification.gmail.is_some() {
+            NotificationProvider::Gmail
+        } else {
+            NotificationProvider::Console
+        });
+        if provider == NotificationProvider::Gmail && self.notification.gmail.is_none() {
+            report
+                .error("notification.gmail", "the `gmail` provider is selected but not configured");
+        }
+        if self.production && provider != NotificationProvider::Gmail {
+            report.warning("notification.provider", "emails are not delivered in production mode");
+        }
+
+        self.validate_events(&mut report);
+
+        report
+    }
+
+    fn validate_events(&self, report: &mut ValidationReport) {
+        let events = &self.events;
+        match events.publisher() {
+            EventPublisher::Nats if events.nats.is_none() => {
+                report.error("events.nats", "the `nats` publisher is selected but not configured");
+            }
+            EventPublisher::Kafka if events.kafka.is_none() => {
+                report
+                    .error("events.kafka", "the `kafka` publisher is selected but not configured");
+            }
+            _ => {}
+        }
+        if events.publisher.is_none() && events.nats.is_some() && events.kafka.is_some() {
+            report.warning(
+                "events.publisher",
+                "both `nats` and `kafka` are configured, events are published to NATS",
+            );
+        }
+
+        if let Some(nats) = &events.nats {
+            report.check_url("events.nats.url", &nats.url, &["nats", "tls"]);
+            if nats.subject.is_empty() {
+                report.error("events.nats.subject", "must not be empty");
+            }
+        }
+        if let Some(kafka) = &events.kafka {
+            if kafka.brokers.is_empty() {
+                report.error("events.kafka.brokers", "must not be empty");
+            }
+            if kafka.topic.is_empty() {
+                report.error("events.kafka.topic", "must not be empty");
+            }
+        }
+        if events.batch_size == 0 {
+            report.error("events.batch_size", "must be greater than 0");
+        }
+    }
+
+    /// Every listener needs a port of its own
+    fn validate_listeners(&self, report: &mut ValidationReport) {
+        let mut listeners = vec![
+            ("web.port", self.web.socket_address()),
+            ("health_check.port", self.health_check.socket_address()),
+        ];
+        if self.metrics.enable {
+            listeners.push(("metrics.port", self.metrics.socket_address()));
+        }
+
+        for (i, (field, address)) in listeners.iter().enumerate() {
+            report.check_port(*field, address.port());
+
+            if let Some((other, _)) =
+                listeners[..i].iter().find(|(_, other)| addresses_overlap(address, other))
+            {
+                report.error(*field, format!("{address} is already used by `{other}`"));
+            }
+        }
+    }
+}
+
+fn validate_health_check(health_check: &HealthCheckConfig, report: &mut ValidationReport) {
+    if health_check.components.is_empty() {
+        report.warning("health_check.components", "the health check always reports serving");
+    }
+    if health_check.bearer_token.as_ref().and_then(Secret::as_plain).is_some_and(str::is_empty) {
+        report.error("health_check.bearer_token", "must not be empty");
+    }
+    if !health_check.host.is_loopback() {
+        if health_check.tls.is_none() && health_check.bearer_token.is_none() {
+            report.warning(
+                "health_check.host",
+                "the health check is reachable beyond localhost without TLS or a bearer token",
+            );
+        } else if health_check.tls.is_none() {
+            report.warning("health_check.tls", "the bearer token is sent in plain text");
+        }
+    }
+}
+
+fn validate_postgres(postgres: &PostgresConfig, production: bool, report: &mut ValidationReport) {
+    if postgres.host.is_empty() {
+        report.error("postgres.host", "must not be empty");
+    }
+    report.check_port("postgres.port", postgres.port);
+    if postgres.max_connections == 0 {
+        report.error("postgres.max_connections", "must be greater than 0");
+    }
+    if postgres.migration_timeout_seconds == 0 {
+        report.error("postgres.migration_timeout_seconds", "must be greater than 0");
+    }
+    if postgres.transaction_retry.max_attempts == 0 {
+        report.error("postgres.transaction_retry.max_attempts", "must be greater than 0");
+    }
+    if postgres.read_replicas.iter().any(String::is_empty) {
+        report.error("postgres.read_replicas", "must not contain an empty host");
+    }
+    if production && postgres.password == PostgresConfig::default_password() {
+        report.error("postgres.password", "the default password is not allowed in production mode");
+    }
+}
+
+fn validate_keycloak(keycloak: &KeycloakConfig, production: bool, report: &mut ValidationReport) {
+    report.check_url("keycloak.server_url", &keycloak.server_url, &["http", "https"]);
+    if keycloak.realm.is_empty() {
+        report.error("keycloak.realm", "must not be empty");
+    }
+    if keycloak.client_id.is_empty() {
+        report.error("keycloak.client_id", "must not be empty");
+    }
+    if keycloak.issuer.as_deref().is_some_and(str::is_empty) {
+        report.error("keycloak.issuer", "must not be empty if set");
+    }
+    for (index, realm) in keycloak.realms.iter().enumerate() {
+        if realm.realm.is_empty() {
+            report
+                .error("keycloak.realms", format!("the realm of entry {index} must not be empty"));
+        } else if realm.realm == keycloak.realm
+            || keycloak.realms[..index].iter().any(|other| other.realm == realm.realm)
+        {
+            report.error("keycloak.realms", format!("realm `{}` is configured twice", realm.realm));
+        }
+        if realm.client_id.is_empty() {
+            report.error(
+                "keycloak.realms",
+                format!("the client_id of realm `{}` must not be empty", realm.realm),
+            );
+        }
+        if realm.issuer.as_deref().is_some_and(str::is_empty) {
+            report.error(
+                "keycloak.realms",
+                format!("the issuer of realm `{}` must not be empty if set", realm.realm),
+            );
+        }
+        if production && realm.client_secret == KeycloakConfig::default_client_secret() {
+            report.error(
+                "keycloak.realms",
+                format!(
+                    "the default secret of realm `{}` is not allowed in production mode",
+                    realm.realm
+                ),
+            );
+        }
+    }
+    if keycloak.jwks_cache_ttl_seconds == 0 {
+        report.error("keycloak.jwks_cache_ttl_seconds", "must be greater than 0");
+    }
+    if keycloak.jwks_refresh_interval_seconds == 0 {
+        report.error("keycloak.jwks_refresh_interval_seconds", "must be greater than 0");
+    } else if keycloak.jwks_refresh_interval_seconds >= keycloak.jwks_cache_ttl_seconds {
+        report.warning(
+            "keycloak.jwks_refresh_interval_seconds",
+            "should be shorter than `jwks_cache_ttl_seconds`, requests may wait for a JWKS fetch",
+        );
+    }
+
+    if production {
+        if keycloak.client_secret == KeycloakConfig::default_client_secret() {
+            report.error(
+                "keycloak.client_secret",
+                "the default secret is not allowed in production mode",
+            );
+        }
+        if !keycloak.verify_ssl {
+            report.error(
+                "keycloak.verify_ssl",
+                "TLS verification cannot be disabled in production mode",
+            );
+        }
+    }
+}
+
+fn validate_rate_limit(rate_limit: &RateLimitConfig, report: &mut ValidationReport) {
+    if !rate_limit.enable {
+        return;
+    }
+    for (field, limit) in [
+        ("rate_limit.per_ip.burst", rate_limit.per_ip),
+        ("rate_limit.per_user.burst", rate_limit.per_user),
+        ("rate_limit.login_per_ip.burst", rate_limit.login_per_ip),
+    ] {
+        if limit.requests_per_minute > 0 && limit.burst == 0 {
+            report.error(field, "must be greater than 0, every request would be rejected");
+        }
+    }
+}
+
+fn validate_circuit_breaker(circuit_breaker: &CircuitBreakerConfig, report: &mut ValidationReport) {
+    if circuit_breaker.failure_threshold == 0 {
+        report.error("circuit_breaker.failure_threshold", "must be greater than 0");
+    }
+    if circuit_breaker.cooldown_seconds == 0 {
+        report.warning(
+            "circuit_breaker.cooldown_seconds",
+            "an open circuit breaker lets the next call through at once",
+        );
+    }
+}
+
+fn validate_session(
+    session: &SessionConfig,
+    cors_allowed_origins: &[String],
+    production: bool,
+    report: &mut ValidationReport,
+) {
+    if !session.enable {
+        return;
+    }
+    // the session cookie is sent cross-origin, any origin could use it
+    if cors_allowed_origins.is_empty() {
+        report.error(
+            "web.cors_allowed_origins",
+            "must list the allowed origins when sessions are enabled",
+        );
+    }
+    if session.ttl_seconds == 0 {
+        report.error("session.ttl_seconds", "must be greater than 0");
+    }
+    // RFC 6265 token, which rules out separators and whitespace
+    if session.cookie_name.is_empty()
+        || !session
+            .cookie_name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+    {
+        report.error("session.cookie_name", "must be a non-empty cookie name without separators");
+    }
+    if !session.secure {
+        if production {
+            report.error("session.secure", "insecure cookies are not allowed in production mode");
+        } else {
+            report.warning("session.secure", "the session cookie is sent over plain HTTP");
+        }
+    }
+}
+
+fn validate_login_lockout(login_lockout: &LoginLockoutConfig, report: &mut ValidationReport) {
+    if !login_lockout.enable {
+        return;
+    }
+    if login_lockout.max_failed_attempts == 0 {
+        report.error("login_lockout.max_failed_attempts", "must be greater than 0");
+    }
+    if login_lockout.failure_window_seconds == 0 {
+        report.error("login_lockout.failure_window_seconds", "must be greater than 0");
+    }
+    if login_lockout.lockout_seconds == 0 {
+        report.warning(
+            "login_lockout.lockout_seconds",
+            "locked emails stay locked until an admin unlocks them",
+        );
+    }
+}
+
+fn validate_fault_injection(
+    fault_injection: &FaultInjectionConfig,
+    production: bool,
+    report: &mut ValidationReport,
+) {
+    if production && fault_injection.enable {
+        report.error(
+            "web.fault_injection.enable",
+            "fault injection is not allowed in production mode",
+        );
+    }
+    for rule in &fault_injection.rules {
+        if !rule.route.starts_with('/') {
+            report.error(
+                "web.fault_injection.rules",
+                format!("route `{}` must start with `/`", rule.route),
+            );
+        }
+        if !(0.0..=100.0).contains(&rule.percentage) {
+            report.error("web.fault_injection.rules", "percentage must be between 0 and 100");
+        }
+        if let FaultConfig::Error { status } = rule.fault {
+            if !(500..=599).contains(&status) {
+                report.error(
+                    "web.fault_injection.rules",
+                    format!("status {status} of an injected error must be a 5xx status"),
+                );
+            }
+        }
+    }
+}
+
+fn validate_debug_http(
+    debug_http: &DebugHttpConfig,
+    production: bool,
+    report: &mut ValidationReport,
+) {
+    if production && debug_http.enable {
+        report.error(
+            "web.debug_http.enable",
+            "logging request and response bodies is not allowed in production mode",
+        );
+    }
+    for route in &debug_http.routes {
+        if !route.starts_with('/') {
+            report.error("web.debug_http.routes", format!("route `{route}` must start with `/`"));
+        }
+    }
+    if debug_http.enable && debug_http.capacity == 0 {
+        report.error("web.debug_http.capacity", "must be greater than 0");
+    }
+}
+
+/// Whether two listeners would bind the same port, an unspecified address
+/// binds every interface
+fn addresses_overlap(a: &SocketAddr, b: &SocketAddr) -> bool {
+    a.port() == b.port() && (a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified())
+}
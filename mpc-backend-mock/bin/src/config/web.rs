@@ -1,5 +1,9 @@
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
 
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -9,6 +13,99 @@ pub struct WebConfig {
 
     #[serde(default = "WebConfig::default_port")]
     pub port: u16,
+
+    /// Proxies allowed to set `X-Forwarded-For` / `X-Real-IP`, in CIDR
+    /// notation
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNet>,
+
+    #[serde(default)]
+    pub admin_access: IpAccessListConfig,
+
+    /// Origins allowed to call the API from a browser, e.g.
+    /// `https://app.example.com`, any origin when empty
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// Serve Swagger UI of the API under `/docs`
+    #[serde(default = "WebConfig::default_docs_ui")]
+    pub docs_ui: bool,
+
+    /// Serve the GraphQL API over the user and transaction models under
+    /// `/api/graphql`, alongside the REST API
+    #[serde(default)]
+    pub graphql: bool,
+
+    /// Faults injected into API responses, for frontend and SDK resilience
+    /// testing
+    #[serde(default)]
+    pub fault_injection: FaultInjectionConfig,
+
+    /// Log the sanitized request and response bodies of chosen routes, to
+    /// reproduce frontend bug reports
+    #[serde(default)]
+    pub debug_http: DebugHttpConfig,
+}
+
+/// CIDR allow/deny list for `/api/v1/admin/*`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IpAccessListConfig {
+    #[serde(default = "IpAccessListConfig::default_allow")]
+    pub allow: Vec<IpNet>,
+
+    #[serde(default)]
+    pub deny: Vec<IpNet>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct FaultInjectionConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub rules: Vec<FaultRuleConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DebugHttpConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Logged request paths and the paths under them, e.g. `/api/v1/users`,
+    /// every path when empty
+    #[serde(default)]
+    pub routes: Vec<String>,
+
+    /// JSON fields and query parameters whose values are redacted
+    #[serde(default = "DebugHttpConfig::default_redacted_fields")]
+    pub redacted_fields: Vec<String>,
+
+    /// Exchanges kept in memory
+    #[serde(default = "DebugHttpConfig::default_capacity")]
+    pub capacity: usize,
+
+    #[serde(default = "DebugHttpConfig::default_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+/// Fault injected into `percentage` percent of the requests whose path is
+/// `route` or under it, e.g. `/api/v1/bitcoin`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FaultRuleConfig {
+    pub route: String,
+
+    pub percentage: f64,
+
+    pub fault: FaultConfig,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FaultConfig {
+    Latency { latency_ms: u64 },
+    Error { status: u16 },
+    DropConnection,
+    MalformedJson,
 }
 
 impl WebConfig {
@@ -20,12 +117,135 @@ impl WebConfig {
 
     #[inline]
     pub const fn default_port() -> u16 { mpc_backend_mock_core::DEFAULT_WEB_PORT }
+
+    #[inline]
+    pub const fn default_docs_ui() -> bool { true }
 }
 
 impl Default for WebConfig {
-    fn default() -> Self { Self { host: Self::default_host(), port: Self::default_port() } }
+    fn default() -> Self {
+        Self {
+            host: Self::default_host(),
+            port: Self::default_port(),
+            trusted_proxies: Vec::new(),
+            admin_access: IpAccessListConfig::default(),
+            cors_allowed_origins: Vec::new(),
+            docs_ui: Self::default_docs_ui(),
+            graphql: false,
+            fault_injection: FaultInjectionConfig::default(),
+            debug_http: DebugHttpConfig::default(),
+        }
+    }
+}
+
+impl DebugHttpConfig {
+    /// Credentials of the login, activation, password, session and webhook
+    /// requests and responses
+    #[inline]
+    pub fn default_redacted_fields() -> Vec<String> {
+        [
+            "password",
+            "current_password",
+            "new_password",
+            "token",
+            "access_token",
+            "refresh_token",
+            "id_token",
+            "secret",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+
+    #[inline]
+    pub const fn default_capacity() -> usize { 200 }
+
+    #[inline]
+    pub const fn default_max_body_bytes() -> usize { 64 * 1024 }
+}
+
+impl Default for DebugHttpConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            routes: Vec::new(),
+            redacted_fields: Self::default_redacted_fields(),
+            capacity: Self::default_capacity(),
+            max_body_bytes: Self::default_max_body_bytes(),
+        }
+    }
+}
+
+impl IpAccessListConfig {
+    /// Only loopback addresses are allowed by default
+    #[inline]
+    pub fn default_allow() -> Vec<IpNet> {
+        vec![
+            IpNet::from(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            IpNet::from(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+        ]
+    }
+}
+
+impl Default for IpAccessListConfig {
+    fn default() -> Self { Self { allow: Self::default_allow(), deny: Vec::new() } }
 }
 
 impl From<WebConfig> for mpc_backend_mock_core::config::WebConfig {
-    fn from(config: WebConfig) -> Self { Self { listen_address: config.socket_address() } }
+    fn from(config: WebConfig) -> Self {
+        Self {
+            listen_address: config.socket_address(),
+            trusted_proxies: config.trusted_proxies,
+            admin_access: mpc_backend_mock_core::config::IpAccessList {
+                allow: config.admin_access.allow,
+                deny: config.admin_access.deny,
+            },
+            cors_allowed_origins: config.cors_allowed_origins,
+            docs_ui: config.docs_ui,
+            graphql: config.graphql,
+            fault_injection: config.fault_injection.into(),
+            debug_http: config.debug_http.into(),
+        }
+    }
+}
+
+impl From<DebugHttpConfig> for mpc_backend_mock_core::config::DebugHttpConfig {
+    fn from(config: DebugHttpConfig) -> Self {
+        Self {
+            enable: config.enable,
+            routes: config.routes,
+            redacted_fields: config.redacted_fields,
+            capacity: config.capacity,
+            max_body_size: config.max_body_bytes,
+        }
+    }
+}
+
+impl From<FaultInjectionConfig> for mpc_backend_mock_core::config::FaultInjectionConfig {
+    fn from(config: FaultInjectionConfig) -> Self {
+        Self {
+            enable: config.enable,
+            rules: config
+                .rules
+                .into_iter()
+                .map(|rule| mpc_backend_mock_core::config::FaultRule {
+                    route: rule.route,
+                    percentage: rule.percentage,
+                    fault: rule.fault.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<FaultConfig> for mpc_backend_mock_core::config::Fault {
+    fn from(config: FaultConfig) -> Self {
+        match config {
+            FaultConfig::Latency { latency_ms } => Self::Latency(Duration::from_millis(latency_ms)),
+            FaultConfig::Error { status } => Self::Error(status),
+            FaultConfig::DropConnection => Self::DropConnection,
+            FaultConfig::MalformedJson => Self::MalformedJson,
+        }
+    }
 }
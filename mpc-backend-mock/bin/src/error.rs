@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use snafu::Snafu;
 
 use crate::config;
@@ -17,6 +19,20 @@ pub enum Error {
 
     #[snafu(display("{source}"))]
     Config { source: config::Error },
+
+    #[snafu(display(
+        "Configuration check failed with {errors} error(s) and {failed_probes} failed probe(s)"
+    ))]
+    CheckConfig { errors: usize, failed_probes: usize },
+
+    #[snafu(display("Could not write TypeScript types to {}, error: {source}", path.display()))]
+    WriteTypeScriptClient { path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not read fixtures from {}, error: {source}", path.display()))]
+    ReadFixtures { path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not parse fixtures of {}, error: {source}", path.display()))]
+    ParseFixtures { path: PathBuf, source: serde_yaml::Error },
 }
 
 impl From<config::Error> for Error {
@@ -35,8 +51,12 @@ impl CommandError for Error {
     fn exit_code(&self) -> exitcode::ExitCode {
         match self {
             Self::Application { .. } => exitcode::SOFTWARE,
-            Self::Config { .. } => exitcode::CONFIG,
+            Self::Config { .. } | Self::CheckConfig { errors: 1.., .. } => exitcode::CONFIG,
+            Self::CheckConfig { .. } => exitcode::UNAVAILABLE,
             Self::InitializeTokioRuntime { .. } => exitcode::IOERR,
+            Self::WriteTypeScriptClient { .. } => exitcode::CANTCREAT,
+            Self::ReadFixtures { .. } => exitcode::NOINPUT,
+            Self::ParseFixtures { .. } => exitcode::DATAERR,
         }
     }
 }
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_kms::{error::DisplayErrorContext, primitives::Blob};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use snafu::ResultExt;
+
+use crate::kms_client::{
+    error,
+    error::{Error, Result},
+    KeyManagementServiceClient,
+};
+
+#[derive(Clone)]
+pub struct Client {
+    kms: aws_sdk_kms::Client,
+    key_id: Option<String>,
+}
+
+impl Client {
+    /// Create a new client, credentials come from the default AWS provider
+    /// chain: environment, profile, web identity or instance role.
+    ///
+    /// `key_id` is only needed for ciphertexts encrypted with an asymmetric
+    /// key, symmetric ciphertexts name their key.
+    pub async fn new(region: String, key_id: Option<String>) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region))
+            .load()
+            .await;
+
+        Self { kms: aws_sdk_kms::Client::new(&config), key_id }
+    }
+}
+
+#[async_trait]
+impl KeyManagementServiceClient for Client {
+    async fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>> {
+        let ciphertext = BASE64_STANDARD.decode(ciphertext).context(error::Basse64DecodeSnafu)?;
+
+        let output = self
+            .kms
+            .decrypt()
+            .ciphertext_blob(Blob::new(ciphertext))
+            .set_key_id(self.key_id.clone())
+            .send()
+            .await
+            .map_err(|err| Error::AwsDecrypt {
+                location: snafu::location!(),
+                message: DisplayErrorContext(&err).to_string(),
+            })?;
+
+        output
+            .plaintext
+            .map(Blob::into_inner)
+            .ok_or(Error::MissingPlaintext { location: snafu::location!() })
+    }
+}
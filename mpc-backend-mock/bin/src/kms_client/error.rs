@@ -14,6 +14,13 @@ pub enum Error {
         source: google_cloud_auth::error::Error,
     },
 
+    #[snafu(display("Failed to fetch access token: {source}, location: {location}"))]
+    FetchToken {
+        #[snafu(implicit)]
+        location: Location,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[snafu(display("Unexpected HTTP response: {source}, location: {location}"))]
     UnexpectedHttpResponse {
         #[snafu(implicit)]
@@ -56,6 +63,19 @@ pub enum Error {
         source: serde_json::Error,
     },
 
+    #[snafu(display("Failed to decrypt with AWS KMS: {message}, location: {location}"))]
+    AwsDecrypt {
+        #[snafu(implicit)]
+        location: Location,
+        message: String,
+    },
+
+    #[snafu(display("Decrypt response has no plaintext, location: {location}"))]
+    MissingPlaintext {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display(
         "Failed to deserialize JSON from HTTP response, error: {source}, location: {location}"
     ))]
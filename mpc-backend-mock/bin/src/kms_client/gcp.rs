@@ -1,9 +1,14 @@
-use std::fmt;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use base64::{prelude::BASE64_STANDARD, Engine};
 use google_cloud_token::TokenSourceProvider;
 use snafu::ResultExt;
+use tokio::sync::Mutex;
 
 use crate::kms_client::{
     error,
@@ -13,11 +18,16 @@ use crate::kms_client::{
 
 const SCOPES: [&str; 1] = ["https://www.googleapis.com/auth/cloud-platform"];
 
+/// Access tokens of Google Cloud are valid for an hour, a cached token is
+/// refreshed well before it expires
+const TOKEN_TTL: Duration = Duration::from_secs(45 * 60);
+
 #[derive(Clone)]
 pub struct Client {
     http: reqwest::Client,
     endpoint: http::Uri,
-    token: String,
+    token_source: Arc<dyn google_cloud_token::TokenSource>,
+    token: Arc<Mutex<Option<CachedToken>>>,
     project_id: String,
     location: String,
     key_ring: String,
@@ -46,15 +56,17 @@ impl Client {
         key_ring: String,
         crypto_key: String,
     ) -> Result<Self> {
-        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config())
-            .await
-            .context(error::InitializeTokenSourceSnafu)?
-            .token_source();
+        let token_source =
+            google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config())
+                .await
+                .context(error::InitializeTokenSourceSnafu)?
+                .token_source();
 
         Ok(Self {
             http: http_client,
             endpoint: "https://cloudkms.googleapis.com".parse().expect("valid uri"),
-            token: ts.token().await.expect("valid token"),
+            token_source,
+            token: Arc::new(Mutex::new(None)),
             project_id,
             location,
             key_ring,
@@ -62,6 +74,29 @@ impl Client {
         })
     }
 
+    /// Access token for the `Authorization` header, fetched from the token
+    /// source when the cached one is stale or `refresh` is set
+    ///
+    /// # Errors
+    async fn token(&self, refresh: bool) -> Result<String> {
+        let mut cached = self.token.lock().await;
+        if let Some(CachedToken { ref value, fetched_at }) = *cached {
+            if !refresh && fetched_at.elapsed() < TOKEN_TTL {
+                return Ok(value.clone());
+            }
+        }
+
+        tracing::debug!("Fetch Google Cloud access token");
+        let value = self.token_source.token().await.context(error::FetchTokenSnafu)?;
+        *cached = Some(CachedToken { value: value.clone(), fetched_at: Instant::now() });
+        drop(cached);
+
+        Ok(value)
+    }
+
+    /// Send the request, retried once with a refreshed token if the cached one
+    /// was rejected
+    ///
     /// # Errors
     async fn request(
         &self,
@@ -72,27 +107,39 @@ impl Client {
     ) -> Result<serde_json::Value> {
         let path = path.to_string();
 
-        let mut reqeust_builder = match method {
-            http::Method::POST => {
-                let url = http::uri::Builder::from(self.endpoint.clone())
-                    .path_and_query(&path)
-                    .build()
-                    .expect("valid url")
-                    .to_string();
-
-                if let Some(ref body) = body {
-                    self.http.post(url).json(body)
-                } else {
-                    self.http.post(url).form(params)
+        let mut refresh = false;
+        let maybe_response = loop {
+            let reqeust_builder = match method {
+                http::Method::POST => {
+                    let url = http::uri::Builder::from(self.endpoint.clone())
+                        .path_and_query(&path)
+                        .build()
+                        .expect("valid url")
+                        .to_string();
+
+                    if let Some(ref body) = body {
+                        self.http.post(url).json(body)
+                    } else {
+                        self.http.post(url).form(params)
+                    }
                 }
+                _ => unreachable!("unsupported http method"),
+            };
+
+            let maybe_response = reqeust_builder
+                .header("Authorization", self.token(refresh).await?)
+                .send()
+                .await
+                .with_context(|_| error::UnexpectedHttpResponseSnafu)?;
+
+            if maybe_response.status() == reqwest::StatusCode::UNAUTHORIZED && !refresh {
+                tracing::warn!("Google Cloud access token rejected, refreshing it");
+                refresh = true;
+                continue;
             }
-            _ => unreachable!("unsupported http method"),
-        };
-
-        reqeust_builder = reqeust_builder.header("Authorization", self.token.clone());
 
-        let maybe_response =
-            reqeust_builder.send().await.with_context(|_| error::UnexpectedHttpResponseSnafu)?;
+            break maybe_response;
+        };
 
         let status = maybe_response.status();
 
@@ -123,7 +170,6 @@ impl Client {
     }
 }
 
-#[allow(unused)]
 #[async_trait]
 impl KeyManagementServiceClient for Client {
     async fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>> {
@@ -151,3 +197,9 @@ impl KeyManagementServiceClient for Client {
         BASE64_STANDARD.decode(plaintext).context(error::Basse64DecodeSnafu)
     }
 }
+
+#[derive(Clone)]
+struct CachedToken {
+    value: String,
+    fetched_at: Instant,
+}
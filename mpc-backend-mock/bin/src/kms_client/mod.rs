@@ -1,11 +1,14 @@
+pub mod aws;
 mod error;
 pub mod gcp;
+pub mod vault;
 
 use async_trait::async_trait;
 pub use error::{Error, Result};
 
-#[allow(unused)]
+/// Decrypts secrets encrypted with a cloud key management service, see
+/// `KeyManagementService` in the configuration for the providers
 #[async_trait]
-pub trait KeyManagementServiceClient {
+pub trait KeyManagementServiceClient: Send + Sync {
     async fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>>;
 }
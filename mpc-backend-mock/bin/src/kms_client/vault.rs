@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use snafu::ResultExt;
+
+use crate::kms_client::{
+    error,
+    error::{Error, Result},
+    KeyManagementServiceClient,
+};
+
+/// Client of the HashiCorp Vault transit secrets engine
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    address: String,
+    token: String,
+    namespace: Option<String>,
+    mount: String,
+    key: String,
+}
+
+impl Client {
+    /// Create a new client with the default http client.
+    ///
+    /// `mount` is the path the transit engine is mounted at, `key` the name of
+    /// the encryption key.
+    #[must_use]
+    pub fn new(
+        address: String,
+        token: String,
+        namespace: Option<String>,
+        mount: String,
+        key: String,
+    ) -> Self {
+        Self { http: reqwest::Client::new(), address, token, namespace, mount, key }
+    }
+}
+
+#[async_trait]
+impl KeyManagementServiceClient for Client {
+    /// Decrypt a `vault:v<version>:<base64>` ciphertext
+    async fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>> {
+        let url = format!(
+            "{}/v1/{}/decrypt/{}",
+            self.address.trim_end_matches('/'),
+            self.mount.trim_matches('/'),
+            self.key
+        );
+
+        let mut request = self
+            .http
+            .post(url)
+            .header("X-Vault-Token", &self.token)
+            .json(&serde_json::json!({ "ciphertext": ciphertext }));
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+
+        let response = request.send().await.with_context(|_| error::UnexpectedHttpResponseSnafu)?;
+
+        let status = response.status();
+        if status.is_client_error() {
+            return Err(Error::OtherClientSide {
+                status,
+                location: snafu::location!(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+        if status.is_server_error() {
+            return Err(Error::ServerSide {
+                status,
+                location: snafu::location!(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        let body = response.text().await.with_context(|_| error::UnexpectedHttpResponseSnafu)?;
+        let response: serde_json::Value =
+            serde_json::from_str(&body).with_context(|_| error::DeserializeJsonResponseSnafu)?;
+
+        let Some(plaintext) = response.pointer("/data/plaintext").and_then(|val| val.as_str())
+        else {
+            return Err(error::UnexpectedJsonResponseSnafu {
+                operation: "cannot parse plaintext".to_string(),
+                response,
+            }
+            .build());
+        };
+
+        BASE64_STANDARD.decode(plaintext).context(error::Basse64DecodeSnafu)
+    }
+}
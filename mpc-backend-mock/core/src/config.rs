@@ -1,5 +1,10 @@
-use std::{fmt::Debug, net::SocketAddr};
+use std::{
+    fmt::Debug,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
+use ipnet::IpNet;
 use sqlx::postgres::PgSslMode;
 
 /// JWT validation method
@@ -14,19 +19,104 @@ pub enum JwtValidationMethod {
 
 #[derive(Clone, Debug)]
 pub struct Config {
+    /// Filters of the log, e.g. `info,sqlx=warn`
+    pub log_filters: String,
+
     pub web: WebConfig,
 
     pub postgres: PostgresConfig,
 
     pub metrics: MetricsConfig,
 
-    pub health_check_listen_address: SocketAddr,
+    pub health_check: HealthCheckConfig,
 
     pub bitcoin: BitcoinConfig,
 
     pub solana: SolanaConfig,
 
+    pub chain_state: ChainStateConfig,
+
     pub keycloak: KeycloakConfig,
+
+    pub notification: NotificationConfig,
+
+    pub events: EventsConfig,
+
+    pub activation: ActivationConfig,
+
+    pub session: SessionConfig,
+
+    pub login_lockout: LoginLockoutConfig,
+
+    pub signing: SigningConfig,
+
+    pub key_generation: KeyGenerationConfig,
+
+    pub rate_limit: RateLimitConfig,
+
+    pub circuit_breaker: CircuitBreakerConfig,
+
+    pub shutdown: ShutdownConfig,
+
+    /// Caches and rate limits are kept in process when unset
+    pub redis: Option<RedisConfig>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthCheckConfig {
+    pub listen_address: SocketAddr,
+
+    /// Dependencies the gRPC health check reports on
+    pub components: Vec<HealthCheckComponent>,
+
+    /// Serve over TLS, plain text when unset
+    pub tls: Option<HealthCheckTlsConfig>,
+
+    /// Token callers must send as `authorization: Bearer <token>`
+    pub bearer_token: Option<String>,
+}
+
+/// PEM encoded identity of the gRPC health check server
+#[derive(Clone)]
+pub struct HealthCheckTlsConfig {
+    pub certificate: Vec<u8>,
+
+    pub private_key: Vec<u8>,
+
+    /// Clients must present a certificate signed by one of these CAs when set
+    pub client_ca_certificate: Option<Vec<u8>>,
+}
+
+impl Debug for HealthCheckTlsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthCheckTlsConfig")
+            .field("verify_client", &self.client_ca_certificate.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Dependency checked by the gRPC health check
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HealthCheckComponent {
+    Postgres,
+    Bitcoin,
+    Keycloak,
+    Solana,
+}
+
+impl HealthCheckComponent {
+    pub const ALL: [Self; 4] = [Self::Postgres, Self::Bitcoin, Self::Keycloak, Self::Solana];
+
+    /// Service name of the component in health check requests
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Bitcoin => "bitcoin",
+            Self::Keycloak => "keycloak",
+            Self::Solana => "solana",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -35,8 +125,267 @@ pub struct KeycloakConfig {
     pub realm: String,
     pub client_id: String,
     pub client_secret: String,
+
+    /// `iss` claim of the tokens of `realm`
+    pub issuer: String,
+
     pub verify_ssl: bool,
     pub jwt_validation_method: JwtValidationMethod,
+
+    /// How long the claims of an active token are reused instead of
+    /// introspecting it again, zero disables the cache
+    pub introspection_cache_ttl: Duration,
+
+    /// How long fetched JWKS are served from the cache
+    pub jwks_cache_ttl: Duration,
+
+    /// Time between two background JWKS refreshes
+    pub jwks_refresh_interval: Duration,
+
+    /// Realms of the same server whose tokens are accepted besides those of
+    /// `realm`, which stays the one users log in to and are created in
+    pub additional_realms: Vec<KeycloakRealmConfig>,
+}
+
+impl KeycloakConfig {
+    /// Configuration of the backend client in `realm` of the same server
+    #[must_use]
+    pub fn for_realm(&self, realm: &KeycloakRealmConfig) -> Self {
+        Self {
+            realm: realm.realm.clone(),
+            client_id: realm.client_id.clone(),
+            client_secret: realm.client_secret.clone(),
+            issuer: realm.issuer.clone(),
+            additional_realms: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Keycloak realm whose tokens are accepted besides the primary one
+#[derive(Clone, Debug)]
+pub struct KeycloakRealmConfig {
+    pub realm: String,
+
+    /// Client of the backend in the realm, introspects its tokens
+    pub client_id: String,
+    pub client_secret: String,
+
+    /// `iss` claim of the tokens of the realm
+    pub issuer: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum NotificationConfig {
+    /// Send emails via the Gmail API
+    Gmail(notification::gmail::Config),
+
+    /// Print emails to the console, no mail provider is contacted
+    Console(notification::console::Config),
+
+    /// Only log notifications
+    Log,
+
+    /// Drop notifications without logging them
+    Noop,
+
+    /// Render emails and record them in the `captured_notifications` table
+    /// instead of sending them
+    Capture(notification::capture::Config),
+}
+
+#[derive(Clone, Debug)]
+pub struct EventsConfig {
+    pub publisher: EventPublisherConfig,
+
+    /// Events published per run of the publisher
+    pub batch_size: u32,
+}
+
+#[derive(Clone, Debug)]
+pub enum EventPublisherConfig {
+    /// Domain events are not written to the outbox
+    Disabled,
+
+    /// Only log the events
+    Log,
+
+    /// Publish to NATS JetStream
+    Nats(events::nats::Config),
+
+    /// Publish to a Kafka topic
+    Kafka(events::kafka::Config),
+}
+
+impl EventPublisherConfig {
+    /// Whether domain events are recorded and published
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool { !matches!(self, Self::Disabled) }
+
+    /// Name the publisher tracks its offset in the outbox under
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Log => "log",
+            Self::Nats(_) => "nats",
+            Self::Kafka(_) => "kafka",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ActivationConfig {
+    /// Page the activation link points to, the token is appended as the
+    /// `token` query parameter
+    pub url: String,
+
+    /// Page the link confirming a new email points to, the token is appended
+    /// as the `token` query parameter
+    pub email_change_url: String,
+
+    /// How long an activation or email change token can be used
+    pub token_ttl: Duration,
+}
+
+/// Cookie sessions of `POST /api/v1/auth/login`, for browsers which should
+/// not hold the access token
+#[derive(Clone, Debug)]
+pub struct SessionConfig {
+    pub enable: bool,
+
+    /// Name of the HttpOnly session cookie, the CSRF token is set in the
+    /// cookie `<cookie_name>_csrf`
+    pub cookie_name: String,
+
+    /// How long a session lasts after the login
+    pub ttl: Duration,
+
+    /// Send the cookies over HTTPS only
+    pub secure: bool,
+}
+
+impl SessionConfig {
+    /// Name of the cookie holding the CSRF token, readable by the scripts of
+    /// the page
+    #[must_use]
+    pub fn csrf_cookie_name(&self) -> String { format!("{}_csrf", self.cookie_name) }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            cookie_name: "mpc_session".to_string(),
+            ttl: Duration::from_secs(8 * 60 * 60),
+            secure: true,
+        }
+    }
+}
+
+/// Lockout of emails after repeated failed logins of `POST /api/v1/auth/login`,
+/// their Keycloak accounts are disabled while locked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoginLockoutConfig {
+    pub enable: bool,
+
+    /// Failed logins within `failure_window` which lock an email
+    pub max_failed_attempts: u32,
+
+    /// How long failed logins are counted, the count starts over afterwards
+    pub failure_window: Duration,
+
+    /// How long an email stays locked, `None` until an admin unlocks it
+    pub lockout_duration: Option<Duration>,
+}
+
+impl Default for LoginLockoutConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_failed_attempts: 5,
+            failure_window: Duration::from_secs(15 * 60),
+            lockout_duration: Some(Duration::from_secs(15 * 60)),
+        }
+    }
+}
+
+/// Simulation of the mock MPC signing sessions of `POST
+/// /api/v1/signing/sessions`
+#[derive(Clone, Copy, Debug)]
+pub struct SigningConfig {
+    /// How long each simulated signing round lasts
+    pub round_duration: Duration,
+
+    /// Share of the sessions failing in a random round, between `0` and `1`
+    pub failure_rate: f64,
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self { Self { round_duration: Duration::from_secs(2), failure_rate: 0.0 } }
+}
+
+/// Simulation of the key generation of `POST /api/v1/keys`, for testing
+/// frontends against a slow or failing ceremony
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyGenerationConfig {
+    /// Delay before a key is generated
+    pub simulate_delay: Duration,
+
+    /// Fail every key generation
+    pub simulate_failure: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitConfig {
+    pub enable: bool,
+
+    /// Limit per client IP
+    pub per_ip: RateLimit,
+
+    /// Limit per authenticated user
+    pub per_user: RateLimit,
+
+    /// Limit of the logins per client IP, on top of `per_ip`
+    pub login_per_ip: RateLimit,
+}
+
+/// Token bucket refilled at `requests_per_minute`, holding at most `burst`
+/// requests
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// `0` disables the limit
+    pub requests_per_minute: u32,
+
+    pub burst: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShutdownConfig {
+    /// How long in-flight HTTP requests and the notification outbox flush may
+    /// take on shutdown
+    pub grace_period: Duration,
+}
+
+/// Circuit breaker of each external dependency, opened after
+/// `failure_threshold` consecutive failed calls for `cooldown`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self { Self { failure_threshold: 5, cooldown: Duration::from_secs(30) } }
+}
+
+#[derive(Clone, Debug)]
+pub struct RedisConfig {
+    pub url: String,
+
+    /// Prepended to every key
+    pub key_prefix: String,
 }
 
 #[derive(Clone, Debug)]
@@ -44,16 +393,129 @@ pub struct BitcoinConfig {
     pub endpoint: eris_bitcoin_rpc_client::RpcEndpoint,
 
     pub block_number_to_confirm: u64,
+
+    /// How long fee estimates and mempool information are served from the
+    /// cache, zero disables the cache
+    pub fee_cache_ttl: Duration,
+
+    /// Serve canned data instead of connecting to `endpoint`
+    pub mock: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct SolanaConfig {
     pub endpoint: zpl_rpc_client::Endpoint,
+
+    /// Serve canned data instead of connecting to `endpoint`
+    pub mock: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChainStateConfig {
+    /// Time between two reads of the Bitcoin block height and the Solana slot
+    /// served from the cache
+    pub refresh_interval: Duration,
 }
 
 #[derive(Clone, Debug)]
 pub struct WebConfig {
     pub listen_address: SocketAddr,
+
+    /// Proxies whose `X-Forwarded-For` / `X-Real-IP` headers are trusted when
+    /// resolving the client IP
+    pub trusted_proxies: Vec<IpNet>,
+
+    /// Networks allowed to reach `/api/v1/admin/*`
+    pub admin_access: IpAccessList,
+
+    /// Origins allowed to call the API from a browser, any origin when empty
+    pub cors_allowed_origins: Vec<String>,
+
+    /// Serve Swagger UI under `/docs`
+    pub docs_ui: bool,
+
+    /// Serve the GraphQL API under `/api/graphql`
+    pub graphql: bool,
+
+    /// Faults injected into API responses, for resilience testing
+    pub fault_injection: FaultInjectionConfig,
+
+    /// Request and response bodies logged for debugging
+    pub debug_http: DebugHttpConfig,
+}
+
+/// Request and response bodies of chosen routes logged for debugging, the
+/// latest exchanges are kept in memory and served by
+/// `GET /api/v1/admin/http-log`, never those of `/api/*/admin/*`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DebugHttpConfig {
+    pub enable: bool,
+
+    /// Logged request paths and the paths under them, every path when empty
+    pub routes: Vec<String>,
+
+    /// JSON fields and query parameters whose values are redacted, compared
+    /// case-insensitively
+    pub redacted_fields: Vec<String>,
+
+    /// Exchanges kept in memory, the oldest is dropped first
+    pub capacity: usize,
+
+    /// Bodies larger than this are not captured
+    pub max_body_size: usize,
+}
+
+/// Faults injected into the responses of chosen routes, never into
+/// `/api/*/admin/*` so that injection can always be turned off again
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FaultInjectionConfig {
+    pub enable: bool,
+
+    /// Applied in order, each one rolled separately
+    pub rules: Vec<FaultRule>,
+}
+
+/// Fault injected into `percentage` percent of the requests whose path is
+/// `route` or under it
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultRule {
+    pub route: String,
+
+    /// Between 0 and 100
+    pub percentage: f64,
+
+    pub fault: Fault,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Delay the request before it is handled
+    Latency(Duration),
+
+    /// Respond with this 5xx status instead of handling the request
+    Error(u16),
+
+    /// Close the connection after the response headers
+    DropConnection,
+
+    /// Cut the JSON response body in half
+    MalformedJson,
+}
+
+/// CIDR allow/deny list, deny entries take precedence over allow entries
+#[derive(Clone, Debug, Default)]
+pub struct IpAccessList {
+    pub allow: Vec<IpNet>,
+
+    pub deny: Vec<IpNet>,
+}
+
+impl IpAccessList {
+    #[must_use]
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        !self.deny.iter().any(|network| network.contains(ip))
+            && self.allow.iter().any(|network| network.contains(ip))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -61,6 +523,19 @@ pub struct MetricsConfig {
     pub enable: bool,
 
     pub listen_address: SocketAddr,
+
+    /// Pushgateway the metrics are pushed to, alongside or instead of being
+    /// scraped
+    pub push_gateway: Option<MetricsPushGatewayConfig>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MetricsPushGatewayConfig {
+    pub endpoint: String,
+
+    pub job: String,
+
+    pub interval: Duration,
 }
 
 #[derive(Clone, Debug)]
@@ -82,4 +557,35 @@ pub struct PostgresConfig {
     pub max_connections: u32,
 
     pub application_name: Option<String>,
+
+    /// How long to wait for the advisory lock and the migrations on startup
+    pub migration_timeout: Duration,
+
+    /// `statement_timeout` of the sessions, `None` keeps the one of the
+    /// server
+    pub statement_timeout: Option<Duration>,
+
+    /// Duration above which a user query is logged as slow, `None` disables
+    /// the log
+    pub slow_query_threshold: Option<Duration>,
+
+    pub transaction_retry: TransactionRetryConfig,
+
+    /// Hosts of the read replicas, reached with the port and the credentials
+    /// of the primary
+    pub read_replicas: Vec<String>,
+}
+
+/// Retries of a database transaction aborted by a serialization failure or a
+/// deadlock, `max_attempts` counts the first attempt, the delay before a retry
+/// doubles from `base_delay` with jitter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionRetryConfig {
+    pub max_attempts: u32,
+
+    pub base_delay: Duration,
+}
+
+impl Default for TransactionRetryConfig {
+    fn default() -> Self { Self { max_attempts: 3, base_delay: Duration::from_millis(50) } }
 }
@@ -4,7 +4,10 @@ use axum::{
 };
 use indexmap::IndexMap;
 use snafu::Snafu;
-use zeus_axum::{json_response, response, response::EncapsulatedJsonError};
+use zeus_axum::{
+    json_response, response,
+    response::{EncapsulatedJsonError, ErrorCode},
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -17,6 +20,35 @@ pub enum Error {
 
     #[snafu(display("Invalid date format: '{}'. Expected YYYY-MM-DD", date_str))]
     InvalidDateFormat { date_str: String },
+
+    #[snafu(display("Invalid amount: '{value}'. Expected a non-negative integer"))]
+    InvalidAmount { value: String },
+
+    #[snafu(display("Invalid email address: '{email}'"))]
+    InvalidEmail { email: String },
+
+    #[snafu(display("Invalid pagination: {message}"))]
+    InvalidPagination { message: String },
+
+    #[snafu(display("Invalid sort: {message}"))]
+    InvalidSort { message: String },
+
+    #[snafu(display("Invalid fields: {message}"))]
+    InvalidFields { message: String },
+}
+
+impl ErrorCode for Error {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::InvalidRoleType { .. } => "INVALID_ROLE_TYPE",
+            Self::InvalidDateFormat { .. } => "INVALID_DATE_FORMAT",
+            Self::InvalidAmount { .. } => "INVALID_AMOUNT",
+            Self::InvalidEmail { .. } => "INVALID_EMAIL",
+            Self::InvalidPagination { .. } => "INVALID_PAGINATION",
+            Self::InvalidSort { .. } => "INVALID_SORT",
+            Self::InvalidFields { .. } => "INVALID_FIELDS",
+        }
+    }
 }
 
 impl IntoResponse for Error {
@@ -24,11 +56,18 @@ impl IntoResponse for Error {
     #[allow(clippy::cognitive_complexity)]
     fn into_response(self) -> Response {
         match self {
-            Self::InvalidRoleType { .. } | Self::InvalidDateFormat { .. } => json_response! {
+            Self::InvalidRoleType { .. }
+            | Self::InvalidDateFormat { .. }
+            | Self::InvalidAmount { .. }
+            | Self::InvalidEmail { .. }
+            | Self::InvalidPagination { .. }
+            | Self::InvalidSort { .. }
+            | Self::InvalidFields { .. } => json_response! {
                 reason: self,
                 status: StatusCode::BAD_REQUEST,
                 error: response::Error {
                     type_: response::ErrorType::BadRequest,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
                     additional_fields: IndexMap::default(),
                 }
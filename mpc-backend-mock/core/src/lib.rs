@@ -3,6 +3,8 @@ pub mod error;
 pub mod model;
 
 use std::{
+    collections::BTreeMap,
+    fmt,
     net::{IpAddr, Ipv4Addr},
     path::{Path, PathBuf},
     sync::LazyLock,
@@ -68,4 +70,82 @@ pub struct ServerInfo {
     pub bitcoin_network: String,
     pub solana_cluster: String,
     pub start_time: DateTime<Utc>,
+    pub capabilities: Capabilities,
+}
+
+/// Optional subsystems a deployment may have enabled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Capability {
+    Bitcoin,
+    Solana,
+    Notifications,
+    Kms,
+    Webhooks,
+    Sessions,
+}
+
+impl Capability {
+    pub const ALL: [Self; 6] = [
+        Self::Bitcoin,
+        Self::Solana,
+        Self::Notifications,
+        Self::Kms,
+        Self::Webhooks,
+        Self::Sessions,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bitcoin => "bitcoin",
+            Self::Solana => "solana",
+            Self::Notifications => "notifications",
+            Self::Kms => "kms",
+            Self::Webhooks => "webhooks",
+            Self::Sessions => "sessions",
+        }
+    }
+}
+
+/// Which subsystems are enabled, so frontends can feature-detect against
+/// different deployments
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(transparent)]
+#[schema(example = json!({"bitcoin": true, "solana": true, "notifications": false, "kms": false, "webhooks": false, "sessions": false}))]
+pub struct Capabilities(BTreeMap<Capability, bool>);
+
+impl Capabilities {
+    /// Every capability disabled
+    #[must_use]
+    pub fn new() -> Self { Self(Capability::ALL.into_iter().map(|cap| (cap, false)).collect()) }
+
+    #[must_use]
+    pub fn with(mut self, capability: Capability, enabled: bool) -> Self {
+        let _previous = self.0.insert(capability, enabled);
+        self
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self, capability: Capability) -> bool {
+        self.0.get(&capability).copied().unwrap_or_default()
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self { Self::new() }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (capability, enabled) in &self.0 {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            write!(f, "{}={}", capability.as_str(), if *enabled { "on" } else { "off" })?;
+        }
+        Ok(())
+    }
 }
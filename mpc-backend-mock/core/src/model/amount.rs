@@ -0,0 +1,172 @@
+//! Amounts in the smallest unit of a chain or token.
+//!
+//! Amounts are `u64` under the hood, serialized as decimal strings so that
+//! clients never round-trip them through floating point, and stored as
+//! `NUMERIC(20, 0)` in Postgres.
+
+use std::{fmt, str::FromStr};
+
+use bigdecimal::{BigDecimal, ToPrimitive};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+use utoipa::ToSchema;
+
+use crate::error::Error;
+
+macro_rules! amount {
+    ($(#[$meta:meta])* $name:ident, example = $example:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema)]
+        #[schema(value_type = String, example = $example)]
+        pub struct $name(u64);
+
+        impl $name {
+            pub const MAX: Self = Self(u64::MAX);
+            pub const ZERO: Self = Self(0);
+
+            #[must_use]
+            pub const fn new(value: u64) -> Self { Self(value) }
+
+            #[must_use]
+            pub const fn get(self) -> u64 { self.0 }
+
+            /// Returns `None` on overflow
+            #[must_use]
+            pub fn checked_add(self, rhs: Self) -> Option<Self> { self.0.checked_add(rhs.0).map(Self) }
+
+            /// Returns `None` if `rhs` is greater than `self`
+            #[must_use]
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> { self.0.checked_sub(rhs.0).map(Self) }
+
+            /// Returns `None` on overflow
+            #[must_use]
+            pub fn checked_mul(self, rhs: u64) -> Option<Self> { self.0.checked_mul(rhs).map(Self) }
+
+            /// Sum of `amounts`, `None` on overflow
+            #[must_use]
+            pub fn checked_sum<I>(amounts: I) -> Option<Self>
+            where
+                I: IntoIterator<Item = Self>,
+            {
+                amounts.into_iter().try_fold(Self::ZERO, Self::checked_add)
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self { Self(value) }
+        }
+
+        impl From<$name> for u64 {
+            fn from(amount: $name) -> Self { amount.0 }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self).map_err(|_| Error::InvalidAmount { value: s.to_string() })
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.collect_str(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+            }
+        }
+
+        impl Type<Postgres> for $name {
+            fn type_info() -> PgTypeInfo { <BigDecimal as Type<Postgres>>::type_info() }
+        }
+
+        impl Encode<'_, Postgres> for $name {
+            fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+                <BigDecimal as Encode<'_, Postgres>>::encode(BigDecimal::from(self.0), buf)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $name {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                let decimal = <BigDecimal as Decode<'r, Postgres>>::decode(value)?;
+                decimal
+                    .to_u64()
+                    .filter(|_| decimal.is_integer())
+                    .map(Self)
+                    .ok_or_else(|| format!("{decimal} is not a valid {}", stringify!($name)).into())
+            }
+        }
+    };
+}
+
+amount! {
+    /// Amount of bitcoin in satoshis
+    Satoshis, example = "100000"
+}
+
+amount! {
+    /// Amount of SOL in lamports
+    Lamports, example = "1000000000"
+}
+
+amount! {
+    /// Amount of a token in its smallest unit, e.g. satoshis for BTC, lamports
+    /// for SOL or the raw amount of an SPL token
+    TokenAmount, example = "2500000"
+}
+
+impl From<Satoshis> for TokenAmount {
+    fn from(amount: Satoshis) -> Self { Self(amount.0) }
+}
+
+impl From<Lamports> for TokenAmount {
+    fn from(amount: Lamports) -> Self { Self(amount.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let amount = Satoshis::new(10);
+        assert_eq!(amount.checked_add(Satoshis::new(5)), Some(Satoshis::new(15)));
+        assert_eq!(Satoshis::MAX.checked_add(Satoshis::new(1)), None);
+        assert_eq!(amount.checked_sub(Satoshis::new(11)), None);
+        assert_eq!(amount.checked_mul(3), Some(Satoshis::new(30)));
+        assert_eq!(Satoshis::checked_sum([Satoshis::MAX, Satoshis::new(1)]), None);
+        assert_eq!(Satoshis::checked_sum([]), Some(Satoshis::ZERO));
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let amount = Lamports::new(u64::MAX);
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, "\"18446744073709551615\"");
+        assert_eq!(serde_json::from_str::<Lamports>(&json).unwrap(), amount);
+
+        assert!(serde_json::from_str::<Lamports>("1000").is_err());
+        assert!(serde_json::from_str::<Lamports>("\"1.5\"").is_err());
+        assert!(serde_json::from_str::<Lamports>("\"-1\"").is_err());
+        assert!(serde_json::from_str::<Lamports>("\"18446744073709551616\"").is_err());
+    }
+}
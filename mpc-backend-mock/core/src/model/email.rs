@@ -0,0 +1,128 @@
+//! Email addresses of users.
+//!
+//! Addresses are checked against RFC 5322 and lowercased, so two spellings of
+//! the same address always compare equal.
+
+use std::{fmt, str::FromStr};
+
+use email_address::{EmailAddress, Options};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use utoipa::ToSchema;
+
+use crate::error::Error;
+
+/// Normalized email address
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema)]
+#[schema(value_type = String, example = "user@example.com")]
+pub struct Email(String);
+
+impl Email {
+    #[must_use]
+    pub fn as_str(&self) -> &str { &self.0 }
+
+    #[must_use]
+    pub fn into_inner(self) -> String { self.0 }
+
+    /// Part before the `@`
+    #[must_use]
+    pub fn local_part(&self) -> &str { self.0.rsplit_once('@').map_or("", |(local, _)| local) }
+
+    /// Part after the `@`
+    #[must_use]
+    pub fn domain(&self) -> &str { self.0.rsplit_once('@').map_or("", |(_, domain)| domain) }
+}
+
+impl AsRef<str> for Email {
+    fn as_ref(&self) -> &str { &self.0 }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> Self { email.0 }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+
+impl FromStr for Email {
+    type Err = Error;
+
+    /// Surrounding whitespace is ignored, display names (`Name <addr>`) and
+    /// domain literals (`user@[127.0.0.1]`) are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let options = Options::default().without_display_text().without_domain_literal();
+
+        EmailAddress::parse_with_options(s.trim(), options)
+            .map(|address| Self(address.as_str().to_lowercase()))
+            .map_err(|_| Error::InvalidEmail { email: s.to_string() })
+    }
+}
+
+impl Serialize for Email {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Email {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalizes() {
+        let email: Email = " User.Name+tag@Example.COM ".parse().unwrap();
+        assert_eq!(email.as_str(), "user.name+tag@example.com");
+        assert_eq!(email.local_part(), "user.name+tag");
+        assert_eq!(email.domain(), "example.com");
+    }
+
+    #[test]
+    fn test_parse_accepts_valid_addresses() {
+        for email in ["a@b", "user@localhost", "o'brien@example.org", "\"quoted name\"@example.com"]
+        {
+            assert!(email.parse::<Email>().is_ok(), "{email} should be valid");
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_addresses() {
+        for email in [
+            "",
+            "user",
+            "@example.com",
+            "user@",
+            "user@@example.com",
+            "us er@example.com",
+            "user@exa mple.com",
+            ".user@example.com",
+            "user.@example.com",
+            "user@-example.com",
+            "Name <user@example.com>",
+            "user@[127.0.0.1]",
+        ] {
+            assert!(
+                matches!(email.parse::<Email>(), Err(Error::InvalidEmail { .. })),
+                "{email} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let email: Email = serde_json::from_str("\"User@Example.com\"").unwrap();
+        assert_eq!(serde_json::to_string(&email).unwrap(), "\"user@example.com\"");
+        assert!(serde_json::from_str::<Email>("\"not an email\"").is_err());
+    }
+}
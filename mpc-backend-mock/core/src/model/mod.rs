@@ -1,12 +1,14 @@
 // include the model for api input, output. EX: CreateUserRequest,
 // CreateUserResponse....
 
-use serde::{Deserialize, Serialize};
-use utoipa::{IntoParams, ToSchema};
+mod amount;
+mod email;
+mod pagination;
+mod query;
 
-#[derive(Clone, Debug, Serialize, Deserialize, IntoParams, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct Pagination {
-    pub page: Option<u32>,
-    pub limit: Option<u32>,
-}
+pub use self::{
+    amount::{Lamports, Satoshis, TokenAmount},
+    email::Email,
+    pagination::{PageMetadata, Paginated, Pagination},
+    query::{Column, Fields, Sort, SortDirection, SortKey},
+};
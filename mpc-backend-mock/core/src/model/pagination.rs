@@ -0,0 +1,169 @@
+//! Pagination of list endpoints.
+//!
+//! Requests pass `page` and `limit` as query parameters, responses return the
+//! page in `data` and a [`PageMetadata`] in `_metadata`.
+
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::error::{Error, Result};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, IntoParams, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    /// Page to return, starting from 1
+    #[param(minimum = 1, example = 1)]
+    pub page: Option<u32>,
+
+    /// Number of items per page, 20 by default
+    #[param(minimum = 1, maximum = 100, example = 20)]
+    pub limit: Option<u32>,
+}
+
+impl Pagination {
+    pub const DEFAULT_LIMIT: u32 = 20;
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// Rejects a `page` of 0 and a `limit` outside `1..=MAX_LIMIT`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPagination`] naming the invalid parameter
+    pub fn validate(&self) -> Result<()> {
+        if self.page == Some(0) {
+            return Err(Error::InvalidPagination { message: "page starts from 1".to_string() });
+        }
+        if self.limit.is_some_and(|limit| !(1..=Self::MAX_LIMIT).contains(&limit)) {
+            return Err(Error::InvalidPagination {
+                message: format!("limit must be between 1 and {}", Self::MAX_LIMIT),
+            });
+        }
+        Ok(())
+    }
+
+    /// Requested page, starting from 1
+    #[must_use]
+    pub fn page(&self) -> u32 { self.page.unwrap_or(1).max(1) }
+
+    /// Requested page size, clamped to `1..=MAX_LIMIT`
+    #[must_use]
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+
+    /// Number of rows to skip
+    #[must_use]
+    pub fn offset(&self) -> u64 { u64::from(self.page() - 1) * u64::from(self.limit()) }
+
+    /// Page size as a SQL `LIMIT`
+    #[must_use]
+    pub fn sql_limit(&self) -> i64 { i64::from(self.limit()) }
+
+    /// Rows to skip as a SQL `OFFSET`
+    #[must_use]
+    pub fn sql_offset(&self) -> i64 { i64::try_from(self.offset()).unwrap_or(i64::MAX) }
+}
+
+/// Page position returned in the `_metadata` of a list response
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PageMetadata {
+    /// Number of items matching the request across all pages
+    #[serde(rename = "totalCount")]
+    #[schema(example = 42)]
+    pub total: u64,
+
+    /// Returned page, starting from 1
+    #[schema(example = 1)]
+    pub page: u32,
+
+    /// Page size
+    #[schema(example = 20)]
+    pub limit: u32,
+
+    /// Whether a further page exists
+    #[schema(example = true)]
+    pub has_next: bool,
+}
+
+impl PageMetadata {
+    #[must_use]
+    pub fn new(total: u64, pagination: &Pagination) -> Self {
+        let page = pagination.page();
+        let limit = pagination.limit();
+        let has_next = pagination.offset() + u64::from(limit) < total;
+
+        Self { total, page, limit, has_next }
+    }
+}
+
+/// One page of a list response, serialized as an `EncapsulatedJson` with the
+/// items in `data` and the [`PageMetadata`] in `_metadata`
+#[derive(Clone, Debug)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub metadata: PageMetadata,
+}
+
+impl<T> Paginated<T> {
+    /// Page of `items` out of `total` matching items
+    #[must_use]
+    pub fn new(items: Vec<T>, total: u64, pagination: &Pagination) -> Self {
+        Self { items, metadata: PageMetadata::new(total, pagination) }
+    }
+}
+
+impl<T> IntoResponse for Paginated<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        EncapsulatedJson::ok(self.items).metadata(self.metadata).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn pagination(page: Option<u32>, limit: Option<u32>) -> Pagination {
+        Pagination { page, limit }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let default = Pagination::default();
+        assert!(default.validate().is_ok());
+        assert_eq!(default.page(), 1);
+        assert_eq!(default.sql_limit(), 20);
+        assert_eq!(default.sql_offset(), 0);
+
+        assert_eq!(pagination(Some(3), Some(50)).sql_offset(), 100);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(pagination(Some(1), Some(Pagination::MAX_LIMIT)).validate().is_ok());
+
+        for invalid in
+            [pagination(Some(0), None), pagination(None, Some(0)), pagination(None, Some(101))]
+        {
+            assert!(matches!(invalid.validate(), Err(Error::InvalidPagination { .. })));
+        }
+    }
+
+    #[test]
+    fn test_page_metadata_has_next() {
+        assert!(PageMetadata::new(41, &pagination(Some(2), Some(20))).has_next);
+        assert!(!PageMetadata::new(40, &pagination(Some(2), Some(20))).has_next);
+        assert!(!PageMetadata::new(0, &Pagination::default()).has_next);
+
+        let metadata = serde_json::to_value(PageMetadata::new(42, &Pagination::default())).unwrap();
+        assert_eq!(
+            metadata,
+            serde_json::json!({ "totalCount": 42, "page": 1, "limit": 20, "hasNext": true })
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Sorting and field selection of list endpoints.
+//!
+//! `?sort=created_at:desc,email:asc` orders by the given columns, the
+//! direction defaults to ascending. `?fields=id,email` returns only the given
+//! fields. Each endpoint allows its own [`Column`]s, so a parameter never
+//! reaches SQL unless it names one of them.
+
+use crate::error::{Error, Result};
+
+/// Column of a list endpoint which may be sorted by or selected
+pub trait Column: Copy + Eq + Send + Sync + 'static {
+    /// Columns accepted by the endpoint
+    const ALLOWED: &'static [Self];
+
+    /// Name of the column, both in query parameters and in SQL
+    fn name(self) -> &'static str;
+
+    /// Column named `name`, if the endpoint allows it
+    #[must_use]
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALLOWED.iter().copied().find(|column| column.name() == name)
+    }
+}
+
+/// Direction of a [`SortKey`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    /// SQL keyword of the direction
+    #[must_use]
+    pub const fn as_sql(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// Column to sort by and its direction
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SortKey<C> {
+    pub column: C,
+    pub direction: SortDirection,
+}
+
+/// Sort order parsed from `?sort=`, empty when the endpoint's default order
+/// applies
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sort<C>(Vec<SortKey<C>>);
+
+impl<C> Default for Sort<C> {
+    fn default() -> Self { Self(Vec::new()) }
+}
+
+impl<C: Column> Sort<C> {
+    /// Parses a comma-separated list of `column[:asc|desc]`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSort`] if a column is not allowed, is repeated
+    /// or has an unknown direction
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut keys: Vec<SortKey<C>> = Vec::new();
+        for key in s.split(',').map(str::trim) {
+            let (name, direction) = key.split_once(':').unwrap_or((key, "asc"));
+            let column = C::from_name(name.trim()).ok_or_else(|| Error::InvalidSort {
+                message: format!("cannot sort by `{name}`, allowed: {}", allowed::<C>()),
+            })?;
+            let direction = match direction.trim().to_ascii_lowercase().as_str() {
+                "asc" => SortDirection::Asc,
+                "desc" => SortDirection::Desc,
+                other => {
+                    return Err(Error::InvalidSort {
+                        message: format!("unknown direction `{other}`, expected asc or desc"),
+                    })
+                }
+            };
+            if keys.iter().any(|key| key.column == column) {
+                return Err(Error::InvalidSort {
+                    message: format!("`{}` is sorted by twice", column.name()),
+                });
+            }
+            keys.push(SortKey { column, direction });
+        }
+        Ok(Self(keys))
+    }
+
+    #[must_use]
+    pub fn keys(&self) -> &[SortKey<C>] { &self.0 }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+/// Fields selected by `?fields=`, in the order they were requested
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fields<C>(Vec<C>);
+
+impl<C: Column> Fields<C> {
+    /// Parses a comma-separated list of columns, repeated columns are selected
+    /// once
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidFields`] if a column is not allowed
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut columns: Vec<C> = Vec::new();
+        for name in s.split(',').map(str::trim) {
+            let column = C::from_name(name).ok_or_else(|| Error::InvalidFields {
+                message: format!("unknown field `{name}`, allowed: {}", allowed::<C>()),
+            })?;
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        Ok(Self(columns))
+    }
+
+    #[must_use]
+    pub fn columns(&self) -> &[C] { &self.0 }
+}
+
+fn allowed<C: Column>() -> String {
+    C::ALLOWED.iter().map(|column| column.name()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum TestColumn {
+        CreatedAt,
+        Email,
+    }
+
+    impl Column for TestColumn {
+        const ALLOWED: &'static [Self] = &[Self::CreatedAt, Self::Email];
+
+        fn name(self) -> &'static str {
+            match self {
+                Self::CreatedAt => "created_at",
+                Self::Email => "email",
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        let sort = Sort::<TestColumn>::parse("created_at:desc, email").unwrap();
+        assert_eq!(
+            sort.keys(),
+            [
+                SortKey { column: TestColumn::CreatedAt, direction: SortDirection::Desc },
+                SortKey { column: TestColumn::Email, direction: SortDirection::Asc },
+            ]
+        );
+
+        for invalid in ["password", "email:sideways", "email,email:desc", "", "email;DROP TABLE"] {
+            assert!(
+                matches!(Sort::<TestColumn>::parse(invalid), Err(Error::InvalidSort { .. })),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = Fields::<TestColumn>::parse("email,created_at,email").unwrap();
+        assert_eq!(fields.columns(), [TestColumn::Email, TestColumn::CreatedAt]);
+
+        assert!(matches!(
+            Fields::<TestColumn>::parse("email,password"),
+            Err(Error::InvalidFields { .. })
+        ));
+    }
+}
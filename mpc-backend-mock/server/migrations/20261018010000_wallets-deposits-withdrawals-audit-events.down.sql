@@ -0,0 +1,23 @@
+-- Revert wallets, deposits, withdrawals and audit_events table creation
+-- Drop triggers
+DROP TRIGGER IF EXISTS update_withdrawals_updated_at ON withdrawals;
+
+DROP TRIGGER IF EXISTS update_deposits_updated_at ON deposits;
+
+DROP TRIGGER IF EXISTS update_wallets_updated_at ON wallets;
+
+-- Drop tables (indexes are dropped with the tables)
+DROP TABLE IF EXISTS audit_events;
+
+DROP TABLE IF EXISTS withdrawals;
+
+DROP TABLE IF EXISTS deposits;
+
+DROP TABLE IF EXISTS wallets;
+
+-- Drop enum types
+DROP TYPE IF EXISTS withdrawal_status;
+
+DROP TYPE IF EXISTS deposit_status;
+
+DROP TYPE IF EXISTS chain;
@@ -0,0 +1,104 @@
+-- Create enum types for the wallet domain
+CREATE TYPE chain AS ENUM ('bitcoin', 'solana');
+
+CREATE TYPE deposit_status AS ENUM ('pending', 'confirmed', 'failed');
+
+CREATE TYPE withdrawal_status AS ENUM ('pending', 'broadcast', 'confirmed', 'failed');
+
+-- Create wallets table
+-- A wallet is an on-chain address owned by a user
+CREATE TABLE wallets (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    chain chain NOT NULL,
+    address VARCHAR(128) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (chain, address)
+);
+
+CREATE INDEX idx_wallets_user_id ON wallets(user_id);
+
+-- Create deposits table
+-- Amounts are stored in the smallest unit of the chain (satoshis, lamports)
+CREATE TABLE deposits (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_id UUID NOT NULL REFERENCES wallets(id),
+    tx_id VARCHAR(128) NOT NULL,
+    amount NUMERIC(20, 0) NOT NULL CHECK (amount >= 0),
+    status deposit_status NOT NULL DEFAULT 'pending',
+    block_height BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (wallet_id, tx_id)
+);
+
+CREATE INDEX idx_deposits_wallet_id ON deposits(wallet_id);
+
+CREATE INDEX idx_deposits_status ON deposits(status);
+
+-- Create withdrawals table
+CREATE TABLE withdrawals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_id UUID NOT NULL REFERENCES wallets(id),
+    destination_address VARCHAR(128) NOT NULL,
+    amount NUMERIC(20, 0) NOT NULL CHECK (amount > 0),
+    status withdrawal_status NOT NULL DEFAULT 'pending',
+    tx_id VARCHAR(128),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_withdrawals_wallet_id ON withdrawals(wallet_id);
+
+CREATE INDEX idx_withdrawals_status ON withdrawals(status);
+
+-- Create audit_events table
+-- Audit events are append-only, so there is no updated_at column
+CREATE TABLE audit_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    actor_user_id UUID REFERENCES users(id),
+    action VARCHAR(64) NOT NULL,
+    target_type VARCHAR(64) NOT NULL,
+    target_id UUID,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_events_actor_user_id ON audit_events(actor_user_id);
+
+CREATE INDEX idx_audit_events_target ON audit_events(target_type, target_id);
+
+CREATE INDEX idx_audit_events_created_at ON audit_events(created_at);
+
+-- Add comment to tables
+COMMENT ON TABLE wallets IS 'On-chain addresses owned by users';
+
+COMMENT ON TABLE deposits IS 'Incoming transfers to user wallets';
+
+COMMENT ON TABLE withdrawals IS 'Outgoing transfers requested by users';
+
+COMMENT ON TABLE audit_events IS 'Append-only log of security-relevant actions';
+
+COMMENT ON COLUMN deposits.amount IS 'Amount in the smallest unit of the chain';
+
+COMMENT ON COLUMN deposits.block_height IS 'Block height of the deposit, NULL until it is confirmed';
+
+COMMENT ON COLUMN withdrawals.amount IS 'Amount in the smallest unit of the chain';
+
+COMMENT ON COLUMN withdrawals.tx_id IS 'Transaction ID, NULL until the withdrawal is broadcast';
+
+COMMENT ON COLUMN audit_events.actor_user_id IS 'User who performed the action, NULL for system actions';
+
+-- Create triggers to automatically update updated_at on row updates
+CREATE TRIGGER update_wallets_updated_at BEFORE
+UPDATE
+    ON wallets FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_deposits_updated_at BEFORE
+UPDATE
+    ON deposits FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_withdrawals_updated_at BEFORE
+UPDATE
+    ON withdrawals FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,9 @@
+-- Revert transactions table creation
+-- Drop trigger
+DROP TRIGGER IF EXISTS update_transactions_updated_at ON transactions;
+
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS transactions;
+
+-- Drop enum type
+DROP TYPE IF EXISTS transaction_status;
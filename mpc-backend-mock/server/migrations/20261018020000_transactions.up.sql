@@ -0,0 +1,34 @@
+-- Create enum type for the transaction lifecycle
+CREATE TYPE transaction_status AS ENUM ('pending', 'submitted', 'confirmed', 'finalized', 'failed');
+
+-- Create transactions table
+-- A transaction is a signed Solana transaction submitted by a user, e.g. a zBTC
+-- mint or redeem, identified on-chain by its first signature
+CREATE TABLE transactions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    signature VARCHAR(128) NOT NULL UNIQUE,
+    status transaction_status NOT NULL DEFAULT 'pending',
+    slot BIGINT,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_transactions_user_id ON transactions(user_id);
+
+CREATE INDEX idx_transactions_status ON transactions(status);
+
+-- Add comment to table
+COMMENT ON TABLE transactions IS 'Signed Solana transactions submitted by users';
+
+COMMENT ON COLUMN transactions.signature IS 'First signature of the transaction, base58 encoded';
+
+COMMENT ON COLUMN transactions.slot IS 'Slot the transaction was processed in, NULL until it is seen on-chain';
+
+COMMENT ON COLUMN transactions.error IS 'Reason the transaction failed, NULL unless it failed';
+
+-- Create trigger to automatically update updated_at on row updates
+CREATE TRIGGER update_transactions_updated_at BEFORE
+UPDATE
+    ON transactions FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,4 @@
+-- Revert case-insensitive email uniqueness, emails stay lowercased
+DROP INDEX IF EXISTS idx_users_email_lower;
+
+COMMENT ON COLUMN users.email IS 'User email address, must be unique';
@@ -0,0 +1,14 @@
+-- Store emails trimmed and lowercased, which is how the backend and Keycloak
+-- compare them, the migration fails if two users only differ in case and have
+-- to be merged by hand first
+UPDATE
+    users
+SET
+    email = LOWER(TRIM(email))
+WHERE
+    email <> LOWER(TRIM(email));
+
+-- Keep emails which only differ in case from becoming two users
+CREATE UNIQUE INDEX idx_users_email_lower ON users(LOWER(email));
+
+COMMENT ON COLUMN users.email IS 'User email address, lowercase and unique regardless of case';
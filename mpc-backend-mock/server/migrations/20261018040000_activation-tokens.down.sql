@@ -0,0 +1,3 @@
+-- Revert activation_tokens table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS activation_tokens;
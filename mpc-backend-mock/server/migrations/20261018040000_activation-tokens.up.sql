@@ -0,0 +1,22 @@
+-- Create activation_tokens table
+-- An activation token is sent to a new user by email and activates the user
+-- once, only its SHA-256 hash is stored
+CREATE TABLE activation_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_activation_tokens_user_id ON activation_tokens(user_id);
+
+CREATE INDEX idx_activation_tokens_expires_at ON activation_tokens(expires_at);
+
+-- Add comment to table
+COMMENT ON TABLE activation_tokens IS 'Single-use tokens activating new users';
+
+COMMENT ON COLUMN activation_tokens.token_hash IS 'SHA-256 hash of the token, hex encoded';
+
+COMMENT ON COLUMN activation_tokens.used_at IS 'Timestamp when the token activated the user, NULL until it is used';
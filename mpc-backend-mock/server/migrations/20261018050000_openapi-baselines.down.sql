@@ -0,0 +1,3 @@
+-- Revert openapi_baselines table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS openapi_baselines;
@@ -0,0 +1,13 @@
+-- Create openapi_baselines table
+-- OpenAPI documents uploaded by operators, the latest one is the baseline the
+-- live document is compared against to detect API drift
+CREATE TABLE openapi_baselines (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    document JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_openapi_baselines_created_at ON openapi_baselines(created_at);
+
+-- Add comment to table
+COMMENT ON TABLE openapi_baselines IS 'Uploaded OpenAPI documents, the latest is the drift baseline';
@@ -0,0 +1,6 @@
+-- Revert wallet_balance_snapshots table creation
+-- Drop trigger
+DROP TRIGGER IF EXISTS update_wallet_balance_snapshots_updated_at ON wallet_balance_snapshots;
+
+-- Drop table
+DROP TABLE IF EXISTS wallet_balance_snapshots;
@@ -0,0 +1,21 @@
+-- Create wallet_balance_snapshots table
+-- One row per wallet and day (UTC), snapshots taken later in the day replace
+-- earlier ones, so past days hold their end of day balance
+CREATE TABLE wallet_balance_snapshots (
+    wallet_id UUID NOT NULL REFERENCES wallets(id),
+    snapshot_date DATE NOT NULL,
+    balance NUMERIC(20, 0) NOT NULL CHECK (balance >= 0),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_id, snapshot_date)
+);
+
+-- Add comment to table
+COMMENT ON TABLE wallet_balance_snapshots IS 'Daily balance history of wallets';
+
+COMMENT ON COLUMN wallet_balance_snapshots.balance IS 'Balance in the smallest unit of the chain';
+
+-- Create trigger to automatically update updated_at on row updates
+CREATE TRIGGER update_wallet_balance_snapshots_updated_at BEFORE
+UPDATE
+    ON wallet_balance_snapshots FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
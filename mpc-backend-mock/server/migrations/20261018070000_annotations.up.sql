@@ -0,0 +1,21 @@
+-- Create annotations table
+-- Internal notes and flags operators attach to a user or a transaction, they
+-- are only returned on admin routes
+CREATE TABLE annotations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID REFERENCES users(id),
+    transaction_id UUID REFERENCES transactions(id),
+    note TEXT NOT NULL DEFAULT '',
+    flags VARCHAR(64)[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK ((user_id IS NULL) <> (transaction_id IS NULL))
+);
+
+CREATE INDEX idx_annotations_user_id ON annotations(user_id);
+
+CREATE INDEX idx_annotations_transaction_id ON annotations(transaction_id);
+
+-- Add comment to table
+COMMENT ON TABLE annotations IS 'Operator notes and flags on users and transactions';
+
+COMMENT ON COLUMN annotations.flags IS 'Short labels such as "test account" or "fraud sim"';
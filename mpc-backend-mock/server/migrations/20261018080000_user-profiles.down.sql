@@ -0,0 +1,2 @@
+-- Revert user_profiles table creation
+DROP TABLE IF EXISTS user_profiles;
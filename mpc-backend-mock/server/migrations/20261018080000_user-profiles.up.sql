@@ -0,0 +1,15 @@
+-- Create user_profiles table
+-- Settings a user edits on their own, a user without a row has the defaults
+CREATE TABLE user_profiles (
+    user_id UUID PRIMARY KEY REFERENCES users(id),
+    display_name VARCHAR(64),
+    locale VARCHAR(35),
+    email_notifications BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Add comment to table
+COMMENT ON TABLE user_profiles IS 'Display name, locale and notification preferences of users';
+
+COMMENT ON COLUMN user_profiles.locale IS 'BCP 47 language tag such as "zh-TW", synced to the Keycloak `locale` attribute';
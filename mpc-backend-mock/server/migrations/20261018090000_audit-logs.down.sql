@@ -0,0 +1,20 @@
+-- Revert audit_logs to audit_events
+DROP INDEX IF EXISTS idx_audit_logs_action;
+
+ALTER INDEX idx_audit_logs_created_at RENAME TO idx_audit_events_created_at;
+
+ALTER INDEX idx_audit_logs_target RENAME TO idx_audit_events_target;
+
+ALTER INDEX idx_audit_logs_actor_user_id RENAME TO idx_audit_events_actor_user_id;
+
+ALTER TABLE audit_logs
+    DROP COLUMN request_id,
+    DROP COLUMN ip_address;
+
+ALTER TABLE audit_logs RENAME COLUMN payload TO details;
+
+ALTER TABLE audit_logs RENAME CONSTRAINT audit_logs_actor_user_id_fkey TO audit_events_actor_user_id_fkey;
+
+ALTER TABLE audit_logs RENAME CONSTRAINT audit_logs_pkey TO audit_events_pkey;
+
+ALTER TABLE audit_logs RENAME TO audit_events;
@@ -0,0 +1,29 @@
+-- Rename audit_events to audit_logs
+-- Every entry records where the request came from, the payload column holds a
+-- snapshot of the request as it was audited
+ALTER TABLE audit_events RENAME TO audit_logs;
+
+ALTER TABLE audit_logs RENAME CONSTRAINT audit_events_pkey TO audit_logs_pkey;
+
+ALTER TABLE audit_logs RENAME CONSTRAINT audit_events_actor_user_id_fkey TO audit_logs_actor_user_id_fkey;
+
+ALTER TABLE audit_logs RENAME COLUMN details TO payload;
+
+ALTER TABLE audit_logs
+    ADD COLUMN ip_address VARCHAR(45),
+    ADD COLUMN request_id VARCHAR(128);
+
+ALTER INDEX idx_audit_events_actor_user_id RENAME TO idx_audit_logs_actor_user_id;
+
+ALTER INDEX idx_audit_events_target RENAME TO idx_audit_logs_target;
+
+ALTER INDEX idx_audit_events_created_at RENAME TO idx_audit_logs_created_at;
+
+CREATE INDEX idx_audit_logs_action ON audit_logs(action);
+
+-- Add comment to table
+COMMENT ON TABLE audit_logs IS 'Append-only log of security-relevant actions';
+
+COMMENT ON COLUMN audit_logs.ip_address IS 'Client IP of the request, NULL for actions outside a request';
+
+COMMENT ON COLUMN audit_logs.request_id IS 'X-Request-Id of the request, NULL for actions outside a request';
@@ -0,0 +1,5 @@
+-- Revert notification outbox table creation
+-- Drop table (indexes and trigger are dropped with the table)
+DROP TABLE IF EXISTS notification_outbox;
+
+DROP TYPE IF EXISTS notification_status;
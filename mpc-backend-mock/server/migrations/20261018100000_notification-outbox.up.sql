@@ -0,0 +1,42 @@
+-- Create enum type for the delivery of queued notifications
+CREATE TYPE notification_status AS ENUM ('pending', 'sent', 'dead');
+
+-- Create notification outbox table
+-- Notifications are queued in the transaction of the change they belong to and
+-- sent by the notification dispatcher, a notification which keeps failing is
+-- dead-lettered
+CREATE TABLE notification_outbox (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind VARCHAR(64) NOT NULL,
+    recipient VARCHAR(255) NOT NULL,
+    payload JSONB NOT NULL,
+    status notification_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notification_outbox_due ON notification_outbox(next_attempt_at)
+WHERE
+    status = 'pending';
+
+CREATE INDEX idx_notification_outbox_status ON notification_outbox(status, created_at);
+
+-- Add comment to table
+COMMENT ON TABLE notification_outbox IS 'Notifications queued for delivery';
+
+COMMENT ON COLUMN notification_outbox.kind IS 'Template name of the notification, e.g. activation_email';
+
+COMMENT ON COLUMN notification_outbox.payload IS 'Serialized notification, may contain secrets such as activation links';
+
+COMMENT ON COLUMN notification_outbox.last_error IS 'Error of the last failed attempt, NULL if none failed';
+
+COMMENT ON COLUMN notification_outbox.next_attempt_at IS 'Earliest time of the next attempt of a pending notification';
+
+-- Create trigger to automatically update updated_at on row updates
+CREATE TRIGGER update_notification_outbox_updated_at BEFORE
+UPDATE
+    ON notification_outbox FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
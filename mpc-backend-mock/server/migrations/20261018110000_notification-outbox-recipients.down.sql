@@ -0,0 +1,3 @@
+-- Revert notification outbox recipients to a single address
+ALTER TABLE notification_outbox
+    ALTER COLUMN recipient TYPE VARCHAR(255) USING LEFT(recipient, 255);
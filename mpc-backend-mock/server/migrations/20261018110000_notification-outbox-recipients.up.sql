@@ -0,0 +1,3 @@
+-- Notifications may be sent to several recipients, which are stored
+-- comma-separated
+ALTER TABLE notification_outbox ALTER COLUMN recipient TYPE TEXT;
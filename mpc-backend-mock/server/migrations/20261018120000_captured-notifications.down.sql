@@ -0,0 +1,3 @@
+-- Revert captured notifications table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS captured_notifications;
@@ -0,0 +1,21 @@
+-- Create captured notifications table
+-- With the `capture` notification provider, emails are rendered and recorded
+-- here instead of being sent, so staging never emails real users
+CREATE TABLE captured_notifications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind VARCHAR(64) NOT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_captured_notifications_created_at ON captured_notifications(created_at);
+
+-- Add comment to table
+COMMENT ON TABLE captured_notifications IS 'Rendered emails which would have been sent';
+
+COMMENT ON COLUMN captured_notifications.kind IS 'Template name of the notification, e.g. activation_email';
+
+COMMENT ON COLUMN captured_notifications.payload IS 'Serialized notification, may contain secrets such as activation links';
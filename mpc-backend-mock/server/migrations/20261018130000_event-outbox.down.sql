@@ -0,0 +1,5 @@
+-- Revert event outbox table creation
+-- Drop tables (indexes are dropped with the tables)
+DROP TABLE IF EXISTS event_publisher_offsets;
+
+DROP TABLE IF EXISTS event_outbox;
@@ -0,0 +1,37 @@
+-- Create event outbox table
+-- Domain events are written in the transaction of the change they describe and
+-- shipped to the message broker by the event publisher. `transaction_id` orders
+-- the events by the transaction which wrote them, so the publisher can tell
+-- which events may still be committed below its offset
+CREATE TABLE event_outbox (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    sequence BIGINT GENERATED ALWAYS AS IDENTITY UNIQUE,
+    transaction_id BIGINT NOT NULL DEFAULT (pg_current_xact_id()::TEXT::BIGINT),
+    kind VARCHAR(64) NOT NULL,
+    aggregate_id UUID NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_event_outbox_position ON event_outbox(transaction_id, sequence);
+
+-- Add comment to table
+COMMENT ON TABLE event_outbox IS 'Domain events waiting to be, or already, published';
+
+COMMENT ON COLUMN event_outbox.kind IS 'Kind of the event, e.g. user_created';
+
+COMMENT ON COLUMN event_outbox.aggregate_id IS 'ID of the entity the event is about, the partition key of the broker';
+
+COMMENT ON COLUMN event_outbox.transaction_id IS 'ID of the transaction which wrote the event';
+
+-- Create event publisher offsets table
+-- The position of every publisher in the outbox, events at or before it were
+-- acknowledged by the broker
+CREATE TABLE event_publisher_offsets (
+    publisher VARCHAR(64) PRIMARY KEY,
+    last_transaction_id BIGINT NOT NULL DEFAULT 0,
+    last_sequence BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE event_publisher_offsets IS 'Position of the event publishers in the event outbox';
@@ -0,0 +1,3 @@
+-- Revert sessions table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS sessions;
@@ -0,0 +1,22 @@
+-- Create sessions table
+-- A session is created by a login which asks for a session cookie, only the
+-- SHA-256 hashes of the session token and of its CSRF token are stored
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    csrf_token_hash VARCHAR(64) NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
+
+CREATE INDEX idx_sessions_expires_at ON sessions(expires_at);
+
+-- Add comment to table
+COMMENT ON TABLE sessions IS 'Cookie sessions of browsers, an alternative to bearer tokens';
+
+COMMENT ON COLUMN sessions.token_hash IS 'SHA-256 hash of the session cookie, hex encoded';
+
+COMMENT ON COLUMN sessions.csrf_token_hash IS 'SHA-256 hash of the CSRF token mutating requests must send, hex encoded';
@@ -0,0 +1,3 @@
+-- Revert login_attempts table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS login_attempts;
@@ -0,0 +1,23 @@
+-- Create login_attempts table
+-- Failed logins are counted per email, the email is locked once the count
+-- reaches the configured threshold within the failure window
+CREATE TABLE login_attempts (
+    email VARCHAR(255) PRIMARY KEY,
+    failed_attempts INTEGER NOT NULL,
+    first_failed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_failed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_failed_ip VARCHAR(45),
+    locked_at TIMESTAMP WITH TIME ZONE,
+    locked_until TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_login_attempts_locked_at ON login_attempts(locked_at);
+
+-- Add comment to table
+COMMENT ON TABLE login_attempts IS 'Failed logins of emails and their lockouts';
+
+COMMENT ON COLUMN login_attempts.first_failed_at IS 'When the failed logins of the current failure window started';
+
+COMMENT ON COLUMN login_attempts.last_failed_ip IS 'Client IP of the last failed login, if known';
+
+COMMENT ON COLUMN login_attempts.locked_until IS 'When the lockout ends, NULL if it lasts until an admin unlocks the email';
@@ -0,0 +1,3 @@
+-- Revert the realm of users
+ALTER TABLE users
+DROP COLUMN IF EXISTS realm;
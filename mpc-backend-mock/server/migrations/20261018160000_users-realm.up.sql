@@ -0,0 +1,7 @@
+-- Record the Keycloak realm of every user, existing users belong to the
+-- primary realm which is stored as NULL so that renaming it in the
+-- configuration keeps them
+ALTER TABLE users
+ADD COLUMN realm VARCHAR(255);
+
+COMMENT ON COLUMN users.realm IS 'Keycloak realm of the user, NULL for the primary realm';
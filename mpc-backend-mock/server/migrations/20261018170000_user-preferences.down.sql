@@ -0,0 +1,22 @@
+-- Revert user_preferences table creation
+-- Enum values cannot be dropped, skipped notifications are kept as sent
+UPDATE notification_outbox
+SET
+    status = 'sent'
+WHERE
+    status = 'skipped';
+
+ALTER TABLE user_profiles
+ADD COLUMN email_notifications BOOLEAN NOT NULL DEFAULT TRUE;
+
+UPDATE user_profiles
+SET
+    email_notifications = user_preferences.email_enabled
+FROM
+    user_preferences
+WHERE
+    user_preferences.user_id = user_profiles.user_id;
+
+COMMENT ON TABLE user_profiles IS 'Display name, locale and notification preferences of users';
+
+DROP TABLE IF EXISTS user_preferences;
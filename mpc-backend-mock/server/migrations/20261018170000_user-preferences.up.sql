@@ -0,0 +1,37 @@
+-- Create user_preferences table
+-- Channels the notifications of a user are delivered over, a user without a
+-- row gets emails only, in the language of the notification
+CREATE TABLE user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id),
+    email_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    webhook_url TEXT,
+    locale VARCHAR(35),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Add comment to table
+COMMENT ON TABLE user_preferences IS 'Notification channel preferences of users';
+
+COMMENT ON COLUMN user_preferences.webhook_url IS 'URL the notifications of the user are posted to, NULL for none';
+
+COMMENT ON COLUMN user_preferences.locale IS 'BCP 47 language tag the notifications are sent in, NULL for the language of the notification';
+
+-- The email switch of the profiles moves to the preferences
+INSERT INTO
+    user_preferences (user_id, email_enabled)
+SELECT
+    user_id,
+    email_notifications
+FROM
+    user_profiles
+WHERE
+    NOT email_notifications;
+
+ALTER TABLE user_profiles
+DROP COLUMN email_notifications;
+
+COMMENT ON TABLE user_profiles IS 'Display name and locale of users';
+
+-- Notifications none of whose recipients want them are skipped
+ALTER TYPE notification_status ADD VALUE 'skipped';
@@ -0,0 +1,3 @@
+-- Revert email_change_requests table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS email_change_requests;
@@ -0,0 +1,25 @@
+-- Create email_change_requests table
+-- A new email of a user is staged here until the token sent to it is
+-- redeemed, only the SHA-256 hash of the token is stored
+CREATE TABLE email_change_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_email_change_requests_user_id ON email_change_requests(user_id);
+
+CREATE INDEX idx_email_change_requests_expires_at ON email_change_requests(expires_at);
+
+-- Add comment to table
+COMMENT ON TABLE email_change_requests IS 'New emails of users waiting for verification';
+
+COMMENT ON COLUMN email_change_requests.new_email IS 'Normalized email the user is changing to';
+
+COMMENT ON COLUMN email_change_requests.token_hash IS 'SHA-256 hash of the token, hex encoded';
+
+COMMENT ON COLUMN email_change_requests.used_at IS 'Timestamp when the change was confirmed, NULL until it is confirmed';
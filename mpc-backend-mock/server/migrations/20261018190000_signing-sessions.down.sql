@@ -0,0 +1,9 @@
+-- Revert signing_sessions table creation
+-- Drop trigger
+DROP TRIGGER IF EXISTS update_signing_sessions_updated_at ON signing_sessions;
+
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS signing_sessions;
+
+-- Drop enum type
+DROP TYPE IF EXISTS signing_session_status;
@@ -0,0 +1,38 @@
+-- Create enum type for the phases of a simulated signing session
+CREATE TYPE signing_session_status AS ENUM ('round1', 'round2', 'complete', 'failed');
+
+-- Create signing_sessions table
+-- A signing session is a fake MPC signing ceremony for a payload of a user, it
+-- moves through the rounds as time passes and is advanced when it is polled
+CREATE TABLE signing_sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    payload TEXT NOT NULL,
+    status signing_session_status NOT NULL DEFAULT 'round1',
+    round_duration_ms BIGINT NOT NULL CHECK (round_duration_ms >= 0),
+    fail_at signing_session_status CHECK (fail_at IN ('round1', 'round2')),
+    signature VARCHAR(128),
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_signing_sessions_user_id ON signing_sessions(user_id);
+
+-- Add comment to table
+COMMENT ON TABLE signing_sessions IS 'Simulated MPC signing sessions of users';
+
+COMMENT ON COLUMN signing_sessions.payload IS 'Payload to sign, hex encoded';
+
+COMMENT ON COLUMN signing_sessions.round_duration_ms IS 'Duration of each simulated round, in milliseconds';
+
+COMMENT ON COLUMN signing_sessions.fail_at IS 'Round the session fails in, NULL if it completes';
+
+COMMENT ON COLUMN signing_sessions.signature IS 'Fake signature, hex encoded, NULL until the session is complete';
+
+COMMENT ON COLUMN signing_sessions.error IS 'Reason the session failed, NULL unless it failed';
+
+-- Create trigger to automatically update updated_at on row updates
+CREATE TRIGGER update_signing_sessions_updated_at BEFORE
+UPDATE
+    ON signing_sessions FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,6 @@
+-- Revert keys table creation
+-- Drop table (indexes are dropped with the table)
+DROP TABLE IF EXISTS keys;
+
+-- Drop enum type
+DROP TYPE IF EXISTS key_curve;
@@ -0,0 +1,20 @@
+-- Create enum type for the curves of the generated keys
+CREATE TYPE key_curve AS ENUM ('secp256k1', 'ed25519');
+
+-- Create keys table
+-- A key is the result of a simulated distributed key generation for a user,
+-- one per chain, only its public half exists
+CREATE TABLE keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    chain chain NOT NULL,
+    curve key_curve NOT NULL,
+    public_key VARCHAR(88) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, chain)
+);
+
+-- Add comment to table
+COMMENT ON TABLE keys IS 'Fake MPC keys of users, one per chain';
+
+COMMENT ON COLUMN keys.public_key IS 'Compressed public key, hex encoded for secp256k1 and base58 encoded for ed25519';
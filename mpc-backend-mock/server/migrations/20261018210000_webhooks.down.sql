@@ -0,0 +1,7 @@
+-- Revert webhooks tables creation
+-- Drop tables (indexes and trigger are dropped with the tables)
+DROP TABLE IF EXISTS webhook_deliveries;
+
+DROP TABLE IF EXISTS webhooks;
+
+DROP TYPE IF EXISTS webhook_delivery_status;
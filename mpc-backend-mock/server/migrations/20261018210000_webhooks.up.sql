@@ -0,0 +1,63 @@
+-- Create enum type for the delivery of webhook events
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'delivered', 'dead');
+
+-- Create webhooks table
+-- A webhook receives the events of its user it subscribes to, each signed
+-- with the secret of the webhook
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    url TEXT NOT NULL,
+    event_types VARCHAR(64)[] NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhooks_user_id ON webhooks(user_id);
+
+-- Create webhook deliveries table
+-- Deliveries are queued in the transaction of the change the event describes
+-- and posted by the webhook dispatcher, a delivery which keeps failing is
+-- dead-lettered
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries(next_attempt_at)
+WHERE
+    status = 'pending';
+
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, created_at);
+
+-- Add comment to tables
+COMMENT ON TABLE webhooks IS 'Callback URLs registered by users for their events';
+
+COMMENT ON COLUMN webhooks.event_types IS 'Types of the events posted to the webhook, e.g. user.activated';
+
+COMMENT ON COLUMN webhooks.secret IS 'Key of the HMAC-SHA256 signature of the posted events';
+
+COMMENT ON TABLE webhook_deliveries IS 'Events queued for delivery to webhooks';
+
+COMMENT ON COLUMN webhook_deliveries.payload IS 'Data of the event, posted as the data of the body';
+
+COMMENT ON COLUMN webhook_deliveries.response_status IS 'HTTP status of the last answered attempt, NULL if none was answered';
+
+COMMENT ON COLUMN webhook_deliveries.last_error IS 'Error of the last failed attempt, NULL if none failed';
+
+COMMENT ON COLUMN webhook_deliveries.next_attempt_at IS 'Earliest time of the next attempt of a pending delivery';
+
+-- Create trigger to automatically update updated_at on row updates
+CREATE TRIGGER update_webhook_deliveries_updated_at BEFORE
+UPDATE
+    ON webhook_deliveries FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,9 @@
+-- Revert per-realm email uniqueness, fails if two realms share an email
+DROP INDEX IF EXISTS idx_users_realm_email;
+
+CREATE UNIQUE INDEX idx_users_email_lower ON users(LOWER(email));
+
+ALTER TABLE users
+ADD CONSTRAINT users_email_key UNIQUE (email);
+
+COMMENT ON COLUMN users.email IS 'User email address, lowercase and unique regardless of case';
@@ -0,0 +1,10 @@
+-- Make emails unique per realm, a partner realm may have a user with the email
+-- of a user of another realm, the primary realm stored as NULL included
+ALTER TABLE users
+DROP CONSTRAINT IF EXISTS users_email_key;
+
+DROP INDEX IF EXISTS idx_users_email_lower;
+
+CREATE UNIQUE INDEX idx_users_realm_email ON users(realm, LOWER(email)) NULLS NOT DISTINCT;
+
+COMMENT ON COLUMN users.email IS 'User email address, lowercase and unique per realm regardless of case';
@@ -0,0 +1,12 @@
+-- Mark an unused and unexpired activation token as used
+-- $1: token hash
+UPDATE
+    activation_tokens
+SET
+    used_at = NOW()
+WHERE
+    token_hash = $1
+    AND used_at IS NULL
+    AND expires_at > NOW()
+RETURNING
+    user_id;
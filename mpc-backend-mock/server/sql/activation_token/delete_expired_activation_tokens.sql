@@ -0,0 +1,4 @@
+-- Delete expired activation tokens, used or not
+DELETE FROM activation_tokens
+WHERE
+    expires_at < NOW();
@@ -0,0 +1,6 @@
+-- Insert a new activation token
+-- $1: user id, $2: token hash, $3: expiry
+INSERT INTO
+    activation_tokens (user_id, token_hash, expires_at)
+VALUES
+    ($1, $2, $3);
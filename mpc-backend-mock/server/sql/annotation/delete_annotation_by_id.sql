@@ -0,0 +1,4 @@
+-- Delete an annotation
+DELETE FROM annotations
+WHERE
+    id = $1;
@@ -0,0 +1,13 @@
+-- Insert an annotation on a user or a transaction, exactly one of $1 and $2 is
+-- set
+INSERT INTO
+    annotations (user_id, transaction_id, note, flags)
+VALUES
+    ($1, $2, $3, $4)
+RETURNING
+    id,
+    user_id,
+    transaction_id,
+    note,
+    flags,
+    created_at;
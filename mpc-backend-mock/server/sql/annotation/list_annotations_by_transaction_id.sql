@@ -0,0 +1,15 @@
+-- List annotations on a transaction, oldest first
+SELECT
+    id,
+    user_id,
+    transaction_id,
+    note,
+    flags,
+    created_at
+FROM
+    annotations
+WHERE
+    transaction_id = $1
+ORDER BY
+    created_at,
+    id;
@@ -0,0 +1,22 @@
+-- Count audit log entries matching the list filters
+SELECT
+    COUNT(*) AS "count!"
+FROM
+    audit_logs
+WHERE
+    (
+        $1::TEXT IS NULL
+        OR action = $1
+    )
+    AND (
+        $2::UUID IS NULL
+        OR actor_user_id = $2
+    )
+    AND (
+        $3::UUID IS NULL
+        OR target_id = $3
+    )
+    AND (
+        $4::TIMESTAMPTZ IS NULL
+        OR created_at >= $4
+    );
@@ -0,0 +1,23 @@
+-- Record a new audit log entry
+INSERT INTO
+    audit_logs (
+        actor_user_id,
+        action,
+        target_type,
+        target_id,
+        ip_address,
+        request_id,
+        payload
+    )
+VALUES
+    ($1, $2, $3, $4, $5, $6, $7)
+RETURNING
+    id,
+    actor_user_id,
+    action,
+    target_type,
+    target_id,
+    ip_address,
+    request_id,
+    payload,
+    created_at;
@@ -0,0 +1,37 @@
+-- List audit log entries page by page, newest first
+-- $1: action, $2: actor user id, $3: target id, $4: created on or after
+-- $5: limit, $6: offset
+SELECT
+    id,
+    actor_user_id,
+    action,
+    target_type,
+    target_id,
+    ip_address,
+    request_id,
+    payload,
+    created_at
+FROM
+    audit_logs
+WHERE
+    (
+        $1::TEXT IS NULL
+        OR action = $1
+    )
+    AND (
+        $2::UUID IS NULL
+        OR actor_user_id = $2
+    )
+    AND (
+        $3::UUID IS NULL
+        OR target_id = $3
+    )
+    AND (
+        $4::TIMESTAMPTZ IS NULL
+        OR created_at >= $4
+    )
+ORDER BY
+    created_at DESC,
+    id
+LIMIT
+    $5 OFFSET $6;
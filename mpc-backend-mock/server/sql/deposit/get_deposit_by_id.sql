@@ -0,0 +1,14 @@
+-- Get deposit by ID
+SELECT
+    id,
+    wallet_id,
+    tx_id,
+    amount AS "amount: TokenAmount",
+    status AS "status: DepositStatus",
+    block_height,
+    created_at,
+    updated_at
+FROM
+    deposits
+WHERE
+    id = $1;
@@ -0,0 +1,14 @@
+-- Insert a new pending deposit
+INSERT INTO
+    deposits (wallet_id, tx_id, amount)
+VALUES
+    ($1, $2, $3)
+RETURNING
+    id,
+    wallet_id,
+    tx_id,
+    amount AS "amount: TokenAmount",
+    status AS "status: DepositStatus",
+    block_height,
+    created_at,
+    updated_at;
@@ -0,0 +1,19 @@
+-- List deposits of a wallet, newest first
+SELECT
+    id,
+    wallet_id,
+    tx_id,
+    amount AS "amount: TokenAmount",
+    status AS "status: DepositStatus",
+    block_height,
+    created_at,
+    updated_at
+FROM
+    deposits
+WHERE
+    wallet_id = $1
+ORDER BY
+    created_at DESC,
+    id
+LIMIT
+    $2 OFFSET $3;
@@ -0,0 +1,17 @@
+-- Update the status of a deposit, keeping the block height if $3 is NULL
+UPDATE
+    deposits
+SET
+    status = $2,
+    block_height = COALESCE($3, block_height)
+WHERE
+    id = $1
+RETURNING
+    id,
+    wallet_id,
+    tx_id,
+    amount AS "amount: TokenAmount",
+    status AS "status: DepositStatus",
+    block_height,
+    created_at,
+    updated_at;
@@ -0,0 +1,13 @@
+-- Mark an unused and unexpired email change as confirmed
+-- $1: token hash
+UPDATE
+    email_change_requests
+SET
+    used_at = NOW()
+WHERE
+    token_hash = $1
+    AND used_at IS NULL
+    AND expires_at > NOW()
+RETURNING
+    user_id,
+    new_email;
@@ -0,0 +1,4 @@
+-- Delete expired email changes, confirmed or not
+DELETE FROM email_change_requests
+WHERE
+    expires_at < NOW();
@@ -0,0 +1,12 @@
+-- Stage a new email of a user, replacing the pending changes of the user
+-- $1: user id, $2: new email, $3: token hash, $4: expiry
+WITH deleted_pending_requests AS (
+    DELETE FROM email_change_requests
+    WHERE
+        user_id = $1
+        AND used_at IS NULL
+)
+INSERT INTO
+    email_change_requests (user_id, new_email, token_hash, expires_at)
+VALUES
+    ($1, $2, $3, $4);
@@ -0,0 +1,8 @@
+-- Write a domain event to the outbox
+-- $1: kind, $2: aggregate id, $3: payload
+INSERT INTO
+    event_outbox (kind, aggregate_id, payload)
+VALUES
+    ($1, $2, $3)
+RETURNING
+    id;
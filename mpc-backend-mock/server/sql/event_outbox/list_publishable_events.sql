@@ -0,0 +1,22 @@
+-- List the events after an offset, in the order they are published
+-- Events of transactions which may still be running are held back, so an event
+-- committed late never lands behind the offset
+-- $1: last transaction id, $2: last sequence, $3: limit
+SELECT
+    id,
+    sequence,
+    transaction_id,
+    kind,
+    aggregate_id,
+    payload,
+    created_at
+FROM
+    event_outbox
+WHERE
+    (transaction_id, sequence) > ($1::BIGINT, $2::BIGINT)
+    AND transaction_id < pg_snapshot_xmin(pg_current_snapshot())::TEXT::BIGINT
+ORDER BY
+    transaction_id,
+    sequence
+LIMIT
+    $3;
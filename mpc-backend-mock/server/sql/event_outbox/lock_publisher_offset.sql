@@ -0,0 +1,13 @@
+-- Lock the offset of a publisher, starting it at the beginning of the outbox
+-- Concurrent publishers of the same name wait for the lock
+-- $1: publisher
+INSERT INTO
+    event_publisher_offsets (publisher)
+VALUES
+    ($1)
+ON CONFLICT (publisher) DO UPDATE
+SET
+    publisher = EXCLUDED.publisher
+RETURNING
+    last_transaction_id,
+    last_sequence;
@@ -0,0 +1,10 @@
+-- Move the offset of a publisher past the events it published
+-- $1: publisher, $2: last transaction id, $3: last sequence
+UPDATE
+    event_publisher_offsets
+SET
+    last_transaction_id = $2,
+    last_sequence = $3,
+    updated_at = NOW()
+WHERE
+    publisher = $1;
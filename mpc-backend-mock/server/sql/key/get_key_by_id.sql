@@ -0,0 +1,13 @@
+-- Get a key by ID, only if it belongs to the user
+SELECT
+    id,
+    user_id,
+    chain AS "chain: Chain",
+    curve AS "curve: KeyCurve",
+    public_key,
+    created_at
+FROM
+    keys
+WHERE
+    id = $1
+    AND user_id = $2;
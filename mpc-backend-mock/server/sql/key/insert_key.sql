@@ -0,0 +1,16 @@
+-- Insert the key of a user on a chain, returning the existing key if the user
+-- already has one there
+INSERT INTO
+    keys (user_id, chain, curve, public_key)
+VALUES
+    ($1, $2, $3, $4)
+ON CONFLICT (user_id, chain) DO UPDATE
+SET
+    user_id = keys.user_id
+RETURNING
+    id,
+    user_id,
+    chain AS "chain: Chain",
+    curve AS "curve: KeyCurve",
+    public_key,
+    created_at;
@@ -0,0 +1,16 @@
+-- Delete ended lockouts and the failed logins whose failure window has passed
+-- $1: failure window in seconds
+DELETE FROM login_attempts
+WHERE
+    locked_until <= NOW()
+    OR (
+        locked_at IS NULL
+        AND first_failed_at < NOW() - make_interval(secs => $1)
+    )
+RETURNING
+    email,
+    failed_attempts,
+    last_failed_at,
+    last_failed_ip,
+    locked_at,
+    locked_until;
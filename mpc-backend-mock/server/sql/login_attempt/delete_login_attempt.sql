@@ -0,0 +1,12 @@
+-- Delete the failed logins and the lockout of an email
+-- $1: email
+DELETE FROM login_attempts
+WHERE
+    email = $1
+RETURNING
+    email,
+    failed_attempts,
+    last_failed_at,
+    last_failed_ip,
+    locked_at,
+    locked_until;
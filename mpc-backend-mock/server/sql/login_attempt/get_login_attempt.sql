@@ -0,0 +1,13 @@
+-- Get the failed logins and the lockout of an email
+-- $1: email
+SELECT
+    email,
+    failed_attempts,
+    last_failed_at,
+    last_failed_ip,
+    locked_at,
+    locked_until
+FROM
+    login_attempts
+WHERE
+    email = $1;
@@ -0,0 +1,18 @@
+-- List the emails which are locked, most recently locked first
+SELECT
+    email,
+    failed_attempts,
+    last_failed_at,
+    last_failed_ip,
+    locked_at,
+    locked_until
+FROM
+    login_attempts
+WHERE
+    locked_at IS NOT NULL
+    AND (
+        locked_until IS NULL
+        OR locked_until > NOW()
+    )
+ORDER BY
+    locked_at DESC;
@@ -0,0 +1,16 @@
+-- Lock an email which is not locked yet, returns nothing if it already is
+-- $1: email, $2: lockout in seconds, NULL locks the email until it is unlocked
+UPDATE login_attempts
+SET
+    locked_at = NOW(),
+    locked_until = NOW() + make_interval(secs => $2)
+WHERE
+    email = $1
+    AND locked_at IS NULL
+RETURNING
+    email,
+    failed_attempts,
+    last_failed_at,
+    last_failed_ip,
+    locked_at,
+    locked_until;
@@ -0,0 +1,26 @@
+-- Count a failed login of an email, the count starts over once the failure
+-- window has passed
+-- $1: email, $2: client IP, $3: failure window in seconds
+INSERT INTO
+    login_attempts (email, failed_attempts, first_failed_at, last_failed_at, last_failed_ip)
+VALUES
+    ($1, 1, NOW(), NOW(), $2)
+ON CONFLICT (email) DO UPDATE
+SET
+    failed_attempts = CASE
+        WHEN login_attempts.first_failed_at < NOW() - make_interval(secs => $3) THEN 1
+        ELSE login_attempts.failed_attempts + 1
+    END,
+    first_failed_at = CASE
+        WHEN login_attempts.first_failed_at < NOW() - make_interval(secs => $3) THEN NOW()
+        ELSE login_attempts.first_failed_at
+    END,
+    last_failed_at = NOW(),
+    last_failed_ip = EXCLUDED.last_failed_ip
+RETURNING
+    email,
+    failed_attempts,
+    last_failed_at,
+    last_failed_ip,
+    locked_at,
+    locked_until;
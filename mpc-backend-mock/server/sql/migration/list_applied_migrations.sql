@@ -0,0 +1,6 @@
+-- List the migrations applied by sqlx and whether they succeeded
+SELECT
+    version,
+    success
+FROM
+    _sqlx_migrations;
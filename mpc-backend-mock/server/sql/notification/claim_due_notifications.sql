@@ -0,0 +1,25 @@
+-- Lock pending notifications whose next attempt is due, oldest first
+-- Rows locked by another dispatcher are skipped
+-- $1: limit
+SELECT
+    id,
+    kind,
+    recipient,
+    payload,
+    status AS "status: NotificationStatus",
+    attempts,
+    last_error,
+    next_attempt_at,
+    sent_at,
+    created_at,
+    updated_at
+FROM
+    notification_outbox
+WHERE
+    status = 'pending'
+    AND next_attempt_at <= NOW()
+ORDER BY
+    next_attempt_at,
+    id
+LIMIT
+    $1 FOR UPDATE SKIP LOCKED;
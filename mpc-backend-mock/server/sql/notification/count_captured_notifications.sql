@@ -0,0 +1,5 @@
+-- Count captured emails
+SELECT
+    COUNT(*) AS "count!"
+FROM
+    captured_notifications;
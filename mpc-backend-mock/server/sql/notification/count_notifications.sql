@@ -0,0 +1,11 @@
+-- Count queued notifications
+-- $1: status
+SELECT
+    COUNT(*) AS "count!"
+FROM
+    notification_outbox
+WHERE
+    (
+        $1::notification_status IS NULL
+        OR status = $1
+    );
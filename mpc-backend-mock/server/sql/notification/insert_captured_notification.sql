@@ -0,0 +1,5 @@
+-- Record an email which would have been sent
+INSERT INTO
+    captured_notifications (kind, recipient, subject, html_body, payload)
+VALUES
+    ($1, $2, $3, $4, $5);
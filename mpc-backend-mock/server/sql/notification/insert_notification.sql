@@ -0,0 +1,7 @@
+-- Queue a notification for delivery
+INSERT INTO
+    notification_outbox (kind, recipient, payload)
+VALUES
+    ($1, $2, $3)
+RETURNING
+    id;
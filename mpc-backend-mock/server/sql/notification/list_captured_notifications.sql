@@ -0,0 +1,16 @@
+-- List captured emails, newest first
+-- $1: limit, $2: offset
+SELECT
+    id,
+    kind,
+    recipient,
+    subject,
+    html_body,
+    created_at
+FROM
+    captured_notifications
+ORDER BY
+    created_at DESC,
+    id DESC
+LIMIT
+    $1 OFFSET $2;
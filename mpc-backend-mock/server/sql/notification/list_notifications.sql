@@ -0,0 +1,26 @@
+-- List queued notifications, newest first
+-- $1: status, $2: limit, $3: offset
+SELECT
+    id,
+    kind,
+    recipient,
+    payload,
+    status AS "status: NotificationStatus",
+    attempts,
+    last_error,
+    next_attempt_at,
+    sent_at,
+    created_at,
+    updated_at
+FROM
+    notification_outbox
+WHERE
+    (
+        $1::notification_status IS NULL
+        OR status = $1
+    )
+ORDER BY
+    created_at DESC,
+    id DESC
+LIMIT
+    $2 OFFSET $3;
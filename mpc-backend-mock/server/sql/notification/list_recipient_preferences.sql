@@ -0,0 +1,14 @@
+-- List the notification preferences of the users with the given emails,
+-- users without preferences are left out (excluding soft-deleted users)
+-- $1: emails
+SELECT
+    users.email,
+    user_preferences.email_enabled,
+    user_preferences.webhook_url,
+    user_preferences.locale
+FROM
+    users
+    JOIN user_preferences ON user_preferences.user_id = users.id
+WHERE
+    users.email = ANY($1)
+    AND users.deleted_at IS NULL;
@@ -0,0 +1,9 @@
+-- Mark a notification as sent
+UPDATE
+    notification_outbox
+SET
+    status = 'sent',
+    attempts = attempts + 1,
+    sent_at = NOW()
+WHERE
+    id = $1;
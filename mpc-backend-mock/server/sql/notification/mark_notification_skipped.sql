@@ -0,0 +1,8 @@
+-- Mark a notification as skipped, none of its recipients wants it delivered
+UPDATE
+    notification_outbox
+SET
+    status = 'skipped',
+    attempts = attempts + 1
+WHERE
+    id = $1;
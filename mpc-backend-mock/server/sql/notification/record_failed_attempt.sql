@@ -0,0 +1,14 @@
+-- Record a failed delivery attempt of a notification
+-- $1: id, $2: error, $3: next attempt, NULL to dead-letter the notification
+UPDATE
+    notification_outbox
+SET
+    status = CASE
+        WHEN $3::TIMESTAMPTZ IS NULL THEN 'dead'::notification_status
+        ELSE status
+    END,
+    attempts = attempts + 1,
+    last_error = $2,
+    next_attempt_at = COALESCE($3, next_attempt_at)
+WHERE
+    id = $1;
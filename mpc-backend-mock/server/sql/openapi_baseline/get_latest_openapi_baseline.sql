@@ -0,0 +1,11 @@
+-- Get the most recently uploaded OpenAPI baseline
+SELECT
+    id,
+    document,
+    created_at
+FROM
+    openapi_baselines
+ORDER BY
+    created_at DESC
+LIMIT
+    1;
@@ -0,0 +1,8 @@
+-- Store an uploaded OpenAPI document as the new baseline
+INSERT INTO
+    openapi_baselines (document)
+VALUES
+    ($1)
+RETURNING
+    id,
+    created_at;
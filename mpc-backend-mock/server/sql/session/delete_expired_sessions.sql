@@ -0,0 +1,4 @@
+-- Delete expired sessions
+DELETE FROM sessions
+WHERE
+    expires_at < NOW();
@@ -0,0 +1,5 @@
+-- Delete a session, e.g. on logout
+-- $1: token hash
+DELETE FROM sessions
+WHERE
+    token_hash = $1;
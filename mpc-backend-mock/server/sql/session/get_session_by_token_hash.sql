@@ -0,0 +1,17 @@
+-- Get an unexpired session of a user who is not deleted
+-- $1: token hash
+SELECT
+    sessions.id,
+    sessions.user_id,
+    users.email,
+    users.keycloak_user_id,
+    users.is_active,
+    sessions.csrf_token_hash,
+    sessions.expires_at
+FROM
+    sessions
+    JOIN users ON users.id = sessions.user_id
+WHERE
+    sessions.token_hash = $1
+    AND sessions.expires_at > NOW()
+    AND users.deleted_at IS NULL;
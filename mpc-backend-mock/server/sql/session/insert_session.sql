@@ -0,0 +1,6 @@
+-- Insert a new session
+-- $1: user id, $2: token hash, $3: CSRF token hash, $4: expiry
+INSERT INTO
+    sessions (user_id, token_hash, csrf_token_hash, expires_at)
+VALUES
+    ($1, $2, $3, $4);
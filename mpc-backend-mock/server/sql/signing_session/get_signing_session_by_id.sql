@@ -0,0 +1,17 @@
+-- Get a signing session by ID, only if it belongs to the user
+SELECT
+    id,
+    user_id,
+    payload,
+    status AS "status: SigningSessionStatus",
+    round_duration_ms,
+    fail_at AS "fail_at: SigningSessionStatus",
+    signature,
+    error,
+    created_at,
+    updated_at
+FROM
+    signing_sessions
+WHERE
+    id = $1
+    AND user_id = $2;
@@ -0,0 +1,16 @@
+-- Insert a new signing session in its first round
+INSERT INTO
+    signing_sessions (user_id, payload, round_duration_ms, fail_at)
+VALUES
+    ($1, $2, $3, $4)
+RETURNING
+    id,
+    user_id,
+    payload,
+    status AS "status: SigningSessionStatus",
+    round_duration_ms,
+    fail_at AS "fail_at: SigningSessionStatus",
+    signature,
+    error,
+    created_at,
+    updated_at;
@@ -0,0 +1,22 @@
+-- Advance a signing session, only from the status it was read with so that
+-- concurrent polls advance it once
+UPDATE
+    signing_sessions
+SET
+    status = $3,
+    signature = $4,
+    error = $5
+WHERE
+    id = $1
+    AND status = $2
+RETURNING
+    id,
+    user_id,
+    payload,
+    status AS "status: SigningSessionStatus",
+    round_duration_ms,
+    fail_at AS "fail_at: SigningSessionStatus",
+    signature,
+    error,
+    created_at,
+    updated_at;
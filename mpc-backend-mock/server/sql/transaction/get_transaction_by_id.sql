@@ -0,0 +1,15 @@
+-- Get a transaction by ID, only if it belongs to the user
+SELECT
+    id,
+    user_id,
+    signature,
+    status AS "status: TransactionStatus",
+    slot,
+    error,
+    created_at,
+    updated_at
+FROM
+    transactions
+WHERE
+    id = $1
+    AND user_id = $2;
@@ -0,0 +1,14 @@
+-- Insert a new pending transaction
+INSERT INTO
+    transactions (user_id, signature)
+VALUES
+    ($1, $2)
+RETURNING
+    id,
+    user_id,
+    signature,
+    status AS "status: TransactionStatus",
+    slot,
+    error,
+    created_at,
+    updated_at;
@@ -0,0 +1,18 @@
+-- Update the status of a transaction, keeping the slot and error if $3 or $4 is NULL
+UPDATE
+    transactions
+SET
+    status = $2,
+    slot = COALESCE($3, slot),
+    error = COALESCE($4, error)
+WHERE
+    id = $1
+RETURNING
+    id,
+    user_id,
+    signature,
+    status AS "status: TransactionStatus",
+    slot,
+    error,
+    created_at,
+    updated_at;
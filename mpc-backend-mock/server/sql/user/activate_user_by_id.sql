@@ -0,0 +1,17 @@
+-- Activate a user which is not soft-deleted
+UPDATE
+    users
+SET
+    is_active = TRUE
+WHERE
+    id = $1
+    AND deleted_at IS NULL
+RETURNING
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at;
@@ -0,0 +1,21 @@
+-- Count users of realm $4 matching the list filters, NULL for the primary realm
+-- (excluding soft-deleted users)
+SELECT
+    COUNT(*) AS "count!"
+FROM
+    users
+WHERE
+    deleted_at IS NULL
+    AND realm IS NOT DISTINCT FROM $4
+    AND (
+        $1::TEXT IS NULL
+        OR email ILIKE '%' || $1 || '%'
+    )
+    AND (
+        $2::BOOLEAN IS NULL
+        OR is_active = $2
+    )
+    AND (
+        $3::TIMESTAMPTZ IS NULL
+        OR created_at >= $3
+    );
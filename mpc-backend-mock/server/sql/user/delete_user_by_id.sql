@@ -1,4 +0,0 @@
-DELETE FROM
-    users
-WHERE
-    id = $1;
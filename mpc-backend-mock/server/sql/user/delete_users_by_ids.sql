@@ -0,0 +1,107 @@
+-- Permanently delete users together with their profiles, preferences,
+-- wallets, deposits, withdrawals, balance snapshots, transactions, activation
+-- tokens, email changes, sessions, webhooks and the annotations on them and
+-- their transactions, audit logs they acted in are kept without an actor
+-- $1: user ids
+WITH user_wallets AS (
+    SELECT
+        id
+    FROM
+        wallets
+    WHERE
+        user_id = ANY($1)
+),
+deleted_deposits AS (
+    DELETE FROM deposits
+    WHERE
+        wallet_id IN (
+            SELECT
+                id
+            FROM
+                user_wallets
+        )
+),
+deleted_withdrawals AS (
+    DELETE FROM withdrawals
+    WHERE
+        wallet_id IN (
+            SELECT
+                id
+            FROM
+                user_wallets
+        )
+),
+deleted_wallet_balance_snapshots AS (
+    DELETE FROM wallet_balance_snapshots
+    WHERE
+        wallet_id IN (
+            SELECT
+                id
+            FROM
+                user_wallets
+        )
+),
+deleted_annotations AS (
+    DELETE FROM annotations
+    WHERE
+        user_id = ANY($1)
+        OR transaction_id IN (
+            SELECT
+                id
+            FROM
+                transactions
+            WHERE
+                user_id = ANY($1)
+        )
+),
+deleted_transactions AS (
+    DELETE FROM transactions
+    WHERE
+        user_id = ANY($1)
+),
+deleted_activation_tokens AS (
+    DELETE FROM activation_tokens
+    WHERE
+        user_id = ANY($1)
+),
+deleted_email_change_requests AS (
+    DELETE FROM email_change_requests
+    WHERE
+        user_id = ANY($1)
+),
+deleted_sessions AS (
+    DELETE FROM sessions
+    WHERE
+        user_id = ANY($1)
+),
+deleted_webhooks AS (
+    DELETE FROM webhooks
+    WHERE
+        user_id = ANY($1)
+),
+deleted_user_profiles AS (
+    DELETE FROM user_profiles
+    WHERE
+        user_id = ANY($1)
+),
+deleted_user_preferences AS (
+    DELETE FROM user_preferences
+    WHERE
+        user_id = ANY($1)
+),
+deleted_wallets AS (
+    DELETE FROM wallets
+    WHERE
+        user_id = ANY($1)
+),
+detached_audit_logs AS (
+    UPDATE
+        audit_logs
+    SET
+        actor_user_id = NULL
+    WHERE
+        actor_user_id = ANY($1)
+)
+DELETE FROM users
+WHERE
+    id = ANY($1);
@@ -0,0 +1,18 @@
+-- Export all users of realm $1, NULL for the primary realm (including
+-- soft-deleted users), oldest first
+SELECT
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at
+FROM
+    users
+WHERE
+    realm IS NOT DISTINCT FROM $1
+ORDER BY
+    created_at,
+    id;
@@ -1,8 +1,10 @@
--- Get user by email (excluding soft-deleted users)
+-- Get user by email in realm $3, NULL for the primary realm (soft-deleted users
+-- are only included if $2 is true)
 SELECT
     id,
     email,
     keycloak_user_id,
+    realm,
     is_active,
     created_at,
     updated_at,
@@ -11,4 +13,8 @@ FROM
     users
 WHERE
     email = $1
-    AND deleted_at IS NULL;
+    AND realm IS NOT DISTINCT FROM $3
+    AND (
+        $2
+        OR deleted_at IS NULL
+    );
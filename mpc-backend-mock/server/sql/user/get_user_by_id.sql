@@ -1,8 +1,9 @@
--- Get user by ID (excluding soft-deleted users)
+-- Get user by ID (soft-deleted users are only included if $2 is true)
 SELECT
     id,
     email,
     keycloak_user_id,
+    realm,
     is_active,
     created_at,
     updated_at,
@@ -11,4 +12,7 @@ FROM
     users
 WHERE
     id = $1
-    AND deleted_at IS NULL;
+    AND (
+        $2
+        OR deleted_at IS NULL
+    );
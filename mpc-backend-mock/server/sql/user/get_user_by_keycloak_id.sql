@@ -1,7 +1,10 @@
+-- Get user by Keycloak user ID and realm, NULL for the primary realm
+-- (soft-deleted users are only included if $2 is true)
 SELECT
     id,
     email,
     keycloak_user_id,
+    realm,
     is_active,
     created_at,
     updated_at,
@@ -10,4 +13,8 @@ FROM
     users
 WHERE
     keycloak_user_id = $1
-    AND deleted_at IS NULL;
+    AND realm IS NOT DISTINCT FROM $3
+    AND (
+        $2
+        OR deleted_at IS NULL
+    );
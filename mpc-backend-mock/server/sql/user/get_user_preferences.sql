@@ -0,0 +1,11 @@
+-- Get the notification preferences of a user, NULL until the user sets them
+SELECT
+    user_id,
+    email_enabled,
+    webhook_url,
+    locale,
+    updated_at AS "updated_at?"
+FROM
+    user_preferences
+WHERE
+    user_id = $1;
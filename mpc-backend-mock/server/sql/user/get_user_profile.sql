@@ -0,0 +1,10 @@
+-- Get the profile of a user, NULL until the user updates it
+SELECT
+    user_id,
+    display_name,
+    locale,
+    updated_at
+FROM
+    user_profiles
+WHERE
+    user_id = $1;
@@ -1,12 +1,13 @@
--- Insert a new user into the database
+-- Insert a new user of realm $4 into the database, NULL for the primary realm
 INSERT INTO
-    users (email, keycloak_user_id, is_active)
+    users (email, keycloak_user_id, is_active, realm)
 VALUES
-    ($1, $2, $3)
+    ($1, $2, $3, $4)
 RETURNING
     id,
     email,
     keycloak_user_id,
+    realm,
     is_active,
     created_at,
     updated_at,
@@ -0,0 +1,27 @@
+-- List users of a realm whose email matches a LIKE pattern (including
+-- soft-deleted users)
+-- $1: LIKE pattern with `\` as escape character, $2: created before
+-- $3: limit, $4: offset, $5: realm, NULL for the primary realm
+SELECT
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at
+FROM
+    users
+WHERE
+    email LIKE $1 ESCAPE '\'
+    AND realm IS NOT DISTINCT FROM $5
+    AND (
+        $2::TIMESTAMPTZ IS NULL
+        OR created_at < $2
+    )
+ORDER BY
+    created_at,
+    id
+LIMIT
+    $3 OFFSET $4;
@@ -0,0 +1,15 @@
+-- List users by Keycloak user IDs (including soft-deleted users)
+-- $1: Keycloak user IDs
+SELECT
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at
+FROM
+    users
+WHERE
+    keycloak_user_id = ANY ($1);
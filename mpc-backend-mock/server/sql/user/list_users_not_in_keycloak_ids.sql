@@ -0,0 +1,22 @@
+-- List users of the primary realm whose Keycloak user ID is not one of the
+-- given IDs (excluding soft-deleted users)
+-- $1: Keycloak user IDs, $2: created before
+SELECT
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at
+FROM
+    users
+WHERE
+    deleted_at IS NULL
+    AND realm IS NULL
+    AND keycloak_user_id <> ALL ($1)
+    AND created_at < $2
+ORDER BY
+    created_at,
+    id;
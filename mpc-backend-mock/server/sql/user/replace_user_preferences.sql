@@ -0,0 +1,18 @@
+-- Replace the notification preferences of a user
+-- $1: user id, $2: email enabled, $3: webhook url, $4: locale
+INSERT INTO
+    user_preferences (user_id, email_enabled, webhook_url, locale)
+VALUES
+    ($1, $2, $3, $4)
+ON CONFLICT (user_id) DO UPDATE
+SET
+    email_enabled = $2,
+    webhook_url = $3,
+    locale = $4,
+    updated_at = NOW()
+RETURNING
+    user_id,
+    email_enabled,
+    webhook_url,
+    locale,
+    updated_at AS "updated_at?";
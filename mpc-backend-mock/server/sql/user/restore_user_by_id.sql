@@ -0,0 +1,17 @@
+-- Restore a soft-deleted user by clearing deleted_at
+UPDATE
+    users
+SET
+    deleted_at = NULL
+WHERE
+    id = $1
+    AND deleted_at IS NOT NULL
+RETURNING
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at;
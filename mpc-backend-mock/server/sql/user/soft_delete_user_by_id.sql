@@ -0,0 +1,8 @@
+-- Soft delete a user by setting deleted_at
+UPDATE
+    users
+SET
+    deleted_at = NOW()
+WHERE
+    id = $1
+    AND deleted_at IS NULL;
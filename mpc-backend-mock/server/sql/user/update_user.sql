@@ -0,0 +1,17 @@
+-- Update the profile of a user, creating it with the defaults first, the
+-- fields which are NULL are kept
+-- $1: user id, $2: display name, $3: locale
+INSERT INTO
+    user_profiles (user_id, display_name, locale)
+VALUES
+    ($1, $2, $3)
+ON CONFLICT (user_id) DO UPDATE
+SET
+    display_name = COALESCE($2, user_profiles.display_name),
+    locale = COALESCE($3, user_profiles.locale),
+    updated_at = NOW()
+RETURNING
+    user_id,
+    display_name,
+    locale,
+    updated_at;
@@ -0,0 +1,18 @@
+-- Change the email of a user which is not soft-deleted
+-- $1: user id, $2: new email
+UPDATE
+    users
+SET
+    email = $2
+WHERE
+    id = $1
+    AND deleted_at IS NULL
+RETURNING
+    id,
+    email,
+    keycloak_user_id,
+    realm,
+    is_active,
+    created_at,
+    updated_at,
+    deleted_at;
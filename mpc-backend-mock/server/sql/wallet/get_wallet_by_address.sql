@@ -0,0 +1,13 @@
+-- Get wallet by chain and address
+SELECT
+    id,
+    user_id,
+    chain AS "chain: Chain",
+    address,
+    created_at,
+    updated_at
+FROM
+    wallets
+WHERE
+    chain = $1
+    AND address = $2;
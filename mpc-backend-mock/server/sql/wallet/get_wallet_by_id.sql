@@ -0,0 +1,12 @@
+-- Get wallet by ID
+SELECT
+    id,
+    user_id,
+    chain AS "chain: Chain",
+    address,
+    created_at,
+    updated_at
+FROM
+    wallets
+WHERE
+    id = $1;
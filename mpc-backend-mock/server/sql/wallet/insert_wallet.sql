@@ -0,0 +1,12 @@
+-- Insert a new wallet for a user
+INSERT INTO
+    wallets (user_id, chain, address)
+VALUES
+    ($1, $2, $3)
+RETURNING
+    id,
+    user_id,
+    chain AS "chain: Chain",
+    address,
+    created_at,
+    updated_at;
@@ -0,0 +1,15 @@
+-- List wallets of a user, oldest first
+SELECT
+    id,
+    user_id,
+    chain AS "chain: Chain",
+    address,
+    created_at,
+    updated_at
+FROM
+    wallets
+WHERE
+    user_id = $1
+ORDER BY
+    created_at,
+    id;
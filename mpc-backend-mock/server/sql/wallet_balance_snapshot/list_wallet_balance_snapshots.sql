@@ -0,0 +1,24 @@
+-- List balance snapshots of a wallet, oldest first, from the last snapshot on
+-- or before $2 until $3, so that the balance on $2 is known
+SELECT
+    snapshot_date AS date,
+    balance AS "balance: TokenAmount"
+FROM
+    wallet_balance_snapshots
+WHERE
+    wallet_id = $1
+    AND snapshot_date <= $3
+    AND snapshot_date >= COALESCE(
+        (
+            SELECT
+                MAX(snapshot_date)
+            FROM
+                wallet_balance_snapshots
+            WHERE
+                wallet_id = $1
+                AND snapshot_date <= $2
+        ),
+        $2
+    )
+ORDER BY
+    snapshot_date;
@@ -0,0 +1,38 @@
+-- Snapshot the balance of every wallet for the current day (UTC), replacing
+-- an earlier snapshot of the same day
+-- The balance is the sum of confirmed deposits minus the withdrawals which did
+-- not fail
+INSERT INTO
+    wallet_balance_snapshots (wallet_id, snapshot_date, balance)
+SELECT
+    wallets.id,
+    (NOW() AT TIME ZONE 'UTC')::DATE,
+    GREATEST(COALESCE(deposited.amount, 0) - COALESCE(withdrawn.amount, 0), 0)
+FROM
+    wallets
+    LEFT JOIN (
+        SELECT
+            wallet_id,
+            SUM(amount) AS amount
+        FROM
+            deposits
+        WHERE
+            status = 'confirmed'
+        GROUP BY
+            wallet_id
+    ) AS deposited ON deposited.wallet_id = wallets.id
+    LEFT JOIN (
+        SELECT
+            wallet_id,
+            SUM(amount) AS amount
+        FROM
+            withdrawals
+        WHERE
+            status <> 'failed'
+        GROUP BY
+            wallet_id
+    ) AS withdrawn ON withdrawn.wallet_id = wallets.id
+ON CONFLICT (wallet_id, snapshot_date) DO
+UPDATE
+SET
+    balance = EXCLUDED.balance;
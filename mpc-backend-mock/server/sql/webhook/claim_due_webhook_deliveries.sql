@@ -0,0 +1,53 @@
+-- Claim pending deliveries whose next attempt is due, oldest first, with the
+-- webhook to post them to
+-- A claimed delivery is not due again before its lease ends, so it is not
+-- claimed by another dispatcher while it is posted
+-- Rows locked by another dispatcher are skipped
+-- $1: limit, $2: end of the lease
+WITH due AS (
+    SELECT
+        id,
+        next_attempt_at
+    FROM
+        webhook_deliveries
+    WHERE
+        status = 'pending'
+        AND next_attempt_at <= NOW()
+    ORDER BY
+        next_attempt_at,
+        id
+    LIMIT
+        $1 FOR UPDATE SKIP LOCKED
+),
+claimed AS (
+    UPDATE
+        webhook_deliveries
+    SET
+        next_attempt_at = $2
+    FROM
+        due
+    WHERE
+        webhook_deliveries.id = due.id
+    RETURNING
+        webhook_deliveries.id,
+        webhook_deliveries.webhook_id,
+        webhook_deliveries.event_type,
+        webhook_deliveries.payload,
+        webhook_deliveries.attempts,
+        webhook_deliveries.created_at,
+        due.next_attempt_at
+)
+SELECT
+    claimed.id AS "id!",
+    webhooks.url AS "url!",
+    webhooks.secret AS "secret!",
+    claimed.event_type AS "event_type!",
+    claimed.payload AS "payload!",
+    claimed.attempts AS "attempts!",
+    claimed.created_at AS "created_at!"
+FROM
+    claimed
+    JOIN webhooks ON webhooks.id = claimed.webhook_id
+ORDER BY
+    claimed.next_attempt_at,
+    claimed.id;
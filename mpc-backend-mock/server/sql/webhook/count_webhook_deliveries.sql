@@ -0,0 +1,8 @@
+-- Count the deliveries of a webhook
+-- $1: webhook id
+SELECT
+    COUNT(*) AS "count!"
+FROM
+    webhook_deliveries
+WHERE
+    webhook_id = $1;
@@ -0,0 +1,14 @@
+-- Get a webhook of a user
+-- $1: webhook id, $2: user id
+SELECT
+    id,
+    user_id,
+    url,
+    event_types,
+    secret,
+    created_at
+FROM
+    webhooks
+WHERE
+    id = $1
+    AND user_id = $2;
@@ -0,0 +1,13 @@
+-- Register a webhook of a user
+-- $1: user id, $2: url, $3: event types, $4: secret
+INSERT INTO
+    webhooks (user_id, url, event_types, secret)
+VALUES
+    ($1, $2, $3, $4)
+RETURNING
+    id,
+    user_id,
+    url,
+    event_types,
+    secret,
+    created_at;
@@ -0,0 +1,14 @@
+-- Queue an event of a user for delivery to every webhook of the user
+-- subscribing to its type
+-- $1: user id, $2: event type, $3: payload
+INSERT INTO
+    webhook_deliveries (webhook_id, event_type, payload)
+SELECT
+    id,
+    $2::VARCHAR,
+    $3
+FROM
+    webhooks
+WHERE
+    user_id = $1
+    AND $2 = ANY(event_types);
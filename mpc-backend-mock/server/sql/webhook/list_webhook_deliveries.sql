@@ -0,0 +1,24 @@
+-- List the deliveries of a webhook, newest first
+-- $1: webhook id, $2: limit, $3: offset
+SELECT
+    id,
+    webhook_id,
+    event_type,
+    payload,
+    status AS "status: WebhookDeliveryStatus",
+    attempts,
+    response_status,
+    last_error,
+    next_attempt_at,
+    delivered_at,
+    created_at,
+    updated_at
+FROM
+    webhook_deliveries
+WHERE
+    webhook_id = $1
+ORDER BY
+    created_at DESC,
+    id DESC
+LIMIT
+    $2 OFFSET $3;
@@ -0,0 +1,11 @@
+-- Mark a webhook delivery as delivered
+-- $1: id, $2: response status
+UPDATE
+    webhook_deliveries
+SET
+    status = 'delivered',
+    attempts = attempts + 1,
+    response_status = $2,
+    delivered_at = NOW()
+WHERE
+    id = $1;
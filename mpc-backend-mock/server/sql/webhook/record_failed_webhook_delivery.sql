@@ -0,0 +1,16 @@
+-- Record a failed attempt of a webhook delivery
+-- $1: id, $2: response status, NULL if the webhook did not answer, $3: error,
+-- $4: next attempt, NULL to dead-letter the delivery
+UPDATE
+    webhook_deliveries
+SET
+    status = CASE
+        WHEN $4::TIMESTAMPTZ IS NULL THEN 'dead'::webhook_delivery_status
+        ELSE status
+    END,
+    attempts = attempts + 1,
+    response_status = $2,
+    last_error = $3,
+    next_attempt_at = COALESCE($4, next_attempt_at)
+WHERE
+    id = $1;
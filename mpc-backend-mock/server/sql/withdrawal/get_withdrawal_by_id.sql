@@ -0,0 +1,14 @@
+-- Get withdrawal by ID
+SELECT
+    id,
+    wallet_id,
+    destination_address,
+    amount AS "amount: TokenAmount",
+    status AS "status: WithdrawalStatus",
+    tx_id,
+    created_at,
+    updated_at
+FROM
+    withdrawals
+WHERE
+    id = $1;
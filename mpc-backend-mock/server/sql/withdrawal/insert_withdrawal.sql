@@ -0,0 +1,14 @@
+-- Insert a new pending withdrawal
+INSERT INTO
+    withdrawals (wallet_id, destination_address, amount)
+VALUES
+    ($1, $2, $3)
+RETURNING
+    id,
+    wallet_id,
+    destination_address,
+    amount AS "amount: TokenAmount",
+    status AS "status: WithdrawalStatus",
+    tx_id,
+    created_at,
+    updated_at;
@@ -0,0 +1,19 @@
+-- List withdrawals of a wallet, newest first
+SELECT
+    id,
+    wallet_id,
+    destination_address,
+    amount AS "amount: TokenAmount",
+    status AS "status: WithdrawalStatus",
+    tx_id,
+    created_at,
+    updated_at
+FROM
+    withdrawals
+WHERE
+    wallet_id = $1
+ORDER BY
+    created_at DESC,
+    id
+LIMIT
+    $2 OFFSET $3;
@@ -0,0 +1,17 @@
+-- Update the status of a withdrawal, keeping the transaction ID if $3 is NULL
+UPDATE
+    withdrawals
+SET
+    status = $2,
+    tx_id = COALESCE($3, tx_id)
+WHERE
+    id = $1
+RETURNING
+    id,
+    wallet_id,
+    destination_address,
+    amount AS "amount: TokenAmount",
+    status AS "status: WithdrawalStatus",
+    tx_id,
+    created_at,
+    updated_at;
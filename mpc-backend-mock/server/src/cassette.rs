@@ -0,0 +1,212 @@
+//! Recording of the upstream responses, and replaying them without the
+//! upstreams.
+//!
+//! With `--record <dir>` the successful responses of Bitcoin, Solana and the
+//! Keycloak user directory are appended to one cassette file per upstream in
+//! `dir`, e.g. `bitcoin.json`. With `--replay <dir>` the same calls are
+//! answered from the cassettes without connecting to the upstreams: the
+//! responses of a call are replayed in the order they were recorded, and the
+//! last one is repeated once they are used up. A call which was never
+//! recorded fails with `NO_RECORDED_RESPONSE`.
+//!
+//! Logins and token validation are not recorded, recorded tokens would
+//! expire, so they still go to Keycloak while replaying.
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use snafu::ResultExt;
+use tokio::sync::Mutex;
+
+use crate::{
+    error::{self, Result},
+    service::error::{self as service_error, Error as ServiceError},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Call the upstreams and record their responses
+    Record,
+
+    /// Answer from the recorded responses, never calling the upstreams
+    Replay,
+}
+
+/// Cassettes of the server, in `dir`
+#[derive(Clone, Debug)]
+pub struct CassetteOptions {
+    pub mode: CassetteMode,
+
+    pub dir: PathBuf,
+}
+
+impl CassetteOptions {
+    /// Cassette of the upstream `name`, stored in `<dir>/<name>.json`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or an existing
+    /// cassette cannot be read
+    pub fn open(&self, name: &'static str) -> Result<Cassette> {
+        Cassette::open(&self.dir, name, self.mode)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// Response of an upstream to a request, named `<method> <arguments>`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: String,
+
+    response: serde_json::Value,
+}
+
+/// Recorded responses of an upstream, clones share the recording
+#[derive(Clone, Debug)]
+pub struct Cassette {
+    name: &'static str,
+    path: PathBuf,
+    tape: Arc<Mutex<Tape>>,
+}
+
+#[derive(Debug, Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+
+    /// Index of the next response to replay by request
+    cursors: HashMap<String, usize>,
+}
+
+impl Cassette {
+    fn open(dir: &Path, name: &'static str, mode: CassetteMode) -> Result<Self> {
+        let path = dir.join(format!("{name}.json"));
+        if mode == CassetteMode::Record {
+            std::fs::create_dir_all(dir)
+                .context(error::OpenCassetteSnafu { path: dir.to_path_buf() })?;
+        }
+
+        // recording again appends to the cassette
+        let file = match std::fs::read(&path) {
+            Ok(content) => serde_json::from_slice::<CassetteFile>(&content)
+                .context(error::ParseCassetteSnafu { path: path.clone() })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if mode == CassetteMode::Replay {
+                    tracing::warn!("No {name} cassette at {}, its calls fail", path.display());
+                }
+                CassetteFile::default()
+            }
+            Err(source) => return Err(error::Error::OpenCassette { path, source }),
+        };
+
+        let tape = Tape { interactions: file.interactions, cursors: HashMap::new() };
+        Ok(Self { name, path, tape: Arc::new(Mutex::new(tape)) })
+    }
+
+    /// Record `response` of `request`, failing to write the cassette is only
+    /// logged
+    pub async fn record<T: Serialize>(&self, request: String, response: &T) {
+        let response = match serde_json::to_value(response) {
+            Ok(response) => response,
+            Err(err) => {
+                tracing::warn!(
+                    "Not recording `{request}` in the {} cassette, error: {err}",
+                    self.name
+                );
+                return;
+            }
+        };
+
+        let mut tape = self.tape.lock().await;
+        tape.interactions.push(Interaction { request, response });
+        let file = CassetteFile { interactions: tape.interactions.clone() };
+        // the tape stays locked so that the writes do not interleave
+        let result = match serde_json::to_vec_pretty(&file) {
+            Ok(content) => tokio::fs::write(&self.path, content).await,
+            Err(err) => Err(io::Error::other(err)),
+        };
+        drop(tape);
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to write cassette {}, error: {err}", self.path.display());
+        }
+    }
+
+    /// Next recorded response of `request`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `request` was never recorded or its response does
+    /// not decode into `T`
+    pub async fn replay<T: DeserializeOwned>(
+        &self,
+        request: &str,
+    ) -> std::result::Result<T, ServiceError> {
+        let mut tape = self.tape.lock().await;
+        let responses = tape
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.request == request)
+            .map(|interaction| &interaction.response)
+            .collect::<Vec<_>>();
+        let Some(last) = responses.len().checked_sub(1) else {
+            return Err(ServiceError::NoRecordedResponse {
+                cassette: self.name,
+                request: request.to_string(),
+            });
+        };
+        let cursor = tape.cursors.get(request).copied().unwrap_or_default();
+        let response = responses[cursor.min(last)].clone();
+        drop(tape.cursors.insert(request.to_string(), cursor + 1));
+        drop(tape);
+
+        serde_json::from_value(response).context(service_error::DecodeRecordedResponseSnafu {
+            cassette: self.name,
+            request: request.to_string(),
+        })
+    }
+}
+
+/// Name of a request to an upstream, e.g. `list_utxos ["bc1q..."]`
+#[must_use]
+pub fn request_name(method: &str, arguments: &serde_json::Value) -> String {
+    format!("{method} {arguments}")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("cassette-test-{}", uuid::Uuid::new_v4()));
+        let options = CassetteOptions { mode: CassetteMode::Record, dir: dir.clone() };
+        let cassette = options.open("bitcoin").unwrap();
+        let request = request_name("get_block_count", &json!([]));
+        cassette.record(request.clone(), &100_u64).await;
+        cassette.record(request.clone(), &101_u64).await;
+
+        let options = CassetteOptions { mode: CassetteMode::Replay, dir: dir.clone() };
+        let cassette = options.open("bitcoin").unwrap();
+        assert_eq!(cassette.replay::<u64>(&request).await.unwrap(), 100);
+        assert_eq!(cassette.replay::<u64>(&request).await.unwrap(), 101);
+        // the last response is repeated
+        assert_eq!(cassette.replay::<u64>(&request).await.unwrap(), 101);
+        assert!(matches!(
+            cassette.replay::<u64>("get_mempool_info []").await,
+            Err(ServiceError::NoRecordedResponse { .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+//! Cache of the Bitcoin block height and the Solana slot.
+//!
+//! The [`RefreshChainStateJob`](crate::worker::RefreshChainStateJob) reads both
+//! chains on the configured interval and stores the values here, so health
+//! checks and endpoints serve them without a round trip to the nodes. A value
+//! not refreshed for [`STALE_AFTER_INTERVALS`] intervals, e.g. while a node is
+//! unreachable, is still served but reported as stale.
+
+use std::{sync::Arc, time::Duration};
+
+use arc_swap::ArcSwapOption;
+use chrono::{DateTime, Utc};
+
+use crate::entity::{ChainHeight, ChainState};
+
+/// Number of refresh intervals after which a cached value is stale
+pub const STALE_AFTER_INTERVALS: u32 = 3;
+
+/// Cached chain heights, clones share the values
+#[derive(Clone, Debug)]
+pub struct ChainStateCache {
+    bitcoin_block_height: Arc<ArcSwapOption<CachedValue>>,
+    solana_slot: Arc<ArcSwapOption<CachedValue>>,
+    stale_after: Duration,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CachedValue {
+    value: u64,
+    updated_at: DateTime<Utc>,
+}
+
+impl Default for ChainStateCache {
+    /// Cache which is never refreshed, its values are stale as soon as they
+    /// are set, so callers always fall back to the chains
+    fn default() -> Self { Self::new(Duration::ZERO) }
+}
+
+impl ChainStateCache {
+    /// Empty cache refreshed every `refresh_interval`
+    #[must_use]
+    pub fn new(refresh_interval: Duration) -> Self {
+        Self {
+            bitcoin_block_height: Arc::new(ArcSwapOption::empty()),
+            solana_slot: Arc::new(ArcSwapOption::empty()),
+            stale_after: refresh_interval.saturating_mul(STALE_AFTER_INTERVALS),
+        }
+    }
+
+    /// Latest Bitcoin block height, `None` until it was read once
+    #[must_use]
+    pub fn bitcoin_block_height(&self) -> Option<ChainHeight> {
+        self.height(&self.bitcoin_block_height)
+    }
+
+    /// Latest Bitcoin block height unless it is stale
+    #[must_use]
+    pub fn fresh_bitcoin_block_height(&self) -> Option<u64> {
+        self.bitcoin_block_height().filter(|height| !height.stale).map(|height| height.value)
+    }
+
+    pub fn set_bitcoin_block_height(&self, height: u64) {
+        Self::set(&self.bitcoin_block_height, height);
+    }
+
+    /// Latest Solana slot, `None` until it was read once
+    #[must_use]
+    pub fn solana_slot(&self) -> Option<ChainHeight> { self.height(&self.solana_slot) }
+
+    /// Latest Solana slot unless it is stale
+    #[must_use]
+    pub fn fresh_solana_slot(&self) -> Option<u64> {
+        self.solana_slot().filter(|slot| !slot.stale).map(|slot| slot.value)
+    }
+
+    pub fn set_solana_slot(&self, slot: u64) { Self::set(&self.solana_slot, slot); }
+
+    /// Every cached value
+    #[must_use]
+    pub fn snapshot(&self) -> ChainState {
+        ChainState {
+            bitcoin_block_height: self.bitcoin_block_height(),
+            solana_slot: self.solana_slot(),
+        }
+    }
+
+    fn height(&self, cached: &ArcSwapOption<CachedValue>) -> Option<ChainHeight> {
+        let cached = cached.load_full()?;
+        let age = (Utc::now() - cached.updated_at).to_std().unwrap_or_default();
+
+        Some(ChainHeight {
+            value: cached.value,
+            updated_at: cached.updated_at,
+            stale: age >= self.stale_after,
+        })
+    }
+
+    fn set(cached: &ArcSwapOption<CachedValue>, value: u64) {
+        cached.store(Some(Arc::new(CachedValue { value, updated_at: Utc::now() })));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_become_stale() {
+        let cache = ChainStateCache::new(Duration::from_secs(10));
+        assert_eq!(cache.bitcoin_block_height(), None);
+
+        cache.set_bitcoin_block_height(100);
+        assert_eq!(cache.fresh_bitcoin_block_height(), Some(100));
+        assert_eq!(cache.fresh_solana_slot(), None);
+
+        let cache = ChainStateCache::default();
+        cache.set_solana_slot(42);
+        let slot = cache.solana_slot().unwrap();
+        assert_eq!(slot.value, 42);
+        assert!(slot.stale);
+        assert_eq!(cache.fresh_solana_slot(), None);
+    }
+}
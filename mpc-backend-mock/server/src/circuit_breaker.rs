@@ -0,0 +1,341 @@
+//! Circuit breakers in front of external dependencies.
+//!
+//! A [`CircuitBreaker`] opens after a number of consecutive failed calls and
+//! then rejects every call until its cooldown is over, so requests fail fast
+//! with `503` instead of piling up on a dependency which is down. After the
+//! cooldown a single trial call is let through, its success closes the
+//! breaker and its failure opens it for another cooldown.
+//!
+//! Each breaker keeps the last failure counted against its dependency, the
+//! dependency matrix of the admin API reports it with the breaker's state.
+
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use mpc_backend_mock_core::config::CircuitBreakerConfig;
+use tokio::time::Instant;
+
+/// Call rejected because the circuit breaker of `dependency` is open
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BreakerOpen {
+    pub dependency: &'static str,
+
+    /// Time until the cooldown is over and a trial call is let through
+    pub retry_after: Duration,
+}
+
+/// State of a circuit breaker as reported to the admin API
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Calls go through
+    Closed,
+
+    /// Calls are rejected until the cooldown is over
+    Open,
+
+    /// The cooldown is over, the next call, or the one in flight, is the
+    /// trial deciding whether the breaker closes
+    HalfOpen,
+}
+
+/// Failed call counted against a dependency
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    pub error: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Circuit breaker of one dependency, clones share the state
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    dependency: &'static str,
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Arc<Mutex<State>>,
+    last_failure: Arc<Mutex<Option<Failure>>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    /// Rejecting calls until the cooldown since `since` is over
+    Open {
+        since: Instant,
+    },
+    /// A trial call started at `since`, a trial which never finished, e.g.
+    /// because its request was cancelled, is replaced after the cooldown
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+impl CircuitBreaker {
+    #[must_use]
+    pub fn new(dependency: &'static str, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            dependency,
+            failure_threshold: config.failure_threshold.max(1),
+            cooldown: config.cooldown,
+            state: Arc::new(Mutex::new(State::Closed { failures: 0 })),
+            last_failure: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Name of the dependency, as reported when the breaker is open
+    #[must_use]
+    pub const fn dependency(&self) -> &'static str { self.dependency }
+
+    /// Whether calls are currently rejected
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        match *self.state() {
+            State::Closed { .. } => false,
+            State::Open { since } | State::HalfOpen { since } => since.elapsed() < self.cooldown,
+        }
+    }
+
+    /// Current state, an open breaker whose cooldown is over is half-open as
+    /// its next call is a trial
+    #[must_use]
+    pub fn current_state(&self) -> BreakerState {
+        match *self.state() {
+            State::Closed { .. } => BreakerState::Closed,
+            State::Open { since } if since.elapsed() < self.cooldown => BreakerState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Last failed call counted against the dependency, kept after the
+    /// breaker closes again
+    #[must_use]
+    pub fn last_failure(&self) -> Option<Failure> {
+        self.last_failure.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Run `call` unless the breaker is open
+    ///
+    /// Only errors for which `is_failure` holds count against the dependency,
+    /// an error caused by the request itself, such as wrong credentials, does
+    /// not.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BreakerOpen`] converted into `E` without running `call` if
+    /// the breaker is open, otherwise the error of `call`.
+    pub async fn call<T, E, F>(&self, call: F, is_failure: impl FnOnce(&E) -> bool) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: From<BreakerOpen> + Display,
+    {
+        self.acquire()?;
+
+        let result = call.await;
+        let failure = result.as_ref().err().filter(|err| is_failure(err));
+        if let Some(err) = failure {
+            *self.last_failure.lock().unwrap_or_else(PoisonError::into_inner) =
+                Some(Failure { error: err.to_string(), at: Utc::now() });
+        }
+        self.record(failure.is_some());
+
+        result
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn acquire(&self) -> Result<(), BreakerOpen> {
+        let mut state = self.state();
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { since } | State::HalfOpen { since } => {
+                if since.elapsed() < self.cooldown {
+                    return Err(BreakerOpen {
+                        dependency: self.dependency,
+                        retry_after: self.cooldown.saturating_sub(since.elapsed()),
+                    });
+                }
+
+                *state = State::HalfOpen { since: Instant::now() };
+                drop(state);
+                tracing::info!(
+                    "Circuit breaker of {} is half-open, trying a call",
+                    self.dependency
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn record(&self, failed: bool) {
+        let mut state = self.state();
+        let previous = *state;
+        let next = match previous {
+            _ if !failed => State::Closed { failures: 0 },
+            State::Closed { failures } if failures + 1 < self.failure_threshold => {
+                State::Closed { failures: failures + 1 }
+            }
+            _ => State::Open { since: Instant::now() },
+        };
+        *state = next;
+        drop(state);
+
+        match (previous, next) {
+            (State::Closed { .. }, State::Open { .. }) => tracing::warn!(
+                "Circuit breaker of {} is open after {} consecutive failures, rejecting calls for \
+                 {:?}",
+                self.dependency,
+                self.failure_threshold,
+                self.cooldown
+            ),
+            (State::HalfOpen { .. }, State::Open { .. }) => {
+                tracing::warn!(
+                    "Circuit breaker of {} is open again, the trial failed",
+                    self.dependency
+                );
+            }
+            (State::Open { .. } | State::HalfOpen { .. }, State::Closed { .. }) => {
+                tracing::info!("Circuit breaker of {} is closed", self.dependency);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Circuit breakers of the dependencies called while serving requests
+#[derive(Clone, Debug)]
+pub struct CircuitBreakers {
+    /// Keycloak admin API, token and introspection endpoints
+    pub keycloak: CircuitBreaker,
+
+    /// Bitcoin RPC endpoint
+    pub bitcoin: CircuitBreaker,
+
+    /// Keycloak JWKS endpoint
+    pub jwks: CircuitBreaker,
+}
+
+impl CircuitBreakers {
+    #[must_use]
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            keycloak: CircuitBreaker::new("Keycloak", config),
+            bitcoin: CircuitBreaker::new("Bitcoin RPC", config),
+            jwks: CircuitBreaker::new("JWKS", config),
+        }
+    }
+}
+
+impl Default for CircuitBreakers {
+    fn default() -> Self { Self::new(&CircuitBreakerConfig::default()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum TestError {
+        Down,
+        Rejected,
+        Open,
+    }
+
+    impl From<BreakerOpen> for TestError {
+        fn from(_: BreakerOpen) -> Self { Self::Open }
+    }
+
+    impl Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{self:?}") }
+    }
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test",
+            &CircuitBreakerConfig { failure_threshold: 2, cooldown: Duration::from_secs(30) },
+        )
+    }
+
+    async fn call(
+        breaker: &CircuitBreaker,
+        result: Result<(), TestError>,
+    ) -> Result<(), TestError> {
+        breaker.call(async { result }, |err| *err == TestError::Down).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_opens_after_consecutive_failures() {
+        let breaker = breaker();
+
+        assert_eq!(call(&breaker, Err(TestError::Down)).await, Err(TestError::Down));
+        // a success resets the count
+        assert_eq!(call(&breaker, Ok(())).await, Ok(()));
+        assert_eq!(call(&breaker, Err(TestError::Down)).await, Err(TestError::Down));
+        // errors caused by the request do not count
+        assert_eq!(call(&breaker, Err(TestError::Rejected)).await, Err(TestError::Rejected));
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.current_state(), BreakerState::Closed);
+        assert_eq!(breaker.last_failure().map(|failure| failure.error).as_deref(), Some("Down"));
+
+        assert_eq!(call(&breaker, Err(TestError::Down)).await, Err(TestError::Down));
+        assert_eq!(call(&breaker, Err(TestError::Down)).await, Err(TestError::Down));
+        assert!(breaker.is_open());
+        assert_eq!(call(&breaker, Ok(())).await, Err(TestError::Open));
+
+        // rejected calls are told when the cooldown is over
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(
+            breaker.acquire(),
+            Err(BreakerOpen { dependency: "test", retry_after: Duration::from_secs(20) })
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_half_open_after_cooldown() {
+        let breaker = breaker();
+        for _ in 0..2 {
+            drop(call(&breaker, Err(TestError::Down)).await);
+        }
+        assert!(breaker.is_open());
+        assert_eq!(breaker.current_state(), BreakerState::Open);
+
+        // a failed trial opens the breaker for another cooldown
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(breaker.current_state(), BreakerState::HalfOpen);
+        assert_eq!(call(&breaker, Err(TestError::Down)).await, Err(TestError::Down));
+        assert_eq!(call(&breaker, Ok(())).await, Err(TestError::Open));
+
+        // a successful trial closes it
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(call(&breaker, Ok(())).await, Ok(()));
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.current_state(), BreakerState::Closed);
+        assert!(breaker.last_failure().is_some());
+        assert_eq!(call(&breaker, Err(TestError::Down)).await, Err(TestError::Down));
+        assert!(!breaker.is_open());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_single_trial_while_half_open() {
+        let breaker = breaker();
+        for _ in 0..2 {
+            drop(call(&breaker, Err(TestError::Down)).await);
+        }
+        tokio::time::advance(Duration::from_secs(30)).await;
+
+        // the trial is still in flight, other calls are rejected
+        breaker.acquire().unwrap();
+        assert_eq!(call(&breaker, Ok(())).await, Err(TestError::Open));
+
+        // a trial which never finished is replaced after the cooldown
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(call(&breaker, Ok(())).await, Ok(()));
+    }
+}
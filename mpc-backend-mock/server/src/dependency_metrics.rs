@@ -0,0 +1,102 @@
+//! Latency metrics of the calls to external dependencies.
+//!
+//! Calls to Keycloak and the Bitcoin endpoints are counted in
+//! `dependency_calls_total` and observed in
+//! `dependency_call_duration_seconds`, both labelled by `dependency`,
+//! `operation` and `result` (`ok` or `error`), so a slow or failing dependency
+//! can be told apart from a slow route.
+
+use std::{future::Future, sync::Arc, time::Instant};
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::error::{self, Result};
+
+/// Metrics of the calls to external dependencies, clones share the metrics
+///
+/// The default records nothing, for clients created without a registry.
+#[derive(Clone, Debug, Default)]
+pub struct DependencyMetrics {
+    inner: Option<Arc<Metrics>>,
+}
+
+#[derive(Debug)]
+struct Metrics {
+    calls: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl DependencyMetrics {
+    const LABELS: [&'static str; 3] = ["dependency", "operation", "result"];
+
+    /// # Errors
+    ///
+    /// Returns an error if the metrics cannot be registered
+    pub fn new(metrics: &DefaultMetrics) -> Result<Self> {
+        let calls = IntCounterVec::new(
+            Opts::new("dependency_calls_total", "Number of calls to external dependencies"),
+            &Self::LABELS,
+        )
+        .context(error::CreateDependencyMetricsSnafu)?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "dependency_call_duration_seconds",
+                "Latency of the calls to external dependencies in seconds",
+            ),
+            &Self::LABELS,
+        )
+        .context(error::CreateDependencyMetricsSnafu)?;
+
+        metrics.register(Box::new(calls.clone()))?;
+        metrics.register(Box::new(duration.clone()))?;
+
+        Ok(Self { inner: Some(Arc::new(Metrics { calls, duration })) })
+    }
+
+    /// Run `call` to `dependency` and record its latency and result under
+    /// `operation`
+    pub async fn observe<T, E, F>(
+        &self,
+        dependency: &str,
+        operation: &str,
+        call: F,
+    ) -> std::result::Result<T, E>
+    where
+        F: Future<Output = std::result::Result<T, E>>,
+    {
+        let Some(metrics) = &self.inner else {
+            return call.await;
+        };
+
+        let started_at = Instant::now();
+        let result = call.await;
+
+        let labels = [dependency, operation, if result.is_ok() { "ok" } else { "error" }];
+        metrics.calls.with_label_values(&labels).inc();
+        metrics.duration.with_label_values(&labels).observe(started_at.elapsed().as_secs_f64());
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_observe() {
+        let metrics = DependencyMetrics::new(&DefaultMetrics::new()).unwrap();
+
+        let _ok = metrics.observe("keycloak", "find_by_email", async { Ok::<_, ()>(()) }).await;
+        let _err = metrics.observe("keycloak", "find_by_email", async { Err::<(), _>(()) }).await;
+
+        let inner = metrics.inner.as_ref().unwrap();
+        for result in ["ok", "error"] {
+            let labels = ["keycloak", "find_by_email", result];
+            assert_eq!(inner.calls.with_label_values(&labels).get(), 1);
+            assert_eq!(inner.duration.with_label_values(&labels).get_sample_count(), 1);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use mpc_backend_mock_core::config::{Fault, FaultInjectionConfig};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Client IP address as resolved by the server
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClientIpResponse {
+    /// Client IP after applying the trusted proxy configuration
+    #[schema(value_type = String, example = "127.0.0.1")]
+    pub ip: IpAddr,
+}
+
+/// Background task running on the server
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackgroundTask {
+    /// Task ID, unique for the lifetime of the process
+    #[schema(example = 0)]
+    pub id: u64,
+
+    /// Task name
+    #[schema(example = "Health check watch")]
+    pub name: String,
+
+    /// Timestamp when the task was spawned
+    pub started_at: DateTime<Utc>,
+}
+
+/// Latency and error rate of one route over the SLO window
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RouteSlo {
+    /// HTTP method
+    #[schema(example = "GET")]
+    pub method: String,
+
+    /// Route pattern as registered in the router
+    #[schema(example = "/api/v1/users/me")]
+    pub route: String,
+
+    /// Number of requests in the window
+    #[schema(example = 120)]
+    pub request_count: u64,
+
+    /// Share of requests answered with a 5xx status, from 0 to 1
+    #[schema(example = 0.01)]
+    pub error_rate: f64,
+
+    /// Median latency in milliseconds
+    #[schema(example = 4.2)]
+    pub p50_ms: Option<f64>,
+
+    /// 95th percentile latency in milliseconds
+    #[schema(example = 18.0)]
+    pub p95_ms: Option<f64>,
+
+    /// 99th percentile latency in milliseconds
+    #[schema(example = 45.5)]
+    pub p99_ms: Option<f64>,
+}
+
+/// Latency SLO report per route over a rolling window
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SloReport {
+    /// Length of the window the report covers, shorter than the configured
+    /// window right after startup
+    #[schema(example = 300)]
+    pub window_seconds: u64,
+
+    /// Routes which served requests in the window
+    pub routes: Vec<RouteSlo>,
+}
+
+/// Upstream dependency with the state of its circuit breaker
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DependencyStatus {
+    #[schema(example = "Keycloak")]
+    pub name: String,
+
+    pub state: DependencyState,
+
+    /// Last failure counted by the circuit breaker, kept after it closes
+    #[schema(example = "Failed to authenticate with Keycloak, error: connection refused")]
+    pub last_error: Option<String>,
+
+    pub last_failed_at: Option<DateTime<Utc>>,
+
+    /// Routes failing fast until the circuit breaker closes, as paths
+    /// relative to `/api/<version>`
+    #[schema(example = json!(["/bitcoin/balance", "/bitcoin/fees"]))]
+    pub degraded_routes: Vec<String>,
+}
+
+/// State of the circuit breaker of a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyState {
+    /// Calls go through
+    Closed,
+
+    /// Calls are rejected until the cooldown is over
+    Open,
+
+    /// The next call is a trial deciding whether the breaker closes
+    HalfOpen,
+
+    /// The dependency has no circuit breaker, its failures are not tracked
+    Untracked,
+}
+
+/// Query parameters for deleting users in bulk
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct BulkDeleteUsersParams {
+    /// Email glob, `*` matches any characters and `?` a single character, must
+    /// contain `@`
+    #[param(example = "*@example.com")]
+    #[schema(example = "*@example.com")]
+    pub pattern: String,
+
+    /// Only delete users created before this date (YYYY-MM-DD, UTC)
+    #[param(value_type = Option<String>, example = "2026-01-01")]
+    #[schema(value_type = Option<String>, example = "2026-01-01")]
+    pub created_before: Option<NaiveDate>,
+
+    /// Keycloak realm of the users, the primary realm if not given
+    #[param(example = "partner")]
+    #[schema(example = "partner")]
+    pub realm: Option<String>,
+
+    /// List the matching users without deleting them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Format of a user export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+
+    /// JSON array of users
+    Json,
+}
+
+/// Query parameters for exporting users
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct ExportUsersParams {
+    /// Format of the export, `csv` if not given
+    #[serde(default)]
+    pub format: ExportFormat,
+
+    /// Keycloak realm of the users, the primary realm if not given
+    #[param(example = "partner")]
+    #[schema(example = "partner")]
+    pub realm: Option<String>,
+}
+
+/// Result of deleting users in bulk
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BulkDeleteUsersResponse {
+    /// Whether this was a dry run and nothing was deleted
+    #[schema(example = false)]
+    pub dry_run: bool,
+
+    /// Number of matching users
+    #[schema(example = 1)]
+    pub count: usize,
+
+    /// Emails of the matching users
+    #[schema(example = json!(["user@example.com"]))]
+    pub emails: Vec<String>,
+}
+
+/// OpenAPI document stored as the drift baseline
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OpenApiBaseline {
+    /// Baseline ID
+    pub id: Uuid,
+
+    /// Timestamp when the baseline was uploaded
+    pub created_at: DateTime<Utc>,
+}
+
+/// Differences between the live OpenAPI document and the baseline
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiDriftReport {
+    /// Baseline the live document was compared against
+    pub baseline: OpenApiBaseline,
+
+    /// Whether anything differs
+    #[schema(example = true)]
+    pub has_drift: bool,
+
+    /// Operations, named `<METHOD> <path>`
+    pub operations: ApiDrift,
+
+    /// Component schemas, by name
+    pub schemas: ApiDrift,
+}
+
+/// Items added, removed or changed since the baseline, sorted by name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ApiDrift {
+    /// Items only in the live document
+    #[schema(example = json!(["GET /api/v1/admin/openapi/drift"]))]
+    pub added: Vec<String>,
+
+    /// Items only in the baseline
+    #[schema(example = json!([]))]
+    pub removed: Vec<String>,
+
+    /// Items in both whose definition differs
+    #[schema(example = json!(["POST /api/v1/users"]))]
+    pub changed: Vec<String>,
+}
+
+impl ApiDrift {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Settings which changed on a configuration reload, named by their path in
+/// the configuration file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ConfigReloadReport {
+    /// Settings applied to the running server
+    #[schema(example = json!(["rate_limit", "log.log_filters"]))]
+    pub applied: Vec<String>,
+
+    /// Settings which take effect after a restart
+    #[schema(example = json!(["postgres"]))]
+    pub restart_required: Vec<String>,
+}
+
+/// Latest request and response bodies logged for debugging
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HttpLog {
+    /// Whether `web.debug_http` is enabled, nothing is logged otherwise
+    #[schema(example = true)]
+    pub enable: bool,
+
+    /// Logged exchanges, newest first
+    pub exchanges: Vec<HttpExchange>,
+}
+
+/// Request and response of a logged route, with the redacted fields replaced
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HttpExchange {
+    pub recorded_at: DateTime<Utc>,
+
+    #[schema(example = "POST")]
+    pub method: String,
+
+    #[schema(example = "/api/v1/auth/login")]
+    pub path: String,
+
+    /// Query string, with the values of the redacted parameters replaced
+    #[schema(example = "access_token=[REDACTED]&page=2")]
+    pub query: Option<String>,
+
+    #[schema(example = 401)]
+    pub status: u16,
+
+    /// Time taken to answer the request
+    #[schema(example = 42)]
+    pub duration_ms: u64,
+
+    /// JSON request body, or a note on the body which is not captured, e.g.
+    /// because it is too large or not JSON
+    #[schema(example = json!({"email": "user@example.com", "password": "[REDACTED]"}))]
+    pub request_body: Option<serde_json::Value>,
+
+    /// JSON response body, or a note on the body which is not captured
+    pub response_body: Option<serde_json::Value>,
+}
+
+/// Faults injected into API responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FaultInjection {
+    /// Whether the rules are applied
+    #[schema(example = false)]
+    pub enable: bool,
+
+    /// Rules as configured in `web.fault_injection.rules`
+    pub rules: Vec<FaultInjectionRule>,
+}
+
+/// Fault injected into a share of the requests of a route
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FaultInjectionRule {
+    /// Request path the rule applies to, with the paths under it
+    #[schema(example = "/api/v1/bitcoin")]
+    pub route: String,
+
+    /// Percentage of the requests the fault is injected into
+    #[schema(example = 10.0)]
+    pub percentage: f64,
+
+    pub fault: InjectedFault,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InjectedFault {
+    /// Request is delayed before it is handled
+    Latency { latency_ms: u64 },
+
+    /// Request is answered with a 5xx status instead of being handled
+    Error { status: u16 },
+
+    /// Connection is closed after the response headers
+    DropConnection,
+
+    /// JSON response body is cut in half
+    MalformedJson,
+}
+
+impl From<&FaultInjectionConfig> for FaultInjection {
+    fn from(config: &FaultInjectionConfig) -> Self {
+        Self {
+            enable: config.enable,
+            rules: config
+                .rules
+                .iter()
+                .map(|rule| FaultInjectionRule {
+                    route: rule.route.clone(),
+                    percentage: rule.percentage,
+                    fault: rule.fault.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Fault> for InjectedFault {
+    fn from(fault: Fault) -> Self {
+        match fault {
+            Fault::Latency(latency) => {
+                Self::Latency { latency_ms: u64::try_from(latency.as_millis()).unwrap_or(u64::MAX) }
+            }
+            Fault::Error(status) => Self::Error { status },
+            Fault::DropConnection => Self::DropConnection,
+            Fault::MalformedJson => Self::MalformedJson,
+        }
+    }
+}
+
+/// Turn the fault injection on or off until the configuration is reloaded
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct UpdateFaultInjectionRequest {
+    #[schema(example = true)]
+    pub enable: bool,
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Internal note and flags an operator attached to a user or a transaction
+///
+/// Annotations are only returned on admin routes.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Annotation {
+    /// Unique annotation ID
+    #[schema(example = "9a8b7c6d-5e4f-4a3b-9c2d-1e0f9a8b7c6d")]
+    pub id: Uuid,
+
+    /// ID of the annotated user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+
+    /// ID of the annotated transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<Uuid>,
+
+    /// Free-form note, may be empty
+    #[schema(example = "Created by the load test suite")]
+    pub note: String,
+
+    /// Short labels
+    #[schema(example = json!(["test account"]))]
+    pub flags: Vec<String>,
+
+    /// Timestamp when the annotation was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to annotate a user or a transaction
+///
+/// The limits are checked by the service, which answers with
+/// `INVALID_ANNOTATION`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateAnnotationRequest {
+    /// Free-form note, at most 2000 characters
+    #[serde(default)]
+    #[schema(example = "Created by the load test suite")]
+    pub note: String,
+
+    /// Short labels of at most 64 characters each, at most 16 of them
+    #[serde(default)]
+    #[schema(example = json!(["test account", "fraud sim"]))]
+    pub flags: Vec<String>,
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Audit log entry, one row per recorded action
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct AuditLog {
+    /// Unique audit log entry ID
+    #[schema(example = "4e5f6a7b-8c9d-4e0f-a1b2-c3d4e5f6a7b8")]
+    pub id: Uuid,
+
+    /// ID of the user who performed the action, absent for anonymous and
+    /// system actions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_user_id: Option<Uuid>,
+
+    /// Action which was performed
+    #[schema(example = "user.deleted")]
+    pub action: String,
+
+    /// Kind of the resource the action was performed on
+    #[schema(example = "user")]
+    pub target_type: String,
+
+    /// ID of the resource the action was performed on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<Uuid>,
+
+    /// Client IP of the request
+    #[schema(example = "203.0.113.7")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+
+    /// `X-Request-Id` of the request
+    #[schema(example = "4f1c2d3e-9a8b-4c7d-8e6f-5a4b3c2d1e0f")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+
+    /// Snapshot of the request as it was audited, secrets are never included
+    pub payload: serde_json::Value,
+
+    /// Timestamp when the action was performed
+    pub created_at: DateTime<Utc>,
+}
+
+/// Security-relevant action recorded in the audit log
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AuditAction {
+    UserCreated,
+    UserDeleted,
+    Login,
+    LoginFailed,
+    AccountLocked,
+    TokenRejected,
+    AdminAction,
+    UserImpersonated,
+    UserEmailChanged,
+}
+
+impl AuditAction {
+    /// Value of the `action` column
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::UserCreated => "user.created",
+            Self::UserDeleted => "user.deleted",
+            Self::Login => "auth.login",
+            Self::LoginFailed => "auth.login_failed",
+            Self::AccountLocked => "auth.account_locked",
+            Self::TokenRejected => "auth.token_rejected",
+            Self::AdminAction => "admin.action",
+            Self::UserImpersonated => "admin.user_impersonated",
+            Self::UserEmailChanged => "user.email_changed",
+        }
+    }
+
+    /// Value of the `target_type` column
+    #[must_use]
+    pub const fn target_type(self) -> &'static str {
+        match self {
+            Self::UserCreated
+            | Self::UserDeleted
+            | Self::Login
+            | Self::LoginFailed
+            | Self::AccountLocked
+            | Self::UserImpersonated
+            | Self::UserEmailChanged => "user",
+            Self::TokenRejected => "token",
+            Self::AdminAction => "route",
+        }
+    }
+}
+
+/// Filters for listing audit log entries
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct ListAuditLogsFilter {
+    /// Action, e.g. `auth.login_failed`
+    #[param(example = "auth.login_failed")]
+    pub action: Option<String>,
+
+    /// ID of the user who performed the action
+    pub actor_user_id: Option<Uuid>,
+
+    /// ID of the resource the action was performed on
+    pub target_id: Option<Uuid>,
+
+    /// Only include entries created on or after this date (YYYY-MM-DD, UTC)
+    #[param(value_type = Option<String>, example = "2026-01-01")]
+    #[schema(value_type = Option<String>, example = "2026-01-01")]
+    pub created_after: Option<NaiveDate>,
+}
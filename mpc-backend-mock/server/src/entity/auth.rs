@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use mpc_backend_mock_core::model::Email;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::keycloak_client::AccessTokenResponse;
+
+/// Request to log in with email and password
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct LoginRequest {
+    /// User's email address, matched case-insensitively
+    #[schema(example = "user@example.com")]
+    pub email: Email,
+
+    /// User's password, at most 128 characters
+    #[schema(example = "test123")]
+    #[validate(length(min = 1, max = 128, message = "must be 1 to 128 characters"))]
+    pub password: String,
+
+    /// Also set a session cookie, which authenticates the following requests
+    /// of a browser, if sessions are enabled
+    #[serde(default)]
+    #[schema(example = false)]
+    pub session: bool,
+}
+
+/// Request to refresh an access token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct RefreshTokenRequest {
+    /// Refresh token issued by a previous login or refresh
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub refresh_token: String,
+}
+
+/// Tokens issued by Keycloak
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TokenResponse {
+    /// Access token to send as `Authorization: Bearer <access_token>`
+    pub access_token: String,
+
+    /// Access token lifetime in seconds
+    #[schema(example = 300)]
+    pub expires_in: i64,
+
+    /// Refresh token used to obtain a new access token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    /// Refresh token lifetime in seconds
+    #[schema(example = 1800)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_expires_in: Option<i64>,
+
+    /// Token type
+    #[schema(example = "Bearer")]
+    pub token_type: String,
+
+    /// Granted scopes
+    #[schema(example = "profile email")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl From<AccessTokenResponse> for TokenResponse {
+    fn from(
+        AccessTokenResponse {
+            access_token,
+            expires_in,
+            refresh_token,
+            refresh_expires_in,
+            token_type,
+            scope,
+        }: AccessTokenResponse,
+    ) -> Self {
+        Self { access_token, expires_in, refresh_token, refresh_expires_in, token_type, scope }
+    }
+}
+
+/// Access token issued to an admin impersonating a user
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImpersonationResponse {
+    /// ID of the impersonated user
+    pub user_id: Uuid,
+
+    /// Access token of the impersonated user, it comes without a refresh
+    /// token and cannot be renewed
+    pub access_token: String,
+
+    /// Access token lifetime in seconds
+    #[schema(example = 300)]
+    pub expires_in: i64,
+
+    /// Token type
+    #[schema(example = "Bearer")]
+    pub token_type: String,
+}
+
+/// Cookie session of a browser together with its user
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Session {
+    pub id: Uuid,
+
+    pub user_id: Uuid,
+
+    pub email: String,
+
+    pub keycloak_user_id: Uuid,
+
+    pub is_active: bool,
+
+    /// Hex encoded SHA-256 hash of the CSRF token of the session
+    pub csrf_token_hash: String,
+
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Failed logins of an email and its lockout
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct LoginAttempt {
+    #[schema(example = "user@example.com")]
+    pub email: String,
+
+    /// Failed logins within the current failure window
+    #[schema(example = 5)]
+    pub failed_attempts: i32,
+
+    pub last_failed_at: DateTime<Utc>,
+
+    /// Client IP of the last failed login, absent if it is not known
+    #[schema(example = "203.0.113.7")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failed_ip: Option<String>,
+
+    /// When the email was locked, absent if it is not locked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked_at: Option<DateTime<Utc>>,
+
+    /// When the lockout ends, `null` if it lasts until an admin unlocks the
+    /// email
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginAttempt {
+    /// Whether the email is locked at `now`
+    #[must_use]
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_at.is_some() && self.locked_until.is_none_or(|until| until > now)
+    }
+}
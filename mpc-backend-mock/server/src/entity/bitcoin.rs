@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use mpc_backend_mock_core::model::Satoshis;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Unspent transaction output held by one of the user's Bitcoin wallets
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BitcoinUtxo {
+    /// Wallet address holding the output
+    #[schema(example = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")]
+    pub address: String,
+
+    /// Transaction ID
+    #[schema(example = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b")]
+    pub txid: String,
+
+    /// Output index in the transaction
+    #[schema(example = 0)]
+    pub vout: u32,
+
+    /// Value of the output
+    pub amount: Satoshis,
+
+    /// Height of the block including the transaction, absent while it is in
+    /// the mempool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+
+    /// Number of confirmations, `0` while the transaction is in the mempool
+    #[schema(example = 6)]
+    pub confirmations: u64,
+
+    /// Whether the output has reached the required number of confirmations
+    #[schema(example = true)]
+    pub confirmed: bool,
+}
+
+/// UTXO set of the user's Bitcoin wallets
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BitcoinUtxoSet {
+    /// Current Bitcoin block height the confirmations are computed from
+    #[schema(example = 850_000)]
+    pub block_height: u64,
+
+    /// Unspent outputs, confirmed and unconfirmed
+    pub utxos: Vec<BitcoinUtxo>,
+}
+
+/// Balance of the user's Bitcoin wallets
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BitcoinBalance {
+    /// Sum of the outputs which reached the required number of confirmations
+    pub confirmed: Satoshis,
+
+    /// Sum of the outputs which did not reach the required number of
+    /// confirmations yet
+    pub unconfirmed: Satoshis,
+
+    /// Current Bitcoin block height the confirmations are computed from
+    #[schema(example = 850_000)]
+    pub block_height: u64,
+
+    /// Number of confirmations an output needs to be counted as confirmed
+    #[schema(example = 6)]
+    pub block_number_to_confirm: u64,
+}
+
+/// Fee rate estimated for a confirmation target
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BitcoinFeeEstimate {
+    /// Number of blocks the transaction should confirm within
+    #[schema(example = 6)]
+    pub target_blocks: u16,
+
+    /// Estimated fee rate in satoshis per virtual byte, absent if the node
+    /// lacks the data to estimate it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 12.5)]
+    pub sat_per_vbyte: Option<f64>,
+}
+
+/// Fee rates estimated by the Bitcoin node for a range of confirmation targets
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BitcoinFeeEstimates {
+    /// Estimates, fastest confirmation target first
+    pub estimates: Vec<BitcoinFeeEstimate>,
+
+    /// When the estimates were read from the node, they are cached for a few
+    /// seconds
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Mempool of the Bitcoin node
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BitcoinMempool {
+    /// Number of transactions in the mempool
+    #[schema(example = 1_500)]
+    pub transaction_count: u64,
+
+    /// Sum of the virtual sizes of the transactions, in virtual bytes
+    #[schema(example = 750_000)]
+    pub vsize: u64,
+
+    /// Fee rate below which transactions are not accepted into the mempool,
+    /// in satoshis per virtual byte
+    #[schema(example = 1.0)]
+    pub min_fee_sat_per_vbyte: f64,
+
+    /// Fee rate below which transactions are not relayed, in satoshis per
+    /// virtual byte
+    #[schema(example = 1.0)]
+    pub min_relay_fee_sat_per_vbyte: f64,
+
+    /// When the mempool was read from the node, it is cached for a few seconds
+    pub fetched_at: DateTime<Utc>,
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Value last read from a chain, with when it was read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChainHeight {
+    /// Block height or slot
+    #[schema(example = 870_000)]
+    pub value: u64,
+
+    /// When the value was read from the chain
+    pub updated_at: DateTime<Utc>,
+
+    /// Whether the value was not refreshed for several refresh intervals,
+    /// e.g. while the node is unreachable
+    #[schema(example = false)]
+    pub stale: bool,
+}
+
+/// Latest Bitcoin block height and Solana slot, served from the cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChainState {
+    /// Latest Bitcoin block height, absent until it was read once
+    pub bitcoin_block_height: Option<ChainHeight>,
+
+    /// Latest Solana slot, absent until it was read once
+    pub solana_slot: Option<ChainHeight>,
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Changes of the HTTP API per release
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiChangelog {
+    /// Version of the deployed server
+    #[schema(example = "0.1.0")]
+    pub version: String,
+
+    /// Releases of the deployed build, newest first
+    pub releases: Vec<ApiRelease>,
+}
+
+/// Changes of the HTTP API in one release
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiRelease {
+    /// Version of the release
+    #[schema(example = "0.1.0")]
+    pub version: String,
+
+    /// Changed operations
+    pub changes: Vec<ApiChange>,
+}
+
+/// Change of one operation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiChange {
+    /// Kind of change
+    pub kind: ApiChangeKind,
+
+    /// HTTP method of the operation, upper case
+    #[schema(example = "GET")]
+    pub method: String,
+
+    /// Path of the operation, as in the OpenAPI document
+    #[schema(example = "/api/v1/wallets/{id}/balance-history")]
+    pub path: String,
+
+    /// What changed
+    #[schema(example = "Daily balance history of a wallet")]
+    pub description: String,
+}
+
+/// Kind of an API change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiChangeKind {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+}
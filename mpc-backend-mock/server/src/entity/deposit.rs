@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use mpc_backend_mock_core::model::TokenAmount;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Status of a deposit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "deposit_status", rename_all = "lowercase")]
+pub enum DepositStatus {
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// Deposit entity representing an incoming transfer to a wallet
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Deposit {
+    /// Unique deposit ID
+    #[schema(example = "2b8e4c1a-9d7f-4a3b-8c6e-5f4d3c2b1a09")]
+    pub id: Uuid,
+
+    /// ID of the receiving wallet
+    #[schema(example = "7f1c2a9e-3b4d-4e5f-8a6b-1c2d3e4f5a6b")]
+    pub wallet_id: Uuid,
+
+    /// Transaction ID of the deposit
+    pub tx_id: String,
+
+    /// Amount in the smallest unit of the chain
+    pub amount: TokenAmount,
+
+    /// Status of the deposit
+    pub status: DepositStatus,
+
+    /// Block height of the deposit, absent until it is confirmed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<i64>,
+
+    /// Timestamp when the deposit was created
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the deposit was last updated
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Change of an entity, written to the event outbox in the transaction of the
+/// change and published to the message broker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// A user was created, it is inactive until its activation token is
+    /// redeemed
+    UserCreated { user_id: Uuid, email: String, keycloak_user_id: Uuid },
+
+    /// A user was soft-deleted
+    UserDeleted { user_id: Uuid, email: String },
+
+    /// A Solana transaction submitted by a user reached the confirmed or
+    /// finalized commitment
+    TransactionConfirmed {
+        transaction_id: Uuid,
+        user_id: Uuid,
+        signature: String,
+        slot: Option<i64>,
+    },
+}
+
+impl DomainEvent {
+    /// Kind of the event, the `type` of its payload
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::UserCreated { .. } => "user_created",
+            Self::UserDeleted { .. } => "user_deleted",
+            Self::TransactionConfirmed { .. } => "transaction_confirmed",
+        }
+    }
+
+    /// ID of the entity the event is about, the broker keeps the events of an
+    /// entity in order
+    #[must_use]
+    pub const fn aggregate_id(&self) -> Uuid {
+        match self {
+            Self::UserCreated { user_id, .. } | Self::UserDeleted { user_id, .. } => *user_id,
+            Self::TransactionConfirmed { transaction_id, .. } => *transaction_id,
+        }
+    }
+}
+
+/// Event stored in the outbox
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxEvent {
+    /// Unique event ID, consumers drop redeliveries by it
+    pub id: Uuid,
+
+    /// Order of the event within its transaction
+    pub sequence: i64,
+
+    /// ID of the transaction which wrote the event
+    pub transaction_id: i64,
+
+    /// Kind of the event, e.g. `user_created`
+    pub kind: String,
+
+    /// ID of the entity the event is about
+    pub aggregate_id: Uuid,
+
+    /// Serialized [`DomainEvent`]
+    pub payload: serde_json::Value,
+
+    /// Timestamp when the event was written
+    pub created_at: DateTime<Utc>,
+}
+
+/// Position of a publisher in the outbox, the events up to and including it
+/// are published
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, sqlx::FromRow)]
+pub struct EventOffset {
+    pub last_transaction_id: i64,
+
+    pub last_sequence: i64,
+}
+
+impl EventOffset {
+    /// Offset right after `event`
+    #[must_use]
+    pub const fn after(event: &OutboxEvent) -> Self {
+        Self { last_transaction_id: event.transaction_id, last_sequence: event.sequence }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_event_payload() {
+        let user_id = Uuid::new_v4();
+        let event = DomainEvent::UserDeleted { user_id, email: "user@example.com".to_string() };
+
+        let payload = serde_json::to_value(&event).unwrap();
+        assert_eq!(payload["type"], event.kind());
+        assert_eq!(payload["user_id"], user_id.to_string());
+        assert_eq!(serde_json::from_value::<DomainEvent>(payload).unwrap(), event);
+        assert_eq!(event.aggregate_id(), user_id);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of every error response, as written by `zeus_axum`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    /// HTTP status of the response
+    #[serde(rename = "_status")]
+    #[schema(example = 404)]
+    pub status: u16,
+
+    pub error: ErrorDetail,
+}
+
+/// What went wrong
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ErrorDetail {
+    /// Coarse category of the error
+    #[serde(rename = "type")]
+    pub type_: ErrorType,
+
+    /// Stable machine-readable code, clients should branch on it rather than
+    /// on `message`
+    #[schema(example = "USER_NOT_FOUND")]
+    pub code: String,
+
+    /// English description of the error
+    #[schema(example = "User not found: user@example.com")]
+    pub message: String,
+
+    /// ID of the request, also returned in the `x-request-id` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "5f0c8a52-5c1e-4d6f-9a57-0c2a7e3b9d41")]
+    pub request_id: Option<String>,
+
+    /// Code of the cause of a service error, which tells apart the failures
+    /// reported as `INTERNAL_ERROR` and is `code` otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "KEYCLOAK_UNAVAILABLE")]
+    pub domain_code: Option<String>,
+
+    /// Whether sending the request again unchanged may succeed, true for
+    /// transient failures such as an unavailable dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = false)]
+    pub retryable: Option<bool>,
+
+    /// Milliseconds to wait before retrying, when known, also sent as
+    /// `Retry-After` in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 30000)]
+    pub retry_after_ms: Option<u64>,
+
+    /// Violations per request body field, for `VALIDATION_FAILED` only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<BTreeMap<String, Vec<FieldViolation>>>,
+}
+
+/// Validation rule a request body field broke
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FieldViolation {
+    #[schema(example = "email")]
+    pub code: String,
+
+    #[schema(example = "must be a valid email address")]
+    pub message: Option<String>,
+}
+
+/// Category of an error, mirrors `zeus_axum::response::ErrorType`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorType {
+    Unknown,
+    Internal,
+    Validation,
+    Execution,
+    NotComplete,
+    NotFound,
+    Unauthorized,
+    BadRequest,
+    TooManyRequests,
+    Conflict,
+    UnprocessableEntity,
+    UnavailableForLegalReasons,
+}
+
+impl ErrorType {
+    /// Category the server responds with along `status`
+    #[must_use]
+    pub const fn of_status(status: StatusCode) -> Self {
+        match status.as_u16() {
+            400 => Self::BadRequest,
+            401 | 403 | 423 => Self::Unauthorized,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            422 => Self::UnprocessableEntity,
+            429 => Self::TooManyRequests,
+            451 => Self::UnavailableForLegalReasons,
+            500..=599 => Self::Internal,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use zeus_axum::response::{self, EncapsulatedResponseError};
+
+    use super::*;
+
+    #[test]
+    fn test_mirrors_zeus_axum_error() {
+        let error = response::Error {
+            type_: response::ErrorType::NotFound,
+            code: "USER_NOT_FOUND".to_string(),
+            message: "User not found: user@example.com".to_string(),
+            additional_fields: IndexMap::from([("request_id".to_string(), "abc".into())]),
+        };
+        let body =
+            serde_json::to_value(EncapsulatedResponseError::from((StatusCode::NOT_FOUND, error)))
+                .unwrap();
+
+        let mirrored: ErrorResponse = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(mirrored.status, 404);
+        assert_eq!(mirrored.error.type_, ErrorType::NotFound);
+        assert_eq!(mirrored.error.request_id.as_deref(), Some("abc"));
+        assert_eq!(serde_json::to_value(mirrored).unwrap(), body);
+    }
+}
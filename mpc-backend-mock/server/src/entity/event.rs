@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Transaction, User};
+
+/// Event pushed to the clients subscribed to `/api/v1/ws`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// A user was activated, deleted or restored, only sent to that user
+    UserUpdated {
+        /// User after the update
+        user: User,
+    },
+
+    /// The status of a transaction changed, only sent to the submitting user
+    TransactionUpdated {
+        /// Transaction after the status change
+        transaction: Transaction,
+    },
+
+    /// The Bitcoin block height increased, sent to every client
+    BitcoinBlockAdvanced {
+        /// Latest Bitcoin block height
+        #[schema(example = 870_000)]
+        height: u64,
+    },
+}
+
+impl Event {
+    /// Whether the event may be sent to the user with the given ID
+    #[must_use]
+    pub fn is_visible_to(&self, user_id: &Uuid) -> bool {
+        match self {
+            Self::UserUpdated { user } => user.id == *user_id,
+            Self::TransactionUpdated { transaction } => transaction.user_id == *user_id,
+            Self::BitcoinBlockAdvanced { .. } => true,
+        }
+    }
+}
+
+/// Periodic snapshot of the server state streamed from `/api/v1/events`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ServerSnapshot {
+    /// Latest Bitcoin block height, absent if the Bitcoin node is unreachable
+    #[schema(example = 870_000)]
+    pub bitcoin_block_height: Option<u64>,
+
+    /// Highest Bitcoin block with the confirmations required for an output to
+    /// count as confirmed
+    #[schema(example = 869_995)]
+    pub bitcoin_confirmed_height: Option<u64>,
+
+    /// Latest Solana slot, absent if the Solana node is unreachable
+    #[schema(example = 300_000_000)]
+    pub solana_slot: Option<u64>,
+
+    /// Seconds since the server started
+    #[schema(example = 3600)]
+    pub uptime_seconds: u64,
+
+    /// When the snapshot was taken
+    pub taken_at: DateTime<Utc>,
+}
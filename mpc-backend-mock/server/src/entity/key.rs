@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::Chain;
+
+/// Elliptic curve of a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "key_curve", rename_all = "lowercase")]
+pub enum KeyCurve {
+    /// Bitcoin keys
+    Secp256k1,
+    /// Solana keys
+    Ed25519,
+}
+
+/// Key of a user resulting from a simulated distributed key generation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Key {
+    /// Unique key ID
+    #[schema(example = "0b7e5f1c-8a2d-4c3e-9f4b-6d5e7a8b9c0d")]
+    pub id: Uuid,
+
+    /// ID of the user owning the key
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// Chain the key is used on
+    pub chain: Chain,
+
+    /// Curve of the key, `secp256k1` on Bitcoin and `ed25519` on Solana
+    pub curve: KeyCurve,
+
+    /// Fake public key, compressed and hex encoded for secp256k1, base58
+    /// encoded for ed25519
+    #[schema(example = "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5")]
+    pub public_key: String,
+
+    /// Timestamp when the key was generated
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to generate the key of the current user on a chain
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateKeyRequest {
+    /// Chain to generate the key for
+    pub chain: Chain,
+}
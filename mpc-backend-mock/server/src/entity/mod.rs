@@ -1,4 +1,63 @@
 // include the entities for the services
+mod admin;
+mod annotation;
+mod audit_log;
+mod auth;
+mod bitcoin;
+mod chain_state;
+mod changelog;
+mod deposit;
+mod domain_event;
+mod error_response;
+mod event;
+mod key;
+mod notification;
+mod signing;
+mod solana;
+mod transaction;
 mod user;
+mod wallet;
+mod webhook;
+mod withdrawal;
 
-pub use user::{CreateUserRequest, CreateUserResponse, DeleteUserParams, User, UserInfo};
+pub use admin::{
+    ApiDrift, ApiDriftReport, BackgroundTask, BulkDeleteUsersParams, BulkDeleteUsersResponse,
+    ClientIpResponse, ConfigReloadReport, DependencyState, DependencyStatus, ExportFormat,
+    ExportUsersParams, FaultInjection, FaultInjectionRule, HttpExchange, HttpLog, InjectedFault,
+    OpenApiBaseline, RouteSlo, SloReport, UpdateFaultInjectionRequest,
+};
+pub use annotation::{Annotation, CreateAnnotationRequest};
+pub use audit_log::{AuditAction, AuditLog, ListAuditLogsFilter};
+pub use auth::{
+    ImpersonationResponse, LoginAttempt, LoginRequest, RefreshTokenRequest, Session, TokenResponse,
+};
+pub use bitcoin::{
+    BitcoinBalance, BitcoinFeeEstimate, BitcoinFeeEstimates, BitcoinMempool, BitcoinUtxo,
+    BitcoinUtxoSet,
+};
+pub use chain_state::{ChainHeight, ChainState};
+pub use changelog::{ApiChange, ApiChangeKind, ApiChangelog, ApiRelease};
+pub use deposit::{Deposit, DepositStatus};
+pub use domain_event::{DomainEvent, EventOffset, OutboxEvent};
+pub use error_response::{ErrorDetail, ErrorResponse, ErrorType, FieldViolation};
+pub use event::{Event, ServerSnapshot};
+pub use key::{CreateKeyRequest, Key, KeyCurve};
+pub use notification::{
+    CapturedNotification, ListNotificationsFilter, NotificationStatus, OutboxNotification,
+    RecipientPreferences,
+};
+pub use signing::{CreateSigningSessionRequest, SigningSession, SigningSessionStatus};
+pub use solana::{SolanaAccount, SolanaBalance};
+pub use transaction::{SubmitTransactionRequest, Transaction, TransactionStatus};
+pub use user::{
+    ActivateUserRequest, ChangeEmailRequest, ChangePasswordRequest, ConfirmEmailChangeRequest,
+    CreateUserRequest, CreateUserResponse, DeleteUserParams, ListUsersFilter, ListedUser,
+    UpdateUserPreferencesRequest, UpdateUserProfileRequest, User, UserField, UserInfo,
+    UserPreferences, UserProfile, UserSortColumn,
+};
+pub use wallet::{BalanceHistoryParams, Chain, DailyBalance, Wallet, WalletBalanceHistory};
+pub use webhook::{
+    CreateWebhookRequest, DueWebhookDelivery, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+    WebhookEvent, WebhookEventType,
+};
+pub use withdrawal::{Withdrawal, WithdrawalStatus};
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Delivery status of a queued notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "notification_status", rename_all = "lowercase")]
+pub enum NotificationStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+
+    /// Sent successfully
+    Sent,
+
+    /// Dead-lettered after its last attempt failed, it is not retried
+    Dead,
+
+    /// Not delivered, none of its recipients wants it over any channel
+    Skipped,
+}
+
+/// Notification queued in the outbox
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct OutboxNotification {
+    /// Unique notification ID
+    #[schema(example = "9a8b7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d")]
+    pub id: Uuid,
+
+    /// Template name of the notification
+    #[schema(example = "activation_email")]
+    pub kind: String,
+
+    /// Primary recipients of the notification, comma-separated
+    #[schema(example = "user@example.com")]
+    pub recipient: String,
+
+    /// Serialized notification, never returned as it may contain secrets such
+    /// as activation links
+    #[serde(skip)]
+    pub payload: serde_json::Value,
+
+    /// Delivery status
+    pub status: NotificationStatus,
+
+    /// Number of delivery attempts made
+    #[schema(example = 1)]
+    pub attempts: i32,
+
+    /// Error of the last failed attempt
+    #[schema(example = "Failed to send email: 503 Service Unavailable")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    /// Earliest time of the next attempt, only meaningful while pending
+    pub next_attempt_at: DateTime<Utc>,
+
+    /// Timestamp when the notification was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<DateTime<Utc>>,
+
+    /// Timestamp when the notification was queued
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the notification was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Notification preferences of a recipient who set them, see
+/// [`UserPreferences`](super::UserPreferences)
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct RecipientPreferences {
+    /// Email address of the recipient
+    pub email: String,
+
+    /// Whether notifications are sent to the email address
+    pub email_enabled: bool,
+
+    /// URL the notifications are posted to
+    pub webhook_url: Option<String>,
+
+    /// BCP 47 language tag the notifications are sent in
+    pub locale: Option<String>,
+}
+
+/// Filters for listing queued notifications
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct ListNotificationsFilter {
+    /// Delivery status, e.g. `dead` for the dead-lettered notifications
+    pub status: Option<NotificationStatus>,
+}
+
+/// Email recorded by the `capture` notification provider instead of being
+/// sent
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct CapturedNotification {
+    /// Unique captured notification ID
+    #[schema(example = "9a8b7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d")]
+    pub id: Uuid,
+
+    /// Template name of the notification
+    #[schema(example = "activation_email")]
+    pub kind: String,
+
+    /// Primary recipients of the notification, comma-separated
+    #[schema(example = "user@example.com")]
+    pub recipient: String,
+
+    /// Rendered subject
+    #[schema(example = "Activate your Account")]
+    pub subject: String,
+
+    /// Rendered HTML body, activation links included
+    #[schema(example = "<h1>Welcome to Zionx!</h1>")]
+    pub html_body: String,
+
+    /// Timestamp when the email was captured
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Phase of a simulated MPC signing session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "signing_session_status", rename_all = "lowercase")]
+pub enum SigningSessionStatus {
+    /// Parties exchange their nonce commitments
+    Round1,
+    /// Parties exchange their partial signatures
+    Round2,
+    /// Signature is aggregated
+    Complete,
+    /// Session aborted, see its error
+    Failed,
+}
+
+/// Simulated MPC signing session of a payload
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct SigningSession {
+    /// Unique signing session ID
+    #[schema(example = "7c9e6679-7425-40de-944b-e07fc1f90ae7")]
+    pub id: Uuid,
+
+    /// ID of the requesting user
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// Payload to sign, hex encoded
+    #[schema(example = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+    pub payload: String,
+
+    /// Current phase of the session
+    pub status: SigningSessionStatus,
+
+    /// Duration of each simulated round, in milliseconds
+    #[schema(example = 2000)]
+    #[serde(rename = "round_duration_milliseconds")]
+    pub round_duration_ms: i64,
+
+    /// Round the session is set to fail in, absent if it completes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_at: Option<SigningSessionStatus>,
+
+    /// Fake 64-byte signature, hex encoded, absent until the session is
+    /// complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+
+    /// Reason the session failed, absent unless it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Timestamp when the session was created
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the session last changed phase
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to start a simulated MPC signing session
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateSigningSessionRequest {
+    /// Payload to sign, hex encoded, at most 4 KiB
+    #[schema(example = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+    #[validate(length(min = 2, max = 8192, message = "must be between 1 and 4096 bytes"))]
+    pub payload: String,
+
+    /// Duration of each simulated round in milliseconds, the configured
+    /// `signing.round_duration_milliseconds` when absent
+    #[schema(example = 500)]
+    #[validate(range(max = 60_000, message = "must be at most one minute"))]
+    pub round_duration_milliseconds: Option<u64>,
+
+    /// Round to fail the session in, `round1` or `round2`, for testing error
+    /// handling
+    pub fail_at: Option<SigningSessionStatus>,
+}
@@ -0,0 +1,57 @@
+use mpc_backend_mock_core::model::Lamports;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// SOL balance of a Solana account
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SolanaBalance {
+    /// Account public key
+    #[schema(example = "11111111111111111111111111111111")]
+    pub pubkey: String,
+
+    /// Balance of the account
+    pub lamports: Lamports,
+
+    /// Slot the balance was read at
+    #[schema(example = 315_000_000)]
+    pub slot: u64,
+
+    /// Commitment level the balance was read with
+    #[schema(example = "confirmed")]
+    pub commitment: String,
+}
+
+/// Solana account
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SolanaAccount {
+    /// Account public key
+    #[schema(example = "11111111111111111111111111111111")]
+    pub pubkey: String,
+
+    /// Balance of the account
+    pub lamports: Lamports,
+
+    /// Program owning the account
+    #[schema(example = "NativeLoader1111111111111111111111111111111")]
+    pub owner: String,
+
+    /// Whether the account holds a program
+    #[schema(example = false)]
+    pub executable: bool,
+
+    /// Epoch at which the account will next owe rent
+    #[schema(example = 0)]
+    pub rent_epoch: u64,
+
+    /// Account data, base64 encoded
+    #[schema(example = "")]
+    pub data: String,
+
+    /// Slot the account was read at
+    #[schema(example = 315_000_000)]
+    pub slot: u64,
+
+    /// Commitment level the account was read with
+    #[schema(example = "confirmed")]
+    pub commitment: String,
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Status of a submitted Solana transaction
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    sqlx::Type,
+    async_graphql::Enum,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "transaction_status", rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Pending,
+    Submitted,
+    Confirmed,
+    Finalized,
+    Failed,
+}
+
+/// Transaction entity representing a signed Solana transaction submitted by a
+/// user
+#[derive(
+    Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow, async_graphql::SimpleObject,
+)]
+pub struct Transaction {
+    /// Unique transaction ID
+    #[schema(example = "3f2b8c1d-5e6f-4a7b-9c8d-0e1f2a3b4c5d")]
+    pub id: Uuid,
+
+    /// ID of the submitting user
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// First signature of the transaction, base58 encoded
+    #[schema(
+        example = "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+    )]
+    pub signature: String,
+
+    /// Status of the transaction
+    pub status: TransactionStatus,
+
+    /// Slot the transaction was processed in, absent until it is seen on-chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<i64>,
+
+    /// Reason the transaction failed, absent unless it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Timestamp when the transaction was submitted
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the transaction was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to submit a signed Solana transaction
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct SubmitTransactionRequest {
+    /// Signed transaction in wire format, base64 encoded
+    #[schema(example = "AVXo5X7UNzpuOmYzkZ+fqHDGiRLTSMlWlUCcZKzEV5CIKlrdvZa3/\
+                        2GrJJfPrXgZqJbYDaGiOnP99tI/sRJfiwwBAAEDZ...")]
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub transaction: String,
+}
@@ -1,10 +1,14 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use mpc_backend_mock_core::model::Column;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
+use validator::Validate;
 
 /// User entity representing a user in the database
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+#[derive(
+    Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow, async_graphql::SimpleObject,
+)]
 pub struct User {
     /// Unique user ID
     #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
@@ -18,6 +22,11 @@ pub struct User {
     #[schema(example = "550e8400-e29b-41d4-a716-446655440001")]
     pub keycloak_user_id: Uuid,
 
+    /// Keycloak realm of the user, absent for the primary realm
+    #[schema(example = "partner")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realm: Option<String>,
+
     /// Whether the user account is active
     #[schema(example = true)]
     pub is_active: bool,
@@ -49,18 +58,260 @@ pub struct UserInfo {
     #[schema(example = true)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email_verified: Option<bool>,
+
+    /// Profile edited by the user, absent until it is first updated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<UserProfile>,
+}
+
+/// Settings a user edits on their own
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct UserProfile {
+    /// User ID
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// Name shown instead of the email address
+    #[schema(example = "Satoshi")]
+    pub display_name: Option<String>,
+
+    /// BCP 47 language tag of the user's language
+    #[schema(example = "zh-TW")]
+    pub locale: Option<String>,
+
+    /// Timestamp when the profile was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Partial update of the current user's profile, absent fields are kept
+///
+/// The fields are checked by the service, which answers with
+/// `INVALID_USER_PROFILE`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUserProfileRequest {
+    /// Name shown instead of the email address, 1 to 64 characters
+    #[schema(example = "Satoshi")]
+    pub display_name: Option<String>,
+
+    /// BCP 47 language tag, e.g. `en` or `zh-TW`
+    #[schema(example = "zh-TW")]
+    pub locale: Option<String>,
+}
+
+/// Channels the notifications of a user are delivered over
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct UserPreferences {
+    /// User ID
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// Whether notifications are sent to the user's email address
+    #[schema(example = true)]
+    pub email_enabled: bool,
+
+    /// URL the notifications are posted to as JSON
+    #[schema(example = "https://example.com/hooks/mpc")]
+    pub webhook_url: Option<String>,
+
+    /// BCP 47 language tag the notifications are sent in, the language of
+    /// the notification without
+    #[schema(example = "zh-TW")]
+    pub locale: Option<String>,
+
+    /// Timestamp when the preferences were last updated, absent while they
+    /// are the defaults
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl UserPreferences {
+    /// Preferences of a user who never set them: emails only, in the language
+    /// of the notification
+    #[must_use]
+    pub const fn defaults(user_id: Uuid) -> Self {
+        Self { user_id, email_enabled: true, webhook_url: None, locale: None, updated_at: None }
+    }
+}
+
+/// Notification preferences of the current user, replacing the current ones
+///
+/// The fields are checked by the service, which answers with
+/// `INVALID_USER_PREFERENCES`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUserPreferencesRequest {
+    /// Whether notifications are sent to the user's email address
+    #[schema(example = true)]
+    pub email_enabled: bool,
+
+    /// `http` or `https` URL the notifications are posted to, none without
+    #[schema(example = "https://example.com/hooks/mpc")]
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+
+    /// BCP 47 language tag the notifications are sent in, e.g. `en` or
+    /// `zh-TW`
+    #[schema(example = "zh-TW")]
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Request to change the current user's password
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ChangePasswordRequest {
+    /// Password the user currently logs in with
+    #[schema(example = "test123")]
+    #[validate(length(min = 1, max = 128, message = "must be 1 to 128 characters"))]
+    pub current_password: String,
+
+    /// New password, 12 to 128 characters mixing letters with digits or
+    /// symbols
+    #[schema(example = "correct-horse-42")]
+    pub new_password: String,
+}
+
+/// Filters for listing users
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct ListUsersFilter {
+    /// Case-insensitive substring of the user's email address
+    #[param(example = "example.com")]
+    pub email_like: Option<String>,
+
+    /// Whether the user account is active
+    pub is_active: Option<bool>,
+
+    /// Only include users created on or after this date (YYYY-MM-DD, UTC)
+    #[param(value_type = Option<String>, example = "2026-01-01")]
+    #[schema(value_type = Option<String>, example = "2026-01-01")]
+    pub created_after: Option<NaiveDate>,
+}
+
+/// Column `GET /api/v1/users` may be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserSortColumn {
+    CreatedAt,
+    UpdatedAt,
+    Email,
+    IsActive,
+}
+
+impl Column for UserSortColumn {
+    const ALLOWED: &'static [Self] =
+        &[Self::CreatedAt, Self::UpdatedAt, Self::Email, Self::IsActive];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::Email => "email",
+            Self::IsActive => "is_active",
+        }
+    }
+}
+
+/// Field of [`User`] `GET /api/v1/users` may be projected onto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserField {
+    Id,
+    Email,
+    KeycloakUserId,
+    IsActive,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl Column for UserField {
+    const ALLOWED: &'static [Self] = &[
+        Self::Id,
+        Self::Email,
+        Self::KeycloakUserId,
+        Self::IsActive,
+        Self::CreatedAt,
+        Self::UpdatedAt,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Email => "email",
+            Self::KeycloakUserId => "keycloak_user_id",
+            Self::IsActive => "is_active",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+/// User as listed, only the requested fields are present when `fields` is set
+///
+/// Documented as [`User`], whose fields a projection is a subset of.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ListedUser {
+    User(User),
+    Fields(serde_json::Map<String, serde_json::Value>),
 }
 
 /// Request to create a new user
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateUserRequest {
-    /// User's email address
+    /// User's email address, parsed by the service so that an invalid one is
+    /// answered with `INVALID_EMAIL`
     #[schema(example = "user@example.com")]
+    #[validate(length(max = 254, message = "must be at most 254 characters"))]
+    pub email: String,
+
+    /// BCP 47 language tag of the user's language, e.g. `en` or `zh-TW`, the
+    /// activation email is sent in it and it is stored on the user's profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "zh-TW")]
+    #[validate(length(max = 35, message = "must be at most 35 characters"))]
+    pub locale: Option<String>,
+
+    /// Keycloak realm to create the user in, one of `keycloak.realms`, the
+    /// primary realm if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "partner")]
+    #[validate(length(max = 255, message = "must be at most 255 characters"))]
+    pub realm: Option<String>,
+}
+
+/// Request to activate a user
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ActivateUserRequest {
+    /// Activation token from the activation email
+    #[schema(example = "q3Vx2cYz8Jm0b1N4kLw7Hs5fTg9Ra6Ue2Pd1Oi3Mn4K")]
+    #[validate(length(min = 1, max = 128, message = "must be 1 to 128 characters"))]
+    pub token: String,
+}
+
+/// Request to change the current user's email
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ChangeEmailRequest {
+    /// New email address, a verification link is sent to it
+    #[schema(example = "new@example.com")]
+    #[validate(length(max = 254, message = "must be at most 254 characters"))]
     pub email: String,
 }
 
-/// alias for delete user query parameters
-pub type DeleteUserParams = CreateUserRequest;
+/// Request to confirm an email change
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ConfirmEmailChangeRequest {
+    /// Token from the email change verification link
+    #[schema(example = "q3Vx2cYz8Jm0b1N4kLw7Hs5fTg9Ra6Ue2Pd1Oi3Mn4K")]
+    #[validate(length(min = 1, max = 128, message = "must be 1 to 128 characters"))]
+    pub token: String,
+}
+
+/// Query parameters to delete a user
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteUserParams {
+    /// Email of the user to delete
+    #[schema(example = "user@example.com")]
+    pub email: String,
+}
 
 /// Response after creating a user
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
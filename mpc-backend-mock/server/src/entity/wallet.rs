@@ -0,0 +1,88 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use mpc_backend_mock_core::model::TokenAmount;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Blockchain a wallet lives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "chain", rename_all = "lowercase")]
+pub enum Chain {
+    Bitcoin,
+    Solana,
+}
+
+/// Wallet entity representing an on-chain address owned by a user
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Wallet {
+    /// Unique wallet ID
+    #[schema(example = "7f1c2a9e-3b4d-4e5f-8a6b-1c2d3e4f5a6b")]
+    pub id: Uuid,
+
+    /// ID of the user who owns the wallet
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// Blockchain of the wallet
+    pub chain: Chain,
+
+    /// On-chain address
+    #[schema(example = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx")]
+    pub address: String,
+
+    /// Timestamp when the wallet was created
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the wallet was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Balance of a wallet at the end of a day (UTC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct DailyBalance {
+    /// Day of the balance (YYYY-MM-DD, UTC)
+    #[schema(value_type = String, example = "2026-01-01")]
+    pub date: NaiveDate,
+
+    /// Balance in the smallest unit of the chain
+    pub balance: TokenAmount,
+}
+
+/// Query parameters of the wallet balance history
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BalanceHistoryParams {
+    /// First day of the history (YYYY-MM-DD, UTC), defaults to 29 days before
+    /// `to`
+    #[param(value_type = Option<String>, example = "2026-01-01")]
+    pub from: Option<NaiveDate>,
+
+    /// Last day of the history (YYYY-MM-DD, UTC), defaults to today and is
+    /// capped at today
+    #[param(value_type = Option<String>, example = "2026-01-31")]
+    pub to: Option<NaiveDate>,
+}
+
+/// Daily balance history of a wallet
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WalletBalanceHistory {
+    /// ID of the wallet
+    #[schema(example = "7f1c2a9e-3b4d-4e5f-8a6b-1c2d3e4f5a6b")]
+    pub wallet_id: Uuid,
+
+    /// Blockchain of the wallet
+    pub chain: Chain,
+
+    /// First day of the history
+    #[schema(value_type = String, example = "2026-01-01")]
+    pub from: NaiveDate,
+
+    /// Last day of the history
+    #[schema(value_type = String, example = "2026-01-31")]
+    pub to: NaiveDate,
+
+    /// One balance per day, oldest first, days before the first snapshot of
+    /// the wallet are left out
+    pub balances: Vec<DailyBalance>,
+}
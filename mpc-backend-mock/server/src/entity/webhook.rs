@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Type of the events a webhook subscribes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEventType {
+    /// The user redeemed its activation token
+    #[serde(rename = "user.activated")]
+    UserActivated,
+
+    /// A Solana transaction submitted by the user reached the confirmed or
+    /// finalized commitment
+    #[serde(rename = "transaction.confirmed")]
+    TransactionConfirmed,
+}
+
+impl WebhookEventType {
+    /// Name of the event type, e.g. `user.activated`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::UserActivated => "user.activated",
+            Self::TransactionConfirmed => "transaction.confirmed",
+        }
+    }
+}
+
+/// Event of a user posted to the webhooks of the user subscribing to its type,
+/// serialized as the `data` of the posted body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum WebhookEvent {
+    UserActivated { user_id: Uuid, email: String },
+
+    TransactionConfirmed { transaction_id: Uuid, signature: String, slot: Option<i64> },
+}
+
+impl WebhookEvent {
+    /// Type of the event
+    #[must_use]
+    pub const fn event_type(&self) -> WebhookEventType {
+        match self {
+            Self::UserActivated { .. } => WebhookEventType::UserActivated,
+            Self::TransactionConfirmed { .. } => WebhookEventType::TransactionConfirmed,
+        }
+    }
+}
+
+/// Callback URL of a user, receiving the events of the user it subscribes to
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Webhook {
+    /// Unique webhook ID
+    #[schema(example = "3f1c2b4a-5d6e-4f70-8a9b-0c1d2e3f4a5b")]
+    pub id: Uuid,
+
+    /// ID of the user owning the webhook
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub user_id: Uuid,
+
+    /// URL the events are posted to
+    #[schema(example = "https://example.com/hooks/mpc")]
+    pub url: String,
+
+    /// Types of the events posted to the webhook
+    #[schema(example = json!(["user.activated", "transaction.confirmed"]))]
+    pub event_types: Vec<String>,
+
+    /// Key of the HMAC-SHA256 signature sent with every event in the
+    /// `X-Webhook-Signature` header
+    #[schema(example = "whsec_5f2b9c0e7a1d4e3b8c6f0a9d2e7b4c1f8a3d6e9b0c2f5a7d")]
+    pub secret: String,
+
+    /// Timestamp when the webhook was registered
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to register a webhook of the current user
+///
+/// The fields are checked by the service, which answers with
+/// `INVALID_WEBHOOK`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWebhookRequest {
+    /// `http` or `https` URL the events are posted to
+    #[schema(example = "https://example.com/hooks/mpc")]
+    pub url: String,
+
+    /// Types of the events to post, at least one
+    #[schema(example = json!(["user.activated", "transaction.confirmed"]))]
+    pub event_types: Vec<WebhookEventType>,
+}
+
+/// Delivery status of an event posted to a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+
+    /// Answered with a success status by the webhook
+    Delivered,
+
+    /// Dead-lettered after its last attempt failed, it is not retried
+    Dead,
+}
+
+/// Event queued for delivery to a webhook, with its delivery attempts
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    /// Unique delivery ID, also the `id` of the posted body
+    #[schema(example = "7c6d5e4f-3a2b-4c1d-9e0f-8a7b6c5d4e3f")]
+    pub id: Uuid,
+
+    /// ID of the webhook
+    #[schema(example = "3f1c2b4a-5d6e-4f70-8a9b-0c1d2e3f4a5b")]
+    pub webhook_id: Uuid,
+
+    /// Type of the event
+    #[schema(example = "user.activated")]
+    pub event_type: String,
+
+    /// Data of the event, posted as the `data` of the body
+    pub payload: serde_json::Value,
+
+    /// Delivery status
+    pub status: WebhookDeliveryStatus,
+
+    /// Number of delivery attempts made
+    #[schema(example = 1)]
+    pub attempts: i32,
+
+    /// HTTP status the webhook answered the last attempt with
+    #[schema(example = 204)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<i32>,
+
+    /// Error of the last failed attempt
+    #[schema(example = "Webhook answered with 503 Service Unavailable")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    /// Earliest time of the next attempt, only meaningful while pending
+    pub next_attempt_at: DateTime<Utc>,
+
+    /// Timestamp when the webhook accepted the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<DateTime<Utc>>,
+
+    /// Timestamp when the event was queued
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the delivery was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Delivery whose next attempt is due, with the webhook to post it to
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DueWebhookDelivery {
+    pub id: Uuid,
+
+    pub url: String,
+
+    pub secret: String,
+
+    pub event_type: String,
+
+    pub payload: serde_json::Value,
+
+    pub attempts: i32,
+
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_event_type_names() {
+        for event_type in [WebhookEventType::UserActivated, WebhookEventType::TransactionConfirmed]
+        {
+            assert_eq!(serde_json::to_value(event_type).unwrap(), event_type.as_str());
+        }
+
+        let event = WebhookEvent::TransactionConfirmed {
+            transaction_id: Uuid::nil(),
+            signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb".to_string(),
+            slot: Some(42),
+        };
+        assert_eq!(event.event_type(), WebhookEventType::TransactionConfirmed);
+        assert_eq!(serde_json::to_value(&event).unwrap()["slot"], 42);
+    }
+}
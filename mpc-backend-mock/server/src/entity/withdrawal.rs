@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use mpc_backend_mock_core::model::TokenAmount;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Status of a withdrawal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "withdrawal_status", rename_all = "lowercase")]
+pub enum WithdrawalStatus {
+    Pending,
+    Broadcast,
+    Confirmed,
+    Failed,
+}
+
+/// Withdrawal entity representing an outgoing transfer from a wallet
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Withdrawal {
+    /// Unique withdrawal ID
+    #[schema(example = "9c3d5e7f-1a2b-4c4d-8e6f-7a8b9c0d1e2f")]
+    pub id: Uuid,
+
+    /// ID of the sending wallet
+    #[schema(example = "7f1c2a9e-3b4d-4e5f-8a6b-1c2d3e4f5a6b")]
+    pub wallet_id: Uuid,
+
+    /// Address the funds are sent to
+    #[schema(example = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx")]
+    pub destination_address: String,
+
+    /// Amount in the smallest unit of the chain
+    pub amount: TokenAmount,
+
+    /// Status of the withdrawal
+    pub status: WithdrawalStatus,
+
+    /// Transaction ID, absent until the withdrawal is broadcast
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_id: Option<String>,
+
+    /// Timestamp when the withdrawal was created
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the withdrawal was last updated
+    pub updated_at: DateTime<Utc>,
+}
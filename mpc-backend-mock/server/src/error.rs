@@ -22,9 +22,24 @@ pub enum Error {
         source: sqlx::error::Error,
     },
 
+    #[snafu(display("Failed to acquire postgres connection for migration, error: {source}"))]
+    AcquireMigrationConnection { source: sqlx::error::Error },
+
+    #[snafu(display("Failed to acquire migration advisory lock, error: {source}"))]
+    AcquireMigrationLock { source: sqlx::error::Error },
+
+    #[snafu(display("Failed to release migration advisory lock, error: {source}"))]
+    ReleaseMigrationLock { source: sqlx::error::Error },
+
     #[snafu(display("Fail to migrate postgres schema, error: {source}",))]
     MigrateSchema { source: sqlx::migrate::MigrateError },
 
+    #[snafu(display("Failed to check for the migrations table, error: {source}"))]
+    CheckMigrationTable { source: sqlx::error::Error },
+
+    #[snafu(display("Postgres schema migration did not finish within {timeout:?}"))]
+    MigrationTimeout { timeout: std::time::Duration },
+
     #[snafu(display("Error occurs while creating Bitcoin RPC client, error: {source}"))]
     CreateBitcoinRpcClient { source: eris_bitcoin_rpc_client::Error },
 
@@ -34,9 +49,27 @@ pub enum Error {
     #[snafu(display("{source}"))]
     Metrics { source: zeus_metrics::Error },
 
+    #[snafu(display("Failed to create background task metrics, error: {source}"))]
+    CreateTaskMetrics { source: prometheus::Error },
+
+    #[snafu(display("Failed to create HTTP metrics, error: {source}"))]
+    CreateHttpMetrics { source: prometheus::Error },
+
+    #[snafu(display("Failed to create database metrics, error: {source}"))]
+    CreateDatabaseMetrics { source: prometheus::Error },
+
+    #[snafu(display("Failed to create worker metrics, error: {source}"))]
+    CreateWorkerMetrics { source: prometheus::Error },
+
+    #[snafu(display("Failed to create dependency metrics, error: {source}"))]
+    CreateDependencyMetrics { source: prometheus::Error },
+
     #[snafu(display("Error occurs while starting tonic server, error: {source}"))]
     StartTonicServer { source: tonic::transport::Error },
 
+    #[snafu(display("Failed to configure TLS of the gRPC health check server, error: {source}"))]
+    ConfigureHealthCheckTls { source: tonic::transport::Error },
+
     #[snafu(display("{source}"))]
     Web { source: web::Error },
 
@@ -46,11 +79,35 @@ pub enum Error {
     #[snafu(display("Failed to initialize JWKS client: {message}"))]
     InitializeJwksClient { message: String },
 
+    #[snafu(display("Failed to initialize notification client, error: {source}"))]
+    InitializeNotificationClient { source: notification::Error },
+
+    #[snafu(display("Failed to initialize event publisher, error: {source}"))]
+    InitializeEventPublisher { source: events::Error },
+
+    #[snafu(display("Failed to initialize the cache and rate limit store, error: {source}"))]
+    InitializeStore { source: crate::store::Error },
+
     #[snafu(display("Failed to initialize Keycloak admin client: {message}"))]
     InitializeKeycloakAdmin { message: String },
 
     #[snafu(display("Failed to initialize Keycloak client: {message}"))]
     InitializeKeycloakClient { message: String },
+
+    #[snafu(display("{source}"))]
+    ManageUser { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to seed fixtures, error: {source}"))]
+    SeedFixtures { source: crate::service::error::Error },
+
+    #[snafu(display("Initializing {step} did not finish within {timeout:?}"))]
+    StartupStepTimeout { step: &'static str, timeout: std::time::Duration },
+
+    #[snafu(display("Failed to open cassette `{}`, error: {source}", path.display()))]
+    OpenCassette { path: std::path::PathBuf, source: std::io::Error },
+
+    #[snafu(display("Failed to parse cassette `{}`, error: {source}", path.display()))]
+    ParseCassette { path: std::path::PathBuf, source: serde_json::Error },
 }
 
 impl From<zeus_metrics::Error> for Error {
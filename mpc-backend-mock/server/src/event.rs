@@ -0,0 +1,103 @@
+//! In-process event bus.
+//!
+//! Services publish [`Event`]s to the [`EventBus`] and every subscriber, such
+//! as a WebSocket connection, receives its own copy. Events are not persisted,
+//! a subscriber only sees the events published after it subscribed and a
+//! subscriber falling more than [`EventBus::CAPACITY`] events behind misses the
+//! oldest ones.
+//!
+//! The latest [`ServerSnapshot`] is kept apart from the events: a subscriber
+//! receives it right away and then every newer one, skipping those replaced
+//! before it got to them.
+
+use tokio::sync::{broadcast, watch};
+
+use crate::entity::{Event, ServerSnapshot};
+
+/// Broadcast channel shared by the event publishers and subscribers
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+
+    snapshot: watch::Sender<Option<ServerSnapshot>>,
+}
+
+impl EventBus {
+    /// Number of events buffered for each subscriber
+    pub const CAPACITY: usize = 1024;
+
+    /// Create an event bus without subscribers
+    #[must_use]
+    pub fn new() -> Self {
+        let (sender, _receiver) = broadcast::channel(Self::CAPACITY);
+        let (snapshot, _receiver) = watch::channel(None);
+        Self { sender, snapshot }
+    }
+
+    /// Publish an event to the current subscribers
+    pub fn publish(&self, event: Event) {
+        // sending only fails when nobody is subscribed, the event is dropped then
+        let _receivers = self.sender.send(event);
+    }
+
+    /// Subscribe to the events published from now on
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> { self.sender.subscribe() }
+
+    /// Replace the latest server snapshot
+    pub fn publish_snapshot(&self, snapshot: ServerSnapshot) {
+        let _previous = self.snapshot.send_replace(Some(snapshot));
+    }
+
+    /// Subscribe to the latest server snapshot, `None` until the first one is
+    /// published
+    #[must_use]
+    pub fn subscribe_snapshots(&self) -> watch::Receiver<Option<ServerSnapshot>> {
+        self.snapshot.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_and_subscribe() {
+        let bus = EventBus::new();
+
+        // publishing without subscribers is not an error
+        bus.publish(Event::BitcoinBlockAdvanced { height: 1 });
+
+        let mut receiver = bus.subscribe();
+        bus.publish(Event::BitcoinBlockAdvanced { height: 2 });
+
+        let event = receiver.recv().await.expect("event is received");
+        assert!(matches!(event, Event::BitcoinBlockAdvanced { height: 2 }));
+    }
+
+    #[tokio::test]
+    async fn test_latest_snapshot() {
+        let bus = EventBus::new();
+        let snapshot = |uptime_seconds| ServerSnapshot {
+            bitcoin_block_height: Some(100),
+            bitcoin_confirmed_height: Some(95),
+            solana_slot: None,
+            uptime_seconds,
+            taken_at: chrono::Utc::now(),
+        };
+
+        let mut receiver = bus.subscribe_snapshots();
+        assert!(receiver.borrow_and_update().is_none());
+
+        // a late subscriber only sees the latest snapshot
+        bus.publish_snapshot(snapshot(1));
+        bus.publish_snapshot(snapshot(2));
+        receiver.changed().await.expect("snapshot is published");
+        let latest = receiver.borrow_and_update().clone().expect("snapshot is set");
+        assert_eq!(latest.uptime_seconds, 2);
+    }
+}
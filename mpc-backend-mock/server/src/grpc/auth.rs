@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use tonic::{service::Interceptor, Request, Status};
+
+/// Rejects calls without the configured `authorization: Bearer <token>`
+///
+/// Every call passes when no token is configured.
+#[derive(Clone, Debug, Default)]
+pub struct BearerTokenInterceptor {
+    token: Option<Arc<str>>,
+}
+
+impl BearerTokenInterceptor {
+    pub fn new(token: Option<String>) -> Self { Self { token: token.map(Into::into) } }
+}
+
+impl Interceptor for BearerTokenInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(expected) = &self.token else {
+            return Ok(request);
+        };
+
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+
+        if constant_time_eq(token.as_bytes(), expected.as_bytes()) {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("invalid bearer token"))
+        }
+    }
+}
+
+/// Compares without returning early, so the time taken does not tell how
+/// much of the token matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::Code;
+
+    use super::*;
+
+    fn request(authorization: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        if let Some(authorization) = authorization {
+            let _previous =
+                request.metadata_mut().insert("authorization", authorization.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let mut interceptor = BearerTokenInterceptor::new(Some("s3cret".to_string()));
+
+        assert!(interceptor.call(request(Some("Bearer s3cret"))).is_ok());
+        for authorization in [None, Some("Bearer wrong"), Some("Bearer s3cre"), Some("s3cret")] {
+            let status = interceptor.call(request(authorization)).unwrap_err();
+            assert_eq!(status.code(), Code::Unauthenticated, "{authorization:?}");
+        }
+
+        assert!(BearerTokenInterceptor::new(None).call(request(None)).is_ok());
+    }
+}
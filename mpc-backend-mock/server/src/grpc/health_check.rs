@@ -1,8 +1,9 @@
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use eris_bitcoin_rpc_client::Client as BitcoinRpcClient;
-use sqlx::{Executor, PgPool};
+use futures::future;
+use mpc_backend_mock_core::config::HealthCheckComponent;
+use sqlx::{Executor, PgConnection, PgPool};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
@@ -10,21 +11,66 @@ use zeus_protobuf_types::health_check::{
     self as proto, HealthCheckRequest, HealthCheckResponse, HealthCheckServingStatus,
 };
 
+use crate::{
+    chain_state::ChainStateCache,
+    keycloak_client::KeycloakClient,
+    migrate::MIGRATOR,
+    service::{BitcoinChain, SolanaChain},
+    task::TaskSupervisor,
+};
+
+type CheckResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// gRPC health service reporting on the configured components
+///
+/// An empty service name checks every component, the name of a component,
+/// such as `keycloak`, checks that one alone.
 #[derive(Clone)]
 pub struct HealthCheckService {
-    bitcoin_rpc_client: BitcoinRpcClient,
+    components: Vec<HealthCheckComponent>,
+
+    checker: HealthChecker,
+
+    tasks: TaskSupervisor,
+}
+
+/// Dependencies the components are checked against
+///
+/// A chain whose height was refreshed recently is healthy without a call to
+/// its node, the node is only asked once the cached height is stale.
+#[derive(Clone)]
+pub struct HealthChecker {
+    pub bitcoin_chain: Arc<dyn BitcoinChain>,
+
+    pub solana_chain: Arc<dyn SolanaChain>,
 
-    database: PgPool,
+    pub chain_state: ChainStateCache,
+
+    pub keycloak_client: Arc<KeycloakClient>,
+
+    pub database: PgPool,
 }
 
 impl HealthCheckService {
     #[must_use]
-    pub const fn new(bitcoin_rpc_client: BitcoinRpcClient, database: PgPool) -> Self {
-        Self { bitcoin_rpc_client, database }
+    pub const fn new(
+        components: Vec<HealthCheckComponent>,
+        checker: HealthChecker,
+        tasks: TaskSupervisor,
+    ) -> Self {
+        Self { components, checker, tasks }
     }
 
-    async fn perform_health_checking(&self) -> Result<(), Box<dyn std::error::Error>> {
-        perform_health_checking(&self.bitcoin_rpc_client, &self.database).await
+    /// Components checked for `service`, `None` if it names none of them
+    fn components_of(&self, service: &str) -> Option<Vec<HealthCheckComponent>> {
+        if service.is_empty() {
+            return Some(self.components.clone());
+        }
+
+        self.components
+            .iter()
+            .find(|component| component.as_str() == service)
+            .map(|component| vec![*component])
     }
 }
 
@@ -34,36 +80,39 @@ impl proto::Health for HealthCheckService {
 
     async fn check(
         &self,
-        _req: Request<HealthCheckRequest>,
+        req: Request<HealthCheckRequest>,
     ) -> Result<Response<HealthCheckResponse>, Status> {
-        let status = match self.perform_health_checking().await {
-            Ok(()) => HealthCheckServingStatus::Serving,
-            Err(err) => {
-                tracing::error!("{err}");
-                HealthCheckServingStatus::NotServing
-            }
-        };
+        let service = req.into_inner().service;
+        let components = self
+            .components_of(&service)
+            .ok_or_else(|| Status::not_found(format!("unknown service `{service}`")))?;
+
+        let status = self.checker.check(&components).await;
 
         Ok(Response::new(HealthCheckResponse { status: status.into() }))
     }
 
     async fn watch(
         &self,
-        _req: Request<HealthCheckRequest>,
+        req: Request<HealthCheckRequest>,
     ) -> Result<Response<Self::WatchStream>, Status> {
         let (tx, rx) = mpsc::channel(10);
 
-        let bitcoin_rpc_client = self.bitcoin_rpc_client.clone();
-        let database = self.database.clone();
-        let _unused = tokio::spawn(async move {
+        let service = req.into_inner().service;
+        let Some(components) = self.components_of(&service) else {
+            // the protocol keeps the call open for services which may become
+            // known later, this server never learns new ones
+            let response =
+                HealthCheckResponse { status: HealthCheckServingStatus::ServiceUnknown.into() };
+            drop(tx.try_send(Ok(response)));
+            self.tasks.spawn("Health check watch", async move { tx.closed().await });
+            return Ok(Response::new(ReceiverStream::new(rx)));
+        };
+
+        let checker = self.checker.clone();
+        self.tasks.spawn("Health check watch", async move {
             loop {
-                let status = match perform_health_checking(&bitcoin_rpc_client, &database).await {
-                    Ok(()) => HealthCheckServingStatus::Serving,
-                    Err(err) => {
-                        tracing::error!("{err}");
-                        HealthCheckServingStatus::NotServing
-                    }
-                };
+                let status = checker.check(&components).await;
 
                 if tx.send(Ok(HealthCheckResponse { status: status.into() })).await.is_err() {
                     break;
@@ -77,18 +126,83 @@ impl proto::Health for HealthCheckService {
     }
 }
 
-async fn perform_health_checking(
-    bitcoin_rpc_client: &BitcoinRpcClient,
-    database: &PgPool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    tracing::debug!(
-        "Checking Bitcoin client with Bitcoin RPC client via endpoint {}",
-        &bitcoin_rpc_client.rpc_endpoint()
-    );
-    let _unused = bitcoin_rpc_client.get_block_count().await?;
-
-    let mut conn = database.acquire().await?;
-    let _unused = conn.execute("SELECT 1").await?;
+impl HealthChecker {
+    /// Serving only if every component is, the components are checked
+    /// concurrently
+    async fn check(&self, components: &[HealthCheckComponent]) -> HealthCheckServingStatus {
+        let results =
+            future::join_all(components.iter().map(|component| self.check_component(*component)))
+                .await;
+
+        let mut status = HealthCheckServingStatus::Serving;
+        for (component, result) in components.iter().zip(results) {
+            if let Err(err) = result {
+                tracing::error!(component = component.as_str(), "Health check failed: {err}");
+                status = HealthCheckServingStatus::NotServing;
+            }
+        }
+
+        status
+    }
+
+    async fn check_component(&self, component: HealthCheckComponent) -> CheckResult {
+        match component {
+            HealthCheckComponent::Postgres => {
+                let mut conn = self.database.acquire().await?;
+                let _unused = conn.execute("SELECT 1").await?;
+
+                check_migrations(&mut conn).await
+            }
+            HealthCheckComponent::Bitcoin => {
+                if self.chain_state.fresh_bitcoin_block_height().is_some() {
+                    return Ok(());
+                }
+
+                tracing::debug!("Checking Bitcoin client via {}", self.bitcoin_chain.describe());
+                let block_height = self.bitcoin_chain.get_block_count().await?;
+                self.chain_state.set_bitcoin_block_height(block_height);
+                Ok(())
+            }
+            HealthCheckComponent::Keycloak => {
+                if self.keycloak_client.health_check().await? {
+                    Ok(())
+                } else {
+                    Err("Keycloak is not ready".into())
+                }
+            }
+            HealthCheckComponent::Solana => {
+                if self.chain_state.fresh_solana_slot().is_some() {
+                    return Ok(());
+                }
+
+                let slot = self.solana_chain.get_slot().await?;
+                self.chain_state.set_solana_slot(slot);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Fail unless every migration of this build is applied, e.g. while another
+/// replica holds the migration lock or after a migration failed
+async fn check_migrations(conn: &mut PgConnection) -> CheckResult {
+    let applied: HashMap<i64, bool> =
+        sqlx::query_file!("sql/migration/list_applied_migrations.sql")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|migration| (migration.version, migration.success))
+            .collect();
+
+    for migration in
+        MIGRATOR.iter().filter(|migration| !migration.migration_type.is_down_migration())
+    {
+        match applied.get(&migration.version) {
+            Some(true) => {}
+            Some(false) => return Err(format!("Migration {} failed", migration.version).into()),
+            None => return Err(format!("Migration {} is not applied", migration.version).into()),
+        }
+    }
 
     Ok(())
 }
@@ -1,3 +1,7 @@
+mod auth;
 mod health_check;
 
-pub use self::health_check::HealthCheckService;
+pub use self::{
+    auth::BearerTokenInterceptor,
+    health_check::{HealthCheckService, HealthChecker},
+};
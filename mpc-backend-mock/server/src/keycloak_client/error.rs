@@ -91,4 +91,30 @@ pub enum Error {
         location: Location,
         source: serde_json::Error,
     },
+
+    #[snafu(display("Failed to request token: {source}, location: {location}"))]
+    RequestToken {
+        #[snafu(implicit)]
+        location: Location,
+        source: reqwest::Error,
+    },
+
+    #[snafu(display(
+        "Keycloak rejected token request with status {status}: {error} ({description}), location: \
+         {location}"
+    ))]
+    TokenRejected {
+        #[snafu(implicit)]
+        location: Location,
+        status: u16,
+        error: String,
+        description: String,
+    },
+
+    #[snafu(display("Failed to parse token response: {source}, location: {location}"))]
+    ParseTokenResponse {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_json::Error,
+    },
 }
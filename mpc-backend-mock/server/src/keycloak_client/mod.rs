@@ -10,8 +10,16 @@ use uuid::Uuid;
 
 use self::error::{
     CreateUserSnafu, GetUserSnafu, HealthCheckSnafu, IntrospectTokenSnafu,
-    ParseIntrospectionResponseSnafu, Result, UserNotFoundSnafu,
+    ParseIntrospectionResponseSnafu, ParseTokenResponseSnafu, RequestTokenSnafu, Result,
+    TokenRejectedSnafu, UserNotFoundSnafu,
 };
+use crate::dependency_metrics::DependencyMetrics;
+
+/// Grant type of Keycloak's token exchange
+const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+/// Token type of access tokens requested by a token exchange
+const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
 
 /// Token introspection response from Keycloak
 #[derive(Debug, serde::Deserialize)]
@@ -51,6 +59,45 @@ pub struct TokenIntrospectionResponse {
     /// JWT ID
     #[serde(default)]
     pub jti: Option<String>,
+    /// Realm roles granted to the subject
+    #[serde(default)]
+    pub realm_access: Option<RealmAccess>,
+}
+
+/// `realm_access` claim of Keycloak tokens
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct RealmAccess {
+    /// Realm roles granted to the subject
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// Access token response from Keycloak's token endpoint
+#[derive(Debug, serde::Deserialize)]
+pub struct AccessTokenResponse {
+    /// The issued access token
+    pub access_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+    /// The issued refresh token
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Refresh token lifetime in seconds
+    #[serde(default)]
+    pub refresh_expires_in: Option<i64>,
+    /// Token type, usually `Bearer`
+    pub token_type: String,
+    /// Granted scopes
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+/// OAuth 2.0 error response from Keycloak's token endpoint
+#[derive(Debug, serde::Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
 }
 
 /// Keycloak client wrapper for user management and authentication
@@ -61,6 +108,7 @@ pub struct KeycloakClient {
     server_url: String,
     client_id: String,
     client_secret: String,
+    dependency_metrics: DependencyMetrics,
 }
 
 impl KeycloakClient {
@@ -86,9 +134,18 @@ impl KeycloakClient {
             server_url: config.server_url,
             client_id: config.client_id,
             client_secret: config.client_secret,
+            dependency_metrics: DependencyMetrics::default(),
         })
     }
 
+    /// Record the latency of the token and introspection requests in
+    /// `dependency_metrics`
+    #[must_use]
+    pub fn with_dependency_metrics(mut self, dependency_metrics: DependencyMetrics) -> Self {
+        self.dependency_metrics = dependency_metrics;
+        self
+    }
+
     /// Check if Keycloak is healthy and reachable
     ///
     /// # Errors
@@ -264,6 +321,12 @@ impl KeycloakClient {
     /// # }
     /// ```
     pub async fn introspect_token(&self, token: &str) -> Result<TokenIntrospectionResponse> {
+        self.dependency_metrics
+            .observe("keycloak", "introspect_token", self.introspect(token))
+            .await
+    }
+
+    async fn introspect(&self, token: &str) -> Result<TokenIntrospectionResponse> {
         // Build introspection endpoint URL
         let introspect_url = format!(
             "{}/realms/{}/protocol/openid-connect/token/introspect",
@@ -298,4 +361,115 @@ impl KeycloakClient {
 
         Ok(introspection_response)
     }
+
+    /// Exchange user credentials for tokens using the resource owner password
+    /// grant
+    ///
+    /// The backend service client must have "Direct Access Grants" enabled in
+    /// Keycloak for this grant type to be accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The token request fails
+    /// - Keycloak rejects the credentials
+    /// - The response cannot be parsed
+    pub async fn request_password_token(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<AccessTokenResponse> {
+        self.request_token(
+            "request_password_token",
+            &[("grant_type", "password"), ("username", username), ("password", password)],
+        )
+        .await
+    }
+
+    /// Exchange a refresh token for a new set of tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The token request fails
+    /// - Keycloak rejects the refresh token
+    /// - The response cannot be parsed
+    pub async fn refresh_token(&self, refresh_token: &str) -> Result<AccessTokenResponse> {
+        self.request_token(
+            "refresh_token",
+            &[("grant_type", "refresh_token"), ("refresh_token", refresh_token)],
+        )
+        .await
+    }
+
+    /// Issue an access token of the user `keycloak_user_id` to the backend
+    /// service client, using Keycloak's token exchange to impersonate the user
+    ///
+    /// The backend service client must be granted the `impersonation` role
+    /// and be allowed to exchange tokens in Keycloak.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The token request fails
+    /// - Keycloak does not allow the client to impersonate the user
+    /// - The response cannot be parsed
+    pub async fn impersonate(&self, keycloak_user_id: &Uuid) -> Result<AccessTokenResponse> {
+        let requested_subject = keycloak_user_id.to_string();
+        self.request_token(
+            "impersonate",
+            &[
+                ("grant_type", TOKEN_EXCHANGE_GRANT_TYPE),
+                ("requested_subject", &requested_subject),
+                ("requested_token_type", ACCESS_TOKEN_TYPE),
+            ],
+        )
+        .await
+    }
+
+    /// Call Keycloak's token endpoint with the given grant parameters,
+    /// authenticating as the backend service client, observed as `operation`
+    async fn request_token(
+        &self,
+        operation: &str,
+        grant: &[(&str, &str)],
+    ) -> Result<AccessTokenResponse> {
+        self.dependency_metrics.observe("keycloak", operation, self.send_token_request(grant)).await
+    }
+
+    async fn send_token_request(&self, grant: &[(&str, &str)]) -> Result<AccessTokenResponse> {
+        let token_url =
+            format!("{}/realms/{}/protocol/openid-connect/token", self.server_url, self.realm);
+
+        let mut form_data = vec![
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        form_data.extend_from_slice(grant);
+
+        let response = self
+            .client
+            .post(&token_url)
+            .form(&form_data)
+            .send()
+            .await
+            .context(RequestTokenSnafu)?;
+
+        let status = response.status();
+        let response_text = response.text().await.context(RequestTokenSnafu)?;
+
+        if !status.is_success() {
+            let (error, description) =
+                match serde_json::from_str::<TokenErrorResponse>(&response_text) {
+                    Ok(TokenErrorResponse { error, error_description }) => {
+                        (error, error_description.unwrap_or_default())
+                    }
+                    Err(_) => ("unknown_error".to_string(), response_text),
+                };
+
+            return TokenRejectedSnafu { status: status.as_u16(), error, description }.fail();
+        }
+
+        serde_json::from_str(&response_text).context(ParseTokenResponseSnafu)
+    }
 }
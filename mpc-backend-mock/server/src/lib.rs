@@ -1,24 +1,48 @@
+mod cassette;
+mod chain_state;
+mod circuit_breaker;
+mod dependency_metrics;
 pub mod entity;
 mod error;
+mod event;
 mod grpc;
 pub mod keycloak_client;
+mod migrate;
+mod probe;
+mod reload;
+mod seeder;
 mod service;
+mod shutdown;
+mod store;
+mod task;
+mod user;
 mod web;
+mod worker;
 
-use std::{future::Future, net::SocketAddr, pin::Pin, sync::Arc};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use eris_bitcoin_rpc_client::Client as BitcoinRpcClient;
+use events::EventPublisher;
 use futures::{future::BoxFuture, FutureExt};
 use mpc_backend_mock_core::{
-    config::{BitcoinConfig, Config, KeycloakConfig, PostgresConfig, SolanaConfig},
+    config::{
+        BitcoinConfig, Config, EventPublisherConfig, HealthCheckConfig, KeycloakConfig,
+        MetricsPushGatewayConfig, NotificationConfig, PostgresConfig, RedisConfig, SolanaConfig,
+    },
     ServerInfo,
 };
+use notification::NotificationClient;
 use sigfinn::{ExitStatus, LifecycleManager, Shutdown};
 use snafu::ResultExt;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 use sqlx::{
-    migrate::Migrator,
     postgres::{PgConnectOptions, PgPoolOptions},
     Executor, PgPool,
 };
@@ -27,90 +51,373 @@ use zeus_metrics::DefaultMetrics;
 use zeus_protobuf_types::health_check::HealthServer;
 use zpl_rpc_client::RpcClient as ZplRpcClient;
 
-use self::grpc::HealthCheckService;
 pub use self::{
+    cassette::{CassetteMode, CassetteOptions},
+    chain_state::ChainStateCache,
+    circuit_breaker::{BreakerOpen, CircuitBreaker, CircuitBreakers},
+    dependency_metrics::DependencyMetrics,
     error::{Error, Result},
-    web::{controller, middleware::JwksClient, ApiDoc, ServiceState},
+    event::EventBus,
+    migrate::{migrate, MigrateOptions, MigrationReport},
+    probe::{probe, ProbeReport},
+    reload::{ConfigReloader, ConfigSource},
+    seeder::seed,
+    service::{
+        webhook_signature, BitcoinChain, CapturedNotificationStore, DirectoryUser, EventOutbox,
+        EventOutboxService, Fixtures, KeycloakUserDirectory, MemoryUserDirectory, MockBitcoinChain,
+        MockSolanaChain, NotificationService, QueryMetrics, RecordingBitcoinChain,
+        RecordingSolanaChain, RecordingUserDirectory, ReplayedBitcoinChain, ReplayedSolanaChain,
+        ReplayedUserDirectory, RpcBitcoinChain, RpcSolanaChain, SeedReport, SeedService,
+        SolanaChain, TransactionFixture, UserDirectory, UserFixture, UserManagementService,
+        UserReconciliation, WebhookService,
+    },
+    store::{MemoryStore, RedisStore, Store},
+    task::{TaskRegistry, TaskSupervisor},
+    user::{create_user, delete_user},
+    web::{
+        controller,
+        middleware::{
+            AdminIpFilter, CorsOrigins, FaultInjector, HttpLog, HttpMetrics, IntrospectionCache,
+            JwksClient, RateLimiter, Realm, Realms,
+        },
+        ApiDoc, ServiceState,
+    },
+};
+use self::{
+    grpc::{BearerTokenInterceptor, HealthCheckService, HealthChecker},
+    service::{InstrumentedBitcoinChain, InstrumentedUserDirectory, PgPoolMetrics},
+    shutdown::ShutdownReport,
+    worker::{
+        DeliverWebhooksJob, DispatchNotificationsJob, ExpireActivationTokensJob, ExpireSessionsJob,
+        PublishEventsJob, PublishServerSnapshotJob, ReconcileUsersJob, RefreshChainStateJob,
+        RefreshJwksJob, SnapshotWalletBalancesJob, UnlockExpiredLoginsJob, Worker,
+    },
 };
 use crate::keycloak_client::KeycloakClient;
 
-const MIGRATOR: Migrator = Migrator { ignore_missing: true, ..sqlx::migrate!() };
+/// How long a subsystem may take to initialize on startup, Postgres is given
+/// its migration timeout on top
+const STARTUP_STEP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a read waits for a connection of a read replica before it falls
+/// back to the next replica or the primary
+const READ_REPLICA_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(3);
 
+/// Serve until shutdown, reloading the configuration from `config_source` on
+/// `SIGHUP`, and recording or replaying the upstream responses with the
+/// cassettes of `cassette` when set
+///
 /// # Errors
 /// Returns errors when server fails to start
-pub async fn serve_with_shutdown(config: Config, server_info: ServerInfo) -> Result<()> {
-    let Config { postgres, web, bitcoin, solana, metrics, health_check_listen_address, keycloak } =
-        config;
+pub async fn serve_with_shutdown(
+    config: Config,
+    server_info: ServerInfo,
+    config_source: Arc<dyn ConfigSource>,
+    cassette: Option<CassetteOptions>,
+) -> Result<()> {
+    let running_config = config.clone();
+    let Config {
+        log_filters: _,
+        postgres,
+        web,
+        bitcoin,
+        solana,
+        chain_state,
+        metrics,
+        health_check,
+        keycloak,
+        notification,
+        events,
+        activation,
+        session,
+        login_lockout,
+        signing,
+        key_generation,
+        rate_limit,
+        circuit_breaker,
+        shutdown,
+        redis,
+    } = config;
+
+    let default_metrics = DefaultMetrics::new();
+    let health_check_server = create_grpc_health_check_server(&health_check)?;
 
-    let database = initialize_postgres_pool(&postgres).await?;
+    let lifecycle_manager = LifecycleManager::<Error>::new();
 
-    let bitcoin_rpc_client = initialize_bitcoin_rpc_client(&bitcoin).await?;
+    let background_tasks = TaskRegistry::default();
+    let task_supervisor = TaskSupervisor::new(
+        lifecycle_manager.handle(),
+        background_tasks.clone(),
+        &default_metrics,
+    )?;
 
-    let _solana_rpc_client = initialize_solana_rpc_client(solana.endpoint.url.to_string());
+    // the subsystems do not depend on each other, a slow upstream only delays
+    // startup by its own latency
+    let started_at = Instant::now();
+    let (
+        (database, postgres_elapsed),
+        (bitcoin_chain, bitcoin_elapsed),
+        ((keycloak_client, user_directory), keycloak_elapsed),
+        (store, store_elapsed),
+        (event_publisher, events_elapsed),
+    ) = tokio::try_join!(
+        startup_step(
+            "Postgres",
+            STARTUP_STEP_TIMEOUT + postgres.migration_timeout,
+            initialize_postgres_pool(&postgres),
+        ),
+        startup_step(
+            "Bitcoin",
+            STARTUP_STEP_TIMEOUT,
+            initialize_bitcoin_chain(&bitcoin, cassette.as_ref()),
+        ),
+        startup_step(
+            "Keycloak",
+            STARTUP_STEP_TIMEOUT,
+            initialize_keycloak_clients(&keycloak, cassette.as_ref()),
+        ),
+        startup_step(
+            "store",
+            STARTUP_STEP_TIMEOUT,
+            initialize_store(redis.as_ref(), &task_supervisor),
+        ),
+        startup_step("events", STARTUP_STEP_TIMEOUT, initialize_event_publisher(&events.publisher)),
+    )?;
+
+    // the capture provider records emails in Postgres, so the notification
+    // client is created once the pool is up
+    let (notification_client, notification_elapsed) = startup_step(
+        "notification",
+        STARTUP_STEP_TIMEOUT,
+        initialize_notification_client(notification, &database),
+    )
+    .await?;
+
+    let solana_chain = initialize_solana_chain(&solana, cassette.as_ref())?;
 
     let zpl_rpc_client = initialize_zpl_rpc_client(solana).await;
 
-    let jwks_client = initialize_jwks_client(&keycloak)?;
+    tracing::info!(
+        "Initialized subsystems in {:?} (Postgres: {postgres_elapsed:?}, Bitcoin: \
+         {bitcoin_elapsed:?}, notification: {notification_elapsed:?}, Keycloak: \
+         {keycloak_elapsed:?}, store: {store_elapsed:?}, events: {events_elapsed:?})",
+        started_at.elapsed()
+    );
 
-    // Initialize KeycloakClient (always needed for admin operations)
-    let keycloak_client_instance =
-        KeycloakClient::new(keycloak.clone()).map_err(|err| Error::InitializeKeycloakClient {
-            message: format!("Failed to initialize Keycloak client: {err}"),
-        })?;
+    let dependency_metrics = DependencyMetrics::new(&default_metrics)?;
+    let bitcoin_chain: Arc<dyn BitcoinChain> =
+        Arc::new(InstrumentedBitcoinChain::new(bitcoin_chain, dependency_metrics.clone()));
+    let user_directory =
+        Arc::new(InstrumentedUserDirectory::new(user_directory, dependency_metrics.clone()));
 
-    // Get admin client for user management operations
-    let keycloak_admin =
-        Arc::new(keycloak_client_instance.get_admin_client().await.map_err(|err| {
-            Error::InitializeKeycloakAdmin {
-                message: format!("Failed to get Keycloak admin client: {err}"),
-            }
-        })?);
+    // Shared by token introspection and the login/refresh endpoints
+    let keycloak_client =
+        Arc::new(keycloak_client.with_dependency_metrics(dependency_metrics.clone()));
 
-    // Wrap KeycloakClient in Arc only if introspection mode is used
-    let keycloak_client = match keycloak.jwt_validation_method {
-        mpc_backend_mock_core::config::JwtValidationMethod::Introspection => {
-            Some(Arc::new(keycloak_client_instance))
-        }
-        mpc_backend_mock_core::config::JwtValidationMethod::Jwks => None,
-    };
+    let circuit_breakers = CircuitBreakers::new(&circuit_breaker);
+
+    let realms = initialize_realms(
+        &keycloak,
+        &keycloak_client,
+        &store,
+        &circuit_breakers,
+        &dependency_metrics,
+    )?;
+    let realm_directories =
+        initialize_realm_directories(&realms, cassette.as_ref(), &dependency_metrics).await?;
+
+    let http_metrics = HttpMetrics::new(&default_metrics)?;
+    task_supervisor.spawn("HTTP metrics snapshot", http_metrics.clone().record_snapshots());
+
+    let pool_metrics = PgPoolMetrics::new(database.clone(), &default_metrics)?;
+    task_supervisor.spawn("Postgres pool metrics", pool_metrics.record_samples());
+    let query_metrics = QueryMetrics::new(&default_metrics)?
+        .with_slow_query_threshold(postgres.slow_query_threshold);
+
+    let event_bus = EventBus::new();
+    let chain_state_cache = ChainStateCache::new(chain_state.refresh_interval);
+
+    let rate_limiter =
+        RateLimiter::new(web.trusted_proxies.clone(), rate_limit, Arc::clone(&store));
+    let cors_origins = CorsOrigins::new(&web.cors_allowed_origins);
+    let fault_injector = FaultInjector::new(web.fault_injection.clone());
+    if web.fault_injection.enable {
+        tracing::warn!("Injecting faults into API responses");
+    }
+    if web.debug_http.enable {
+        tracing::warn!("Logging HTTP request and response bodies");
+    }
+    let config_reloader = ConfigReloader::new(
+        config_source,
+        running_config,
+        rate_limiter.clone(),
+        realms.clone(),
+        cors_origins.clone(),
+        fault_injector.clone(),
+    );
+    task_supervisor.spawn("Config reload on SIGHUP", config_reloader.clone().reload_on_hangup());
 
     let service_state = ServiceState::new(
         database.clone(),
-        &bitcoin_rpc_client,
+        Arc::clone(&bitcoin_chain),
+        &bitcoin,
+        Arc::clone(&solana_chain),
         zpl_rpc_client,
-        jwks_client,
-        keycloak_admin,
-        keycloak.realm.clone(),
-        keycloak_client,
+        realms.clone(),
+        user_directory,
+        notification_client,
+        &activation,
+        Arc::clone(&keycloak_client),
         keycloak.jwt_validation_method.clone(),
-    );
+        IntrospectionCache::new(Arc::clone(&store), keycloak.introspection_cache_ttl),
+        AdminIpFilter::new(web.trusted_proxies, web.admin_access),
+        background_tasks,
+        http_metrics,
+        event_bus.clone(),
+        query_metrics,
+        rate_limiter,
+        circuit_breakers,
+    )
+    .with_cors_origins(cors_origins)
+    .with_fault_injector(fault_injector)
+    .with_http_log(HttpLog::new(web.debug_http.clone()))
+    .with_graphql(web.graphql)
+    .with_transaction_retry(postgres.transaction_retry)
+    .with_read_replicas(connect_read_replicas(&postgres))
+    .with_event_outbox(EventOutbox::new(event_publisher.is_some()))
+    .with_store(Arc::clone(&store))
+    .with_chain_state(chain_state_cache.clone())
+    .with_session_config(session.clone())
+    .with_login_lockout_config(login_lockout)
+    .with_signing_config(signing)
+    .with_key_generation_config(key_generation)
+    .with_config_reloader(config_reloader)
+    .with_metrics(default_metrics.handle());
+    let service_state =
+        realm_directories.into_iter().fold(service_state, |state, (realm, directory)| {
+            state.with_realm_directory(&realm, directory)
+        });
 
-    let lifecycle_manager = LifecycleManager::<Error>::new();
+    let worker = Worker::new(&default_metrics)?
+        .with_job(RefreshJwksJob::new(realms, keycloak.jwks_refresh_interval))
+        .with_job(RefreshChainStateJob::new(
+            Arc::clone(&bitcoin_chain),
+            Arc::clone(&solana_chain),
+            chain_state_cache.clone(),
+            chain_state.refresh_interval,
+            event_bus.clone(),
+            &default_metrics,
+        )?)
+        .with_job(PublishServerSnapshotJob::new(
+            chain_state_cache.clone(),
+            bitcoin.block_number_to_confirm,
+            server_info.start_time,
+            event_bus,
+        ))
+        .with_job(ExpireActivationTokensJob::new(service_state.user_management_service.clone()))
+        .with_job(DispatchNotificationsJob::new(
+            service_state.notification_service.clone(),
+            &default_metrics,
+        )?)
+        .with_job(DeliverWebhooksJob::new(service_state.webhook_service.clone(), &default_metrics)?)
+        .with_job(ReconcileUsersJob::new(
+            service_state.user_management_service.clone(),
+            &default_metrics,
+        )?)
+        .with_job(SnapshotWalletBalancesJob::new(service_state.wallet_service.clone()));
+    let worker = if session.enable {
+        worker.with_job(ExpireSessionsJob::new(service_state.session_service.clone()))
+    } else {
+        worker
+    };
+    let worker = if login_lockout.enable {
+        worker.with_job(UnlockExpiredLoginsJob::new(service_state.login_lockout_service.clone()))
+    } else {
+        worker
+    };
+    let worker = if let Some(event_publisher) = event_publisher {
+        worker.with_job(PublishEventsJob::new(
+            EventOutboxService::new(
+                database.clone(),
+                event_publisher,
+                events.publisher.name(),
+                events.batch_size,
+            ),
+            &default_metrics,
+        )?)
+    } else {
+        worker
+    };
+
+    let shutdown_report = ShutdownReport::default();
+    let notification_service = service_state.notification_service.clone();
 
     let _handle = lifecycle_manager
+        .spawn("Shutdown signal", shutdown_report.watch_signal())
         .spawn(
             "Health check server",
-            create_grpc_health_check_server_future(
-                health_check_listen_address,
-                bitcoin_rpc_client,
-                database.clone(),
+            shutdown_report.track(
+                "gRPC health check server",
+                create_grpc_health_check_server_future(
+                    health_check_server,
+                    health_check.listen_address,
+                    BearerTokenInterceptor::new(health_check.bearer_token),
+                    HealthCheckService::new(
+                        health_check.components,
+                        HealthChecker {
+                            bitcoin_chain,
+                            solana_chain,
+                            chain_state: chain_state_cache,
+                            keycloak_client,
+                            database: database.clone(),
+                        },
+                        task_supervisor,
+                    ),
+                ),
             ),
         )
         .spawn(
             "Http Server",
-            create_web_http_server_future(web.listen_address, service_state, server_info),
-        );
+            shutdown_report.track(
+                "HTTP server",
+                create_web_http_server_future(
+                    web.listen_address,
+                    service_state,
+                    server_info,
+                    web.docs_ui,
+                    shutdown.grace_period,
+                ),
+            ),
+        )
+        .spawn("Worker", shutdown_report.track("Worker", create_worker_future(worker)));
 
+    if let Some(push_gateway) = metrics.push_gateway {
+        let _handle = lifecycle_manager.spawn(
+            "Metrics push gateway",
+            shutdown_report.track(
+                "Metrics push gateway",
+                create_metrics_push_gateway_future(push_gateway, default_metrics.clone()),
+            ),
+        );
+    }
     if metrics.enable {
-        let default_metrics = DefaultMetrics::new()?;
-
         let _handle = lifecycle_manager.spawn(
             "Metrics server",
-            create_metrics_server_future(metrics.listen_address, default_metrics),
+            shutdown_report.track(
+                "Metrics server",
+                create_metrics_server_future(metrics.listen_address, default_metrics),
+            ),
         );
     }
 
-    if let Ok(Err(err)) = lifecycle_manager.serve().await {
+    let result = lifecycle_manager.serve().await;
+
+    // the worker dispatching the outbox is down, nothing else claims it now
+    flush_notification_outbox(&shutdown_report, &notification_service, shutdown.grace_period).await;
+    shutdown_report.step("Postgres pool", database.close()).await;
+    shutdown_report.log();
+
+    if let Ok(Err(err)) = result {
         tracing::error!("{err}");
         Err(err)
     } else {
@@ -118,15 +425,102 @@ pub async fn serve_with_shutdown(config: Config, server_info: ServerInfo) -> Res
     }
 }
 
+/// Send the notifications still due before the process exits, for at most
+/// `grace_period`
+async fn flush_notification_outbox(
+    shutdown_report: &ShutdownReport,
+    notification_service: &NotificationService,
+    grace_period: Duration,
+) {
+    let flush = tokio::time::timeout(grace_period, notification_service.flush());
+    match shutdown_report.step("Notification outbox", flush).await {
+        Ok(Ok(dispatch)) if dispatch.handled() > 0 => tracing::info!(
+            "Flushed the notification outbox, sent: {}, skipped: {}, retried later: {}, \
+             dead-lettered: {}",
+            dispatch.sent,
+            dispatch.skipped,
+            dispatch.retried,
+            dispatch.dead_lettered
+        ),
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => tracing::warn!("Failed to flush the notification outbox, error: {err}"),
+        Err(_) => tracing::warn!(
+            "Notification outbox is not flushed within {grace_period:?}, the rest stays queued"
+        ),
+    }
+}
+
+/// Run a startup step within `timeout`, returning its output and how long it
+/// took
+async fn startup_step<T>(
+    step: &'static str,
+    timeout: Duration,
+    future: impl Future<Output = Result<T>>,
+) -> Result<(T, Duration)> {
+    let started_at = Instant::now();
+    let output = tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| Error::StartupStepTimeout { step, timeout })??;
+    let elapsed = started_at.elapsed();
+    tracing::debug!("Initialized {step} in {elapsed:?}");
+
+    Ok((output, elapsed))
+}
+
+async fn initialize_postgres_pool(config: &PostgresConfig) -> Result<PgPool> {
+    let pool = connect_postgres_pool(config).await?;
+
+    migrate::run_migrations(&pool, config.migration_timeout)
+        .instrument(tracing::info_span!("migrate"))
+        .await?;
+
+    Ok(pool)
+}
+
 #[tracing::instrument(
-    skip(password, database, ssl_mode, max_connections),
+    skip(config),
     fields(
-        host = %host,
-        port = port,
-        username = %username
+        host = %config.host,
+        port = config.port,
+        username = %config.username
     )
 )]
-async fn initialize_postgres_pool(
+async fn connect_postgres_pool(config: &PostgresConfig) -> Result<PgPool> {
+    tracing::info!("Initializing database");
+
+    let PostgresConfig { host, port, username, database, .. } = config;
+    let (connect_opts, pool_opts) = postgres_options(config);
+    let pool =
+        pool_opts.connect_with(connect_opts).await.context(error::InitializePostgresPoolSnafu {
+            host: host.clone(),
+            port: *port,
+            username: username.clone(),
+            database: database.clone(),
+        })?;
+
+    Ok(pool)
+}
+
+/// Pools of the read replicas, in read-only sessions
+///
+/// The pools connect lazily, a replica which is down does not hold up startup
+/// and the reads fall back to the primary until it is back.
+fn connect_read_replicas(config: &PostgresConfig) -> Vec<PgPool> {
+    config
+        .read_replicas
+        .iter()
+        .map(|host| {
+            tracing::info!("Initializing read replica {host}");
+            let (connect_opts, pool_opts) = postgres_options(config);
+            pool_opts.acquire_timeout(READ_REPLICA_ACQUIRE_TIMEOUT).connect_lazy_with(
+                connect_opts.host(host).options([("default_transaction_read_only", "on")]),
+            )
+        })
+        .collect()
+}
+
+/// Options to connect to the primary and of its pool
+fn postgres_options(
     PostgresConfig {
         host,
         port,
@@ -137,10 +531,13 @@ async fn initialize_postgres_pool(
         ssl_mode,
         max_connections,
         application_name,
+        statement_timeout,
+        migration_timeout: _,
+        slow_query_threshold: _,
+        transaction_retry: _,
+        read_replicas: _,
     }: &PostgresConfig,
-) -> Result<PgPool> {
-    tracing::info!("Initializing database");
-
+) -> (PgConnectOptions, PgPoolOptions) {
     let connect_opts = PgConnectOptions::new_without_pgpass()
         .host(host)
         .port(*port)
@@ -156,38 +553,61 @@ async fn initialize_postgres_pool(
         connect_opts
     };
 
+    // session settings applied to every new connection
+    let mut session = Vec::new();
+    if let Some(role) = role {
+        session.push(format!(r#"SET SESSION ROLE = "{role}";"#));
+    }
+    if let Some(timeout) = statement_timeout {
+        session.push(format!("SET statement_timeout = {};", timeout.as_millis()));
+    }
+
     let pool_opts = {
         let opts = PgPoolOptions::new().max_connections(*max_connections);
 
-        if let Some(role) = role {
-            let set_role = format!(r#"SET SESSION ROLE = "{role}";"#);
+        if session.is_empty() {
+            opts
+        } else {
+            let session = session.concat();
             opts.after_connect(move |conn, _meta| {
-                let set_role = set_role.clone();
+                let session = session.clone();
                 async move {
-                    let _ = conn.execute(set_role.as_str()).await?;
+                    let _ = conn.execute(session.as_str()).await?;
                     Ok(())
                 }
                 .boxed()
             })
-        } else {
-            opts
         }
     };
-    let pool =
-        pool_opts.connect_with(connect_opts).await.context(error::InitializePostgresPoolSnafu {
-            host: host.clone(),
-            port: *port,
-            username: username.clone(),
-            database: database.clone(),
-        })?;
 
-    MIGRATOR
-        .run(&pool)
-        .instrument(tracing::info_span!("migrate"))
-        .await
-        .context(error::MigrateSchemaSnafu)?;
+    (connect_opts, pool_opts)
+}
 
-    Ok(pool)
+/// Bitcoin chain of the configuration, the mock chain never touches the
+/// network
+async fn initialize_bitcoin_chain(
+    config: &BitcoinConfig,
+    cassette: Option<&CassetteOptions>,
+) -> Result<Arc<dyn BitcoinChain>> {
+    if let Some(cassette) = cassette.filter(|cassette| cassette.mode == CassetteMode::Replay) {
+        tracing::warn!("Replaying the Bitcoin chain from {}", cassette.dir.display());
+        return Ok(Arc::new(ReplayedBitcoinChain::new(cassette.open("bitcoin")?)));
+    }
+    if config.mock {
+        tracing::warn!("Using the mock Bitcoin chain, balances are canned");
+        return Ok(Arc::new(MockBitcoinChain));
+    }
+
+    let rpc_client = initialize_bitcoin_rpc_client(config).await?;
+    let chain: Arc<dyn BitcoinChain> =
+        Arc::new(RpcBitcoinChain::new(rpc_client, config.endpoint.indexer_endpoint.clone()));
+    match cassette {
+        Some(cassette) => {
+            tracing::info!("Recording the Bitcoin chain to {}", cassette.dir.display());
+            Ok(Arc::new(RecordingBitcoinChain::new(chain, cassette.open("bitcoin")?)))
+        }
+        None => Ok(chain),
+    }
 }
 
 #[tracing::instrument(
@@ -199,7 +619,7 @@ async fn initialize_postgres_pool(
     )
 )]
 async fn initialize_bitcoin_rpc_client(
-    BitcoinConfig { endpoint, block_number_to_confirm }: &BitcoinConfig,
+    BitcoinConfig { endpoint, block_number_to_confirm, .. }: &BitcoinConfig,
 ) -> Result<BitcoinRpcClient> {
     tracing::info!("Initializing Bitcoin RPC client");
 
@@ -224,6 +644,33 @@ async fn initialize_bitcoin_rpc_client(
     Ok(bitcoin_rpc_client)
 }
 
+/// Solana chain of the configuration, the mock and the replayed chain never
+/// touch the network
+fn initialize_solana_chain(
+    config: &SolanaConfig,
+    cassette: Option<&CassetteOptions>,
+) -> Result<Arc<dyn SolanaChain>> {
+    if let Some(cassette) = cassette.filter(|cassette| cassette.mode == CassetteMode::Replay) {
+        tracing::warn!("Replaying the Solana chain from {}", cassette.dir.display());
+        return Ok(Arc::new(ReplayedSolanaChain::new(cassette.open("solana")?)));
+    }
+    if config.mock {
+        tracing::warn!("Using the mock Solana chain, accounts are canned");
+        return Ok(Arc::new(MockSolanaChain::default()));
+    }
+
+    let chain: Arc<dyn SolanaChain> = Arc::new(RpcSolanaChain::new(initialize_solana_rpc_client(
+        config.endpoint.url.to_string(),
+    )));
+    match cassette {
+        Some(cassette) => {
+            tracing::info!("Recording the Solana chain to {}", cassette.dir.display());
+            Ok(Arc::new(RecordingSolanaChain::new(chain, cassette.open("solana")?)))
+        }
+        None => Ok(chain),
+    }
+}
+
 #[tracing::instrument]
 fn initialize_solana_rpc_client(url: String) -> Arc<RpcClient> {
     tracing::info!("Initializing Solana RPC client");
@@ -239,7 +686,7 @@ fn initialize_solana_rpc_client(url: String) -> Arc<RpcClient> {
         cluster = %endpoint.cluster
     )
 )]
-async fn initialize_zpl_rpc_client(SolanaConfig { endpoint }: SolanaConfig) -> ZplRpcClient {
+async fn initialize_zpl_rpc_client(SolanaConfig { endpoint, .. }: SolanaConfig) -> ZplRpcClient {
     tracing::info!("Initializing ZPL RPC client");
 
     ZplRpcClient::new(
@@ -254,13 +701,13 @@ async fn initialize_zpl_rpc_client(SolanaConfig { endpoint }: SolanaConfig) -> Z
 }
 
 #[tracing::instrument(
-    skip(keycloak),
+    skip(keycloak, store),
     fields(
         server_url = %keycloak.server_url,
         realm = %keycloak.realm
     )
 )]
-fn initialize_jwks_client(keycloak: &KeycloakConfig) -> Result<JwksClient> {
+fn initialize_jwks_client(keycloak: &KeycloakConfig, store: Arc<dyn Store>) -> Result<JwksClient> {
     tracing::info!("Initializing JWKS client");
 
     tracing::info!(
@@ -269,23 +716,259 @@ fn initialize_jwks_client(keycloak: &KeycloakConfig) -> Result<JwksClient> {
         keycloak.realm
     );
 
-    JwksClient::new(&keycloak.server_url, &keycloak.realm).map_err(|err| {
-        Error::InitializeJwksClient { message: format!("Failed to create JWKS client: {err}") }
-    })
+    JwksClient::new(&keycloak.server_url, &keycloak.realm, store, keycloak.jwks_cache_ttl).map_err(
+        |err| Error::InitializeJwksClient {
+            message: format!("Failed to create JWKS client: {err}"),
+        },
+    )
+}
+
+/// Realms whose tokens are accepted, the primary one introspects tokens with
+/// `keycloak_client`
+#[tracing::instrument(skip_all)]
+fn initialize_realms(
+    keycloak: &KeycloakConfig,
+    keycloak_client: &Arc<KeycloakClient>,
+    store: &Arc<dyn Store>,
+    circuit_breakers: &CircuitBreakers,
+    dependency_metrics: &DependencyMetrics,
+) -> Result<Realms> {
+    let jwks_client = initialize_jwks_client(keycloak, Arc::clone(store))?
+        .with_circuit_breaker(circuit_breakers.jwks.clone())
+        .with_dependency_metrics(dependency_metrics.clone());
+    let mut realms = Realms::new(Realm::new(
+        keycloak.realm.clone(),
+        keycloak.issuer.clone(),
+        jwks_client,
+        Arc::clone(keycloak_client),
+    ));
+
+    for realm in &keycloak.additional_realms {
+        let realm_keycloak = keycloak.for_realm(realm);
+        let jwks_client = initialize_jwks_client(&realm_keycloak, Arc::clone(store))?
+            .with_circuit_breaker(circuit_breakers.jwks.clone())
+            .with_dependency_metrics(dependency_metrics.clone());
+        let keycloak_client = KeycloakClient::new(realm_keycloak)
+            .map_err(|err| Error::InitializeKeycloakClient {
+                message: format!(
+                    "Failed to initialize Keycloak client of realm `{}`: {err}",
+                    realm.realm
+                ),
+            })?
+            .with_dependency_metrics(dependency_metrics.clone());
+        tracing::info!(
+            "Accepting the tokens of realm `{}` issued by {}",
+            realm.realm,
+            realm.issuer
+        );
+
+        realms = realms.with_realm(Realm::new(
+            realm.realm.clone(),
+            realm.issuer.clone(),
+            jwks_client,
+            Arc::new(keycloak_client),
+        ));
+    }
+
+    Ok(realms)
+}
+
+/// User directories of the realms other than the primary one, by realm
+///
+/// They are neither recorded nor replayed, none is created while replaying so
+/// that no admin token is fetched, and users of those realms cannot be created
+/// or changed then.
+#[tracing::instrument(skip_all)]
+async fn initialize_realm_directories(
+    realms: &Realms,
+    cassette: Option<&CassetteOptions>,
+    dependency_metrics: &DependencyMetrics,
+) -> Result<Vec<(String, Arc<dyn UserDirectory>)>> {
+    let mut directories = Vec::new();
+    for realm in realms.iter().filter(|realm| realm.user_realm().is_some()) {
+        if cassette.is_some_and(|cassette| cassette.mode == CassetteMode::Replay) {
+            tracing::warn!(
+                "Users of realm `{}` cannot be managed while replaying, its user directory is not \
+                 replayed",
+                realm.name()
+            );
+            continue;
+        }
+
+        let admin = realm.keycloak_client().get_admin_client().await.map_err(|err| {
+            Error::InitializeKeycloakAdmin {
+                message: format!(
+                    "Failed to get Keycloak admin client of realm `{}`: {err}",
+                    realm.name()
+                ),
+            }
+        })?;
+        let directory: Arc<dyn UserDirectory> = Arc::new(InstrumentedUserDirectory::new(
+            Arc::new(KeycloakUserDirectory::new(Arc::new(admin), realm.name().to_string())),
+            dependency_metrics.clone(),
+        ));
+        directories.push((realm.name().to_string(), directory));
+    }
+
+    Ok(directories)
+}
+
+#[tracing::instrument(
+    skip(keycloak),
+    fields(
+        server_url = %keycloak.server_url,
+        realm = %keycloak.realm
+    )
+)]
+/// Keycloak client and the user directory, the replayed directory does not
+/// fetch an admin token
+///
+/// The client is never replayed, logins and token validation go to Keycloak
+/// while replaying too.
+async fn initialize_keycloak_clients(
+    keycloak: &KeycloakConfig,
+    cassette: Option<&CassetteOptions>,
+) -> Result<(KeycloakClient, Arc<dyn UserDirectory>)> {
+    tracing::info!("Initializing Keycloak clients");
+
+    // Always needed, logins and token validation are not replayed
+    let client =
+        KeycloakClient::new(keycloak.clone()).map_err(|err| Error::InitializeKeycloakClient {
+            message: format!("Failed to initialize Keycloak client: {err}"),
+        })?;
+
+    if let Some(cassette) = cassette.filter(|cassette| cassette.mode == CassetteMode::Replay) {
+        tracing::warn!("Replaying the Keycloak user directory from {}", cassette.dir.display());
+        return Ok((client, Arc::new(ReplayedUserDirectory::new(cassette.open("keycloak")?))));
+    }
+
+    // Admin client for user management operations
+    let admin = client.get_admin_client().await.map_err(|err| Error::InitializeKeycloakAdmin {
+        message: format!("Failed to get Keycloak admin client: {err}"),
+    })?;
+    let user_directory: Arc<dyn UserDirectory> =
+        Arc::new(KeycloakUserDirectory::new(Arc::new(admin), keycloak.realm.clone()));
+
+    match cassette {
+        Some(cassette) => {
+            tracing::info!("Recording the Keycloak user directory to {}", cassette.dir.display());
+            let cassette = cassette.open("keycloak")?;
+            Ok((client, Arc::new(RecordingUserDirectory::new(user_directory, cassette))))
+        }
+        None => Ok((client, user_directory)),
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn initialize_store(
+    redis: Option<&RedisConfig>,
+    task_supervisor: &TaskSupervisor,
+) -> Result<Arc<dyn Store>> {
+    if let Some(redis) = redis {
+        tracing::info!("Connecting to Redis, caches and rate limits are shared between replicas");
+        let store = RedisStore::connect(redis).await.context(error::InitializeStoreSnafu)?;
+        Ok(Arc::new(store))
+    } else {
+        tracing::info!("Redis is not configured, caches and rate limits are kept in process");
+        let store = MemoryStore::default();
+        task_supervisor.spawn("In-memory store pruning", store.clone().prune_expired());
+        Ok(Arc::new(store))
+    }
+}
+
+#[tracing::instrument(skip(config, database))]
+async fn initialize_notification_client(
+    config: NotificationConfig,
+    database: &PgPool,
+) -> Result<Arc<dyn NotificationClient>> {
+    tracing::info!("Initializing notification client");
+
+    match config {
+        NotificationConfig::Gmail(gmail) => {
+            let client = notification::gmail::Client::new(gmail)
+                .await
+                .context(error::InitializeNotificationClientSnafu)?;
+            Ok(Arc::new(client))
+        }
+        NotificationConfig::Console(console) => {
+            tracing::info!(
+                "Emails are printed to the console, bodies are written to {}",
+                console.output_directory.display()
+            );
+            let client = notification::console::Client::new(console)
+                .context(error::InitializeNotificationClientSnafu)?;
+            Ok(Arc::new(client))
+        }
+        NotificationConfig::Log => {
+            tracing::warn!("Notifications are only logged");
+            Ok(Arc::new(notification::log::Client::new()))
+        }
+        NotificationConfig::Noop => {
+            tracing::warn!("Notifications are dropped");
+            Ok(Arc::new(notification::noop::Client::new()))
+        }
+        NotificationConfig::Capture(capture) => {
+            tracing::warn!("Notifications are not sent, they are captured in Postgres");
+            let store = Arc::new(CapturedNotificationStore::new(database.clone()));
+            let client = notification::capture::Client::new(capture, store)
+                .context(error::InitializeNotificationClientSnafu)?;
+            Ok(Arc::new(client))
+        }
+    }
+}
+
+/// Publisher of the domain events, `None` if publishing is disabled
+#[tracing::instrument(skip(config))]
+async fn initialize_event_publisher(
+    config: &EventPublisherConfig,
+) -> Result<Option<Arc<dyn EventPublisher>>> {
+    tracing::info!("Initializing event publisher");
+
+    match config {
+        EventPublisherConfig::Disabled => {
+            tracing::info!("Event publishing is disabled, domain events are not recorded");
+            Ok(None)
+        }
+        EventPublisherConfig::Log => {
+            tracing::warn!("Domain events are only logged");
+            Ok(Some(Arc::new(events::log::Publisher::new())))
+        }
+        EventPublisherConfig::Nats(nats) => {
+            tracing::info!("Publishing domain events to NATS subject `{}.*`", nats.subject);
+            let publisher = events::nats::Publisher::connect(nats)
+                .await
+                .context(error::InitializeEventPublisherSnafu)?;
+            Ok(Some(Arc::new(publisher)))
+        }
+        EventPublisherConfig::Kafka(kafka) => {
+            tracing::info!("Publishing domain events to Kafka topic `{}`", kafka.topic);
+            let publisher = events::kafka::Publisher::new(kafka)
+                .context(error::InitializeEventPublisherSnafu)?;
+            Ok(Some(Arc::new(publisher)))
+        }
+    }
 }
 
 fn create_web_http_server_future(
     listen_address: SocketAddr,
     service_state: ServiceState,
     server_info: ServerInfo,
+    docs_ui: bool,
+    grace_period: Duration,
 ) -> impl FnOnce(Shutdown) -> BoxFuture<'static, ExitStatus<Error>> {
     move |shutdown_signal| {
         async move {
             tracing::info!("Listen Web HTTP server endpoint on {listen_address}");
 
-            let result =
-                web::new_api_server(listen_address, service_state, server_info, shutdown_signal)
-                    .await;
+            let result = web::new_api_server(
+                listen_address,
+                service_state,
+                server_info,
+                docs_ui,
+                shutdown_signal,
+                grace_period,
+            )
+            .await;
 
             match result {
                 Ok(()) => {
@@ -299,6 +982,19 @@ fn create_web_http_server_future(
     }
 }
 
+fn create_worker_future(
+    worker: Worker,
+) -> impl FnOnce(Shutdown) -> BoxFuture<'static, ExitStatus<Error>> {
+    move |shutdown_signal| {
+        async move {
+            worker.run(shutdown_signal).await;
+            tracing::info!("Worker is shut down gracefully");
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
 fn create_metrics_server_future<Metrics>(
     listen_address: SocketAddr,
     metrics: Metrics,
@@ -322,20 +1018,61 @@ where
     }
 }
 
+fn create_metrics_push_gateway_future<Metrics>(
+    MetricsPushGatewayConfig { endpoint, job, interval }: MetricsPushGatewayConfig,
+    metrics: Metrics,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>>
+where
+    Metrics: zeus_metrics::Metrics + 'static,
+{
+    move |signal| {
+        async move {
+            tracing::info!("Push metrics to {endpoint} every {interval:?}");
+            let config = zeus_metrics::PushGatewayConfig { endpoint, job, interval };
+            match zeus_metrics::start_push_gateway(config, metrics, signal).await {
+                Ok(()) => {
+                    tracing::info!("Metrics push gateway is shut down gracefully");
+                    ExitStatus::Success
+                }
+                Err(err) => ExitStatus::FatalError(Error::from(err)),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Server builder of the gRPC health check, verifying client certificates when
+/// a client CA is configured
+fn create_grpc_health_check_server(
+    HealthCheckConfig { tls, .. }: &HealthCheckConfig,
+) -> Result<tonic::transport::Server> {
+    let server = tonic::transport::Server::builder();
+    let Some(tls) = tls else {
+        return Ok(server);
+    };
+
+    let mut tls_config = tonic::transport::ServerTlsConfig::new()
+        .identity(tonic::transport::Identity::from_pem(&tls.certificate, &tls.private_key));
+    if let Some(client_ca_certificate) = &tls.client_ca_certificate {
+        tls_config = tls_config
+            .client_ca_root(tonic::transport::Certificate::from_pem(client_ca_certificate));
+    }
+
+    server.tls_config(tls_config).context(error::ConfigureHealthCheckTlsSnafu)
+}
+
 fn create_grpc_health_check_server_future(
+    mut server: tonic::transport::Server,
     listen_address: SocketAddr,
-    bitcoin_rpc_client: BitcoinRpcClient,
-    database: PgPool,
+    interceptor: BearerTokenInterceptor,
+    health_check_service: HealthCheckService,
 ) -> impl FnOnce(Shutdown) -> BoxFuture<'static, ExitStatus<Error>> {
     move |signal| {
         async move {
             tracing::info!("Listen gRPC health check endpoint on {listen_address}");
 
-            let result = tonic::transport::Server::builder()
-                .add_service(HealthServer::new(HealthCheckService::new(
-                    bitcoin_rpc_client,
-                    database,
-                )))
+            let result = server
+                .add_service(HealthServer::with_interceptor(health_check_service, interceptor))
                 .serve_with_shutdown(listen_address, signal)
                 .await
                 .context(error::StartTonicServerSnafu);
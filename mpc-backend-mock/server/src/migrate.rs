@@ -0,0 +1,201 @@
+//! Postgres schema migrations.
+//!
+//! The server applies every pending migration on startup. [`migrate`] applies
+//! them without starting the server, so that they can run as a separate
+//! deployment step, and can stop at a given version or only report what would
+//! be applied.
+
+use std::{collections::HashMap, time::Duration};
+
+use futures::{future::BoxFuture, FutureExt};
+use mpc_backend_mock_core::config::PostgresConfig;
+use snafu::ResultExt;
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migration, Migrator},
+    PgConnection, PgPool,
+};
+
+use crate::error::{self, Error, Result};
+
+/// Migrations of this build, run while holding [`MIGRATION_LOCK_KEY`] rather
+/// than the advisory lock of sqlx, which is waited for without a timeout
+pub(crate) static MIGRATOR: Migrator =
+    Migrator { ignore_missing: true, locking: false, ..sqlx::migrate!() };
+
+/// Key of the session advisory lock held while migrating, so that replicas
+/// starting at the same time run the migrations one after another
+const MIGRATION_LOCK_KEY: i64 = 0x6d70_635f_6d69_6772;
+
+/// Options of [`migrate`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MigrateOptions {
+    /// Only report the pending migrations
+    pub dry_run: bool,
+
+    /// Last version to apply, every pending migration when unset
+    pub target_version: Option<i64>,
+}
+
+/// Migration which is pending or was applied by [`migrate`]
+#[derive(Clone, Debug)]
+pub struct MigrationReport {
+    pub version: i64,
+
+    pub description: String,
+
+    /// How long the migration took, `None` on a dry run
+    pub elapsed: Option<Duration>,
+}
+
+/// Apply the pending migrations up to `options.target_version` without
+/// starting the server
+///
+/// Migrations are never reverted, a target older than the applied migrations
+/// applies nothing.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Postgres cannot be reached
+/// - A migration was applied with a different checksum or failed before
+/// - A migration fails, or the migration lock is not acquired in time
+pub async fn migrate(
+    config: &PostgresConfig,
+    options: MigrateOptions,
+) -> Result<Vec<MigrationReport>> {
+    let pool = crate::connect_postgres_pool(config).await?;
+
+    if options.dry_run {
+        let mut conn = pool.acquire().await.context(error::AcquireMigrationConnectionSnafu)?;
+        let pending = pending_migrations(&mut *conn, options.target_version).await?;
+
+        return Ok(pending
+            .into_iter()
+            .map(|migration| MigrationReport {
+                version: migration.version,
+                description: migration.description.to_string(),
+                elapsed: None,
+            })
+            .collect());
+    }
+
+    with_migration_lock(&pool, config.migration_timeout, |conn| {
+        async move {
+            conn.ensure_migrations_table().await.context(error::MigrateSchemaSnafu)?;
+
+            let mut applied = Vec::new();
+            for migration in pending_migrations(conn, options.target_version).await? {
+                tracing::info!("Applying migration {}", migration.version);
+                let elapsed = conn.apply(migration).await.context(error::MigrateSchemaSnafu)?;
+
+                applied.push(MigrationReport {
+                    version: migration.version,
+                    description: migration.description.to_string(),
+                    elapsed: Some(elapsed),
+                });
+            }
+            Ok(applied)
+        }
+        .boxed()
+    })
+    .await
+}
+
+/// Run the pending migrations while holding the migration advisory lock
+///
+/// Replicas starting at the same time wait for each other, the wait and the
+/// migrations together must finish within `timeout`.
+pub(crate) async fn run_migrations(pool: &PgPool, timeout: Duration) -> Result<()> {
+    with_migration_lock(pool, timeout, |conn| {
+        MIGRATOR.run(conn).map(|result| result.context(error::MigrateSchemaSnafu)).boxed()
+    })
+    .await
+}
+
+/// Run `f` while holding the migration advisory lock, waiting for the lock and
+/// `f` together at most `timeout`
+async fn with_migration_lock<T>(
+    pool: &PgPool,
+    timeout: Duration,
+    f: impl for<'c> FnOnce(&'c mut PgConnection) -> BoxFuture<'c, Result<T>>,
+) -> Result<T> {
+    let mut conn = pool.acquire().await.context(error::AcquireMigrationConnectionSnafu)?;
+
+    let result = tokio::time::timeout(timeout, async {
+        let _lock = sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await
+            .context(error::AcquireMigrationLockSnafu)?;
+        tracing::info!("Acquired migration lock");
+
+        let migrated = f(&mut *conn).await;
+
+        let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await
+            .context(error::ReleaseMigrationLockSnafu);
+
+        // a migration error is more telling than an unlock error
+        migrated.and_then(|migrated| unlocked.map(|_| migrated))
+    })
+    .await;
+
+    if let Ok(result) = result {
+        result
+    } else {
+        // the connection may still wait for the lock or hold it, closing it ends
+        // the session and releases the lock
+        drop(conn.detach());
+        Err(Error::MigrationTimeout { timeout })
+    }
+}
+
+/// Migrations of this build which are not applied yet, oldest first
+///
+/// Fails like [`Migrator::run`] when a migration failed before or an applied
+/// migration was changed since.
+async fn pending_migrations(
+    conn: &mut PgConnection,
+    target_version: Option<i64>,
+) -> Result<Vec<&'static Migration>> {
+    let has_migrations_table =
+        sqlx::query_scalar::<_, bool>("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut *conn)
+            .await
+            .context(error::CheckMigrationTableSnafu)?;
+
+    let applied = if has_migrations_table {
+        if let Some(version) = conn.dirty_version().await.context(error::MigrateSchemaSnafu)? {
+            return Err(Error::MigrateSchema { source: MigrateError::Dirty(version) });
+        }
+
+        conn.list_applied_migrations()
+            .await
+            .context(error::MigrateSchemaSnafu)?
+            .into_iter()
+            .map(|migration| (migration.version, migration.checksum))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut pending = Vec::new();
+    for migration in
+        MIGRATOR.iter().filter(|migration| !migration.migration_type.is_down_migration())
+    {
+        match applied.get(&migration.version) {
+            Some(checksum) if *checksum != migration.checksum => {
+                return Err(Error::MigrateSchema {
+                    source: MigrateError::VersionMismatch(migration.version),
+                });
+            }
+            Some(_) => {}
+            None if target_version.is_some_and(|target| migration.version > target) => {}
+            None => pending.push(migration),
+        }
+    }
+
+    Ok(pending)
+}
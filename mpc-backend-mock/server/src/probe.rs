@@ -0,0 +1,78 @@
+//! Connectivity probes of the upstreams the server depends on.
+//!
+//! [`probe`] connects to every upstream the way the server would on startup,
+//! without running migrations or starting anything, so that a configuration
+//! can be checked before it is deployed.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use mpc_backend_mock_core::config::{Config, KeycloakConfig};
+
+/// How long a single probe may take
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of probing one upstream
+#[derive(Clone, Debug)]
+pub struct ProbeReport {
+    pub name: &'static str,
+
+    pub elapsed: Duration,
+
+    /// Why the upstream could not be reached, `None` when it could
+    pub error: Option<String>,
+}
+
+/// Probe Postgres, Keycloak and Bitcoin concurrently
+pub async fn probe(config: &Config) -> Vec<ProbeReport> {
+    let (postgres, keycloak, bitcoin) = tokio::join!(
+        run_probe("Postgres", async {
+            crate::connect_postgres_pool(&config.postgres)
+                .await
+                .map(drop)
+                .map_err(|err| err.to_string())
+        }),
+        run_probe("Keycloak", probe_keycloak(&config.keycloak)),
+        run_probe("Bitcoin", async {
+            crate::initialize_bitcoin_chain(&config.bitcoin, None)
+                .await
+                .map(drop)
+                .map_err(|err| err.to_string())
+        }),
+    );
+
+    vec![postgres, keycloak, bitcoin]
+}
+
+async fn run_probe(
+    name: &'static str,
+    future: impl Future<Output = Result<(), String>>,
+) -> ProbeReport {
+    let started_at = Instant::now();
+    let result = tokio::time::timeout(PROBE_TIMEOUT, future)
+        .await
+        .unwrap_or_else(|_| Err(format!("no response within {PROBE_TIMEOUT:?}")));
+
+    ProbeReport { name, elapsed: started_at.elapsed(), error: result.err() }
+}
+
+/// Fetch the public description of the realm, which fails unless both the
+/// server and the realm exist
+async fn probe_keycloak(
+    KeycloakConfig { server_url, realm, verify_ssl, .. }: &KeycloakConfig,
+) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(!verify_ssl)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let url = format!("{}/realms/{realm}", server_url.trim_end_matches('/'));
+
+    let response = client.get(&url).send().await.map_err(|err| err.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("GET {url} returned {}", response.status()))
+    }
+}
@@ -0,0 +1,224 @@
+//! Reloading the configuration while serving.
+//!
+//! On `SIGHUP` or `POST /api/v1/admin/reload` the configuration is read again
+//! from its [`ConfigSource`]. The log filters, rate limits, CORS origins, fault
+//! injection and JWKS cache TTL are swapped in place, other changed settings
+//! are logged and take effect after a restart.
+
+use std::{fmt::Debug, sync::Arc};
+
+use async_trait::async_trait;
+use mpc_backend_mock_core::config::{Config, KeycloakConfig, WebConfig};
+use snafu::Snafu;
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::Mutex,
+};
+
+use crate::{
+    entity::ConfigReloadReport,
+    web::middleware::{CorsOrigins, FaultInjector, RateLimiter, Realms},
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Failed to load the configuration, {message}"))]
+    LoadConfig { message: String },
+
+    #[snafu(display("Failed to reload the log filters, {message}"))]
+    ReloadLogFilters { message: String },
+}
+
+/// Where the configuration is read again from on reload
+#[async_trait]
+pub trait ConfigSource: Send + Sync {
+    /// Read and validate the configuration
+    async fn load(&self) -> std::result::Result<Config, String>;
+
+    /// Filter the log with `log_filters` from now on
+    fn reload_log_filters(&self, log_filters: &str) -> std::result::Result<(), String>;
+}
+
+/// Applies reloaded configurations to the running server
+#[derive(Clone)]
+pub struct ConfigReloader {
+    source: Arc<dyn ConfigSource>,
+
+    /// Configuration the server runs with, locked for the whole reload so
+    /// that concurrent reloads apply one after the other
+    current: Arc<Mutex<Config>>,
+
+    rate_limiter: RateLimiter,
+
+    realms: Realms,
+
+    cors_origins: CorsOrigins,
+
+    fault_injector: FaultInjector,
+}
+
+impl ConfigReloader {
+    #[must_use]
+    pub fn new(
+        source: Arc<dyn ConfigSource>,
+        config: Config,
+        rate_limiter: RateLimiter,
+        realms: Realms,
+        cors_origins: CorsOrigins,
+        fault_injector: FaultInjector,
+    ) -> Self {
+        Self {
+            source,
+            current: Arc::new(Mutex::new(config)),
+            rate_limiter,
+            realms,
+            cors_origins,
+            fault_injector,
+        }
+    }
+
+    /// Read the configuration again and apply the reloadable settings
+    ///
+    /// Settings which need a restart keep being reported until the server is
+    /// restarted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded, nothing is
+    /// applied then
+    pub async fn reload(&self) -> Result<ConfigReloadReport> {
+        let mut current = self.current.lock().await;
+        let config = self.source.load().await.map_err(|message| Error::LoadConfig { message })?;
+        let report = changes(&current, &config);
+
+        if config.log_filters != current.log_filters {
+            self.source
+                .reload_log_filters(&config.log_filters)
+                .map_err(|message| Error::ReloadLogFilters { message })?;
+            current.log_filters = config.log_filters;
+        }
+        self.rate_limiter.reload(config.rate_limit);
+        current.rate_limit = config.rate_limit;
+        self.cors_origins.reload(&config.web.cors_allowed_origins);
+        current.web.cors_allowed_origins = config.web.cors_allowed_origins;
+        if config.web.fault_injection != current.web.fault_injection {
+            // a toggle through the admin endpoint is kept until the rules change
+            self.fault_injector.reload(config.web.fault_injection.clone());
+            current.web.fault_injection = config.web.fault_injection;
+        }
+        for realm in self.realms.iter() {
+            realm.jwks_client().reload_cache_ttl(config.keycloak.jwks_cache_ttl);
+        }
+        current.keycloak.jwks_cache_ttl = config.keycloak.jwks_cache_ttl;
+        drop(current);
+
+        for field in &report.applied {
+            tracing::info!("Applied the reloaded `{field}`");
+        }
+        for field in &report.restart_required {
+            tracing::warn!("`{field}` changed, restart the server to apply it");
+        }
+        if report.applied.is_empty() && report.restart_required.is_empty() {
+            tracing::info!("Reloaded configuration is unchanged");
+        }
+
+        Ok(report)
+    }
+
+    /// Reload on every `SIGHUP`
+    pub async fn reload_on_hangup(self) {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(err) => {
+                tracing::warn!("Configuration is not reloaded on SIGHUP, error: {err}");
+                return;
+            }
+        };
+
+        while hangup.recv().await.is_some() {
+            tracing::info!("Reloading configuration on SIGHUP");
+            if let Err(err) = self.reload().await {
+                tracing::error!("{err}");
+            }
+        }
+    }
+}
+
+/// Settings which differ between `current` and `new`, named by their path in
+/// the configuration file
+fn changes(current: &Config, new: &Config) -> ConfigReloadReport {
+    let Config {
+        log_filters,
+        web,
+        postgres,
+        metrics,
+        health_check,
+        bitcoin,
+        solana,
+        chain_state,
+        keycloak,
+        notification,
+        events,
+        activation,
+        session,
+        login_lockout,
+        signing,
+        key_generation,
+        rate_limit,
+        circuit_breaker,
+        shutdown,
+        redis,
+    } = new;
+
+    let applied = [
+        ("log.log_filters", *log_filters != current.log_filters),
+        ("rate_limit", differs(rate_limit, &current.rate_limit)),
+        ("web.cors_allowed_origins", web.cors_allowed_origins != current.web.cors_allowed_origins),
+        ("web.fault_injection", web.fault_injection != current.web.fault_injection),
+        (
+            "keycloak.jwks_cache_ttl_seconds",
+            keycloak.jwks_cache_ttl != current.keycloak.jwks_cache_ttl,
+        ),
+    ];
+
+    // the reloadable settings of a section do not make it need a restart
+    let web = WebConfig {
+        cors_allowed_origins: current.web.cors_allowed_origins.clone(),
+        fault_injection: current.web.fault_injection.clone(),
+        ..web.clone()
+    };
+    let keycloak =
+        KeycloakConfig { jwks_cache_ttl: current.keycloak.jwks_cache_ttl, ..keycloak.clone() };
+    let restart_required = [
+        ("web", differs(&web, &current.web)),
+        ("postgres", differs(postgres, &current.postgres)),
+        ("metrics", differs(metrics, &current.metrics)),
+        ("health_check", differs(health_check, &current.health_check)),
+        ("bitcoin", differs(bitcoin, &current.bitcoin)),
+        ("solana", differs(solana, &current.solana)),
+        ("chain_state", differs(chain_state, &current.chain_state)),
+        ("keycloak", differs(&keycloak, &current.keycloak)),
+        ("notification", differs(notification, &current.notification)),
+        ("events", differs(events, &current.events)),
+        ("activation", differs(activation, &current.activation)),
+        ("session", differs(session, &current.session)),
+        ("login_lockout", differs(login_lockout, &current.login_lockout)),
+        ("signing", differs(signing, &current.signing)),
+        ("key_generation", differs(key_generation, &current.key_generation)),
+        ("circuit_breaker", differs(circuit_breaker, &current.circuit_breaker)),
+        ("shutdown", differs(shutdown, &current.shutdown)),
+        ("redis", differs(redis, &current.redis)),
+    ];
+
+    ConfigReloadReport { applied: changed(&applied), restart_required: changed(&restart_required) }
+}
+
+/// Not every section is `PartialEq`, their `Debug` output tells them apart
+fn differs<T: Debug>(a: &T, b: &T) -> bool { format!("{a:?}") != format!("{b:?}") }
+
+fn changed(fields: &[(&str, bool)]) -> Vec<String> {
+    fields.iter().filter(|(_, changed)| *changed).map(|(field, _)| (*field).to_string()).collect()
+}
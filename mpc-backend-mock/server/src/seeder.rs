@@ -0,0 +1,40 @@
+//! Seed data for demo environments.
+//!
+//! [`seed`] applies declarative [`Fixtures`], usually read from a YAML file, to
+//! Postgres and Keycloak so an environment can be populated reproducibly.
+//! Seeded users skip the activation flow: no activation email is sent, active
+//! users are created activated with a verified email.
+
+use std::sync::Arc;
+
+use mpc_backend_mock_core::config::Config;
+
+use crate::{
+    error::{Error, Result},
+    service::{Fixtures, KeycloakUserDirectory, SeedReport, SeedService},
+};
+
+/// Create the users, Bitcoin addresses and transactions of `fixtures`, without
+/// running migrations
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Postgres or Keycloak cannot be initialized
+/// - A fixture is invalid or conflicts with existing data
+/// - Keycloak or database operation fails
+pub async fn seed(
+    Config { postgres, keycloak, .. }: Config,
+    fixtures: &Fixtures,
+) -> Result<SeedReport> {
+    let (database, (_keycloak_client, keycloak_admin)) = tokio::try_join!(
+        crate::connect_postgres_pool(&postgres),
+        crate::initialize_keycloak_clients(&keycloak),
+    )?;
+
+    let service = SeedService::new(
+        database,
+        Arc::new(KeycloakUserDirectory::new(Arc::new(keycloak_admin), keycloak.realm)),
+    );
+    service.seed(fixtures).await.map_err(|source| Error::SeedFixtures { source })
+}
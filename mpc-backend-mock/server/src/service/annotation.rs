@@ -0,0 +1,189 @@
+use snafu::ResultExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{Annotation, CreateAnnotationRequest},
+    service::{error, sql_executor::AnnotationSqlExecutor},
+};
+
+/// Longest note, in characters
+const MAX_NOTE_LEN: usize = 2000;
+
+/// Longest flag, in characters
+const MAX_FLAG_LEN: usize = 64;
+
+/// Most flags of an annotation
+const MAX_FLAGS: usize = 16;
+
+/// Annotation service for the notes and flags operators attach to users and
+/// transactions
+///
+/// Annotations live in their own table and are never part of the user or
+/// transaction responses of user-facing routes.
+#[derive(Clone)]
+pub struct AnnotationService {
+    db: PgPool,
+}
+
+impl AnnotationService {
+    /// Create a new annotation service
+    #[inline]
+    #[must_use]
+    pub const fn new(db: PgPool) -> Self { Self { db } }
+
+    /// Annotate a user, deleted users included
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Annotation is empty or too long
+    /// - User not found
+    /// - Database operation fails
+    pub async fn annotate_user(
+        &self,
+        user_id: &Uuid,
+        request: &CreateAnnotationRequest,
+    ) -> Result<Annotation> {
+        let CreateAnnotationRequest { note, flags } = validate(request)?;
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        match conn.insert_annotation(Some(user_id), None, &note, &flags).await {
+            Err(Error::InsertAnnotation { source }) if is_foreign_key_violation(&source) => {
+                Err(Error::UserNotFound { user_id: *user_id })
+            }
+            result => result,
+        }
+    }
+
+    /// Annotate a transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Annotation is empty or too long
+    /// - Transaction not found
+    /// - Database operation fails
+    pub async fn annotate_transaction(
+        &self,
+        transaction_id: &Uuid,
+        request: &CreateAnnotationRequest,
+    ) -> Result<Annotation> {
+        let CreateAnnotationRequest { note, flags } = validate(request)?;
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        match conn.insert_annotation(None, Some(transaction_id), &note, &flags).await {
+            Err(Error::InsertAnnotation { source }) if is_foreign_key_violation(&source) => {
+                Err(Error::TransactionNotFound { transaction_id: *transaction_id })
+            }
+            result => result,
+        }
+    }
+
+    /// List the annotations on a user, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails
+    pub async fn list_user_annotations(&self, user_id: &Uuid) -> Result<Vec<Annotation>> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        conn.list_annotations_by_user_id(user_id).await
+    }
+
+    /// List the annotations on a transaction, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails
+    pub async fn list_transaction_annotations(
+        &self,
+        transaction_id: &Uuid,
+    ) -> Result<Vec<Annotation>> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        conn.list_annotations_by_transaction_id(transaction_id).await
+    }
+
+    /// Delete an annotation
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Annotation not found
+    /// - Database operation fails
+    pub async fn delete_annotation(&self, annotation_id: &Uuid) -> Result<()> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        if conn.delete_annotation_by_id(annotation_id).await? == 0 {
+            return Err(Error::AnnotationNotFound { annotation_id: *annotation_id });
+        }
+
+        tracing::info!("Annotation {annotation_id} deleted");
+        Ok(())
+    }
+}
+
+fn is_foreign_key_violation(source: &sqlx::Error) -> bool {
+    source.as_database_error().is_some_and(|err| err.is_foreign_key_violation())
+}
+
+/// Trim the note and flags, dropping empty and duplicate flags
+fn validate(request: &CreateAnnotationRequest) -> Result<CreateAnnotationRequest> {
+    let note = request.note.trim().to_string();
+    let mut flags = Vec::<String>::new();
+    for flag in
+        request.flags.iter().map(String::as_str).map(str::trim).filter(|flag| !flag.is_empty())
+    {
+        if !flags.iter().any(|existing| existing == flag) {
+            flags.push(flag.to_string());
+        }
+    }
+
+    let reason = if note.is_empty() && flags.is_empty() {
+        Some("a note or a flag is required")
+    } else if note.chars().count() > MAX_NOTE_LEN {
+        Some("the note is longer than 2000 characters")
+    } else if flags.len() > MAX_FLAGS {
+        Some("there are more than 16 flags")
+    } else if flags.iter().any(|flag| flag.chars().count() > MAX_FLAG_LEN) {
+        Some("a flag is longer than 64 characters")
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        return Err(Error::InvalidAnnotation { reason });
+    }
+
+    Ok(CreateAnnotationRequest { note, flags })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(note: &str, flags: &[&str]) -> CreateAnnotationRequest {
+        CreateAnnotationRequest {
+            note: note.to_string(),
+            flags: flags.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_normalizes() {
+        let annotation = validate(&request("  note ", &["test account", " ", "test account "]))
+            .expect("annotation is valid");
+
+        assert_eq!(annotation.note, "note");
+        assert_eq!(annotation.flags, vec!["test account".to_string()]);
+    }
+
+    #[test]
+    fn test_validate_rejects() {
+        assert!(validate(&request(" ", &[""])).is_err());
+        assert!(validate(&request(&"a".repeat(MAX_NOTE_LEN + 1), &[])).is_err());
+        assert!(validate(&request("", &["a".repeat(MAX_FLAG_LEN + 1).as_str()])).is_err());
+
+        let flags = (0..=MAX_FLAGS).map(|i| format!("flag {i}")).collect::<Vec<_>>();
+        let flags = flags.iter().map(String::as_str).collect::<Vec<_>>();
+        assert!(validate(&request("", &flags)).is_err());
+    }
+}
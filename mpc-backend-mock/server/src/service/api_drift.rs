@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use snafu::{OptionExt, ResultExt};
+use sqlx::PgPool;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{ApiDrift, ApiDriftReport, OpenApiBaseline},
+    service::{error, sql_executor::OpenApiBaselineSqlExecutor},
+};
+
+/// Keys of a path item which are operations
+const HTTP_METHODS: [&str; 8] =
+    ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// API drift service comparing the live OpenAPI document against an uploaded
+/// baseline
+///
+/// Operations and component schemas are compared by their JSON definition, a
+/// schema referenced by `$ref` shows up as changed under `schemas` only.
+#[derive(Clone)]
+pub struct ApiDriftService {
+    db: PgPool,
+}
+
+impl ApiDriftService {
+    /// Create a new API drift service
+    #[inline]
+    #[must_use]
+    pub const fn new(db: PgPool) -> Self { Self { db } }
+
+    /// Store `document` as the baseline later reports compare against
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Document has no `openapi` version or no `paths` object
+    /// - Database operation fails
+    pub async fn upload_baseline(&self, document: &Value) -> Result<OpenApiBaseline> {
+        let is_openapi = document.get("openapi").is_some_and(Value::is_string)
+            && document.get("paths").is_some_and(Value::is_object);
+        if !is_openapi {
+            return Err(Error::InvalidOpenApiDocument);
+        }
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let baseline = conn.insert_openapi_baseline(document).await?;
+
+        tracing::info!("OpenAPI baseline {} uploaded", baseline.id);
+        Ok(baseline)
+    }
+
+    /// Compare `live` against the latest baseline
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No baseline was uploaded
+    /// - Live document cannot be serialized
+    /// - Database operation fails
+    pub async fn drift_report(&self, live: &utoipa::openapi::OpenApi) -> Result<ApiDriftReport> {
+        let live = serde_json::to_value(live).context(error::SerializeOpenApiDocumentSnafu)?;
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let (baseline, document) = conn
+            .get_latest_openapi_baseline()
+            .await?
+            .context(error::OpenApiBaselineNotFoundSnafu)?;
+
+        let operations = diff(&operations(&document), &operations(&live));
+        let schemas = diff(&schemas(&document), &schemas(&live));
+
+        Ok(ApiDriftReport {
+            baseline,
+            has_drift: !(operations.is_empty() && schemas.is_empty()),
+            operations,
+            schemas,
+        })
+    }
+}
+
+/// Operations of a document, keyed by `<METHOD> <path>`
+fn operations(document: &Value) -> BTreeMap<String, &Value> {
+    document
+        .get("paths")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(path, item)| Some((path, item.as_object()?)))
+        .flat_map(|(path, item)| {
+            item.iter().filter(|(method, _)| HTTP_METHODS.contains(&method.as_str())).map(
+                move |(method, operation)| (format!("{} {path}", method.to_uppercase()), operation),
+            )
+        })
+        .collect()
+}
+
+/// Component schemas of a document, keyed by name
+fn schemas(document: &Value) -> BTreeMap<String, &Value> {
+    document
+        .pointer("/components/schemas")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(name, schema)| (name.clone(), schema))
+        .collect()
+}
+
+fn diff(baseline: &BTreeMap<String, &Value>, live: &BTreeMap<String, &Value>) -> ApiDrift {
+    let added = live.keys().filter(|name| !baseline.contains_key(*name)).cloned().collect();
+    let removed = baseline.keys().filter(|name| !live.contains_key(*name)).cloned().collect();
+    let changed = live
+        .iter()
+        .filter(|(name, definition)| baseline.get(*name).is_some_and(|old| old != *definition))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    ApiDrift { added, removed, changed }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_operations_and_schemas() {
+        let baseline = json!({
+            "openapi": "3.1.0",
+            "paths": {
+                "/api/v1/users": {
+                    "get": { "operationId": "list_users" },
+                    "post": { "operationId": "create_user" }
+                },
+                "/api/v1/info": { "get": { "operationId": "get_server_info" } }
+            },
+            "components": { "schemas": { "User": { "type": "object" }, "Old": {} } }
+        });
+        let live = json!({
+            "openapi": "3.1.0",
+            "paths": {
+                "/api/v1/users": {
+                    "get": { "operationId": "list_users" },
+                    "post": { "operationId": "create_user", "deprecated": true },
+                    "delete": { "operationId": "delete_user" },
+                    "parameters": []
+                }
+            },
+            "components": { "schemas": { "User": { "type": "object" }, "New": {} } }
+        });
+
+        assert_eq!(
+            diff(&operations(&baseline), &operations(&live)),
+            ApiDrift {
+                added: vec!["DELETE /api/v1/users".to_string()],
+                removed: vec!["GET /api/v1/info".to_string()],
+                changed: vec!["POST /api/v1/users".to_string()],
+            }
+        );
+        assert_eq!(
+            diff(&schemas(&baseline), &schemas(&live)),
+            ApiDrift {
+                added: vec!["New".to_string()],
+                removed: vec!["Old".to_string()],
+                changed: Vec::new(),
+            }
+        );
+        assert!(diff(&operations(&live), &operations(&live)).is_empty());
+    }
+}
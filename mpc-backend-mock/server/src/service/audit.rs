@@ -0,0 +1,106 @@
+use std::net::IpAddr;
+
+use mpc_backend_mock_core::model::Pagination;
+use snafu::ResultExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::Result;
+use crate::{
+    entity::{AuditAction, AuditLog, ListAuditLogsFilter},
+    service::{error, sql_executor::AuditLogSqlExecutor},
+};
+
+/// Where an audited action came from
+#[derive(Clone, Debug, Default)]
+pub struct AuditContext {
+    /// User performing the action, `None` for anonymous and system actions
+    pub actor_user_id: Option<Uuid>,
+
+    /// Client IP of the request
+    pub ip_address: Option<IpAddr>,
+
+    /// `X-Request-Id` of the request
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    #[must_use]
+    pub const fn with_actor(mut self, actor_user_id: Uuid) -> Self {
+        self.actor_user_id = Some(actor_user_id);
+        self
+    }
+}
+
+/// Audit service recording security-relevant actions in the `audit_logs`
+/// table
+#[derive(Clone)]
+pub struct AuditService {
+    db: PgPool,
+}
+
+impl AuditService {
+    /// Create a new audit service
+    #[inline]
+    #[must_use]
+    pub const fn new(db: PgPool) -> Self { Self { db } }
+
+    /// Record an action on the resource `target_id`
+    ///
+    /// A failure is logged instead of returned, the audited request is not
+    /// failed because it could not be recorded.
+    pub async fn record(
+        &self,
+        context: &AuditContext,
+        action: AuditAction,
+        target_id: Option<&Uuid>,
+        payload: serde_json::Value,
+    ) {
+        if let Err(err) = self.insert(context, action, target_id, &payload).await {
+            tracing::error!(action = action.as_str(), "Failed to record audit log, error: {err}");
+        }
+    }
+
+    async fn insert(
+        &self,
+        context: &AuditContext,
+        action: AuditAction,
+        target_id: Option<&Uuid>,
+        payload: &serde_json::Value,
+    ) -> Result<AuditLog> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.insert_audit_log(
+            context.actor_user_id.as_ref(),
+            action.as_str(),
+            action.target_type(),
+            target_id,
+            context.ip_address.map(|ip| ip.to_string()).as_deref(),
+            context.request_id.as_deref(),
+            payload,
+        )
+        .await
+    }
+
+    /// List audit log entries page by page, newest first, returns the entries
+    /// and the number of entries matching the filters
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn list_audit_logs(
+        &self,
+        pagination: &Pagination,
+        filter: &ListAuditLogsFilter,
+    ) -> Result<(Vec<AuditLog>, u64)> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        let limit = pagination.sql_limit();
+        let offset = pagination.sql_offset();
+
+        let logs = conn.list_audit_logs(filter, limit, offset).await?;
+        let total_count = conn.count_audit_logs(filter).await?;
+
+        Ok((logs, u64::try_from(total_count).unwrap_or_default()))
+    }
+}
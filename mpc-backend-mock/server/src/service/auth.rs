@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    circuit_breaker::CircuitBreaker,
+    entity::{ImpersonationResponse, TokenResponse},
+    keycloak_client::{error::Error as KeycloakClientError, KeycloakClient},
+};
+
+/// Authentication service for issuing tokens through Keycloak
+#[derive(Clone)]
+pub struct AuthService {
+    keycloak_client: Arc<KeycloakClient>,
+
+    keycloak_breaker: CircuitBreaker,
+}
+
+impl AuthService {
+    /// Create a new authentication service
+    #[inline]
+    #[must_use]
+    pub const fn new(
+        keycloak_client: Arc<KeycloakClient>,
+        keycloak_breaker: CircuitBreaker,
+    ) -> Self {
+        Self { keycloak_client, keycloak_breaker }
+    }
+
+    /// Log in with email and password
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Keycloak rejects the credentials
+    /// - The Keycloak token request fails
+    /// - Keycloak is considered down by its circuit breaker
+    pub async fn login(&self, email: &str, password: &str) -> Result<TokenResponse> {
+        let login = async {
+            self.keycloak_client
+                .request_password_token(email, password)
+                .await
+                .map(TokenResponse::from)
+                .map_err(|source| match source {
+                    KeycloakClientError::TokenRejected { ref error, .. }
+                        if error == "invalid_grant" =>
+                    {
+                        Error::InvalidCredentials { email: email.to_string() }
+                    }
+                    source => Error::RequestKeycloakToken { source },
+                })
+        };
+
+        self.keycloak_breaker.call(login, Error::is_dependency_failure).await
+    }
+
+    /// Check the password of a user without keeping the issued tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Keycloak rejects the credentials
+    /// - The Keycloak token request fails
+    /// - Keycloak is considered down by its circuit breaker
+    pub async fn verify_password(&self, email: &str, password: &str) -> Result<()> {
+        self.login(email, password).await.map(drop)
+    }
+
+    /// Exchange a refresh token for a new set of tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The refresh token is invalid, expired or revoked
+    /// - The Keycloak token request fails
+    /// - Keycloak is considered down by its circuit breaker
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenResponse> {
+        let refresh = async {
+            self.keycloak_client
+                .refresh_token(refresh_token)
+                .await
+                .map(TokenResponse::from)
+                .map_err(|source| match source {
+                    KeycloakClientError::TokenRejected { ref error, .. }
+                        if error == "invalid_grant" =>
+                    {
+                        Error::InvalidRefreshToken
+                    }
+                    source => Error::RequestKeycloakToken { source },
+                })
+        };
+
+        self.keycloak_breaker.call(refresh, Error::is_dependency_failure).await
+    }
+
+    /// Issue an access token of `user_id`, whose Keycloak user is
+    /// `keycloak_user_id`, for an admin to act as the user
+    ///
+    /// The refresh token Keycloak may issue along is dropped, the access token
+    /// is only valid for the access token lifespan of the realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Keycloak does not allow the backend to impersonate the user
+    /// - The Keycloak token request fails
+    /// - Keycloak is considered down by its circuit breaker
+    pub async fn impersonate(
+        &self,
+        user_id: Uuid,
+        keycloak_user_id: &Uuid,
+    ) -> Result<ImpersonationResponse> {
+        let impersonate = async {
+            self.keycloak_client
+                .impersonate(keycloak_user_id)
+                .await
+                .map(|token| ImpersonationResponse {
+                    user_id,
+                    access_token: token.access_token,
+                    expires_in: token.expires_in,
+                    token_type: token.token_type,
+                })
+                .map_err(|source| Error::RequestKeycloakToken { source })
+        };
+
+        self.keycloak_breaker.call(impersonate, Error::is_dependency_failure).await
+    }
+}
+
+/// Minimum number of characters of a new password
+pub const MIN_PASSWORD_LENGTH: usize = 12;
+
+/// Maximum number of characters of a new password
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Check a new password of the user with `email`, returning why it is too
+/// weak
+///
+/// # Errors
+///
+/// Returns the reason if the password is too short or too long, does not mix
+/// letters with digits or symbols, or contains the local part of the email.
+pub fn check_password_strength(
+    password: &str,
+    email: &str,
+) -> std::result::Result<(), &'static str> {
+    let length = password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        return Err("password must be at least 12 characters");
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err("password must be at most 128 characters");
+    }
+    if !password.chars().any(char::is_alphabetic)
+        || password.chars().all(|c| c.is_alphabetic() || c.is_whitespace())
+    {
+        return Err("password must mix letters with digits or symbols");
+    }
+
+    let local_part = email.split('@').next().unwrap_or_default().to_lowercase();
+    if local_part.len() >= 3 && password.to_lowercase().contains(&local_part) {
+        return Err("password must not contain the email address");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_password_strength() {
+        let email = "satoshi@example.com";
+
+        assert_eq!(check_password_strength("correct-horse-42", email), Ok(()));
+        assert_eq!(check_password_strength("密碼很安全而且夠長了吧1", email), Ok(()));
+        assert!(check_password_strength("short-1", email).is_err());
+        assert!(check_password_strength(&"a1".repeat(65), email).is_err());
+        assert!(check_password_strength("onlyletterspassword", email).is_err());
+        assert!(check_password_strength("123456789012345", email).is_err());
+        assert!(check_password_strength("Satoshi-is-42-years", email).is_err());
+    }
+}
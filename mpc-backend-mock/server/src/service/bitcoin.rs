@@ -0,0 +1,282 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use mpc_backend_mock_core::{config::BitcoinConfig, model::Satoshis};
+use serde::{de::DeserializeOwned, Serialize};
+use snafu::{OptionExt, ResultExt};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    chain_state::ChainStateCache,
+    entity::{
+        BitcoinBalance, BitcoinFeeEstimate, BitcoinFeeEstimates, BitcoinMempool, BitcoinUtxo,
+        BitcoinUtxoSet, Chain,
+    },
+    service::{
+        chain::{AddressUtxo, BitcoinChain},
+        error,
+        sql_executor::{UserSqlExecutor, WalletSqlExecutor},
+    },
+    store::{MemoryStore, Store},
+};
+
+/// Confirmation targets of the fee estimates, in blocks
+pub const FEE_ESTIMATE_TARGETS: [u16; 6] = [1, 3, 6, 12, 24, 144];
+
+const FEE_ESTIMATES_CACHE_KEY: &str = "bitcoin:fee_estimates";
+
+const MEMPOOL_CACHE_KEY: &str = "bitcoin:mempool";
+
+/// Bitcoin service for reading the balance of the user's Bitcoin wallets and
+/// the fee market of the node
+///
+/// The UTXOs come from the [`BitcoinChain`] and the current block height from
+/// the [`ChainStateCache`] while it is fresh, an output counts as confirmed
+/// once it has `block_number_to_confirm` confirmations. Fee estimates and the
+/// mempool are cached in the [`Store`] for `fee_cache_ttl`, so that clients
+/// polling them do not hammer the node.
+#[derive(Clone)]
+pub struct BitcoinService {
+    db: PgPool,
+    chain: Arc<dyn BitcoinChain>,
+    block_number_to_confirm: u64,
+    store: Arc<dyn Store>,
+    fee_cache_ttl: Duration,
+    chain_state: ChainStateCache,
+}
+
+impl BitcoinService {
+    /// Create a new Bitcoin service, caching in process
+    #[must_use]
+    pub fn new(db: PgPool, chain: Arc<dyn BitcoinChain>, config: &BitcoinConfig) -> Self {
+        Self {
+            db,
+            chain,
+            block_number_to_confirm: config.block_number_to_confirm,
+            store: Arc::new(MemoryStore::default()),
+            fee_cache_ttl: config.fee_cache_ttl,
+            chain_state: ChainStateCache::default(),
+        }
+    }
+
+    /// Cache fee estimates and the mempool in `store`, e.g. shared by the
+    /// replicas in Redis
+    #[must_use]
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Read the current block height from `chain_state` while it is fresh
+    #[must_use]
+    pub fn with_chain_state(mut self, chain_state: ChainStateCache) -> Self {
+        self.chain_state = chain_state;
+        self
+    }
+
+    /// List the UTXOs of the Bitcoin wallets owned by a user
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The indexer endpoint is not configured
+    /// - User not found
+    /// - The Bitcoin RPC or indexer request fails
+    /// - Database operation fails
+    pub async fn list_utxos(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+    ) -> Result<BitcoinUtxoSet> {
+        let addresses = {
+            let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+            let user = conn
+                .get_user_by_keycloak_id(keycloak_user_id, realm, false)
+                .await?
+                .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })?;
+
+            conn.list_wallets_by_user_id(&user.id)
+                .await?
+                .into_iter()
+                .filter(|wallet| wallet.chain == Chain::Bitcoin)
+                .map(|wallet| wallet.address)
+                .collect::<Vec<_>>()
+        };
+
+        let utxos = self.chain.list_utxos(&addresses).await?;
+        let block_height = match self.chain_state.fresh_bitcoin_block_height() {
+            // the cache may lag behind the indexer by a block
+            Some(block_height) => {
+                utxos.iter().filter_map(|utxo| utxo.block_height).fold(block_height, u64::max)
+            }
+            None => self.chain.get_block_count().await?,
+        };
+        let utxos = utxos
+            .into_iter()
+            .map(|utxo| to_utxo(block_height, self.block_number_to_confirm, utxo))
+            .collect();
+
+        Ok(BitcoinUtxoSet { block_height, utxos })
+    }
+
+    /// Get the confirmed and unconfirmed balance of the Bitcoin wallets owned
+    /// by a user
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Self::list_utxos`], or an error if the balance
+    /// overflows
+    pub async fn get_balance(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+    ) -> Result<BitcoinBalance> {
+        let BitcoinUtxoSet { block_height, utxos } =
+            self.list_utxos(keycloak_user_id, realm).await?;
+
+        let (confirmed, unconfirmed): (Vec<_>, Vec<_>) =
+            utxos.into_iter().partition(|utxo| utxo.confirmed);
+        let sum = |utxos: Vec<BitcoinUtxo>| {
+            Satoshis::checked_sum(utxos.into_iter().map(|utxo| utxo.amount))
+                .context(error::BitcoinBalanceOverflowSnafu)
+        };
+
+        Ok(BitcoinBalance {
+            confirmed: sum(confirmed)?,
+            unconfirmed: sum(unconfirmed)?,
+            block_height,
+            block_number_to_confirm: self.block_number_to_confirm,
+        })
+    }
+
+    /// Fee rates estimated by the node for each of the
+    /// [`FEE_ESTIMATE_TARGETS`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bitcoin RPC request fails
+    pub async fn get_fee_estimates(&self) -> Result<BitcoinFeeEstimates> {
+        if let Some(estimates) = self.cached(FEE_ESTIMATES_CACHE_KEY).await {
+            return Ok(estimates);
+        }
+
+        let mut estimates = Vec::with_capacity(FEE_ESTIMATE_TARGETS.len());
+        for target_blocks in FEE_ESTIMATE_TARGETS {
+            let sat_per_vbyte = self.chain.estimate_smart_fee(target_blocks).await?;
+            estimates.push(BitcoinFeeEstimate { target_blocks, sat_per_vbyte });
+        }
+        let estimates = BitcoinFeeEstimates { estimates, fetched_at: Utc::now() };
+
+        self.cache(FEE_ESTIMATES_CACHE_KEY, &estimates).await;
+        Ok(estimates)
+    }
+
+    /// Size and minimum fee rates of the mempool of the node
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bitcoin RPC request fails
+    pub async fn get_mempool(&self) -> Result<BitcoinMempool> {
+        if let Some(mempool) = self.cached(MEMPOOL_CACHE_KEY).await {
+            return Ok(mempool);
+        }
+
+        let info = self.chain.get_mempool_info().await?;
+        let mempool = BitcoinMempool {
+            transaction_count: info.size,
+            vsize: info.bytes,
+            min_fee_sat_per_vbyte: info.min_fee_rate,
+            min_relay_fee_sat_per_vbyte: info.min_relay_fee_rate,
+            fetched_at: Utc::now(),
+        };
+
+        self.cache(MEMPOOL_CACHE_KEY, &mempool).await;
+        Ok(mempool)
+    }
+
+    /// Value cached under `key`, a failing store is a cache miss
+    async fn cached<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        if self.fee_cache_ttl.is_zero() {
+            return None;
+        }
+
+        let data = match self.store.get(key).await {
+            Ok(data) => data?,
+            Err(err) => {
+                tracing::warn!("Failed to read cached `{key}`, error: {err}");
+                return None;
+            }
+        };
+
+        serde_json::from_slice(&data)
+            .inspect_err(|err| tracing::warn!("Ignoring malformed cached `{key}`: {err}"))
+            .ok()
+    }
+
+    async fn cache<T: Serialize>(&self, key: &str, value: &T) {
+        if self.fee_cache_ttl.is_zero() {
+            return;
+        }
+
+        let data = match serde_json::to_vec(value) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::warn!("Failed to serialize `{key}`, error: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = self.store.set(key, &data, self.fee_cache_ttl).await {
+            tracing::warn!("Failed to cache `{key}`, error: {err}");
+        }
+    }
+}
+
+fn to_utxo(block_height: u64, block_number_to_confirm: u64, utxo: AddressUtxo) -> BitcoinUtxo {
+    let AddressUtxo { address, txid, vout, value, block_height: utxo_block_height } = utxo;
+    let confirmations = utxo_block_height
+        .map_or(0, |utxo_block_height| block_height.saturating_sub(utxo_block_height) + 1);
+
+    BitcoinUtxo {
+        address,
+        txid,
+        vout,
+        amount: Satoshis::new(value),
+        block_height: utxo_block_height,
+        confirmations,
+        confirmed: confirmations > 0 && confirmations >= block_number_to_confirm,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address_utxo(block_height: Option<u64>) -> AddressUtxo {
+        AddressUtxo {
+            address: "address".to_string(),
+            txid: "00".repeat(32),
+            vout: 0,
+            value: 1_000,
+            block_height,
+        }
+    }
+
+    #[test]
+    fn test_confirmation_rule() {
+        let utxo = to_utxo(105, 6, address_utxo(Some(100)));
+        assert_eq!(utxo.confirmations, 6);
+        assert!(utxo.confirmed);
+
+        let utxo = to_utxo(105, 6, address_utxo(Some(101)));
+        assert_eq!(utxo.confirmations, 5);
+        assert!(!utxo.confirmed);
+
+        let utxo = to_utxo(105, 0, address_utxo(None));
+        assert_eq!(utxo.confirmations, 0);
+        assert_eq!(utxo.block_height, None);
+        assert!(!utxo.confirmed);
+    }
+}
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use eris_bitcoin_rpc_client::Client as BitcoinRpcClient;
+use serde::Deserialize;
+use snafu::{OptionExt, ResultExt};
+
+use super::{AddressUtxo, BitcoinChain, MempoolInfo};
+use crate::service::error::{self, Result};
+
+/// UTXO as returned by the Esplora `GET /address/{address}/utxo` API
+#[derive(Debug, Deserialize)]
+struct IndexerUtxo {
+    txid: String,
+    vout: u32,
+    value: u64,
+    status: IndexerUtxoStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexerUtxoStatus {
+    confirmed: bool,
+    block_height: Option<u64>,
+}
+
+/// Satoshis per virtual byte of a fee rate in BTC per 1000 virtual bytes, as
+/// returned by the Bitcoin RPC endpoint
+const fn sat_per_vbyte(btc_per_kvbyte: f64) -> f64 { btc_per_kvbyte * 100_000.0 }
+
+/// [`BitcoinChain`] reading the block height and the fee market from the
+/// Bitcoin RPC endpoint and the UTXOs from the indexer endpoint
+#[derive(Clone)]
+pub struct RpcBitcoinChain {
+    rpc_client: BitcoinRpcClient,
+    http_client: reqwest::Client,
+    indexer_endpoint: Option<http::Uri>,
+}
+
+impl RpcBitcoinChain {
+    #[must_use]
+    pub fn new(rpc_client: BitcoinRpcClient, indexer_endpoint: Option<http::Uri>) -> Self {
+        Self { rpc_client, http_client: reqwest::Client::new(), indexer_endpoint }
+    }
+}
+
+#[async_trait]
+impl BitcoinChain for RpcBitcoinChain {
+    fn describe(&self) -> String {
+        format!("Bitcoin RPC endpoint {}", self.rpc_client.rpc_endpoint())
+    }
+
+    async fn get_block_count(&self) -> Result<u64> {
+        self.rpc_client.get_block_count().await.context(error::GetBitcoinBlockCountSnafu)
+    }
+
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>> {
+        let indexer_endpoint =
+            self.indexer_endpoint.as_ref().context(error::BitcoinIndexerNotConfiguredSnafu)?;
+        let indexer_endpoint = indexer_endpoint.to_string();
+        let indexer_endpoint = indexer_endpoint.trim_end_matches('/');
+
+        let mut utxos = Vec::new();
+        for address in addresses {
+            let indexer_utxos: Vec<IndexerUtxo> = self
+                .http_client
+                .get(format!("{indexer_endpoint}/address/{address}/utxo"))
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .context(error::RequestBitcoinIndexerSnafu { address: address.clone() })?
+                .json()
+                .await
+                .context(error::RequestBitcoinIndexerSnafu { address: address.clone() })?;
+
+            utxos.extend(indexer_utxos.into_iter().map(
+                |IndexerUtxo { txid, vout, value, status }| AddressUtxo {
+                    address: address.clone(),
+                    txid,
+                    vout,
+                    value,
+                    block_height: status.block_height.filter(|_| status.confirmed),
+                },
+            ));
+        }
+
+        Ok(utxos)
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        let estimate = self
+            .rpc_client
+            .estimate_smart_fee(conf_target)
+            .await
+            .context(error::EstimateBitcoinSmartFeeSnafu { conf_target })?;
+
+        Ok(estimate.fee_rate.map(sat_per_vbyte))
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        let info =
+            self.rpc_client.get_mempool_info().await.context(error::GetBitcoinMempoolInfoSnafu)?;
+
+        Ok(MempoolInfo {
+            size: info.size,
+            bytes: info.bytes,
+            min_fee_rate: sat_per_vbyte(info.mempool_min_fee),
+            min_relay_fee_rate: sat_per_vbyte(info.min_relay_tx_fee),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sat_per_vbyte() {
+        // the default minimum relay fee of Bitcoin Core
+        assert!((sat_per_vbyte(0.000_01) - 1.0).abs() < f64::EPSILON);
+        assert!((sat_per_vbyte(0.000_254_3) - 25.43).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,287 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+};
+
+use super::{AddressUtxo, AtSlot, BitcoinChain, MempoolInfo, SignatureStatus, SolanaChain};
+use crate::{
+    cassette::{request_name, Cassette},
+    service::error::Result,
+};
+
+/// [`BitcoinChain`] recording the successful responses of `inner` in
+/// `cassette`
+#[derive(Clone)]
+pub struct RecordingBitcoinChain {
+    inner: Arc<dyn BitcoinChain>,
+    cassette: Cassette,
+}
+
+impl RecordingBitcoinChain {
+    #[must_use]
+    pub fn new(inner: Arc<dyn BitcoinChain>, cassette: Cassette) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+#[async_trait]
+impl BitcoinChain for RecordingBitcoinChain {
+    fn describe(&self) -> String { format!("{}, recorded", self.inner.describe()) }
+
+    async fn get_block_count(&self) -> Result<u64> {
+        let block_count = self.inner.get_block_count().await?;
+        self.cassette.record(request_name("get_block_count", &json!([])), &block_count).await;
+        Ok(block_count)
+    }
+
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>> {
+        let utxos = self.inner.list_utxos(addresses).await?;
+        self.cassette.record(request_name("list_utxos", &json!(addresses)), &utxos).await;
+        Ok(utxos)
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        let fee_rate = self.inner.estimate_smart_fee(conf_target).await?;
+        self.cassette
+            .record(request_name("estimate_smart_fee", &json!([conf_target])), &fee_rate)
+            .await;
+        Ok(fee_rate)
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        let mempool_info = self.inner.get_mempool_info().await?;
+        self.cassette.record(request_name("get_mempool_info", &json!([])), &mempool_info).await;
+        Ok(mempool_info)
+    }
+}
+
+/// [`BitcoinChain`] answering from the responses recorded in `cassette`
+#[derive(Clone)]
+pub struct ReplayedBitcoinChain {
+    cassette: Cassette,
+}
+
+impl ReplayedBitcoinChain {
+    #[must_use]
+    pub const fn new(cassette: Cassette) -> Self { Self { cassette } }
+}
+
+#[async_trait]
+impl BitcoinChain for ReplayedBitcoinChain {
+    fn describe(&self) -> String { "replayed Bitcoin chain".to_string() }
+
+    async fn get_block_count(&self) -> Result<u64> {
+        self.cassette.replay(&request_name("get_block_count", &json!([]))).await
+    }
+
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>> {
+        self.cassette.replay(&request_name("list_utxos", &json!(addresses))).await
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        self.cassette.replay(&request_name("estimate_smart_fee", &json!([conf_target]))).await
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        self.cassette.replay(&request_name("get_mempool_info", &json!([]))).await
+    }
+}
+
+/// [`SolanaChain`] recording the successful responses of `inner` in
+/// `cassette`, and the rejections of sent transactions
+#[derive(Clone)]
+pub struct RecordingSolanaChain {
+    inner: Arc<dyn SolanaChain>,
+    cassette: Cassette,
+}
+
+impl RecordingSolanaChain {
+    #[must_use]
+    pub fn new(inner: Arc<dyn SolanaChain>, cassette: Cassette) -> Self { Self { inner, cassette } }
+}
+
+#[async_trait]
+impl SolanaChain for RecordingSolanaChain {
+    fn commitment(&self) -> CommitmentConfig { self.inner.commitment() }
+
+    async fn get_slot(&self) -> Result<u64> {
+        let slot = self.inner.get_slot().await?;
+        self.cassette.record(request_name("get_slot", &json!([])), &slot).await;
+        Ok(slot)
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<AtSlot<u64>> {
+        let balance = self.inner.get_balance(pubkey).await?;
+        self.cassette
+            .record(request_name("get_balance", &json!([pubkey.to_string()])), &balance)
+            .await;
+        Ok(balance)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<AtSlot<Option<Account>>> {
+        let account = self.inner.get_account(pubkey).await?;
+        self.cassette
+            .record(request_name("get_account", &json!([pubkey.to_string()])), &account)
+            .await;
+        Ok(account)
+    }
+
+    async fn send_transaction(&self, transaction: &str) -> std::result::Result<(), ClientError> {
+        let result = self.inner.send_transaction(transaction).await;
+        // rejections are part of the response, they are recorded on the
+        // transaction
+        let response = result.as_ref().map_err(ToString::to_string);
+        self.cassette
+            .record(request_name("send_transaction", &json!([transaction])), &response)
+            .await;
+        result
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>> {
+        let status = self.inner.get_signature_status(signature).await?;
+        self.cassette
+            .record(request_name("get_signature_status", &json!([signature.to_string()])), &status)
+            .await;
+        Ok(status)
+    }
+}
+
+/// [`SolanaChain`] answering from the responses recorded in `cassette`
+#[derive(Clone)]
+pub struct ReplayedSolanaChain {
+    cassette: Cassette,
+}
+
+impl ReplayedSolanaChain {
+    #[must_use]
+    pub const fn new(cassette: Cassette) -> Self { Self { cassette } }
+}
+
+#[async_trait]
+impl SolanaChain for ReplayedSolanaChain {
+    fn commitment(&self) -> CommitmentConfig { CommitmentConfig::confirmed() }
+
+    async fn get_slot(&self) -> Result<u64> {
+        self.cassette.replay(&request_name("get_slot", &json!([]))).await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<AtSlot<u64>> {
+        self.cassette.replay(&request_name("get_balance", &json!([pubkey.to_string()]))).await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<AtSlot<Option<Account>>> {
+        self.cassette.replay(&request_name("get_account", &json!([pubkey.to_string()]))).await
+    }
+
+    async fn send_transaction(&self, transaction: &str) -> std::result::Result<(), ClientError> {
+        let request = request_name("send_transaction", &json!([transaction]));
+        let response = self
+            .cassette
+            .replay::<std::result::Result<(), String>>(&request)
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|response| response);
+
+        response.map_err(|message| ClientError::from(ClientErrorKind::Custom(message)))
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>> {
+        self.cassette
+            .replay(&request_name("get_signature_status", &json!([signature.to_string()])))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        cassette::{CassetteMode, CassetteOptions},
+        service::{
+            chain::{MockBitcoinChain, MockSolanaChain},
+            error::Error,
+        },
+    };
+
+    /// Cassette `name` in `dir`, recorded or replayed
+    fn open(dir: &Path, name: &'static str, mode: CassetteMode) -> Cassette {
+        CassetteOptions { mode, dir: dir.to_path_buf() }.open(name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bitcoin_chain_is_replayed_as_recorded() {
+        let dir = std::env::temp_dir().join(format!("cassette-test-{}", uuid::Uuid::new_v4()));
+        let addresses = vec!["bcrt1qexample".to_string()];
+
+        let recording = RecordingBitcoinChain::new(
+            Arc::new(MockBitcoinChain),
+            open(&dir, "bitcoin", CassetteMode::Record),
+        );
+        let block_count = recording.get_block_count().await.unwrap();
+        let utxos = recording.list_utxos(&addresses).await.unwrap();
+        let fee_rate = recording.estimate_smart_fee(2).await.unwrap();
+        let mempool_info = recording.get_mempool_info().await.unwrap();
+
+        let replayed = ReplayedBitcoinChain::new(open(&dir, "bitcoin", CassetteMode::Replay));
+        assert_eq!(replayed.get_block_count().await.unwrap(), block_count);
+        assert_eq!(replayed.list_utxos(&addresses).await.unwrap(), utxos);
+        assert_eq!(replayed.estimate_smart_fee(2).await.unwrap(), fee_rate);
+        assert_eq!(replayed.get_mempool_info().await.unwrap(), mempool_info);
+
+        // calls with other arguments were never recorded
+        assert!(matches!(
+            replayed.list_utxos(&["bcrt1qother".to_string()]).await,
+            Err(Error::NoRecordedResponse { cassette: "bitcoin", .. })
+        ));
+        assert!(matches!(
+            replayed.estimate_smart_fee(6).await,
+            Err(Error::NoRecordedResponse { cassette: "bitcoin", .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_solana_chain_is_replayed_as_recorded() {
+        let dir = std::env::temp_dir().join(format!("cassette-test-{}", uuid::Uuid::new_v4()));
+        let pubkey = Pubkey::new_from_array([1; 32]);
+        let signature = Signature::from([7; 64]);
+
+        let recording = RecordingSolanaChain::new(
+            Arc::new(MockSolanaChain::default()),
+            open(&dir, "solana", CassetteMode::Record),
+        );
+        let slot = recording.get_slot().await.unwrap();
+        let balance = recording.get_balance(&pubkey).await.unwrap();
+        let account = recording.get_account(&pubkey).await.unwrap();
+        recording.send_transaction("transaction").await.unwrap();
+        let processed = recording.get_signature_status(&signature).await.unwrap();
+        let confirmed = recording.get_signature_status(&signature).await.unwrap();
+
+        let replayed = ReplayedSolanaChain::new(open(&dir, "solana", CassetteMode::Replay));
+        assert_eq!(replayed.get_slot().await.unwrap(), slot);
+        assert_eq!(replayed.get_balance(&pubkey).await.unwrap(), balance);
+        assert_eq!(replayed.get_account(&pubkey).await.unwrap(), account);
+        replayed.send_transaction("transaction").await.unwrap();
+        // the statuses are replayed in the order they were read
+        assert_eq!(replayed.get_signature_status(&signature).await.unwrap(), processed);
+        assert_eq!(replayed.get_signature_status(&signature).await.unwrap(), confirmed);
+        assert_eq!(replayed.get_signature_status(&signature).await.unwrap(), confirmed);
+
+        assert!(matches!(
+            replayed.get_balance(&Pubkey::new_from_array([2; 32])).await,
+            Err(Error::NoRecordedResponse { cassette: "solana", .. })
+        ));
+        // transactions which were never sent are rejected
+        let err = replayed.send_transaction("other transaction").await.unwrap_err();
+        assert!(err.to_string().contains("No response of `send_transaction"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{AddressUtxo, BitcoinChain, MempoolInfo};
+use crate::{
+    circuit_breaker::CircuitBreaker,
+    service::error::{Error, Result},
+};
+
+/// [`BitcoinChain`] calling the Bitcoin RPC endpoint of `inner` through a
+/// [`CircuitBreaker`], the indexer endpoint is called as it is
+#[derive(Clone)]
+pub struct CircuitBreakingBitcoinChain {
+    inner: Arc<dyn BitcoinChain>,
+    breaker: CircuitBreaker,
+}
+
+impl CircuitBreakingBitcoinChain {
+    #[must_use]
+    pub fn new(inner: Arc<dyn BitcoinChain>, breaker: CircuitBreaker) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl BitcoinChain for CircuitBreakingBitcoinChain {
+    fn describe(&self) -> String { self.inner.describe() }
+
+    async fn get_block_count(&self) -> Result<u64> {
+        self.breaker.call(self.inner.get_block_count(), Error::is_dependency_failure).await
+    }
+
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>> {
+        self.inner.list_utxos(addresses).await
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        self.breaker
+            .call(self.inner.estimate_smart_fee(conf_target), Error::is_dependency_failure)
+            .await
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        self.breaker.call(self.inner.get_mempool_info(), Error::is_dependency_failure).await
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{AddressUtxo, BitcoinChain, MempoolInfo};
+use crate::{dependency_metrics::DependencyMetrics, service::error::Result};
+
+/// [`BitcoinChain`] recording the latency of the calls to `inner` in
+/// [`DependencyMetrics`], RPC endpoint and indexer endpoint apart
+#[derive(Clone)]
+pub struct InstrumentedBitcoinChain {
+    inner: Arc<dyn BitcoinChain>,
+    metrics: DependencyMetrics,
+}
+
+impl InstrumentedBitcoinChain {
+    #[must_use]
+    pub fn new(inner: Arc<dyn BitcoinChain>, metrics: DependencyMetrics) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+#[async_trait]
+impl BitcoinChain for InstrumentedBitcoinChain {
+    fn describe(&self) -> String { self.inner.describe() }
+
+    async fn get_block_count(&self) -> Result<u64> {
+        self.metrics.observe("bitcoin_rpc", "get_block_count", self.inner.get_block_count()).await
+    }
+
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>> {
+        self.metrics
+            .observe("bitcoin_indexer", "list_utxos", self.inner.list_utxos(addresses))
+            .await
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        self.metrics
+            .observe(
+                "bitcoin_rpc",
+                "estimate_smart_fee",
+                self.inner.estimate_smart_fee(conf_target),
+            )
+            .await
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        self.metrics.observe("bitcoin_rpc", "get_mempool_info", self.inner.get_mempool_info()).await
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    system_program,
+};
+use solana_transaction_status_client_types::TransactionConfirmationStatus;
+
+use super::{AddressUtxo, AtSlot, BitcoinChain, MempoolInfo, SignatureStatus, SolanaChain};
+use crate::service::error::Result;
+
+/// Height of the mock Bitcoin chain, which never advances
+pub const MOCK_BITCOIN_BLOCK_HEIGHT: u64 = 1_000;
+
+/// Confirmed output held by every address of the mock Bitcoin chain
+pub const MOCK_BITCOIN_CONFIRMED_SATOSHIS: u64 = 50_000_000;
+
+/// Mempool output held by every address of the mock Bitcoin chain
+pub const MOCK_BITCOIN_UNCONFIRMED_SATOSHIS: u64 = 1_000_000;
+
+/// Fee rate of the mock Bitcoin chain for the next block, in satoshis per
+/// virtual byte, later blocks are cheaper
+pub const MOCK_BITCOIN_NEXT_BLOCK_FEE_RATE: u16 = 20;
+
+/// Slot of every read of the mock Solana chain
+pub const MOCK_SOLANA_SLOT: u64 = 100_000;
+
+/// Balance of every account of the mock Solana chain
+pub const MOCK_SOLANA_LAMPORTS: u64 = 1_000_000_000;
+
+/// Bitcoin chain answering from canned data
+///
+/// Every address holds a confirmed output of
+/// [`MOCK_BITCOIN_CONFIRMED_SATOSHIS`], mined 100 blocks below
+/// [`MOCK_BITCOIN_BLOCK_HEIGHT`], and an output of
+/// [`MOCK_BITCOIN_UNCONFIRMED_SATOSHIS`] in the mempool, the transaction ids
+/// are derived from the address. The fee rate for confirming within `n` blocks
+/// is [`MOCK_BITCOIN_NEXT_BLOCK_FEE_RATE`] divided by `n`, at least 1 sat/vB.
+#[derive(Clone, Debug, Default)]
+pub struct MockBitcoinChain;
+
+#[async_trait]
+impl BitcoinChain for MockBitcoinChain {
+    fn describe(&self) -> String { "mock Bitcoin chain".to_string() }
+
+    async fn get_block_count(&self) -> Result<u64> { Ok(MOCK_BITCOIN_BLOCK_HEIGHT) }
+
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>> {
+        Ok(addresses
+            .iter()
+            .flat_map(|address| {
+                let txid = hex::encode(Sha256::digest(address.as_bytes()));
+                [
+                    AddressUtxo {
+                        address: address.clone(),
+                        txid: txid.clone(),
+                        vout: 0,
+                        value: MOCK_BITCOIN_CONFIRMED_SATOSHIS,
+                        block_height: Some(MOCK_BITCOIN_BLOCK_HEIGHT - 100),
+                    },
+                    AddressUtxo {
+                        address: address.clone(),
+                        txid,
+                        vout: 1,
+                        value: MOCK_BITCOIN_UNCONFIRMED_SATOSHIS,
+                        block_height: None,
+                    },
+                ]
+            })
+            .collect())
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>> {
+        Ok(Some(f64::from((MOCK_BITCOIN_NEXT_BLOCK_FEE_RATE / conf_target.max(1)).max(1))))
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        Ok(MempoolInfo { size: 1_500, bytes: 750_000, min_fee_rate: 1.0, min_relay_fee_rate: 1.0 })
+    }
+}
+
+/// Solana chain answering from canned data
+///
+/// Every account exists, is owned by the system program and holds
+/// [`MOCK_SOLANA_LAMPORTS`]. Every transaction is accepted and moves one
+/// confirmation level each time its status is read: processed, confirmed,
+/// then finalized.
+#[derive(Debug, Default)]
+pub struct MockSolanaChain {
+    status_reads: Mutex<HashMap<Signature, usize>>,
+}
+
+#[async_trait]
+impl SolanaChain for MockSolanaChain {
+    fn commitment(&self) -> CommitmentConfig { CommitmentConfig::confirmed() }
+
+    async fn get_slot(&self) -> Result<u64> { Ok(MOCK_SOLANA_SLOT) }
+
+    async fn get_balance(&self, _pubkey: &Pubkey) -> Result<AtSlot<u64>> {
+        Ok(AtSlot { slot: MOCK_SOLANA_SLOT, value: MOCK_SOLANA_LAMPORTS })
+    }
+
+    async fn get_account(&self, _pubkey: &Pubkey) -> Result<AtSlot<Option<Account>>> {
+        let account = Account {
+            lamports: MOCK_SOLANA_LAMPORTS,
+            data: Vec::new(),
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        Ok(AtSlot { slot: MOCK_SOLANA_SLOT, value: Some(account) })
+    }
+
+    async fn send_transaction(&self, _transaction: &str) -> std::result::Result<(), ClientError> {
+        Ok(())
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>> {
+        let reads = {
+            let mut status_reads = self.status_reads.lock().unwrap_or_else(PoisonError::into_inner);
+            let reads = status_reads.entry(*signature).or_default();
+            *reads += 1;
+            *reads
+        };
+
+        let confirmation_status = match reads {
+            1 => TransactionConfirmationStatus::Processed,
+            2 => TransactionConfirmationStatus::Confirmed,
+            _ => TransactionConfirmationStatus::Finalized,
+        };
+
+        Ok(Some(SignatureStatus { slot: MOCK_SOLANA_SLOT, err: None, confirmation_status }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_bitcoin_chain_is_deterministic() {
+        let chain = MockBitcoinChain;
+        let addresses = vec!["bcrt1qexample".to_string()];
+
+        assert_eq!(chain.get_block_count().await.unwrap(), MOCK_BITCOIN_BLOCK_HEIGHT);
+        let utxos = chain.list_utxos(&addresses).await.unwrap();
+        assert_eq!(utxos, chain.list_utxos(&addresses).await.unwrap());
+        assert_eq!(utxos.len(), 2);
+        assert!(utxos[0].block_height.is_some());
+        assert!(utxos[1].block_height.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_solana_chain_confirms_transactions() {
+        let chain = MockSolanaChain::default();
+        let signature = Signature::from([7; 64]);
+
+        let statuses = [
+            TransactionConfirmationStatus::Processed,
+            TransactionConfirmationStatus::Confirmed,
+            TransactionConfirmationStatus::Finalized,
+            TransactionConfirmationStatus::Finalized,
+        ];
+        for expected in statuses {
+            let status = chain.get_signature_status(&signature).await.unwrap().unwrap();
+            assert_eq!(status.confirmation_status, expected);
+            assert_eq!(status.err, None);
+        }
+
+        // every transaction is scripted on its own
+        let status = chain.get_signature_status(&Signature::from([8; 64])).await.unwrap().unwrap();
+        assert_eq!(status.confirmation_status, TransactionConfirmationStatus::Processed);
+    }
+}
@@ -0,0 +1,134 @@
+//! Access to the Bitcoin and Solana chains.
+//!
+//! The services read the chains through [`BitcoinChain`] and [`SolanaChain`],
+//! backed by the RPC endpoints of the configuration, or by [`MockBitcoinChain`]
+//! and [`MockSolanaChain`] when the chain is configured with `mock: true`, so
+//! that the server boots without a regtest or devnet node. The responses of
+//! the RPC endpoints can be recorded with [`RecordingBitcoinChain`] and
+//! [`RecordingSolanaChain`] and served again by [`ReplayedBitcoinChain`] and
+//! [`ReplayedSolanaChain`], see [`crate::cassette`].
+
+mod bitcoin;
+mod cassette;
+mod circuit_breaking;
+mod instrumented;
+mod mock;
+mod solana;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+};
+use solana_transaction_status_client_types::TransactionConfirmationStatus;
+
+pub use self::{
+    bitcoin::RpcBitcoinChain,
+    cassette::{
+        RecordingBitcoinChain, RecordingSolanaChain, ReplayedBitcoinChain, ReplayedSolanaChain,
+    },
+    circuit_breaking::CircuitBreakingBitcoinChain,
+    instrumented::InstrumentedBitcoinChain,
+    mock::{MockBitcoinChain, MockSolanaChain},
+    solana::RpcSolanaChain,
+};
+use crate::service::error::Result;
+
+#[async_trait]
+pub trait BitcoinChain: Send + Sync {
+    /// Where the chain is read from, for logs
+    fn describe(&self) -> String;
+
+    /// Height of the latest block
+    async fn get_block_count(&self) -> Result<u64>;
+
+    /// Unspent outputs of `addresses`, including the ones still in the
+    /// mempool
+    async fn list_utxos(&self, addresses: &[String]) -> Result<Vec<AddressUtxo>>;
+
+    /// Fee rate for a transaction to confirm within `conf_target` blocks, in
+    /// satoshis per virtual byte, `None` if the node lacks the data to
+    /// estimate it
+    async fn estimate_smart_fee(&self, conf_target: u16) -> Result<Option<f64>>;
+
+    /// Size and minimum fee rates of the mempool
+    async fn get_mempool_info(&self) -> Result<MempoolInfo>;
+}
+
+#[async_trait]
+pub trait SolanaChain: Send + Sync {
+    /// Commitment level of every read
+    fn commitment(&self) -> CommitmentConfig;
+
+    /// Slot the chain has reached at the commitment level
+    async fn get_slot(&self) -> Result<u64>;
+
+    /// Balance in lamports, zero if the account does not exist
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<AtSlot<u64>>;
+
+    /// Account, `None` if it does not exist
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<AtSlot<Option<Account>>>;
+
+    /// Send a signed transaction in base64 wire format as it is
+    ///
+    /// # Errors
+    ///
+    /// Returns the rejection of the RPC endpoint, which is recorded on the
+    /// transaction instead of failing the request.
+    async fn send_transaction(&self, transaction: &str) -> std::result::Result<(), ClientError>;
+
+    /// Status of a transaction, `None` while the chain does not know it
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>>;
+}
+
+/// Unspent output of a Bitcoin address
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddressUtxo {
+    pub address: String,
+
+    pub txid: String,
+
+    pub vout: u32,
+
+    /// Amount in satoshis
+    pub value: u64,
+
+    /// Block containing the output, `None` while it is in the mempool
+    pub block_height: Option<u64>,
+}
+
+/// Mempool of the Bitcoin node, fee rates in satoshis per virtual byte
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolInfo {
+    /// Number of transactions
+    pub size: u64,
+
+    /// Sum of the virtual sizes of the transactions
+    pub bytes: u64,
+
+    /// Fee rate below which transactions are not accepted into the mempool
+    pub min_fee_rate: f64,
+
+    /// Fee rate below which transactions are not relayed
+    pub min_relay_fee_rate: f64,
+}
+
+/// Value read from Solana with the slot it was read at
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AtSlot<T> {
+    pub slot: u64,
+
+    pub value: T,
+}
+
+/// On-chain status of a Solana transaction
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignatureStatus {
+    pub slot: u64,
+
+    /// Why the transaction failed, `None` if it succeeded
+    pub err: Option<String>,
+
+    pub confirmation_status: TransactionConfirmationStatus,
+}
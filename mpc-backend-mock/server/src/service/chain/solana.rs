@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use snafu::ResultExt;
+use solana_client::{
+    client_error::ClientError, nonblocking::rpc_client::RpcClient, rpc_request::RpcRequest,
+};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+};
+
+use super::{AtSlot, SignatureStatus, SolanaChain};
+use crate::service::error::{self, Result};
+
+/// [`SolanaChain`] reading from the Solana RPC endpoint with the commitment
+/// level of the client
+#[derive(Clone)]
+pub struct RpcSolanaChain {
+    rpc_client: Arc<RpcClient>,
+}
+
+impl RpcSolanaChain {
+    #[inline]
+    #[must_use]
+    pub const fn new(rpc_client: Arc<RpcClient>) -> Self { Self { rpc_client } }
+}
+
+#[async_trait]
+impl SolanaChain for RpcSolanaChain {
+    fn commitment(&self) -> CommitmentConfig { self.rpc_client.commitment() }
+
+    async fn get_slot(&self) -> Result<u64> {
+        self.rpc_client
+            .get_slot_with_commitment(self.rpc_client.commitment())
+            .await
+            .context(error::GetSolanaSlotSnafu)
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<AtSlot<u64>> {
+        let response = self
+            .rpc_client
+            .get_balance_with_commitment(pubkey, self.rpc_client.commitment())
+            .await
+            .context(error::GetSolanaBalanceSnafu { pubkey: *pubkey })?;
+
+        Ok(AtSlot { slot: response.context.slot, value: response.value })
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<AtSlot<Option<Account>>> {
+        let response = self
+            .rpc_client
+            .get_account_with_commitment(pubkey, self.rpc_client.commitment())
+            .await
+            .context(error::GetSolanaAccountSnafu { pubkey: *pubkey })?;
+
+        Ok(AtSlot { slot: response.context.slot, value: response.value })
+    }
+
+    async fn send_transaction(&self, transaction: &str) -> std::result::Result<(), ClientError> {
+        let commitment = self.rpc_client.commitment().commitment;
+        let params =
+            json!([transaction, { "encoding": "base64", "preflightCommitment": commitment }]);
+
+        self.rpc_client.send::<String>(RpcRequest::SendTransaction, params).await.map(drop)
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>> {
+        let response = self
+            .rpc_client
+            .get_signature_statuses(&[*signature])
+            .await
+            .context(error::GetSolanaSignatureStatusSnafu { signature: *signature })?;
+
+        Ok(response.value.into_iter().next().flatten().map(|status| SignatureStatus {
+            slot: status.slot,
+            confirmation_status: status.confirmation_status(),
+            err: status.err.map(|err| err.to_string()),
+        }))
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::LazyLock;
+
+use crate::entity::{ApiChangelog, ApiRelease};
+
+/// Changelog of the HTTP API, newest release first
+const API_CHANGELOG: &str = include_str!("../../api-changelog.yaml");
+
+static RELEASES: LazyLock<Vec<ApiRelease>> = LazyLock::new(|| {
+    serde_yaml::from_str(API_CHANGELOG).expect("built-in API changelog is valid; qed")
+});
+
+/// Changelog of the HTTP API embedded in this build
+#[must_use]
+pub fn api_changelog(version: &str) -> ApiChangelog {
+    ApiChangelog { version: version.to_string(), releases: RELEASES.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use utoipa::OpenApi;
+
+    use super::*;
+    use crate::{entity::ApiChangeKind, ApiDoc};
+
+    #[test]
+    fn test_every_operation_is_in_changelog() {
+        // replay the releases oldest first
+        let mut operations = BTreeSet::new();
+        for change in RELEASES.iter().rev().flat_map(|release| &release.changes) {
+            let operation = format!("{} {}", change.method, change.path);
+            match change.kind {
+                ApiChangeKind::Added => drop(operations.insert(operation)),
+                ApiChangeKind::Removed => drop(operations.remove(&operation)),
+                ApiChangeKind::Changed | ApiChangeKind::Deprecated => {
+                    assert!(operations.contains(&operation), "{operation} was never added");
+                }
+            }
+        }
+
+        let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = document["paths"].as_object().unwrap();
+        assert!(!paths.is_empty());
+        for (path, item) in paths {
+            for method in ["get", "put", "post", "delete", "patch"] {
+                if item.get(method).is_some() {
+                    let operation = format!("{} {path}", method.to_uppercase());
+                    assert!(operations.contains(&operation), "{operation} is not in the changelog");
+                }
+            }
+        }
+    }
+}
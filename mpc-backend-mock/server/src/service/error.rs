@@ -1,10 +1,18 @@
+use std::time::Duration;
+
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
 };
 use indexmap::IndexMap;
 use snafu::Snafu;
-use zeus_axum::{json_response, response, response::EncapsulatedJsonError};
+use solana_client::client_error::ClientErrorKind;
+use zeus_axum::{
+    json_response, response,
+    response::{EncapsulatedJsonError, ErrorCode},
+};
+
+use crate::circuit_breaker::BreakerOpen;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -77,8 +85,20 @@ pub enum Error {
     #[snafu(display("Fail to get user by id, error: {source}"))]
     GetUserById { source: sqlx::Error },
 
-    #[snafu(display("Fail to delete user by id, error: {source}"))]
-    DeleteUserById { source: sqlx::Error },
+    #[snafu(display("Fail to soft delete user by id, error: {source}"))]
+    SoftDeleteUserById { source: sqlx::Error },
+
+    #[snafu(display("Fail to restore user by id, error: {source}"))]
+    RestoreUserById { source: sqlx::Error },
+
+    #[snafu(display("Fail to activate user by id, error: {source}"))]
+    ActivateUserById { source: sqlx::Error },
+
+    #[snafu(display("Fail to update user email, error: {source}"))]
+    UpdateUserEmail { source: sqlx::Error },
+
+    #[snafu(display("User is not deleted: {user_id}"))]
+    UserNotDeleted { user_id: uuid::Uuid },
 
     #[snafu(display("Fail to get user by email, error: {source}"))]
     GetUserByEmail { source: sqlx::Error },
@@ -86,9 +106,360 @@ pub enum Error {
     #[snafu(display("Fail to get user by keycloak id, error: {source}"))]
     GetUserByKeycloakId { source: sqlx::Error },
 
+    #[snafu(display("Fail to update user, error: {source}"))]
+    UpdateUser { source: sqlx::Error },
+
+    #[snafu(display("Fail to get user profile, error: {source}"))]
+    GetUserProfile { source: sqlx::Error },
+
+    #[snafu(display("Invalid user profile: {reason}"))]
+    InvalidUserProfile { reason: &'static str },
+
+    #[snafu(display("Fail to get user preferences, error: {source}"))]
+    GetUserPreferences { source: sqlx::Error },
+
+    #[snafu(display("Fail to update user preferences, error: {source}"))]
+    UpdateUserPreferences { source: sqlx::Error },
+
+    #[snafu(display("Invalid user preferences: {reason}"))]
+    InvalidUserPreferences { reason: &'static str },
+
+    #[snafu(display("Invalid fixture of user {email}: {reason}"))]
+    InvalidFixture { email: String, reason: String },
+
+    #[snafu(display("Fail to list users, error: {source}"))]
+    ListUsers { source: sqlx::Error },
+
+    #[snafu(display("Fail to count users, error: {source}"))]
+    CountUsers { source: sqlx::Error },
+
+    #[snafu(display("Fail to list users by email pattern, error: {source}"))]
+    ListUsersByEmailPattern { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete users by ids, error: {source}"))]
+    DeleteUsersByIds { source: sqlx::Error },
+
+    #[snafu(display("Fail to list users by Keycloak ids, error: {source}"))]
+    ListUsersByKeycloakIds { source: sqlx::Error },
+
+    #[snafu(display("Fail to export users, error: {source}"))]
+    ExportUsers { source: sqlx::Error },
+
+    #[snafu(display("Fail to write user export as CSV, error: {source}"))]
+    WriteUserCsv { source: csv::Error },
+
+    #[snafu(display("Fail to write user export as JSON, error: {source}"))]
+    WriteUserJson { source: serde_json::Error },
+
+    #[snafu(display("Fail to insert wallet, error: {source}"))]
+    InsertWallet { source: sqlx::Error },
+
+    #[snafu(display("Fail to get wallet by id, error: {source}"))]
+    GetWalletById { source: sqlx::Error },
+
+    #[snafu(display("Fail to get wallet by address, error: {source}"))]
+    GetWalletByAddress { source: sqlx::Error },
+
+    #[snafu(display("Fail to list wallets, error: {source}"))]
+    ListWallets { source: sqlx::Error },
+
+    #[snafu(display("Wallet not found: {wallet_id}"))]
+    WalletNotFound { wallet_id: uuid::Uuid },
+
+    #[snafu(display(
+        "Invalid date range from {from} to {to}, at most {max_days} days are supported"
+    ))]
+    InvalidDateRange { from: chrono::NaiveDate, to: chrono::NaiveDate, max_days: i64 },
+
+    #[snafu(display("Fail to upsert wallet balance snapshots, error: {source}"))]
+    UpsertWalletBalanceSnapshots { source: sqlx::Error },
+
+    #[snafu(display("Fail to list wallet balance snapshots, error: {source}"))]
+    ListWalletBalanceSnapshots { source: sqlx::Error },
+
+    #[snafu(display("Fail to insert deposit, error: {source}"))]
+    InsertDeposit { source: sqlx::Error },
+
+    #[snafu(display("Fail to get deposit by id, error: {source}"))]
+    GetDepositById { source: sqlx::Error },
+
+    #[snafu(display("Fail to list deposits, error: {source}"))]
+    ListDeposits { source: sqlx::Error },
+
+    #[snafu(display("Fail to update deposit status, error: {source}"))]
+    UpdateDepositStatus { source: sqlx::Error },
+
+    #[snafu(display("Fail to insert withdrawal, error: {source}"))]
+    InsertWithdrawal { source: sqlx::Error },
+
+    #[snafu(display("Fail to get withdrawal by id, error: {source}"))]
+    GetWithdrawalById { source: sqlx::Error },
+
+    #[snafu(display("Fail to list withdrawals, error: {source}"))]
+    ListWithdrawals { source: sqlx::Error },
+
+    #[snafu(display("Fail to update withdrawal status, error: {source}"))]
+    UpdateWithdrawalStatus { source: sqlx::Error },
+
+    #[snafu(display("Fail to insert audit log, error: {source}"))]
+    InsertAuditLog { source: sqlx::Error },
+
+    #[snafu(display("Fail to list audit logs, error: {source}"))]
+    ListAuditLogs { source: sqlx::Error },
+
+    #[snafu(display("Fail to count audit logs, error: {source}"))]
+    CountAuditLogs { source: sqlx::Error },
+
+    #[snafu(display("Fail to insert notification, error: {source}"))]
+    InsertNotification { source: sqlx::Error },
+
+    #[snafu(display("Fail to claim due notifications, error: {source}"))]
+    ClaimDueNotifications { source: sqlx::Error },
+
+    #[snafu(display("Fail to update notification, error: {source}"))]
+    UpdateNotification { source: sqlx::Error },
+
+    #[snafu(display("Fail to list notifications, error: {source}"))]
+    ListNotifications { source: sqlx::Error },
+
+    #[snafu(display("Fail to count notifications, error: {source}"))]
+    CountNotifications { source: sqlx::Error },
+
+    #[snafu(display("Fail to list the preferences of notification recipients, error: {source}"))]
+    ListRecipientPreferences { source: sqlx::Error },
+
+    #[snafu(display("Fail to serialize notification, error: {source}"))]
+    SerializeNotification { source: serde_json::Error },
+
+    #[snafu(display("Fail to insert event, error: {source}"))]
+    InsertEvent { source: sqlx::Error },
+
+    #[snafu(display("Fail to serialize event, error: {source}"))]
+    SerializeEvent { source: serde_json::Error },
+
+    #[snafu(display("Fail to lock event publisher offset, error: {source}"))]
+    LockPublisherOffset { source: sqlx::Error },
+
+    #[snafu(display("Fail to list events, error: {source}"))]
+    ListEvents { source: sqlx::Error },
+
+    #[snafu(display("Fail to update event publisher offset, error: {source}"))]
+    UpdatePublisherOffset { source: sqlx::Error },
+
+    #[snafu(display("Fail to publish event {event_id}, error: {source}"))]
+    PublishEvent { event_id: uuid::Uuid, source: events::Error },
+
+    #[snafu(display("Fail to insert captured notification, error: {source}"))]
+    InsertCapturedNotification { source: sqlx::Error },
+
+    #[snafu(display("Fail to list captured notifications, error: {source}"))]
+    ListCapturedNotifications { source: sqlx::Error },
+
+    #[snafu(display("Fail to count captured notifications, error: {source}"))]
+    CountCapturedNotifications { source: sqlx::Error },
+
+    #[snafu(display("Bitcoin indexer endpoint is not configured"))]
+    BitcoinIndexerNotConfigured,
+
+    #[snafu(display("Fail to get Bitcoin block count, error: {source}"))]
+    GetBitcoinBlockCount { source: eris_bitcoin_rpc_client::Error },
+
+    #[snafu(display(
+        "Fail to estimate Bitcoin fee rate for {conf_target} blocks, error: {source}"
+    ))]
+    EstimateBitcoinSmartFee { conf_target: u16, source: eris_bitcoin_rpc_client::Error },
+
+    #[snafu(display("Fail to get Bitcoin mempool info, error: {source}"))]
+    GetBitcoinMempoolInfo { source: eris_bitcoin_rpc_client::Error },
+
+    #[snafu(display(
+        "Fail to request UTXOs of `{address}` from Bitcoin indexer, error: {source}"
+    ))]
+    RequestBitcoinIndexer { address: String, source: reqwest::Error },
+
+    #[snafu(display("Bitcoin balance overflows"))]
+    BitcoinBalanceOverflow,
+
+    #[snafu(display("Fail to get Solana balance of `{pubkey}`, error: {source}"))]
+    GetSolanaBalance {
+        pubkey: solana_sdk::pubkey::Pubkey,
+        #[snafu(source(from(solana_client::client_error::ClientError, Box::new)))]
+        source: Box<solana_client::client_error::ClientError>,
+    },
+
+    #[snafu(display("Fail to get Solana account `{pubkey}`, error: {source}"))]
+    GetSolanaAccount {
+        pubkey: solana_sdk::pubkey::Pubkey,
+        #[snafu(source(from(solana_client::client_error::ClientError, Box::new)))]
+        source: Box<solana_client::client_error::ClientError>,
+    },
+
+    #[snafu(display("Fail to get Solana slot, error: {source}"))]
+    GetSolanaSlot {
+        #[snafu(source(from(solana_client::client_error::ClientError, Box::new)))]
+        source: Box<solana_client::client_error::ClientError>,
+    },
+
+    #[snafu(display("Solana account not found: {pubkey}"))]
+    SolanaAccountNotFound { pubkey: solana_sdk::pubkey::Pubkey },
+
+    #[snafu(display("Fail to decode base64 transaction, error: {source}"))]
+    DecodeTransaction { source: base64::DecodeError },
+
+    #[snafu(display("Transaction is not signed"))]
+    MissingTransactionSignature,
+
+    #[snafu(display("Transaction was already submitted: {signature}"))]
+    TransactionAlreadySubmitted { signature: String },
+
+    #[snafu(display("Transaction not found: {transaction_id}"))]
+    TransactionNotFound { transaction_id: uuid::Uuid },
+
+    #[snafu(display("Fail to insert transaction, error: {source}"))]
+    InsertTransaction { source: sqlx::Error },
+
+    #[snafu(display("Fail to get transaction by id, error: {source}"))]
+    GetTransactionById { source: sqlx::Error },
+
+    #[snafu(display("Fail to update transaction status, error: {source}"))]
+    UpdateTransactionStatus { source: sqlx::Error },
+
+    #[snafu(display("Fail to get status of Solana transaction `{signature}`, error: {source}"))]
+    GetSolanaSignatureStatus {
+        signature: solana_sdk::signature::Signature,
+        #[snafu(source(from(solana_client::client_error::ClientError, Box::new)))]
+        source: Box<solana_client::client_error::ClientError>,
+    },
+
+    #[snafu(display("Simulated key generation failure"))]
+    KeyGenerationFailed,
+
+    #[snafu(display("Key not found: {key_id}"))]
+    KeyNotFound { key_id: uuid::Uuid },
+
+    #[snafu(display("Fail to insert key, error: {source}"))]
+    InsertKey { source: sqlx::Error },
+
+    #[snafu(display("Fail to get key by id, error: {source}"))]
+    GetKeyById { source: sqlx::Error },
+
+    #[snafu(display("Invalid webhook: {reason}"))]
+    InvalidWebhook { reason: &'static str },
+
+    #[snafu(display("Webhook not found: {webhook_id}"))]
+    WebhookNotFound { webhook_id: uuid::Uuid },
+
+    #[snafu(display("Fail to insert webhook, error: {source}"))]
+    InsertWebhook { source: sqlx::Error },
+
+    #[snafu(display("Fail to get webhook by id, error: {source}"))]
+    GetWebhookById { source: sqlx::Error },
+
+    #[snafu(display("Fail to queue webhook deliveries, error: {source}"))]
+    InsertWebhookDeliveries { source: sqlx::Error },
+
+    #[snafu(display("Fail to claim due webhook deliveries, error: {source}"))]
+    ClaimDueWebhookDeliveries { source: sqlx::Error },
+
+    #[snafu(display("Fail to update webhook delivery, error: {source}"))]
+    UpdateWebhookDelivery { source: sqlx::Error },
+
+    #[snafu(display("Fail to list webhook deliveries, error: {source}"))]
+    ListWebhookDeliveries { source: sqlx::Error },
+
+    #[snafu(display("Fail to count webhook deliveries, error: {source}"))]
+    CountWebhookDeliveries { source: sqlx::Error },
+
+    #[snafu(display("Fail to serialize webhook event, error: {source}"))]
+    SerializeWebhookEvent { source: serde_json::Error },
+
+    #[snafu(display("Invalid signing session: {reason}"))]
+    InvalidSigningSession { reason: &'static str },
+
+    #[snafu(display("Signing session not found: {session_id}"))]
+    SigningSessionNotFound { session_id: uuid::Uuid },
+
+    #[snafu(display("Fail to insert signing session, error: {source}"))]
+    InsertSigningSession { source: sqlx::Error },
+
+    #[snafu(display("Fail to get signing session by id, error: {source}"))]
+    GetSigningSessionById { source: sqlx::Error },
+
+    #[snafu(display("Fail to update signing session status, error: {source}"))]
+    UpdateSigningSessionStatus { source: sqlx::Error },
+
+    #[snafu(display("Activation token is invalid, expired or already used"))]
+    InvalidActivationToken,
+
+    #[snafu(display("Fail to insert activation token, error: {source}"))]
+    InsertActivationToken { source: sqlx::Error },
+
+    #[snafu(display("Fail to consume activation token, error: {source}"))]
+    ConsumeActivationToken { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete expired activation tokens, error: {source}"))]
+    DeleteExpiredActivationTokens { source: sqlx::Error },
+
+    #[snafu(display("Invalid email change: {reason}"))]
+    InvalidEmailChange { reason: &'static str },
+
+    #[snafu(display("Email change token is invalid, expired or already used"))]
+    InvalidEmailChangeToken,
+
+    #[snafu(display("Fail to insert email change request, error: {source}"))]
+    InsertEmailChangeRequest { source: sqlx::Error },
+
+    #[snafu(display("Fail to consume email change request, error: {source}"))]
+    ConsumeEmailChangeRequest { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete expired email change requests, error: {source}"))]
+    DeleteExpiredEmailChangeRequests { source: sqlx::Error },
+
+    #[snafu(display("Session cookies are not enabled"))]
+    SessionsDisabled,
+
+    #[snafu(display("Fail to insert session, error: {source}"))]
+    InsertSession { source: sqlx::Error },
+
+    #[snafu(display("Fail to get session, error: {source}"))]
+    GetSession { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete session, error: {source}"))]
+    DeleteSession { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete expired sessions, error: {source}"))]
+    DeleteExpiredSessions { source: sqlx::Error },
+
+    #[snafu(display("Account is locked after too many failed logins"))]
+    AccountLocked { email: String },
+
+    #[snafu(display("No failed logins of `{email}`"))]
+    LoginAttemptNotFound { email: String },
+
+    #[snafu(display("Fail to get failed logins, error: {source}"))]
+    GetLoginAttempt { source: sqlx::Error },
+
+    #[snafu(display("Fail to record failed login, error: {source}"))]
+    RecordFailedLogin { source: sqlx::Error },
+
+    #[snafu(display("Fail to lock login, error: {source}"))]
+    LockLogin { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete failed logins, error: {source}"))]
+    DeleteLoginAttempt { source: sqlx::Error },
+
+    #[snafu(display("Fail to list locked logins, error: {source}"))]
+    ListLockedLogins { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete expired failed logins, error: {source}"))]
+    DeleteExpiredLoginAttempts { source: sqlx::Error },
+
     #[snafu(display("Invalid email format: {email}"))]
     InvalidEmail { email: String },
 
+    #[snafu(display("Invalid email pattern, it must contain `@`: {pattern}"))]
+    InvalidEmailPattern { pattern: String },
+
     #[snafu(display("Failed to authenticate with Keycloak, error: {source}"))]
     AuthenticateKeycloak { source: keycloak::KeycloakError },
 
@@ -98,14 +469,410 @@ pub enum Error {
     #[snafu(display("Failed to create user in Keycloak, error: {source}"))]
     CreateKeycloakUser { source: keycloak::KeycloakError },
 
+    #[snafu(display("Failed to update user in Keycloak, error: {source}"))]
+    UpdateKeycloakUser { source: keycloak::KeycloakError },
+
     #[snafu(display("Failed to delete user in Keycloak, error: {source}"))]
     DeleteKeycloakUser { source: keycloak::KeycloakError },
 
+    #[snafu(display("Failed to list users in Keycloak, error: {source}"))]
+    ListKeycloakUsers { source: keycloak::KeycloakError },
+
+    #[snafu(display("Failed to set password in Keycloak, error: {source}"))]
+    SetKeycloakPassword { source: keycloak::KeycloakError },
+
+    #[snafu(display("Password rejected by the Keycloak password policy"))]
+    PasswordRejected { source: keycloak::KeycloakError },
+
     #[snafu(display("User already exists in Keycloak: {email}"))]
     UserExistsInKeycloak { email: String },
 
     #[snafu(display("Failed to retrieve created Keycloak user: {email}"))]
     KeycloakUserNotFound { email: String },
+
+    #[snafu(display("User not found in the user directory: {user_id}"))]
+    DirectoryUserNotFound { user_id: uuid::Uuid },
+
+    #[snafu(display("Invalid credentials for user: {email}"))]
+    InvalidCredentials { email: String },
+
+    #[snafu(display("Invalid or expired refresh token"))]
+    InvalidRefreshToken,
+
+    #[snafu(display("Failed to request token from Keycloak, error: {source}"))]
+    RequestKeycloakToken { source: crate::keycloak_client::error::Error },
+
+    #[snafu(display("Users of the realm `{realm}` cannot be impersonated"))]
+    ImpersonationUnsupported { realm: String },
+
+    #[snafu(display("Realm `{realm}` is not one of the configured realms"))]
+    UnknownRealm { realm: String },
+
+    #[snafu(display("Document is not an OpenAPI document, `openapi` and `paths` are required"))]
+    InvalidOpenApiDocument,
+
+    #[snafu(display("No OpenAPI baseline was uploaded"))]
+    OpenApiBaselineNotFound,
+
+    #[snafu(display("Fail to insert OpenAPI baseline, error: {source}"))]
+    InsertOpenApiBaseline { source: sqlx::Error },
+
+    #[snafu(display("Fail to get latest OpenAPI baseline, error: {source}"))]
+    GetLatestOpenApiBaseline { source: sqlx::Error },
+
+    #[snafu(display("Failed to serialize OpenAPI document, error: {source}"))]
+    SerializeOpenApiDocument { source: serde_json::Error },
+
+    #[snafu(display("Invalid annotation, {reason}"))]
+    InvalidAnnotation { reason: &'static str },
+
+    #[snafu(display("Annotation not found: {annotation_id}"))]
+    AnnotationNotFound { annotation_id: uuid::Uuid },
+
+    #[snafu(display("Fail to insert annotation, error: {source}"))]
+    InsertAnnotation { source: sqlx::Error },
+
+    #[snafu(display("Fail to list annotations, error: {source}"))]
+    ListAnnotations { source: sqlx::Error },
+
+    #[snafu(display("Fail to delete annotation, error: {source}"))]
+    DeleteAnnotation { source: sqlx::Error },
+
+    #[snafu(display("{dependency} is unavailable, try again later"))]
+    DependencyUnavailable { dependency: &'static str, retry_after: Duration },
+
+    #[snafu(display("No response of `{request}` is recorded in the {cassette} cassette"))]
+    NoRecordedResponse { cassette: &'static str, request: String },
+
+    #[snafu(display(
+        "Fail to decode the response of `{request}` recorded in the {cassette} cassette, error: \
+         {source}"
+    ))]
+    DecodeRecordedResponse { cassette: &'static str, request: String, source: serde_json::Error },
+}
+
+impl Error {
+    /// Whether the error means that a dependency is failing, rather than that
+    /// it rejected the request, as counted by its circuit breaker
+    #[must_use]
+    pub const fn is_dependency_failure(&self) -> bool {
+        match self {
+            Self::AuthenticateKeycloak { source }
+            | Self::GetKeycloakUser { source }
+            | Self::CreateKeycloakUser { source }
+            | Self::UpdateKeycloakUser { source }
+            | Self::DeleteKeycloakUser { source }
+            | Self::ListKeycloakUsers { source }
+            | Self::SetKeycloakPassword { source } => {
+                !matches!(source, keycloak::KeycloakError::HttpFailure { status: 400..=499, .. })
+            }
+            Self::RequestKeycloakToken { source } => match source {
+                crate::keycloak_client::error::Error::TokenRejected { status, .. } => {
+                    *status >= 500
+                }
+                _ => true,
+            },
+            Self::GetBitcoinBlockCount { .. }
+            | Self::EstimateBitcoinSmartFee { .. }
+            | Self::GetBitcoinMempoolInfo { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Stable machine-readable code of the error by its cause, returned as
+    /// `domain_code` in the error body
+    ///
+    /// Unlike [`ErrorCode::error_code`], which reports every failure of the
+    /// database, Keycloak or the RPC nodes as `INTERNAL_ERROR`, it tells them
+    /// apart, e.g. `KEYCLOAK_UNAVAILABLE`. The match is exhaustive so that a
+    /// new variant cannot be added without its code.
+    #[must_use]
+    pub const fn domain_code(&self) -> &'static str {
+        match self {
+            Self::DuplicateFileHash { .. } => "DUPLICATE_FILE_HASH",
+            Self::UserAlreadyExists { .. } => "USER_ALREADY_EXISTS",
+            Self::UserExistsInKeycloak { .. } => "USER_EXISTS_IN_KEYCLOAK",
+            Self::UserNotDeleted { .. } => "USER_NOT_DELETED",
+            Self::TransactionAlreadySubmitted { .. } => "TRANSACTION_ALREADY_SUBMITTED",
+            Self::UserNotFound { .. } => "USER_NOT_FOUND",
+            Self::KeycloakUserNotFound { .. } => "KEYCLOAK_USER_NOT_FOUND",
+            Self::SolanaAccountNotFound { .. } => "SOLANA_ACCOUNT_NOT_FOUND",
+            Self::TransactionNotFound { .. } => "TRANSACTION_NOT_FOUND",
+            Self::SigningSessionNotFound { .. } => "SIGNING_SESSION_NOT_FOUND",
+            Self::KeyNotFound { .. } => "KEY_NOT_FOUND",
+            Self::WebhookNotFound { .. } => "WEBHOOK_NOT_FOUND",
+            Self::WalletNotFound { .. } => "WALLET_NOT_FOUND",
+            Self::InvalidCredentials { .. } => "INVALID_CREDENTIALS",
+            Self::InvalidRefreshToken => "INVALID_REFRESH_TOKEN",
+            Self::ImpersonationUnsupported { .. } => "IMPERSONATION_UNSUPPORTED",
+            Self::UnknownRealm { .. } => "UNKNOWN_REALM",
+            Self::BitcoinIndexerNotConfigured => "BITCOIN_INDEXER_NOT_CONFIGURED",
+            Self::KeyGenerationFailed => "KEY_GENERATION_FAILED",
+            Self::NoRecordedResponse { .. } => "NO_RECORDED_RESPONSE",
+            Self::DecodeRecordedResponse { .. } => "DECODE_RECORDED_RESPONSE",
+            Self::InvalidEmail { .. } => "INVALID_EMAIL",
+            Self::InvalidEmailPattern { .. } => "INVALID_EMAIL_PATTERN",
+            Self::DecodeTransaction { .. } => "INVALID_TRANSACTION_ENCODING",
+            Self::MissingTransactionSignature => "MISSING_TRANSACTION_SIGNATURE",
+            Self::InvalidSigningSession { .. } => "INVALID_SIGNING_SESSION",
+            Self::InvalidActivationToken => "INVALID_ACTIVATION_TOKEN",
+            Self::InvalidEmailChange { .. } => "INVALID_EMAIL_CHANGE",
+            Self::InvalidEmailChangeToken => "INVALID_EMAIL_CHANGE_TOKEN",
+            Self::SessionsDisabled => "SESSIONS_DISABLED",
+            Self::AccountLocked { .. } => "ACCOUNT_LOCKED",
+            Self::LoginAttemptNotFound { .. } => "LOGIN_ATTEMPT_NOT_FOUND",
+            Self::InvalidOpenApiDocument => "INVALID_OPENAPI_DOCUMENT",
+            Self::OpenApiBaselineNotFound => "OPENAPI_BASELINE_NOT_FOUND",
+            Self::InvalidDateRange { .. } => "INVALID_DATE_RANGE",
+            Self::InvalidAnnotation { .. } => "INVALID_ANNOTATION",
+            Self::InvalidUserProfile { .. } => "INVALID_USER_PROFILE",
+            Self::InvalidUserPreferences { .. } => "INVALID_USER_PREFERENCES",
+            Self::InvalidWebhook { .. } => "INVALID_WEBHOOK",
+            Self::InvalidFixture { .. } => "INVALID_FIXTURE",
+            Self::PasswordRejected { .. } => "PASSWORD_REJECTED",
+            Self::AnnotationNotFound { .. } => "ANNOTATION_NOT_FOUND",
+            Self::DependencyUnavailable { .. } => "DEPENDENCY_UNAVAILABLE",
+            // failures which `error_code` reports as `INTERNAL_ERROR`, by cause
+            Self::BeginTransaction { .. }
+            | Self::CommitTransaction { .. }
+            | Self::RollBackTransaction { .. }
+            | Self::AcquireConnection { .. }
+            | Self::GetBitcoinClaimBalance { .. }
+            | Self::GetGoogleOAuthProviderByAccountId { .. }
+            | Self::GoogleOAuthProviderCreate { .. }
+            | Self::GoogleOAuthProviderUpdate { .. }
+            | Self::InsertUser { .. }
+            | Self::GetUserById { .. }
+            | Self::SoftDeleteUserById { .. }
+            | Self::RestoreUserById { .. }
+            | Self::ActivateUserById { .. }
+            | Self::UpdateUserEmail { .. }
+            | Self::GetUserByEmail { .. }
+            | Self::GetUserByKeycloakId { .. }
+            | Self::UpdateUser { .. }
+            | Self::GetUserProfile { .. }
+            | Self::GetUserPreferences { .. }
+            | Self::UpdateUserPreferences { .. }
+            | Self::ListUsers { .. }
+            | Self::CountUsers { .. }
+            | Self::ListUsersByEmailPattern { .. }
+            | Self::DeleteUsersByIds { .. }
+            | Self::ListUsersByKeycloakIds { .. }
+            | Self::ExportUsers { .. }
+            | Self::InsertWallet { .. }
+            | Self::GetWalletById { .. }
+            | Self::GetWalletByAddress { .. }
+            | Self::ListWallets { .. }
+            | Self::UpsertWalletBalanceSnapshots { .. }
+            | Self::ListWalletBalanceSnapshots { .. }
+            | Self::InsertDeposit { .. }
+            | Self::GetDepositById { .. }
+            | Self::ListDeposits { .. }
+            | Self::UpdateDepositStatus { .. }
+            | Self::InsertWithdrawal { .. }
+            | Self::GetWithdrawalById { .. }
+            | Self::ListWithdrawals { .. }
+            | Self::UpdateWithdrawalStatus { .. }
+            | Self::InsertAuditLog { .. }
+            | Self::ListAuditLogs { .. }
+            | Self::CountAuditLogs { .. }
+            | Self::InsertNotification { .. }
+            | Self::ClaimDueNotifications { .. }
+            | Self::UpdateNotification { .. }
+            | Self::ListNotifications { .. }
+            | Self::CountNotifications { .. }
+            | Self::ListRecipientPreferences { .. }
+            | Self::InsertEvent { .. }
+            | Self::LockPublisherOffset { .. }
+            | Self::ListEvents { .. }
+            | Self::UpdatePublisherOffset { .. }
+            | Self::InsertCapturedNotification { .. }
+            | Self::ListCapturedNotifications { .. }
+            | Self::CountCapturedNotifications { .. }
+            | Self::InsertTransaction { .. }
+            | Self::GetTransactionById { .. }
+            | Self::UpdateTransactionStatus { .. }
+            | Self::InsertKey { .. }
+            | Self::GetKeyById { .. }
+            | Self::InsertWebhook { .. }
+            | Self::GetWebhookById { .. }
+            | Self::InsertWebhookDeliveries { .. }
+            | Self::ClaimDueWebhookDeliveries { .. }
+            | Self::UpdateWebhookDelivery { .. }
+            | Self::ListWebhookDeliveries { .. }
+            | Self::CountWebhookDeliveries { .. }
+            | Self::InsertSigningSession { .. }
+            | Self::GetSigningSessionById { .. }
+            | Self::UpdateSigningSessionStatus { .. }
+            | Self::InsertActivationToken { .. }
+            | Self::ConsumeActivationToken { .. }
+            | Self::DeleteExpiredActivationTokens { .. }
+            | Self::InsertEmailChangeRequest { .. }
+            | Self::ConsumeEmailChangeRequest { .. }
+            | Self::DeleteExpiredEmailChangeRequests { .. }
+            | Self::InsertSession { .. }
+            | Self::GetSession { .. }
+            | Self::DeleteSession { .. }
+            | Self::DeleteExpiredSessions { .. }
+            | Self::GetLoginAttempt { .. }
+            | Self::RecordFailedLogin { .. }
+            | Self::LockLogin { .. }
+            | Self::DeleteLoginAttempt { .. }
+            | Self::ListLockedLogins { .. }
+            | Self::DeleteExpiredLoginAttempts { .. }
+            | Self::InsertOpenApiBaseline { .. }
+            | Self::GetLatestOpenApiBaseline { .. }
+            | Self::InsertAnnotation { .. }
+            | Self::ListAnnotations { .. }
+            | Self::DeleteAnnotation { .. } => "DATABASE_ERROR",
+            Self::MissingFileField => "MISSING_FILE_FIELD",
+            Self::MissingFileName => "MISSING_FILE_NAME",
+            Self::InvalidFileName { .. } => "INVALID_FILE_NAME",
+            Self::ParseMultipart { .. } | Self::ReadFile { .. } => "INVALID_MULTIPART",
+            Self::ParseCsv { .. } => "INVALID_CSV",
+            Self::ParseDate { .. } => "INVALID_DATE",
+            Self::MissingDateInCsvRow => "MISSING_CSV_DATE",
+            Self::InvalidNumericValue => "INVALID_NUMERIC_VALUE",
+            Self::WriteUserCsv { .. }
+            | Self::WriteUserJson { .. }
+            | Self::SerializeNotification { .. }
+            | Self::SerializeEvent { .. }
+            | Self::SerializeWebhookEvent { .. }
+            | Self::SerializeOpenApiDocument { .. } => "SERIALIZATION_FAILED",
+            Self::PublishEvent { .. } => "EVENT_BROKER_UNAVAILABLE",
+            Self::GetBitcoinBlockCount { .. }
+            | Self::EstimateBitcoinSmartFee { .. }
+            | Self::GetBitcoinMempoolInfo { .. } => "BITCOIN_NODE_UNAVAILABLE",
+            Self::RequestBitcoinIndexer { .. } => "BITCOIN_INDEXER_UNAVAILABLE",
+            Self::BitcoinBalanceOverflow => "BITCOIN_BALANCE_OVERFLOW",
+            Self::GetSolanaBalance { .. }
+            | Self::GetSolanaAccount { .. }
+            | Self::GetSolanaSlot { .. }
+            | Self::GetSolanaSignatureStatus { .. } => "SOLANA_NODE_UNAVAILABLE",
+            Self::AuthenticateKeycloak { .. }
+            | Self::GetKeycloakUser { .. }
+            | Self::CreateKeycloakUser { .. }
+            | Self::UpdateKeycloakUser { .. }
+            | Self::DeleteKeycloakUser { .. }
+            | Self::ListKeycloakUsers { .. }
+            | Self::SetKeycloakPassword { .. }
+            | Self::RequestKeycloakToken { .. } => {
+                // a 4xx answer rejects the request, Keycloak itself is up
+                if self.is_dependency_failure() {
+                    "KEYCLOAK_UNAVAILABLE"
+                } else {
+                    "KEYCLOAK_REJECTED"
+                }
+            }
+            Self::DirectoryUserNotFound { .. } => "DIRECTORY_USER_NOT_FOUND",
+        }
+    }
+
+    /// Whether the request may succeed when sent again unchanged, as the error
+    /// is a transient failure, e.g. a Keycloak timeout, an exhausted database
+    /// pool or a Bitcoin RPC node answering 503
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::DependencyUnavailable { .. } => true,
+            Self::RequestBitcoinIndexer { source, .. } => {
+                source.is_timeout()
+                    || source.is_connect()
+                    || source.status().is_some_and(|status| status.is_server_error())
+            }
+            Self::GetSolanaBalance { source, .. }
+            | Self::GetSolanaAccount { source, .. }
+            | Self::GetSolanaSlot { source }
+            | Self::GetSolanaSignatureStatus { source, .. } => {
+                matches!(source.kind(), ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+            }
+            _ => self.is_dependency_failure() || self.is_transient_database_failure(),
+        }
+    }
+
+    /// How long to wait before retrying, when it is known
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::DependencyUnavailable { retry_after, .. } => Some(*retry_after),
+            _ => None,
+        }
+    }
+
+    /// Whether the database failed with an exhausted pool, a broken connection,
+    /// a serialization failure or a deadlock
+    fn is_transient_database_failure(&self) -> bool {
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(err) = source {
+            if matches!(
+                err.downcast_ref::<sqlx::Error>(),
+                Some(sqlx::Error::PoolTimedOut | sqlx::Error::Io(_))
+            ) {
+                return true;
+            }
+            source = err.source();
+        }
+
+        super::retry::is_retryable(self)
+    }
+
+    /// Fields added to the error body, the domain code and the retry hint
+    fn additional_fields(&self) -> IndexMap<String, serde_json::Value> {
+        let mut fields = IndexMap::from([("domain_code".to_string(), self.domain_code().into())]);
+        fields.extend(retry_hint(self.is_retryable(), self.retry_after()));
+        fields
+    }
+}
+
+impl From<BreakerOpen> for Error {
+    fn from(BreakerOpen { dependency, retry_after }: BreakerOpen) -> Self {
+        Self::DependencyUnavailable { dependency, retry_after }
+    }
+}
+
+/// Fields of an error body telling clients whether sending the request again
+/// may succeed, and after how many milliseconds when it is known
+#[must_use]
+pub fn retry_hint(
+    retryable: bool,
+    retry_after: Option<Duration>,
+) -> IndexMap<String, serde_json::Value> {
+    let mut fields = IndexMap::from([("retryable".to_string(), retryable.into())]);
+    if let Some(retry_after) = retry_after {
+        // rounded up so that retrying on time does not come too early
+        let retry_after_ms =
+            u64::try_from(retry_after.as_micros().div_ceil(1000)).unwrap_or(u64::MAX);
+        let _previous = fields.insert("retry_after_ms".to_string(), retry_after_ms.into());
+    }
+    fields
+}
+
+impl ErrorCode for Error {
+    fn error_code(&self) -> &'static str {
+        match self.domain_code() {
+            // failures of the database, Keycloak or RPC nodes are not actionable
+            // for clients
+            "DATABASE_ERROR"
+            | "MISSING_FILE_FIELD"
+            | "MISSING_FILE_NAME"
+            | "INVALID_FILE_NAME"
+            | "INVALID_MULTIPART"
+            | "INVALID_CSV"
+            | "INVALID_DATE"
+            | "MISSING_CSV_DATE"
+            | "INVALID_NUMERIC_VALUE"
+            | "SERIALIZATION_FAILED"
+            | "EVENT_BROKER_UNAVAILABLE"
+            | "BITCOIN_NODE_UNAVAILABLE"
+            | "BITCOIN_INDEXER_UNAVAILABLE"
+            | "BITCOIN_BALANCE_OVERFLOW"
+            | "SOLANA_NODE_UNAVAILABLE"
+            | "KEYCLOAK_UNAVAILABLE"
+            | "KEYCLOAK_REJECTED"
+            | "DIRECTORY_USER_NOT_FOUND" => "INTERNAL_ERROR",
+            code => code,
+        }
+    }
 }
 
 #[allow(clippy::match_single_binding)]
@@ -113,34 +880,103 @@ impl IntoResponse for Error {
     // SAFETY: allow: high cognitive complexity caused by `tracing` macro
     #[allow(clippy::cognitive_complexity, clippy::single_match_else)]
     fn into_response(self) -> Response {
-        match self {
+        let retry_after = self.retry_after();
+        let mut response = match self {
             Self::DuplicateFileHash { .. }
             | Self::UserAlreadyExists { .. }
-            | Self::UserExistsInKeycloak { .. } => json_response! {
+            | Self::UserExistsInKeycloak { .. }
+            | Self::UserNotDeleted { .. }
+            | Self::TransactionAlreadySubmitted { .. } => json_response! {
                 reason: self,
                 status: StatusCode::CONFLICT,
                 error: response::Error {
                     type_: response::ErrorType::Conflict,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
-                    additional_fields: IndexMap::default(),
+                    additional_fields: self.additional_fields(),
                 }
             },
-            Self::UserNotFound { .. } | Self::KeycloakUserNotFound { .. } => json_response! {
+            Self::UserNotFound { .. }
+            | Self::KeycloakUserNotFound { .. }
+            | Self::SolanaAccountNotFound { .. }
+            | Self::TransactionNotFound { .. }
+            | Self::SigningSessionNotFound { .. }
+            | Self::KeyNotFound { .. }
+            | Self::WebhookNotFound { .. }
+            | Self::WalletNotFound { .. }
+            | Self::AnnotationNotFound { .. }
+            | Self::LoginAttemptNotFound { .. }
+            | Self::OpenApiBaselineNotFound => json_response! {
                 reason: self,
                 status: StatusCode::NOT_FOUND,
                 error: response::Error {
                     type_: response::ErrorType::NotFound,
+                    code: self.error_code().to_string(),
+                    message: self.to_string(),
+                    additional_fields: self.additional_fields(),
+                }
+            },
+            Self::InvalidCredentials { .. } | Self::InvalidRefreshToken => json_response! {
+                reason: self,
+                status: StatusCode::UNAUTHORIZED,
+                error: response::Error {
+                    type_: response::ErrorType::Unauthorized,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
-                    additional_fields: IndexMap::default(),
+                    additional_fields: self.additional_fields(),
                 }
             },
-            Self::InvalidEmail { .. } => json_response! {
+            Self::AccountLocked { .. } => json_response! {
+                reason: self,
+                status: StatusCode::LOCKED,
+                error: response::Error {
+                    type_: response::ErrorType::Unauthorized,
+                    code: self.error_code().to_string(),
+                    message: self.to_string(),
+                    additional_fields: self.additional_fields(),
+                }
+            },
+            Self::BitcoinIndexerNotConfigured
+            | Self::KeyGenerationFailed
+            | Self::NoRecordedResponse { .. }
+            | Self::DependencyUnavailable { .. } => {
+                json_response! {
+                    reason: self,
+                    status: StatusCode::SERVICE_UNAVAILABLE,
+                    error: response::Error {
+                        type_: response::ErrorType::Internal,
+                        code: self.error_code().to_string(),
+                        message: self.to_string(),
+                        additional_fields: self.additional_fields(),
+                    }
+                }
+            }
+            Self::InvalidEmail { .. }
+            | Self::InvalidEmailPattern { .. }
+            | Self::DecodeTransaction { .. }
+            | Self::MissingTransactionSignature
+            | Self::InvalidSigningSession { .. }
+            | Self::InvalidActivationToken
+            | Self::InvalidEmailChange { .. }
+            | Self::InvalidEmailChangeToken
+            | Self::SessionsDisabled
+            | Self::InvalidOpenApiDocument
+            | Self::InvalidDateRange { .. }
+            | Self::InvalidAnnotation { .. }
+            | Self::InvalidUserProfile { .. }
+            | Self::InvalidUserPreferences { .. }
+            | Self::InvalidWebhook { .. }
+            | Self::InvalidFixture { .. }
+            | Self::PasswordRejected { .. }
+            | Self::ImpersonationUnsupported { .. }
+            | Self::UnknownRealm { .. } => json_response! {
                 reason: self,
                 status: StatusCode::BAD_REQUEST,
                 error: response::Error {
                     type_: response::ErrorType::BadRequest,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
-                    additional_fields: IndexMap::default(),
+                    additional_fields: self.additional_fields(),
                 }
             },
             _ => json_response! {
@@ -148,10 +984,133 @@ impl IntoResponse for Error {
                 status: StatusCode::INTERNAL_SERVER_ERROR,
                 error: response::Error {
                     type_: response::ErrorType::Internal,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
-                    additional_fields: IndexMap::default(),
+                    additional_fields: self.additional_fields(),
                 }
             },
+        };
+
+        if let Some(retry_after) = retry_after {
+            // whole seconds, rounded up so that retrying on time succeeds
+            let retry_after_seconds =
+                retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            drop(
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds)),
+            );
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Errors of every kind of cause, actionable or not
+    fn errors() -> Vec<Error> {
+        vec![
+            Error::UserAlreadyExists { email: "user@example.com".to_string() },
+            Error::InvalidActivationToken,
+            Error::DependencyUnavailable {
+                dependency: "keycloak",
+                retry_after: Duration::from_secs(30),
+            },
+            Error::GetUserById { source: sqlx::Error::PoolTimedOut },
+            Error::CreateKeycloakUser {
+                source: keycloak::KeycloakError::HttpFailure {
+                    status: 503,
+                    body: None,
+                    text: String::new(),
+                },
+            },
+            Error::SerializeEvent {
+                source: serde_json::from_str::<serde_json::Value>("{").unwrap_err(),
+            },
+            Error::BitcoinBalanceOverflow,
+            Error::DirectoryUserNotFound { user_id: uuid::Uuid::nil() },
+        ]
+    }
+
+    #[test]
+    fn test_domain_codes() {
+        for error in errors() {
+            let code = error.domain_code();
+            assert!(
+                code.chars().all(|c| c.is_ascii_uppercase() || c == '_'),
+                "`{code}` of {error:?} is not in SCREAMING_SNAKE_CASE"
+            );
+            // the domain code refines the code of the internal errors only
+            assert!(error.error_code() == code || error.error_code() == "INTERNAL_ERROR");
+        }
+
+        let error = Error::CreateKeycloakUser {
+            source: keycloak::KeycloakError::HttpFailure {
+                status: 503,
+                body: None,
+                text: String::new(),
+            },
+        };
+        assert_eq!(error.error_code(), "INTERNAL_ERROR");
+        assert_eq!(error.domain_code(), "KEYCLOAK_UNAVAILABLE");
+
+        // Keycloak is up when it rejects a request
+        let error = Error::SetKeycloakPassword {
+            source: keycloak::KeycloakError::HttpFailure {
+                status: 404,
+                body: None,
+                text: String::new(),
+            },
+        };
+        assert_eq!(error.error_code(), "INTERNAL_ERROR");
+        assert_eq!(error.domain_code(), "KEYCLOAK_REJECTED");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_carries_domain_code() {
+        for error in errors() {
+            let (code, domain_code) = (error.error_code(), error.domain_code());
+            let response = error.into_response();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            assert_eq!(body["error"]["code"], code);
+            assert_eq!(body["error"]["domain_code"], domain_code);
+            assert!(body["error"]["retryable"].is_boolean());
         }
     }
+
+    #[test]
+    fn test_retryable_errors() {
+        let keycloak_error = |status| Error::CreateKeycloakUser {
+            source: keycloak::KeycloakError::HttpFailure {
+                status,
+                body: None,
+                text: String::new(),
+            },
+        };
+
+        assert!(keycloak_error(503).is_retryable());
+        assert!(Error::GetUserById { source: sqlx::Error::PoolTimedOut }.is_retryable());
+        assert!(!keycloak_error(400).is_retryable());
+        assert!(!Error::GetUserById { source: sqlx::Error::RowNotFound }.is_retryable());
+        assert!(!Error::UserAlreadyExists { email: "user@example.com".to_string() }.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_of_unavailable_dependency() {
+        let error = Error::DependencyUnavailable {
+            dependency: "keycloak",
+            retry_after: Duration::from_millis(12_345),
+        };
+        let response = error.into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], "13");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["retryable"], true);
+        assert_eq!(body["error"]["retry_after_ms"], 12_345);
+    }
 }
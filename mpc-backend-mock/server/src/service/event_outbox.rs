@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use events::{EventPublisher, Message};
+use serde::Serialize;
+use snafu::ResultExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::Result;
+use crate::{
+    entity::{DomainEvent, EventOffset, OutboxEvent},
+    service::{error, sql_executor::EventOutboxSqlExecutor},
+};
+
+/// Writes domain events to the `event_outbox` table, in the transaction of the
+/// change they describe
+///
+/// The outbox is disabled unless a publisher ships the events, recording an
+/// event is a no-op then.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EventOutbox {
+    enabled: bool,
+}
+
+impl EventOutbox {
+    /// Outbox recording events if `enabled`
+    #[must_use]
+    pub const fn new(enabled: bool) -> Self { Self { enabled } }
+
+    /// Whether events are recorded
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool { self.enabled }
+
+    /// Write `event` to the outbox through `executor`, usually the transaction
+    /// of the change the event describes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be serialized or the database
+    /// operation fails
+    pub(crate) async fn record<E>(&self, executor: &mut E, event: &DomainEvent) -> Result<()>
+    where
+        E: EventOutboxSqlExecutor + Send,
+    {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let payload = serde_json::to_value(event).context(error::SerializeEventSnafu)?;
+        let _event_id =
+            executor.insert_event(event.kind(), &event.aggregate_id(), &payload).await?;
+
+        Ok(())
+    }
+}
+
+/// Message body published for an [`OutboxEvent`]
+#[derive(Serialize)]
+struct Envelope<'a> {
+    id: Uuid,
+    kind: &'a str,
+    aggregate_id: Uuid,
+    occurred_at: DateTime<Utc>,
+    data: &'a serde_json::Value,
+}
+
+/// Event outbox service shipping the events of the `event_outbox` table to
+/// the message broker
+///
+/// Events are published in the order their transactions committed, and the
+/// offset of the publisher is only moved past an event once the broker
+/// acknowledged it. Delivery is at least once: an event is published again if
+/// the offset cannot be stored, consumers deduplicate by event ID.
+#[derive(Clone)]
+pub struct EventOutboxService {
+    db: PgPool,
+    publisher: Arc<dyn EventPublisher>,
+    publisher_name: String,
+    batch_size: i64,
+}
+
+impl EventOutboxService {
+    /// Create a new event outbox service publishing through `publisher`, whose
+    /// offset is tracked under `publisher_name`
+    #[inline]
+    #[must_use]
+    pub fn new(
+        db: PgPool,
+        publisher: Arc<dyn EventPublisher>,
+        publisher_name: &str,
+        batch_size: u32,
+    ) -> Self {
+        Self {
+            db,
+            publisher,
+            publisher_name: publisher_name.to_string(),
+            batch_size: i64::from(batch_size),
+        }
+    }
+
+    /// Publish the events after the offset of the publisher, batch by batch
+    /// until none is left, returns the number of published events
+    ///
+    /// Concurrent publishers of the same name take turns, the offset is
+    /// locked while a batch is published.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the broker rejects an event or the database
+    /// operation fails, the events published before are kept
+    pub async fn publish_pending(&self) -> Result<u64> {
+        let mut total = 0;
+        loop {
+            let published = self.publish_batch().await?;
+            total += published;
+
+            if published < self.batch_size.unsigned_abs() {
+                return Ok(total);
+            }
+        }
+    }
+
+    async fn publish_batch(&self) -> Result<u64> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let mut offset = tx.lock_publisher_offset(&self.publisher_name).await?;
+        let events = tx.list_publishable_events(&offset, self.batch_size).await?;
+
+        let mut published = 0;
+        let mut failure = None;
+        for event in &events {
+            if let Err(err) = self.publish(event).await {
+                failure = Some(err);
+                break;
+            }
+            offset = EventOffset::after(event);
+            published += 1;
+        }
+
+        if published > 0 {
+            tx.update_publisher_offset(&self.publisher_name, &offset).await?;
+        }
+        tx.commit().await.context(error::CommitTransactionSnafu)?;
+
+        failure.map_or(Ok(published), Err)
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> Result<()> {
+        let envelope = Envelope {
+            id: event.id,
+            kind: &event.kind,
+            aggregate_id: event.aggregate_id,
+            occurred_at: event.created_at,
+            data: &event.payload,
+        };
+        let payload = serde_json::to_vec(&envelope).context(error::SerializeEventSnafu)?;
+
+        self.publisher
+            .publish(&Message {
+                id: &event.id.to_string(),
+                kind: &event.kind,
+                key: &event.aggregate_id.to_string(),
+                payload: &payload,
+            })
+            .await
+            .context(error::PublishEventSnafu { event_id: event.id })
+    }
+}
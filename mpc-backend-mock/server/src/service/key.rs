@@ -0,0 +1,152 @@
+use mpc_backend_mock_core::config::KeyGenerationConfig;
+use sha2::{Digest, Sha256};
+use snafu::{OptionExt, ResultExt};
+use solana_sdk::pubkey::Pubkey;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{Chain, Key, KeyCurve},
+    service::{
+        error,
+        sql_executor::{KeySqlExecutor, UserSqlExecutor},
+    },
+};
+
+/// Key service simulating the distributed key generation of the users' MPC
+/// keys
+///
+/// No key material exists, the public key of a user on a chain is derived from
+/// the user and the chain, so it is the same across databases and reruns. A
+/// user has one key per chain, generating it again returns the existing one.
+/// `simulate_delay` and `simulate_failure` slow down or fail the generation,
+/// so that frontends can be tested against a slow or failing ceremony.
+#[derive(Clone)]
+pub struct KeyService {
+    db: PgPool,
+    config: KeyGenerationConfig,
+}
+
+impl KeyService {
+    /// Create a new key service generating keys right away
+    #[must_use]
+    pub fn new(db: PgPool) -> Self { Self { db, config: KeyGenerationConfig::default() } }
+
+    /// Simulate the key generation as configured by `config`
+    #[must_use]
+    pub const fn with_config(mut self, config: KeyGenerationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Generate the key of a user on `chain`, or get the existing one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The failure is simulated
+    /// - User not found
+    /// - Database operation fails
+    pub async fn create_key(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        chain: Chain,
+    ) -> Result<Key> {
+        if !self.config.simulate_delay.is_zero() {
+            tokio::time::sleep(self.config.simulate_delay).await;
+        }
+        if self.config.simulate_failure {
+            return Err(Error::KeyGenerationFailed);
+        }
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user_id = get_user_id(&mut conn, keycloak_user_id, realm).await?;
+
+        let (curve, public_key) = fake_public_key(&user_id, chain);
+        conn.insert_key(&user_id, chain, curve, &public_key).await
+    }
+
+    /// Get a key of a user
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User or key not found
+    /// - Database operation fails
+    pub async fn get_key(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        key_id: &Uuid,
+    ) -> Result<Key> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user_id = get_user_id(&mut conn, keycloak_user_id, realm).await?;
+
+        conn.get_key_by_id(key_id, &user_id)
+            .await?
+            .context(error::KeyNotFoundSnafu { key_id: *key_id })
+    }
+}
+
+async fn get_user_id(
+    conn: &mut PgConnection,
+    keycloak_user_id: &Uuid,
+    realm: Option<&str>,
+) -> Result<Uuid> {
+    conn.get_user_by_keycloak_id(keycloak_user_id, realm, false)
+        .await?
+        .map(|user| user.id)
+        .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })
+}
+
+/// Curve and encoded public key of a user on `chain`
+///
+/// The key is shaped like a real one, a compressed secp256k1 point in hex or
+/// an ed25519 point in base58, but is a hash of the user and the chain, it is
+/// not guaranteed to lie on the curve.
+fn fake_public_key(user_id: &Uuid, chain: Chain) -> (KeyCurve, String) {
+    let (curve, tag) = match chain {
+        Chain::Bitcoin => (KeyCurve::Secp256k1, "bitcoin"),
+        Chain::Solana => (KeyCurve::Ed25519, "solana"),
+    };
+    let digest: [u8; 32] = Sha256::new()
+        .chain_update(b"mpc-backend-mock:key:")
+        .chain_update(tag.as_bytes())
+        .chain_update(user_id.as_bytes())
+        .finalize()
+        .into();
+
+    let public_key = match curve {
+        KeyCurve::Secp256k1 => {
+            // the parity prefix of a compressed point
+            let prefix = if digest[31] % 2 == 0 { 0x02 } else { 0x03 };
+            hex::encode([&[prefix][..], &digest].concat())
+        }
+        KeyCurve::Ed25519 => Pubkey::new_from_array(digest).to_string(),
+    };
+
+    (curve, public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_public_key_is_deterministic() {
+        let user_id = Uuid::new_v4();
+
+        let (curve, bitcoin_key) = fake_public_key(&user_id, Chain::Bitcoin);
+        assert_eq!(curve, KeyCurve::Secp256k1);
+        assert_eq!(bitcoin_key.len(), 66);
+        assert!(bitcoin_key.starts_with("02") || bitcoin_key.starts_with("03"));
+        assert_eq!(fake_public_key(&user_id, Chain::Bitcoin).1, bitcoin_key);
+
+        let (curve, solana_key) = fake_public_key(&user_id, Chain::Solana);
+        assert_eq!(curve, KeyCurve::Ed25519);
+        assert!(solana_key.parse::<Pubkey>().is_ok());
+        assert_ne!(fake_public_key(&Uuid::new_v4(), Chain::Solana).1, solana_key);
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use mpc_backend_mock_core::config::LoginLockoutConfig;
+use snafu::ResultExt;
+use sqlx::PgPool;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::LoginAttempt,
+    service::{
+        error,
+        sql_executor::{LoginAttemptSqlExecutor, UserSqlExecutor},
+        user_directory::UserDirectory,
+    },
+};
+
+/// Lockout of emails after repeated failed logins
+///
+/// Failed logins are counted per email in the `login_attempts` table. Once
+/// `max_failed_attempts` of them fall within the failure window, the email is
+/// locked and the Keycloak account of its user is disabled, so that neither
+/// the backend nor Keycloak let it log in. The lockout ends on its own after
+/// the lockout duration or when an admin unlocks the email, which enables the
+/// account again.
+#[derive(Clone)]
+pub struct LoginLockoutService {
+    db: PgPool,
+
+    user_directory: Arc<dyn UserDirectory>,
+
+    config: LoginLockoutConfig,
+}
+
+impl LoginLockoutService {
+    /// Create a new login lockout service, the lockout stays disabled until
+    /// enabled by [`LoginLockoutService::with_config`]
+    #[inline]
+    #[must_use]
+    pub fn new(db: PgPool, user_directory: Arc<dyn UserDirectory>) -> Self {
+        Self { db, user_directory, config: LoginLockoutConfig::default() }
+    }
+
+    /// Lock emails as configured by `config`
+    #[must_use]
+    pub const fn with_config(mut self, config: LoginLockoutConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Reject the login of `email` while it is locked, a lockout which ended
+    /// is lifted here if the cleanup job has not done it yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The email is locked
+    /// - Keycloak or database operation fails
+    pub async fn check(&self, email: &str) -> Result<()> {
+        if !self.config.enable {
+            return Ok(());
+        }
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let Some(attempt) = conn.get_login_attempt(email).await? else {
+            return Ok(());
+        };
+        drop(conn);
+
+        if attempt.is_locked(Utc::now()) {
+            return Err(Error::AccountLocked { email: email.to_string() });
+        }
+        if attempt.locked_at.is_some() {
+            // the account is still disabled in Keycloak, which would reject
+            // the login as invalid credentials
+            let _attempt = self.unlock(email).await?;
+        }
+        Ok(())
+    }
+
+    /// Count a failed login of `email` from `ip_address`, returns the lockout
+    /// if this failure locked the email
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn record_failure(
+        &self,
+        email: &str,
+        ip_address: Option<&str>,
+    ) -> Result<Option<LoginAttempt>> {
+        if !self.config.enable {
+            return Ok(None);
+        }
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let attempt = conn
+            .record_failed_login(email, ip_address, self.config.failure_window.as_secs_f64())
+            .await?;
+        if u32::try_from(attempt.failed_attempts).unwrap_or_default()
+            < self.config.max_failed_attempts
+        {
+            return Ok(None);
+        }
+
+        let lockout_seconds = self.config.lockout_duration.map(|duration| duration.as_secs_f64());
+        let Some(attempt) = conn.lock_login(email, lockout_seconds).await? else {
+            return Ok(None);
+        };
+
+        // the lockout holds in the backend even if Keycloak cannot be told
+        if let Some(user) = conn.get_user_by_email(email, None, false).await? {
+            if let Err(err) = self.user_directory.set_enabled(&user.keycloak_user_id, false).await {
+                tracing::warn!("Fail to disable the Keycloak account of locked `{email}`: {err}");
+            }
+        }
+        tracing::info!("Locked `{email}` after {} failed logins", attempt.failed_attempts);
+
+        Ok(Some(attempt))
+    }
+
+    /// Forget the failed logins of `email` after it logged in
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn record_success(&self, email: &str) -> Result<()> {
+        if !self.config.enable {
+            return Ok(());
+        }
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let _attempt = conn.delete_login_attempt(email).await?;
+
+        Ok(())
+    }
+
+    /// Emails which are locked, most recently locked first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn list_locked(&self) -> Result<Vec<LoginAttempt>> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.list_locked_logins().await
+    }
+
+    /// Unlock `email` and forget its failed logins, the Keycloak account of
+    /// its user is enabled again unless the user is deleted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The email has no failed logins
+    /// - Keycloak or database operation fails
+    pub async fn unlock(&self, email: &str) -> Result<LoginAttempt> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let unlock_result = async {
+            let attempt = tx
+                .delete_login_attempt(email)
+                .await?
+                .ok_or_else(|| Error::LoginAttemptNotFound { email: email.to_string() })?;
+            if attempt.locked_at.is_some() {
+                if let Some(user) = tx.get_user_by_email(email, None, false).await? {
+                    self.user_directory.set_enabled(&user.keycloak_user_id, true).await?;
+                }
+            }
+
+            Ok::<LoginAttempt, Error>(attempt)
+        }
+        .await;
+
+        match unlock_result {
+            Ok(attempt) => {
+                tx.commit().await.context(error::CommitTransactionSnafu)?;
+                Ok(attempt)
+            }
+            Err(e) => {
+                tx.rollback().await.context(error::RollBackTransactionSnafu)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Lift the lockouts which ended and forget the failed logins whose
+    /// failure window has passed, returns the number of lifted lockouts
+    ///
+    /// Keycloak accounts which cannot be enabled again are left to the user
+    /// reconciliation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn unlock_expired(&self) -> Result<usize> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let attempts =
+            conn.delete_expired_login_attempts(self.config.failure_window.as_secs_f64()).await?;
+
+        let mut unlocked = 0;
+        for attempt in attempts.iter().filter(|attempt| attempt.locked_at.is_some()) {
+            unlocked += 1;
+            let Some(user) = conn.get_user_by_email(&attempt.email, None, false).await? else {
+                continue;
+            };
+            if let Err(err) = self.user_directory.set_enabled(&user.keycloak_user_id, true).await {
+                tracing::warn!(
+                    "Fail to enable the Keycloak account of unlocked `{}`: {err}",
+                    attempt.email
+                );
+            }
+        }
+        Ok(unlocked)
+    }
+}
@@ -1,5 +1,54 @@
+mod annotation;
+mod api_drift;
+mod audit;
+mod auth;
+mod bitcoin;
+mod chain;
+mod changelog;
 pub mod error;
+mod event_outbox;
+mod key;
+mod login_lockout;
+mod notification;
+mod read_pool;
+mod retry;
+mod seeder;
+mod session;
+mod signing;
+mod solana;
 mod sql_executor;
+mod transaction;
+mod user_directory;
 mod user_management;
+mod wallet;
+mod webhook;
 
-pub use user_management::UserManagementService;
+pub use annotation::AnnotationService;
+pub use api_drift::ApiDriftService;
+pub use audit::{AuditContext, AuditService};
+pub use auth::{check_password_strength, AuthService, MAX_PASSWORD_LENGTH, MIN_PASSWORD_LENGTH};
+pub use bitcoin::BitcoinService;
+pub use chain::{
+    BitcoinChain, CircuitBreakingBitcoinChain, InstrumentedBitcoinChain, MockBitcoinChain,
+    MockSolanaChain, RecordingBitcoinChain, RecordingSolanaChain, ReplayedBitcoinChain,
+    ReplayedSolanaChain, RpcBitcoinChain, RpcSolanaChain, SolanaChain,
+};
+pub use changelog::api_changelog;
+pub use event_outbox::{EventOutbox, EventOutboxService};
+pub use key::KeyService;
+pub use login_lockout::LoginLockoutService;
+pub use notification::{CapturedNotificationStore, NotificationDispatch, NotificationService};
+pub use seeder::{Fixtures, SeedReport, SeedService, TransactionFixture, UserFixture};
+pub use session::{verify_csrf_token, NewSession, SessionService};
+pub use signing::SigningService;
+pub use solana::SolanaService;
+pub use sql_executor::{PgPoolMetrics, QueryMetrics};
+pub use transaction::TransactionService;
+pub use user_directory::{
+    CircuitBreakingUserDirectory, DirectoryAccount, DirectoryUser, InstrumentedUserDirectory,
+    KeycloakUserDirectory, MemoryUserDirectory, RecordingUserDirectory, ReplayedUserDirectory,
+    UserDirectory,
+};
+pub use user_management::{UserManagementService, UserReconciliation};
+pub use wallet::WalletService;
+pub use webhook::{webhook_signature, WebhookDispatch, WebhookService};
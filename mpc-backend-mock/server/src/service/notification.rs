@@ -0,0 +1,478 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{TimeDelta, Utc};
+use mpc_backend_mock_core::model::Pagination;
+use notification::{
+    capture::{CapturedEmail, Store},
+    Notification, NotificationClient,
+};
+use snafu::ResultExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::Result;
+use crate::{
+    entity::{
+        CapturedNotification, ListNotificationsFilter, NotificationStatus, OutboxNotification,
+        RecipientPreferences,
+    },
+    service::{error, sql_executor::NotificationSqlExecutor},
+};
+
+/// Number of due notifications sent per [`NotificationService::dispatch_due`]
+const DISPATCH_BATCH_SIZE: i64 = 50;
+
+/// Attempts after which a failing notification is dead-lettered
+const MAX_ATTEMPTS: i32 = 8;
+
+/// Delay before the first retry, it doubles with every further failure up to
+/// [`RETRY_MAX_DELAY`]
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Time a webhook has to answer a posted notification
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Notifications handled by one [`NotificationService::dispatch_due`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NotificationDispatch {
+    /// Notifications sent successfully
+    pub sent: u64,
+
+    /// Notifications none of whose recipients wants them, see
+    /// [`UserPreferences`](crate::entity::UserPreferences)
+    pub skipped: u64,
+
+    /// Notifications which failed and are retried later
+    pub retried: u64,
+
+    /// Notifications which failed for the last time and were dead-lettered
+    pub dead_lettered: u64,
+}
+
+impl NotificationDispatch {
+    /// Notifications sent or attempted
+    #[must_use]
+    pub const fn handled(&self) -> u64 {
+        self.sent + self.skipped + self.retried + self.dead_lettered
+    }
+}
+
+/// Queue `notification` in the outbox through `executor`, usually the
+/// transaction of the change the notification belongs to
+///
+/// # Errors
+///
+/// Returns an error if the notification cannot be serialized or the database
+/// operation fails
+pub(super) async fn enqueue_notification<E>(
+    executor: &mut E,
+    notification: &Notification,
+) -> Result<Uuid>
+where
+    E: NotificationSqlExecutor + Send,
+{
+    let payload = serde_json::to_value(notification).context(error::SerializeNotificationSnafu)?;
+
+    executor
+        .insert_notification(
+            notification.template_name(),
+            &notification.recipients().to_string(),
+            &payload,
+        )
+        .await
+}
+
+/// Notification service sending the notifications queued in the
+/// `notification_outbox` table
+#[derive(Clone)]
+pub struct NotificationService {
+    db: PgPool,
+    notification_client: Arc<dyn NotificationClient>,
+    http_client: reqwest::Client,
+}
+
+impl NotificationService {
+    /// Create a new notification service
+    #[inline]
+    #[must_use]
+    pub fn new(db: PgPool, notification_client: Arc<dyn NotificationClient>) -> Self {
+        Self { db, notification_client, http_client: reqwest::Client::new() }
+    }
+
+    /// Send the pending notifications whose next attempt is due, oldest first
+    ///
+    /// Each notification is delivered over the channels its primary
+    /// recipients prefer, see [`plan_delivery`], and skipped if none of them
+    /// wants it. A failed notification is retried over all its channels with
+    /// exponential backoff and dead-lettered after its last attempt.
+    /// Notifications are locked while they are sent, so concurrent
+    /// dispatchers never send the same one. Delivery is at least once, a
+    /// notification is sent again if its result cannot be recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn dispatch_due(&self) -> Result<NotificationDispatch> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let notifications = tx.claim_due_notifications(DISPATCH_BATCH_SIZE).await?;
+
+        let mut dispatch = NotificationDispatch::default();
+        for outbox in notifications {
+            // a payload which cannot be read will not be read on a retry either
+            let result = match serde_json::from_value::<Notification>(outbox.payload) {
+                Ok(notification) => {
+                    let preferences =
+                        tx.list_recipient_preferences(&notification.recipients().to).await?;
+                    self.deliver(&plan_delivery(notification, &preferences))
+                        .await
+                        .map_err(|err| (err, true))
+                }
+                Err(err) => Err((format!("Invalid notification payload, error: {err}"), false)),
+            };
+
+            let (err, retryable) = match result {
+                Ok(true) => {
+                    tx.mark_notification_sent(&outbox.id).await?;
+                    dispatch.sent += 1;
+                    continue;
+                }
+                Ok(false) => {
+                    tx.mark_notification_skipped(&outbox.id).await?;
+                    dispatch.skipped += 1;
+                    continue;
+                }
+                Err(failure) => failure,
+            };
+
+            let attempts = outbox.attempts + 1;
+            let next_attempt_at = (retryable && attempts < MAX_ATTEMPTS).then(|| {
+                Utc::now() + TimeDelta::from_std(retry_delay(attempts)).unwrap_or_default()
+            });
+            tx.record_failed_attempt(&outbox.id, &err, next_attempt_at).await?;
+
+            if next_attempt_at.is_some() {
+                tracing::warn!(
+                    "Failed to send notification {} (attempt {attempts}), error: {err}",
+                    outbox.id
+                );
+                dispatch.retried += 1;
+            } else {
+                tracing::error!(
+                    "Dead-lettered notification {} after {attempts} attempts, error: {err}",
+                    outbox.id
+                );
+                dispatch.dead_lettered += 1;
+            }
+        }
+
+        tx.commit().await.context(error::CommitTransactionSnafu)?;
+
+        Ok(dispatch)
+    }
+
+    /// Dispatch the due notifications batch by batch until none is left, e.g.
+    /// before shutting down
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn flush(&self) -> Result<NotificationDispatch> {
+        let mut total = NotificationDispatch::default();
+        loop {
+            let dispatch = self.dispatch_due().await?;
+            total.sent += dispatch.sent;
+            total.skipped += dispatch.skipped;
+            total.retried += dispatch.retried;
+            total.dead_lettered += dispatch.dead_lettered;
+
+            // failed notifications are not due again, a short batch is the last
+            if dispatch.handled() < DISPATCH_BATCH_SIZE.unsigned_abs() {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Deliver a notification over the channels of `delivery`, returns
+    /// whether it was delivered over any
+    async fn deliver(&self, delivery: &Delivery) -> std::result::Result<bool, String> {
+        if delivery.email.is_none() && delivery.webhook_urls.is_empty() {
+            return Ok(false);
+        }
+
+        if let Some(email) = &delivery.email {
+            self.notification_client
+                .send_notification(email)
+                .await
+                .map_err(|err| err.to_string())?;
+        }
+        for webhook_url in &delivery.webhook_urls {
+            self.post_webhook(webhook_url, &delivery.notification).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Post `notification` as JSON to a webhook, which must answer with a
+    /// success status
+    async fn post_webhook(
+        &self,
+        webhook_url: &str,
+        notification: &Notification,
+    ) -> std::result::Result<(), String> {
+        // the URL is left out of the error, it is stored as the last error and
+        // may carry a secret of the user
+        let response = self
+            .http_client
+            .post(webhook_url)
+            .timeout(WEBHOOK_TIMEOUT)
+            .json(notification)
+            .send()
+            .await
+            .map_err(|err| format!("Failed to post webhook, error: {}", err.without_url()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Webhook answered with {status}"));
+        }
+        Ok(())
+    }
+
+    /// Number of notifications waiting for their first or next attempt
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn pending_count(&self) -> Result<u64> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        let filter = ListNotificationsFilter { status: Some(NotificationStatus::Pending) };
+        let count = conn.count_notifications(&filter).await?;
+
+        Ok(u64::try_from(count).unwrap_or_default())
+    }
+
+    /// List queued notifications page by page, newest first, returns the
+    /// notifications and the number of notifications matching the filters
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn list_notifications(
+        &self,
+        pagination: &Pagination,
+        filter: &ListNotificationsFilter,
+    ) -> Result<(Vec<OutboxNotification>, u64)> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        let limit = pagination.sql_limit();
+        let offset = pagination.sql_offset();
+
+        let notifications = conn.list_notifications(filter, limit, offset).await?;
+        let total_count = conn.count_notifications(filter).await?;
+
+        Ok((notifications, u64::try_from(total_count).unwrap_or_default()))
+    }
+
+    /// List the emails recorded by the `capture` notification provider page by
+    /// page, newest first, returns the emails and the number of captured
+    /// emails
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn list_captured_notifications(
+        &self,
+        pagination: &Pagination,
+    ) -> Result<(Vec<CapturedNotification>, u64)> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        let limit = pagination.sql_limit();
+        let offset = pagination.sql_offset();
+
+        let notifications = conn.list_captured_notifications(limit, offset).await?;
+        let total_count = conn.count_captured_notifications().await?;
+
+        Ok((notifications, u64::try_from(total_count).unwrap_or_default()))
+    }
+}
+
+/// Capture store recording the emails of the `capture` notification provider in
+/// the `captured_notifications` table
+#[derive(Clone, Debug)]
+pub struct CapturedNotificationStore {
+    db: PgPool,
+}
+
+impl CapturedNotificationStore {
+    /// Create a new capture store
+    #[inline]
+    #[must_use]
+    pub const fn new(db: PgPool) -> Self { Self { db } }
+
+    async fn insert(&self, email: &CapturedEmail) -> Result<()> {
+        let payload =
+            serde_json::to_value(&email.notification).context(error::SerializeNotificationSnafu)?;
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        conn.insert_captured_notification(
+            email.notification.template_name(),
+            &email.notification.recipients().to_string(),
+            &email.rendered.subject,
+            &email.rendered.html_body,
+            &payload,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl Store for CapturedNotificationStore {
+    async fn record(&self, email: &CapturedEmail) -> std::result::Result<(), notification::Error> {
+        self.insert(email)
+            .await
+            .map_err(|err| notification::Error::RecordEmail { message: err.to_string() })
+    }
+}
+
+/// Channels a notification is delivered over
+#[derive(Debug)]
+struct Delivery {
+    /// Notification emailed to the primary recipients who want emails, `None`
+    /// if none does
+    email: Option<Notification>,
+
+    /// Notification posted to the webhooks
+    notification: Notification,
+
+    /// Webhooks of the primary recipients
+    webhook_urls: Vec<String>,
+}
+
+/// Plan the delivery of `notification` by the preferences of its primary
+/// recipients, recipients without preferences get emails only
+///
+/// Recipients who turned emails off are dropped from the email, which is not
+/// sent if no primary recipient is left. Carbon copies are not users and are
+/// only emailed along. The preferred locale applies to a notification with a
+/// single primary recipient.
+fn plan_delivery(mut notification: Notification, preferences: &[RecipientPreferences]) -> Delivery {
+    let of_recipient =
+        |recipient: &str| preferences.iter().find(|preferences| preferences.email == recipient);
+
+    if let [recipient] = notification.recipients().to.as_slice() {
+        if let Some(locale) = of_recipient(recipient).and_then(|p| p.locale.clone()) {
+            notification.set_locale(Some(locale));
+        }
+    }
+
+    let mut webhook_urls = Vec::new();
+    for recipient in &notification.recipients().to {
+        if let Some(webhook_url) = of_recipient(recipient).and_then(|p| p.webhook_url.as_ref()) {
+            if !webhook_urls.contains(webhook_url) {
+                webhook_urls.push(webhook_url.clone());
+            }
+        }
+    }
+
+    let mut email = notification.clone();
+    email
+        .recipients_mut()
+        .to
+        .retain(|recipient| of_recipient(recipient).is_none_or(|p| p.email_enabled));
+    let email = (!email.recipients().to.is_empty()).then_some(email);
+
+    Delivery { email, notification, webhook_urls }
+}
+
+/// Delay before the attempt following the `attempts`th failed one
+pub(super) fn retry_delay(attempts: i32) -> Duration {
+    let doublings = u32::try_from(attempts - 1).unwrap_or_default().min(16);
+    RETRY_BASE_DELAY.saturating_mul(1 << doublings).min(RETRY_MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use notification::Recipients;
+
+    use super::*;
+
+    fn preferences(
+        email: &str,
+        email_enabled: bool,
+        webhook_url: Option<&str>,
+    ) -> RecipientPreferences {
+        RecipientPreferences {
+            email: email.to_string(),
+            email_enabled,
+            webhook_url: webhook_url.map(ToString::to_string),
+            locale: Some("zh-TW".to_string()),
+        }
+    }
+
+    fn activation_email(recipients: Recipients) -> Notification {
+        Notification::ActivationEmail {
+            recipients,
+            link: "https://example.com/activate?token=abc".to_string(),
+            locale: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_delivery_without_preferences() {
+        let delivery = plan_delivery(activation_email(Recipients::new("a@example.com")), &[]);
+
+        let email = delivery.email.expect("emails are sent by default");
+        assert_eq!(email.recipients().to, ["a@example.com"]);
+        assert_eq!(email.locale(), None);
+        assert!(delivery.webhook_urls.is_empty());
+    }
+
+    #[test]
+    fn test_plan_delivery_with_preferences() {
+        let delivery = plan_delivery(
+            activation_email(Recipients::new("a@example.com")),
+            &[preferences("a@example.com", false, Some("https://example.com/hook"))],
+        );
+        assert!(delivery.email.is_none());
+        assert_eq!(delivery.webhook_urls, ["https://example.com/hook"]);
+        assert_eq!(delivery.notification.locale(), Some("zh-TW"));
+
+        // the email goes to the recipients who want it, in the language of the
+        // notification as they are several
+        let delivery = plan_delivery(
+            activation_email(
+                Recipients::new("a@example.com").with_to("b@example.com").with_cc("c@example.com"),
+            ),
+            &[
+                preferences("a@example.com", false, None),
+                preferences("b@example.com", true, Some("https://example.com/hook")),
+            ],
+        );
+        let email = delivery.email.expect("b wants emails");
+        assert_eq!(email.recipients().to, ["b@example.com"]);
+        assert_eq!(email.recipients().cc, ["c@example.com"]);
+        assert_eq!(email.locale(), None);
+        assert_eq!(delivery.webhook_urls, ["https://example.com/hook"]);
+
+        // a notification nobody wants is not delivered
+        let delivery = plan_delivery(
+            activation_email(Recipients::new("a@example.com")),
+            &[preferences("a@example.com", false, None)],
+        );
+        assert!(delivery.email.is_none() && delivery.webhook_urls.is_empty());
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(3), Duration::from_secs(120));
+        assert_eq!(retry_delay(7), Duration::from_secs(32 * 60));
+        assert_eq!(retry_delay(8), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(i32::MAX), RETRY_MAX_DELAY);
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use snafu::ResultExt;
+use sqlx::{pool::PoolConnection, PgPool, Postgres};
+
+use super::error::{self, Result};
+
+/// Connections for read-only queries, taken from the read replicas in turn
+///
+/// A replica which cannot hand out a connection is skipped, and the primary
+/// serves the read if none of them can. Replicas lag behind the primary, so a
+/// read may not see a write which just committed.
+#[derive(Clone, Debug)]
+pub struct ReadPool {
+    primary: PgPool,
+    replicas: Arc<[PgPool]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl ReadPool {
+    /// Read from `replicas`, or from `primary` if there is none
+    #[must_use]
+    pub fn new(primary: PgPool, replicas: Vec<PgPool>) -> Self {
+        Self { primary, replicas: replicas.into(), next: Arc::default() }
+    }
+
+    /// Connection of the next replica which has one available, or of the
+    /// primary
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no connection of the primary can be acquired either
+    pub async fn acquire(&self) -> Result<PoolConnection<Postgres>> {
+        let first = self.next.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..self.replicas.len() {
+            let index = first.wrapping_add(offset) % self.replicas.len();
+            match self.replicas[index].acquire().await {
+                Ok(conn) => return Ok(conn),
+                Err(err) => tracing::warn!(
+                    "Failed to acquire connection of read replica {index}, falling back, error: \
+                     {err}"
+                ),
+            }
+        }
+
+        self.primary.acquire().await.context(error::AcquireConnectionSnafu)
+    }
+}
@@ -0,0 +1,179 @@
+use std::{future::Future, time::Duration};
+
+use mpc_backend_mock_core::config::TransactionRetryConfig;
+use rand::Rng;
+
+use super::error::{Error, Result};
+
+/// SQLSTATE of a transaction aborted by a serialization failure
+const SERIALIZATION_FAILURE: &str = "40001";
+
+/// SQLSTATE of a transaction aborted to break a deadlock
+const DEADLOCK_DETECTED: &str = "40P01";
+
+/// Run the database transaction of `transaction` again while Postgres aborts
+/// it with a serialization failure or a deadlock, at most
+/// `retry.max_attempts` times in total
+///
+/// `transaction` must begin a new database transaction on every call, the
+/// aborted one is rolled back when it is dropped.
+///
+/// # Errors
+///
+/// Returns the error of the last attempt, or the first error which is not
+/// retryable
+pub async fn retry_transaction<T, F, Fut>(
+    retry: &TransactionRetryConfig,
+    operation: &str,
+    mut transaction: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match transaction().await {
+            Err(err) if attempt < retry.max_attempts && is_retryable(&err) => {
+                let delay = backoff(retry.base_delay, attempt);
+                tracing::warn!(
+                    "Transaction of {operation} is aborted (attempt {attempt}/{}), retrying in \
+                     {delay:?}, error: {err}",
+                    retry.max_attempts
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether `err` is caused by a serialization failure or a deadlock, which
+/// succeed when the transaction is run again
+pub(super) fn is_retryable(err: &Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        let code = err
+            .downcast_ref::<sqlx::Error>()
+            .and_then(sqlx::Error::as_database_error)
+            .and_then(|err| err.code());
+        if code.is_some_and(|code| code == SERIALIZATION_FAILURE || code == DEADLOCK_DETECTED) {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Delay before the retry following `attempt`, doubling from `base_delay`
+/// with up to 50% jitter so that the aborted transactions do not collide again
+fn backoff(base_delay: Duration, attempt: u32) -> Duration {
+    let delay = base_delay.saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)));
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        borrow::Cow,
+        fmt,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use sqlx::error::{DatabaseError, ErrorKind};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct PgError(&'static str);
+
+    impl fmt::Display for PgError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for PgError {}
+
+    impl DatabaseError for PgError {
+        fn message(&self) -> &str { self.0 }
+
+        fn code(&self) -> Option<Cow<'_, str>> { Some(Cow::Borrowed(self.0)) }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) { self }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) { self }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind { ErrorKind::Other }
+    }
+
+    fn database_error(code: &'static str) -> Error {
+        Error::InsertUser { source: sqlx::Error::Database(Box::new(PgError(code))) }
+    }
+
+    const RETRY: TransactionRetryConfig =
+        TransactionRetryConfig { max_attempts: 3, base_delay: Duration::from_millis(50) };
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&database_error(SERIALIZATION_FAILURE)));
+        assert!(is_retryable(&database_error(DEADLOCK_DETECTED)));
+        // unique violation
+        assert!(!is_retryable(&database_error("23505")));
+        assert!(!is_retryable(&Error::InsertUser { source: sqlx::Error::PoolTimedOut }));
+        assert!(!is_retryable(&Error::InvalidRefreshToken));
+    }
+
+    #[test]
+    fn test_backoff() {
+        for attempt in 1..=3 {
+            let max = Duration::from_millis(50) * 2_u32.pow(attempt - 1);
+            let delay = backoff(Duration::from_millis(50), attempt);
+            assert!(delay >= max / 2 && delay <= max, "{delay:?}");
+        }
+        assert_eq!(backoff(Duration::ZERO, 1), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_transaction_until_success() {
+        let attempts = AtomicU32::new(0);
+        let result = retry_transaction(&RETRY, "test", || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(database_error(DEADLOCK_DETECTED))
+            } else {
+                Ok(42)
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_transaction_gives_up() {
+        let attempts = AtomicU32::new(0);
+        let result = retry_transaction(&RETRY, "test", || async {
+            let _attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(database_error(SERIALIZATION_FAILURE))
+        })
+        .await;
+        assert!(matches!(result, Err(Error::InsertUser { .. })));
+        assert_eq!(attempts.load(Ordering::SeqCst), RETRY.max_attempts);
+
+        // other errors are not retried
+        attempts.store(0, Ordering::SeqCst);
+        let result = retry_transaction(&RETRY, "test", || async {
+            let _attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(database_error("23505"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
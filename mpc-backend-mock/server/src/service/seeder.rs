@@ -0,0 +1,261 @@
+use std::{str::FromStr, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use solana_sdk::signature::Signature;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{Chain, TransactionStatus, User},
+    service::{
+        error,
+        sql_executor::{TransactionSqlExecutor, UserSqlExecutor, WalletSqlExecutor},
+        user_directory::UserDirectory,
+        user_management::parse_email,
+    },
+};
+
+/// Longest address the `wallets` table stores
+const MAX_ADDRESS_LENGTH: usize = 128;
+
+/// Declarative description of the data of a demo environment
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixtures {
+    #[serde(default)]
+    pub users: Vec<UserFixture>,
+}
+
+/// User with its Keycloak account, Bitcoin addresses and Solana transactions
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserFixture {
+    pub email: String,
+
+    /// Password of the Keycloak account, the account has none if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// Whether the user is activated, with a verified email
+    #[serde(default = "UserFixture::default_active")]
+    pub active: bool,
+
+    #[serde(default)]
+    pub bitcoin_addresses: Vec<String>,
+
+    #[serde(default)]
+    pub transactions: Vec<TransactionFixture>,
+}
+
+impl UserFixture {
+    const fn default_active() -> bool { true }
+}
+
+/// Solana transaction submitted by the user
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionFixture {
+    /// First signature of the transaction, base58 encoded
+    pub signature: String,
+
+    #[serde(default = "TransactionFixture::default_status")]
+    pub status: TransactionStatus,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<i64>,
+
+    /// Reason the transaction failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TransactionFixture {
+    const fn default_status() -> TransactionStatus { TransactionStatus::Pending }
+}
+
+/// Records created by [`SeedService::seed`], records which already exist are
+/// counted as skipped
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SeedReport {
+    pub users_created: u64,
+    pub users_skipped: u64,
+    pub wallets_created: u64,
+    pub wallets_skipped: u64,
+    pub transactions_created: u64,
+    pub transactions_skipped: u64,
+}
+
+/// Seed service for populating Postgres and Keycloak from [`Fixtures`]
+///
+/// Seeding is idempotent: users are matched by email, wallets by address and
+/// transactions by signature, so the same fixtures can be applied again to
+/// top up an environment.
+#[derive(Clone)]
+pub struct SeedService {
+    db: PgPool,
+    user_directory: Arc<dyn UserDirectory>,
+}
+
+impl SeedService {
+    /// Create a new seed service
+    #[inline]
+    #[must_use]
+    pub const fn new(db: PgPool, user_directory: Arc<dyn UserDirectory>) -> Self {
+        Self { db, user_directory }
+    }
+
+    /// Create the users, Bitcoin addresses and transactions of `fixtures`
+    ///
+    /// All fixtures are validated before anything is written. The records of a
+    /// user are written in one database transaction, users seeded before a
+    /// failure are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A fixture is invalid, e.g. a malformed email or signature
+    /// - A Bitcoin address belongs to another user
+    /// - Keycloak rejects the password of a user
+    /// - Keycloak or database operation fails
+    pub async fn seed(&self, fixtures: &Fixtures) -> Result<SeedReport> {
+        let users = fixtures
+            .users
+            .iter()
+            .map(|fixture| Ok((parse_email(&fixture.email)?.into_inner(), validate(fixture)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut report = SeedReport::default();
+        for (email, fixture) in users {
+            self.seed_user(&email, fixture, &mut report).await?;
+        }
+
+        Ok(report)
+    }
+
+    async fn seed_user(
+        &self,
+        email: &str,
+        fixture: &UserFixture,
+        report: &mut SeedReport,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let user = match tx.get_user_by_email(email, None, true).await? {
+            Some(user) => {
+                report.users_skipped += 1;
+                user
+            }
+            None => {
+                let keycloak_user_id = self.ensure_account(email, fixture).await?;
+                let user = tx.insert_user(email, &keycloak_user_id, None, fixture.active).await?;
+                report.users_created += 1;
+                user
+            }
+        };
+        if let Some(password) = &fixture.password {
+            self.user_directory.set_password(&user.keycloak_user_id, password).await?;
+        }
+
+        for address in &fixture.bitcoin_addresses {
+            if seed_wallet(&mut tx, &user, address).await? {
+                report.wallets_created += 1;
+            } else {
+                report.wallets_skipped += 1;
+            }
+        }
+        for transaction in &fixture.transactions {
+            if seed_transaction(&mut tx, &user, transaction).await? {
+                report.transactions_created += 1;
+            } else {
+                report.transactions_skipped += 1;
+            }
+        }
+
+        tx.commit().await.context(error::CommitTransactionSnafu)?;
+        tracing::info!("Seeded user {} <{email}>", user.id);
+
+        Ok(())
+    }
+
+    /// Keycloak account of `email`, created unless a previous run left one
+    /// behind
+    async fn ensure_account(&self, email: &str, fixture: &UserFixture) -> Result<Uuid> {
+        let keycloak_user_id = match self.user_directory.find_by_email(email).await? {
+            Some(keycloak_user_id) => keycloak_user_id,
+            None => self.user_directory.create_user(email, None).await?,
+        };
+        self.user_directory.set_enabled(&keycloak_user_id, true).await?;
+        self.user_directory.set_email_verified(&keycloak_user_id, fixture.active).await?;
+
+        Ok(keycloak_user_id)
+    }
+}
+
+/// Check the addresses and transactions of a user
+fn validate(fixture: &UserFixture) -> Result<&UserFixture> {
+    let invalid =
+        |reason: String| Err(Error::InvalidFixture { email: fixture.email.clone(), reason });
+
+    for address in &fixture.bitcoin_addresses {
+        if address.is_empty() || address.len() > MAX_ADDRESS_LENGTH {
+            return invalid(format!(
+                "Bitcoin address `{address}` must be 1 to {MAX_ADDRESS_LENGTH} characters"
+            ));
+        }
+    }
+    for transaction in &fixture.transactions {
+        if Signature::from_str(&transaction.signature).is_err() {
+            return invalid(format!("`{}` is not a transaction signature", transaction.signature));
+        }
+    }
+
+    Ok(fixture)
+}
+
+/// Add the wallet of `address` to `user`, returning whether it is new
+async fn seed_wallet(conn: &mut PgConnection, user: &User, address: &str) -> Result<bool> {
+    match conn.get_wallet_by_address(Chain::Bitcoin, address).await? {
+        Some(wallet) if wallet.user_id == user.id => Ok(false),
+        Some(_) => Err(Error::InvalidFixture {
+            email: user.email.clone(),
+            reason: format!("Bitcoin address `{address}` belongs to another user"),
+        }),
+        None => {
+            let _wallet = conn.insert_wallet(&user.id, Chain::Bitcoin, address).await?;
+            Ok(true)
+        }
+    }
+}
+
+/// Record `transaction` as submitted by `user`, returning whether it is new
+async fn seed_transaction(
+    conn: &mut PgConnection,
+    user: &User,
+    transaction: &TransactionFixture,
+) -> Result<bool> {
+    let TransactionFixture { signature, status, slot, error } = transaction;
+
+    // a savepoint keeps the database transaction usable after a conflict
+    let mut savepoint =
+        sqlx::Connection::begin(&mut *conn).await.context(error::BeginTransactionSnafu)?;
+    let record = match savepoint.insert_transaction(&user.id, signature).await {
+        Err(Error::InsertTransaction { source })
+            if source.as_database_error().is_some_and(|e| e.is_unique_violation()) =>
+        {
+            savepoint.rollback().await.context(error::RollBackTransactionSnafu)?;
+            return Ok(false);
+        }
+        result => result?,
+    };
+
+    if *status != TransactionStatus::Pending {
+        let _transaction = savepoint
+            .update_transaction_status(&record.id, *status, *slot, error.as_deref())
+            .await?;
+    }
+    savepoint.commit().await.context(error::CommitTransactionSnafu)?;
+
+    Ok(true)
+}
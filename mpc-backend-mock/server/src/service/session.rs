@@ -0,0 +1,162 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, TimeDelta, Utc};
+use mpc_backend_mock_core::config::SessionConfig;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use snafu::ResultExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::Session,
+    service::{error, sql_executor::SessionSqlExecutor},
+};
+
+/// Tokens of a session which was just created, only their hashes are stored
+#[derive(Clone, Debug)]
+pub struct NewSession {
+    /// Value of the HttpOnly session cookie
+    pub token: String,
+
+    /// Token the browser echoes in the `X-CSRF-Token` header of mutating
+    /// requests
+    pub csrf_token: String,
+
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Session service for the cookie sessions of browsers
+///
+/// A session authenticates the requests of a browser in place of a bearer
+/// token. It is kept in the `sessions` table until it expires or its user logs
+/// out, its user is not asked again in the meantime.
+#[derive(Clone)]
+pub struct SessionService {
+    db: PgPool,
+
+    config: SessionConfig,
+}
+
+impl SessionService {
+    /// Create a new session service, sessions stay disabled until enabled by
+    /// [`SessionService::with_config`]
+    #[inline]
+    #[must_use]
+    pub fn new(db: PgPool) -> Self { Self { db, config: SessionConfig::default() } }
+
+    /// Create sessions as configured by `config`
+    #[must_use]
+    pub fn with_config(mut self, config: SessionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &SessionConfig { &self.config }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool { self.config.enable }
+
+    /// Create a session of the user with `user_id`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Sessions are not enabled
+    /// - Database operation fails
+    pub async fn create(&self, user_id: &Uuid) -> Result<NewSession> {
+        if !self.config.enable {
+            return Err(Error::SessionsDisabled);
+        }
+
+        let token = generate_token();
+        let csrf_token = generate_token();
+        let expires_at = Utc::now()
+            + TimeDelta::from_std(self.config.ttl).unwrap_or_else(|_| TimeDelta::days(1));
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        conn.insert_session(user_id, &hash_token(&token), &hash_token(&csrf_token), expires_at)
+            .await?;
+
+        Ok(NewSession { token, csrf_token, expires_at })
+    }
+
+    /// Session of the session cookie `token`, `None` if it is unknown or
+    /// expired
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn authenticate(&self, token: &str) -> Result<Option<Session>> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.get_session_by_token_hash(&hash_token(token)).await
+    }
+
+    /// End the session of the session cookie `token`, returns whether there
+    /// was one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn delete(&self, token: &str) -> Result<bool> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.delete_session_by_token_hash(&hash_token(token)).await
+    }
+
+    /// Delete expired sessions, returns the number of deleted sessions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn delete_expired(&self) -> Result<u64> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.delete_expired_sessions().await
+    }
+}
+
+/// Whether `csrf_token` is the CSRF token of `session`
+#[must_use]
+pub fn verify_csrf_token(session: &Session, csrf_token: &str) -> bool {
+    hash_token(csrf_token) == session.csrf_token_hash
+}
+
+/// Random URL safe token
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hex encoded SHA-256 hash of a session or CSRF token
+fn hash_token(token: &str) -> String { hex::encode(Sha256::digest(token.as_bytes())) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_csrf_token() {
+        let csrf_token = generate_token();
+        let session = Session {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            email: "user@example.com".to_string(),
+            keycloak_user_id: Uuid::new_v4(),
+            is_active: true,
+            csrf_token_hash: hash_token(&csrf_token),
+            expires_at: Utc::now(),
+        };
+
+        assert!(verify_csrf_token(&session, &csrf_token));
+        assert!(!verify_csrf_token(&session, &generate_token()));
+        assert!(!verify_csrf_token(&session, ""));
+        assert_eq!(csrf_token.len(), 43);
+    }
+}
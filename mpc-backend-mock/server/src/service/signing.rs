@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use mpc_backend_mock_core::config::SigningConfig;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use snafu::{OptionExt, ResultExt};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{SigningSession, SigningSessionStatus},
+    service::{
+        error,
+        sql_executor::{SigningSessionSqlExecutor, UserSqlExecutor},
+    },
+};
+
+/// Longest round a client may ask for
+const MAX_ROUND_DURATION: Duration = Duration::from_secs(60);
+
+/// Signing service simulating MPC signing ceremonies
+///
+/// Nothing is signed. A session moves from `round1` to `round2` and then to
+/// `complete` as each round of `round_duration` passes, and carries a fake
+/// signature derived from the session and its payload once complete. A session
+/// set to fail in a round, by the client or at random with the configured
+/// `failure_rate`, turns `failed` when that round is over instead. Sessions are
+/// advanced when they are read, so no background work is needed.
+#[derive(Clone)]
+pub struct SigningService {
+    db: PgPool,
+    config: SigningConfig,
+}
+
+impl SigningService {
+    /// Create a new signing service with the default simulation
+    #[must_use]
+    pub fn new(db: PgPool) -> Self { Self { db, config: SigningConfig::default() } }
+
+    /// Simulate the sessions as configured by `config`
+    #[must_use]
+    pub const fn with_config(mut self, config: SigningConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Start a signing session of `payload` for a user
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Payload is not hex encoded
+    /// - Round duration is too long or `fail_at` is not a round
+    /// - User not found
+    /// - Database operation fails
+    pub async fn create_session(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        payload: &str,
+        round_duration: Option<Duration>,
+        fail_at: Option<SigningSessionStatus>,
+    ) -> Result<SigningSession> {
+        let payload = hex::decode(payload)
+            .map(hex::encode)
+            .map_err(|_| Error::InvalidSigningSession { reason: "payload is not hex encoded" })?;
+        let round_duration = round_duration.unwrap_or(self.config.round_duration);
+        if round_duration > MAX_ROUND_DURATION {
+            return Err(Error::InvalidSigningSession {
+                reason: "round duration is longer than a minute",
+            });
+        }
+        let fail_at = match fail_at {
+            Some(round @ (SigningSessionStatus::Round1 | SigningSessionStatus::Round2)) => {
+                Some(round)
+            }
+            Some(SigningSessionStatus::Complete | SigningSessionStatus::Failed) => {
+                return Err(Error::InvalidSigningSession {
+                    reason: "a session can only fail in `round1` or `round2`",
+                });
+            }
+            None => self.random_failure(),
+        };
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user_id = get_user_id(&mut conn, keycloak_user_id, realm).await?;
+
+        let round_duration_ms = i64::try_from(round_duration.as_millis()).unwrap_or(i64::MAX);
+        let session =
+            conn.insert_signing_session(&user_id, &payload, round_duration_ms, fail_at).await?;
+
+        // a session without delay is over right away
+        advance(&mut conn, session).await
+    }
+
+    /// Get a signing session of a user, advanced to its current phase
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User or session not found
+    /// - Database operation fails
+    pub async fn get_session(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        session_id: &Uuid,
+    ) -> Result<SigningSession> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user_id = get_user_id(&mut conn, keycloak_user_id, realm).await?;
+
+        let session = conn
+            .get_signing_session_by_id(session_id, &user_id)
+            .await?
+            .context(error::SigningSessionNotFoundSnafu { session_id: *session_id })?;
+
+        advance(&mut conn, session).await
+    }
+
+    /// Round to fail a session in, drawn with the configured `failure_rate`
+    fn random_failure(&self) -> Option<SigningSessionStatus> {
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f64>() >= self.config.failure_rate {
+            return None;
+        }
+
+        Some(if rng.gen() { SigningSessionStatus::Round1 } else { SigningSessionStatus::Round2 })
+    }
+}
+
+async fn get_user_id(
+    conn: &mut PgConnection,
+    keycloak_user_id: &Uuid,
+    realm: Option<&str>,
+) -> Result<Uuid> {
+    conn.get_user_by_keycloak_id(keycloak_user_id, realm, false)
+        .await?
+        .map(|user| user.id)
+        .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })
+}
+
+/// Move `session` to the phase it has reached by now
+async fn advance(conn: &mut PgConnection, session: SigningSession) -> Result<SigningSession> {
+    if matches!(session.status, SigningSessionStatus::Complete | SigningSessionStatus::Failed) {
+        return Ok(session);
+    }
+
+    let elapsed = (Utc::now() - session.created_at).num_milliseconds().max(0);
+    // rounds without duration are all over at once
+    let elapsed_rounds = elapsed
+        .checked_div(session.round_duration_ms)
+        .map_or(u64::MAX, |rounds| u64::try_from(rounds).unwrap_or(0));
+    let status = simulated_status(elapsed_rounds, session.fail_at);
+    if status == session.status {
+        return Ok(session);
+    }
+
+    let (signature, failure) = match status {
+        SigningSessionStatus::Complete => (Some(fake_signature(&session)), None),
+        SigningSessionStatus::Failed => {
+            let round = if session.fail_at == Some(SigningSessionStatus::Round1) { 1 } else { 2 };
+            (None, Some(format!("Simulated failure of a party in round {round}")))
+        }
+        SigningSessionStatus::Round1 | SigningSessionStatus::Round2 => (None, None),
+    };
+    let updated = conn
+        .update_signing_session_status(
+            &session.id,
+            session.status,
+            status,
+            signature.as_deref(),
+            failure.as_deref(),
+        )
+        .await?;
+
+    match updated {
+        Some(session) => Ok(session),
+        // advanced by a concurrent read
+        None => conn
+            .get_signing_session_by_id(&session.id, &session.user_id)
+            .await?
+            .context(error::SigningSessionNotFoundSnafu { session_id: session.id }),
+    }
+}
+
+/// Phase of a session `elapsed_rounds` rounds after it was created
+fn simulated_status(
+    elapsed_rounds: u64,
+    fail_at: Option<SigningSessionStatus>,
+) -> SigningSessionStatus {
+    let rounds = [SigningSessionStatus::Round1, SigningSessionStatus::Round2];
+    for (index, round) in (0_u64..).zip(rounds) {
+        if elapsed_rounds <= index {
+            return round;
+        }
+        if fail_at == Some(round) {
+            return SigningSessionStatus::Failed;
+        }
+    }
+
+    SigningSessionStatus::Complete
+}
+
+/// Fake 64-byte signature, the same for a session every time it is derived
+fn fake_signature(session: &SigningSession) -> String {
+    let half = |tag: &[u8]| {
+        Sha256::new()
+            .chain_update(tag)
+            .chain_update(session.id.as_bytes())
+            .chain_update(session.payload.as_bytes())
+            .finalize()
+    };
+
+    hex::encode([half(b"r").as_slice(), half(b"s").as_slice()].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_status() {
+        use SigningSessionStatus::{Complete, Failed, Round1, Round2};
+
+        assert_eq!(simulated_status(0, None), Round1);
+        assert_eq!(simulated_status(1, None), Round2);
+        assert_eq!(simulated_status(2, None), Complete);
+        assert_eq!(simulated_status(u64::MAX, None), Complete);
+
+        assert_eq!(simulated_status(0, Some(Round1)), Round1);
+        assert_eq!(simulated_status(1, Some(Round1)), Failed);
+        assert_eq!(simulated_status(1, Some(Round2)), Round2);
+        assert_eq!(simulated_status(2, Some(Round2)), Failed);
+    }
+}
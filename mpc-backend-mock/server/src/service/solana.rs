@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_sdk::pubkey::Pubkey;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{SolanaAccount, SolanaBalance},
+    service::chain::{AtSlot, SolanaChain},
+};
+
+/// Solana service for reading accounts with the configured commitment level
+#[derive(Clone)]
+pub struct SolanaService {
+    chain: Arc<dyn SolanaChain>,
+}
+
+impl SolanaService {
+    /// Create a new Solana service
+    #[inline]
+    #[must_use]
+    pub const fn new(chain: Arc<dyn SolanaChain>) -> Self { Self { chain } }
+
+    /// Get the SOL balance of an account
+    ///
+    /// An account which does not exist has a balance of zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Solana RPC request fails
+    pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<SolanaBalance> {
+        let commitment = self.chain.commitment();
+        let AtSlot { slot, value: lamports } = self.chain.get_balance(pubkey).await?;
+
+        Ok(SolanaBalance {
+            pubkey: pubkey.to_string(),
+            lamports: lamports.into(),
+            slot,
+            commitment: commitment.commitment.to_string(),
+        })
+    }
+
+    /// Get an account
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Account does not exist
+    /// - Solana RPC request fails
+    pub async fn get_account(&self, pubkey: &Pubkey) -> Result<SolanaAccount> {
+        let commitment = self.chain.commitment();
+        let AtSlot { slot, value: account } = self.chain.get_account(pubkey).await?;
+
+        let account = account.ok_or(Error::SolanaAccountNotFound { pubkey: *pubkey })?;
+
+        Ok(SolanaAccount {
+            pubkey: pubkey.to_string(),
+            lamports: account.lamports.into(),
+            owner: account.owner.to_string(),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data: BASE64.encode(&account.data),
+            slot,
+            commitment: commitment.commitment.to_string(),
+        })
+    }
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::service::error::{self, Result};
+
+#[async_trait]
+pub trait ActivationTokenSqlExecutor {
+    async fn insert_activation_token(
+        &mut self,
+        user_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Mark the token as used, returns the ID of the user it activates or
+    /// `None` if the token is unknown, expired or already used
+    async fn consume_activation_token(&mut self, token_hash: &str) -> Result<Option<Uuid>>;
+
+    async fn delete_expired_activation_tokens(&mut self) -> Result<u64>;
+}
+
+#[async_trait]
+impl<E> ActivationTokenSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_activation_token(
+        &mut self,
+        user_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/activation_token/insert_activation_token.sql",
+            user_id,
+            token_hash,
+            expires_at
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::InsertActivationTokenSnafu)?;
+
+        Ok(())
+    }
+
+    async fn consume_activation_token(&mut self, token_hash: &str) -> Result<Option<Uuid>> {
+        let user_id = sqlx::query_file_scalar!(
+            "sql/activation_token/consume_activation_token.sql",
+            token_hash
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::ConsumeActivationTokenSnafu)?;
+
+        Ok(user_id)
+    }
+
+    async fn delete_expired_activation_tokens(&mut self) -> Result<u64> {
+        let result = sqlx::query_file!("sql/activation_token/delete_expired_activation_tokens.sql")
+            .execute(&mut *self)
+            .await
+            .context(error::DeleteExpiredActivationTokensSnafu)?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::Annotation,
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait AnnotationSqlExecutor {
+    async fn insert_annotation(
+        &mut self,
+        user_id: Option<&Uuid>,
+        transaction_id: Option<&Uuid>,
+        note: &str,
+        flags: &[String],
+    ) -> Result<Annotation>;
+
+    async fn list_annotations_by_user_id(&mut self, user_id: &Uuid) -> Result<Vec<Annotation>>;
+
+    async fn list_annotations_by_transaction_id(
+        &mut self,
+        transaction_id: &Uuid,
+    ) -> Result<Vec<Annotation>>;
+
+    async fn delete_annotation_by_id(&mut self, annotation_id: &Uuid) -> Result<u64>;
+}
+
+#[async_trait]
+impl<E> AnnotationSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_annotation(
+        &mut self,
+        user_id: Option<&Uuid>,
+        transaction_id: Option<&Uuid>,
+        note: &str,
+        flags: &[String],
+    ) -> Result<Annotation> {
+        let annotation = sqlx::query_file_as!(
+            Annotation,
+            "sql/annotation/insert_annotation.sql",
+            user_id,
+            transaction_id,
+            note,
+            flags
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertAnnotationSnafu)?;
+
+        Ok(annotation)
+    }
+
+    async fn list_annotations_by_user_id(&mut self, user_id: &Uuid) -> Result<Vec<Annotation>> {
+        let annotations = sqlx::query_file_as!(
+            Annotation,
+            "sql/annotation/list_annotations_by_user_id.sql",
+            user_id
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListAnnotationsSnafu)?;
+
+        Ok(annotations)
+    }
+
+    async fn list_annotations_by_transaction_id(
+        &mut self,
+        transaction_id: &Uuid,
+    ) -> Result<Vec<Annotation>> {
+        let annotations = sqlx::query_file_as!(
+            Annotation,
+            "sql/annotation/list_annotations_by_transaction_id.sql",
+            transaction_id
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListAnnotationsSnafu)?;
+
+        Ok(annotations)
+    }
+
+    async fn delete_annotation_by_id(&mut self, annotation_id: &Uuid) -> Result<u64> {
+        let result = sqlx::query_file!("sql/annotation/delete_annotation_by_id.sql", annotation_id)
+            .execute(&mut *self)
+            .await
+            .context(error::DeleteAnnotationSnafu)?;
+
+        Ok(result.rows_affected())
+    }
+}
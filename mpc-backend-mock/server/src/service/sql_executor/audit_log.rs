@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use chrono::NaiveTime;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{AuditLog, ListAuditLogsFilter},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait AuditLogSqlExecutor {
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_audit_log(
+        &mut self,
+        actor_user_id: Option<&Uuid>,
+        action: &str,
+        target_type: &str,
+        target_id: Option<&Uuid>,
+        ip_address: Option<&str>,
+        request_id: Option<&str>,
+        payload: &serde_json::Value,
+    ) -> Result<AuditLog>;
+
+    async fn list_audit_logs(
+        &mut self,
+        filter: &ListAuditLogsFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AuditLog>>;
+
+    async fn count_audit_logs(&mut self, filter: &ListAuditLogsFilter) -> Result<i64>;
+}
+
+#[async_trait]
+impl<E> AuditLogSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_audit_log(
+        &mut self,
+        actor_user_id: Option<&Uuid>,
+        action: &str,
+        target_type: &str,
+        target_id: Option<&Uuid>,
+        ip_address: Option<&str>,
+        request_id: Option<&str>,
+        payload: &serde_json::Value,
+    ) -> Result<AuditLog> {
+        let log = sqlx::query_file_as!(
+            AuditLog,
+            "sql/audit_log/insert_audit_log.sql",
+            actor_user_id,
+            action,
+            target_type,
+            target_id,
+            ip_address,
+            request_id,
+            payload
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertAuditLogSnafu)?;
+
+        Ok(log)
+    }
+
+    async fn list_audit_logs(
+        &mut self,
+        filter: &ListAuditLogsFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AuditLog>> {
+        let created_after =
+            filter.created_after.map(|date| date.and_time(NaiveTime::MIN).and_utc());
+
+        let logs = sqlx::query_file_as!(
+            AuditLog,
+            "sql/audit_log/list_audit_logs.sql",
+            filter.action,
+            filter.actor_user_id,
+            filter.target_id,
+            created_after,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListAuditLogsSnafu)?;
+
+        Ok(logs)
+    }
+
+    async fn count_audit_logs(&mut self, filter: &ListAuditLogsFilter) -> Result<i64> {
+        let created_after =
+            filter.created_after.map(|date| date.and_time(NaiveTime::MIN).and_utc());
+
+        let count = sqlx::query_file_scalar!(
+            "sql/audit_log/count_audit_logs.sql",
+            filter.action,
+            filter.actor_user_id,
+            filter.target_id,
+            created_after
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::CountAuditLogsSnafu)?;
+
+        Ok(count)
+    }
+}
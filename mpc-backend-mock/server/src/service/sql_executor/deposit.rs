@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use mpc_backend_mock_core::model::TokenAmount;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{Deposit, DepositStatus},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait DepositSqlExecutor {
+    async fn insert_deposit(
+        &mut self,
+        wallet_id: &Uuid,
+        tx_id: &str,
+        amount: TokenAmount,
+    ) -> Result<Deposit>;
+
+    async fn get_deposit_by_id(&mut self, deposit_id: &Uuid) -> Result<Option<Deposit>>;
+
+    async fn list_deposits_by_wallet_id(
+        &mut self,
+        wallet_id: &Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Deposit>>;
+
+    async fn update_deposit_status(
+        &mut self,
+        deposit_id: &Uuid,
+        status: DepositStatus,
+        block_height: Option<i64>,
+    ) -> Result<Option<Deposit>>;
+}
+
+#[async_trait]
+impl<E> DepositSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_deposit(
+        &mut self,
+        wallet_id: &Uuid,
+        tx_id: &str,
+        amount: TokenAmount,
+    ) -> Result<Deposit> {
+        let deposit = sqlx::query_file_as!(
+            Deposit,
+            "sql/deposit/insert_deposit.sql",
+            wallet_id,
+            tx_id,
+            amount as TokenAmount
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertDepositSnafu)?;
+
+        Ok(deposit)
+    }
+
+    async fn get_deposit_by_id(&mut self, deposit_id: &Uuid) -> Result<Option<Deposit>> {
+        let deposit =
+            sqlx::query_file_as!(Deposit, "sql/deposit/get_deposit_by_id.sql", deposit_id)
+                .fetch_optional(&mut *self)
+                .await
+                .context(error::GetDepositByIdSnafu)?;
+
+        Ok(deposit)
+    }
+
+    async fn list_deposits_by_wallet_id(
+        &mut self,
+        wallet_id: &Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Deposit>> {
+        let deposits = sqlx::query_file_as!(
+            Deposit,
+            "sql/deposit/list_deposits_by_wallet_id.sql",
+            wallet_id,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListDepositsSnafu)?;
+
+        Ok(deposits)
+    }
+
+    async fn update_deposit_status(
+        &mut self,
+        deposit_id: &Uuid,
+        status: DepositStatus,
+        block_height: Option<i64>,
+    ) -> Result<Option<Deposit>> {
+        let deposit = sqlx::query_file_as!(
+            Deposit,
+            "sql/deposit/update_deposit_status.sql",
+            deposit_id,
+            status as DepositStatus,
+            block_height
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::UpdateDepositStatusSnafu)?;
+
+        Ok(deposit)
+    }
+}
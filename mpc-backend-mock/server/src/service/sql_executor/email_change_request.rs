@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::service::error::{self, Result};
+
+#[async_trait]
+pub trait EmailChangeRequestSqlExecutor {
+    /// Stage `new_email` for the user, the pending changes of the user are
+    /// replaced
+    async fn insert_email_change_request(
+        &mut self,
+        user_id: &Uuid,
+        new_email: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Mark the change as confirmed, returns the ID of the user and the new
+    /// email or `None` if the token is unknown, expired or already used
+    async fn consume_email_change_request(
+        &mut self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, String)>>;
+
+    async fn delete_expired_email_change_requests(&mut self) -> Result<u64>;
+}
+
+#[async_trait]
+impl<E> EmailChangeRequestSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_email_change_request(
+        &mut self,
+        user_id: &Uuid,
+        new_email: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/email_change_request/insert_email_change_request.sql",
+            user_id,
+            new_email,
+            token_hash,
+            expires_at
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::InsertEmailChangeRequestSnafu)?;
+
+        Ok(())
+    }
+
+    async fn consume_email_change_request(
+        &mut self,
+        token_hash: &str,
+    ) -> Result<Option<(Uuid, String)>> {
+        let request = sqlx::query_file!(
+            "sql/email_change_request/consume_email_change_request.sql",
+            token_hash
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::ConsumeEmailChangeRequestSnafu)?;
+
+        Ok(request.map(|request| (request.user_id, request.new_email)))
+    }
+
+    async fn delete_expired_email_change_requests(&mut self) -> Result<u64> {
+        let result =
+            sqlx::query_file!("sql/email_change_request/delete_expired_email_change_requests.sql")
+                .execute(&mut *self)
+                .await
+                .context(error::DeleteExpiredEmailChangeRequestsSnafu)?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{EventOffset, OutboxEvent},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait EventOutboxSqlExecutor {
+    async fn insert_event(
+        &mut self,
+        kind: &str,
+        aggregate_id: &Uuid,
+        payload: &serde_json::Value,
+    ) -> Result<Uuid>;
+
+    async fn lock_publisher_offset(&mut self, publisher: &str) -> Result<EventOffset>;
+
+    async fn list_publishable_events(
+        &mut self,
+        offset: &EventOffset,
+        limit: i64,
+    ) -> Result<Vec<OutboxEvent>>;
+
+    async fn update_publisher_offset(
+        &mut self,
+        publisher: &str,
+        offset: &EventOffset,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+impl<E> EventOutboxSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_event(
+        &mut self,
+        kind: &str,
+        aggregate_id: &Uuid,
+        payload: &serde_json::Value,
+    ) -> Result<Uuid> {
+        let event_id = sqlx::query_file_scalar!(
+            "sql/event_outbox/insert_event.sql",
+            kind,
+            aggregate_id,
+            payload
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertEventSnafu)?;
+
+        Ok(event_id)
+    }
+
+    async fn lock_publisher_offset(&mut self, publisher: &str) -> Result<EventOffset> {
+        let offset = sqlx::query_file_as!(
+            EventOffset,
+            "sql/event_outbox/lock_publisher_offset.sql",
+            publisher
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::LockPublisherOffsetSnafu)?;
+
+        Ok(offset)
+    }
+
+    async fn list_publishable_events(
+        &mut self,
+        offset: &EventOffset,
+        limit: i64,
+    ) -> Result<Vec<OutboxEvent>> {
+        let events = sqlx::query_file_as!(
+            OutboxEvent,
+            "sql/event_outbox/list_publishable_events.sql",
+            offset.last_transaction_id,
+            offset.last_sequence,
+            limit
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListEventsSnafu)?;
+
+        Ok(events)
+    }
+
+    async fn update_publisher_offset(
+        &mut self,
+        publisher: &str,
+        offset: &EventOffset,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/event_outbox/update_publisher_offset.sql",
+            publisher,
+            offset.last_transaction_id,
+            offset.last_sequence
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::UpdatePublisherOffsetSnafu)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{Chain, Key, KeyCurve},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait KeySqlExecutor {
+    /// Insert the key of the user on `chain`, returns the existing key if the
+    /// user already has one there
+    async fn insert_key(
+        &mut self,
+        user_id: &Uuid,
+        chain: Chain,
+        curve: KeyCurve,
+        public_key: &str,
+    ) -> Result<Key>;
+
+    async fn get_key_by_id(&mut self, key_id: &Uuid, user_id: &Uuid) -> Result<Option<Key>>;
+}
+
+#[async_trait]
+impl<E> KeySqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_key(
+        &mut self,
+        user_id: &Uuid,
+        chain: Chain,
+        curve: KeyCurve,
+        public_key: &str,
+    ) -> Result<Key> {
+        let key = sqlx::query_file_as!(
+            Key,
+            "sql/key/insert_key.sql",
+            user_id,
+            chain as Chain,
+            curve as KeyCurve,
+            public_key
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertKeySnafu)?;
+
+        Ok(key)
+    }
+
+    async fn get_key_by_id(&mut self, key_id: &Uuid, user_id: &Uuid) -> Result<Option<Key>> {
+        let key = sqlx::query_file_as!(Key, "sql/key/get_key_by_id.sql", key_id, user_id)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::GetKeyByIdSnafu)?;
+
+        Ok(key)
+    }
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+
+use crate::{
+    entity::LoginAttempt,
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait LoginAttemptSqlExecutor {
+    async fn get_login_attempt(&mut self, email: &str) -> Result<Option<LoginAttempt>>;
+
+    /// Count a failed login, starting over once `failure_window_seconds` have
+    /// passed since the first counted one
+    async fn record_failed_login(
+        &mut self,
+        email: &str,
+        ip_address: Option<&str>,
+        failure_window_seconds: f64,
+    ) -> Result<LoginAttempt>;
+
+    /// Lock the email for `lockout_seconds`, or until it is unlocked if
+    /// `None`, returns `None` if it is already locked
+    async fn lock_login(
+        &mut self,
+        email: &str,
+        lockout_seconds: Option<f64>,
+    ) -> Result<Option<LoginAttempt>>;
+
+    async fn delete_login_attempt(&mut self, email: &str) -> Result<Option<LoginAttempt>>;
+
+    async fn list_locked_logins(&mut self) -> Result<Vec<LoginAttempt>>;
+
+    /// Delete ended lockouts and failed logins whose failure window has
+    /// passed
+    async fn delete_expired_login_attempts(
+        &mut self,
+        failure_window_seconds: f64,
+    ) -> Result<Vec<LoginAttempt>>;
+}
+
+#[async_trait]
+impl<E> LoginAttemptSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn get_login_attempt(&mut self, email: &str) -> Result<Option<LoginAttempt>> {
+        sqlx::query_file_as!(LoginAttempt, "sql/login_attempt/get_login_attempt.sql", email)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::GetLoginAttemptSnafu)
+    }
+
+    async fn record_failed_login(
+        &mut self,
+        email: &str,
+        ip_address: Option<&str>,
+        failure_window_seconds: f64,
+    ) -> Result<LoginAttempt> {
+        sqlx::query_file_as!(
+            LoginAttempt,
+            "sql/login_attempt/record_failed_login.sql",
+            email,
+            ip_address,
+            failure_window_seconds
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::RecordFailedLoginSnafu)
+    }
+
+    async fn lock_login(
+        &mut self,
+        email: &str,
+        lockout_seconds: Option<f64>,
+    ) -> Result<Option<LoginAttempt>> {
+        sqlx::query_file_as!(
+            LoginAttempt,
+            "sql/login_attempt/lock_login.sql",
+            email,
+            lockout_seconds
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::LockLoginSnafu)
+    }
+
+    async fn delete_login_attempt(&mut self, email: &str) -> Result<Option<LoginAttempt>> {
+        sqlx::query_file_as!(LoginAttempt, "sql/login_attempt/delete_login_attempt.sql", email)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::DeleteLoginAttemptSnafu)
+    }
+
+    async fn list_locked_logins(&mut self) -> Result<Vec<LoginAttempt>> {
+        sqlx::query_file_as!(LoginAttempt, "sql/login_attempt/list_locked_logins.sql")
+            .fetch_all(&mut *self)
+            .await
+            .context(error::ListLockedLoginsSnafu)
+    }
+
+    async fn delete_expired_login_attempts(
+        &mut self,
+        failure_window_seconds: f64,
+    ) -> Result<Vec<LoginAttempt>> {
+        sqlx::query_file_as!(
+            LoginAttempt,
+            "sql/login_attempt/delete_expired_login_attempts.sql",
+            failure_window_seconds
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::DeleteExpiredLoginAttemptsSnafu)
+    }
+}
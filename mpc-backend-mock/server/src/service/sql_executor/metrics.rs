@@ -0,0 +1,406 @@
+//! Postgres connection pool and query metrics.
+//!
+//! [`PgPoolMetrics`] samples the pool every [`POOL_SAMPLE_INTERVAL`] into the
+//! `db_pool_connections` gauge and times a connection acquire as a probe of
+//! the `db_pool_acquire_duration_seconds` histogram. [`QueryMetrics`] wraps a
+//! connection in [`InstrumentedUserSqlExecutor`], which observes every
+//! [`UserSqlExecutor`] query in the `db_query_duration_seconds` histogram,
+//! labeled by query name and result. Queries slower than the slow query
+//! threshold are also logged and counted in `db_slow_queries_total`.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use mpc_backend_mock_core::model::{Fields, Sort};
+use prometheus::{Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts};
+use snafu::ResultExt;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use zeus_metrics::DefaultMetrics;
+
+use super::UserSqlExecutor;
+use crate::{
+    entity::{
+        ListUsersFilter, UpdateUserPreferencesRequest, UpdateUserProfileRequest, User, UserField,
+        UserPreferences, UserProfile, UserSortColumn,
+    },
+    error as crate_error,
+    service::error::Result,
+};
+
+/// How often the pool is sampled
+pub const POOL_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Connection pool statistics
+#[derive(Clone, Debug)]
+pub struct PgPoolMetrics {
+    pool: PgPool,
+    connections: IntGaugeVec,
+    acquire_duration: Histogram,
+}
+
+impl PgPoolMetrics {
+    /// # Errors
+    ///
+    /// Returns an error if the metrics cannot be registered
+    pub fn new(pool: PgPool, metrics: &DefaultMetrics) -> crate_error::Result<Self> {
+        let connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Number of Postgres pool connections by state"),
+            &["state"],
+        )
+        .context(crate_error::CreateDatabaseMetricsSnafu)?;
+        let acquire_duration = Histogram::with_opts(HistogramOpts::new(
+            "db_pool_acquire_duration_seconds",
+            "Time to acquire a Postgres connection from the pool, sampled periodically",
+        ))
+        .context(crate_error::CreateDatabaseMetricsSnafu)?;
+
+        metrics.register(Box::new(connections.clone()))?;
+        metrics.register(Box::new(acquire_duration.clone()))?;
+
+        Ok(Self { pool, connections, acquire_duration })
+    }
+
+    /// Sample the pool every [`POOL_SAMPLE_INTERVAL`], forever
+    pub async fn record_samples(self) {
+        let mut interval = tokio::time::interval(POOL_SAMPLE_INTERVAL);
+        loop {
+            let _instant = interval.tick().await;
+            self.record_sample().await;
+        }
+    }
+
+    async fn record_sample(&self) {
+        let size = i64::from(self.pool.size());
+        let idle = i64::try_from(self.pool.num_idle()).unwrap_or(i64::MAX);
+        self.connections.with_label_values(&["idle"]).set(idle);
+        self.connections.with_label_values(&["active"]).set(size.saturating_sub(idle).max(0));
+
+        let started_at = Instant::now();
+        match self.pool.acquire().await {
+            Ok(conn) => {
+                self.acquire_duration.observe(started_at.elapsed().as_secs_f64());
+                drop(conn);
+            }
+            Err(err) => tracing::warn!("Failed to acquire Postgres connection, error: {err}"),
+        }
+    }
+}
+
+/// Duration of the queries run through [`InstrumentedUserSqlExecutor`]
+#[derive(Clone, Debug)]
+pub struct QueryMetrics {
+    queries: HistogramVec,
+    slow_queries: IntCounterVec,
+    slow_query_threshold: Option<Duration>,
+}
+
+impl QueryMetrics {
+    /// # Errors
+    ///
+    /// Returns an error if the metrics cannot be registered
+    pub fn new(metrics: &DefaultMetrics) -> crate_error::Result<Self> {
+        let queries = HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Postgres query latency in seconds"),
+            &["query", "result"],
+        )
+        .context(crate_error::CreateDatabaseMetricsSnafu)?;
+        let slow_queries = IntCounterVec::new(
+            Opts::new(
+                "db_slow_queries_total",
+                "Number of Postgres queries above the slow threshold",
+            ),
+            &["query"],
+        )
+        .context(crate_error::CreateDatabaseMetricsSnafu)?;
+
+        metrics.register(Box::new(queries.clone()))?;
+        metrics.register(Box::new(slow_queries.clone()))?;
+
+        Ok(Self { queries, slow_queries, slow_query_threshold: None })
+    }
+
+    /// Log and count the queries running longer than `threshold`, `None`
+    /// disables it
+    #[must_use]
+    pub const fn with_slow_query_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.slow_query_threshold = threshold;
+        self
+    }
+
+    /// Run the user queries on `conn` and observe them
+    #[must_use]
+    pub const fn instrument<'a>(
+        &'a self,
+        conn: &'a mut PgConnection,
+    ) -> InstrumentedUserSqlExecutor<'a> {
+        InstrumentedUserSqlExecutor { conn, metrics: self }
+    }
+
+    async fn observe<T, F>(&self, query: &str, future: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>> + Send,
+    {
+        let started_at = Instant::now();
+        let result = future.await;
+
+        let elapsed = started_at.elapsed();
+        let outcome = if result.is_ok() { "ok" } else { "error" };
+        self.queries.with_label_values(&[query, outcome]).observe(elapsed.as_secs_f64());
+
+        if self.slow_query_threshold.is_some_and(|threshold| elapsed > threshold) {
+            tracing::warn!("Slow query {query} took {elapsed:?} ({outcome})");
+            self.slow_queries.with_label_values(&[query]).inc();
+        }
+
+        result
+    }
+}
+
+/// [`UserSqlExecutor`] observing every query in [`QueryMetrics`]
+pub struct InstrumentedUserSqlExecutor<'a> {
+    conn: &'a mut PgConnection,
+    metrics: &'a QueryMetrics,
+}
+
+#[async_trait]
+impl UserSqlExecutor for InstrumentedUserSqlExecutor<'_> {
+    async fn get_user_by_email(
+        &mut self,
+        email: &str,
+        realm: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<Option<User>> {
+        self.metrics
+            .observe(
+                "get_user_by_email",
+                self.conn.get_user_by_email(email, realm, include_deleted),
+            )
+            .await
+    }
+
+    async fn insert_user(
+        &mut self,
+        email: &str,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        is_active: bool,
+    ) -> Result<User> {
+        self.metrics
+            .observe(
+                "insert_user",
+                self.conn.insert_user(email, keycloak_user_id, realm, is_active),
+            )
+            .await
+    }
+
+    async fn get_user_by_id(
+        &mut self,
+        user_id: &Uuid,
+        include_deleted: bool,
+    ) -> Result<Option<User>> {
+        self.metrics
+            .observe("get_user_by_id", self.conn.get_user_by_id(user_id, include_deleted))
+            .await
+    }
+
+    async fn soft_delete_user_by_id(&mut self, user_id: &Uuid) -> Result<()> {
+        self.metrics
+            .observe("soft_delete_user_by_id", self.conn.soft_delete_user_by_id(user_id))
+            .await
+    }
+
+    async fn restore_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>> {
+        self.metrics.observe("restore_user_by_id", self.conn.restore_user_by_id(user_id)).await
+    }
+
+    async fn activate_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>> {
+        self.metrics.observe("activate_user_by_id", self.conn.activate_user_by_id(user_id)).await
+    }
+
+    async fn update_user_email(&mut self, user_id: &Uuid, email: &str) -> Result<Option<User>> {
+        self.metrics.observe("update_user_email", self.conn.update_user_email(user_id, email)).await
+    }
+
+    async fn get_user_by_keycloak_id(
+        &mut self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<Option<User>> {
+        self.metrics
+            .observe(
+                "get_user_by_keycloak_id",
+                self.conn.get_user_by_keycloak_id(keycloak_user_id, realm, include_deleted),
+            )
+            .await
+    }
+
+    async fn list_users(
+        &mut self,
+        realm: Option<&str>,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>> {
+        self.metrics
+            .observe("list_users", self.conn.list_users(realm, filter, sort, limit, offset))
+            .await
+    }
+
+    async fn list_user_fields(
+        &mut self,
+        realm: Option<&str>,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        fields: &Fields<UserField>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>> {
+        self.metrics
+            .observe(
+                "list_user_fields",
+                self.conn.list_user_fields(realm, filter, sort, fields, limit, offset),
+            )
+            .await
+    }
+
+    async fn count_users(&mut self, realm: Option<&str>, filter: &ListUsersFilter) -> Result<i64> {
+        self.metrics.observe("count_users", self.conn.count_users(realm, filter)).await
+    }
+
+    async fn list_users_by_email_pattern(
+        &mut self,
+        email_like: &str,
+        created_before: Option<DateTime<Utc>>,
+        realm: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>> {
+        self.metrics
+            .observe(
+                "list_users_by_email_pattern",
+                self.conn.list_users_by_email_pattern(
+                    email_like,
+                    created_before,
+                    realm,
+                    limit,
+                    offset,
+                ),
+            )
+            .await
+    }
+
+    async fn delete_users_by_ids(&mut self, user_ids: &[Uuid]) -> Result<u64> {
+        self.metrics.observe("delete_users_by_ids", self.conn.delete_users_by_ids(user_ids)).await
+    }
+
+    async fn list_users_by_keycloak_ids(
+        &mut self,
+        keycloak_user_ids: &[Uuid],
+    ) -> Result<Vec<User>> {
+        self.metrics
+            .observe(
+                "list_users_by_keycloak_ids",
+                self.conn.list_users_by_keycloak_ids(keycloak_user_ids),
+            )
+            .await
+    }
+
+    fn export_users<'a>(&'a mut self, realm: Option<&'a str>) -> BoxStream<'a, Result<User>> {
+        // a stream outlives a single observation, it is passed on as is
+        self.conn.export_users(realm)
+    }
+
+    async fn list_users_not_in_keycloak_ids(
+        &mut self,
+        keycloak_user_ids: &[Uuid],
+        created_before: DateTime<Utc>,
+    ) -> Result<Vec<User>> {
+        self.metrics
+            .observe(
+                "list_users_not_in_keycloak_ids",
+                self.conn.list_users_not_in_keycloak_ids(keycloak_user_ids, created_before),
+            )
+            .await
+    }
+
+    async fn update_user(
+        &mut self,
+        user_id: &Uuid,
+        update: &UpdateUserProfileRequest,
+    ) -> Result<UserProfile> {
+        self.metrics.observe("update_user", self.conn.update_user(user_id, update)).await
+    }
+
+    async fn get_user_profile(&mut self, user_id: &Uuid) -> Result<Option<UserProfile>> {
+        self.metrics.observe("get_user_profile", self.conn.get_user_profile(user_id)).await
+    }
+
+    async fn get_user_preferences(&mut self, user_id: &Uuid) -> Result<Option<UserPreferences>> {
+        self.metrics.observe("get_user_preferences", self.conn.get_user_preferences(user_id)).await
+    }
+
+    async fn replace_user_preferences(
+        &mut self,
+        user_id: &Uuid,
+        preferences: &UpdateUserPreferencesRequest,
+    ) -> Result<UserPreferences> {
+        self.metrics
+            .observe(
+                "replace_user_preferences",
+                self.conn.replace_user_preferences(user_id, preferences),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::error::Error;
+
+    #[tokio::test]
+    async fn test_observe_query() {
+        let metrics = QueryMetrics::new(&DefaultMetrics::new()).unwrap();
+
+        let user_id = Uuid::nil();
+        assert_eq!(metrics.observe("get_user_by_id", async { Ok(1) }).await.unwrap(), 1);
+        assert!(metrics
+            .observe::<(), _>("get_user_by_id", async { Err(Error::UserNotFound { user_id }) })
+            .await
+            .is_err());
+
+        let count = |outcome: &str| {
+            metrics.queries.with_label_values(&["get_user_by_id", outcome]).get_sample_count()
+        };
+        assert_eq!(count("ok"), 1);
+        assert_eq!(count("error"), 1);
+        // no threshold, nothing is slow
+        assert_eq!(metrics.slow_queries.with_label_values(&["get_user_by_id"]).get(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_observe_slow_query() {
+        let metrics = QueryMetrics::new(&DefaultMetrics::new())
+            .unwrap()
+            .with_slow_query_threshold(Some(Duration::from_millis(100)));
+
+        let query = |delay: u64| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(())
+        };
+        metrics.observe("get_user_by_id", query(10)).await.unwrap();
+        metrics.observe("list_users", query(500)).await.unwrap();
+
+        let slow = |query: &str| metrics.slow_queries.with_label_values(&[query]).get();
+        assert_eq!(slow("get_user_by_id"), 0);
+        assert_eq!(slow("list_users"), 1);
+    }
+}
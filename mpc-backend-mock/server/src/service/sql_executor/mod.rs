@@ -1,3 +1,48 @@
 // include the sql interaction interface for different modules
+mod activation_token;
+mod annotation;
+mod audit_log;
+mod email_change_request;
+mod event_outbox;
+mod key;
+mod login_attempt;
+mod metrics;
+mod notification;
+mod openapi_baseline;
+mod session;
+mod signing_session;
+mod transaction;
 mod user;
-pub use user::UserSqlExecutor;
+mod wallet_balance_snapshot;
+mod webhook;
+pub use self::{
+    activation_token::ActivationTokenSqlExecutor,
+    annotation::AnnotationSqlExecutor,
+    audit_log::AuditLogSqlExecutor,
+    email_change_request::EmailChangeRequestSqlExecutor,
+    event_outbox::EventOutboxSqlExecutor,
+    key::KeySqlExecutor,
+    login_attempt::LoginAttemptSqlExecutor,
+    metrics::{PgPoolMetrics, QueryMetrics},
+    notification::NotificationSqlExecutor,
+    openapi_baseline::OpenApiBaselineSqlExecutor,
+    session::SessionSqlExecutor,
+    signing_session::SigningSessionSqlExecutor,
+    transaction::TransactionSqlExecutor,
+    user::UserSqlExecutor,
+    wallet_balance_snapshot::WalletBalanceSnapshotSqlExecutor,
+    webhook::WebhookSqlExecutor,
+};
+
+// FIXME: drop the `allow`s once the wallet, deposit and withdrawal services
+// use these executors
+#[allow(dead_code)]
+mod deposit;
+#[allow(dead_code)]
+mod wallet;
+#[allow(dead_code)]
+mod withdrawal;
+#[allow(unused_imports)]
+pub use self::{
+    deposit::DepositSqlExecutor, wallet::WalletSqlExecutor, withdrawal::WithdrawalSqlExecutor,
+};
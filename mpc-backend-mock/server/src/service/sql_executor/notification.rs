@@ -0,0 +1,242 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{
+        CapturedNotification, ListNotificationsFilter, NotificationStatus, OutboxNotification,
+        RecipientPreferences,
+    },
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait NotificationSqlExecutor {
+    async fn insert_notification(
+        &mut self,
+        kind: &str,
+        recipient: &str,
+        payload: &serde_json::Value,
+    ) -> Result<Uuid>;
+
+    async fn claim_due_notifications(&mut self, limit: i64) -> Result<Vec<OutboxNotification>>;
+
+    async fn mark_notification_sent(&mut self, notification_id: &Uuid) -> Result<()>;
+
+    async fn mark_notification_skipped(&mut self, notification_id: &Uuid) -> Result<()>;
+
+    async fn list_recipient_preferences(
+        &mut self,
+        emails: &[String],
+    ) -> Result<Vec<RecipientPreferences>>;
+
+    async fn record_failed_attempt(
+        &mut self,
+        notification_id: &Uuid,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()>;
+
+    async fn list_notifications(
+        &mut self,
+        filter: &ListNotificationsFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<OutboxNotification>>;
+
+    async fn count_notifications(&mut self, filter: &ListNotificationsFilter) -> Result<i64>;
+
+    async fn insert_captured_notification(
+        &mut self,
+        kind: &str,
+        recipient: &str,
+        subject: &str,
+        html_body: &str,
+        payload: &serde_json::Value,
+    ) -> Result<()>;
+
+    async fn list_captured_notifications(
+        &mut self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CapturedNotification>>;
+
+    async fn count_captured_notifications(&mut self) -> Result<i64>;
+}
+
+#[async_trait]
+impl<E> NotificationSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_notification(
+        &mut self,
+        kind: &str,
+        recipient: &str,
+        payload: &serde_json::Value,
+    ) -> Result<Uuid> {
+        let notification_id = sqlx::query_file_scalar!(
+            "sql/notification/insert_notification.sql",
+            kind,
+            recipient,
+            payload
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertNotificationSnafu)?;
+
+        Ok(notification_id)
+    }
+
+    async fn claim_due_notifications(&mut self, limit: i64) -> Result<Vec<OutboxNotification>> {
+        let notifications = sqlx::query_file_as!(
+            OutboxNotification,
+            "sql/notification/claim_due_notifications.sql",
+            limit
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ClaimDueNotificationsSnafu)?;
+
+        Ok(notifications)
+    }
+
+    async fn mark_notification_sent(&mut self, notification_id: &Uuid) -> Result<()> {
+        let _result =
+            sqlx::query_file!("sql/notification/mark_notification_sent.sql", notification_id)
+                .execute(&mut *self)
+                .await
+                .context(error::UpdateNotificationSnafu)?;
+
+        Ok(())
+    }
+
+    async fn mark_notification_skipped(&mut self, notification_id: &Uuid) -> Result<()> {
+        let _result =
+            sqlx::query_file!("sql/notification/mark_notification_skipped.sql", notification_id)
+                .execute(&mut *self)
+                .await
+                .context(error::UpdateNotificationSnafu)?;
+
+        Ok(())
+    }
+
+    async fn list_recipient_preferences(
+        &mut self,
+        emails: &[String],
+    ) -> Result<Vec<RecipientPreferences>> {
+        let preferences = sqlx::query_file_as!(
+            RecipientPreferences,
+            "sql/notification/list_recipient_preferences.sql",
+            emails
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListRecipientPreferencesSnafu)?;
+
+        Ok(preferences)
+    }
+
+    async fn record_failed_attempt(
+        &mut self,
+        notification_id: &Uuid,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/notification/record_failed_attempt.sql",
+            notification_id,
+            error,
+            next_attempt_at
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::UpdateNotificationSnafu)?;
+
+        Ok(())
+    }
+
+    async fn list_notifications(
+        &mut self,
+        filter: &ListNotificationsFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<OutboxNotification>> {
+        let notifications = sqlx::query_file_as!(
+            OutboxNotification,
+            "sql/notification/list_notifications.sql",
+            filter.status as Option<NotificationStatus>,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListNotificationsSnafu)?;
+
+        Ok(notifications)
+    }
+
+    async fn count_notifications(&mut self, filter: &ListNotificationsFilter) -> Result<i64> {
+        let count = sqlx::query_file_scalar!(
+            "sql/notification/count_notifications.sql",
+            filter.status as Option<NotificationStatus>
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::CountNotificationsSnafu)?;
+
+        Ok(count)
+    }
+
+    async fn insert_captured_notification(
+        &mut self,
+        kind: &str,
+        recipient: &str,
+        subject: &str,
+        html_body: &str,
+        payload: &serde_json::Value,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/notification/insert_captured_notification.sql",
+            kind,
+            recipient,
+            subject,
+            html_body,
+            payload
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::InsertCapturedNotificationSnafu)?;
+
+        Ok(())
+    }
+
+    async fn list_captured_notifications(
+        &mut self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CapturedNotification>> {
+        let notifications = sqlx::query_file_as!(
+            CapturedNotification,
+            "sql/notification/list_captured_notifications.sql",
+            limit,
+            offset
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListCapturedNotificationsSnafu)?;
+
+        Ok(notifications)
+    }
+
+    async fn count_captured_notifications(&mut self) -> Result<i64> {
+        let count = sqlx::query_file_scalar!("sql/notification/count_captured_notifications.sql")
+            .fetch_one(&mut *self)
+            .await
+            .context(error::CountCapturedNotificationsSnafu)?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+
+use crate::{
+    entity::OpenApiBaseline,
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait OpenApiBaselineSqlExecutor {
+    async fn insert_openapi_baseline(
+        &mut self,
+        document: &serde_json::Value,
+    ) -> Result<OpenApiBaseline>;
+
+    /// Most recently uploaded baseline and its document
+    async fn get_latest_openapi_baseline(
+        &mut self,
+    ) -> Result<Option<(OpenApiBaseline, serde_json::Value)>>;
+}
+
+#[async_trait]
+impl<E> OpenApiBaselineSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_openapi_baseline(
+        &mut self,
+        document: &serde_json::Value,
+    ) -> Result<OpenApiBaseline> {
+        sqlx::query_file_as!(
+            OpenApiBaseline,
+            "sql/openapi_baseline/insert_openapi_baseline.sql",
+            document
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertOpenApiBaselineSnafu)
+    }
+
+    async fn get_latest_openapi_baseline(
+        &mut self,
+    ) -> Result<Option<(OpenApiBaseline, serde_json::Value)>> {
+        let row = sqlx::query_file!("sql/openapi_baseline/get_latest_openapi_baseline.sql")
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::GetLatestOpenApiBaselineSnafu)?;
+
+        Ok(row
+            .map(|row| (OpenApiBaseline { id: row.id, created_at: row.created_at }, row.document)))
+    }
+}
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::Session,
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait SessionSqlExecutor {
+    async fn insert_session(
+        &mut self,
+        user_id: &Uuid,
+        token_hash: &str,
+        csrf_token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Session of the token, `None` if it is unknown, expired or its user is
+    /// deleted
+    async fn get_session_by_token_hash(&mut self, token_hash: &str) -> Result<Option<Session>>;
+
+    /// Delete the session of the token, returns whether there was one
+    async fn delete_session_by_token_hash(&mut self, token_hash: &str) -> Result<bool>;
+
+    async fn delete_expired_sessions(&mut self) -> Result<u64>;
+}
+
+#[async_trait]
+impl<E> SessionSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_session(
+        &mut self,
+        user_id: &Uuid,
+        token_hash: &str,
+        csrf_token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/session/insert_session.sql",
+            user_id,
+            token_hash,
+            csrf_token_hash,
+            expires_at
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::InsertSessionSnafu)?;
+
+        Ok(())
+    }
+
+    async fn get_session_by_token_hash(&mut self, token_hash: &str) -> Result<Option<Session>> {
+        sqlx::query_file_as!(Session, "sql/session/get_session_by_token_hash.sql", token_hash)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::GetSessionSnafu)
+    }
+
+    async fn delete_session_by_token_hash(&mut self, token_hash: &str) -> Result<bool> {
+        let result = sqlx::query_file!("sql/session/delete_session_by_token_hash.sql", token_hash)
+            .execute(&mut *self)
+            .await
+            .context(error::DeleteSessionSnafu)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_expired_sessions(&mut self) -> Result<u64> {
+        let result = sqlx::query_file!("sql/session/delete_expired_sessions.sql")
+            .execute(&mut *self)
+            .await
+            .context(error::DeleteExpiredSessionsSnafu)?;
+
+        Ok(result.rows_affected())
+    }
+}
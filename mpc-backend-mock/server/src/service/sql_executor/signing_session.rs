@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{SigningSession, SigningSessionStatus},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait SigningSessionSqlExecutor {
+    async fn insert_signing_session(
+        &mut self,
+        user_id: &Uuid,
+        payload: &str,
+        round_duration_ms: i64,
+        fail_at: Option<SigningSessionStatus>,
+    ) -> Result<SigningSession>;
+
+    async fn get_signing_session_by_id(
+        &mut self,
+        session_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<SigningSession>>;
+
+    /// Move the session from `from` to `to`, returns `None` if it is not in
+    /// `from` anymore
+    async fn update_signing_session_status(
+        &mut self,
+        session_id: &Uuid,
+        from: SigningSessionStatus,
+        to: SigningSessionStatus,
+        signature: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<Option<SigningSession>>;
+}
+
+#[async_trait]
+impl<E> SigningSessionSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_signing_session(
+        &mut self,
+        user_id: &Uuid,
+        payload: &str,
+        round_duration_ms: i64,
+        fail_at: Option<SigningSessionStatus>,
+    ) -> Result<SigningSession> {
+        let session = sqlx::query_file_as!(
+            SigningSession,
+            "sql/signing_session/insert_signing_session.sql",
+            user_id,
+            payload,
+            round_duration_ms,
+            fail_at as Option<SigningSessionStatus>
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertSigningSessionSnafu)?;
+
+        Ok(session)
+    }
+
+    async fn get_signing_session_by_id(
+        &mut self,
+        session_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<SigningSession>> {
+        let session = sqlx::query_file_as!(
+            SigningSession,
+            "sql/signing_session/get_signing_session_by_id.sql",
+            session_id,
+            user_id
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::GetSigningSessionByIdSnafu)?;
+
+        Ok(session)
+    }
+
+    async fn update_signing_session_status(
+        &mut self,
+        session_id: &Uuid,
+        from: SigningSessionStatus,
+        to: SigningSessionStatus,
+        signature: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<Option<SigningSession>> {
+        let session = sqlx::query_file_as!(
+            SigningSession,
+            "sql/signing_session/update_signing_session_status.sql",
+            session_id,
+            from as SigningSessionStatus,
+            to as SigningSessionStatus,
+            signature,
+            error
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::UpdateSigningSessionStatusSnafu)?;
+
+        Ok(session)
+    }
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{Transaction, TransactionStatus},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait TransactionSqlExecutor {
+    async fn insert_transaction(&mut self, user_id: &Uuid, signature: &str) -> Result<Transaction>;
+
+    async fn get_transaction_by_id(
+        &mut self,
+        transaction_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<Transaction>>;
+
+    async fn update_transaction_status(
+        &mut self,
+        transaction_id: &Uuid,
+        status: TransactionStatus,
+        slot: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<Option<Transaction>>;
+}
+
+#[async_trait]
+impl<E> TransactionSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_transaction(&mut self, user_id: &Uuid, signature: &str) -> Result<Transaction> {
+        let transaction = sqlx::query_file_as!(
+            Transaction,
+            "sql/transaction/insert_transaction.sql",
+            user_id,
+            signature
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertTransactionSnafu)?;
+
+        Ok(transaction)
+    }
+
+    async fn get_transaction_by_id(
+        &mut self,
+        transaction_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<Transaction>> {
+        let transaction = sqlx::query_file_as!(
+            Transaction,
+            "sql/transaction/get_transaction_by_id.sql",
+            transaction_id,
+            user_id
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::GetTransactionByIdSnafu)?;
+
+        Ok(transaction)
+    }
+
+    async fn update_transaction_status(
+        &mut self,
+        transaction_id: &Uuid,
+        status: TransactionStatus,
+        slot: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<Option<Transaction>> {
+        let transaction = sqlx::query_file_as!(
+            Transaction,
+            "sql/transaction/update_transaction_status.sql",
+            transaction_id,
+            status as TransactionStatus,
+            slot,
+            error
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::UpdateTransactionStatusSnafu)?;
+
+        Ok(transaction)
+    }
+}
@@ -1,29 +1,122 @@
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
+use mpc_backend_mock_core::model::{Column, Fields, Sort};
 use snafu::ResultExt;
-use sqlx::{Executor, Postgres};
+use sqlx::{postgres::PgRow, Executor, Postgres, QueryBuilder, Row};
 use uuid::Uuid;
 
 use crate::{
-    entity::User,
+    entity::{
+        ListUsersFilter, UpdateUserPreferencesRequest, UpdateUserProfileRequest, User, UserField,
+        UserPreferences, UserProfile, UserSortColumn,
+    },
     service::error::{self, Result},
 };
 
 #[async_trait]
 pub trait UserSqlExecutor {
-    async fn get_user_by_email(&mut self, email: &str) -> Result<Option<User>>;
+    /// User with `email` in `realm`, `None` for the primary realm
+    async fn get_user_by_email(
+        &mut self,
+        email: &str,
+        realm: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<Option<User>>;
 
     async fn insert_user(
         &mut self,
         email: &str,
         keycloak_user_id: &Uuid,
+        realm: Option<&str>,
         is_active: bool,
     ) -> Result<User>;
 
-    async fn get_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>>;
+    async fn get_user_by_id(
+        &mut self,
+        user_id: &Uuid,
+        include_deleted: bool,
+    ) -> Result<Option<User>>;
+
+    async fn soft_delete_user_by_id(&mut self, user_id: &Uuid) -> Result<()>;
+
+    async fn restore_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>>;
+
+    async fn activate_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>>;
+
+    /// Change the email of a user which is not soft-deleted
+    async fn update_user_email(&mut self, user_id: &Uuid, email: &str) -> Result<Option<User>>;
+
+    /// User of the Keycloak user in `realm`, `None` for the primary realm
+    async fn get_user_by_keycloak_id(
+        &mut self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<Option<User>>;
+
+    /// Users of `realm`, `None` for the primary realm
+    async fn list_users(
+        &mut self,
+        realm: Option<&str>,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>>;
+
+    /// List users like [`Self::list_users`], projected onto `fields`
+    async fn list_user_fields(
+        &mut self,
+        realm: Option<&str>,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        fields: &Fields<UserField>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>>;
+
+    async fn count_users(&mut self, realm: Option<&str>, filter: &ListUsersFilter) -> Result<i64>;
+
+    async fn list_users_by_email_pattern(
+        &mut self,
+        email_like: &str,
+        created_before: Option<DateTime<Utc>>,
+        realm: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>>;
+
+    async fn delete_users_by_ids(&mut self, user_ids: &[Uuid]) -> Result<u64>;
+
+    async fn list_users_by_keycloak_ids(&mut self, keycloak_user_ids: &[Uuid])
+        -> Result<Vec<User>>;
 
-    async fn delete_user_by_id(&mut self, user_id: &Uuid) -> Result<()>;
+    /// Stream every user of `realm`, `None` for the primary realm,
+    /// soft-deleted users included, without loading them all into memory
+    fn export_users<'a>(&'a mut self, realm: Option<&'a str>) -> BoxStream<'a, Result<User>>;
 
-    async fn get_user_by_keycloak_id(&mut self, keycloak_user_id: &Uuid) -> Result<Option<User>>;
+    async fn list_users_not_in_keycloak_ids(
+        &mut self,
+        keycloak_user_ids: &[Uuid],
+        created_before: DateTime<Utc>,
+    ) -> Result<Vec<User>>;
+
+    async fn update_user(
+        &mut self,
+        user_id: &Uuid,
+        update: &UpdateUserProfileRequest,
+    ) -> Result<UserProfile>;
+
+    async fn get_user_profile(&mut self, user_id: &Uuid) -> Result<Option<UserProfile>>;
+
+    async fn get_user_preferences(&mut self, user_id: &Uuid) -> Result<Option<UserPreferences>>;
+
+    async fn replace_user_preferences(
+        &mut self,
+        user_id: &Uuid,
+        preferences: &UpdateUserPreferencesRequest,
+    ) -> Result<UserPreferences>;
 }
 
 #[async_trait]
@@ -31,11 +124,22 @@ impl<E> UserSqlExecutor for E
 where
     for<'c> &'c mut E: Executor<'c, Database = Postgres>,
 {
-    async fn get_user_by_email(&mut self, email: &str) -> Result<Option<User>> {
-        let user = sqlx::query_file_as!(User, "sql/user/get_user_by_email.sql", email)
-            .fetch_optional(&mut *self)
-            .await
-            .context(error::GetUserByEmailSnafu)?;
+    async fn get_user_by_email(
+        &mut self,
+        email: &str,
+        realm: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<Option<User>> {
+        let user = sqlx::query_file_as!(
+            User,
+            "sql/user/get_user_by_email.sql",
+            email,
+            include_deleted,
+            realm
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::GetUserByEmailSnafu)?;
 
         Ok(user)
     }
@@ -44,6 +148,7 @@ where
         &mut self,
         email: &str,
         keycloak_user_id: &Uuid,
+        realm: Option<&str>,
         is_active: bool,
     ) -> Result<User> {
         let user = sqlx::query_file_as!(
@@ -51,7 +156,8 @@ where
             "sql/user/insert_user.sql",
             email,
             keycloak_user_id,
-            is_active
+            is_active,
+            realm
         )
         .fetch_one(&mut *self)
         .await
@@ -60,31 +166,337 @@ where
         Ok(user)
     }
 
-    async fn get_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>> {
-        let user = sqlx::query_file_as!(User, "sql/user/get_user_by_id.sql", user_id)
+    async fn get_user_by_id(
+        &mut self,
+        user_id: &Uuid,
+        include_deleted: bool,
+    ) -> Result<Option<User>> {
+        let user =
+            sqlx::query_file_as!(User, "sql/user/get_user_by_id.sql", user_id, include_deleted)
+                .fetch_optional(&mut *self)
+                .await
+                .context(error::GetUserByIdSnafu)?;
+
+        Ok(user)
+    }
+
+    async fn soft_delete_user_by_id(&mut self, user_id: &Uuid) -> Result<()> {
+        let _result = sqlx::query_file!("sql/user/soft_delete_user_by_id.sql", user_id)
+            .execute(&mut *self)
+            .await
+            .context(error::SoftDeleteUserByIdSnafu)?;
+
+        Ok(())
+    }
+
+    async fn restore_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_file_as!(User, "sql/user/restore_user_by_id.sql", user_id)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::RestoreUserByIdSnafu)?;
+
+        Ok(user)
+    }
+
+    async fn activate_user_by_id(&mut self, user_id: &Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_file_as!(User, "sql/user/activate_user_by_id.sql", user_id)
             .fetch_optional(&mut *self)
             .await
-            .context(error::GetUserByIdSnafu)?;
+            .context(error::ActivateUserByIdSnafu)?;
 
         Ok(user)
     }
 
-    async fn delete_user_by_id(&mut self, user_id: &Uuid) -> Result<()> {
-        let _result = sqlx::query_file!("sql/user/delete_user_by_id.sql", user_id)
+    async fn update_user_email(&mut self, user_id: &Uuid, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_file_as!(User, "sql/user/update_user_email.sql", user_id, email)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::UpdateUserEmailSnafu)?;
+
+        Ok(user)
+    }
+
+    async fn get_user_by_keycloak_id(
+        &mut self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        include_deleted: bool,
+    ) -> Result<Option<User>> {
+        let user = sqlx::query_file_as!(
+            User,
+            "sql/user/get_user_by_keycloak_id.sql",
+            keycloak_user_id,
+            include_deleted,
+            realm
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::GetUserByKeycloakIdSnafu)?;
+
+        Ok(user)
+    }
+
+    async fn list_users(
+        &mut self,
+        realm: Option<&str>,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, email, keycloak_user_id, realm, is_active, created_at, updated_at, \
+             deleted_at",
+        );
+        push_list_users_clauses(&mut query, realm, filter, sort, limit, offset);
+
+        let users = query
+            .build_query_as::<User>()
+            .fetch_all(&mut *self)
+            .await
+            .context(error::ListUsersSnafu)?;
+
+        Ok(users)
+    }
+
+    async fn list_user_fields(
+        &mut self,
+        realm: Option<&str>,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        fields: &Fields<UserField>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>> {
+        let mut query = QueryBuilder::new("SELECT ");
+        let mut columns = query.separated(", ");
+        for field in fields.columns() {
+            // names come from the allowlist, never from the request
+            let _ = columns.push(field.name());
+        }
+        push_list_users_clauses(&mut query, realm, filter, sort, limit, offset);
+
+        let rows = query.build().fetch_all(&mut *self).await.context(error::ListUsersSnafu)?;
+
+        rows.iter()
+            .map(|row| {
+                fields
+                    .columns()
+                    .iter()
+                    .map(|&field| Ok((field.name().to_string(), user_field_value(row, field)?)))
+                    .collect::<std::result::Result<_, sqlx::Error>>()
+            })
+            .collect::<std::result::Result<_, _>>()
+            .context(error::ListUsersSnafu)
+    }
+
+    async fn count_users(&mut self, realm: Option<&str>, filter: &ListUsersFilter) -> Result<i64> {
+        let created_after =
+            filter.created_after.map(|date| date.and_time(NaiveTime::MIN).and_utc());
+
+        let count = sqlx::query_file_scalar!(
+            "sql/user/count_users.sql",
+            filter.email_like,
+            filter.is_active,
+            created_after,
+            realm
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::CountUsersSnafu)?;
+
+        Ok(count)
+    }
+
+    async fn list_users_by_email_pattern(
+        &mut self,
+        email_like: &str,
+        created_before: Option<DateTime<Utc>>,
+        realm: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>> {
+        let users = sqlx::query_file_as!(
+            User,
+            "sql/user/list_users_by_email_pattern.sql",
+            email_like,
+            created_before,
+            limit,
+            offset,
+            realm
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListUsersByEmailPatternSnafu)?;
+
+        Ok(users)
+    }
+
+    async fn delete_users_by_ids(&mut self, user_ids: &[Uuid]) -> Result<u64> {
+        let result = sqlx::query_file!("sql/user/delete_users_by_ids.sql", user_ids)
             .execute(&mut *self)
             .await
-            .context(error::DeleteUserByIdSnafu)?;
+            .context(error::DeleteUsersByIdsSnafu)?;
 
-        Ok(())
+        Ok(result.rows_affected())
     }
 
-    async fn get_user_by_keycloak_id(&mut self, keycloak_user_id: &Uuid) -> Result<Option<User>> {
-        let user =
-            sqlx::query_file_as!(User, "sql/user/get_user_by_keycloak_id.sql", keycloak_user_id)
+    async fn list_users_by_keycloak_ids(
+        &mut self,
+        keycloak_user_ids: &[Uuid],
+    ) -> Result<Vec<User>> {
+        let users = sqlx::query_file_as!(
+            User,
+            "sql/user/list_users_by_keycloak_ids.sql",
+            keycloak_user_ids
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListUsersByKeycloakIdsSnafu)?;
+
+        Ok(users)
+    }
+
+    fn export_users<'a>(&'a mut self, realm: Option<&'a str>) -> BoxStream<'a, Result<User>> {
+        sqlx::query_file_as!(User, "sql/user/export_users.sql", realm)
+            .fetch(&mut *self)
+            .map(|user| user.context(error::ExportUsersSnafu))
+            .boxed()
+    }
+
+    async fn list_users_not_in_keycloak_ids(
+        &mut self,
+        keycloak_user_ids: &[Uuid],
+        created_before: DateTime<Utc>,
+    ) -> Result<Vec<User>> {
+        let users = sqlx::query_file_as!(
+            User,
+            "sql/user/list_users_not_in_keycloak_ids.sql",
+            keycloak_user_ids,
+            created_before
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListUsersByKeycloakIdsSnafu)?;
+
+        Ok(users)
+    }
+
+    async fn update_user(
+        &mut self,
+        user_id: &Uuid,
+        update: &UpdateUserProfileRequest,
+    ) -> Result<UserProfile> {
+        let profile = sqlx::query_file_as!(
+            UserProfile,
+            "sql/user/update_user.sql",
+            user_id,
+            update.display_name,
+            update.locale
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::UpdateUserSnafu)?;
+
+        Ok(profile)
+    }
+
+    async fn get_user_profile(&mut self, user_id: &Uuid) -> Result<Option<UserProfile>> {
+        let profile = sqlx::query_file_as!(UserProfile, "sql/user/get_user_profile.sql", user_id)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::GetUserProfileSnafu)?;
+
+        Ok(profile)
+    }
+
+    async fn get_user_preferences(&mut self, user_id: &Uuid) -> Result<Option<UserPreferences>> {
+        let preferences =
+            sqlx::query_file_as!(UserPreferences, "sql/user/get_user_preferences.sql", user_id)
                 .fetch_optional(&mut *self)
                 .await
-                .context(error::GetUserByKeycloakIdSnafu)?;
+                .context(error::GetUserPreferencesSnafu)?;
 
-        Ok(user)
+        Ok(preferences)
+    }
+
+    async fn replace_user_preferences(
+        &mut self,
+        user_id: &Uuid,
+        preferences: &UpdateUserPreferencesRequest,
+    ) -> Result<UserPreferences> {
+        let preferences = sqlx::query_file_as!(
+            UserPreferences,
+            "sql/user/replace_user_preferences.sql",
+            user_id,
+            preferences.email_enabled,
+            preferences.webhook_url,
+            preferences.locale
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::UpdateUserPreferencesSnafu)?;
+
+        Ok(preferences)
+    }
+}
+
+/// Append the realm, filters, order and page of the users list to a `SELECT` of
+/// `users` columns
+///
+/// Sort columns come from the allowlist of [`UserSortColumn`], the ID breaks
+/// ties so that pages never overlap.
+fn push_list_users_clauses(
+    query: &mut QueryBuilder<'_, Postgres>,
+    realm: Option<&str>,
+    filter: &ListUsersFilter,
+    sort: &Sort<UserSortColumn>,
+    limit: i64,
+    offset: i64,
+) {
+    let _ = query
+        .push(" FROM users WHERE deleted_at IS NULL AND realm IS NOT DISTINCT FROM ")
+        .push_bind(realm.map(ToString::to_string));
+    if let Some(ref email_like) = filter.email_like {
+        let _ =
+            query.push(" AND email ILIKE '%' || ").push_bind(email_like.clone()).push(" || '%'");
+    }
+    if let Some(is_active) = filter.is_active {
+        let _ = query.push(" AND is_active = ").push_bind(is_active);
     }
+    if let Some(created_after) = filter.created_after {
+        let _ = query
+            .push(" AND created_at >= ")
+            .push_bind(created_after.and_time(NaiveTime::MIN).and_utc());
+    }
+
+    let _ = query.push(" ORDER BY ");
+    if sort.is_empty() {
+        let _ = query.push("created_at DESC, ");
+    }
+    for key in sort.keys() {
+        let _ = query.push(key.column.name()).push(" ").push(key.direction.as_sql()).push(", ");
+    }
+    let _ = query.push("id LIMIT ").push_bind(limit).push(" OFFSET ").push_bind(offset);
+}
+
+/// Value of `field` in a row of the users list, encoded like [`User`] encodes
+/// it
+fn user_field_value(
+    row: &PgRow,
+    field: UserField,
+) -> std::result::Result<serde_json::Value, sqlx::Error> {
+    let name = field.name();
+    let value = match field {
+        UserField::Id | UserField::KeycloakUserId => {
+            serde_json::Value::String(row.try_get::<Uuid, _>(name)?.to_string())
+        }
+        UserField::Email => serde_json::Value::String(row.try_get(name)?),
+        UserField::IsActive => serde_json::Value::Bool(row.try_get(name)?),
+        UserField::CreatedAt | UserField::UpdatedAt => {
+            serde_json::to_value(row.try_get::<DateTime<Utc>, _>(name)?)
+                .map_err(|err| sqlx::Error::Decode(err.into()))?
+        }
+    };
+    Ok(value)
 }
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{Chain, Wallet},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait WalletSqlExecutor {
+    async fn insert_wallet(
+        &mut self,
+        user_id: &Uuid,
+        chain: Chain,
+        address: &str,
+    ) -> Result<Wallet>;
+
+    async fn get_wallet_by_id(&mut self, wallet_id: &Uuid) -> Result<Option<Wallet>>;
+
+    async fn get_wallet_by_address(
+        &mut self,
+        chain: Chain,
+        address: &str,
+    ) -> Result<Option<Wallet>>;
+
+    async fn list_wallets_by_user_id(&mut self, user_id: &Uuid) -> Result<Vec<Wallet>>;
+}
+
+#[async_trait]
+impl<E> WalletSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_wallet(
+        &mut self,
+        user_id: &Uuid,
+        chain: Chain,
+        address: &str,
+    ) -> Result<Wallet> {
+        let wallet = sqlx::query_file_as!(
+            Wallet,
+            "sql/wallet/insert_wallet.sql",
+            user_id,
+            chain as Chain,
+            address
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertWalletSnafu)?;
+
+        Ok(wallet)
+    }
+
+    async fn get_wallet_by_id(&mut self, wallet_id: &Uuid) -> Result<Option<Wallet>> {
+        let wallet = sqlx::query_file_as!(Wallet, "sql/wallet/get_wallet_by_id.sql", wallet_id)
+            .fetch_optional(&mut *self)
+            .await
+            .context(error::GetWalletByIdSnafu)?;
+
+        Ok(wallet)
+    }
+
+    async fn get_wallet_by_address(
+        &mut self,
+        chain: Chain,
+        address: &str,
+    ) -> Result<Option<Wallet>> {
+        let wallet = sqlx::query_file_as!(
+            Wallet,
+            "sql/wallet/get_wallet_by_address.sql",
+            chain as Chain,
+            address
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::GetWalletByAddressSnafu)?;
+
+        Ok(wallet)
+    }
+
+    async fn list_wallets_by_user_id(&mut self, user_id: &Uuid) -> Result<Vec<Wallet>> {
+        let wallets =
+            sqlx::query_file_as!(Wallet, "sql/wallet/list_wallets_by_user_id.sql", user_id)
+                .fetch_all(&mut *self)
+                .await
+                .context(error::ListWalletsSnafu)?;
+
+        Ok(wallets)
+    }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use mpc_backend_mock_core::model::TokenAmount;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::DailyBalance,
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait WalletBalanceSnapshotSqlExecutor {
+    async fn upsert_wallet_balance_snapshots(&mut self) -> Result<u64>;
+
+    async fn list_wallet_balance_snapshots(
+        &mut self,
+        wallet_id: &Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DailyBalance>>;
+}
+
+#[async_trait]
+impl<E> WalletBalanceSnapshotSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn upsert_wallet_balance_snapshots(&mut self) -> Result<u64> {
+        let result =
+            sqlx::query_file!("sql/wallet_balance_snapshot/upsert_wallet_balance_snapshots.sql")
+                .execute(&mut *self)
+                .await
+                .context(error::UpsertWalletBalanceSnapshotsSnafu)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn list_wallet_balance_snapshots(
+        &mut self,
+        wallet_id: &Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DailyBalance>> {
+        let balances = sqlx::query_file_as!(
+            DailyBalance,
+            "sql/wallet_balance_snapshot/list_wallet_balance_snapshots.sql",
+            wallet_id,
+            from,
+            to
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListWalletBalanceSnapshotsSnafu)?;
+
+        Ok(balances)
+    }
+}
@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{DueWebhookDelivery, Webhook, WebhookDelivery, WebhookDeliveryStatus},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait WebhookSqlExecutor {
+    async fn insert_webhook(
+        &mut self,
+        user_id: &Uuid,
+        url: &str,
+        event_types: &[String],
+        secret: &str,
+    ) -> Result<Webhook>;
+
+    async fn get_webhook_by_id(
+        &mut self,
+        webhook_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<Webhook>>;
+
+    /// Queue an event of the user for every webhook subscribing to its type,
+    /// returns the number of queued deliveries
+    async fn insert_webhook_deliveries(
+        &mut self,
+        user_id: &Uuid,
+        event_type: &str,
+        payload: &serde_json::Value,
+    ) -> Result<u64>;
+
+    /// Claim due deliveries until `lease_until`, other dispatchers do not
+    /// claim them again before
+    async fn claim_due_webhook_deliveries(
+        &mut self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> Result<Vec<DueWebhookDelivery>>;
+
+    async fn mark_webhook_delivery_delivered(
+        &mut self,
+        delivery_id: &Uuid,
+        response_status: i32,
+    ) -> Result<()>;
+
+    async fn record_failed_webhook_delivery(
+        &mut self,
+        delivery_id: &Uuid,
+        response_status: Option<i32>,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()>;
+
+    async fn list_webhook_deliveries(
+        &mut self,
+        webhook_id: &Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookDelivery>>;
+
+    async fn count_webhook_deliveries(&mut self, webhook_id: &Uuid) -> Result<i64>;
+}
+
+#[async_trait]
+impl<E> WebhookSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_webhook(
+        &mut self,
+        user_id: &Uuid,
+        url: &str,
+        event_types: &[String],
+        secret: &str,
+    ) -> Result<Webhook> {
+        let webhook = sqlx::query_file_as!(
+            Webhook,
+            "sql/webhook/insert_webhook.sql",
+            user_id,
+            url,
+            event_types,
+            secret
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertWebhookSnafu)?;
+
+        Ok(webhook)
+    }
+
+    async fn get_webhook_by_id(
+        &mut self,
+        webhook_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<Webhook>> {
+        let webhook =
+            sqlx::query_file_as!(Webhook, "sql/webhook/get_webhook_by_id.sql", webhook_id, user_id)
+                .fetch_optional(&mut *self)
+                .await
+                .context(error::GetWebhookByIdSnafu)?;
+
+        Ok(webhook)
+    }
+
+    async fn insert_webhook_deliveries(
+        &mut self,
+        user_id: &Uuid,
+        event_type: &str,
+        payload: &serde_json::Value,
+    ) -> Result<u64> {
+        let result = sqlx::query_file!(
+            "sql/webhook/insert_webhook_deliveries.sql",
+            user_id,
+            event_type,
+            payload
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::InsertWebhookDeliveriesSnafu)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn claim_due_webhook_deliveries(
+        &mut self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> Result<Vec<DueWebhookDelivery>> {
+        let deliveries = sqlx::query_file_as!(
+            DueWebhookDelivery,
+            "sql/webhook/claim_due_webhook_deliveries.sql",
+            limit,
+            lease_until
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ClaimDueWebhookDeliveriesSnafu)?;
+
+        Ok(deliveries)
+    }
+
+    async fn mark_webhook_delivery_delivered(
+        &mut self,
+        delivery_id: &Uuid,
+        response_status: i32,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/webhook/mark_webhook_delivery_delivered.sql",
+            delivery_id,
+            response_status
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::UpdateWebhookDeliverySnafu)?;
+
+        Ok(())
+    }
+
+    async fn record_failed_webhook_delivery(
+        &mut self,
+        delivery_id: &Uuid,
+        response_status: Option<i32>,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let _result = sqlx::query_file!(
+            "sql/webhook/record_failed_webhook_delivery.sql",
+            delivery_id,
+            response_status,
+            error,
+            next_attempt_at
+        )
+        .execute(&mut *self)
+        .await
+        .context(error::UpdateWebhookDeliverySnafu)?;
+
+        Ok(())
+    }
+
+    async fn list_webhook_deliveries(
+        &mut self,
+        webhook_id: &Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_file_as!(
+            WebhookDelivery,
+            "sql/webhook/list_webhook_deliveries.sql",
+            webhook_id,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListWebhookDeliveriesSnafu)?;
+
+        Ok(deliveries)
+    }
+
+    async fn count_webhook_deliveries(&mut self, webhook_id: &Uuid) -> Result<i64> {
+        let count =
+            sqlx::query_file_scalar!("sql/webhook/count_webhook_deliveries.sql", webhook_id)
+                .fetch_one(&mut *self)
+                .await
+                .context(error::CountWebhookDeliveriesSnafu)?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use mpc_backend_mock_core::model::TokenAmount;
+use snafu::ResultExt;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entity::{Withdrawal, WithdrawalStatus},
+    service::error::{self, Result},
+};
+
+#[async_trait]
+pub trait WithdrawalSqlExecutor {
+    async fn insert_withdrawal(
+        &mut self,
+        wallet_id: &Uuid,
+        destination_address: &str,
+        amount: TokenAmount,
+    ) -> Result<Withdrawal>;
+
+    async fn get_withdrawal_by_id(&mut self, withdrawal_id: &Uuid) -> Result<Option<Withdrawal>>;
+
+    async fn list_withdrawals_by_wallet_id(
+        &mut self,
+        wallet_id: &Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Withdrawal>>;
+
+    async fn update_withdrawal_status(
+        &mut self,
+        withdrawal_id: &Uuid,
+        status: WithdrawalStatus,
+        tx_id: Option<&str>,
+    ) -> Result<Option<Withdrawal>>;
+}
+
+#[async_trait]
+impl<E> WithdrawalSqlExecutor for E
+where
+    for<'c> &'c mut E: Executor<'c, Database = Postgres>,
+{
+    async fn insert_withdrawal(
+        &mut self,
+        wallet_id: &Uuid,
+        destination_address: &str,
+        amount: TokenAmount,
+    ) -> Result<Withdrawal> {
+        let withdrawal = sqlx::query_file_as!(
+            Withdrawal,
+            "sql/withdrawal/insert_withdrawal.sql",
+            wallet_id,
+            destination_address,
+            amount as TokenAmount
+        )
+        .fetch_one(&mut *self)
+        .await
+        .context(error::InsertWithdrawalSnafu)?;
+
+        Ok(withdrawal)
+    }
+
+    async fn get_withdrawal_by_id(&mut self, withdrawal_id: &Uuid) -> Result<Option<Withdrawal>> {
+        let withdrawal = sqlx::query_file_as!(
+            Withdrawal,
+            "sql/withdrawal/get_withdrawal_by_id.sql",
+            withdrawal_id
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::GetWithdrawalByIdSnafu)?;
+
+        Ok(withdrawal)
+    }
+
+    async fn list_withdrawals_by_wallet_id(
+        &mut self,
+        wallet_id: &Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Withdrawal>> {
+        let withdrawals = sqlx::query_file_as!(
+            Withdrawal,
+            "sql/withdrawal/list_withdrawals_by_wallet_id.sql",
+            wallet_id,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *self)
+        .await
+        .context(error::ListWithdrawalsSnafu)?;
+
+        Ok(withdrawals)
+    }
+
+    async fn update_withdrawal_status(
+        &mut self,
+        withdrawal_id: &Uuid,
+        status: WithdrawalStatus,
+        tx_id: Option<&str>,
+    ) -> Result<Option<Withdrawal>> {
+        let withdrawal = sqlx::query_file_as!(
+            Withdrawal,
+            "sql/withdrawal/update_withdrawal_status.sql",
+            withdrawal_id,
+            status as WithdrawalStatus,
+            tx_id
+        )
+        .fetch_optional(&mut *self)
+        .await
+        .context(error::UpdateWithdrawalStatusSnafu)?;
+
+        Ok(withdrawal)
+    }
+}
@@ -0,0 +1,245 @@
+use std::{str::FromStr, sync::Arc};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use snafu::{OptionExt, ResultExt};
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::TransactionConfirmationStatus;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{DomainEvent, Event, Transaction, TransactionStatus, User, WebhookEvent},
+    event::EventBus,
+    service::{
+        chain::{SignatureStatus, SolanaChain},
+        error,
+        event_outbox::EventOutbox,
+        sql_executor::{QueryMetrics, TransactionSqlExecutor, UserSqlExecutor},
+        webhook::enqueue_webhook_event,
+    },
+};
+
+/// Transaction service for submitting signed Solana transactions and tracking
+/// their status
+///
+/// Transactions are sent to the [`SolanaChain`] as they are, the service never
+/// signs anything. The status is refreshed from the chain when a transaction
+/// is polled, every status change is published as
+/// [`Event::TransactionUpdated`], and a transaction reaching the confirmed
+/// commitment is recorded as [`DomainEvent::TransactionConfirmed`] and posted
+/// to the `transaction.confirmed` webhooks of its user.
+#[derive(Clone)]
+pub struct TransactionService {
+    db: PgPool,
+    chain: Arc<dyn SolanaChain>,
+    event_bus: EventBus,
+    query_metrics: QueryMetrics,
+    event_outbox: EventOutbox,
+}
+
+impl TransactionService {
+    /// Create a new transaction service
+    #[inline]
+    #[must_use]
+    pub const fn new(
+        db: PgPool,
+        chain: Arc<dyn SolanaChain>,
+        event_bus: EventBus,
+        query_metrics: QueryMetrics,
+    ) -> Self {
+        Self { db, chain, event_bus, query_metrics, event_outbox: EventOutbox::new(false) }
+    }
+
+    /// Record the confirmation of transactions in `event_outbox`
+    #[must_use]
+    pub const fn with_event_outbox(mut self, event_outbox: EventOutbox) -> Self {
+        self.event_outbox = event_outbox;
+        self
+    }
+
+    /// Submit a signed transaction on behalf of a user
+    ///
+    /// The transaction is recorded before it is sent, a transaction rejected by
+    /// the RPC endpoint is recorded as failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Transaction is not valid base64 or carries no signature
+    /// - Transaction was already submitted
+    /// - User not found
+    /// - Database operation fails
+    pub async fn submit_transaction(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        transaction: &str,
+    ) -> Result<Transaction> {
+        let wire_transaction = BASE64.decode(transaction).context(error::DecodeTransactionSnafu)?;
+        let signature = first_signature(&wire_transaction)
+            .context(error::MissingTransactionSignatureSnafu)?
+            .to_string();
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user = self.get_user(&mut conn, keycloak_user_id, realm).await?;
+
+        let record = match conn.insert_transaction(&user.id, &signature).await {
+            Err(Error::InsertTransaction { source })
+                if source.as_database_error().is_some_and(|e| e.is_unique_violation()) =>
+            {
+                return Err(Error::TransactionAlreadySubmitted { signature });
+            }
+            result => result?,
+        };
+
+        let (status, failure) = match self.chain.send_transaction(transaction).await {
+            Ok(()) => (TransactionStatus::Submitted, None),
+            Err(err) => {
+                tracing::warn!("Solana transaction {signature} is rejected, error: {err}");
+                (TransactionStatus::Failed, Some(err.to_string()))
+            }
+        };
+
+        let transaction = conn
+            .update_transaction_status(&record.id, status, None, failure.as_deref())
+            .await?
+            .context(error::TransactionNotFoundSnafu { transaction_id: record.id })?;
+        self.event_bus.publish(Event::TransactionUpdated { transaction: transaction.clone() });
+
+        Ok(transaction)
+    }
+
+    /// Get a transaction submitted by a user, refreshing its status if it is
+    /// not final yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User or transaction not found
+    /// - Solana RPC request fails
+    /// - Database operation fails
+    pub async fn get_transaction(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        transaction_id: &Uuid,
+    ) -> Result<Transaction> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user = self.get_user(&mut conn, keycloak_user_id, realm).await?;
+
+        let transaction = conn
+            .get_transaction_by_id(transaction_id, &user.id)
+            .await?
+            .context(error::TransactionNotFoundSnafu { transaction_id: *transaction_id })?;
+
+        if !matches!(
+            transaction.status,
+            TransactionStatus::Submitted | TransactionStatus::Confirmed
+        ) {
+            return Ok(transaction);
+        }
+
+        // signatures are validated before they are stored
+        let Ok(signature) = Signature::from_str(&transaction.signature) else {
+            return Ok(transaction);
+        };
+
+        // the chain does not know the transaction yet
+        let Some(on_chain) = self.chain.get_signature_status(&signature).await? else {
+            return Ok(transaction);
+        };
+        let SignatureStatus { slot, err, confirmation_status } = on_chain;
+
+        let status = match (&err, confirmation_status) {
+            (Some(_), _) => TransactionStatus::Failed,
+            (None, TransactionConfirmationStatus::Finalized) => TransactionStatus::Finalized,
+            (None, TransactionConfirmationStatus::Confirmed) => TransactionStatus::Confirmed,
+            (None, TransactionConfirmationStatus::Processed) => TransactionStatus::Submitted,
+        };
+        if status == transaction.status {
+            return Ok(transaction);
+        }
+
+        // a finalized transaction may never have been seen as confirmed
+        let confirmed =
+            matches!(status, TransactionStatus::Confirmed | TransactionStatus::Finalized)
+                && transaction.status != TransactionStatus::Confirmed;
+
+        let slot = i64::try_from(slot).ok();
+        let mut tx =
+            sqlx::Connection::begin(&mut *conn).await.context(error::BeginTransactionSnafu)?;
+        let transaction = tx
+            .update_transaction_status(&transaction.id, status, slot, err.as_deref())
+            .await?
+            .context(error::TransactionNotFoundSnafu { transaction_id: transaction.id })?;
+        if confirmed {
+            let event = DomainEvent::TransactionConfirmed {
+                transaction_id: transaction.id,
+                user_id: transaction.user_id,
+                signature: transaction.signature.clone(),
+                slot: transaction.slot,
+            };
+            self.event_outbox.record(&mut tx, &event).await?;
+
+            let event = WebhookEvent::TransactionConfirmed {
+                transaction_id: transaction.id,
+                signature: transaction.signature.clone(),
+                slot: transaction.slot,
+            };
+            enqueue_webhook_event(&mut tx, &transaction.user_id, &event).await?;
+        }
+        tx.commit().await.context(error::CommitTransactionSnafu)?;
+        self.event_bus.publish(Event::TransactionUpdated { transaction: transaction.clone() });
+
+        Ok(transaction)
+    }
+
+    async fn get_user(
+        &self,
+        conn: &mut PgConnection,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+    ) -> Result<User> {
+        self.query_metrics
+            .instrument(conn)
+            .get_user_by_keycloak_id(keycloak_user_id, realm, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })
+    }
+}
+
+/// First signature of a transaction in wire format, which identifies the
+/// transaction on-chain
+///
+/// The wire format starts with the number of signatures as compact-u16
+/// followed by the 64-byte signatures.
+fn first_signature(wire_transaction: &[u8]) -> Option<Signature> {
+    // a transaction fits into a packet, so it never has 128 or more signatures
+    // and the count is a single byte
+    let (&count, signatures) = wire_transaction.split_first()?;
+    if count == 0 || count >= 0x80 {
+        return None;
+    }
+
+    Signature::try_from(signatures.get(..64)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_signature() {
+        let mut wire_transaction = vec![1];
+        wire_transaction.extend([7; 64]);
+        wire_transaction.extend([0; 32]);
+        assert_eq!(first_signature(&wire_transaction), Some(Signature::from([7; 64])));
+
+        // no signature
+        assert_eq!(first_signature(&[0]), None);
+        assert_eq!(first_signature(&[]), None);
+        // truncated signature
+        assert_eq!(first_signature(&[1; 10]), None);
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{DirectoryAccount, UserDirectory};
+use crate::{
+    cassette::{request_name, Cassette},
+    service::error::Result,
+};
+
+/// [`UserDirectory`] recording the successful responses of `inner` in
+/// `cassette`
+///
+/// Passwords are left out of the recorded requests, so they never reach the
+/// disk.
+#[derive(Clone)]
+pub struct RecordingUserDirectory {
+    inner: Arc<dyn UserDirectory>,
+    cassette: Cassette,
+}
+
+impl RecordingUserDirectory {
+    #[must_use]
+    pub fn new(inner: Arc<dyn UserDirectory>, cassette: Cassette) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+#[async_trait]
+impl UserDirectory for RecordingUserDirectory {
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid> {
+        let user_id = self.inner.create_user(email, locale).await?;
+        self.cassette.record(request_name("create_user", &json!([email, locale])), &user_id).await;
+        Ok(user_id)
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>> {
+        let user_id = self.inner.find_by_email(email).await?;
+        self.cassette.record(request_name("find_by_email", &json!([email])), &user_id).await;
+        Ok(user_id)
+    }
+
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>> {
+        let accounts = self.inner.list_accounts(first, max).await?;
+        self.cassette.record(request_name("list_accounts", &json!([first, max])), &accounts).await;
+        Ok(accounts)
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<()> {
+        self.inner.delete(user_id).await?;
+        self.cassette.record(request_name("delete", &json!([user_id])), &()).await;
+        Ok(())
+    }
+
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()> {
+        self.inner.set_enabled(user_id, enabled).await?;
+        self.cassette.record(request_name("set_enabled", &json!([user_id, enabled])), &()).await;
+        Ok(())
+    }
+
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()> {
+        self.inner.set_email_verified(user_id, verified).await?;
+        self.cassette
+            .record(request_name("set_email_verified", &json!([user_id, verified])), &())
+            .await;
+        Ok(())
+    }
+
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()> {
+        self.inner.set_email(user_id, email).await?;
+        self.cassette.record(request_name("set_email", &json!([user_id, email])), &()).await;
+        Ok(())
+    }
+
+    async fn set_password(&self, user_id: &Uuid, password: &str) -> Result<()> {
+        self.inner.set_password(user_id, password).await?;
+        self.cassette.record(request_name("set_password", &json!([user_id])), &()).await;
+        Ok(())
+    }
+
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        self.inner.set_profile(user_id, display_name, locale).await?;
+        self.cassette
+            .record(request_name("set_profile", &json!([user_id, display_name, locale])), &())
+            .await;
+        Ok(())
+    }
+}
+
+/// [`UserDirectory`] answering from the responses recorded in `cassette`
+///
+/// Changes to the accounts succeed only if the same change was recorded, they
+/// are not applied to the recorded accounts.
+#[derive(Clone)]
+pub struct ReplayedUserDirectory {
+    cassette: Cassette,
+}
+
+impl ReplayedUserDirectory {
+    #[must_use]
+    pub const fn new(cassette: Cassette) -> Self { Self { cassette } }
+}
+
+#[async_trait]
+impl UserDirectory for ReplayedUserDirectory {
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid> {
+        self.cassette.replay(&request_name("create_user", &json!([email, locale]))).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>> {
+        self.cassette.replay(&request_name("find_by_email", &json!([email]))).await
+    }
+
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>> {
+        self.cassette.replay(&request_name("list_accounts", &json!([first, max]))).await
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<()> {
+        self.cassette.replay(&request_name("delete", &json!([user_id]))).await
+    }
+
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()> {
+        self.cassette.replay(&request_name("set_enabled", &json!([user_id, enabled]))).await
+    }
+
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()> {
+        self.cassette.replay(&request_name("set_email_verified", &json!([user_id, verified]))).await
+    }
+
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()> {
+        self.cassette.replay(&request_name("set_email", &json!([user_id, email]))).await
+    }
+
+    async fn set_password(&self, user_id: &Uuid, _password: &str) -> Result<()> {
+        self.cassette.replay(&request_name("set_password", &json!([user_id]))).await
+    }
+
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        self.cassette
+            .replay(&request_name("set_profile", &json!([user_id, display_name, locale])))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cassette::{CassetteMode, CassetteOptions},
+        service::{error::Error, user_directory::MemoryUserDirectory},
+    };
+
+    #[tokio::test]
+    async fn test_user_directory_is_replayed_as_recorded() {
+        let dir = std::env::temp_dir().join(format!("cassette-test-{}", Uuid::new_v4()));
+        let password = "correct horse battery staple";
+
+        let options = CassetteOptions { mode: CassetteMode::Record, dir: dir.clone() };
+        let recording = RecordingUserDirectory::new(
+            Arc::new(MemoryUserDirectory::default()),
+            options.open("keycloak").unwrap(),
+        );
+        let user_id = recording.create_user("user@example.com", Some("en")).await.unwrap();
+        assert_eq!(recording.find_by_email("user@example.com").await.unwrap(), Some(user_id));
+        recording.set_email_verified(&user_id, true).await.unwrap();
+        recording.set_password(&user_id, password).await.unwrap();
+        let accounts = recording.list_accounts(0, 10).await.unwrap();
+
+        // passwords never reach the disk
+        let content = std::fs::read_to_string(dir.join("keycloak.json")).unwrap();
+        assert!(!content.contains(password));
+
+        let options = CassetteOptions { mode: CassetteMode::Replay, dir: dir.clone() };
+        let replayed = ReplayedUserDirectory::new(options.open("keycloak").unwrap());
+        assert_eq!(replayed.create_user("user@example.com", Some("en")).await.unwrap(), user_id);
+        assert_eq!(replayed.find_by_email("user@example.com").await.unwrap(), Some(user_id));
+        replayed.set_email_verified(&user_id, true).await.unwrap();
+        replayed.set_password(&user_id, "any password").await.unwrap();
+        assert_eq!(replayed.list_accounts(0, 10).await.unwrap(), accounts);
+
+        // changes which were never recorded fail
+        assert!(matches!(
+            replayed.find_by_email("other@example.com").await,
+            Err(Error::NoRecordedResponse { cassette: "keycloak", .. })
+        ));
+        assert!(matches!(
+            replayed.set_enabled(&user_id, false).await,
+            Err(Error::NoRecordedResponse { cassette: "keycloak", .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{DirectoryAccount, UserDirectory};
+use crate::{
+    circuit_breaker::CircuitBreaker,
+    service::error::{Error, Result},
+};
+
+/// [`UserDirectory`] calling `inner` through a [`CircuitBreaker`]
+#[derive(Clone)]
+pub struct CircuitBreakingUserDirectory {
+    inner: Arc<dyn UserDirectory>,
+    breaker: CircuitBreaker,
+}
+
+impl CircuitBreakingUserDirectory {
+    #[must_use]
+    pub fn new(inner: Arc<dyn UserDirectory>, breaker: CircuitBreaker) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl UserDirectory for CircuitBreakingUserDirectory {
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid> {
+        self.breaker.call(self.inner.create_user(email, locale), Error::is_dependency_failure).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>> {
+        self.breaker.call(self.inner.find_by_email(email), Error::is_dependency_failure).await
+    }
+
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>> {
+        self.breaker.call(self.inner.list_accounts(first, max), Error::is_dependency_failure).await
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<()> {
+        self.breaker.call(self.inner.delete(user_id), Error::is_dependency_failure).await
+    }
+
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()> {
+        self.breaker
+            .call(self.inner.set_enabled(user_id, enabled), Error::is_dependency_failure)
+            .await
+    }
+
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()> {
+        self.breaker
+            .call(self.inner.set_email_verified(user_id, verified), Error::is_dependency_failure)
+            .await
+    }
+
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()> {
+        self.breaker.call(self.inner.set_email(user_id, email), Error::is_dependency_failure).await
+    }
+
+    async fn set_password(&self, user_id: &Uuid, password: &str) -> Result<()> {
+        self.breaker
+            .call(self.inner.set_password(user_id, password), Error::is_dependency_failure)
+            .await
+    }
+
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        self.breaker
+            .call(
+                self.inner.set_profile(user_id, display_name, locale),
+                Error::is_dependency_failure,
+            )
+            .await
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{DirectoryAccount, UserDirectory};
+use crate::{dependency_metrics::DependencyMetrics, service::error::Result};
+
+/// Dependency label of the calls to the directory
+const DEPENDENCY: &str = "keycloak";
+
+/// [`UserDirectory`] recording the latency of the calls to `inner` in
+/// [`DependencyMetrics`]
+#[derive(Clone)]
+pub struct InstrumentedUserDirectory {
+    inner: Arc<dyn UserDirectory>,
+    metrics: DependencyMetrics,
+}
+
+impl InstrumentedUserDirectory {
+    #[must_use]
+    pub fn new(inner: Arc<dyn UserDirectory>, metrics: DependencyMetrics) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+#[async_trait]
+impl UserDirectory for InstrumentedUserDirectory {
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid> {
+        self.metrics.observe(DEPENDENCY, "create_user", self.inner.create_user(email, locale)).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>> {
+        self.metrics.observe(DEPENDENCY, "find_by_email", self.inner.find_by_email(email)).await
+    }
+
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>> {
+        self.metrics
+            .observe(DEPENDENCY, "list_accounts", self.inner.list_accounts(first, max))
+            .await
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<()> {
+        self.metrics.observe(DEPENDENCY, "delete", self.inner.delete(user_id)).await
+    }
+
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()> {
+        self.metrics
+            .observe(DEPENDENCY, "set_enabled", self.inner.set_enabled(user_id, enabled))
+            .await
+    }
+
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()> {
+        self.metrics
+            .observe(
+                DEPENDENCY,
+                "set_email_verified",
+                self.inner.set_email_verified(user_id, verified),
+            )
+            .await
+    }
+
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()> {
+        self.metrics.observe(DEPENDENCY, "set_email", self.inner.set_email(user_id, email)).await
+    }
+
+    async fn set_password(&self, user_id: &Uuid, password: &str) -> Result<()> {
+        self.metrics
+            .observe(DEPENDENCY, "set_password", self.inner.set_password(user_id, password))
+            .await
+    }
+
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        self.metrics
+            .observe(
+                DEPENDENCY,
+                "set_profile",
+                self.inner.set_profile(user_id, display_name, locale),
+            )
+            .await
+    }
+}
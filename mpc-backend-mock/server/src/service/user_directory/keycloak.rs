@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use keycloak::{
+    types::{CredentialRepresentation, UserRepresentation},
+    KeycloakAdmin, KeycloakError, KeycloakServiceAccountAdminTokenRetriever,
+};
+use snafu::ResultExt;
+use uuid::Uuid;
+
+use super::{DirectoryAccount, UserDirectory};
+use crate::service::{
+    error::{self, Error, Result},
+    user_management::normalize_email,
+};
+
+/// [`UserDirectory`] managing the users of a Keycloak realm through the admin
+/// API
+#[derive(Clone)]
+pub struct KeycloakUserDirectory {
+    keycloak_admin: Arc<KeycloakAdmin<KeycloakServiceAccountAdminTokenRetriever>>,
+    realm: String,
+}
+
+impl KeycloakUserDirectory {
+    #[inline]
+    #[must_use]
+    pub const fn new(
+        keycloak_admin: Arc<KeycloakAdmin<KeycloakServiceAccountAdminTokenRetriever>>,
+        realm: String,
+    ) -> Self {
+        Self { keycloak_admin, realm }
+    }
+
+    async fn update_user(&self, user_id: &Uuid, user: UserRepresentation) -> Result<()> {
+        let _response = self
+            .keycloak_admin
+            .realm_users_with_user_id_put(&self.realm, &user_id.to_string(), user)
+            .await
+            .context(error::UpdateKeycloakUserSnafu)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserDirectory for KeycloakUserDirectory {
+    /// The user ID is taken from the `Location` header of the create response.
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid> {
+        let user = UserRepresentation {
+            email: Some(email.to_string()),
+            username: Some(email.to_string()),
+            enabled: Some(true),
+            email_verified: Some(false),
+            attributes: locale.map(|locale| {
+                [("locale".to_string(), vec![locale.to_string()])].into_iter().collect()
+            }),
+            ..Default::default()
+        };
+
+        let response = match self.keycloak_admin.realm_users_post(&self.realm, user).await {
+            Ok(response) => response,
+            Err(KeycloakError::HttpFailure { status: 409, .. }) => {
+                return Err(Error::UserExistsInKeycloak { email: email.to_string() });
+            }
+            Err(source) => return Err(Error::CreateKeycloakUser { source }),
+        };
+
+        response
+            .to_id()
+            .and_then(|keycloak_id| Uuid::parse_str(keycloak_id).ok())
+            .ok_or_else(|| Error::KeycloakUserNotFound { email: email.to_string() })
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>> {
+        // the search matches substrings, only an exact match counts
+        let users = self
+            .keycloak_admin
+            .realm_users_get(
+                &self.realm,
+                None,
+                Some(email.to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .context(error::GetKeycloakUserSnafu)?;
+
+        Ok(users
+            .into_iter()
+            .filter(|user| user.email.as_deref().is_some_and(|e| normalize_email(e) == email))
+            .find_map(|user| user.id.and_then(|id| Uuid::parse_str(&id).ok())))
+    }
+
+    /// Keycloak orders the users of a realm by username.
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>> {
+        let users = self
+            .keycloak_admin
+            .realm_users_get(
+                &self.realm,
+                Some(true),
+                None,
+                None,
+                None,
+                None,
+                Some(i32::try_from(first).unwrap_or(i32::MAX)),
+                None,
+                None,
+                None,
+                None,
+                Some(i32::try_from(max).unwrap_or(i32::MAX)),
+                None,
+                None,
+                None,
+            )
+            .await
+            .context(error::ListKeycloakUsersSnafu)?;
+
+        Ok(users
+            .into_iter()
+            .filter_map(|user| {
+                Some(DirectoryAccount {
+                    id: Uuid::parse_str(user.id.as_deref()?).ok()?,
+                    enabled: user.enabled.unwrap_or(false),
+                    created_at: user.created_timestamp.and_then(DateTime::from_timestamp_millis),
+                })
+            })
+            .collect())
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<()> {
+        match self
+            .keycloak_admin
+            .realm_users_with_user_id_delete(&self.realm, &user_id.to_string())
+            .await
+        {
+            Ok(_) | Err(KeycloakError::HttpFailure { status: 404, .. }) => Ok(()),
+            Err(source) => Err(Error::DeleteKeycloakUser { source }),
+        }
+    }
+
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()> {
+        self.update_user(
+            user_id,
+            UserRepresentation { enabled: Some(enabled), ..Default::default() },
+        )
+        .await
+    }
+
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()> {
+        self.update_user(
+            user_id,
+            UserRepresentation { email_verified: Some(verified), ..Default::default() },
+        )
+        .await
+    }
+
+    /// The username is kept, users log in with their email.
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()> {
+        let user = UserRepresentation {
+            email: Some(email.to_string()),
+            email_verified: Some(true),
+            ..Default::default()
+        };
+
+        match self
+            .keycloak_admin
+            .realm_users_with_user_id_put(&self.realm, &user_id.to_string(), user)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(KeycloakError::HttpFailure { status: 409, .. }) => {
+                Err(Error::UserExistsInKeycloak { email: email.to_string() })
+            }
+            Err(source) => Err(Error::UpdateKeycloakUser { source }),
+        }
+    }
+
+    async fn set_password(&self, user_id: &Uuid, password: &str) -> Result<()> {
+        let credential = CredentialRepresentation {
+            type_: Some("password".to_string()),
+            value: Some(password.to_string()),
+            temporary: Some(false),
+            ..Default::default()
+        };
+
+        match self
+            .keycloak_admin
+            .realm_users_with_user_id_reset_password_put(
+                &self.realm,
+                &user_id.to_string(),
+                credential,
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            // the password policy of the realm rejects the password
+            Err(source @ KeycloakError::HttpFailure { status: 400, .. }) => {
+                Err(Error::PasswordRejected { source })
+            }
+            Err(source) => Err(Error::SetKeycloakPassword { source }),
+        }
+    }
+
+    /// The display name is the first name of the account and the locale is
+    /// the `locale` attribute Keycloak localizes its pages with.
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        if display_name.is_none() && locale.is_none() {
+            return Ok(());
+        }
+
+        // attributes are replaced as a whole, so the current ones are carried
+        // over
+        let attributes = match locale {
+            Some(locale) => {
+                let mut attributes = self
+                    .keycloak_admin
+                    .realm_users_with_user_id_get(&self.realm, &user_id.to_string(), None)
+                    .await
+                    .context(error::GetKeycloakUserSnafu)?
+                    .attributes
+                    .unwrap_or_default();
+                let _previous = attributes.insert("locale".to_string(), vec![locale.to_string()]);
+                Some(attributes)
+            }
+            None => None,
+        };
+
+        self.update_user(
+            user_id,
+            UserRepresentation {
+                first_name: display_name.map(ToString::to_string),
+                attributes,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+}
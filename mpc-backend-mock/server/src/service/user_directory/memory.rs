@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{DirectoryAccount, UserDirectory};
+use crate::service::error::{Error, Result};
+
+/// Account of a [`MemoryUserDirectory`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DirectoryUser {
+    pub email: String,
+
+    pub enabled: bool,
+
+    pub email_verified: bool,
+
+    pub password: Option<String>,
+
+    pub display_name: Option<String>,
+
+    pub locale: Option<String>,
+}
+
+/// In-process [`UserDirectory`], for tests which do not run Keycloak
+#[derive(Clone, Debug, Default)]
+pub struct MemoryUserDirectory {
+    users: Arc<Mutex<HashMap<Uuid, DirectoryUser>>>,
+}
+
+impl MemoryUserDirectory {
+    /// Account with `user_id`, `None` if it does not exist
+    #[must_use]
+    pub fn get(&self, user_id: &Uuid) -> Option<DirectoryUser> {
+        self.users().get(user_id).cloned()
+    }
+
+    fn users(&self) -> MutexGuard<'_, HashMap<Uuid, DirectoryUser>> {
+        self.users.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, user_id: &Uuid, update: impl FnOnce(&mut DirectoryUser)) -> Result<()> {
+        let mut users = self.users();
+        let user =
+            users.get_mut(user_id).ok_or(Error::DirectoryUserNotFound { user_id: *user_id })?;
+        update(user);
+        drop(users);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserDirectory for MemoryUserDirectory {
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid> {
+        let mut users = self.users();
+        if users.values().any(|user| user.email == email) {
+            return Err(Error::UserExistsInKeycloak { email: email.to_string() });
+        }
+
+        let user_id = Uuid::new_v4();
+        let _unused = users.insert(
+            user_id,
+            DirectoryUser {
+                email: email.to_string(),
+                enabled: true,
+                email_verified: false,
+                password: None,
+                display_name: None,
+                locale: locale.map(ToString::to_string),
+            },
+        );
+        drop(users);
+
+        Ok(user_id)
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>> {
+        Ok(self.users().iter().find(|(_, user)| user.email == email).map(|(user_id, _)| *user_id))
+    }
+
+    /// Accounts are ordered by ID and have no creation time.
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>> {
+        let mut accounts = self
+            .users()
+            .iter()
+            .map(|(id, user)| DirectoryAccount { id: *id, enabled: user.enabled, created_at: None })
+            .collect::<Vec<_>>();
+        accounts.sort_unstable_by_key(|account| account.id);
+
+        Ok(accounts.into_iter().skip(first as usize).take(max as usize).collect())
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<()> {
+        let _unused = self.users().remove(user_id);
+        Ok(())
+    }
+
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()> {
+        self.update(user_id, |user| user.enabled = enabled)
+    }
+
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()> {
+        self.update(user_id, |user| user.email_verified = verified)
+    }
+
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()> {
+        if self.users().iter().any(|(id, user)| id != user_id && user.email == email) {
+            return Err(Error::UserExistsInKeycloak { email: email.to_string() });
+        }
+        self.update(user_id, |user| {
+            user.email = email.to_string();
+            user.email_verified = true;
+        })
+    }
+
+    async fn set_password(&self, user_id: &Uuid, password: &str) -> Result<()> {
+        self.update(user_id, |user| user.password = Some(password.to_string()))
+    }
+
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()> {
+        self.update(user_id, |user| {
+            if let Some(display_name) = display_name {
+                user.display_name = Some(display_name.to_string());
+            }
+            if let Some(locale) = locale {
+                user.locale = Some(locale.to_string());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_user_directory() {
+        let directory = MemoryUserDirectory::default();
+
+        let user_id = directory.create_user("user@example.com", Some("en")).await.unwrap();
+        assert_eq!(directory.get(&user_id).unwrap().locale.as_deref(), Some("en"));
+        assert!(matches!(
+            directory.create_user("user@example.com", None).await,
+            Err(Error::UserExistsInKeycloak { .. })
+        ));
+        assert_eq!(directory.find_by_email("user@example.com").await.unwrap(), Some(user_id));
+        assert_eq!(directory.find_by_email("other@example.com").await.unwrap(), None);
+
+        directory.set_email_verified(&user_id, true).await.unwrap();
+        directory.set_enabled(&user_id, false).await.unwrap();
+        directory.set_password(&user_id, "correct horse battery staple").await.unwrap();
+        directory.set_profile(&user_id, Some("User"), None).await.unwrap();
+        directory.set_profile(&user_id, None, Some("zh-TW")).await.unwrap();
+        assert_eq!(
+            directory.get(&user_id),
+            Some(DirectoryUser {
+                email: "user@example.com".to_string(),
+                enabled: false,
+                email_verified: true,
+                password: Some("correct horse battery staple".to_string()),
+                display_name: Some("User".to_string()),
+                locale: Some("zh-TW".to_string()),
+            })
+        );
+
+        assert_eq!(
+            directory.list_accounts(0, 10).await.unwrap(),
+            vec![DirectoryAccount { id: user_id, enabled: false, created_at: None }]
+        );
+        assert!(directory.list_accounts(1, 10).await.unwrap().is_empty());
+
+        directory.delete(&user_id).await.unwrap();
+        assert_eq!(directory.get(&user_id), None);
+        // deleting twice is fine, updating a missing account is not
+        directory.delete(&user_id).await.unwrap();
+        assert!(matches!(
+            directory.set_enabled(&user_id, true).await,
+            Err(Error::DirectoryUserNotFound { .. })
+        ));
+    }
+}
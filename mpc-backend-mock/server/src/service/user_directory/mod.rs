@@ -0,0 +1,92 @@
+//! Identity provider holding the accounts users log in with.
+//!
+//! [`UserManagementService`](super::UserManagementService) keeps the accounts
+//! in step with the database through [`UserDirectory`], backed by Keycloak
+//! with [`KeycloakUserDirectory`], or kept in process by
+//! [`MemoryUserDirectory`] for tests. The responses of Keycloak can be
+//! recorded with [`RecordingUserDirectory`] and served again by
+//! [`ReplayedUserDirectory`], see [`crate::cassette`].
+
+mod cassette;
+mod circuit_breaking;
+mod instrumented;
+mod keycloak;
+mod memory;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use self::{
+    cassette::{RecordingUserDirectory, ReplayedUserDirectory},
+    circuit_breaking::CircuitBreakingUserDirectory,
+    instrumented::InstrumentedUserDirectory,
+    keycloak::KeycloakUserDirectory,
+    memory::{DirectoryUser, MemoryUserDirectory},
+};
+use crate::service::error::Result;
+
+/// Account listed by [`UserDirectory::list_accounts`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryAccount {
+    pub id: Uuid,
+
+    pub enabled: bool,
+
+    /// `None` if the directory does not record when the account was created
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait UserDirectory: Send + Sync {
+    /// Create an enabled account with an unverified email and an optional
+    /// locale, returning its ID
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UserExistsInKeycloak`](crate::service::error::Error::UserExistsInKeycloak)
+    /// if the email is taken.
+    async fn create_user(&self, email: &str, locale: Option<&str>) -> Result<Uuid>;
+
+    /// ID of the account with the normalized `email`
+    async fn find_by_email(&self, email: &str) -> Result<Option<Uuid>>;
+
+    /// Page of at most `max` accounts starting at offset `first`, in a stable
+    /// order
+    async fn list_accounts(&self, first: u32, max: u32) -> Result<Vec<DirectoryAccount>>;
+
+    /// Delete an account, an account already missing is not an error
+    async fn delete(&self, user_id: &Uuid) -> Result<()>;
+
+    /// Enable or disable an account, a disabled account cannot log in
+    async fn set_enabled(&self, user_id: &Uuid, enabled: bool) -> Result<()>;
+
+    /// Mark the email of an account as verified or not
+    async fn set_email_verified(&self, user_id: &Uuid, verified: bool) -> Result<()>;
+
+    /// Replace the email of an account with the verified `email`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UserExistsInKeycloak`](crate::service::error::Error::UserExistsInKeycloak)
+    /// if another account has the email.
+    async fn set_email(&self, user_id: &Uuid, email: &str) -> Result<()>;
+
+    /// Replace the password of an account, the new one is not temporary
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PasswordRejected`](crate::service::error::Error::PasswordRejected)
+    /// if the password does not satisfy the password policy of the directory.
+    async fn set_password(&self, user_id: &Uuid, password: &str) -> Result<()>;
+
+    /// Set the display name and the locale of an account, `None` keeps the
+    /// current value
+    async fn set_profile(
+        &self,
+        user_id: &Uuid,
+        display_name: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<()>;
+}
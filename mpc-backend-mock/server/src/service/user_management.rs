@@ -1,92 +1,666 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
 
-use keycloak::{
-    types::UserRepresentation, KeycloakAdmin, KeycloakServiceAccountAdminTokenRetriever,
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{NaiveDate, NaiveTime, TimeDelta, Utc};
+use futures::{Stream, TryStreamExt};
+use mpc_backend_mock_core::{
+    config::{ActivationConfig, TransactionRetryConfig},
+    model::{Email, Fields, Pagination, Sort},
 };
+use notification::{Notification, Recipients};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
 use snafu::ResultExt;
 use sqlx::PgPool;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
 use uuid::Uuid;
 
 use super::error::{Error, Result};
 use crate::{
-    entity::User,
-    service::{error, sql_executor::UserSqlExecutor},
+    entity::{
+        DomainEvent, Event, ExportFormat, ListUsersFilter, ListedUser,
+        UpdateUserPreferencesRequest, UpdateUserProfileRequest, User, UserField, UserPreferences,
+        UserProfile, UserSortColumn, WebhookEvent,
+    },
+    event::EventBus,
+    service::{
+        error,
+        event_outbox::EventOutbox,
+        notification::enqueue_notification,
+        read_pool::ReadPool,
+        retry::retry_transaction,
+        sql_executor::{
+            ActivationTokenSqlExecutor, EmailChangeRequestSqlExecutor, LoginAttemptSqlExecutor,
+            QueryMetrics, UserSqlExecutor,
+        },
+        user_directory::{DirectoryAccount, UserDirectory},
+        webhook::enqueue_webhook_event,
+    },
 };
 
+/// Number of users deleted per batch by
+/// [`UserManagementService::delete_users_by_pattern`]
+const BULK_DELETE_BATCH_SIZE: usize = 100;
+
+/// Attempts to delete the Keycloak user of a creation which failed in the
+/// database, the delay before a retry doubles from
+/// [`COMPENSATION_RETRY_DELAY`]
+const COMPENSATION_ATTEMPTS: u32 = 3;
+
+const COMPENSATION_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Number of Keycloak accounts listed per request by
+/// [`UserManagementService::reconcile_users`]
+const RECONCILE_PAGE_SIZE: u32 = 100;
+
+/// Drift between Keycloak and the database repaired by
+/// [`UserManagementService::reconcile_users`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UserReconciliation {
+    /// Keycloak accounts without a user, left behind by failed creations
+    pub deleted_accounts: u64,
+
+    /// Keycloak accounts enabled or disabled to match whether their user is
+    /// soft-deleted
+    pub toggled_accounts: u64,
+
+    /// IDs of users without a Keycloak account, they cannot log in and are
+    /// not repaired automatically
+    pub users_without_account: Vec<Uuid>,
+}
+
 /// User management service for handling user-related operations
 #[derive(Clone)]
 pub struct UserManagementService {
     db: PgPool,
-    keycloak_admin: Arc<KeycloakAdmin<KeycloakServiceAccountAdminTokenRetriever>>,
-    realm: String,
+    /// Serves the lookups and listings of users
+    read_pool: ReadPool,
+    user_directory: Arc<dyn UserDirectory>,
+    /// Directories of the realms other than the primary one, by realm
+    realm_directories: HashMap<String, Arc<dyn UserDirectory>>,
+    activation: ActivationConfig,
+    event_bus: EventBus,
+    query_metrics: QueryMetrics,
+    creates_in_flight: InFlightLocks,
+    transaction_retry: TransactionRetryConfig,
+    event_outbox: EventOutbox,
 }
 
 impl UserManagementService {
     /// Create a new user management service
     #[inline]
     #[must_use]
-    pub const fn new(
+    pub fn new(
         db: PgPool,
-        keycloak_admin: Arc<KeycloakAdmin<KeycloakServiceAccountAdminTokenRetriever>>,
-        realm: String,
+        user_directory: Arc<dyn UserDirectory>,
+        activation: &ActivationConfig,
+        event_bus: EventBus,
+        query_metrics: QueryMetrics,
+    ) -> Self {
+        Self {
+            read_pool: ReadPool::new(db.clone(), Vec::new()),
+            db,
+            user_directory,
+            realm_directories: HashMap::new(),
+            activation: activation.clone(),
+            event_bus,
+            query_metrics,
+            creates_in_flight: InFlightLocks::default(),
+            transaction_retry: TransactionRetryConfig::default(),
+            event_outbox: EventOutbox::default(),
+        }
+    }
+
+    /// Look users up and list them on `replicas`, falling back to the primary
+    #[must_use]
+    pub fn with_read_replicas(mut self, replicas: Vec<PgPool>) -> Self {
+        self.read_pool = ReadPool::new(self.db.clone(), replicas);
+        self
+    }
+
+    /// Retry transactions aborted by a serialization failure or a deadlock as
+    /// configured by `transaction_retry`
+    #[must_use]
+    pub const fn with_transaction_retry(
+        mut self,
+        transaction_retry: TransactionRetryConfig,
     ) -> Self {
-        Self { db, keycloak_admin, realm }
+        self.transaction_retry = transaction_retry;
+        self
+    }
+
+    /// Record the creation and deletion of users in `event_outbox`
+    #[must_use]
+    pub const fn with_event_outbox(mut self, event_outbox: EventOutbox) -> Self {
+        self.event_outbox = event_outbox;
+        self
+    }
+
+    /// Manage the users of `realm` in `directory`
+    #[must_use]
+    pub fn with_realm_directory(mut self, realm: &str, directory: Arc<dyn UserDirectory>) -> Self {
+        let _previous = self.realm_directories.insert(realm.to_string(), directory);
+        self
+    }
+
+    /// Directory of the users of `realm`, `None` for the primary realm
+    fn user_directory(&self, realm: Option<&str>) -> Result<&Arc<dyn UserDirectory>> {
+        match realm {
+            None => Ok(&self.user_directory),
+            Some(realm) => self
+                .realm_directories
+                .get(realm)
+                .ok_or_else(|| Error::UnknownRealm { realm: realm.to_string() }),
+        }
     }
 
-    /// Create a new user
+    /// Create a new user in `realm`, `None` for the primary realm
+    ///
+    /// The user is inactive until the activation token sent to its email is
+    /// redeemed with [`Self::activate_user`]. The activation email is queued
+    /// with the user and sent by the
+    /// [`NotificationService`](super::NotificationService), in `locale` if
+    /// given, which is stored on the user's profile. Emails are unique within
+    /// a realm.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - Email is invalid
+    /// - Email, locale or realm is invalid
     /// - User already exists in database
     /// - User already exists in Keycloak
     /// - Keycloak user creation fails
     /// - Database operation fails
-    pub async fn create_user(&self, email: &str) -> Result<User> {
-        // Validate email format
-        if !Self::is_valid_email(email) {
-            return Err(Error::InvalidEmail { email: email.to_string() });
+    pub async fn create_user(
+        &self,
+        email: &str,
+        realm: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<User> {
+        let email = &parse_email(email)?.into_inner();
+        if locale.is_some_and(|locale| !is_language_tag(locale)) {
+            return Err(Error::InvalidUserProfile {
+                reason: "locale must be a BCP 47 language tag",
+            });
         }
+        let user_directory = self.user_directory(realm)?;
+
+        // Step 1: Wait for concurrent creations of the same email in the realm, so
+        // only one of them reaches Keycloak
+        let _in_flight =
+            self.creates_in_flight.lock(&format!("{}/{email}", realm.unwrap_or_default())).await;
+
+        // Step 2: Create user in Keycloak, a conflict means the email is taken
+        let keycloak_user_id = match user_directory.create_user(email, locale).await {
+            Err(Error::UserExistsInKeycloak { .. }) => {
+                return Err(self.user_exists_error(email, realm).await);
+            }
+            result => result?,
+        };
+
+        // Step 3: Create user in system database with Keycloak user ID and send its
+        // activation token, the unique index on the realm and email catches users
+        // the database already has, a soft-deleted user still owns its email and has
+        // to be restored instead
+        let insert_user = retry_transaction(&self.transaction_retry, "create_user", || {
+            self.insert_inactive_user(email, &keycloak_user_id, realm, locale)
+        });
+        match insert_user.await {
+            Ok(user) => Ok(user),
+            Err(err) => {
+                // do not leave an orphaned Keycloak user behind, the error of the
+                // database is returned even if the compensation fails
+                self.delete_orphaned_account(user_directory, &keycloak_user_id).await;
+
+                if let Error::InsertUser { source } = &err {
+                    if source.as_database_error().is_some_and(|e| e.is_unique_violation()) {
+                        return Err(Error::UserAlreadyExists { email: email.to_string() });
+                    }
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Delete the Keycloak user of a failed creation, retrying with backoff
+    ///
+    /// An account which cannot be deleted is left to
+    /// [`Self::reconcile_users`].
+    async fn delete_orphaned_account(
+        &self,
+        user_directory: &Arc<dyn UserDirectory>,
+        keycloak_user_id: &Uuid,
+    ) {
+        let mut delay = COMPENSATION_RETRY_DELAY;
+        for attempt in 1..=COMPENSATION_ATTEMPTS {
+            match user_directory.delete(keycloak_user_id).await {
+                Ok(()) => return,
+                Err(err) if attempt < COMPENSATION_ATTEMPTS => {
+                    tracing::warn!(
+                        "Failed to delete orphaned Keycloak user {keycloak_user_id} (attempt \
+                         {attempt}/{COMPENSATION_ATTEMPTS}), error: {err}"
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to delete orphaned Keycloak user {keycloak_user_id}, it is left \
+                         to the user reconciliation, error: {err}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Repair drift between the Keycloak accounts and the users of the
+    /// database
+    ///
+    /// - Accounts without a user, soft-deleted users included, are deleted
+    /// - Accounts are enabled if their user is active and disabled if it is
+    ///   soft-deleted or its email is locked after repeated failed logins
+    /// - Users without an account are reported, they are not repaired
+    ///
+    /// Accounts and users created within `grace_period` are skipped, they may
+    /// belong to a creation in progress. Accounts whose creation time is
+    /// unknown are not skipped. Only the users of the primary realm are
+    /// reconciled, the directory holds no other realm.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a Keycloak or database operation fails, repairs
+    /// made before the failure are kept
+    pub async fn reconcile_users(&self, grace_period: Duration) -> Result<UserReconciliation> {
+        let created_before = Utc::now() - TimeDelta::from_std(grace_period).unwrap_or_default();
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let locked_emails = conn
+            .list_locked_logins()
+            .await?
+            .into_iter()
+            .map(|attempt| attempt.email)
+            .collect::<HashSet<_>>();
+        let enabled =
+            |user: &User| user.deleted_at.is_none() && !locked_emails.contains(&user.email);
 
+        // repairs are made once every page is listed, deleting accounts while
+        // listing would shift the pages
+        let mut account_ids = Vec::new();
+        let mut orphaned_accounts = Vec::new();
+        let mut toggled_accounts = Vec::new();
+        let mut first = 0;
+        loop {
+            let accounts = self.user_directory.list_accounts(first, RECONCILE_PAGE_SIZE).await?;
+            let ids = accounts.iter().map(|account| account.id).collect::<Vec<_>>();
+            let users = self
+                .query_metrics
+                .instrument(&mut conn)
+                .list_users_by_keycloak_ids(&ids)
+                .await?
+                .into_iter()
+                .filter(|user| user.realm.is_none())
+                .map(|user| (user.keycloak_user_id, user))
+                .collect::<HashMap<_, _>>();
+
+            let page_len = accounts.len();
+            for account in accounts {
+                match users.get(&account.id) {
+                    None if account.created_at.is_none_or(|at| at < created_before) => {
+                        orphaned_accounts.push(account.id);
+                    }
+                    None => {}
+                    Some(user) if account.enabled != enabled(user) => {
+                        toggled_accounts
+                            .push(DirectoryAccount { enabled: enabled(user), ..account });
+                    }
+                    Some(_) => {}
+                }
+            }
+            account_ids.extend(ids);
+
+            if page_len < RECONCILE_PAGE_SIZE as usize {
+                break;
+            }
+            first += RECONCILE_PAGE_SIZE;
+        }
+
+        let mut reconciliation = UserReconciliation::default();
+        for account_id in orphaned_accounts {
+            self.user_directory.delete(&account_id).await?;
+            tracing::info!("Deleted Keycloak user {account_id} which has no user");
+            reconciliation.deleted_accounts += 1;
+        }
+        for account in toggled_accounts {
+            self.user_directory.set_enabled(&account.id, account.enabled).await?;
+            tracing::info!(
+                "Set Keycloak user {} enabled to {} to match its user",
+                account.id,
+                account.enabled
+            );
+            reconciliation.toggled_accounts += 1;
+        }
+
+        reconciliation.users_without_account = self
+            .query_metrics
+            .instrument(&mut conn)
+            .list_users_not_in_keycloak_ids(&account_ids, created_before)
+            .await?
+            .into_iter()
+            .map(|user| {
+                tracing::warn!(
+                    "User {} has no Keycloak user {}, it cannot log in",
+                    user.id,
+                    user.keycloak_user_id
+                );
+                user.id
+            })
+            .collect();
+
+        Ok(reconciliation)
+    }
+
+    /// Insert an inactive user with the profile of its locale and queue the
+    /// email of its activation token in the same transaction
+    async fn insert_inactive_user(
+        &self,
+        email: &str,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<User> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let user = self
+            .query_metrics
+            .instrument(&mut tx)
+            .insert_user(email, keycloak_user_id, realm, false)
+            .await?;
+
+        if let Some(locale) = locale {
+            let profile = UpdateUserProfileRequest {
+                locale: Some(locale.to_string()),
+                ..UpdateUserProfileRequest::default()
+            };
+            let _profile =
+                self.query_metrics.instrument(&mut tx).update_user(&user.id, &profile).await?;
+        }
+
+        let (token, token_hash) = generate_token();
+        let expires_at = Utc::now() + self.activation.token_ttl;
+        tx.insert_activation_token(&user.id, &token_hash, expires_at).await?;
+
+        let notification = Notification::ActivationEmail {
+            recipients: Recipients::new(user.email.clone()),
+            link: format!("{}?token={token}", self.activation.url),
+            locale: locale.map(ToString::to_string),
+        };
+        let _notification_id = enqueue_notification(&mut tx, &notification).await?;
+
+        let event = DomainEvent::UserCreated {
+            user_id: user.id,
+            email: user.email.clone(),
+            keycloak_user_id: user.keycloak_user_id,
+        };
+        self.event_outbox.record(&mut tx, &event).await?;
+
+        tx.commit().await.context(error::CommitTransactionSnafu)?;
+
+        Ok(user)
+    }
+
+    /// Activate the user an activation token was sent to
+    ///
+    /// The token is single-use, the user is marked active and its email is
+    /// marked verified in Keycloak. The activation is posted to the
+    /// `user.activated` webhooks of the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Token is unknown, expired or already used
+    /// - User was deleted
+    /// - Keycloak or database operation fails
+    pub async fn activate_user(&self, token: &str) -> Result<User> {
         let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
 
-        // Step 1: Check if user already exists in system database
-        let existing_user = tx.get_user_by_email(email).await?;
+        let user_id = tx
+            .consume_activation_token(&hash_token(token))
+            .await?
+            .ok_or(Error::InvalidActivationToken)?;
+
+        let user = self
+            .query_metrics
+            .instrument(&mut tx)
+            .activate_user_by_id(&user_id)
+            .await?
+            .ok_or(Error::UserNotFound { user_id })?;
+        let event = WebhookEvent::UserActivated { user_id: user.id, email: user.email.clone() };
+        enqueue_webhook_event(&mut tx, &user.id, &event).await?;
+
+        // the token stays unused if Keycloak fails, the transaction is rolled back
+        // on drop
+        self.user_directory(user.realm.as_deref())?
+            .set_email_verified(&user.keycloak_user_id, true)
+            .await?;
+
+        tx.commit().await.context(error::CommitTransactionSnafu)?;
+
+        self.event_bus.publish(Event::UserUpdated { user: user.clone() });
+
+        Ok(user)
+    }
+
+    /// Delete expired activation tokens, returns the number of deleted tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn delete_expired_activation_tokens(&self) -> Result<u64> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.delete_expired_activation_tokens().await
+    }
 
-        if existing_user.is_some() {
-            return Err(Error::UserAlreadyExists { email: email.to_string() });
+    /// Stage `new_email` as the email of the user with `keycloak_user_id` in
+    /// `realm`, `None` for the primary realm
+    ///
+    /// A link with a single-use token is emailed to the new address in the
+    /// user's locale, the email only changes once the token is redeemed with
+    /// [`Self::confirm_email_change`]. A new request replaces the pending one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Email is invalid or is the current email
+    /// - User is not found or belongs to another realm than the primary one
+    /// - Email is taken in the database or Keycloak
+    /// - Keycloak or database operation fails
+    pub async fn request_email_change(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        new_email: &str,
+    ) -> Result<()> {
+        let new_email = parse_email(new_email)?.into_inner();
+        if realm.is_some() {
+            return Err(Error::InvalidEmailChange {
+                reason: "only users of the primary realm can change their email",
+            });
         }
 
-        // Step 2: Check if user exists in Keycloak
-        let user_exists_in_keycloak = self.check_user_exists_in_keycloak(email).await?;
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
 
-        if user_exists_in_keycloak {
-            return Err(Error::UserExistsInKeycloak { email: email.to_string() });
+        let user = self
+            .query_metrics
+            .instrument(&mut tx)
+            .get_user_by_keycloak_id(keycloak_user_id, realm, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })?;
+        if user.email == new_email {
+            return Err(Error::InvalidEmailChange { reason: "new email is the current email" });
+        }
+        // a soft-deleted user still owns its email
+        if self
+            .query_metrics
+            .instrument(&mut tx)
+            .get_user_by_email(&new_email, realm, true)
+            .await?
+            .is_some()
+        {
+            return Err(Error::UserAlreadyExists { email: new_email });
+        }
+        if self.user_directory.find_by_email(&new_email).await?.is_some() {
+            return Err(Error::UserExistsInKeycloak { email: new_email });
         }
 
-        // Step 3: Create user in Keycloak
-        let keycloak_user_id = self.create_keycloak_user(email).await?;
+        let locale = self
+            .query_metrics
+            .instrument(&mut tx)
+            .get_user_profile(&user.id)
+            .await?
+            .and_then(|profile| profile.locale);
+
+        let (token, token_hash) = generate_token();
+        let expires_at = Utc::now() + self.activation.token_ttl;
+        tx.insert_email_change_request(&user.id, &new_email, &token_hash, expires_at).await?;
 
-        // Step 4: Create user in system database with Keycloak user ID
-        let user = tx.insert_user(email, &keycloak_user_id, true).await?;
+        let notification = Notification::EmailChangeVerification {
+            recipients: Recipients::new(new_email),
+            link: format!("{}?token={token}", self.activation.email_change_url),
+            locale,
+        };
+        let _notification_id = enqueue_notification(&mut tx, &notification).await?;
 
         tx.commit().await.context(error::CommitTransactionSnafu)?;
 
+        Ok(())
+    }
+
+    /// Change the email of the user an email change token was sent to, in the
+    /// database and in Keycloak
+    ///
+    /// The token is single-use. The database is only committed once Keycloak
+    /// has the new email, and Keycloak is given the previous email back if the
+    /// commit fails, so both keep the same email.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Token is unknown, expired or already used
+    /// - User was deleted
+    /// - Email was taken since the change was requested
+    /// - Keycloak or database operation fails
+    pub async fn confirm_email_change(&self, token: &str) -> Result<User> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let (user_id, new_email) = tx
+            .consume_email_change_request(&hash_token(token))
+            .await?
+            .ok_or(Error::InvalidEmailChangeToken)?;
+
+        let previous_email = self
+            .query_metrics
+            .instrument(&mut tx)
+            .get_user_by_id(&user_id, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id })?
+            .email;
+        let user = match self
+            .query_metrics
+            .instrument(&mut tx)
+            .update_user_email(&user_id, &new_email)
+            .await
+        {
+            Ok(user) => user.ok_or(Error::UserNotFound { user_id })?,
+            Err(Error::UpdateUserEmail { source })
+                if source.as_database_error().is_some_and(|e| e.is_unique_violation()) =>
+            {
+                return Err(Error::UserAlreadyExists { email: new_email });
+            }
+            Err(err) => return Err(err),
+        };
+
+        // the token stays unused if Keycloak fails, the transaction is rolled back
+        // on drop
+        self.user_directory.set_email(&user.keycloak_user_id, &new_email).await?;
+
+        if let Err(source) = tx.commit().await {
+            if let Err(err) =
+                self.user_directory.set_email(&user.keycloak_user_id, &previous_email).await
+            {
+                tracing::error!(
+                    "Failed to restore the email of Keycloak user {} after the email change could \
+                     not be committed, error: {err}",
+                    user.keycloak_user_id
+                );
+            }
+            return Err(Error::CommitTransaction { source });
+        }
+
+        self.event_bus.publish(Event::UserUpdated { user: user.clone() });
+
         Ok(user)
     }
 
-    /// Delete a user by email (for testing purposes)
-    pub async fn delete_user_by_email(&self, email: &str) -> Result<Uuid> {
-        // Validate email format
-        if !Self::is_valid_email(email) {
-            return Err(Error::InvalidEmail { email: email.to_string() });
+    /// Delete expired email change requests, returns the number of deleted
+    /// requests
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn delete_expired_email_change_requests(&self) -> Result<u64> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        conn.delete_expired_email_change_requests().await
+    }
+
+    /// Error for an email which is already taken in the Keycloak realm, tells
+    /// whether the user is known to the database
+    async fn user_exists_error(&self, email: &str, realm: Option<&str>) -> Error {
+        let existing_user = match self.db.acquire().await {
+            Ok(mut conn) => {
+                self.query_metrics.instrument(&mut conn).get_user_by_email(email, realm, true).await
+            }
+            Err(source) => Err(Error::AcquireConnection { source }),
+        };
+
+        match existing_user {
+            Ok(Some(_)) => Error::UserAlreadyExists { email: email.to_string() },
+            Ok(None) => Error::UserExistsInKeycloak { email: email.to_string() },
+            Err(err) => err,
         }
+    }
 
+    /// Soft delete a user by email (for testing purposes)
+    ///
+    /// The database record is kept with `deleted_at` set and the Keycloak
+    /// account is disabled, so the user can be restored later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Email is invalid
+    /// - User not found in database or Keycloak
+    /// - Keycloak or database operation fails
+    pub async fn delete_user_by_email(&self, email: &str) -> Result<Uuid> {
+        let email = &parse_email(email)?.into_inner();
+
+        retry_transaction(&self.transaction_retry, "delete_user_by_email", || {
+            self.soft_delete_user_by_email(email)
+        })
+        .await
+    }
+
+    /// Soft delete a user in one database transaction, disabling its account
+    async fn soft_delete_user_by_email(&self, email: &str) -> Result<Uuid> {
         let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
 
         // Step 1: check if user exists in database
-        let database_existing_user = tx.get_user_by_email(email).await?;
+        let database_existing_user =
+            self.query_metrics.instrument(&mut tx).get_user_by_email(email, None, false).await?;
 
         if database_existing_user.is_none() {
             return Err(Error::UserNotFound {
@@ -97,33 +671,39 @@ impl UserManagementService {
         let database_existing_user = database_existing_user.unwrap();
 
         // Step 2: check if user exists in Keycloak
-        let keycloak_existing_user = self.check_user_exists_in_keycloak(email).await?;
+        let keycloak_existing_user = self.user_directory.find_by_email(email).await?;
 
-        if !keycloak_existing_user {
+        if keycloak_existing_user.is_none() {
             return Err(Error::KeycloakUserNotFound { email: email.to_string() });
         }
 
-        // Step 3: delete user from database and Keycloak, commit if successful or
-        // rollback on error
+        // Step 3: soft delete user in database and disable it in Keycloak, commit if
+        // successful or rollback on error
         let delete_result = async {
-            tx.delete_user_by_id(&database_existing_user.id).await?;
+            self.query_metrics
+                .instrument(&mut tx)
+                .soft_delete_user_by_id(&database_existing_user.id)
+                .await?;
+            let user = self
+                .query_metrics
+                .instrument(&mut tx)
+                .get_user_by_id(&database_existing_user.id, true)
+                .await?
+                .ok_or(Error::UserNotFound { user_id: database_existing_user.id })?;
 
-            let _result = self
-                .keycloak_admin
-                .realm_users_with_user_id_delete(
-                    &self.realm,
-                    &database_existing_user.keycloak_user_id.to_string(),
-                )
-                .await
-                .context(error::DeleteKeycloakUserSnafu)?;
+            let event = DomainEvent::UserDeleted { user_id: user.id, email: user.email.clone() };
+            self.event_outbox.record(&mut tx, &event).await?;
 
-            Ok::<(), Error>(())
+            self.user_directory.set_enabled(&user.keycloak_user_id, false).await?;
+
+            Ok::<User, Error>(user)
         }
         .await;
 
         match delete_result {
-            Ok(()) => {
+            Ok(user) => {
                 tx.commit().await.context(error::CommitTransactionSnafu)?;
+                self.event_bus.publish(Event::UserUpdated { user });
             }
             Err(e) => {
                 tx.rollback().await.context(error::RollBackTransactionSnafu)?;
@@ -134,90 +714,134 @@ impl UserManagementService {
         Ok(database_existing_user.id)
     }
 
-    /// Check if a user exists in Keycloak by email
-    async fn check_user_exists_in_keycloak(&self, email: &str) -> Result<bool> {
-        // Search for user by email
-        let users = self
-            .keycloak_admin
-            .realm_users_get(
-                &self.realm,
-                None,
-                Some(email.to_string()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-            )
-            .await
-            .context(error::GetKeycloakUserSnafu)?;
+    /// Restore a soft-deleted user
+    ///
+    /// Clears `deleted_at` and re-enables the Keycloak account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User not found
+    /// - User is not deleted
+    /// - Keycloak or database operation fails
+    pub async fn restore_user(&self, user_id: Uuid) -> Result<User> {
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let user = self
+            .query_metrics
+            .instrument(&mut tx)
+            .get_user_by_id(&user_id, true)
+            .await?
+            .ok_or(Error::UserNotFound { user_id })?;
+
+        if user.deleted_at.is_none() {
+            return Err(Error::UserNotDeleted { user_id });
+        }
+
+        let restore_result = async {
+            let user = self
+                .query_metrics
+                .instrument(&mut tx)
+                .restore_user_by_id(&user_id)
+                .await?
+                .ok_or(Error::UserNotDeleted { user_id })?;
+
+            self.user_directory(user.realm.as_deref())?
+                .set_enabled(&user.keycloak_user_id, true)
+                .await?;
+
+            Ok::<User, Error>(user)
+        }
+        .await;
 
-        // Check if any user with exact email match exists
-        Ok(users.iter().any(|u| u.email.as_ref().is_some_and(|e| e == email)))
+        match restore_result {
+            Ok(user) => {
+                tx.commit().await.context(error::CommitTransactionSnafu)?;
+                self.event_bus.publish(Event::UserUpdated { user: user.clone() });
+                Ok(user)
+            }
+            Err(e) => {
+                tx.rollback().await.context(error::RollBackTransactionSnafu)?;
+                Err(e)
+            }
+        }
     }
 
-    /// Create a new user in Keycloak
-    async fn create_keycloak_user(&self, email: &str) -> Result<Uuid> {
-        // Create user representation
-        let user = UserRepresentation {
-            email: Some(email.to_string()),
-            username: Some(email.to_string()),
-            enabled: Some(true),
-            email_verified: Some(false),
-            ..Default::default()
-        };
+    /// Permanently delete all users of `realm`, `None` for the primary realm,
+    /// whose email matches `pattern` (for testing purposes)
+    ///
+    /// `pattern` is a glob where `*` matches any characters and `?` matches a
+    /// single character, e.g. `*@example.com`. If `created_before` is given,
+    /// only users created before that date (UTC) are matched. Soft-deleted
+    /// users are matched as well.
+    ///
+    /// Users are deleted from Keycloak and then from the database, together
+    /// with their wallets, in batches of 100. If `dry_run` is set, nothing is
+    /// deleted.
+    ///
+    /// Returns the emails of the matched users.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Pattern does not contain `@`
+    /// - Realm is not configured
+    /// - Keycloak or database operation fails
+    pub async fn delete_users_by_pattern(
+        &self,
+        pattern: &str,
+        realm: Option<&str>,
+        created_before: Option<NaiveDate>,
+        dry_run: bool,
+    ) -> Result<Vec<String>> {
+        let user_directory = self.user_directory(realm)?;
+        let email_like = email_glob_to_like(&normalize_email(pattern))
+            .ok_or_else(|| Error::InvalidEmailPattern { pattern: pattern.to_string() })?;
+        let created_before = created_before.map(|date| date.and_time(NaiveTime::MIN).and_utc());
+        let limit = i64::try_from(BULK_DELETE_BATCH_SIZE).unwrap_or(i64::MAX);
 
-        // Create user in Keycloak
-        let _create_user_response = self
-            .keycloak_admin
-            .realm_users_post(&self.realm, user)
-            .await
-            .context(error::CreateKeycloakUserSnafu)?;
-
-        // Retrieve the created user to get the UUID
-        let users = self
-            .keycloak_admin
-            .realm_users_get(
-                &self.realm,
-                None,
-                Some(email.to_string()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some(email.to_string()),
-            )
-            .await
-            .context(error::GetKeycloakUserSnafu)?;
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+
+        let mut emails = Vec::new();
+        loop {
+            // deleted users no longer match, so only a dry run has to page forward
+            let offset = if dry_run { i64::try_from(emails.len()).unwrap_or(i64::MAX) } else { 0 };
+
+            let users = self
+                .query_metrics
+                .instrument(&mut conn)
+                .list_users_by_email_pattern(&email_like, created_before, realm, limit, offset)
+                .await?;
+            let batch_size = users.len();
 
-        // Find the user with matching email
-        let created_user = users
-            .iter()
-            .find(|u| u.email.as_ref().is_some_and(|e| e == email))
-            .ok_or_else(|| Error::KeycloakUserNotFound { email: email.to_string() })?;
+            if !dry_run && batch_size > 0 {
+                for user in &users {
+                    user_directory.delete(&user.keycloak_user_id).await?;
+                }
 
-        // Parse the Keycloak user ID
-        let keycloak_id = created_user
-            .id
-            .as_ref()
-            .ok_or_else(|| Error::KeycloakUserNotFound { email: email.to_string() })?;
+                let user_ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
+                let mut tx = sqlx::Connection::begin(&mut *conn)
+                    .await
+                    .context(error::BeginTransactionSnafu)?;
+                let deleted =
+                    self.query_metrics.instrument(&mut tx).delete_users_by_ids(&user_ids).await?;
+                for user in &users {
+                    let event =
+                        DomainEvent::UserDeleted { user_id: user.id, email: user.email.clone() };
+                    self.event_outbox.record(&mut tx, &event).await?;
+                }
+                tx.commit().await.context(error::CommitTransactionSnafu)?;
+                tracing::info!("Deleted {deleted} users matching `{pattern}`");
+            }
+
+            emails.extend(users.into_iter().map(|user| user.email));
+
+            if batch_size < BULK_DELETE_BATCH_SIZE {
+                break;
+            }
+        }
 
-        Uuid::parse_str(keycloak_id)
-            .map_err(|_| Error::KeycloakUserNotFound { email: email.to_string() })
+        Ok(emails)
     }
 
     /// Get user by ID
@@ -228,14 +852,19 @@ impl UserManagementService {
     /// - User not found
     /// - Database operation fails
     pub async fn get_user_by_id(&self, user_id: Uuid) -> Result<User> {
-        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let mut conn = self.read_pool.acquire().await?;
 
-        let user = conn.get_user_by_id(&user_id).await?.ok_or(Error::UserNotFound { user_id })?;
+        let user = self
+            .query_metrics
+            .instrument(&mut conn)
+            .get_user_by_id(&user_id, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id })?;
 
         Ok(user)
     }
 
-    /// Get user by email
+    /// Get user of the primary realm by email
     ///
     /// # Errors
     ///
@@ -243,36 +872,689 @@ impl UserManagementService {
     /// - User not found
     /// - Database operation fails
     pub async fn get_user_by_email(&self, email: String) -> Result<User> {
-        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let mut conn = self.read_pool.acquire().await?;
 
-        let user = conn.get_user_by_email(&email).await?.ok_or(Error::UserNotFound {
-            user_id: Uuid::nil(), // Using nil UUID since we don't have the ID
-        })?;
+        let user = self
+            .query_metrics
+            .instrument(&mut conn)
+            .get_user_by_email(&normalize_email(&email), None, false)
+            .await?
+            .ok_or(Error::UserNotFound {
+                user_id: Uuid::nil(), // Using nil UUID since we don't have the ID
+            })?;
 
         Ok(user)
     }
 
-    /// Get user by Keycloak user ID
+    /// Get user by Keycloak user ID in `realm`, `None` for the primary realm
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - User not found
     /// - Database operation fails
-    pub async fn get_user_by_keycloak_id(&self, keycloak_user_id: &Uuid) -> Result<User> {
-        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+    pub async fn get_user_by_keycloak_id(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+    ) -> Result<User> {
+        let mut conn = self.read_pool.acquire().await?;
 
-        let user = conn
-            .get_user_by_keycloak_id(keycloak_user_id)
+        let user = self
+            .query_metrics
+            .instrument(&mut conn)
+            .get_user_by_keycloak_id(keycloak_user_id, realm, false)
             .await?
             .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })?;
 
         Ok(user)
     }
 
-    /// Validate email format
-    fn is_valid_email(email: &str) -> bool {
-        // Basic email validation
-        email.contains('@') && email.contains('.') && email.len() > 3
+    /// Get the profile of a user, `None` until the user updates it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails
+    pub async fn get_user_profile(&self, user_id: &Uuid) -> Result<Option<UserProfile>> {
+        let mut conn = self.read_pool.acquire().await?;
+
+        self.query_metrics.instrument(&mut conn).get_user_profile(user_id).await
+    }
+
+    /// Update the profile of a user by Keycloak user ID, absent fields are
+    /// kept
+    ///
+    /// The display name and the locale are synced to the user directory of the
+    /// primary realm, the update is rolled back if the sync fails. The profiles
+    /// of other realms are only kept in the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The update is empty or a field is invalid
+    /// - User not found
+    /// - Keycloak or database operation fails
+    pub async fn update_user_profile(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        update: UpdateUserProfileRequest,
+    ) -> Result<UserProfile> {
+        let update = validate_profile_update(update)?;
+
+        let mut tx = self.db.begin().await.context(error::BeginTransactionSnafu)?;
+
+        let user = self
+            .query_metrics
+            .instrument(&mut tx)
+            .get_user_by_keycloak_id(keycloak_user_id, realm, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })?;
+
+        let update_result = async {
+            let profile =
+                self.query_metrics.instrument(&mut tx).update_user(&user.id, &update).await?;
+            if user.realm.is_none() {
+                self.user_directory
+                    .set_profile(
+                        &user.keycloak_user_id,
+                        update.display_name.as_deref(),
+                        update.locale.as_deref(),
+                    )
+                    .await?;
+            }
+
+            Ok::<UserProfile, Error>(profile)
+        }
+        .await;
+
+        match update_result {
+            Ok(profile) => {
+                tx.commit().await.context(error::CommitTransactionSnafu)?;
+                Ok(profile)
+            }
+            Err(e) => {
+                tx.rollback().await.context(error::RollBackTransactionSnafu)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Get the notification preferences of a user by Keycloak user ID, the
+    /// defaults until the user sets them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User not found
+    /// - Database operation fails
+    pub async fn get_user_preferences(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+    ) -> Result<UserPreferences> {
+        let user = self.get_user_by_keycloak_id(keycloak_user_id, realm).await?;
+
+        let mut conn = self.read_pool.acquire().await?;
+        let preferences =
+            self.query_metrics.instrument(&mut conn).get_user_preferences(&user.id).await?;
+
+        Ok(preferences.unwrap_or_else(|| UserPreferences::defaults(user.id)))
+    }
+
+    /// Replace the notification preferences of a user by Keycloak user ID,
+    /// the notification dispatcher follows them from its next batch
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The webhook URL or the locale is invalid
+    /// - User not found
+    /// - Database operation fails
+    pub async fn update_user_preferences(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        preferences: UpdateUserPreferencesRequest,
+    ) -> Result<UserPreferences> {
+        let preferences = validate_preferences(preferences)?;
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let mut executor = self.query_metrics.instrument(&mut conn);
+        let user = executor
+            .get_user_by_keycloak_id(keycloak_user_id, realm, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })?;
+
+        executor.replace_user_preferences(&user.id, &preferences).await
+    }
+
+    /// Replace the password of a user by Keycloak user ID
+    ///
+    /// The caller is expected to have verified the current password.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User not found
+    /// - Keycloak rejects the password or the update fails
+    /// - Database operation fails
+    pub async fn change_password(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        new_password: &str,
+    ) -> Result<()> {
+        let user = self.get_user_by_keycloak_id(keycloak_user_id, realm).await?;
+
+        self.user_directory(user.realm.as_deref())?
+            .set_password(&user.keycloak_user_id, new_password)
+            .await?;
+        tracing::info!(user_id = %user.id, "Changed password");
+
+        Ok(())
+    }
+
+    /// List the users of `realm`, `None` for the primary realm, page by page
+    ///
+    /// Returns the users of the requested page in `sort` order, newest first
+    /// when it is empty, projected onto `fields` if given, and the total number
+    /// of users of the realm matching `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn list_users(
+        &self,
+        realm: Option<&str>,
+        pagination: &Pagination,
+        filter: &ListUsersFilter,
+        sort: &Sort<UserSortColumn>,
+        fields: Option<&Fields<UserField>>,
+    ) -> Result<(Vec<ListedUser>, u64)> {
+        let mut conn = self.read_pool.acquire().await?;
+
+        let limit = pagination.sql_limit();
+        let offset = pagination.sql_offset();
+
+        let mut executor = self.query_metrics.instrument(&mut conn);
+        let users = match fields {
+            Some(fields) => executor
+                .list_user_fields(realm, filter, sort, fields, limit, offset)
+                .await?
+                .into_iter()
+                .map(ListedUser::Fields)
+                .collect(),
+            None => executor
+                .list_users(realm, filter, sort, limit, offset)
+                .await?
+                .into_iter()
+                .map(ListedUser::User)
+                .collect(),
+        };
+        let total_count = executor.count_users(realm, filter).await?;
+
+        Ok((users, u64::try_from(total_count).unwrap_or_default()))
+    }
+
+    /// Export every user of `realm`, `None` for the primary realm, soft-deleted
+    /// users included, oldest first
+    ///
+    /// Users are read through a database cursor and encoded one at a time, so
+    /// an export is never held in memory as a whole. Each item of the stream
+    /// is a chunk of the encoded export.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the realm is not configured or no database
+    /// connection can be acquired, the stream yields an error and ends if
+    /// reading or encoding a user fails
+    pub async fn export_users(
+        &self,
+        realm: Option<&str>,
+        format: ExportFormat,
+    ) -> Result<impl Stream<Item = Result<Vec<u8>>> + Send + 'static> {
+        // an unknown realm has no users, it is rejected rather than exported empty
+        let _user_directory = self.user_directory(realm)?;
+        let realm = realm.map(ToString::to_string);
+        let mut conn = self.read_pool.acquire().await?;
+
+        Ok(async_stream::try_stream! {
+            let mut encoder = UserExportEncoder::new(format);
+            yield encoder.header()?;
+
+            let mut users = conn.export_users(realm.as_deref());
+            while let Some(user) = users.try_next().await? {
+                yield encoder.encode(&user)?;
+            }
+
+            yield encoder.footer();
+        })
+    }
+}
+
+/// Columns of a user export in CSV
+const USER_EXPORT_CSV_HEADER: [&str; 7] =
+    ["id", "email", "keycloak_user_id", "is_active", "created_at", "updated_at", "deleted_at"];
+
+/// Encodes the users of an export one at a time
+enum UserExportEncoder {
+    Csv(csv::Writer<Vec<u8>>),
+
+    /// `first` is set until the first user is encoded, later ones are
+    /// preceded by a comma
+    Json {
+        first: bool,
+    },
+}
+
+impl UserExportEncoder {
+    fn new(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Csv => Self::Csv(csv::Writer::from_writer(Vec::new())),
+            ExportFormat::Json => Self::Json { first: true },
+        }
+    }
+
+    fn header(&mut self) -> Result<Vec<u8>> {
+        match self {
+            Self::Csv(writer) => {
+                writer.write_record(USER_EXPORT_CSV_HEADER).context(error::WriteUserCsvSnafu)?;
+                take_csv_output(writer)
+            }
+            Self::Json { .. } => Ok(b"[".to_vec()),
+        }
+    }
+
+    fn encode(&mut self, user: &User) -> Result<Vec<u8>> {
+        match self {
+            Self::Csv(writer) => {
+                writer
+                    .write_record([
+                        user.id.to_string(),
+                        user.email.clone(),
+                        user.keycloak_user_id.to_string(),
+                        user.is_active.to_string(),
+                        user.created_at.to_rfc3339(),
+                        user.updated_at.to_rfc3339(),
+                        user.deleted_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+                    ])
+                    .context(error::WriteUserCsvSnafu)?;
+                take_csv_output(writer)
+            }
+            Self::Json { first } => {
+                let mut chunk = if *first { Vec::new() } else { b",".to_vec() };
+                *first = false;
+                serde_json::to_writer(&mut chunk, user).context(error::WriteUserJsonSnafu)?;
+                Ok(chunk)
+            }
+        }
+    }
+
+    fn footer(&self) -> Vec<u8> {
+        match self {
+            Self::Csv(_) => Vec::new(),
+            Self::Json { .. } => b"]".to_vec(),
+        }
+    }
+}
+
+/// Flush `writer` and take what it has written so far
+fn take_csv_output(writer: &mut csv::Writer<Vec<u8>>) -> Result<Vec<u8>> {
+    writer.flush().map_err(csv::Error::from).context(error::WriteUserCsvSnafu)?;
+    Ok(std::mem::take(writer.get_mut()))
+}
+
+/// Per-key locks of operations in flight
+///
+/// Entries are removed once the last holder or waiter of a key is gone.
+#[derive(Clone, Debug, Default)]
+struct InFlightLocks(Arc<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>>);
+
+impl InFlightLocks {
+    /// Wait until no other operation holds `key` and hold it until the guard is
+    /// dropped
+    async fn lock(&self, key: &str) -> InFlightGuard {
+        // created first so that it is dropped last, after the lock of the key,
+        // when the wait is cancelled and removes the entry nobody waits for
+        let mut in_flight =
+            InFlightGuard { locks: self.clone(), key: key.to_string(), guard: None };
+
+        let lock = Arc::clone(self.entries().entry(key.to_string()).or_default());
+        in_flight.guard = Some(lock.lock_owned().await);
+        in_flight
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, Arc<AsyncMutex<()>>>> {
+        // entries are inserted and removed whole, a poisoned lock is still usable
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Guard of a key held in [`InFlightLocks`]
+struct InFlightGuard {
+    locks: InFlightLocks,
+    key: String,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        drop(self.guard.take());
+
+        let mut entries = self.locks.entries();
+        // nobody else waits for the key if the map holds the only reference
+        if entries.get(&self.key).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            let _lock = entries.remove(&self.key);
+        }
+        drop(entries);
+    }
+}
+
+/// Generate a random activation or email change token, returns the token and
+/// its hash
+///
+/// The token is URL safe, only the hash is stored.
+fn generate_token() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    let token_hash = hash_token(&token);
+    (token, token_hash)
+}
+
+/// Hex encoded SHA-256 hash of an activation or email change token
+fn hash_token(token: &str) -> String { hex::encode(Sha256::digest(token.as_bytes())) }
+
+/// Normalize an email the way it is stored, trimmed and lowercased
+///
+/// Keycloak lowercases emails as well, so `User@Example.com` and
+/// `user@example.com` resolve to the same user in both systems.
+/// Parse and normalize an email address
+pub(super) fn parse_email(email: &str) -> Result<Email> {
+    email.parse().map_err(|_| Error::InvalidEmail { email: email.to_string() })
+}
+
+pub(super) fn normalize_email(email: &str) -> String { email.trim().to_lowercase() }
+
+/// Check a profile update, trimming the display name
+fn validate_profile_update(
+    mut update: UpdateUserProfileRequest,
+) -> Result<UpdateUserProfileRequest> {
+    if update.display_name.is_none() && update.locale.is_none() {
+        return Err(Error::InvalidUserProfile { reason: "no field to update" });
+    }
+
+    if let Some(display_name) = update.display_name.as_mut() {
+        *display_name = display_name.trim().to_string();
+        if display_name.is_empty() || display_name.chars().count() > 64 {
+            return Err(Error::InvalidUserProfile {
+                reason: "display name must be 1 to 64 characters",
+            });
+        }
+        if display_name.chars().any(char::is_control) {
+            return Err(Error::InvalidUserProfile {
+                reason: "display name must not contain control characters",
+            });
+        }
+    }
+
+    if update.locale.as_deref().is_some_and(|locale| !is_language_tag(locale)) {
+        return Err(Error::InvalidUserProfile { reason: "locale must be a BCP 47 language tag" });
+    }
+
+    Ok(update)
+}
+
+/// Check notification preferences, trimming the webhook URL
+fn validate_preferences(
+    mut preferences: UpdateUserPreferencesRequest,
+) -> Result<UpdateUserPreferencesRequest> {
+    if let Some(webhook_url) = preferences.webhook_url.take() {
+        let webhook_url = webhook_url.trim().to_string();
+        if webhook_url.len() > 2048 {
+            return Err(Error::InvalidUserPreferences {
+                reason: "webhook URL must be at most 2048 characters",
+            });
+        }
+        let is_http = reqwest::Url::parse(&webhook_url)
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+        if !is_http {
+            return Err(Error::InvalidUserPreferences {
+                reason: "webhook URL must be an http or https URL",
+            });
+        }
+        preferences.webhook_url = Some(webhook_url);
+    }
+
+    if preferences.locale.as_deref().is_some_and(|locale| !is_language_tag(locale)) {
+        return Err(Error::InvalidUserPreferences {
+            reason: "locale must be a BCP 47 language tag",
+        });
+    }
+
+    Ok(preferences)
+}
+
+/// Whether `tag` has the shape of a BCP 47 language tag, such as `en` or
+/// `zh-Hant-TW`: a language of 2 or 3 letters followed by subtags of 1 to 8
+/// letters or digits
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let language_ok = subtags.next().is_some_and(|language| {
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic())
+    });
+
+    tag.len() <= 35
+        && language_ok
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// Translate an email glob into a `LIKE` pattern escaped with `\`
+///
+/// Returns `None` if the glob does not contain `@`, which guards against
+/// patterns like `*` matching every user.
+fn email_glob_to_like(glob: &str) -> Option<String> {
+    if !glob.contains('@') {
+        return None;
+    }
+
+    let mut like = String::with_capacity(glob.len());
+    for c in glob.chars() {
+        match c {
+            '*' => like.push('%'),
+            '?' => like.push('_'),
+            '%' | '_' | '\\' => {
+                like.push('\\');
+                like.push(c);
+            }
+            _ => like.push(c),
+        }
+    }
+    Some(like)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_flight_locks() {
+        let locks = InFlightLocks::default();
+
+        let guard = locks.lock("user@example.com").await;
+        let waiter = tokio::spawn({
+            let locks = locks.clone();
+            async move { drop(locks.lock("user@example.com").await) }
+        });
+        // other keys are not blocked
+        drop(locks.lock("other@example.com").await);
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        waiter.await.unwrap();
+        assert!(locks.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_wait_releases_in_flight_lock() {
+        let locks = InFlightLocks::default();
+
+        let guard = locks.lock("user@example.com").await;
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(10), locks.lock("user@example.com")).await;
+        assert!(cancelled.is_err());
+
+        drop(guard);
+        assert!(locks.entries().is_empty());
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let (token, token_hash) = generate_token();
+
+        assert_eq!(token.len(), 43);
+        assert_eq!(token_hash, hash_token(&token));
+        assert_eq!(token_hash.len(), 64);
+        assert_ne!(generate_token().0, token);
+    }
+
+    #[test]
+    fn test_user_export_encoder() {
+        let created_at = chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let user = |email: &str, deleted: bool| User {
+            id: Uuid::nil(),
+            email: email.to_string(),
+            keycloak_user_id: Uuid::nil(),
+            realm: None,
+            is_active: true,
+            created_at,
+            updated_at: created_at,
+            deleted_at: deleted.then_some(created_at),
+        };
+        let export = |format| {
+            let mut encoder = UserExportEncoder::new(format);
+            let mut output = encoder.header().unwrap();
+            output.extend(encoder.encode(&user("a@example.com", false)).unwrap());
+            output.extend(encoder.encode(&user("b,c@example.com", true)).unwrap());
+            output.extend(encoder.footer());
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            export(ExportFormat::Csv),
+            "id,email,keycloak_user_id,is_active,created_at,updated_at,deleted_at\\
+             \
+             n00000000-0000-0000-0000-000000000000,a@example.com,\
+             00000000-0000-0000-0000-000000000000,true,2026-01-02T03:04:05+00:00,2026-01-02T03:04:\
+             05+00:00,\n00000000-0000-0000-0000-000000000000,\"b,c@example.com\",\
+             00000000-0000-0000-0000-000000000000,true,2026-01-02T03:04:05+00:00,2026-01-02T03:04:\
+             05+00:00,2026-01-02T03:04:05+00:00\n"
+        );
+
+        let json: Vec<serde_json::Value> =
+            serde_json::from_str(&export(ExportFormat::Json)).unwrap();
+        assert_eq!(json.len(), 2);
+        assert_eq!(json[1]["email"], "b,c@example.com");
+        // an empty export is still an array
+        let mut encoder = UserExportEncoder::new(ExportFormat::Json);
+        let mut output = encoder.header().unwrap();
+        output.extend(encoder.footer());
+        assert_eq!(output, b"[]");
+    }
+
+    #[test]
+    fn test_parse_email() {
+        assert_eq!(parse_email(" User@Example.COM ").unwrap().as_str(), "user@example.com");
+        assert_eq!(parse_email("admin@localhost").unwrap().as_str(), "admin@localhost");
+        // accepted by the former `@` and `.` check
+        assert!(matches!(parse_email("@."), Err(Error::InvalidEmail { .. })));
+        assert!(matches!(parse_email("a b@c.d"), Err(Error::InvalidEmail { .. })));
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email(" User@Example.COM "), "user@example.com");
+        assert_eq!(normalize_email("user@example.com"), "user@example.com");
+    }
+
+    #[test]
+    fn test_validate_profile_update() {
+        let update = validate_profile_update(UpdateUserProfileRequest {
+            display_name: Some("  Satoshi ".to_string()),
+            locale: Some("zh-TW".to_string()),
+        })
+        .unwrap();
+        assert_eq!(update.display_name.as_deref(), Some("Satoshi"));
+
+        for update in [
+            UpdateUserProfileRequest::default(),
+            UpdateUserProfileRequest { display_name: Some(" ".to_string()), ..Default::default() },
+            UpdateUserProfileRequest { display_name: Some("a".repeat(65)), ..Default::default() },
+            UpdateUserProfileRequest {
+                display_name: Some("a\nb".to_string()),
+                ..Default::default()
+            },
+            UpdateUserProfileRequest { locale: Some("english".to_string()), ..Default::default() },
+        ] {
+            assert!(matches!(
+                validate_profile_update(update),
+                Err(Error::InvalidUserProfile { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_preferences() {
+        let preferences = |webhook_url: &str, locale: Option<&str>| UpdateUserPreferencesRequest {
+            email_enabled: false,
+            webhook_url: Some(webhook_url.to_string()),
+            locale: locale.map(ToString::to_string),
+        };
+
+        let validated =
+            validate_preferences(preferences(" https://example.com/hooks ", Some("en"))).unwrap();
+        assert_eq!(validated.webhook_url.as_deref(), Some("https://example.com/hooks"));
+
+        for invalid in [
+            preferences("ftp://example.com/hooks", None),
+            preferences("example.com/hooks", None),
+            preferences("", None),
+            preferences(&format!("https://example.com/{}", "a".repeat(2048)), None),
+            preferences("https://example.com/hooks", Some("english")),
+        ] {
+            assert!(matches!(
+                validate_preferences(invalid),
+                Err(Error::InvalidUserPreferences { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_is_language_tag() {
+        assert!(is_language_tag("en"));
+        assert!(is_language_tag("zh-TW"));
+        assert!(is_language_tag("zh-Hant-TW"));
+        assert!(!is_language_tag(""));
+        assert!(!is_language_tag("e"));
+        assert!(!is_language_tag("zh_TW"));
+        assert!(!is_language_tag("zh-"));
+        assert!(!is_language_tag("zh-toolongsubtag"));
+    }
+
+    #[test]
+    fn test_email_glob_to_like() {
+        assert_eq!(email_glob_to_like("*@example.com").as_deref(), Some("%@example.com"));
+        assert_eq!(
+            email_glob_to_like("user_?@ex%ample.com").as_deref(),
+            Some("user\\__@ex\\%ample.com")
+        );
+        assert_eq!(email_glob_to_like("*"), None);
     }
 }
@@ -0,0 +1,149 @@
+use chrono::{Days, NaiveDate, Utc};
+use snafu::ResultExt;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{DailyBalance, WalletBalanceHistory},
+    service::{
+        error,
+        sql_executor::{UserSqlExecutor, WalletBalanceSnapshotSqlExecutor, WalletSqlExecutor},
+    },
+};
+
+/// Days in a history when `from` is not given, `to` included
+const DEFAULT_HISTORY_DAYS: u64 = 30;
+
+/// Longest history which can be requested, in days
+const MAX_HISTORY_DAYS: i64 = 366;
+
+/// Wallet service for the balance history of wallets
+///
+/// Balances are computed from the deposits and withdrawals in the database and
+/// snapshotted once per day by the `snapshot_wallet_balances` job, the latest
+/// snapshot of a day is its end of day balance.
+#[derive(Clone)]
+pub struct WalletService {
+    db: PgPool,
+}
+
+impl WalletService {
+    /// Create a new wallet service
+    #[inline]
+    #[must_use]
+    pub const fn new(db: PgPool) -> Self { Self { db } }
+
+    /// Snapshot the current balance of every wallet, returning the number of
+    /// wallets snapshotted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails
+    pub async fn snapshot_balances(&self) -> Result<u64> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        conn.upsert_wallet_balance_snapshots().await
+    }
+
+    /// Get the daily balance history of a wallet owned by a user
+    ///
+    /// A day without a snapshot carries the balance of the day before.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `from` is after `to` or the range is longer than 366 days
+    /// - User not found
+    /// - Wallet not found or owned by another user
+    /// - Database operation fails
+    pub async fn balance_history(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        wallet_id: &Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<WalletBalanceHistory> {
+        let today = Utc::now().date_naive();
+        let to = to.map_or(today, |to| to.min(today));
+        let from = from.unwrap_or_else(|| to - Days::new(DEFAULT_HISTORY_DAYS - 1));
+        if from > to || (to - from).num_days() >= MAX_HISTORY_DAYS {
+            return Err(Error::InvalidDateRange { from, to, max_days: MAX_HISTORY_DAYS });
+        }
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user = conn
+            .get_user_by_keycloak_id(keycloak_user_id, realm, false)
+            .await?
+            .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })?;
+        let wallet = conn
+            .get_wallet_by_id(wallet_id)
+            .await?
+            .filter(|wallet| wallet.user_id == user.id)
+            .ok_or(Error::WalletNotFound { wallet_id: *wallet_id })?;
+
+        let snapshots = conn.list_wallet_balance_snapshots(&wallet.id, from, to).await?;
+
+        Ok(WalletBalanceHistory {
+            wallet_id: wallet.id,
+            chain: wallet.chain,
+            from,
+            to,
+            balances: daily_balances(&snapshots, from, to),
+        })
+    }
+}
+
+/// One balance per day from `from` to `to`, carrying the latest snapshot
+/// forward over days without one
+///
+/// `snapshots` must be sorted by date.
+fn daily_balances(snapshots: &[DailyBalance], from: NaiveDate, to: NaiveDate) -> Vec<DailyBalance> {
+    let mut snapshots = snapshots.iter().peekable();
+    let mut balance = None;
+
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter_map(|date| {
+            while let Some(snapshot) = snapshots.next_if(|snapshot| snapshot.date <= date) {
+                balance = Some(snapshot.balance);
+            }
+            balance.map(|balance| DailyBalance { date, balance })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use mpc_backend_mock_core::model::TokenAmount;
+
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate { NaiveDate::from_ymd_opt(2026, 1, day).unwrap() }
+
+    fn balance(day: u32, balance: u64) -> DailyBalance {
+        DailyBalance { date: date(day), balance: TokenAmount::new(balance) }
+    }
+
+    #[test]
+    fn test_daily_balances_fill_gaps() {
+        // the snapshot of the 1st is the latest one on or before `from`
+        let snapshots = [balance(1, 100), balance(4, 250), balance(5, 0)];
+
+        assert_eq!(
+            daily_balances(&snapshots, date(2), date(6)),
+            vec![balance(2, 100), balance(3, 100), balance(4, 250), balance(5, 0), balance(6, 0)]
+        );
+    }
+
+    #[test]
+    fn test_daily_balances_before_first_snapshot() {
+        let snapshots = [balance(3, 100)];
+
+        assert_eq!(
+            daily_balances(&snapshots, date(1), date(4)),
+            vec![balance(3, 100), balance(4, 100)]
+        );
+        assert!(daily_balances(&[], date(1), date(4)).is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use mpc_backend_mock_core::model::Pagination;
+use rand::RngCore;
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use sha2::Sha256;
+use snafu::{OptionExt, ResultExt};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::error::{Error, Result};
+use crate::{
+    entity::{CreateWebhookRequest, DueWebhookDelivery, Webhook, WebhookDelivery, WebhookEvent},
+    service::{
+        error,
+        notification::retry_delay,
+        sql_executor::{UserSqlExecutor, WebhookSqlExecutor},
+    },
+};
+
+/// Number of due deliveries posted per [`WebhookService::deliver_due`]
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Attempts after which a failing delivery is dead-lettered, retried with the
+/// backoff of the notifications
+const MAX_ATTEMPTS: i32 = 8;
+
+/// Time a webhook has to answer a posted event
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a claimed delivery is not claimed again, outlasts the posts of a batch
+const DELIVERY_LEASE: Duration = Duration::from_secs(15 * 60);
+
+/// Header carrying the type of the posted event
+const EVENT_HEADER: &str = "X-Webhook-Event";
+
+/// Header carrying the signature of the posted body, see [`webhook_signature`]
+const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Deliveries handled by one [`WebhookService::deliver_due`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WebhookDispatch {
+    /// Deliveries accepted by their webhook
+    pub delivered: u64,
+
+    /// Deliveries which failed and are retried later
+    pub retried: u64,
+
+    /// Deliveries which failed for the last time and were dead-lettered
+    pub dead_lettered: u64,
+}
+
+/// Queue `event` of the user `user_id` for the webhooks of the user
+/// subscribing to its type through `executor`, usually the transaction of the
+/// change the event describes
+///
+/// # Errors
+///
+/// Returns an error if the event cannot be serialized or the database
+/// operation fails
+pub(super) async fn enqueue_webhook_event<E>(
+    executor: &mut E,
+    user_id: &Uuid,
+    event: &WebhookEvent,
+) -> Result<()>
+where
+    E: WebhookSqlExecutor + Send,
+{
+    let payload = serde_json::to_value(event).context(error::SerializeWebhookEventSnafu)?;
+    let _deliveries =
+        executor.insert_webhook_deliveries(user_id, event.event_type().as_str(), &payload).await?;
+
+    Ok(())
+}
+
+/// Body posted to a webhook
+#[derive(Serialize)]
+struct WebhookBody<'a> {
+    /// ID of the delivery, consumers drop redeliveries by it
+    id: Uuid,
+
+    #[serde(rename = "type")]
+    type_: &'a str,
+
+    created_at: DateTime<Utc>,
+
+    data: &'a serde_json::Value,
+}
+
+/// Webhook service registering the webhooks of the users and posting their
+/// events queued in the `webhook_deliveries` table
+///
+/// Every posted body is signed with the secret of its webhook, see
+/// [`webhook_signature`]. A delivery which is not answered with a success
+/// status is retried with exponential backoff and dead-lettered after its last
+/// attempt, every attempt is kept in the delivery log of the webhook.
+#[derive(Clone)]
+pub struct WebhookService {
+    db: PgPool,
+    http_client: reqwest::Client,
+}
+
+impl WebhookService {
+    /// Create a new webhook service
+    #[inline]
+    #[must_use]
+    pub fn new(db: PgPool) -> Self { Self { db, http_client: reqwest::Client::new() } }
+
+    /// Register a webhook of a user, returned with the secret its events are
+    /// signed with
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - URL is not an http or https URL, or no event type is given
+    /// - User not found
+    /// - Database operation fails
+    pub async fn create_webhook(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        request: CreateWebhookRequest,
+    ) -> Result<Webhook> {
+        let (url, event_types) = validate_webhook(request)?;
+
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user_id = get_user_id(&mut conn, keycloak_user_id, realm).await?;
+
+        conn.insert_webhook(&user_id, &url, &event_types, &generate_secret()).await
+    }
+
+    /// List the deliveries of a webhook of a user page by page, newest first,
+    /// returns the deliveries and the number of deliveries of the webhook
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - User or webhook not found
+    /// - Database operation fails
+    pub async fn list_deliveries(
+        &self,
+        keycloak_user_id: &Uuid,
+        realm: Option<&str>,
+        webhook_id: &Uuid,
+        pagination: &Pagination,
+    ) -> Result<(Vec<WebhookDelivery>, u64)> {
+        let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+        let user_id = get_user_id(&mut conn, keycloak_user_id, realm).await?;
+
+        let webhook = conn
+            .get_webhook_by_id(webhook_id, &user_id)
+            .await?
+            .context(error::WebhookNotFoundSnafu { webhook_id: *webhook_id })?;
+
+        let deliveries = conn
+            .list_webhook_deliveries(&webhook.id, pagination.sql_limit(), pagination.sql_offset())
+            .await?;
+        let total_count = conn.count_webhook_deliveries(&webhook.id).await?;
+
+        Ok((deliveries, u64::try_from(total_count).unwrap_or_default()))
+    }
+
+    /// Post the pending deliveries whose next attempt is due, oldest first
+    ///
+    /// Deliveries are claimed for [`DELIVERY_LEASE`] before they are posted,
+    /// so concurrent dispatchers never post the same one, and the result of
+    /// each post is recorded on its own. Delivery is at least once, an event is
+    /// posted again once its lease ends if its result cannot be recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub async fn deliver_due(&self) -> Result<WebhookDispatch> {
+        let lease_until = Utc::now() + TimeDelta::from_std(DELIVERY_LEASE).unwrap_or_default();
+        let deliveries = {
+            let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+            conn.claim_due_webhook_deliveries(DELIVERY_BATCH_SIZE, lease_until).await?
+        };
+
+        let mut dispatch = WebhookDispatch::default();
+        for delivery in deliveries {
+            let result = self.post(&delivery).await;
+
+            let mut conn = self.db.acquire().await.context(error::AcquireConnectionSnafu)?;
+            let (response_status, err) = match result {
+                Ok(response_status) => {
+                    conn.mark_webhook_delivery_delivered(&delivery.id, response_status).await?;
+                    dispatch.delivered += 1;
+                    continue;
+                }
+                Err(failure) => failure,
+            };
+
+            let attempts = delivery.attempts + 1;
+            let next_attempt_at = (attempts < MAX_ATTEMPTS).then(|| {
+                Utc::now() + TimeDelta::from_std(retry_delay(attempts)).unwrap_or_default()
+            });
+            conn.record_failed_webhook_delivery(
+                &delivery.id,
+                response_status,
+                &err,
+                next_attempt_at,
+            )
+            .await?;
+
+            if next_attempt_at.is_some() {
+                tracing::warn!(
+                    "Failed to deliver webhook event {} (attempt {attempts}), error: {err}",
+                    delivery.id
+                );
+                dispatch.retried += 1;
+            } else {
+                tracing::error!(
+                    "Dead-lettered webhook event {} after {attempts} attempts, error: {err}",
+                    delivery.id
+                );
+                dispatch.dead_lettered += 1;
+            }
+        }
+
+        Ok(dispatch)
+    }
+
+    /// Post a delivery to its webhook, which must answer with a success status,
+    /// returns the status or the status and the error of a failure
+    async fn post(
+        &self,
+        delivery: &DueWebhookDelivery,
+    ) -> std::result::Result<i32, (Option<i32>, String)> {
+        let body = serde_json::to_vec(&WebhookBody {
+            id: delivery.id,
+            type_: &delivery.event_type,
+            created_at: delivery.created_at,
+            data: &delivery.payload,
+        })
+        .map_err(|err| (None, format!("Invalid webhook event, error: {err}")))?;
+        let signature = webhook_signature(&delivery.secret, &body);
+
+        // the URL is left out of the error, it is listed with the deliveries
+        // and may carry a secret of the user
+        let response = self
+            .http_client
+            .post(&delivery.url)
+            .timeout(WEBHOOK_TIMEOUT)
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| {
+                (None, format!("Failed to post webhook, error: {}", err.without_url()))
+            })?;
+
+        let status = response.status();
+        let response_status = i32::from(status.as_u16());
+        if !status.is_success() {
+            return Err((Some(response_status), format!("Webhook answered with {status}")));
+        }
+        Ok(response_status)
+    }
+}
+
+/// Signature of a body posted to a webhook, `sha256=` followed by the hex
+/// encoded HMAC-SHA256 of the body keyed with the secret of the webhook
+#[must_use]
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(hmac_sha256(secret.as_bytes(), body)))
+}
+
+/// HMAC-SHA256 of `message` keyed with `key`, see RFC 2104
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Random secret of a webhook
+fn generate_secret() -> String {
+    let mut bytes = [0_u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Check a webhook, returns its trimmed URL and its distinct event types
+fn validate_webhook(request: CreateWebhookRequest) -> Result<(String, Vec<String>)> {
+    let url = request.url.trim().to_string();
+    if url.len() > 2048 {
+        return Err(Error::InvalidWebhook { reason: "URL must be at most 2048 characters" });
+    }
+    let is_http = reqwest::Url::parse(&url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+    if !is_http {
+        return Err(Error::InvalidWebhook { reason: "URL must be an http or https URL" });
+    }
+
+    let mut event_types = Vec::new();
+    for event_type in request.event_types {
+        let event_type = event_type.as_str().to_string();
+        if !event_types.contains(&event_type) {
+            event_types.push(event_type);
+        }
+    }
+    if event_types.is_empty() {
+        return Err(Error::InvalidWebhook { reason: "at least one event type is required" });
+    }
+
+    Ok((url, event_types))
+}
+
+async fn get_user_id(
+    conn: &mut PgConnection,
+    keycloak_user_id: &Uuid,
+    realm: Option<&str>,
+) -> Result<Uuid> {
+    conn.get_user_by_keycloak_id(keycloak_user_id, realm, false)
+        .await?
+        .map(|user| user.id)
+        .ok_or(Error::UserNotFound { user_id: *keycloak_user_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::WebhookEventType;
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231, test cases 2 and 6
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_validate_webhook() {
+        let request = |url: &str, event_types: Vec<WebhookEventType>| CreateWebhookRequest {
+            url: url.to_string(),
+            event_types,
+        };
+
+        let (url, event_types) = validate_webhook(request(
+            " https://example.com/hook ",
+            vec![
+                WebhookEventType::UserActivated,
+                WebhookEventType::TransactionConfirmed,
+                WebhookEventType::UserActivated,
+            ],
+        ))
+        .unwrap();
+        assert_eq!(url, "https://example.com/hook");
+        assert_eq!(event_types, ["user.activated", "transaction.confirmed"]);
+
+        for invalid in [
+            request("ftp://example.com/hook", vec![WebhookEventType::UserActivated]),
+            request("example.com/hook", vec![WebhookEventType::UserActivated]),
+            request("https://example.com/hook", Vec::new()),
+        ] {
+            assert!(matches!(validate_webhook(invalid), Err(Error::InvalidWebhook { .. })));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Shutdown ordering and report.
+//!
+//! The lifecycle manager signals every subsystem at once: the HTTP server
+//! stops accepting connections and drains in-flight requests, the worker and
+//! the other servers stop. Once all of them are down the notification outbox
+//! is flushed and the Postgres pool is closed. How long each of them took is
+//! logged at info level.
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use sigfinn::{ExitStatus, Shutdown};
+
+use crate::Error;
+
+/// Time each subsystem and step took to shut down
+#[derive(Clone, Default)]
+pub struct ShutdownReport {
+    inner: Arc<Mutex<ShutdownSteps>>,
+}
+
+#[derive(Default)]
+struct ShutdownSteps {
+    /// When the shutdown was signalled, or the first subsystem exited
+    started_at: Option<Instant>,
+
+    steps: Vec<(&'static str, Duration)>,
+}
+
+impl ShutdownReport {
+    /// Subsystem recording when the shutdown is signalled
+    pub fn watch_signal(&self) -> impl FnOnce(Shutdown) -> BoxFuture<'static, ExitStatus<Error>> {
+        let report = self.clone();
+        move |signal| {
+            async move {
+                signal.await;
+                let _started_at = *report.lock().started_at.get_or_insert_with(Instant::now);
+                ExitStatus::Success
+            }
+            .boxed()
+        }
+    }
+
+    /// Subsystem created by `create`, recorded as `name` once it exits
+    pub fn track<F>(
+        &self,
+        name: &'static str,
+        create: F,
+    ) -> impl FnOnce(Shutdown) -> BoxFuture<'static, ExitStatus<Error>>
+    where
+        F: FnOnce(Shutdown) -> BoxFuture<'static, ExitStatus<Error>> + Send + 'static,
+    {
+        let report = self.clone();
+        move |signal| {
+            let subsystem = create(signal);
+            async move {
+                let status = subsystem.await;
+                report.record_exit(name);
+                status
+            }
+            .boxed()
+        }
+    }
+
+    /// Run `step` once the subsystems are down, recorded as `name`
+    pub async fn step<T>(&self, name: &'static str, step: impl Future<Output = T>) -> T {
+        let started_at = Instant::now();
+        let output = step.await;
+        self.lock().steps.push((name, started_at.elapsed()));
+        output
+    }
+
+    /// Log the total shutdown time and the time of each subsystem and step
+    pub fn log(&self) {
+        let steps = self.lock();
+        let Some(started_at) = steps.started_at else {
+            return;
+        };
+
+        let durations = steps
+            .steps
+            .iter()
+            .map(|(name, elapsed)| format!("{name}: {elapsed:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        tracing::info!("Shut down in {:?} ({durations})", started_at.elapsed());
+    }
+
+    /// A subsystem exiting on its own, e.g. with a fatal error, starts the
+    /// shutdown of the others
+    fn record_exit(&self, name: &'static str) {
+        let mut steps = self.lock();
+        let started_at = *steps.started_at.get_or_insert_with(Instant::now);
+        steps.steps.push((name, started_at.elapsed()));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ShutdownSteps> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_steps_are_recorded_in_order() {
+        let report = ShutdownReport::default();
+        report.record_exit("HTTP server");
+        report.step("Postgres pool", async {}).await;
+
+        let steps = report.lock().steps.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(steps, ["HTTP server", "Postgres pool"]);
+    }
+}
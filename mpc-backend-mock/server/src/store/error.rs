@@ -0,0 +1,16 @@
+use snafu::Snafu;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Failed to connect to Redis, error: {source}"))]
+    ConnectRedis { source: redis::RedisError },
+
+    #[snafu(display("Redis command `{command}` failed, error: {source}"))]
+    RedisCommand { command: &'static str, source: redis::RedisError },
+
+    #[snafu(display("Invalid token bucket reply `{reply}` from Redis"))]
+    InvalidTokenBucketReply { reply: String },
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use mpc_backend_mock_core::config::RateLimit;
+
+use super::{bucket_capacity, bucket_refill_rate, Result, Store};
+
+/// How often expired entries are dropped
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// In-process [`Store`], not shared between replicas
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    values: Arc<Mutex<HashMap<String, Value>>>,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+#[derive(Debug)]
+struct Value {
+    data: Vec<u8>,
+    expires_at: Instant,
+}
+
+impl MemoryStore {
+    /// Drop expired values and idle buckets every [`PRUNE_INTERVAL`],
+    /// forever
+    pub async fn prune_expired(self) {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            let _instant = interval.tick().await;
+            self.prune(Instant::now());
+        }
+    }
+
+    fn prune(&self, now: Instant) {
+        lock(&self.values).retain(|_, value| value.expires_at > now);
+        lock(&self.buckets).retain(|_, bucket| bucket.full_at > now);
+    }
+
+    fn acquire_token_at(&self, key: &str, limit: &RateLimit, now: Instant) -> Option<Duration> {
+        if limit.requests_per_minute == 0 {
+            return None;
+        }
+
+        lock(&self.buckets)
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::full(limit, now))
+            .acquire(limit, now)
+    }
+}
+
+#[async_trait]
+impl Store for MemoryStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let now = Instant::now();
+        Ok(lock(&self.values)
+            .get(key)
+            .filter(|value| value.expires_at > now)
+            .map(|value| value.data.clone()))
+    }
+
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<()> {
+        let value = Value { data: value.to_vec(), expires_at: Instant::now() + ttl };
+        let _previous = lock(&self.values).insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn acquire_token(&self, key: &str, limit: &RateLimit) -> Result<Option<Duration>> {
+        Ok(self.acquire_token_at(key, limit, Instant::now()))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // entries are replaced whole, a poisoned lock is still usable
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+    /// When the bucket is refilled and can be dropped
+    full_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self { tokens: bucket_capacity(limit), updated_at: now, full_at: now }
+    }
+
+    fn acquire(&mut self, limit: &RateLimit, now: Instant) -> Option<Duration> {
+        let capacity = bucket_capacity(limit);
+        let rate = bucket_refill_rate(limit);
+
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = elapsed.mul_add(rate, self.tokens).min(capacity);
+        self.updated_at = now;
+
+        let retry_after = if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        };
+        self.full_at = now + Duration::from_secs_f64((capacity - self.tokens) / rate);
+
+        retry_after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: RateLimit = RateLimit { requests_per_minute: 60, burst: 2 };
+
+    #[tokio::test]
+    async fn test_get_set() {
+        let store = MemoryStore::default();
+
+        assert_eq!(store.get("key").await.unwrap(), None);
+        store.set("key", b"value", Duration::from_secs(60)).await.unwrap();
+        assert_eq!(store.get("key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        store.set("key", b"value", Duration::ZERO).await.unwrap();
+        assert_eq!(store.get("key").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_acquire_within_burst() {
+        let store = MemoryStore::default();
+        let now = Instant::now();
+
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now), None);
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now), None);
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now), Some(Duration::from_secs(1)));
+
+        // other clients have their own bucket
+        assert_eq!(store.acquire_token_at("b", &LIMIT, now), None);
+    }
+
+    #[test]
+    fn test_acquire_after_refill() {
+        let store = MemoryStore::default();
+        let now = Instant::now();
+
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now), None);
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now), None);
+        assert!(store.acquire_token_at("a", &LIMIT, now).is_some());
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let store = MemoryStore::default();
+        let limit = RateLimit { requests_per_minute: 0, burst: 0 };
+        let now = Instant::now();
+
+        assert!((0..100).all(|_| store.acquire_token_at("a", &limit, now).is_none()));
+    }
+
+    #[test]
+    fn test_prune_idle_buckets() {
+        let store = MemoryStore::default();
+        let now = Instant::now();
+
+        assert_eq!(store.acquire_token_at("a", &LIMIT, now), None);
+        store.prune(now);
+        assert_eq!(lock(&store.buckets).len(), 1);
+
+        store.prune(now + Duration::from_secs(1));
+        assert!(lock(&store.buckets).is_empty());
+    }
+}
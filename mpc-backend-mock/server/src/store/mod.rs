@@ -0,0 +1,40 @@
+//! Key-value store backing caches and rate limits.
+//!
+//! With a `redis` section in the configuration the state lives in Redis and
+//! is shared by every replica, otherwise [`MemoryStore`] keeps it in process.
+
+mod error;
+mod memory;
+mod redis;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mpc_backend_mock_core::config::RateLimit;
+
+pub use self::{
+    error::{Error, Result},
+    memory::MemoryStore,
+    redis::RedisStore,
+};
+
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Value of `key`, `None` if it is missing or expired
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Set `key` to `value`, expiring after `ttl`
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<()>;
+
+    /// Take a token from the token bucket `key`, which is created full
+    ///
+    /// Returns how long to wait until a token is available when the bucket
+    /// is empty.
+    async fn acquire_token(&self, key: &str, limit: &RateLimit) -> Result<Option<Duration>>;
+}
+
+/// A burst of zero would reject every request, at least one is allowed
+fn bucket_capacity(limit: &RateLimit) -> f64 { f64::from(limit.burst.max(1)) }
+
+/// Tokens per second
+fn bucket_refill_rate(limit: &RateLimit) -> f64 { f64::from(limit.requests_per_minute) / 60.0 }
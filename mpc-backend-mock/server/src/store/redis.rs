@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mpc_backend_mock_core::config::{RateLimit, RedisConfig};
+use redis::{aio::ConnectionManager, Script};
+use snafu::ResultExt;
+
+use super::{bucket_capacity, bucket_refill_rate, error, Result, Store};
+
+/// Token bucket stored as a hash of `tokens` and `updated_at`, refilled with
+/// the Redis server clock so that replicas with skewed clocks agree.
+/// Returns the seconds to wait for a token, `0` when one was taken.
+const TOKEN_BUCKET_SCRIPT: &str = r"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or capacity
+local updated_at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated_at) * rate)
+
+local retry_after = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+else
+  retry_after = (1 - tokens) / rate
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', tostring(now))
+redis.call('PEXPIRE', KEYS[1], math.ceil((capacity - tokens) / rate * 1000) + 1000)
+return tostring(retry_after)
+";
+
+/// [`Store`] shared by every replica connected to the same Redis
+#[derive(Clone)]
+pub struct RedisStore {
+    connection: ConnectionManager,
+    key_prefix: String,
+    token_bucket: Script,
+}
+
+impl RedisStore {
+    /// # Errors
+    ///
+    /// Returns an error if Redis cannot be reached
+    pub async fn connect(config: &RedisConfig) -> Result<Self> {
+        let connection = redis::Client::open(config.url.as_str())
+            .context(error::ConnectRedisSnafu)?
+            .get_connection_manager()
+            .await
+            .context(error::ConnectRedisSnafu)?;
+
+        Ok(Self {
+            connection,
+            key_prefix: config.key_prefix.clone(),
+            token_bucket: Script::new(TOKEN_BUCKET_SCRIPT),
+        })
+    }
+
+    fn key(&self, key: &str) -> String { format!("{}:{key}", self.key_prefix) }
+}
+
+#[async_trait]
+impl Store for RedisStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        redis::cmd("GET")
+            .arg(self.key(key))
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(error::RedisCommandSnafu { command: "GET" })
+    }
+
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<()> {
+        // `PX 0` is rejected, a zero TTL expires as soon as possible instead
+        let ttl_millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+
+        redis::cmd("SET")
+            .arg(self.key(key))
+            .arg(value)
+            .arg("PX")
+            .arg(ttl_millis)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(error::RedisCommandSnafu { command: "SET" })
+    }
+
+    async fn acquire_token(&self, key: &str, limit: &RateLimit) -> Result<Option<Duration>> {
+        if limit.requests_per_minute == 0 {
+            return Ok(None);
+        }
+
+        let reply: String = self
+            .token_bucket
+            .key(self.key(key))
+            .arg(bucket_capacity(limit))
+            .arg(bucket_refill_rate(limit))
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .context(error::RedisCommandSnafu { command: "EVALSHA" })?;
+
+        let retry_after = reply
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+            .ok_or_else(|| error::Error::InvalidTokenBucketReply { reply: reply.clone() })?;
+
+        Ok((retry_after > 0.0).then(|| Duration::from_secs_f64(retry_after)))
+    }
+}
@@ -0,0 +1,197 @@
+//! Lifecycle-managed background tasks.
+//!
+//! Background work must be spawned through [`TaskSupervisor`] instead of a
+//! bare `tokio::spawn`, so that every task is registered with the
+//! [`LifecycleManager`](sigfinn::LifecycleManager), is stopped on shutdown,
+//! has its panics counted in metrics and shows up in [`TaskRegistry`].
+
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use chrono::Utc;
+use futures::FutureExt;
+use prometheus::{IntCounterVec, IntGaugeVec, Opts};
+use sigfinn::{ExitStatus, Handle};
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    entity::BackgroundTask,
+    error::{self, Error, Result},
+};
+
+/// Background tasks which are currently running
+#[derive(Clone, Debug, Default)]
+pub struct TaskRegistry {
+    next_id: Arc<AtomicU64>,
+    tasks: Arc<Mutex<BTreeMap<u64, BackgroundTask>>>,
+}
+
+impl TaskRegistry {
+    /// Running tasks, ordered by spawn order
+    #[must_use]
+    pub fn list(&self) -> Vec<BackgroundTask> { self.lock().values().cloned().collect() }
+
+    fn register(&self, name: &str) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let task = BackgroundTask { id, name: name.to_string(), started_at: Utc::now() };
+        let _previous = self.lock().insert(id, task);
+        id
+    }
+
+    fn deregister(&self, id: u64) { let _task = self.lock().remove(&id); }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u64, BackgroundTask>> {
+        // the map is always left consistent, so a poisoned lock is still usable
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TaskMetrics {
+    running: IntGaugeVec,
+    panics: IntCounterVec,
+}
+
+impl TaskMetrics {
+    fn new(metrics: &DefaultMetrics) -> Result<Self> {
+        let running = IntGaugeVec::new(
+            Opts::new("background_tasks_running", "Number of running background tasks"),
+            &["task"],
+        )
+        .context(error::CreateTaskMetricsSnafu)?;
+        let panics = IntCounterVec::new(
+            Opts::new("background_task_panics_total", "Number of panicked background tasks"),
+            &["task"],
+        )
+        .context(error::CreateTaskMetricsSnafu)?;
+
+        metrics.register(Box::new(running.clone()))?;
+        metrics.register(Box::new(panics.clone()))?;
+
+        Ok(Self { running, panics })
+    }
+}
+
+/// Removes a task from the registry when it finishes, panics or is cancelled
+struct RunningTask {
+    id: u64,
+    name: String,
+    registry: TaskRegistry,
+    metrics: TaskMetrics,
+}
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        self.registry.deregister(self.id);
+        self.metrics.running.with_label_values(&[&self.name]).dec();
+    }
+}
+
+/// Spawns background tasks onto the
+/// [`LifecycleManager`](sigfinn::LifecycleManager)
+#[derive(Clone)]
+pub struct TaskSupervisor {
+    handle: Handle<Error>,
+    registry: TaskRegistry,
+    metrics: TaskMetrics,
+}
+
+impl TaskSupervisor {
+    /// # Errors
+    ///
+    /// Returns an error if the task metrics cannot be registered
+    pub fn new(
+        handle: Handle<Error>,
+        registry: TaskRegistry,
+        metrics: &DefaultMetrics,
+    ) -> Result<Self> {
+        Ok(Self { handle, registry, metrics: TaskMetrics::new(metrics)? })
+    }
+
+    /// Spawn `task`, it is dropped when the server shuts down.
+    ///
+    /// A panic in `task` is logged and counted, it does not bring the server
+    /// down.
+    pub fn spawn<Fut>(&self, name: &str, task: Fut)
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let running = RunningTask {
+            id: self.registry.register(name),
+            name: name.to_string(),
+            registry: self.registry.clone(),
+            metrics: self.metrics.clone(),
+        };
+        running.metrics.running.with_label_values(&[name]).inc();
+
+        let _handle = self.handle.spawn(name, move |shutdown| {
+            async move {
+                tokio::select! {
+                    () = shutdown => {
+                        tracing::debug!("Background task `{}` is stopped on shutdown", running.name);
+                    }
+                    result = AssertUnwindSafe(task).catch_unwind() => {
+                        if let Err(panic) = result {
+                            running.metrics.panics.with_label_values(&[&running.name]).inc();
+                            tracing::error!(
+                                "Background task `{}` panicked: {}",
+                                running.name,
+                                panic_message(panic.as_ref())
+                            );
+                        }
+                    }
+                }
+
+                ExitStatus::Success
+            }
+            .boxed()
+        });
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic payload")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lists_running_tasks_in_spawn_order() {
+        let registry = TaskRegistry::default();
+        let first = registry.register("first");
+        let second = registry.register("second");
+
+        let names: Vec<_> = registry.list().into_iter().map(|task| task.name).collect();
+        assert_eq!(names, ["first", "second"]);
+
+        registry.deregister(first);
+        let tasks = registry.list();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, second);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let panic = std::panic::catch_unwind(|| panic!("boom")).unwrap_err();
+        assert_eq!(panic_message(panic.as_ref()), "boom");
+
+        let panic =
+            std::panic::catch_unwind(|| panic!("{}", String::from("formatted"))).unwrap_err();
+        assert_eq!(panic_message(panic.as_ref()), "formatted");
+    }
+}
@@ -0,0 +1,91 @@
+//! User management from the command line.
+//!
+//! [`create_user`] and [`delete_user`] go through the same
+//! [`UserManagementService`] as the admin API, so users seeded from a terminal
+//! get a Keycloak account and an activation email like any other user. The
+//! activation email is sent right away instead of waiting for the
+//! notification dispatcher of a running server. Their domain events are
+//! recorded in the outbox and published by a running server.
+
+use std::sync::Arc;
+
+use mpc_backend_mock_core::config::Config;
+use uuid::Uuid;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    entity::User,
+    error::{Error, Result},
+    service::{EventOutbox, KeycloakUserDirectory, NotificationService, UserManagementService},
+    EventBus, QueryMetrics,
+};
+
+/// Create a user, which stays inactive until its activation token is redeemed,
+/// with the activation email in `locale` if given
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Postgres, Keycloak or the notification client cannot be initialized
+/// - Email or locale is invalid, or the email is already taken
+/// - Keycloak or database operation fails
+pub async fn create_user(config: Config, email: &str, locale: Option<&str>) -> Result<User> {
+    let (service, notification_service) = user_management_service(config).await?;
+    let user = service
+        .create_user(email, None, locale)
+        .await
+        .map_err(|source| Error::ManageUser { source })?;
+
+    // a failed email stays queued for the dispatcher of the server
+    match notification_service.dispatch_due().await {
+        Ok(dispatch) if dispatch.sent > 0 => {}
+        Ok(_) => tracing::warn!("Activation email is not sent yet, it stays queued"),
+        Err(err) => {
+            tracing::warn!("Failed to send activation email, it stays queued, error: {err}")
+        }
+    }
+
+    Ok(user)
+}
+
+/// Soft delete a user by email, returning its id
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Postgres, Keycloak or the notification client cannot be initialized
+/// - Email is invalid or the user is not found
+/// - Keycloak or database operation fails
+pub async fn delete_user(config: Config, email: &str) -> Result<Uuid> {
+    let (service, _notification_service) = user_management_service(config).await?;
+    service.delete_user_by_email(email).await.map_err(|source| Error::ManageUser { source })
+}
+
+/// Build the user management and notification services the server would use,
+/// without running migrations
+async fn user_management_service(
+    Config { postgres, keycloak, notification, events, activation, .. }: Config,
+) -> Result<(UserManagementService, NotificationService)> {
+    let (database, (_keycloak_client, keycloak_admin), notification_client) = tokio::try_join!(
+        crate::connect_postgres_pool(&postgres),
+        crate::initialize_keycloak_clients(&keycloak),
+        crate::initialize_notification_client(notification),
+    )?;
+
+    // the metrics are never exported, they only satisfy the service
+    let query_metrics = QueryMetrics::new(&DefaultMetrics::new())?
+        .with_slow_query_threshold(postgres.slow_query_threshold);
+
+    Ok((
+        UserManagementService::new(
+            database.clone(),
+            Arc::new(KeycloakUserDirectory::new(Arc::new(keycloak_admin), keycloak.realm)),
+            &activation,
+            EventBus::new(),
+            query_metrics,
+        )
+        .with_transaction_retry(postgres.transaction_retry)
+        .with_event_outbox(EventOutbox::new(events.publisher.is_enabled())),
+        NotificationService::new(database, notification_client),
+    ))
+}
@@ -0,0 +1,61 @@
+//! Business metrics recorded by the handlers and middlewares through the
+//! [`MetricsHandle`] of the [`ServiceState`](super::ServiceState).
+//!
+//! A metric which cannot be created is logged and not recorded, the request
+//! does not fail because of it.
+
+use prometheus::IntCounterVec;
+use zeus_metrics::MetricsHandle;
+
+/// Count a user created through the API in `users_created_total`
+pub fn record_user_created(metrics: &MetricsHandle) {
+    if let Some(created) =
+        counter(metrics, "users_created_total", "Number of users created through the API", &[])
+    {
+        created.with_label_values(&[]).inc();
+    }
+}
+
+/// Count a rejected authentication in `auth_failures_total`, labelled by the
+/// error `code` the client received
+pub fn record_auth_failure(metrics: &MetricsHandle, code: &str) {
+    if let Some(failures) = counter(
+        metrics,
+        "auth_failures_total",
+        "Number of rejected logins and access tokens by error code",
+        &["code"],
+    ) {
+        failures.with_label_values(&[code]).inc();
+    }
+}
+
+fn counter(
+    metrics: &MetricsHandle,
+    name: &str,
+    help: &str,
+    labels: &[&str],
+) -> Option<IntCounterVec> {
+    metrics
+        .counter(name, help, labels)
+        .inspect_err(|err| tracing::warn!("Failed to create metric `{name}`, error: {err}"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use zeus_metrics::{DefaultMetrics, Metrics};
+
+    use super::*;
+
+    #[test]
+    fn test_record_auth_failure() {
+        let metrics = DefaultMetrics::new();
+        record_auth_failure(&metrics.handle(), "INVALID_TOKEN");
+        record_auth_failure(&metrics.handle(), "INVALID_TOKEN");
+
+        let families = metrics.gather();
+        assert_eq!(families[0].get_name(), "auth_failures_total");
+        let failures = families[0].get_metric()[0].get_counter().get_value();
+        assert!((failures - 2.0).abs() < f64::EPSILON);
+    }
+}
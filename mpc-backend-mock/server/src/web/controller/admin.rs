@@ -0,0 +1,752 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use futures::TryStreamExt;
+use mpc_backend_mock_core::{
+    config::JwtValidationMethod,
+    model::{Email, Paginated, Pagination},
+};
+use utoipa::OpenApi;
+use uuid::Uuid;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    circuit_breaker::{BreakerState, CircuitBreaker},
+    entity::{
+        Annotation, ApiDriftReport, AuditAction, AuditLog, BackgroundTask, BulkDeleteUsersParams,
+        BulkDeleteUsersResponse, CapturedNotification, ClientIpResponse, ConfigReloadReport,
+        CreateAnnotationRequest, DependencyState, DependencyStatus, ExportFormat,
+        ExportUsersParams, FaultInjection, HttpLog, ImpersonationResponse, ListAuditLogsFilter,
+        ListNotificationsFilter, LoginAttempt, OpenApiBaseline, OutboxNotification, SloReport,
+        UpdateFaultInjectionRequest, User,
+    },
+    service::error::Error as ServiceError,
+    web::{
+        controller::{ApiDoc, Error, Result},
+        extractor::{Audit, AuthUser, PaginationQuery, ValidatedJson, ValidatedQuery},
+        middleware::ClientIp,
+    },
+    ServiceState,
+};
+
+/// Get the caller's IP address
+///
+/// This endpoint returns the client IP the server resolved for the request,
+/// which is the address checked against the admin allowlist. Use it to verify
+/// the trusted proxy configuration.
+#[utoipa::path(
+    get,
+    operation_id = "get_client_ip",
+    path = "/api/v1/admin/client-ip",
+    responses(
+        (status = 200, description = "Client IP resolved", body = ClientIpResponse),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn client_ip(
+    Extension(ClientIp(ip)): Extension<ClientIp>,
+) -> Result<EncapsulatedJson<ClientIpResponse>> {
+    Ok(EncapsulatedJson::ok(ClientIpResponse { ip }))
+}
+
+/// List running background tasks
+///
+/// This endpoint returns the background tasks currently registered with the
+/// server's lifecycle manager.
+#[utoipa::path(
+    get,
+    operation_id = "list_background_tasks",
+    path = "/api/v1/admin/tasks",
+    responses(
+        (status = 200, description = "Running background tasks", body = [BackgroundTask]),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_background_tasks(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<Vec<BackgroundTask>>> {
+    Ok(EncapsulatedJson::ok(state.background_tasks.list()))
+}
+
+/// Get the latency SLO report
+///
+/// This endpoint summarizes p50/p95/p99 latency and the 5xx error rate per
+/// route over the last 5 minutes, computed from the
+/// `http_request_duration_seconds` histogram.
+#[utoipa::path(
+    get,
+    operation_id = "get_slo_report",
+    path = "/api/v1/admin/slo",
+    responses(
+        (status = 200, description = "Latency SLO report", body = SloReport),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn get_slo_report(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<SloReport>> {
+    Ok(EncapsulatedJson::ok(state.http_metrics.slo_report()))
+}
+
+/// List the upstream dependencies
+///
+/// This endpoint returns the state and last failure of the circuit breaker of
+/// each dependency, with the routes failing fast while the breaker is not
+/// closed. Dependencies without a circuit breaker are reported as
+/// `untracked`.
+#[utoipa::path(
+    get,
+    operation_id = "list_dependencies",
+    path = "/api/v1/admin/dependencies",
+    responses(
+        (status = 200, description = "Upstream dependencies", body = [DependencyStatus]),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_dependencies(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<Vec<DependencyStatus>>> {
+    let breakers = &state.circuit_breakers;
+
+    // tokens are validated by the breaker of the configured method
+    let token_routes = super::v1_routes().token_authenticated_paths();
+    let (mut keycloak_routes, jwks_routes) = match state.jwt_validation_method {
+        JwtValidationMethod::Jwks => (Vec::new(), token_routes),
+        JwtValidationMethod::Introspection => (token_routes, Vec::new()),
+    };
+    keycloak_routes.extend_from_slice(KEYCLOAK_ROUTES);
+    keycloak_routes.sort_unstable();
+    keycloak_routes.dedup();
+
+    Ok(EncapsulatedJson::ok(vec![
+        untracked_dependency("Postgres"),
+        tracked_dependency(&breakers.bitcoin, BITCOIN_ROUTES.to_vec()),
+        untracked_dependency("Solana"),
+        tracked_dependency(&breakers.keycloak, keycloak_routes),
+        tracked_dependency(&breakers.jwks, jwks_routes),
+        untracked_dependency("Gmail"),
+        untracked_dependency("KMS"),
+    ]))
+}
+
+/// Reload the configuration
+///
+/// This endpoint reads the configuration file again, like `SIGHUP` does, and
+/// applies the log filters, rate limits, CORS origins, fault injection and
+/// JWKS cache TTL without a restart. Other changed settings are listed in
+/// `restart_required` and take effect after a restart.
+#[utoipa::path(
+    post,
+    operation_id = "reload_config",
+    path = "/api/v1/admin/reload",
+    responses(
+        (status = 200, description = "Configuration reloaded", body = ConfigReloadReport),
+        (status = 403, description = "Client IP is not allowed to access admin routes"),
+        (status = 500, description = "Configuration could not be loaded, nothing was applied")
+    ),
+    tag = "Admin"
+)]
+pub async fn reload_config(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<ConfigReloadReport>> {
+    let reloader = state.config_reloader.as_ref().ok_or(Error::ConfigReloadUnavailable)?;
+    let report = reloader.reload().await.map_err(|source| Error::ReloadConfig { source })?;
+
+    Ok(EncapsulatedJson::ok(report))
+}
+
+/// Get the fault injection
+///
+/// This endpoint returns whether faults are injected into API responses and
+/// the configured rules.
+#[utoipa::path(
+    get,
+    operation_id = "get_fault_injection",
+    path = "/api/v1/admin/fault-injection",
+    responses(
+        (status = 200, description = "Fault injection retrieved", body = FaultInjection),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn get_fault_injection(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<FaultInjection>> {
+    Ok(EncapsulatedJson::ok(FaultInjection::from(&state.fault_injector.config())))
+}
+
+/// Get the HTTP debug log
+///
+/// This endpoint returns the latest requests and responses of the routes of
+/// `web.debug_http.routes`, newest first, with the values of the redacted
+/// fields replaced. Nothing is logged unless `web.debug_http` is enabled.
+#[utoipa::path(
+    get,
+    operation_id = "get_http_log",
+    path = "/api/v1/admin/http-log",
+    responses(
+        (status = 200, description = "HTTP debug log retrieved", body = HttpLog),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn get_http_log(State(state): State<ServiceState>) -> Result<EncapsulatedJson<HttpLog>> {
+    Ok(EncapsulatedJson::ok(HttpLog {
+        enable: state.http_log.is_enabled(),
+        exchanges: state.http_log.exchanges(),
+    }))
+}
+
+/// Turn the fault injection on or off
+///
+/// This endpoint applies or stops applying the rules of
+/// `web.fault_injection.rules` without a restart, until the configuration is
+/// reloaded. Admin routes are never faulted.
+#[utoipa::path(
+    put,
+    operation_id = "update_fault_injection",
+    path = "/api/v1/admin/fault-injection",
+    request_body = UpdateFaultInjectionRequest,
+    responses(
+        (status = 200, description = "Fault injection updated", body = FaultInjection),
+        (status = 400, description = "Invalid request body"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn update_fault_injection(
+    State(state): State<ServiceState>,
+    ValidatedJson(request): ValidatedJson<UpdateFaultInjectionRequest>,
+) -> Result<EncapsulatedJson<FaultInjection>> {
+    state.fault_injector.set_enabled(request.enable);
+    tracing::info!("Fault injection {}", if request.enable { "enabled" } else { "disabled" });
+
+    Ok(EncapsulatedJson::ok(FaultInjection::from(&state.fault_injector.config())))
+}
+
+/// List audit logs
+///
+/// This endpoint returns the recorded security-relevant actions page by page,
+/// newest first. The total number of entries matching the filters is returned
+/// in `_metadata`.
+#[utoipa::path(
+    get,
+    operation_id = "list_audit_logs",
+    path = "/api/v1/admin/audit-logs",
+    params(Pagination, ListAuditLogsFilter),
+    responses(
+        (status = 200, description = "Audit logs retrieved successfully", body = [AuditLog]),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_audit_logs(
+    State(state): State<ServiceState>,
+    PaginationQuery(pagination): PaginationQuery,
+    ValidatedQuery(filter): ValidatedQuery<ListAuditLogsFilter>,
+) -> Result<Paginated<AuditLog>> {
+    let (logs, total_count) = state.audit_service.list_audit_logs(&pagination, &filter).await?;
+
+    Ok(Paginated::new(logs, total_count, &pagination))
+}
+
+/// List queued notifications
+///
+/// This endpoint returns the notifications of the outbox page by page, newest
+/// first, with their delivery attempts and last error. Filter by
+/// `status=dead` to inspect the dead-lettered ones. Payloads are never
+/// returned, they may contain activation links.
+#[utoipa::path(
+    get,
+    operation_id = "list_notifications",
+    path = "/api/v1/admin/notifications",
+    params(Pagination, ListNotificationsFilter),
+    responses(
+        (status = 200, description = "Notifications retrieved successfully", body = [OutboxNotification]),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_notifications(
+    State(state): State<ServiceState>,
+    PaginationQuery(pagination): PaginationQuery,
+    ValidatedQuery(filter): ValidatedQuery<ListNotificationsFilter>,
+) -> Result<Paginated<OutboxNotification>> {
+    let (notifications, total_count) =
+        state.notification_service.list_notifications(&pagination, &filter).await?;
+
+    Ok(Paginated::new(notifications, total_count, &pagination))
+}
+
+/// List captured notifications
+///
+/// This endpoint returns the emails recorded by the `capture` notification
+/// provider page by page, newest first, with their rendered subject and body.
+/// It is empty unless the server runs with the `capture` provider.
+#[utoipa::path(
+    get,
+    operation_id = "list_captured_notifications",
+    path = "/api/v1/admin/notifications/captured",
+    params(Pagination),
+    responses(
+        (status = 200, description = "Captured notifications retrieved successfully", body = [CapturedNotification]),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_captured_notifications(
+    State(state): State<ServiceState>,
+    PaginationQuery(pagination): PaginationQuery,
+) -> Result<Paginated<CapturedNotification>> {
+    let (notifications, total_count) =
+        state.notification_service.list_captured_notifications(&pagination).await?;
+
+    Ok(Paginated::new(notifications, total_count, &pagination))
+}
+
+/// Delete users in bulk (for testing purposes only)
+///
+/// This endpoint permanently deletes every user of `realm` whose email matches
+/// `pattern` from Keycloak and the database, together with their wallets. Use
+/// `dry_run` to list the matching users first.
+#[utoipa::path(
+    delete,
+    operation_id = "delete_users",
+    path = "/api/v1/admin/users",
+    params(BulkDeleteUsersParams),
+    responses(
+        (status = 200, description = "Matching users deleted", body = BulkDeleteUsersResponse),
+        (status = 400, description = "Invalid request (e.g., pattern without `@` or unknown realm)"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn delete_users(
+    State(state): State<ServiceState>,
+    ValidatedQuery(params): ValidatedQuery<BulkDeleteUsersParams>,
+) -> Result<EncapsulatedJson<BulkDeleteUsersResponse>> {
+    let emails = state
+        .user_management_service
+        .delete_users_by_pattern(
+            &params.pattern,
+            params.realm.as_deref(),
+            params.created_before,
+            params.dry_run,
+        )
+        .await?;
+
+    Ok(EncapsulatedJson::ok(BulkDeleteUsersResponse {
+        dry_run: params.dry_run,
+        count: emails.len(),
+        emails,
+    }))
+}
+
+/// Export users
+///
+/// This endpoint streams every user of `realm`, soft-deleted users included,
+/// oldest first, as CSV or as a JSON array. Users are read from the database
+/// while the response is sent, so an export of any size is never buffered. A
+/// failure after the response started aborts it, leaving a truncated export.
+#[utoipa::path(
+    get,
+    operation_id = "export_users",
+    path = "/api/v1/admin/users/export",
+    params(ExportUsersParams),
+    responses(
+        (status = 200, description = "Users exported", content(
+            (String = "text/csv"),
+            (Vec<User> = "application/json")
+        )),
+        (status = 400, description = "Invalid format"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn export_users(
+    State(state): State<ServiceState>,
+    ValidatedQuery(params): ValidatedQuery<ExportUsersParams>,
+) -> Result<Response> {
+    let (content_type, file_name) = match params.format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "users.csv"),
+        ExportFormat::Json => ("application/json", "users.json"),
+    };
+
+    let export = state
+        .user_management_service
+        .export_users(params.realm.as_deref(), params.format)
+        .await?
+        .map_err(|err| {
+            // the status is sent already, the client only sees the export end early
+            tracing::warn!("Failed to export users, error: {err}");
+            std::io::Error::other(err.to_string())
+        });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{file_name}\"")),
+        ],
+        Body::from_stream(export),
+    )
+        .into_response())
+}
+
+/// Upload an OpenAPI baseline
+///
+/// This endpoint stores the OpenAPI document in the request body, usually the
+/// `/openapi.json` of a release, as the baseline drift reports compare
+/// against. Each upload replaces the previous baseline.
+#[utoipa::path(
+    post,
+    operation_id = "upload_openapi_baseline",
+    path = "/api/v1/admin/openapi/baselines",
+    request_body(content = Object, description = "OpenAPI document"),
+    responses(
+        (status = 200, description = "Baseline stored", body = OpenApiBaseline),
+        (status = 400, description = "Body is not an OpenAPI document"),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn upload_openapi_baseline(
+    State(state): State<ServiceState>,
+    Json(document): Json<serde_json::Value>,
+) -> Result<EncapsulatedJson<OpenApiBaseline>> {
+    let baseline = state.api_drift_service.upload_baseline(&document).await?;
+
+    Ok(EncapsulatedJson::ok(baseline))
+}
+
+/// Get the API drift report
+///
+/// This endpoint compares the live OpenAPI document against the latest
+/// baseline and lists the operations and schemas added, removed or changed
+/// since.
+#[utoipa::path(
+    get,
+    operation_id = "get_api_drift",
+    path = "/api/v1/admin/openapi/drift",
+    responses(
+        (status = 200, description = "API drift report", body = ApiDriftReport),
+        (status = 403, description = "Client IP is not allowed to access admin routes"),
+        (status = 404, description = "No baseline was uploaded")
+    ),
+    tag = "Admin"
+)]
+pub async fn get_api_drift(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<ApiDriftReport>> {
+    let report = state.api_drift_service.drift_report(&ApiDoc::openapi()).await?;
+
+    Ok(EncapsulatedJson::ok(report))
+}
+
+/// Annotate a user
+///
+/// This endpoint attaches an internal note and flags, e.g. `test account`, to
+/// a user, deleted users included. Annotations are stored apart from the user
+/// and only returned on admin routes.
+#[utoipa::path(
+    post,
+    operation_id = "annotate_user",
+    path = "/api/v1/admin/users/{id}/annotations",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user")
+    ),
+    request_body = CreateAnnotationRequest,
+    responses(
+        (status = 200, description = "Annotation created", body = Annotation),
+        (status = 400, description = "Annotation is empty or too long"),
+        (status = 403, description = "Client IP is not allowed to access admin routes"),
+        (status = 404, description = "User not found"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    tag = "Admin"
+)]
+pub async fn annotate_user(
+    State(state): State<ServiceState>,
+    Path(user_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateAnnotationRequest>,
+) -> Result<EncapsulatedJson<Annotation>> {
+    let annotation = state.annotation_service.annotate_user(&user_id, &request).await?;
+
+    Ok(EncapsulatedJson::ok(annotation))
+}
+
+/// List the annotations on a user
+#[utoipa::path(
+    get,
+    operation_id = "list_user_annotations",
+    path = "/api/v1/admin/users/{id}/annotations",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user")
+    ),
+    responses(
+        (status = 200, description = "Annotations, oldest first", body = Vec<Annotation>),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_user_annotations(
+    State(state): State<ServiceState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<Vec<Annotation>>> {
+    let annotations = state.annotation_service.list_user_annotations(&user_id).await?;
+
+    Ok(EncapsulatedJson::ok(annotations))
+}
+
+/// Annotate a transaction
+///
+/// This endpoint attaches an internal note and flags, e.g. `fraud sim`, to a
+/// transaction. Annotations are stored apart from the transaction and only
+/// returned on admin routes.
+#[utoipa::path(
+    post,
+    operation_id = "annotate_transaction",
+    path = "/api/v1/admin/transactions/{id}/annotations",
+    params(
+        ("id" = Uuid, Path, description = "ID of the transaction")
+    ),
+    request_body = CreateAnnotationRequest,
+    responses(
+        (status = 200, description = "Annotation created", body = Annotation),
+        (status = 400, description = "Annotation is empty or too long"),
+        (status = 403, description = "Client IP is not allowed to access admin routes"),
+        (status = 404, description = "Transaction not found"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    tag = "Admin"
+)]
+pub async fn annotate_transaction(
+    State(state): State<ServiceState>,
+    Path(transaction_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateAnnotationRequest>,
+) -> Result<EncapsulatedJson<Annotation>> {
+    let annotation =
+        state.annotation_service.annotate_transaction(&transaction_id, &request).await?;
+
+    Ok(EncapsulatedJson::ok(annotation))
+}
+
+/// List the annotations on a transaction
+#[utoipa::path(
+    get,
+    operation_id = "list_transaction_annotations",
+    path = "/api/v1/admin/transactions/{id}/annotations",
+    params(
+        ("id" = Uuid, Path, description = "ID of the transaction")
+    ),
+    responses(
+        (status = 200, description = "Annotations, oldest first", body = Vec<Annotation>),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_transaction_annotations(
+    State(state): State<ServiceState>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<Vec<Annotation>>> {
+    let annotations =
+        state.annotation_service.list_transaction_annotations(&transaction_id).await?;
+
+    Ok(EncapsulatedJson::ok(annotations))
+}
+
+/// Delete an annotation
+#[utoipa::path(
+    delete,
+    operation_id = "delete_annotation",
+    path = "/api/v1/admin/annotations/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID of the annotation")
+    ),
+    responses(
+        (status = 200, description = "Annotation deleted, returns its ID", body = String),
+        (status = 403, description = "Client IP is not allowed to access admin routes"),
+        (status = 404, description = "Annotation not found")
+    ),
+    tag = "Admin"
+)]
+pub async fn delete_annotation(
+    State(state): State<ServiceState>,
+    Path(annotation_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<String>> {
+    state.annotation_service.delete_annotation(&annotation_id).await?;
+
+    Ok(EncapsulatedJson::ok(annotation_id.to_string()))
+}
+
+/// List locked emails
+///
+/// This endpoint returns the emails locked after repeated failed logins, most
+/// recently locked first. Lockouts which ended are not included.
+#[utoipa::path(
+    get,
+    operation_id = "list_login_lockouts",
+    path = "/api/v1/admin/login-lockouts",
+    responses(
+        (status = 200, description = "Locked emails retrieved successfully", body = Vec<LoginAttempt>),
+        (status = 403, description = "Client IP is not allowed to access admin routes")
+    ),
+    tag = "Admin"
+)]
+pub async fn list_login_lockouts(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<Vec<LoginAttempt>>> {
+    let lockouts = state.login_lockout_service.list_locked().await?;
+
+    Ok(EncapsulatedJson::ok(lockouts))
+}
+
+/// Unlock an email
+///
+/// This endpoint lifts the lockout of an email and forgets its failed logins,
+/// the Keycloak account of its user is enabled again. It returns the failed
+/// logins as they were before.
+#[utoipa::path(
+    delete,
+    operation_id = "unlock_login",
+    path = "/api/v1/admin/login-lockouts/{email}",
+    params(
+        ("email" = String, Path, description = "Email to unlock")
+    ),
+    responses(
+        (status = 200, description = "Email unlocked", body = LoginAttempt),
+        (status = 403, description = "Client IP is not allowed to access admin routes"),
+        (status = 404, description = "No failed logins of the email")
+    ),
+    tag = "Admin"
+)]
+pub async fn unlock_login(
+    State(state): State<ServiceState>,
+    Path(email): Path<Email>,
+) -> Result<EncapsulatedJson<LoginAttempt>> {
+    let attempt = state.login_lockout_service.unlock(email.as_str()).await?;
+
+    Ok(EncapsulatedJson::ok(attempt))
+}
+
+/// Impersonate a user
+///
+/// This endpoint issues an access token of the user for support staff to
+/// reproduce what the user sees. The caller must have the `admin` realm role
+/// besides calling from the admin allowlist. The token comes without a
+/// refresh token, and the impersonation is recorded in the audit log as
+/// `admin.user_impersonated`. Only users of the primary realm can be
+/// impersonated.
+#[utoipa::path(
+    post,
+    operation_id = "impersonate_user",
+    path = "/api/v1/admin/users/{id}/impersonate",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user")
+    ),
+    responses(
+        (status = 200, description = "Access token of the user", body = ImpersonationResponse),
+        (status = 400, description = "User belongs to another realm than the primary one"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 403, description = "Caller lacks the admin role"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Admin"
+)]
+pub async fn impersonate_user(
+    State(state): State<ServiceState>,
+    AuthUser(admin): AuthUser,
+    Audit(mut audit): Audit,
+    Path(user_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<ImpersonationResponse>> {
+    let user = state.user_management_service.get_user_by_id(user_id).await?;
+    if let Some(realm) = user.realm {
+        return Err(ServiceError::ImpersonationUnsupported { realm }.into());
+    }
+
+    let impersonation = state.auth_service.impersonate(user.id, &user.keycloak_user_id).await?;
+
+    // the admin may have no user of their own
+    audit.actor_user_id = state
+        .user_management_service
+        .get_user_by_keycloak_id(&admin.keycloak_user_id, admin.realm.as_deref())
+        .await
+        .ok()
+        .map(|admin_user| admin_user.id);
+    let payload = serde_json::json!({
+        "admin_keycloak_user_id": admin.keycloak_user_id,
+        "admin_username": admin.username,
+        "expires_in": impersonation.expires_in,
+    });
+    state
+        .audit_service
+        .record(&audit, AuditAction::UserImpersonated, Some(&user.id), payload)
+        .await;
+
+    Ok(EncapsulatedJson::ok(impersonation))
+}
+
+/// Routes calling the Bitcoin RPC
+const BITCOIN_ROUTES: &[&str] =
+    &["/bitcoin/balance", "/bitcoin/fees", "/bitcoin/mempool", "/bitcoin/utxos"];
+
+/// Routes calling Keycloak besides the token validation
+const KEYCLOAK_ROUTES: &[&str] = &[
+    "/admin/login-lockouts/:email",
+    "/admin/users",
+    "/admin/users/:id/impersonate",
+    "/auth/login",
+    "/auth/refresh",
+    "/users",
+    "/users/:id/restore",
+    "/users/activate",
+    "/users/email/confirm",
+    "/users/me",
+    "/users/me/email",
+    "/users/me/password",
+];
+
+fn tracked_dependency(breaker: &CircuitBreaker, routes: Vec<&str>) -> DependencyStatus {
+    let state = match breaker.current_state() {
+        BreakerState::Closed => DependencyState::Closed,
+        BreakerState::Open => DependencyState::Open,
+        BreakerState::HalfOpen => DependencyState::HalfOpen,
+    };
+    let last_failure = breaker.last_failure();
+    let degraded_routes = if state == DependencyState::Closed {
+        Vec::new()
+    } else {
+        routes.into_iter().map(String::from).collect()
+    };
+
+    DependencyStatus {
+        name: breaker.dependency().to_string(),
+        state,
+        last_error: last_failure.as_ref().map(|failure| failure.error.clone()),
+        last_failed_at: last_failure.map(|failure| failure.at),
+        degraded_routes,
+    }
+}
+
+fn untracked_dependency(name: &str) -> DependencyStatus {
+    DependencyStatus {
+        name: name.to_string(),
+        state: DependencyState::Untracked,
+        last_error: None,
+        last_failed_at: None,
+        degraded_routes: Vec::new(),
+    }
+}
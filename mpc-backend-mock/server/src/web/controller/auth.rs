@@ -0,0 +1,221 @@
+use axum::extract::State;
+use axum_extra::extract::{
+    cookie::{Cookie, SameSite},
+    CookieJar,
+};
+use mpc_backend_mock_core::config::SessionConfig;
+use time::OffsetDateTime;
+use zeus_axum::response::{EncapsulatedJson, ErrorCode};
+
+use crate::{
+    entity::{AuditAction, LoginRequest, RefreshTokenRequest, TokenResponse},
+    service::{error::Error as ServiceError, NewSession},
+    web::{
+        business_metrics,
+        controller::{Error, Result},
+        extractor::{Audit, ValidatedJson},
+    },
+    ServiceState,
+};
+
+/// Log in with email and password
+///
+/// This endpoint exchanges the user's credentials for an access token and a
+/// refresh token using Keycloak's token endpoint. Successful and rejected
+/// attempts are recorded in the audit log.
+///
+/// With `session` set, it also creates a session and sets its HttpOnly,
+/// SameSite session cookie, which authenticates the following requests of a
+/// browser in place of the access token. The CSRF token of the session is set
+/// in the `<cookie>_csrf` cookie, mutating requests must echo it in the
+/// `X-CSRF-Token` header.
+///
+/// Logins are throttled per client IP. With the login lockout enabled,
+/// repeated failed logins lock the email and disable its Keycloak account
+/// until the lockout ends or an admin unlocks it.
+#[utoipa::path(
+    post,
+    operation_id = "login",
+    path = "/api/v1/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in successfully", body = TokenResponse),
+        (status = 400, description = "A session is requested but sessions are not enabled"),
+        (status = 401, description = "Invalid email or password"),
+        (status = 422, description = "Request body failed validation"),
+        (status = 423, description = "Account is locked after too many failed logins"),
+        (status = 429, description = "Too many logins from the client IP")
+    ),
+    tag = "Auth"
+)]
+pub async fn login(
+    State(state): State<ServiceState>,
+    Audit(audit): Audit,
+    jar: CookieJar,
+    ValidatedJson(request): ValidatedJson<LoginRequest>,
+) -> Result<(CookieJar, EncapsulatedJson<TokenResponse>)> {
+    if request.session && !state.session_service.is_enabled() {
+        return Err(ServiceError::SessionsDisabled.into());
+    }
+
+    if let Some(ip) = audit.ip_address {
+        if let Some(retry_after) = state.rate_limiter.acquire_login(ip).await {
+            tracing::debug!("Rate limiting logins from {ip}");
+            return Err(Error::RateLimited { retry_after });
+        }
+    }
+
+    let email = request.email.as_str();
+    let result = match state.login_lockout_service.check(email).await {
+        Ok(()) => state.auth_service.login(email, &request.password).await,
+        Err(err) => Err(err),
+    };
+
+    let action = match &result {
+        Ok(_) => Some(AuditAction::Login),
+        Err(
+            err @ (ServiceError::InvalidCredentials { .. } | ServiceError::AccountLocked { .. }),
+        ) => {
+            business_metrics::record_auth_failure(&state.metrics, err.error_code());
+            Some(AuditAction::LoginFailed)
+        }
+        // failures of Keycloak say nothing about the credentials
+        Err(_) => None,
+    };
+    if let Some(action) = action {
+        let user_id = state
+            .user_management_service
+            .get_user_by_email(request.email.to_string())
+            .await
+            .ok()
+            .map(|user| user.id);
+        let audit = match (action, user_id) {
+            (AuditAction::Login, Some(user_id)) => audit.with_actor(user_id),
+            _ => audit,
+        };
+        state
+            .audit_service
+            .record(&audit, action, user_id.as_ref(), serde_json::json!({ "email": request.email }))
+            .await;
+
+        match &result {
+            Ok(_) => state.login_lockout_service.record_success(email).await?,
+            Err(ServiceError::InvalidCredentials { .. }) => {
+                let ip_address = audit.ip_address.map(|ip| ip.to_string());
+                let locked = state
+                    .login_lockout_service
+                    .record_failure(email, ip_address.as_deref())
+                    .await?;
+                if let Some(attempt) = locked {
+                    let payload = serde_json::json!({
+                        "email": request.email,
+                        "failed_attempts": attempt.failed_attempts,
+                        "locked_until": attempt.locked_until,
+                    });
+                    state
+                        .audit_service
+                        .record(&audit, AuditAction::AccountLocked, user_id.as_ref(), payload)
+                        .await;
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    let tokens = result?;
+    let jar = if request.session {
+        let user =
+            state.user_management_service.get_user_by_email(request.email.to_string()).await?;
+        let session = state.session_service.create(&user.id).await?;
+        set_session_cookies(jar, state.session_service.config(), session)
+    } else {
+        jar
+    };
+
+    Ok((jar, EncapsulatedJson::ok(tokens)))
+}
+
+/// Log out of the session of the session cookie
+///
+/// This endpoint ends the session and removes its cookies. A request
+/// authenticated by a bearer token has no session to end, the token stays
+/// valid until it expires.
+#[utoipa::path(
+    post,
+    operation_id = "logout",
+    path = "/api/v1/auth/logout",
+    responses(
+        (status = 200, description = "Logged out successfully"),
+        (status = 401, description = "Unauthorized - missing or invalid token or session"),
+        (status = 403, description = "Missing or invalid CSRF token")
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("session_cookie" = [])
+    ),
+    tag = "Auth"
+)]
+pub async fn logout(
+    State(state): State<ServiceState>,
+    jar: CookieJar,
+) -> Result<(CookieJar, EncapsulatedJson<()>)> {
+    let config = state.session_service.config();
+    let Some(token) = jar.get(&config.cookie_name).map(|cookie| cookie.value().to_string()) else {
+        return Ok((jar, EncapsulatedJson::ok(())));
+    };
+
+    let _deleted = state.session_service.delete(&token).await?;
+    let jar = jar
+        .remove(Cookie::build(config.cookie_name.clone()).path("/"))
+        .remove(Cookie::build(config.csrf_cookie_name()).path("/"));
+
+    Ok((jar, EncapsulatedJson::ok(())))
+}
+
+/// Refresh an access token
+///
+/// This endpoint exchanges a refresh token for a new access token and refresh
+/// token using Keycloak's token endpoint.
+#[utoipa::path(
+    post,
+    operation_id = "refresh_token",
+    path = "/api/v1/auth/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "Token refreshed successfully", body = TokenResponse),
+        (status = 401, description = "Invalid, expired or revoked refresh token"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    tag = "Auth"
+)]
+pub async fn refresh_token(
+    State(state): State<ServiceState>,
+    ValidatedJson(request): ValidatedJson<RefreshTokenRequest>,
+) -> Result<EncapsulatedJson<TokenResponse>> {
+    let tokens = state.auth_service.refresh(&request.refresh_token).await?;
+
+    Ok(EncapsulatedJson::ok(tokens))
+}
+
+/// Set the session cookie and the CSRF cookie of `session`, both expiring with
+/// the session
+fn set_session_cookies(jar: CookieJar, config: &SessionConfig, session: NewSession) -> CookieJar {
+    let NewSession { token, csrf_token, expires_at } = session;
+    let expires_at = OffsetDateTime::from_unix_timestamp(expires_at.timestamp())
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+
+    let session_cookie = Cookie::build((config.cookie_name.clone(), token))
+        .path("/")
+        .http_only(true)
+        .secure(config.secure)
+        .same_site(SameSite::Strict)
+        .expires(expires_at);
+    // read by the scripts of the page to fill in the CSRF header
+    let csrf_cookie = Cookie::build((config.csrf_cookie_name(), csrf_token))
+        .path("/")
+        .secure(config.secure)
+        .same_site(SameSite::Strict)
+        .expires(expires_at);
+
+    jar.add(session_cookie).add(csrf_cookie)
+}
@@ -0,0 +1,122 @@
+use axum::extract::State;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{BitcoinBalance, BitcoinFeeEstimates, BitcoinMempool, BitcoinUtxoSet},
+    web::{controller::Result, extractor::AuthUser as AuthUserExtractor},
+    ServiceState,
+};
+
+/// Get the Bitcoin balance of the current user
+///
+/// This endpoint sums the UTXOs of the user's Bitcoin wallets. An output is
+/// counted as confirmed once it has `block_number_to_confirm` confirmations,
+/// everything else, including mempool outputs, is unconfirmed.
+#[utoipa::path(
+    get,
+    operation_id = "get_bitcoin_balance",
+    path = "/api/v1/bitcoin/balance",
+    responses(
+        (status = 200, description = "Balance retrieved successfully", body = BitcoinBalance),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 503, description = "Bitcoin indexer endpoint is not configured")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Bitcoin"
+)]
+pub async fn get_balance(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<EncapsulatedJson<BitcoinBalance>> {
+    let balance = state
+        .bitcoin_service
+        .get_balance(&auth_user.keycloak_user_id, auth_user.realm.as_deref())
+        .await?;
+
+    Ok(EncapsulatedJson::ok(balance))
+}
+
+/// List the Bitcoin UTXOs of the current user
+///
+/// This endpoint returns the unspent outputs of the user's Bitcoin wallets
+/// with their number of confirmations.
+#[utoipa::path(
+    get,
+    operation_id = "list_bitcoin_utxos",
+    path = "/api/v1/bitcoin/utxos",
+    responses(
+        (status = 200, description = "UTXOs retrieved successfully", body = BitcoinUtxoSet),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 503, description = "Bitcoin indexer endpoint is not configured")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Bitcoin"
+)]
+pub async fn list_utxos(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<EncapsulatedJson<BitcoinUtxoSet>> {
+    let utxos = state
+        .bitcoin_service
+        .list_utxos(&auth_user.keycloak_user_id, auth_user.realm.as_deref())
+        .await?;
+
+    Ok(EncapsulatedJson::ok(utxos))
+}
+
+/// Get Bitcoin fee estimates
+///
+/// This endpoint returns the fee rates the Bitcoin node estimates for a
+/// transaction to confirm within 1, 3, 6, 12, 24 and 144 blocks. Estimates
+/// are cached for `bitcoin.fee_cache_ttl_seconds`.
+#[utoipa::path(
+    get,
+    operation_id = "get_bitcoin_fee_estimates",
+    path = "/api/v1/bitcoin/fees",
+    responses(
+        (status = 200, description = "Fee estimates retrieved successfully", body = BitcoinFeeEstimates),
+        (status = 401, description = "Unauthorized - missing or invalid token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Bitcoin"
+)]
+pub async fn get_fee_estimates(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<BitcoinFeeEstimates>> {
+    let estimates = state.bitcoin_service.get_fee_estimates().await?;
+
+    Ok(EncapsulatedJson::ok(estimates))
+}
+
+/// Get the Bitcoin mempool
+///
+/// This endpoint returns the size and the minimum fee rates of the mempool of
+/// the Bitcoin node. It is cached for `bitcoin.fee_cache_ttl_seconds`.
+#[utoipa::path(
+    get,
+    operation_id = "get_bitcoin_mempool",
+    path = "/api/v1/bitcoin/mempool",
+    responses(
+        (status = 200, description = "Mempool retrieved successfully", body = BitcoinMempool),
+        (status = 401, description = "Unauthorized - missing or invalid token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Bitcoin"
+)]
+pub async fn get_mempool(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<BitcoinMempool>> {
+    let mempool = state.bitcoin_service.get_mempool().await?;
+
+    Ok(EncapsulatedJson::ok(mempool))
+}
@@ -1,12 +1,17 @@
+use std::{collections::BTreeMap, time::Duration};
+
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
 };
 use indexmap::IndexMap;
 use snafu::Snafu;
-use zeus_axum::{json_response, response, response::EncapsulatedJsonError};
+use zeus_axum::{
+    json_response, response,
+    response::{EncapsulatedJsonError, ErrorCode},
+};
 
-use crate::service::error::Error as ServiceError;
+use crate::service::error::{retry_hint, Error as ServiceError};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -50,8 +55,38 @@ pub enum Error {
     #[snafu(display("User already exists: {email}"))]
     UserAlreadyExists { email: String },
 
+    #[snafu(display("Current password is incorrect"))]
+    InvalidCurrentPassword,
+
+    #[snafu(display("New password is too weak: {reason}"))]
+    WeakPassword { reason: &'static str },
+
+    #[snafu(display("Passwords of the users of realm `{realm}` are changed in that realm"))]
+    PasswordManagedByRealm { realm: String },
+
     #[snafu(display("Invalid date format: '{}'. Expected YYYY-MM-DD", date_str))]
     InvalidDateFormat { date_str: String },
+
+    #[snafu(display("Admin access denied for client IP {ip:?}"))]
+    AdminAccessDenied { ip: Option<std::net::IpAddr> },
+
+    #[snafu(display("Rate limit exceeded, retry after {retry_after:?}"))]
+    RateLimited { retry_after: Duration },
+
+    #[snafu(display("Invalid request body: {message}"))]
+    InvalidRequestBody { message: String },
+
+    #[snafu(display("Request body failed validation: {source}"))]
+    ValidationFailed { source: validator::ValidationErrors },
+
+    #[snafu(display("Configuration reload is not available on this server"))]
+    ConfigReloadUnavailable,
+
+    #[snafu(display("{source}"))]
+    ReloadConfig { source: crate::reload::Error },
+
+    #[snafu(display("Injected fault with status {status}"))]
+    InjectedFault { status: StatusCode },
 }
 
 impl From<ServiceError> for Error {
@@ -62,6 +97,36 @@ impl From<mpc_backend_mock_core::error::Error> for Error {
     fn from(source: mpc_backend_mock_core::error::Error) -> Self { Self::Model { source } }
 }
 
+impl ErrorCode for Error {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Service { source } => source.error_code(),
+            Self::Model { source } => source.error_code(),
+            Self::NotInAllowlist { .. } => "NOT_IN_ALLOWLIST",
+            Self::IPClaimLimitExceeded => "IP_CLAIM_LIMIT_EXCEEDED",
+            Self::BitcoinAddressClaimLimitExceeded => "BITCOIN_ADDRESS_CLAIM_LIMIT_EXCEEDED",
+            Self::InvalidBitcoinAddress { .. } => "INVALID_BITCOIN_ADDRESS",
+            Self::InvalidSolanaAddress { .. } => "INVALID_SOLANA_ADDRESS",
+            Self::InvalidLastReceivedTime { .. } => "INVALID_LAST_RECEIVED_TIME",
+            Self::UserCreationFailed { .. } => "USER_CREATION_FAILED",
+            Self::SignInFailed { .. } => "SIGN_IN_FAILED",
+            Self::UserNotFound { .. } => "USER_NOT_FOUND",
+            Self::UserAlreadyExists { .. } => "USER_ALREADY_EXISTS",
+            Self::InvalidCurrentPassword => "INVALID_CURRENT_PASSWORD",
+            Self::WeakPassword { .. } => "WEAK_PASSWORD",
+            Self::PasswordManagedByRealm { .. } => "PASSWORD_MANAGED_BY_REALM",
+            Self::InvalidDateFormat { .. } => "INVALID_DATE_FORMAT",
+            Self::AdminAccessDenied { .. } => "ADMIN_ACCESS_DENIED",
+            Self::RateLimited { .. } => "RATE_LIMITED",
+            Self::InvalidRequestBody { .. } => "INVALID_REQUEST_BODY",
+            Self::ValidationFailed { .. } => "VALIDATION_FAILED",
+            Self::ConfigReloadUnavailable => "CONFIG_RELOAD_UNAVAILABLE",
+            Self::ReloadConfig { .. } => "CONFIG_RELOAD_FAILED",
+            Self::InjectedFault { .. } => "INJECTED_FAULT",
+        }
+    }
+}
+
 impl IntoResponse for Error {
     // SAFETY: allow: high cognitive complexity caused by `tracing` macro
     #[allow(clippy::cognitive_complexity)]
@@ -74,6 +139,7 @@ impl IntoResponse for Error {
                 status: StatusCode::UNAUTHORIZED,
                 error: response::Error {
                     type_: response::ErrorType::Unauthorized,
+                    code: self.error_code().to_string(),
                     message: "Authorization failed".to_string(),
                     additional_fields: IndexMap::default(),
                 }
@@ -83,15 +149,78 @@ impl IntoResponse for Error {
                 status: StatusCode::UNAUTHORIZED,
                 error: response::Error {
                     type_: response::ErrorType::Unauthorized,
+                    code: self.error_code().to_string(),
+                    message: self.to_string(),
+                    additional_fields: IndexMap::default(),
+                }
+            },
+            Self::AdminAccessDenied { .. } => json_response! {
+                reason: self,
+                status: StatusCode::FORBIDDEN,
+                error: response::Error {
+                    type_: response::ErrorType::Unauthorized,
+                    code: self.error_code().to_string(),
+                    message: "Admin access denied".to_string(),
+                    additional_fields: IndexMap::default(),
+                }
+            },
+            Self::PasswordManagedByRealm { .. } => json_response! {
+                reason: self,
+                status: StatusCode::FORBIDDEN,
+                error: response::Error {
+                    type_: response::ErrorType::Unauthorized,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
                     additional_fields: IndexMap::default(),
                 }
             },
+            Self::RateLimited { retry_after } => {
+                // whole seconds, rounded up so that retrying on time succeeds
+                let retry_after_seconds =
+                    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                let mut response = json_response! {
+                    reason: self,
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    error: response::Error {
+                        type_: response::ErrorType::TooManyRequests,
+                        code: self.error_code().to_string(),
+                        message: "Too many requests".to_string(),
+                        additional_fields: retry_hint(true, Some(retry_after)),
+                    }
+                };
+                drop(
+                    response
+                        .headers_mut()
+                        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds)),
+                );
+                response
+            }
+            // injected faults are as retryable as the failures they simulate
+            Self::InjectedFault { status } => json_response! {
+                reason: self,
+                status: status,
+                error: response::Error {
+                    type_: response::ErrorType::Internal,
+                    code: self.error_code().to_string(),
+                    message: self.to_string(),
+                    additional_fields: retry_hint(
+                        matches!(
+                            status,
+                            StatusCode::TOO_MANY_REQUESTS
+                                | StatusCode::BAD_GATEWAY
+                                | StatusCode::SERVICE_UNAVAILABLE
+                                | StatusCode::GATEWAY_TIMEOUT
+                        ),
+                        None,
+                    ),
+                }
+            },
             Self::UserNotFound { .. } => json_response! {
                 reason: self,
                 status: StatusCode::NOT_FOUND,
                 error: response::Error {
                     type_: response::ErrorType::NotFound,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
                     additional_fields: IndexMap::default(),
                 }
@@ -101,18 +230,36 @@ impl IntoResponse for Error {
                 status: StatusCode::CONFLICT,
                 error: response::Error {
                     type_: response::ErrorType::Conflict,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
                     additional_fields: IndexMap::default(),
                 }
             },
+            Self::ValidationFailed { ref source } => {
+                let fields = field_violations(source);
+                json_response! {
+                    reason: self,
+                    status: StatusCode::UNPROCESSABLE_ENTITY,
+                    error: response::Error {
+                        type_: response::ErrorType::UnprocessableEntity,
+                        code: self.error_code().to_string(),
+                        message: "Request body failed validation".to_string(),
+                        additional_fields: IndexMap::from([("fields".to_string(), fields)]),
+                    }
+                }
+            }
             Self::InvalidBitcoinAddress { .. }
             | Self::InvalidSolanaAddress { .. }
-            | Self::InvalidDateFormat { .. } => {
+            | Self::InvalidDateFormat { .. }
+            | Self::InvalidCurrentPassword
+            | Self::WeakPassword { .. }
+            | Self::InvalidRequestBody { .. } => {
                 json_response! {
                     reason: self,
                     status: StatusCode::BAD_REQUEST,
                     error: response::Error {
                         type_: response::ErrorType::BadRequest,
+                        code: self.error_code().to_string(),
                         message: self.to_string(),
                         additional_fields: IndexMap::default(),
                     }
@@ -123,6 +270,7 @@ impl IntoResponse for Error {
                 status: StatusCode::INTERNAL_SERVER_ERROR,
                 error: response::Error {
                     type_: response::ErrorType::Internal,
+                    code: self.error_code().to_string(),
                     message: self.to_string(),
                     additional_fields: IndexMap::default(),
                 }
@@ -130,3 +278,21 @@ impl IntoResponse for Error {
         }
     }
 }
+
+/// Violations of a request body by field, e.g.
+/// `{"new_password": [{"code": "length", "message": "..."}]}`
+fn field_violations(errors: &validator::ValidationErrors) -> serde_json::Value {
+    let fields = errors
+        .field_errors()
+        .into_iter()
+        .map(|(field, errors)| {
+            let violations = errors
+                .iter()
+                .map(|error| serde_json::json!({ "code": error.code, "message": error.message }))
+                .collect::<Vec<_>>();
+            (field.to_string(), violations)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    serde_json::json!(fields)
+}
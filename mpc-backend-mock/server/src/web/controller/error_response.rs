@@ -0,0 +1,154 @@
+//! Error bodies of the documented 4xx and 5xx responses.
+//!
+//! The paths only describe their error responses, [`ErrorResponseAddon`]
+//! gives each of them the [`ErrorResponse`] schema and an example carrying the
+//! code the server answers with, so generated clients get typed errors.
+
+use axum::http::StatusCode;
+use utoipa::openapi::{path::Operation, ContentBuilder, OpenApi, Ref, RefOr};
+
+use crate::entity::{ErrorDetail, ErrorResponse, ErrorType};
+
+/// Code of the error behind each documented error response, by description
+const ERROR_CODES: &[(&str, &str)] = &[
+    ("A session is requested but sessions are not enabled", "SESSIONS_DISABLED"),
+    ("Account does not exist", "SOLANA_ACCOUNT_NOT_FOUND"),
+    ("Account is locked after too many failed logins", "ACCOUNT_LOCKED"),
+    ("Activation token is invalid, expired or already used", "INVALID_ACTIVATION_TOKEN"),
+    ("Annotation is empty or too long", "INVALID_ANNOTATION"),
+    ("Annotation not found", "ANNOTATION_NOT_FOUND"),
+    ("Bitcoin indexer endpoint is not configured", "BITCOIN_INDEXER_NOT_CONFIGURED"),
+    ("Body is not an OpenAPI document", "INVALID_OPENAPI_DOCUMENT"),
+    ("Caller lacks the admin role", "INSUFFICIENT_PERMISSIONS"),
+    ("Client IP is not allowed to access admin routes", "ADMIN_ACCESS_DENIED"),
+    ("Configuration could not be loaded, nothing was applied", "CONFIG_RELOAD_FAILED"),
+    ("Current password is incorrect or new password is too weak", "INVALID_CURRENT_PASSWORD"),
+    ("Email change token is invalid, expired or already used", "INVALID_EMAIL_CHANGE_TOKEN"),
+    ("Invalid Solana public key", "INVALID_SOLANA_ADDRESS"),
+    ("Invalid date or date range", "INVALID_DATE_RANGE"),
+    ("Invalid email change (e.g., current email or user of another realm)", "INVALID_EMAIL_CHANGE"),
+    ("Invalid email or password", "INVALID_CREDENTIALS"),
+    ("Invalid format", "INVALID_DATE_FORMAT"),
+    ("Invalid preferences", "INVALID_USER_PREFERENCES"),
+    ("Invalid profile update", "INVALID_USER_PROFILE"),
+    ("Invalid query parameters", "INVALID_PAGINATION"),
+    (
+        "Invalid request (e.g., invalid base64 or unsigned transaction)",
+        "INVALID_TRANSACTION_ENCODING",
+    ),
+    ("Invalid request (e.g., invalid email format, locale or realm)", "INVALID_EMAIL"),
+    ("Invalid request (e.g., invalid email format)", "INVALID_EMAIL"),
+    ("Invalid request (e.g., pattern without `@` or unknown realm)", "INVALID_EMAIL_PATTERN"),
+    ("Invalid request body", "INVALID_REQUEST_BODY"),
+    ("Invalid signing session (e.g., payload is not hex encoded)", "INVALID_SIGNING_SESSION"),
+    ("Invalid webhook", "INVALID_WEBHOOK"),
+    ("Invalid, expired or revoked refresh token", "INVALID_REFRESH_TOKEN"),
+    ("Key not found", "KEY_NOT_FOUND"),
+    ("Missing or invalid CSRF token", "INVALID_CSRF_TOKEN"),
+    ("No baseline was uploaded", "OPENAPI_BASELINE_NOT_FOUND"),
+    ("No failed logins of the email", "LOGIN_ATTEMPT_NOT_FOUND"),
+    ("Request body failed validation", "VALIDATION_FAILED"),
+    ("Signing session not found", "SIGNING_SESSION_NOT_FOUND"),
+    ("Simulated key generation failure", "KEY_GENERATION_FAILED"),
+    ("Too many logins from the client IP", "RATE_LIMITED"),
+    ("Transaction not found", "TRANSACTION_NOT_FOUND"),
+    ("Transaction was already submitted", "TRANSACTION_ALREADY_SUBMITTED"),
+    ("Unauthorized - missing or invalid token", "INVALID_TOKEN"),
+    ("Unauthorized - missing or invalid token or session", "INVALID_TOKEN"),
+    ("User already exists (in database or Keycloak)", "USER_ALREADY_EXISTS"),
+    ("User belongs to another realm than the primary one", "IMPERSONATION_UNSUPPORTED"),
+    ("User belongs to another realm than the primary one", "PASSWORD_MANAGED_BY_REALM"),
+    ("User is not deleted", "USER_NOT_DELETED"),
+    ("User not found", "USER_NOT_FOUND"),
+    ("User not found in database", "USER_NOT_FOUND"),
+    ("Wallet not found", "WALLET_NOT_FOUND"),
+    ("Webhook not found", "WEBHOOK_NOT_FOUND"),
+];
+
+/// Document the body of every 4xx and 5xx response as [`ErrorResponse`]
+pub struct ErrorResponseAddon;
+
+impl utoipa::Modify for ErrorResponseAddon {
+    fn modify(&self, openapi: &mut OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            let operations =
+                [&mut item.get, &mut item.put, &mut item.post, &mut item.delete, &mut item.patch];
+            for operation in operations.into_iter().flatten() {
+                document_errors(operation);
+            }
+        }
+    }
+}
+
+fn document_errors(operation: &mut Operation) {
+    for (status, response) in &mut operation.responses.responses {
+        let Some(status) = status.parse().ok().and_then(|s| StatusCode::from_u16(s).ok()) else {
+            continue;
+        };
+        let RefOr::T(response) = response else {
+            continue;
+        };
+        if !(status.is_client_error() || status.is_server_error()) || !response.content.is_empty() {
+            continue;
+        }
+
+        let example = example(status, &response.description)
+            .map(|example| serde_json::to_value(example).expect("is serializable; qed"));
+        let content = ContentBuilder::new()
+            .schema(Some(Ref::from_schema_name("ErrorResponse")))
+            .example(example)
+            .build();
+        let _previous = response.content.insert("application/json".to_string(), content);
+    }
+}
+
+/// Body of the error response described by `description`, `None` when its code
+/// is not known
+fn example(status: StatusCode, description: &str) -> Option<ErrorResponse> {
+    let code = ERROR_CODES.iter().find(|(known, _)| *known == description).map(|(_, code)| code)?;
+
+    Some(ErrorResponse {
+        status: status.as_u16(),
+        error: ErrorDetail {
+            type_: ErrorType::of_status(status),
+            code: (*code).to_string(),
+            message: description.to_string(),
+            request_id: None,
+            domain_code: None,
+            retryable: None,
+            retry_after_ms: None,
+            fields: None,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi as _;
+
+    use super::*;
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_every_error_response_has_an_example() {
+        let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for (path, item) in document["paths"].as_object().unwrap() {
+            for method in ["get", "put", "post", "delete", "patch"] {
+                let Some(operation) = item.get(method) else {
+                    continue;
+                };
+                for (status, response) in operation["responses"].as_object().unwrap() {
+                    if !status.starts_with(['4', '5']) {
+                        continue;
+                    }
+                    let content = &response["content"]["application/json"];
+                    assert_eq!(
+                        content["schema"]["$ref"], "#/components/schemas/ErrorResponse",
+                        "{method} {path} {status}"
+                    );
+                    assert!(content["example"].is_object(), "{method} {path} {status}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::{
+        sse::{self, KeepAlive, Sse},
+        Response,
+    },
+};
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_stream::wrappers::WatchStream;
+use uuid::Uuid;
+
+use crate::{
+    entity::{Event, ServerSnapshot},
+    web::{controller::Result, extractor::AuthUser as AuthUserExtractor},
+    ServiceState,
+};
+
+/// Stream server snapshots as Server-Sent Events
+///
+/// The latest snapshot is sent right after connecting and then every time the
+/// server takes a new one, every few seconds. Each is an SSE event of type
+/// `server_snapshot` with the JSON encoded `ServerSnapshot` as data. A
+/// snapshot replaced before it was sent to a slow client is skipped.
+#[utoipa::path(
+    get,
+    operation_id = "stream_server_snapshots",
+    path = "/api/v1/events",
+    responses(
+        (status = 200, description = "Stream of `server_snapshot` events", content_type = "text/event-stream", body = ServerSnapshot)
+    ),
+    tag = "Events"
+)]
+pub async fn stream_server_snapshots(
+    State(state): State<ServiceState>,
+) -> Sse<impl Stream<Item = std::result::Result<sse::Event, Infallible>>> {
+    let snapshots = WatchStream::new(state.event_bus.subscribe_snapshots())
+        .filter_map(|snapshot| async move { snapshot })
+        .filter_map(|snapshot| async move {
+            sse::Event::default()
+                .event("server_snapshot")
+                .json_data(&snapshot)
+                .inspect_err(|err| {
+                    tracing::warn!("Failed to serialize server snapshot, error: {err}")
+                })
+                .ok()
+        })
+        .map(Ok);
+
+    Sse::new(snapshots).keep_alive(KeepAlive::default())
+}
+
+/// Subscribe to events over a WebSocket
+///
+/// After the handshake, every event visible to the current user is sent as a
+/// JSON text message, see the `Event` schema. Messages sent by the client are
+/// ignored. Browsers, which cannot set the Authorization header on the
+/// handshake, may pass the access token in the `access_token` query parameter.
+#[utoipa::path(
+    get,
+    operation_id = "subscribe_events",
+    path = "/api/v1/ws",
+    params(
+        ("access_token" = Option<String>, Query, description = "Access token, if the Authorization header is not set")
+    ),
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol, events are sent as JSON", body = Event),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Events"
+)]
+pub async fn subscribe_events(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response> {
+    let user = state
+        .user_management_service
+        .get_user_by_keycloak_id(&auth_user.keycloak_user_id, auth_user.realm.as_deref())
+        .await?;
+
+    // subscribe before the handshake completes, so no event is missed in between
+    let events = state.event_bus.subscribe();
+
+    Ok(upgrade.on_upgrade(move |socket| forward_events(socket, events, user.id)))
+}
+
+/// Send the events visible to the user until either side closes the connection
+async fn forward_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<Event>,
+    user_id: Uuid,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.is_visible_to(&user_id) => {
+                    let message = match serde_json::to_string(&event) {
+                        Ok(message) => message,
+                        Err(err) => {
+                            tracing::warn!("Failed to serialize event, error: {err}");
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Skipped {skipped} events for the WebSocket of user {user_id}");
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                // pings are answered by axum
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
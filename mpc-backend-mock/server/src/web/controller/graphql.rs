@@ -0,0 +1,198 @@
+//! GraphQL API over the user and transaction models, served at `/api/graphql`
+//! when enabled with [`ServiceState::with_graphql`].
+//!
+//! Requests are authenticated like the REST API: a request without a bearer
+//! token or session cookie is anonymous and may only run the operations which
+//! are public in REST, `createUser` and `activateUser`, while an invalid token
+//! is answered with 401 before any operation runs. Errors carry the code of
+//! the REST error in `extensions.code`.
+
+use async_graphql::{
+    http::GraphiQLSource, Context, EmptySubscription, ErrorExtensions, Object, Schema,
+};
+use axum::{
+    extract::State,
+    middleware,
+    response::{Html, IntoResponse},
+    routing, Extension, Json, Router,
+};
+use uuid::Uuid;
+use zeus_axum::response::ErrorCode;
+
+use crate::{
+    entity::{AuditAction, Transaction, User},
+    service::{error::Error as ServiceError, AuditContext},
+    web::{
+        business_metrics,
+        extractor::Audit,
+        middleware::{optional_jwt_auth_middleware, user_rate_limit_middleware, AuthUser},
+    },
+    ServiceState,
+};
+
+/// Path of the GraphQL API, outside the versioned REST API
+const GRAPHQL_PATH: &str = "/api/graphql";
+
+/// Deep enough for any query of the schema, which has no nested objects
+const MAX_QUERY_DEPTH: usize = 8;
+
+pub type ApiSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// Routes of the GraphQL API: operations are posted to [`GRAPHQL_PATH`], which
+/// serves GraphiQL to browsers
+pub fn router(service_state: &ServiceState) -> Router<ServiceState> {
+    let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish();
+
+    Router::new()
+        .route(
+            GRAPHQL_PATH,
+            routing::post(execute)
+                .route_layer(middleware::from_fn_with_state(
+                    service_state.clone(),
+                    user_rate_limit_middleware,
+                ))
+                .route_layer(middleware::from_fn_with_state(
+                    service_state.clone(),
+                    optional_jwt_auth_middleware,
+                ))
+                .get(graphiql),
+        )
+        .layer(Extension(schema))
+}
+
+async fn execute(
+    State(state): State<ServiceState>,
+    Extension(schema): Extension<ApiSchema>,
+    auth_user: Option<Extension<AuthUser>>,
+    Audit(audit): Audit,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let mut request = request.data(state).data(audit);
+    if let Some(Extension(auth_user)) = auth_user {
+        request = request.data(auth_user);
+    }
+
+    Json(schema.execute(request).await)
+}
+
+// SAFETY: `axum` handler must be async
+#[allow(clippy::unused_async)]
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).title("MPC Backend Mock").finish())
+}
+
+/// GraphQL error of a service error, with the code of the REST error
+fn graphql_error(err: &ServiceError) -> async_graphql::Error {
+    let code = err.error_code();
+    async_graphql::Error::new(err.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", code);
+    })
+}
+
+/// User authenticated by the request, the operation fails without one
+fn auth_user<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a AuthUser> {
+    ctx.data_opt::<AuthUser>().ok_or_else(|| {
+        async_graphql::Error::new("Missing authentication").extend_with(|_, extensions| {
+            extensions.set("code", "MISSING_TOKEN");
+        })
+    })
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Current user
+    async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let state = ctx.data_unchecked::<ServiceState>();
+        let auth_user = auth_user(ctx)?;
+
+        state
+            .user_management_service
+            .get_user_by_keycloak_id(&auth_user.keycloak_user_id, auth_user.realm.as_deref())
+            .await
+            .map_err(|err| graphql_error(&err))
+    }
+
+    /// Transaction submitted by the current user, its status is refreshed from
+    /// the Solana RPC endpoint until it is finalized or failed
+    async fn transaction(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Transaction> {
+        let state = ctx.data_unchecked::<ServiceState>();
+        let auth_user = auth_user(ctx)?;
+
+        state
+            .transaction_service
+            .get_transaction(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), &id)
+            .await
+            .map_err(|err| graphql_error(&err))
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Create a user in Keycloak and the database, an activation email is sent
+    /// to the user
+    async fn create_user(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(max_length = 254))] email: String,
+        #[graphql(validator(max_length = 35))] locale: Option<String>,
+    ) -> async_graphql::Result<User> {
+        let state = ctx.data_unchecked::<ServiceState>();
+
+        let user = state
+            .user_management_service
+            .create_user(&email, None, locale.as_deref())
+            .await
+            .map_err(|err| graphql_error(&err))?;
+        business_metrics::record_user_created(&state.metrics);
+
+        state
+            .audit_service
+            .record(
+                ctx.data_unchecked::<AuditContext>(),
+                AuditAction::UserCreated,
+                Some(&user.id),
+                serde_json::json!({ "email": user.email }),
+            )
+            .await;
+
+        Ok(user)
+    }
+
+    /// Activate a user with the single-use token from the activation email
+    async fn activate_user(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(min_length = 1, max_length = 128))] token: String,
+    ) -> async_graphql::Result<User> {
+        let state = ctx.data_unchecked::<ServiceState>();
+
+        state.user_management_service.activate_user(&token).await.map_err(|err| graphql_error(&err))
+    }
+
+    /// Submit a signed Solana transaction of the current user, base64 encoded
+    /// in wire format
+    async fn submit_transaction(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(min_length = 1))] transaction: String,
+    ) -> async_graphql::Result<Transaction> {
+        let state = ctx.data_unchecked::<ServiceState>();
+        let auth_user = auth_user(ctx)?;
+
+        state
+            .transaction_service
+            .submit_transaction(
+                &auth_user.keycloak_user_id,
+                auth_user.realm.as_deref(),
+                &transaction,
+            )
+            .await
+            .map_err(|err| graphql_error(&err))
+    }
+}
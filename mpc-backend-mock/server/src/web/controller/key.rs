@@ -0,0 +1,79 @@
+use axum::extract::{Path, State};
+use uuid::Uuid;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{CreateKeyRequest, Key},
+    web::{
+        controller::Result,
+        extractor::{AuthUser as AuthUserExtractor, ValidatedJson},
+    },
+    ServiceState,
+};
+
+/// Generate the key of the current user on a chain
+///
+/// This endpoint simulates a distributed key generation and returns a fake
+/// public key, secp256k1 for Bitcoin and ed25519 for Solana. The key is
+/// derived from the user and the chain, a user has one key per chain and
+/// generating it again returns the existing key.
+#[utoipa::path(
+    post,
+    operation_id = "create_key",
+    path = "/api/v1/keys",
+    request_body = CreateKeyRequest,
+    responses(
+        (status = 200, description = "Key generated or already existing", body = Key),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation"),
+        (status = 503, description = "Simulated key generation failure")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Keys"
+)]
+pub async fn create_key(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<CreateKeyRequest>,
+) -> Result<EncapsulatedJson<Key>> {
+    let key = state
+        .key_service
+        .create_key(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), request.chain)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(key))
+}
+
+/// Get a key of the current user
+#[utoipa::path(
+    get,
+    operation_id = "get_key",
+    path = "/api/v1/keys/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID of the key")
+    ),
+    responses(
+        (status = 200, description = "Key retrieved successfully", body = Key),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "Key not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Keys"
+)]
+pub async fn get_key(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(key_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<Key>> {
+    let key = state
+        .key_service
+        .get_key(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), &key_id)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(key))
+}
@@ -0,0 +1,50 @@
+use axum::{extract::State, Extension};
+use mpc_backend_mock_core::ServerInfo;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{ApiChangelog, ChainState},
+    service,
+    web::{controller::Result, ServiceState},
+};
+
+/// Get the API changelog
+///
+/// This endpoint lists the operations added, changed, deprecated or removed
+/// by each release up to the deployed one, newest first, so that clients can
+/// check that the operations they need exist on this deployment.
+#[utoipa::path(
+    get,
+    operation_id = "get_api_changelog",
+    path = "/api/v1/meta/changelog",
+    responses(
+        (status = 200, description = "API changelog", body = ApiChangelog)
+    ),
+    tag = "Meta"
+)]
+pub async fn get_changelog(
+    Extension(server_info): Extension<ServerInfo>,
+) -> Result<EncapsulatedJson<ApiChangelog>> {
+    Ok(EncapsulatedJson::ok(service::api_changelog(&server_info.version)))
+}
+
+/// Get the chain heights known to the server
+///
+/// This endpoint serves the latest Bitcoin block height and Solana slot from
+/// the cache refreshed in the background, without calling the nodes. Each
+/// value carries when it was read and whether it is stale, i.e. was not
+/// refreshed for several refresh intervals.
+#[utoipa::path(
+    get,
+    operation_id = "get_chain_state",
+    path = "/api/v1/meta/chain-state",
+    responses(
+        (status = 200, description = "Cached chain heights", body = ChainState)
+    ),
+    tag = "Meta"
+)]
+pub async fn get_chain_state(
+    State(state): State<ServiceState>,
+) -> Result<EncapsulatedJson<ChainState>> {
+    Ok(EncapsulatedJson::ok(state.chain_state.snapshot()))
+}
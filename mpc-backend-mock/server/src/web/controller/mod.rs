@@ -1,53 +1,162 @@
 // FIXME: remove this after this utoipa issue is fixed: https://github.com/juhaku/utoipa/pull/1423
 #![allow(clippy::needless_for_each)]
+mod admin;
+mod auth;
+mod bitcoin;
 mod error;
+mod error_response;
+mod event;
+mod graphql;
+mod key;
+mod meta;
+mod signing;
+mod solana;
+mod transaction;
 mod user;
+mod version;
+mod wallet;
+mod webhook;
 
 use axum::{middleware, routing, Extension, Router};
 use http::{HeaderName, Method};
 use mpc_backend_mock_core::ServerInfo;
-use tower_http::{
-    cors,
-    cors::{AllowHeaders, CorsLayer},
-};
+use tower_http::cors::{AllowHeaders, CorsLayer};
 use utoipa::OpenApi;
-use zeus_axum::response::EncapsulatedJson;
+use zeus_axum::response::{negotiate_format, EncapsulatedJson};
 
-pub use self::error::{Error, Result};
-use crate::{web::middleware::jwt_auth_middleware, ServiceState};
+use self::error_response::ErrorResponseAddon;
+pub use self::{
+    error::{Error, Result},
+    version::{ApiDocV2, ApiVersion, RouteSet, VersionedRouter, V1, V2},
+};
+use crate::{
+    web::middleware::{
+        auth::CSRF_TOKEN_HEADER, fault_injection_middleware, http_log_middleware,
+        http_metrics_middleware, ip_rate_limit_middleware, localization_middleware,
+    },
+    ServiceState,
+};
 
-pub fn api_v1_router(service_state: &ServiceState) -> Router {
-    // FIXME: might need to be configurable
-    // allow the request from frontend host: *
+pub fn api_router(service_state: &ServiceState) -> Router {
+    // allow the configured frontend origins, any origin when none is configured
+    // and sessions are disabled
     // sample request header
     // "authorization, content-type"
+    let allow_credentials = service_state.session_service.is_enabled();
     let cors_layer = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::DELETE])
-        .allow_origin(cors::Any)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_origin(service_state.cors_origins.allow_origin(allow_credentials))
         .allow_headers(AllowHeaders::list([
             HeaderName::from_static("authorization"),
             HeaderName::from_static("content-type"),
-        ]));
+            HeaderName::from_static(CSRF_TOKEN_HEADER),
+        ]))
+        // browsers only send the session cookie cross-origin with credentials
+        .allow_credentials(allow_credentials)
+        .expose_headers([HeaderName::from_static("x-request-id")]);
 
-    // Public routes (no authentication required)
-    let public_routes = Router::new()
-        .route("/v1/info", routing::get(server_info))
-        .route("/v1/users", routing::post(user::create_user))
-        .route("/v1/users", routing::delete(user::delete_user));
+    let v1_routes = v1_routes();
+    // v2 shares the v1 handlers until a breaking change replaces one of them
+    let v2_routes = v1_routes.clone();
 
-    // Protected routes (authentication required)
-    let protected_routes = Router::new()
-        .route("/v1/users/me", routing::get(user::get_current_user))
-        .layer(middleware::from_fn_with_state(service_state.clone(), jwt_auth_middleware));
+    let mut router = VersionedRouter::new(service_state)
+        .version::<V1>(&v1_routes)
+        .version::<V2>(&v2_routes)
+        .into_router();
+    if service_state.graphql {
+        router = router.merge(graphql::router(service_state));
+    }
 
-    Router::new()
-        .nest("/api", public_routes)
-        .nest("/api", protected_routes)
+    router
+        // responses are MessagePack for clients asking for it in `Accept`
+        .layer(middleware::from_fn(negotiate_format))
+        .layer(middleware::from_fn_with_state(service_state.clone(), fault_injection_middleware))
+        // logs what the clients receive, including the injected faults
+        .layer(middleware::from_fn_with_state(service_state.clone(), http_log_middleware))
+        .layer(middleware::from_fn(localization_middleware))
+        .layer(middleware::from_fn_with_state(service_state.clone(), ip_rate_limit_middleware))
+        .layer(middleware::from_fn_with_state(service_state.clone(), http_metrics_middleware))
         .layer(cors_layer)
         .with_state(service_state.clone())
 }
 
+/// Routes of `/api/v1`
+fn v1_routes() -> RouteSet {
+    RouteSet::new()
+        .public("/info", routing::get(server_info))
+        .public("/events", routing::get(event::stream_server_snapshots))
+        .public("/meta/changelog", routing::get(meta::get_changelog))
+        .public("/meta/chain-state", routing::get(meta::get_chain_state))
+        .public("/auth/login", routing::post(auth::login))
+        .public("/auth/refresh", routing::post(auth::refresh_token))
+        .protected("/auth/logout", routing::post(auth::logout))
+        .public("/users", routing::post(user::create_user).delete(user::delete_user))
+        .public("/users/activate", routing::post(user::activate_user))
+        .public("/users/email/confirm", routing::post(user::confirm_email_change))
+        .public("/users/:id/restore", routing::post(user::restore_user))
+        .protected("/users", routing::get(user::list_users))
+        .protected(
+            "/users/me",
+            routing::get(user::get_current_user).patch(user::update_current_user),
+        )
+        .protected(
+            "/users/me/preferences",
+            routing::get(user::get_current_user_preferences)
+                .put(user::update_current_user_preferences),
+        )
+        .protected("/users/me/password", routing::post(user::change_password))
+        .protected("/users/me/email", routing::post(user::request_email_change))
+        .protected("/bitcoin/balance", routing::get(bitcoin::get_balance))
+        .protected("/bitcoin/utxos", routing::get(bitcoin::list_utxos))
+        .protected("/bitcoin/fees", routing::get(bitcoin::get_fee_estimates))
+        .protected("/bitcoin/mempool", routing::get(bitcoin::get_mempool))
+        .protected("/solana/balance/:pubkey", routing::get(solana::get_balance))
+        .protected("/solana/account/:pubkey", routing::get(solana::get_account))
+        .protected("/transactions", routing::post(transaction::submit_transaction))
+        .protected("/transactions/:id", routing::get(transaction::get_transaction))
+        .protected("/keys", routing::post(key::create_key))
+        .protected("/keys/:id", routing::get(key::get_key))
+        .protected("/signing/sessions", routing::post(signing::create_signing_session))
+        .protected("/signing/sessions/:id", routing::get(signing::get_signing_session))
+        .protected("/wallets/:id/balance-history", routing::get(wallet::get_balance_history))
+        .protected("/webhooks", routing::post(webhook::create_webhook))
+        .protected("/webhooks/:id/deliveries", routing::get(webhook::list_webhook_deliveries))
+        .protected("/ws", routing::get(event::subscribe_events))
+        .admin("/admin/client-ip", routing::get(admin::client_ip))
+        .admin("/admin/tasks", routing::get(admin::list_background_tasks))
+        .admin("/admin/slo", routing::get(admin::get_slo_report))
+        .admin("/admin/dependencies", routing::get(admin::list_dependencies))
+        .admin("/admin/reload", routing::post(admin::reload_config))
+        .admin(
+            "/admin/fault-injection",
+            routing::get(admin::get_fault_injection).put(admin::update_fault_injection),
+        )
+        .admin("/admin/http-log", routing::get(admin::get_http_log))
+        .admin("/admin/audit-logs", routing::get(admin::list_audit_logs))
+        .admin("/admin/notifications", routing::get(admin::list_notifications))
+        .admin("/admin/notifications/captured", routing::get(admin::list_captured_notifications))
+        .admin("/admin/users", routing::delete(admin::delete_users))
+        .admin("/admin/users/export", routing::get(admin::export_users))
+        .admin(
+            "/admin/users/:id/annotations",
+            routing::get(admin::list_user_annotations).post(admin::annotate_user),
+        )
+        .admin(
+            "/admin/transactions/:id/annotations",
+            routing::get(admin::list_transaction_annotations).post(admin::annotate_transaction),
+        )
+        .admin("/admin/annotations/:id", routing::delete(admin::delete_annotation))
+        .admin("/admin/openapi/baselines", routing::post(admin::upload_openapi_baseline))
+        .admin("/admin/openapi/drift", routing::get(admin::get_api_drift))
+        .admin("/admin/login-lockouts", routing::get(admin::list_login_lockouts))
+        .admin("/admin/login-lockouts/:email", routing::delete(admin::unlock_login))
+        .admin_user("/admin/users/:id/impersonate", routing::post(admin::impersonate_user))
+}
+
 /// Get server info
+///
+/// Includes the `capabilities` map, which tells which optional subsystems are
+/// enabled on this deployment.
 #[utoipa::path(
     get,
     operation_id = "get_server_info",
@@ -66,19 +175,168 @@ pub async fn server_info(
 #[openapi(
     paths(
         server_info,
+        meta::get_changelog,
+        meta::get_chain_state,
+        auth::login,
+        auth::refresh_token,
+        auth::logout,
         user::create_user,
+        user::activate_user,
+        user::list_users,
         user::get_current_user,
+        user::update_current_user,
+        user::get_current_user_preferences,
+        user::update_current_user_preferences,
+        user::change_password,
+        user::request_email_change,
+        user::confirm_email_change,
+        user::restore_user,
+        bitcoin::get_balance,
+        bitcoin::list_utxos,
+        bitcoin::get_fee_estimates,
+        bitcoin::get_mempool,
+        solana::get_balance,
+        solana::get_account,
+        transaction::submit_transaction,
+        transaction::get_transaction,
+        key::create_key,
+        key::get_key,
+        signing::create_signing_session,
+        signing::get_signing_session,
+        wallet::get_balance_history,
+        webhook::create_webhook,
+        webhook::list_webhook_deliveries,
+        event::subscribe_events,
+        event::stream_server_snapshots,
+        admin::client_ip,
+        admin::list_background_tasks,
+        admin::get_slo_report,
+        admin::list_dependencies,
+        admin::reload_config,
+        admin::get_fault_injection,
+        admin::update_fault_injection,
+        admin::get_http_log,
+        admin::list_audit_logs,
+        admin::list_notifications,
+        admin::list_captured_notifications,
+        admin::delete_users,
+        admin::export_users,
+        admin::upload_openapi_baseline,
+        admin::get_api_drift,
+        admin::annotate_user,
+        admin::list_user_annotations,
+        admin::annotate_transaction,
+        admin::list_transaction_annotations,
+        admin::delete_annotation,
+        admin::list_login_lockouts,
+        admin::unlock_login,
+        admin::impersonate_user,
     ),
     components(schemas(
         ServerInfo,
+        crate::entity::ErrorResponse,
+        crate::entity::ErrorDetail,
+        crate::entity::ErrorType,
+        crate::entity::FieldViolation,
+        crate::entity::ApiChangelog,
+        crate::entity::ApiRelease,
+        crate::entity::ApiChange,
+        crate::entity::ApiChangeKind,
+        crate::entity::ChainState,
+        crate::entity::ChainHeight,
         crate::entity::User,
         crate::entity::UserInfo,
+        crate::entity::UserProfile,
+        crate::entity::UpdateUserProfileRequest,
+        crate::entity::UserPreferences,
+        crate::entity::UpdateUserPreferencesRequest,
+        crate::entity::ChangePasswordRequest,
+        crate::entity::ChangeEmailRequest,
+        crate::entity::ConfirmEmailChangeRequest,
         crate::entity::CreateUserRequest,
         crate::entity::CreateUserResponse,
+        crate::entity::ActivateUserRequest,
+        crate::entity::ListUsersFilter,
+        mpc_backend_mock_core::model::Pagination,
+        mpc_backend_mock_core::model::PageMetadata,
+        crate::entity::LoginRequest,
+        crate::entity::RefreshTokenRequest,
+        crate::entity::TokenResponse,
+        crate::entity::ImpersonationResponse,
+        crate::entity::LoginAttempt,
+        crate::entity::BitcoinBalance,
+        crate::entity::BitcoinUtxo,
+        crate::entity::BitcoinUtxoSet,
+        crate::entity::BitcoinFeeEstimate,
+        crate::entity::BitcoinFeeEstimates,
+        crate::entity::BitcoinMempool,
+        mpc_backend_mock_core::model::Satoshis,
+        crate::entity::SolanaBalance,
+        crate::entity::SolanaAccount,
+        mpc_backend_mock_core::model::Lamports,
+        crate::entity::Transaction,
+        crate::entity::TransactionStatus,
+        crate::entity::SubmitTransactionRequest,
+        crate::entity::Key,
+        crate::entity::KeyCurve,
+        crate::entity::CreateKeyRequest,
+        crate::entity::SigningSession,
+        crate::entity::SigningSessionStatus,
+        crate::entity::CreateSigningSessionRequest,
+        crate::entity::Chain,
+        crate::entity::DailyBalance,
+        crate::entity::WalletBalanceHistory,
+        mpc_backend_mock_core::model::TokenAmount,
+        crate::entity::Webhook,
+        crate::entity::WebhookEventType,
+        crate::entity::CreateWebhookRequest,
+        crate::entity::WebhookDelivery,
+        crate::entity::WebhookDeliveryStatus,
+        crate::entity::Event,
+        crate::entity::ServerSnapshot,
+        crate::entity::ClientIpResponse,
+        crate::entity::BackgroundTask,
+        crate::entity::SloReport,
+        crate::entity::ConfigReloadReport,
+        crate::entity::FaultInjection,
+        crate::entity::FaultInjectionRule,
+        crate::entity::InjectedFault,
+        crate::entity::UpdateFaultInjectionRequest,
+        crate::entity::HttpLog,
+        crate::entity::HttpExchange,
+        crate::entity::RouteSlo,
+        crate::entity::DependencyStatus,
+        crate::entity::DependencyState,
+        crate::entity::AuditLog,
+        crate::entity::ListAuditLogsFilter,
+        crate::entity::OutboxNotification,
+        crate::entity::NotificationStatus,
+        crate::entity::ListNotificationsFilter,
+        crate::entity::CapturedNotification,
+        crate::entity::BulkDeleteUsersParams,
+        crate::entity::BulkDeleteUsersResponse,
+        crate::entity::ExportFormat,
+        crate::entity::ExportUsersParams,
+        crate::entity::OpenApiBaseline,
+        crate::entity::ApiDriftReport,
+        crate::entity::ApiDrift,
+        crate::entity::Annotation,
+        crate::entity::CreateAnnotationRequest,
     )),
-    modifiers(&SecurityAddon),
+    modifiers(&SecurityAddon, &ErrorResponseAddon),
     tags(
-        (name = "Users", description = "User management endpoints")
+        (name = "Meta", description = "API metadata endpoints"),
+        (name = "Auth", description = "Token and session issuing endpoints"),
+        (name = "Users", description = "User management endpoints"),
+        (name = "Bitcoin", description = "Bitcoin wallet and fee market endpoints"),
+        (name = "Solana", description = "Solana account endpoints"),
+        (name = "Transactions", description = "Solana transaction submission endpoints"),
+        (name = "Keys", description = "Simulated MPC key generation endpoints"),
+        (name = "Signing", description = "Simulated MPC signing endpoints"),
+        (name = "Wallets", description = "Wallet balance history endpoints"),
+        (name = "Webhooks", description = "Webhook subscription endpoints"),
+        (name = "Events", description = "Real-time event subscription"),
+        (name = "Admin", description = "Operator endpoints, restricted by client IP")
     )
 )]
 pub struct ApiDoc;
@@ -97,6 +355,14 @@ impl utoipa::Modify for SecurityAddon {
                     ),
                 ),
             );
+            components.add_security_scheme(
+                "session_cookie",
+                utoipa::openapi::security::SecurityScheme::ApiKey(
+                    utoipa::openapi::security::ApiKey::Cookie(
+                        utoipa::openapi::security::ApiKeyValue::new("mpc_session"),
+                    ),
+                ),
+            );
         }
     }
 }
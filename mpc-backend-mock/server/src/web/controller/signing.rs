@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use uuid::Uuid;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{CreateSigningSessionRequest, SigningSession},
+    web::{
+        controller::Result,
+        extractor::{AuthUser as AuthUserExtractor, ValidatedJson},
+    },
+    ServiceState,
+};
+
+/// Start a simulated MPC signing session
+///
+/// This endpoint starts a fake signing ceremony of the payload for the current
+/// user. Nothing is signed, the session moves through `round1` and `round2` to
+/// `complete` as each round passes and then carries a fake signature. The
+/// round duration and the round to fail in can be set per session to test
+/// slow and failing ceremonies.
+#[utoipa::path(
+    post,
+    operation_id = "create_signing_session",
+    path = "/api/v1/signing/sessions",
+    request_body = CreateSigningSessionRequest,
+    responses(
+        (status = 200, description = "Signing session started", body = SigningSession),
+        (status = 400, description = "Invalid signing session (e.g., payload is not hex encoded)"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Signing"
+)]
+pub async fn create_signing_session(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<CreateSigningSessionRequest>,
+) -> Result<EncapsulatedJson<SigningSession>> {
+    let session = state
+        .signing_service
+        .create_session(
+            &auth_user.keycloak_user_id,
+            auth_user.realm.as_deref(),
+            &request.payload,
+            request.round_duration_milliseconds.map(Duration::from_millis),
+            request.fail_at,
+        )
+        .await?;
+
+    Ok(EncapsulatedJson::ok(session))
+}
+
+/// Get a simulated MPC signing session
+///
+/// This endpoint is meant for polling, the session is advanced to the phase
+/// it has reached by now until it is complete or failed.
+#[utoipa::path(
+    get,
+    operation_id = "get_signing_session",
+    path = "/api/v1/signing/sessions/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID of the signing session")
+    ),
+    responses(
+        (status = 200, description = "Signing session retrieved successfully", body = SigningSession),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "Signing session not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Signing"
+)]
+pub async fn get_signing_session(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(session_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<SigningSession>> {
+    let session = state
+        .signing_service
+        .get_session(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), &session_id)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(session))
+}
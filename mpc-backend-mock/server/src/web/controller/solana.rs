@@ -0,0 +1,84 @@
+use std::str::FromStr;
+
+use axum::extract::{Path, State};
+use snafu::ResultExt;
+use solana_sdk::pubkey::Pubkey;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{SolanaAccount, SolanaBalance},
+    web::{
+        controller::{error, Result},
+        extractor::AuthUser as AuthUserExtractor,
+    },
+    ServiceState,
+};
+
+/// Get the SOL balance of an account
+///
+/// This endpoint reads the balance with the server's commitment level. An
+/// account which does not exist has a balance of zero.
+#[utoipa::path(
+    get,
+    operation_id = "get_solana_balance",
+    path = "/api/v1/solana/balance/{pubkey}",
+    params(
+        ("pubkey" = String, Path, description = "Base58 public key of the account")
+    ),
+    responses(
+        (status = 200, description = "Balance retrieved successfully", body = SolanaBalance),
+        (status = 400, description = "Invalid Solana public key"),
+        (status = 401, description = "Unauthorized - missing or invalid token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Solana"
+)]
+pub async fn get_balance(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(_auth_user): AuthUserExtractor,
+    Path(pubkey): Path<String>,
+) -> Result<EncapsulatedJson<SolanaBalance>> {
+    let pubkey = parse_pubkey(pubkey)?;
+    let balance = state.solana_service.get_balance(&pubkey).await?;
+
+    Ok(EncapsulatedJson::ok(balance))
+}
+
+/// Get a Solana account
+///
+/// This endpoint returns the lamports, owner and base64 encoded data of the
+/// account, read with the server's commitment level.
+#[utoipa::path(
+    get,
+    operation_id = "get_solana_account",
+    path = "/api/v1/solana/account/{pubkey}",
+    params(
+        ("pubkey" = String, Path, description = "Base58 public key of the account")
+    ),
+    responses(
+        (status = 200, description = "Account retrieved successfully", body = SolanaAccount),
+        (status = 400, description = "Invalid Solana public key"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "Account does not exist")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Solana"
+)]
+pub async fn get_account(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(_auth_user): AuthUserExtractor,
+    Path(pubkey): Path<String>,
+) -> Result<EncapsulatedJson<SolanaAccount>> {
+    let pubkey = parse_pubkey(pubkey)?;
+    let account = state.solana_service.get_account(&pubkey).await?;
+
+    Ok(EncapsulatedJson::ok(account))
+}
+
+fn parse_pubkey(pubkey: String) -> Result<Pubkey> {
+    Pubkey::from_str(&pubkey).context(error::InvalidSolanaAddressSnafu { address: pubkey })
+}
@@ -0,0 +1,86 @@
+use axum::extract::{Path, State};
+use uuid::Uuid;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{SubmitTransactionRequest, Transaction},
+    web::{
+        controller::Result,
+        extractor::{AuthUser as AuthUserExtractor, ValidatedJson},
+    },
+    ServiceState,
+};
+
+/// Submit a signed Solana transaction
+///
+/// This endpoint forwards the transaction to the Solana RPC endpoint and
+/// records it for the current user. A transaction rejected by the RPC endpoint
+/// is recorded with the `failed` status and the reason.
+#[utoipa::path(
+    post,
+    operation_id = "submit_transaction",
+    path = "/api/v1/transactions",
+    request_body = SubmitTransactionRequest,
+    responses(
+        (status = 200, description = "Transaction submitted", body = Transaction),
+        (status = 400, description = "Invalid request (e.g., invalid base64 or unsigned transaction)"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 409, description = "Transaction was already submitted"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Transactions"
+)]
+pub async fn submit_transaction(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<SubmitTransactionRequest>,
+) -> Result<EncapsulatedJson<Transaction>> {
+    let transaction = state
+        .transaction_service
+        .submit_transaction(
+            &auth_user.keycloak_user_id,
+            auth_user.realm.as_deref(),
+            &request.transaction,
+        )
+        .await?;
+
+    Ok(EncapsulatedJson::ok(transaction))
+}
+
+/// Get a submitted transaction
+///
+/// This endpoint is meant for polling, the status is refreshed from the Solana
+/// RPC endpoint until the transaction is finalized or failed.
+#[utoipa::path(
+    get,
+    operation_id = "get_transaction",
+    path = "/api/v1/transactions/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID of the transaction")
+    ),
+    responses(
+        (status = 200, description = "Transaction retrieved successfully", body = Transaction),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "Transaction not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Transactions"
+)]
+pub async fn get_transaction(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<Transaction>> {
+    let transaction = state
+        .transaction_service
+        .get_transaction(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), &transaction_id)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(transaction))
+}
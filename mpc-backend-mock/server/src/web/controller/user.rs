@@ -1,23 +1,76 @@
-use axum::{
-    extract::{Query, State},
-    Json,
-};
+use axum::extract::{Path, Query, State};
+use mpc_backend_mock_core::model::{Paginated, Pagination};
+use uuid::Uuid;
 use zeus_axum::response::EncapsulatedJson;
 
 use crate::{
-    entity::{CreateUserRequest, CreateUserResponse, DeleteUserParams, User, UserInfo},
-    web::{controller::Result, extractor::AuthUser as AuthUserExtractor},
+    entity::{
+        ActivateUserRequest, AuditAction, ChangeEmailRequest, ChangePasswordRequest,
+        ConfirmEmailChangeRequest, CreateUserRequest, CreateUserResponse, DeleteUserParams,
+        ListUsersFilter, ListedUser, UpdateUserPreferencesRequest, UpdateUserProfileRequest, User,
+        UserField, UserInfo, UserPreferences, UserProfile, UserSortColumn,
+    },
+    service::{check_password_strength, error::Error as ServiceError},
+    web::{
+        business_metrics,
+        controller::{Error, Result},
+        extractor::{
+            Audit, AuthUser as AuthUserExtractor, FieldsParams, PaginationQuery, SortParams,
+            ValidatedJson, ValidatedQuery,
+        },
+    },
     ServiceState,
 };
 
-// Get all users (for testing purposes only)
-// GET /api/v1/users, response List<User>
+/// List users
+///
+/// This endpoint returns the users of the caller's realm page by page, newest
+/// first unless `sort` is given. `fields` limits the returned fields of each
+/// user. The total number of users matching the filters is returned in
+/// `_metadata`.
+#[utoipa::path(
+    get,
+    operation_id = "list_users",
+    path = "/api/v1/users",
+    params(
+        Pagination,
+        ListUsersFilter,
+        ("sort" = Option<String>, Query, description = "Comma-separated `column[:asc|desc]`, columns: created_at, updated_at, email, is_active", example = "created_at:desc,email:asc"),
+        ("fields" = Option<String>, Query, description = "Comma-separated fields to return, fields: id, email, keycloak_user_id, is_active, created_at, updated_at", example = "id,email")
+    ),
+    responses(
+        (status = 200, description = "Users retrieved successfully", body = [User]),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 401, description = "Unauthorized - missing or invalid token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn list_users(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    PaginationQuery(pagination): PaginationQuery,
+    ValidatedQuery(filter): ValidatedQuery<ListUsersFilter>,
+    SortParams(sort): SortParams<UserSortColumn>,
+    FieldsParams(fields): FieldsParams<UserField>,
+) -> Result<Paginated<ListedUser>> {
+    let (users, total_count) = state
+        .user_management_service
+        .list_users(auth_user.realm.as_deref(), &pagination, &filter, &sort, fields.as_ref())
+        .await?;
+
+    Ok(Paginated::new(users, total_count, &pagination))
+}
 
 /// Create a new user
 ///
 /// This endpoint creates a new user in both Keycloak and the database.
 /// The user is first created in Keycloak, and upon success, a corresponding
-/// record is created in the database with the Keycloak user ID.
+/// record is created in the database with the Keycloak user ID. The user stays
+/// inactive until the token emailed to it is passed to the activate endpoint.
+/// Users are created in the primary realm unless `realm` names another one.
 #[utoipa::path(
     post,
     operation_id = "create_user",
@@ -25,21 +78,63 @@ use crate::{
     request_body = CreateUserRequest,
     responses(
         (status = 200, description = "User created successfully", body = CreateUserResponse),
-        (status = 400, description = "Invalid request (e.g., invalid email format)"),
-        (status = 409, description = "User already exists (in database or Keycloak)")
+        (status = 400, description = "Invalid request (e.g., invalid email format, locale or realm)"),
+        (status = 409, description = "User already exists (in database or Keycloak)"),
+        (status = 422, description = "Request body failed validation")
     ),
     tag = "Users"
 )]
 pub async fn create_user(
     State(state): State<ServiceState>,
-    Json(request): Json<CreateUserRequest>,
+    Audit(audit): Audit,
+    ValidatedJson(request): ValidatedJson<CreateUserRequest>,
 ) -> Result<EncapsulatedJson<CreateUserResponse>> {
     // Create user in Keycloak and database
-    let user = state.user_management_service.create_user(&request.email).await?;
+    let user = state
+        .user_management_service
+        .create_user(&request.email, request.realm.as_deref(), request.locale.as_deref())
+        .await?;
+    business_metrics::record_user_created(&state.metrics);
+
+    state
+        .audit_service
+        .record(
+            &audit,
+            AuditAction::UserCreated,
+            Some(&user.id),
+            serde_json::json!({ "email": user.email }),
+        )
+        .await;
 
     Ok(EncapsulatedJson::ok(CreateUserResponse { user }))
 }
 
+/// Activate a user
+///
+/// This endpoint redeems the single-use token from the activation email, the
+/// user is marked active and its email is marked verified in Keycloak.
+#[utoipa::path(
+    post,
+    operation_id = "activate_user",
+    path = "/api/v1/users/activate",
+    request_body = ActivateUserRequest,
+    responses(
+        (status = 200, description = "User activated successfully", body = User),
+        (status = 400, description = "Activation token is invalid, expired or already used"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    tag = "Users"
+)]
+pub async fn activate_user(
+    State(state): State<ServiceState>,
+    ValidatedJson(request): ValidatedJson<ActivateUserRequest>,
+) -> Result<EncapsulatedJson<User>> {
+    let user = state.user_management_service.activate_user(&request.token).await?;
+
+    Ok(EncapsulatedJson::ok(user))
+}
+
 /// Get current user information
 ///
 /// This endpoint returns information about the currently authenticated user.
@@ -63,20 +158,273 @@ pub async fn get_current_user(
     AuthUserExtractor(auth_user): AuthUserExtractor,
 ) -> Result<EncapsulatedJson<UserInfo>> {
     // Get user from database using the Keycloak user ID from the JWT token
-    let user: User =
-        state.user_management_service.get_user_by_keycloak_id(&auth_user.keycloak_user_id).await?;
+    let user: User = state
+        .user_management_service
+        .get_user_by_keycloak_id(&auth_user.keycloak_user_id, auth_user.realm.as_deref())
+        .await?;
+    let profile = state.user_management_service.get_user_profile(&user.id).await?;
 
     // Combine database user with Keycloak info from the token
     let user_info = UserInfo {
         user,
         username: auth_user.username,
         email_verified: Some(auth_user.email_verified),
+        profile,
     };
 
     Ok(EncapsulatedJson::ok(user_info))
 }
 
-/// Delete a user by email (for testing purposes only)
+/// Update current user profile
+///
+/// This endpoint updates the display name and locale of the currently
+/// authenticated user. Omitted fields are kept, both are also set on the
+/// Keycloak account.
+#[utoipa::path(
+    patch,
+    operation_id = "update_current_user",
+    path = "/api/v1/users/me",
+    request_body = UpdateUserProfileRequest,
+    responses(
+        (status = 200, description = "User profile updated successfully", body = UserProfile),
+        (status = 400, description = "Invalid profile update"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn update_current_user(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<UpdateUserProfileRequest>,
+) -> Result<EncapsulatedJson<UserProfile>> {
+    let profile = state
+        .user_management_service
+        .update_user_profile(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), request)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(profile))
+}
+
+/// Get current user notification preferences
+///
+/// This endpoint returns the channels the notifications of the currently
+/// authenticated user are delivered over, the defaults until they are set:
+/// emails in the language of the notification and no webhook.
+#[utoipa::path(
+    get,
+    operation_id = "get_current_user_preferences",
+    path = "/api/v1/users/me/preferences",
+    responses(
+        (status = 200, description = "User preferences retrieved successfully", body = UserPreferences),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn get_current_user_preferences(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<EncapsulatedJson<UserPreferences>> {
+    let preferences = state
+        .user_management_service
+        .get_user_preferences(&auth_user.keycloak_user_id, auth_user.realm.as_deref())
+        .await?;
+
+    Ok(EncapsulatedJson::ok(preferences))
+}
+
+/// Replace current user notification preferences
+///
+/// This endpoint replaces the notification preferences of the currently
+/// authenticated user. Notifications are emailed unless `email_enabled` is
+/// false, posted as JSON to `webhook_url` if set and sent in `locale` if set.
+/// A notification none of whose recipients wants it is skipped.
+#[utoipa::path(
+    put,
+    operation_id = "update_current_user_preferences",
+    path = "/api/v1/users/me/preferences",
+    request_body = UpdateUserPreferencesRequest,
+    responses(
+        (status = 200, description = "User preferences updated successfully", body = UserPreferences),
+        (status = 400, description = "Invalid preferences"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn update_current_user_preferences(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<UpdateUserPreferencesRequest>,
+) -> Result<EncapsulatedJson<UserPreferences>> {
+    let preferences = state
+        .user_management_service
+        .update_user_preferences(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), request)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(preferences))
+}
+
+/// Change current user password
+///
+/// This endpoint checks the current password against Keycloak, then replaces
+/// it with the new one. The new password must be 12 to 128 characters, mix
+/// letters with digits or symbols and not contain the email address. Only the
+/// users of the primary realm change their password here, the users of the
+/// other realms change it in their realm.
+#[utoipa::path(
+    post,
+    operation_id = "change_password",
+    path = "/api/v1/users/me/password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed successfully", body = User),
+        (status = 400, description = "Current password is incorrect or new password is too weak"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 403, description = "User belongs to another realm than the primary one"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn change_password(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<ChangePasswordRequest>,
+) -> Result<EncapsulatedJson<User>> {
+    // the current password is checked by logging in to the primary realm, which
+    // may hold another account with the same email
+    if let Some(realm) = auth_user.realm {
+        return Err(Error::PasswordManagedByRealm { realm });
+    }
+
+    let user = state
+        .user_management_service
+        .get_user_by_keycloak_id(&auth_user.keycloak_user_id, None)
+        .await?;
+
+    check_password_strength(&request.new_password, &user.email)
+        .map_err(|reason| Error::WeakPassword { reason })?;
+    if request.new_password == request.current_password {
+        return Err(Error::WeakPassword {
+            reason: "new password must differ from the current one",
+        });
+    }
+
+    // a wrong current password must not read as an expired session
+    state.auth_service.verify_password(&user.email, &request.current_password).await.map_err(
+        |source| match source {
+            ServiceError::InvalidCredentials { .. } => Error::InvalidCurrentPassword,
+            source => Error::Service { source },
+        },
+    )?;
+
+    state
+        .user_management_service
+        .change_password(&auth_user.keycloak_user_id, None, &request.new_password)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(user))
+}
+
+/// Request an email change for the current user
+///
+/// This endpoint emails a verification link to the new address, the email of
+/// the currently authenticated user is only changed once the token of the link
+/// is passed to the confirm endpoint. A new request replaces the pending one.
+#[utoipa::path(
+    post,
+    operation_id = "request_email_change",
+    path = "/api/v1/users/me/email",
+    request_body = ChangeEmailRequest,
+    responses(
+        (status = 200, description = "Verification link sent to the new email", body = ()),
+        (status = 400, description = "Invalid email change (e.g., current email or user of another realm)"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 409, description = "User already exists (in database or Keycloak)"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn request_email_change(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<ChangeEmailRequest>,
+) -> Result<EncapsulatedJson<()>> {
+    state
+        .user_management_service
+        .request_email_change(
+            &auth_user.keycloak_user_id,
+            auth_user.realm.as_deref(),
+            &request.email,
+        )
+        .await?;
+
+    Ok(EncapsulatedJson::ok(()))
+}
+
+/// Confirm an email change
+///
+/// This endpoint redeems the single-use token from the email change
+/// verification link, the user's email is changed in the database and in
+/// Keycloak, where it is marked verified.
+#[utoipa::path(
+    post,
+    operation_id = "confirm_email_change",
+    path = "/api/v1/users/email/confirm",
+    request_body = ConfirmEmailChangeRequest,
+    responses(
+        (status = 200, description = "Email changed successfully", body = User),
+        (status = 400, description = "Email change token is invalid, expired or already used"),
+        (status = 404, description = "User not found in database"),
+        (status = 409, description = "User already exists (in database or Keycloak)"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    tag = "Users"
+)]
+pub async fn confirm_email_change(
+    State(state): State<ServiceState>,
+    Audit(audit): Audit,
+    ValidatedJson(request): ValidatedJson<ConfirmEmailChangeRequest>,
+) -> Result<EncapsulatedJson<User>> {
+    let user = state.user_management_service.confirm_email_change(&request.token).await?;
+
+    state
+        .audit_service
+        .record(
+            &audit,
+            AuditAction::UserEmailChanged,
+            Some(&user.id),
+            serde_json::json!({ "email": user.email }),
+        )
+        .await;
+
+    Ok(EncapsulatedJson::ok(user))
+}
+
+/// Soft delete a user by email (for testing purposes only)
+///
+/// The user is kept in the database with `deleted_at` set and the Keycloak
+/// account is disabled. Use the restore endpoint to undo it.
 // sample path /api/v1/users?email={email}
 #[utoipa::path(
     delete,
@@ -86,7 +434,7 @@ pub async fn get_current_user(
         ("email" = String, Path, description = "Email of the user to delete")
     ),
     responses(
-        (status = 200, description = "User deleted successfully", body = ()),
+        (status = 200, description = "User soft deleted successfully", body = ()),
         (status = 400, description = "Invalid request (e.g., invalid email format)"),
         (status = 404, description = "User not found in database")
     ),
@@ -94,10 +442,48 @@ pub async fn get_current_user(
 )]
 pub async fn delete_user(
     State(state): State<ServiceState>,
+    Audit(audit): Audit,
     Query(params): Query<DeleteUserParams>,
 ) -> Result<EncapsulatedJson<String>> {
     // Delete user in Keycloak and database
     let delete_user_id = state.user_management_service.delete_user_by_email(&params.email).await?;
 
+    state
+        .audit_service
+        .record(
+            &audit,
+            AuditAction::UserDeleted,
+            Some(&delete_user_id),
+            serde_json::json!({ "email": params.email }),
+        )
+        .await;
+
     Ok(EncapsulatedJson::ok(delete_user_id.to_string()))
 }
+
+/// Restore a soft-deleted user (for testing purposes only)
+///
+/// This endpoint clears the user's `deleted_at` and re-enables the Keycloak
+/// account.
+#[utoipa::path(
+    post,
+    operation_id = "restore_user",
+    path = "/api/v1/users/{id}/restore",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user to restore")
+    ),
+    responses(
+        (status = 200, description = "User restored successfully", body = User),
+        (status = 404, description = "User not found in database"),
+        (status = 409, description = "User is not deleted")
+    ),
+    tag = "Users"
+)]
+pub async fn restore_user(
+    State(state): State<ServiceState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<EncapsulatedJson<User>> {
+    let user = state.user_management_service.restore_user(user_id).await?;
+
+    Ok(EncapsulatedJson::ok(user))
+}
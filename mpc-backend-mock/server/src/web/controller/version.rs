@@ -0,0 +1,288 @@
+//! API versions mounted side by side under `/api`.
+//!
+//! Each version serves a [`RouteSet`] under `/api/<version>` and its OpenAPI
+//! document under `/api/<version>/openapi.json`. A new version starts from the
+//! routes of the previous one, so handlers are shared until a breaking change
+//! replaces one of them in the newer version only.
+
+use std::collections::BTreeMap;
+
+use axum::{middleware, routing, routing::MethodRouter, Json, Router};
+use utoipa::OpenApi;
+
+use crate::{
+    web::{
+        controller::ApiDoc,
+        middleware::{
+            admin_ip_filter_middleware, admin_role_middleware, audit_admin_middleware,
+            jwt_auth_middleware, user_rate_limit_middleware,
+        },
+    },
+    ServiceState,
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Version of the HTTP API, sealed as the versions are the ones served below
+pub trait ApiVersion: sealed::Sealed + Send + Sync + 'static {
+    /// Path segment of the version, e.g. `v1`
+    const SEGMENT: &'static str;
+
+    /// OpenAPI document of the operations served under this version
+    fn openapi() -> utoipa::openapi::OpenApi;
+}
+
+/// First version of the API, documented by [`ApiDoc`]
+#[derive(Clone, Copy, Debug)]
+pub enum V1 {}
+
+/// Second version of the API, documented by the [`ApiDoc`] operations rebased
+/// onto `/api/v2` and replaced by those of [`ApiDocV2`]
+#[derive(Clone, Copy, Debug)]
+pub enum V2 {}
+
+impl sealed::Sealed for V1 {}
+
+impl sealed::Sealed for V2 {}
+
+impl ApiVersion for V1 {
+    const SEGMENT: &'static str = "v1";
+
+    fn openapi() -> utoipa::openapi::OpenApi { ApiDoc::openapi() }
+}
+
+impl ApiVersion for V2 {
+    const SEGMENT: &'static str = "v2";
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        let mut openapi = rebase(ApiDoc::openapi(), V1::SEGMENT, Self::SEGMENT);
+
+        let mut changed = ApiDocV2::openapi();
+        let changed_paths = std::mem::take(&mut changed.paths.paths);
+        openapi.merge(changed);
+        for (path, item) in changed_paths {
+            let _previous = openapi.paths.paths.insert(path, item);
+        }
+
+        openapi
+    }
+}
+
+/// Operations whose v2 contract differs from v1, they replace the rebased v1
+/// operations in the v2 document
+#[derive(OpenApi)]
+pub struct ApiDocV2;
+
+/// Who may call a route, which decides the middleware wrapping it
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Access {
+    /// No authentication required
+    Public,
+
+    /// Authenticated users, rate limited per user
+    Protected,
+
+    /// Clients in the admin allowlist, audited
+    Admin,
+
+    /// Users with the admin role calling from the admin allowlist, audited
+    AdminUser,
+}
+
+/// Routes of one API version by path relative to `/api/<version>`
+///
+/// Adding a route at a path which already has one with the same access
+/// replaces it, which is how a newer version overrides a shared handler.
+#[derive(Clone, Default)]
+pub struct RouteSet {
+    routes: BTreeMap<(Access, &'static str), MethodRouter<ServiceState>>,
+}
+
+impl RouteSet {
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Route without authentication
+    #[must_use]
+    pub fn public(self, path: &'static str, route: MethodRouter<ServiceState>) -> Self {
+        self.with(Access::Public, path, route)
+    }
+
+    /// Route requiring an authenticated user
+    #[must_use]
+    pub fn protected(self, path: &'static str, route: MethodRouter<ServiceState>) -> Self {
+        self.with(Access::Protected, path, route)
+    }
+
+    /// Route restricted to the admin allowlist
+    #[must_use]
+    pub fn admin(self, path: &'static str, route: MethodRouter<ServiceState>) -> Self {
+        self.with(Access::Admin, path, route)
+    }
+
+    /// Route restricted to the admin allowlist and to users with the admin
+    /// role
+    #[must_use]
+    pub fn admin_user(self, path: &'static str, route: MethodRouter<ServiceState>) -> Self {
+        self.with(Access::AdminUser, path, route)
+    }
+
+    fn with(
+        mut self,
+        access: Access,
+        path: &'static str,
+        route: MethodRouter<ServiceState>,
+    ) -> Self {
+        let _previous = self.routes.insert((access, path), route);
+        self
+    }
+
+    /// Paths of the routes authenticated by a token, whose validation calls
+    /// Keycloak's JWKS or introspection endpoint
+    #[must_use]
+    pub fn token_authenticated_paths(&self) -> Vec<&'static str> {
+        let mut paths: Vec<_> = self
+            .routes
+            .keys()
+            .filter(|(access, _)| matches!(access, Access::Protected | Access::AdminUser))
+            .map(|(_, path)| *path)
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        paths
+    }
+
+    /// Router of the routes with `access`, under `prefix`
+    fn router(&self, access: Access, prefix: &str) -> Router<ServiceState> {
+        self.routes
+            .iter()
+            .filter(|((route_access, _), _)| *route_access == access)
+            .fold(Router::new(), |router, ((_, path), route)| {
+                router.route(&format!("{prefix}{path}"), route.clone())
+            })
+    }
+}
+
+/// Builder of the `/api` router serving every API version
+pub struct VersionedRouter {
+    service_state: ServiceState,
+    router: Router<ServiceState>,
+}
+
+impl VersionedRouter {
+    #[must_use]
+    pub fn new(service_state: &ServiceState) -> Self {
+        Self { service_state: service_state.clone(), router: Router::new() }
+    }
+
+    /// Serve `routes` and the OpenAPI document of `V` under `/api/<version>`
+    #[must_use]
+    pub fn version<V: ApiVersion>(self, routes: &RouteSet) -> Self {
+        let Self { service_state, router } = self;
+        let prefix = format!("/{}", V::SEGMENT);
+
+        let public_routes = routes
+            .router(Access::Public, &prefix)
+            .route(&format!("{prefix}/openapi.json"), routing::get(openapi_document::<V>));
+        let protected_routes = routes
+            .router(Access::Protected, &prefix)
+            .layer(middleware::from_fn_with_state(
+                service_state.clone(),
+                user_rate_limit_middleware,
+            ))
+            .layer(middleware::from_fn_with_state(service_state.clone(), jwt_auth_middleware));
+        let admin_routes = routes
+            .router(Access::Admin, &prefix)
+            .layer(middleware::from_fn_with_state(service_state.clone(), audit_admin_middleware))
+            .layer(middleware::from_fn_with_state(
+                service_state.clone(),
+                admin_ip_filter_middleware,
+            ));
+
+        let admin_user_routes = routes
+            .router(Access::AdminUser, &prefix)
+            .layer(middleware::from_fn_with_state(service_state.clone(), audit_admin_middleware))
+            .layer(middleware::from_fn_with_state(service_state.clone(), admin_role_middleware))
+            .layer(middleware::from_fn_with_state(service_state.clone(), jwt_auth_middleware))
+            .layer(middleware::from_fn_with_state(
+                service_state.clone(),
+                admin_ip_filter_middleware,
+            ));
+
+        let router = router
+            .nest("/api", public_routes)
+            .nest("/api", protected_routes)
+            .nest("/api", admin_routes)
+            .nest("/api", admin_user_routes);
+        Self { service_state, router }
+    }
+
+    #[must_use]
+    pub fn into_router(self) -> Router<ServiceState> { self.router }
+}
+
+// SAFETY: `axum` handler must be async
+#[allow(clippy::unused_async)]
+async fn openapi_document<V: ApiVersion>() -> Json<utoipa::openapi::OpenApi> { Json(V::openapi()) }
+
+/// Move the paths under `/api/<from>` to `/api/<to>`
+fn rebase(mut openapi: utoipa::openapi::OpenApi, from: &str, to: &str) -> utoipa::openapi::OpenApi {
+    let from = format!("/api/{from}/");
+    let to = format!("/api/{to}/");
+    openapi.paths.paths = std::mem::take(&mut openapi.paths.paths)
+        .into_iter()
+        .map(|(path, item)| match path.strip_prefix(&from) {
+            Some(rest) => (format!("{to}{rest}"), item),
+            None => (path, item),
+        })
+        .collect();
+    openapi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v2_document_rebases_v1_operations() {
+        let v1 = V1::openapi();
+        let v2 = V2::openapi();
+
+        assert_eq!(v1.paths.paths.len(), v2.paths.paths.len());
+        assert!(v1.paths.paths.contains_key("/api/v1/users"));
+        assert!(v2.paths.paths.contains_key("/api/v2/users"));
+        assert!(v2.paths.paths.keys().all(|path| !path.starts_with("/api/v1/")));
+    }
+
+    /// Paths of `routes` missing from the OpenAPI document of `V`
+    fn undocumented<V: ApiVersion>(routes: &RouteSet) -> Vec<String> {
+        let openapi = V::openapi();
+        routes
+            .routes
+            .keys()
+            .map(|(_, path)| {
+                // `/users/:id` is documented as `/users/{id}`
+                let path = path
+                    .split('/')
+                    .map(|segment| match segment.strip_prefix(':') {
+                        Some(parameter) => format!("{{{parameter}}}"),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                format!("/api/{}{path}", V::SEGMENT)
+            })
+            .filter(|path| !openapi.paths.paths.contains_key(path))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_route_is_documented() {
+        let routes = super::super::v1_routes();
+
+        assert_eq!(undocumented::<V1>(&routes), Vec::<String>::new());
+        assert_eq!(undocumented::<V2>(&routes), Vec::<String>::new());
+    }
+}
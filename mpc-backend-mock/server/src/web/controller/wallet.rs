@@ -0,0 +1,57 @@
+use axum::extract::{Path, State};
+use uuid::Uuid;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{BalanceHistoryParams, WalletBalanceHistory},
+    web::{
+        controller::Result,
+        extractor::{AuthUser as AuthUserExtractor, ValidatedQuery},
+    },
+    ServiceState,
+};
+
+/// Get the balance history of a wallet
+///
+/// This endpoint returns one balance per day (UTC) from `from` to `to`, the
+/// last 30 days by default. Balances are snapshotted hourly from confirmed
+/// deposits and withdrawals which did not fail, a day without a snapshot
+/// carries the balance of the day before.
+#[utoipa::path(
+    get,
+    operation_id = "get_wallet_balance_history",
+    path = "/api/v1/wallets/{id}/balance-history",
+    params(
+        ("id" = Uuid, Path, description = "ID of the wallet"),
+        BalanceHistoryParams
+    ),
+    responses(
+        (status = 200, description = "Balance history retrieved successfully", body = WalletBalanceHistory),
+        (status = 400, description = "Invalid date or date range"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "Wallet not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Wallets"
+)]
+pub async fn get_balance_history(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(wallet_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<BalanceHistoryParams>,
+) -> Result<EncapsulatedJson<WalletBalanceHistory>> {
+    let history = state
+        .wallet_service
+        .balance_history(
+            &auth_user.keycloak_user_id,
+            auth_user.realm.as_deref(),
+            &wallet_id,
+            params.from,
+            params.to,
+        )
+        .await?;
+
+    Ok(EncapsulatedJson::ok(history))
+}
@@ -0,0 +1,92 @@
+use axum::extract::{Path, State};
+use mpc_backend_mock_core::model::{Paginated, Pagination};
+use uuid::Uuid;
+use zeus_axum::response::EncapsulatedJson;
+
+use crate::{
+    entity::{CreateWebhookRequest, Webhook, WebhookDelivery},
+    web::{
+        controller::Result,
+        extractor::{AuthUser as AuthUserExtractor, PaginationQuery, ValidatedJson},
+    },
+    ServiceState,
+};
+
+/// Register a webhook of the current user
+///
+/// The events of the user of the given types are posted as JSON to the URL,
+/// signed with the returned secret in the `X-Webhook-Signature` header as
+/// `sha256=<hex HMAC-SHA256 of the body>`. Failed deliveries are retried with
+/// exponential backoff and dead-lettered after their last attempt.
+#[utoipa::path(
+    post,
+    operation_id = "create_webhook",
+    path = "/api/v1/webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered successfully", body = Webhook),
+        (status = 400, description = "Invalid webhook"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "User not found in database"),
+        (status = 422, description = "Request body failed validation")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Webhooks"
+)]
+pub async fn create_webhook(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    ValidatedJson(request): ValidatedJson<CreateWebhookRequest>,
+) -> Result<EncapsulatedJson<Webhook>> {
+    let webhook = state
+        .webhook_service
+        .create_webhook(&auth_user.keycloak_user_id, auth_user.realm.as_deref(), request)
+        .await?;
+
+    Ok(EncapsulatedJson::ok(webhook))
+}
+
+/// List the deliveries of a webhook of the current user
+///
+/// This endpoint returns the events queued for the webhook page by page,
+/// newest first, with their delivery status, attempts, and the response
+/// status or error of the last attempt.
+#[utoipa::path(
+    get,
+    operation_id = "list_webhook_deliveries",
+    path = "/api/v1/webhooks/{id}/deliveries",
+    params(
+        ("id" = Uuid, Path, description = "ID of the webhook"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Deliveries retrieved successfully", body = [WebhookDelivery]),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 401, description = "Unauthorized - missing or invalid token"),
+        (status = 404, description = "Webhook not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Webhooks"
+)]
+pub async fn list_webhook_deliveries(
+    State(state): State<ServiceState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(webhook_id): Path<Uuid>,
+    PaginationQuery(pagination): PaginationQuery,
+) -> Result<Paginated<WebhookDelivery>> {
+    let (deliveries, total_count) = state
+        .webhook_service
+        .list_deliveries(
+            &auth_user.keycloak_user_id,
+            auth_user.realm.as_deref(),
+            &webhook_id,
+            &pagination,
+        )
+        .await?;
+
+    Ok(Paginated::new(deliveries, total_count, &pagination))
+}
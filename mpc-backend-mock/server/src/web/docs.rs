@@ -0,0 +1,48 @@
+//! Swagger UI of the API, served under `/docs`.
+//!
+//! The page is served at `/docs/index.html` rather than `/docs/`, whose
+//! trailing slash is trimmed before routing, so that its relative asset URLs
+//! resolve under `/docs`.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    routing, Extension, Router,
+};
+use utoipa_swagger_ui::{Config, Url};
+
+/// Routes of Swagger UI, listing the OpenAPI document of every API version
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let config = Config::new([
+        Url::new("v1", "/api/v1/openapi.json"),
+        Url::new("v2", "/api/v2/openapi.json"),
+    ]);
+
+    Router::new()
+        .route("/docs", routing::get(|| async { Redirect::permanent("/docs/index.html") }))
+        .route("/docs/*file", routing::get(swagger_ui_file))
+        .layer(Extension(Arc::new(config)))
+}
+
+async fn swagger_ui_file(
+    uri: Uri,
+    Path(file): Path<String>,
+    Extension(config): Extension<Arc<Config<'static>>>,
+) -> Response {
+    match utoipa_swagger_ui::serve(&file, config) {
+        Ok(Some(file)) => {
+            ([(header::CONTENT_TYPE, file.content_type)], file.bytes.into_owned()).into_response()
+        }
+        Ok(None) => super::fallback(uri).await,
+        Err(err) => {
+            tracing::error!("Failed to serve Swagger UI file `{file}`, error: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -1,12 +1,22 @@
-use std::result::Result;
+use std::{convert::Infallible, result::Result};
 
 use axum::{
     async_trait,
-    extract::{FromRequestParts, Query},
-    http::{request::Parts, StatusCode},
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::{request::Parts, Extensions, StatusCode},
+    Json,
 };
+use mpc_backend_mock_core::model::{Column, Fields, Pagination, Sort};
+use serde::Deserialize;
+use validator::Validate;
 
-use crate::web::{controller::Error, middleware::AuthUser as AuthUserData};
+use crate::{
+    service::AuditContext,
+    web::{
+        controller::Error,
+        middleware::{AuthUser as AuthUserData, ClientIp, RequestId},
+    },
+};
 
 /// Extractor for the `userId` header.
 ///
@@ -56,7 +66,6 @@ where
 
 /// Custom query extractor that converts Axum's rejection into our custom error
 /// type
-#[allow(dead_code)]
 #[derive(Debug)]
 pub struct ValidatedQuery<T>(pub T);
 
@@ -85,6 +94,115 @@ where
     }
 }
 
+/// JSON body extractor which checks the `validator` rules of the body
+///
+/// A body which is not valid JSON of `T` is answered with a 400
+/// `INVALID_REQUEST_BODY`, a body breaking a rule with a 422
+/// `VALIDATION_FAILED` listing the offending fields.
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: serde::de::DeserializeOwned + Validate,
+{
+    type Rejection = Error;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(|rejection| Error::InvalidRequestBody { message: rejection.body_text() })?;
+        value.validate().map_err(|source| Error::ValidationFailed { source })?;
+
+        Ok(Self(value))
+    }
+}
+
+/// Query extractor for the `page` and `limit` of list endpoints
+///
+/// A `page` of 0 or a `limit` above [`Pagination::MAX_LIMIT`] is rejected
+/// instead of being clamped, so clients notice they asked for more than a page
+/// holds.
+#[derive(Debug)]
+pub struct PaginationQuery(pub Pagination);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for PaginationQuery
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ValidatedQuery(pagination) =
+            ValidatedQuery::<Pagination>::from_request_parts(parts, state).await?;
+        pagination.validate()?;
+
+        Ok(Self(pagination))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SortQuery {
+    sort: Option<String>,
+}
+
+/// Query extractor for the `sort` of list endpoints, e.g.
+/// `?sort=created_at:desc,email:asc`
+///
+/// Only the columns of `C` are accepted, the order is empty and the endpoint's
+/// default applies when `sort` is missing.
+#[derive(Debug)]
+pub struct SortParams<C>(pub Sort<C>);
+
+#[async_trait]
+impl<S, C> FromRequestParts<S> for SortParams<C>
+where
+    S: Send + Sync,
+    C: Column,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ValidatedQuery(SortQuery { sort }) =
+            ValidatedQuery::<SortQuery>::from_request_parts(parts, state).await?;
+        let sort = sort.as_deref().map(Sort::parse).transpose()?.unwrap_or_default();
+
+        Ok(Self(sort))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldsQuery {
+    fields: Option<String>,
+}
+
+/// Query extractor for the `fields` a list endpoint projects its items onto,
+/// e.g. `?fields=id,email`
+///
+/// Only the columns of `C` are accepted, every field is returned when `fields`
+/// is missing.
+#[derive(Debug)]
+pub struct FieldsParams<C>(pub Option<Fields<C>>);
+
+#[async_trait]
+impl<S, C> FromRequestParts<S> for FieldsParams<C>
+where
+    S: Send + Sync,
+    C: Column,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ValidatedQuery(FieldsQuery { fields }) =
+            ValidatedQuery::<FieldsQuery>::from_request_parts(parts, state).await?;
+
+        Ok(Self(fields.as_deref().map(Fields::parse).transpose()?))
+    }
+}
+
 /// Extractor for authenticated user information
 ///
 /// This extractor retrieves the `AuthUser` data that was inserted by the JWT
@@ -122,3 +240,32 @@ where
         Ok(Self(auth_user))
     }
 }
+
+/// Extractor for the [`AuditContext`] of the request
+///
+/// The client IP and the request id are set by the request id middleware,
+/// the actor is left to the handler, which knows the database user.
+#[derive(Debug, Clone)]
+pub struct Audit(pub AuditContext);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Audit
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(audit_context(&parts.extensions)))
+    }
+}
+
+/// [`AuditContext`] of a request from its extensions, without an actor
+#[must_use]
+pub fn audit_context(extensions: &Extensions) -> AuditContext {
+    AuditContext {
+        actor_user_id: None,
+        ip_address: extensions.get::<ClientIp>().map(|ClientIp(ip)| *ip),
+        request_id: extensions.get::<RequestId>().map(|RequestId(id)| id.clone()),
+    }
+}
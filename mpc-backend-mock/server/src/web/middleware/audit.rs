@@ -0,0 +1,42 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    entity::AuditAction,
+    web::{extractor::audit_context, ServiceState},
+};
+
+/// Admin audit middleware
+///
+/// Records every admin request which may change state, that is every method
+/// but `GET`, `HEAD` and `OPTIONS`, in the audit log once it is answered.
+pub async fn audit_admin_middleware(
+    State(service_state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(request).await;
+    }
+
+    let context = audit_context(request.extensions());
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().map(ToString::to_string);
+
+    let response = next.run(request).await;
+
+    let payload = serde_json::json!({
+        "method": method,
+        "path": path,
+        "query": query,
+        "status": response.status().as_u16(),
+    });
+    service_state.audit_service.record(&context, AuditAction::AdminAction, None, payload).await;
+
+    response
+}
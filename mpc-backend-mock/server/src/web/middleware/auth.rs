@@ -1,16 +1,33 @@
+use std::{collections::HashMap, fmt, time::Duration};
+
 use axum::{
-    extract::Request,
-    http::{HeaderMap, StatusCode},
+    extract::{Query, Request},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use axum_extra::extract::CookieJar;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use zeus_axum::response::EncapsulatedJsonError;
+use zeus_axum::response::{EncapsulatedJsonError, ErrorCode};
+
+use super::{jwks::JwksError, Realm};
+use crate::{
+    circuit_breaker::BreakerOpen,
+    entity::AuditAction,
+    keycloak_client::RealmAccess,
+    service::{error::retry_hint, verify_csrf_token},
+    web::{business_metrics, extractor::audit_context, ServiceState},
+};
+
+/// Header in which requests authenticated by a session cookie send the CSRF
+/// token of the session, unless their method is safe
+pub const CSRF_TOKEN_HEADER: &str = "x-csrf-token";
 
-use super::jwks::JwksClient;
-use crate::web::ServiceState;
+/// Realm role of the users allowed on the routes of authenticated admins
+pub const ADMIN_ROLE: &str = "admin";
 
 /// JWT Claims structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +48,9 @@ pub struct Claims {
     pub preferred_username: Option<String>,
     /// Email verified
     pub email_verified: Option<bool>,
+    /// Realm roles granted to the subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realm_access: Option<RealmAccess>,
 }
 
 /// Authenticated user information extracted from JWT
@@ -39,38 +59,145 @@ pub struct Claims {
 pub struct AuthUser {
     /// User ID from Keycloak (subject claim)
     pub keycloak_user_id: Uuid,
+    /// Realm which issued the token, `None` for the primary realm
+    pub realm: Option<String>,
     /// Email from token
     pub email: Option<String>,
     /// Username from token
     pub username: Option<String>,
     /// Whether email is verified
     pub email_verified: bool,
+    /// Realm roles from token, empty for sessions
+    pub roles: Vec<String>,
+}
+
+impl AuthUser {
+    #[must_use]
+    pub fn has_role(&self, role: &str) -> bool { self.roles.iter().any(|r| r == role) }
 }
 
 /// JWT authentication middleware
 ///
-/// Validates JWT tokens from the Authorization header and extracts user claims
+/// Validates JWT tokens from the Authorization header and extracts user claims.
+/// WebSocket upgrade requests may pass the token in the `access_token` query
+/// parameter instead, as browsers cannot set headers on the handshake.
+/// Without an Authorization header, the session cookie authenticates the
+/// request if sessions are enabled, mutating requests must then send the CSRF
+/// token of the session in the [`CSRF_TOKEN_HEADER`].
+/// A token is validated by the realm named by its `iss` claim, the user is
+/// then looked up in that realm only.
+/// Invalid tokens are recorded in the audit log, every rejected request is
+/// counted in `auth_failures_total`.
 pub async fn jwt_auth_middleware(
     axum::extract::State(service_state): axum::extract::State<ServiceState>,
     headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, AuthError> {
+    authenticate_request(&service_state, &headers, request, next).await.inspect_err(|err| {
+        business_metrics::record_auth_failure(&service_state.metrics, err.error_code());
+    })
+}
+
+/// Authenticate a request as [`jwt_auth_middleware`] does when it carries an
+/// Authorization header or a session cookie, and pass it on without a user
+/// otherwise
+pub async fn optional_jwt_auth_middleware(
+    axum::extract::State(service_state): axum::extract::State<ServiceState>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, AuthError> {
+    let has_session_cookie = service_state.session_service.is_enabled()
+        && CookieJar::from_headers(&headers)
+            .get(&service_state.session_service.config().cookie_name)
+            .is_some();
+    if !headers.contains_key(header::AUTHORIZATION) && !has_session_cookie {
+        return Ok(next.run(request).await);
+    }
+
+    jwt_auth_middleware(axum::extract::State(service_state), headers, request, next).await
+}
+
+async fn authenticate_request(
+    service_state: &ServiceState,
+    headers: &HeaderMap,
     mut request: Request,
     next: Next,
 ) -> Result<Response, AuthError> {
-    // Extract token from Authorization header
-    let token = extract_token_from_headers(&headers)?;
+    let session_token = service_state
+        .session_service
+        .is_enabled()
+        .then(|| {
+            let cookie_name = &service_state.session_service.config().cookie_name;
+            CookieJar::from_headers(headers).get(cookie_name).map(|c| c.value().to_string())
+        })
+        .flatten();
+
+    // the Authorization header wins over the session cookie
+    let authenticated = match (extract_token_from_headers(headers), session_token) {
+        (Err(AuthError::MissingToken), Some(session_token)) => {
+            authenticate_session(&session_token, request.method(), headers, service_state).await
+        }
+        (Err(AuthError::MissingToken), None) if is_websocket_upgrade(headers) => {
+            authenticate(&extract_token_from_query(request.uri())?, service_state).await
+        }
+        (result, _) => authenticate(result?, service_state).await,
+    };
 
+    let auth_user = match authenticated {
+        Ok(auth_user) => auth_user,
+        Err(AuthError::InvalidToken(reason)) => {
+            let payload = serde_json::json!({
+                "method": request.method().as_str(),
+                "path": request.uri().path(),
+                "reason": reason,
+            });
+            service_state
+                .audit_service
+                .record(
+                    &audit_context(request.extensions()),
+                    AuditAction::TokenRejected,
+                    None,
+                    payload,
+                )
+                .await;
+            return Err(AuthError::InvalidToken(reason));
+        }
+        Err(err) => return Err(err),
+    };
+
+    // Insert AuthUser into request extensions so it can be extracted by handlers
+    drop(request.extensions_mut().insert(auth_user.clone()));
+
+    // and into response extensions for the access log
+    let mut response = next.run(request).await;
+    drop(response.extensions_mut().insert(auth_user));
+
+    Ok(response)
+}
+
+/// Validate `token` with the configured method in the realm which issued it,
+/// returning the user it was issued to
+async fn authenticate(token: &str, service_state: &ServiceState) -> Result<AuthUser, AuthError> {
     tracing::debug!(
         "Authenticating JWT token using {:?} method",
         service_state.jwt_validation_method
     );
 
+    let issuer = unverified_issuer(token)?;
+    let realm = service_state
+        .realms
+        .by_issuer(&issuer)
+        .ok_or_else(|| AuthError::InvalidToken(format!("Unknown issuer '{issuer}'")))?;
+
     // Route to appropriate validation method
     let claims = match service_state.jwt_validation_method {
         mpc_backend_mock_core::config::JwtValidationMethod::Jwks => {
-            validate_token_jwks(token, &service_state.jwks_client).await?
+            validate_token_jwks(token, realm).await?
         }
         mpc_backend_mock_core::config::JwtValidationMethod::Introspection => {
-            validate_token_introspection(token, &service_state).await?
+            validate_token_introspection(token, realm, service_state).await?
         }
     };
 
@@ -85,19 +212,81 @@ pub async fn jwt_auth_middleware(
     // Create AuthUser from claims
     let auth_user = AuthUser {
         keycloak_user_id,
+        realm: realm.user_realm().map(ToString::to_string),
         email: claims.email,
         username: claims.preferred_username,
         email_verified: claims.email_verified.unwrap_or(false),
+        roles: claims.realm_access.map(|realm_access| realm_access.roles).unwrap_or_default(),
     };
 
     tracing::info!("auth_user created: {:?}", &auth_user);
 
-    // Insert AuthUser into request extensions so it can be extracted by handlers
-    drop(request.extensions_mut().insert(auth_user));
+    Ok(auth_user)
+}
+
+/// Admin role middleware
+///
+/// Rejects the requests of users authenticated by [`jwt_auth_middleware`]
+/// without the [`ADMIN_ROLE`] realm role of the primary realm, it must run
+/// after it. The admins of the other realms do not administer the backend.
+pub async fn admin_role_middleware(
+    axum::extract::State(service_state): axum::extract::State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AuthError> {
+    let is_admin = request
+        .extensions()
+        .get::<AuthUser>()
+        .is_some_and(|auth_user| auth_user.realm.is_none() && auth_user.has_role(ADMIN_ROLE));
+    if !is_admin {
+        let err = AuthError::InsufficientPermissions;
+        business_metrics::record_auth_failure(&service_state.metrics, err.error_code());
+        return Err(err);
+    }
 
     Ok(next.run(request).await)
 }
 
+/// Look up the session of the session cookie `token`, returning its user
+///
+/// A request whose method is not safe must send the CSRF token of the session
+/// in the [`CSRF_TOKEN_HEADER`], which a page of another site cannot read.
+async fn authenticate_session(
+    token: &str,
+    method: &Method,
+    headers: &HeaderMap,
+    service_state: &ServiceState,
+) -> Result<AuthUser, AuthError> {
+    let session = service_state
+        .session_service
+        .authenticate(token)
+        .await
+        .map_err(|e| AuthError::SessionError(e.to_string()))?
+        .ok_or_else(|| AuthError::InvalidToken("Session is unknown or expired".to_string()))?;
+
+    if !method.is_safe() {
+        let csrf_token = headers
+            .get(CSRF_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if !verify_csrf_token(&session, csrf_token) {
+            return Err(AuthError::InvalidCsrfToken);
+        }
+    }
+
+    tracing::debug!("Session {} valid for user ID: {}", session.id, session.user_id);
+
+    // sessions are created on login, which only the primary realm offers
+    Ok(AuthUser {
+        keycloak_user_id: session.keycloak_user_id,
+        realm: None,
+        email: Some(session.email),
+        username: None,
+        email_verified: session.is_active,
+        roles: Vec::new(),
+    })
+}
+
 /// Extract bearer token from Authorization header
 fn extract_token_from_headers(headers: &HeaderMap) -> Result<&str, AuthError> {
     let auth_header = headers
@@ -115,13 +304,47 @@ fn extract_token_from_headers(headers: &HeaderMap) -> Result<&str, AuthError> {
     Ok(&auth_header[7..])
 }
 
+/// Extract token from the `access_token` query parameter
+fn extract_token_from_query(uri: &Uri) -> Result<String, AuthError> {
+    let Query(mut params) = Query::<HashMap<String, String>>::try_from_uri(uri)
+        .map_err(|_| AuthError::InvalidToken("Invalid query string".to_string()))?;
+
+    params.remove("access_token").ok_or(AuthError::MissingToken)
+}
+
+/// Whether the request is a WebSocket handshake
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// Issuer in the `iss` claim of `token`, read before the token is validated
+/// to pick the realm validating it
+fn unverified_issuer(token: &str) -> Result<String, AuthError> {
+    #[derive(Deserialize)]
+    struct UnverifiedClaims {
+        iss: Option<String>,
+    }
+
+    let claims = token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok())
+        .and_then(|payload| serde_json::from_slice::<UnverifiedClaims>(&payload).ok())
+        .ok_or_else(|| AuthError::InvalidToken("Failed to decode claims".to_string()))?;
+
+    claims.iss.ok_or_else(|| AuthError::InvalidToken("Token missing 'iss' claim".to_string()))
+}
+
 /// Validate JWT token with JWKS-based signature verification
 ///
 /// This implementation:
-/// - Fetches the public key from Keycloak's JWKS endpoint
+/// - Fetches the public key from the JWKS endpoint of `realm`
 /// - Verifies the token signature with the public key
-/// - Validates expiration and other standard claims
-async fn validate_token_jwks(token: &str, jwks_client: &JwksClient) -> Result<Claims, AuthError> {
+/// - Validates expiration, the issuer of `realm` and other standard claims
+async fn validate_token_jwks(token: &str, realm: &Realm) -> Result<Claims, AuthError> {
     tracing::info!("Validating JWT token: {}", token);
 
     // Decode header to get algorithm and key ID
@@ -136,7 +359,12 @@ async fn validate_token_jwks(token: &str, jwks_client: &JwksClient) -> Result<Cl
     tracing::debug!("Token uses key ID: {}", kid);
 
     // Fetch the JWK for this key ID
-    let jwk = jwks_client.get_jwk(&kid).await.map_err(|e| AuthError::JwksError(e.to_string()))?;
+    let jwk = realm.jwks_client().get_jwk(&kid).await.map_err(|e| match e {
+        JwksError::Unavailable { dependency, retry_after } => {
+            AuthError::DependencyUnavailable(dependency, retry_after)
+        }
+        e => AuthError::JwksError(e.to_string()),
+    })?;
 
     // Convert JWK to DecodingKey
     let decoding_key = DecodingKey::from_jwk(&jwk)
@@ -149,9 +377,7 @@ async fn validate_token_jwks(token: &str, jwks_client: &JwksClient) -> Result<Cl
     validation.validate_nbf = false; // Not Before is optional
                                      // validation.validate_aud = false; // Allow any audience for development
 
-    // Optionally validate issuer and audience
-    // FIXME: should be configurable
-    validation.set_issuer(&["http://localhost:8080/realms/mpc"]);
+    validation.set_issuer(&[realm.issuer()]);
     validation.set_audience(&["account"]);
 
     // Decode and validate token with signature verification
@@ -166,27 +392,38 @@ async fn validate_token_jwks(token: &str, jwks_client: &JwksClient) -> Result<Cl
 /// Validate JWT token using Keycloak's token introspection endpoint
 ///
 /// This implementation:
-/// - Calls Keycloak's introspection endpoint to validate the token server-side
+/// - Calls the introspection endpoint of `realm` to validate the token
+///   server-side
 /// - Checks if the token is active
 /// - Converts the introspection response to Claims structure
+/// - Caches the claims of active tokens in the [`IntrospectionCache`]
+///
+/// [`IntrospectionCache`]: super::IntrospectionCache
 async fn validate_token_introspection(
     token: &str,
+    realm: &Realm,
     service_state: &ServiceState,
 ) -> Result<Claims, AuthError> {
+    if let Some(claims) = service_state.introspection_cache.get(token).await {
+        tracing::debug!("Using cached introspection result for subject: {}", claims.sub);
+        return Ok(claims);
+    }
+
     tracing::info!("Validating JWT token via introspection");
 
-    // Get the Keycloak client from service state
-    let keycloak_client = service_state.keycloak_client.as_ref().ok_or_else(|| {
-        AuthError::InvalidConfiguration(
-            "Introspection validation requires KeycloakClient to be configured".to_string(),
+    // Call introspection endpoint, every failure of which is Keycloak's
+    let introspection = service_state
+        .circuit_breakers
+        .keycloak
+        .call(
+            async {
+                realm.keycloak_client().introspect_token(token).await.map_err(|e| {
+                    AuthError::IntrospectionError(format!("Token introspection failed: {e}"))
+                })
+            },
+            |_| true,
         )
-    })?;
-
-    // Call introspection endpoint
-    let introspection = keycloak_client
-        .introspect_token(token)
-        .await
-        .map_err(|e| AuthError::IntrospectionError(format!("Token introspection failed: {e}")))?;
+        .await?;
 
     tracing::debug!("Introspection response: active={}", introspection.active);
 
@@ -209,10 +446,13 @@ async fn validate_token_introspection(
         email: None,
         preferred_username: introspection.username,
         email_verified: None,
+        realm_access: introspection.realm_access,
     };
 
     tracing::debug!("Token successfully validated via introspection for subject: {}", claims.sub);
 
+    service_state.introspection_cache.insert(token, &claims).await;
+
     Ok(claims)
 }
 
@@ -232,38 +472,88 @@ pub enum AuthError {
     InvalidConfiguration(String),
     /// Token introspection error
     IntrospectionError(String),
+    /// Keycloak is considered down by its circuit breaker, until the cooldown
+    /// is over
+    DependencyUnavailable(&'static str, Duration),
+    /// Session lookup error
+    SessionError(String),
+    /// Missing or wrong CSRF token of a request authenticated by a session
+    /// cookie
+    InvalidCsrfToken,
 }
 
-impl IntoResponse for AuthError {
-    fn into_response(self) -> Response {
-        use zeus_axum::{json_response, response};
+impl From<BreakerOpen> for AuthError {
+    fn from(BreakerOpen { dependency, retry_after }: BreakerOpen) -> Self {
+        Self::DependencyUnavailable(dependency, retry_after)
+    }
+}
 
-        let (status, message) = match self {
-            Self::MissingToken => {
-                (StatusCode::UNAUTHORIZED, "Missing authentication token".to_string())
-            }
-            Self::InvalidToken(msg) => (StatusCode::UNAUTHORIZED, format!("Invalid token: {msg}")),
-            Self::InsufficientPermissions => {
-                (StatusCode::FORBIDDEN, "Insufficient permissions".to_string())
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingToken => write!(f, "Missing authentication token"),
+            Self::InvalidToken(msg) => write!(f, "Invalid token: {msg}"),
+            Self::InsufficientPermissions => write!(f, "Insufficient permissions"),
+            Self::JwksError(msg) => write!(f, "Authentication service error: {msg}"),
+            Self::InvalidConfiguration(msg) => {
+                write!(f, "Authentication configuration error: {msg}")
             }
-            Self::JwksError(msg) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, format!("Authentication service error: {msg}"))
+            Self::IntrospectionError(msg) => write!(f, "Token introspection error: {msg}"),
+            Self::DependencyUnavailable(dependency, _) => {
+                write!(f, "{dependency} is unavailable, try again later")
             }
-            Self::InvalidConfiguration(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Authentication configuration error: {msg}"),
-            ),
-            Self::IntrospectionError(msg) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, format!("Token introspection error: {msg}"))
+            Self::SessionError(msg) => write!(f, "Session lookup error: {msg}"),
+            Self::InvalidCsrfToken => {
+                write!(f, "Missing or invalid CSRF token in the `{CSRF_TOKEN_HEADER}` header")
             }
+        }
+    }
+}
+
+impl ErrorCode for AuthError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::MissingToken => "MISSING_TOKEN",
+            Self::InvalidToken(_) => "INVALID_TOKEN",
+            Self::InsufficientPermissions => "INSUFFICIENT_PERMISSIONS",
+            Self::JwksError(_)
+            | Self::InvalidConfiguration(_)
+            | Self::IntrospectionError(_)
+            | Self::SessionError(_) => "INTERNAL_ERROR",
+            Self::DependencyUnavailable(..) => "DEPENDENCY_UNAVAILABLE",
+            Self::InvalidCsrfToken => "INVALID_CSRF_TOKEN",
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        use zeus_axum::{json_response, response};
+
+        let code = self.error_code();
+        // only the failures of Keycloak known by its circuit breaker are
+        // transient, the other errors persist until the request changes
+        let additional_fields = match self {
+            Self::DependencyUnavailable(_, retry_after) => retry_hint(true, Some(retry_after)),
+            _ => retry_hint(false, None),
+        };
+        let status = match self {
+            Self::MissingToken | Self::InvalidToken(_) => StatusCode::UNAUTHORIZED,
+            Self::InsufficientPermissions | Self::InvalidCsrfToken => StatusCode::FORBIDDEN,
+            Self::JwksError(_)
+            | Self::InvalidConfiguration(_)
+            | Self::IntrospectionError(_)
+            | Self::SessionError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::DependencyUnavailable(..) => StatusCode::SERVICE_UNAVAILABLE,
         };
 
         json_response! {
             status: status,
             error: response::Error {
                 type_: response::ErrorType::Unauthorized,
-                message,
-                additional_fields: indexmap::IndexMap::default(),
+                code: code.to_string(),
+                message: self.to_string(),
+                additional_fields,
             }
         }
     }
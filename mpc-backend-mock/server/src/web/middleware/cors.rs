@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use http::HeaderValue;
+use tower_http::cors::AllowOrigin;
+
+/// Origins allowed to call the API from a browser, any origin when empty
+/// unless credentials are allowed
+///
+/// The origins can be replaced while serving, see [`CorsOrigins::reload`].
+#[derive(Clone, Default)]
+pub struct CorsOrigins {
+    origins: Arc<ArcSwap<Vec<HeaderValue>>>,
+}
+
+impl CorsOrigins {
+    #[must_use]
+    pub fn new(origins: &[String]) -> Self {
+        Self { origins: Arc::new(ArcSwap::from_pointee(parse_origins(origins))) }
+    }
+
+    /// Check the `Origin` of following requests against `origins`
+    pub fn reload(&self, origins: &[String]) {
+        self.origins.store(Arc::new(parse_origins(origins)));
+    }
+
+    /// `allow_origin` of the CORS layer, consulting the current origins on
+    /// every request
+    ///
+    /// With `allow_credentials` no origin is reflected while none is
+    /// configured, any site could otherwise send requests with the cookies of
+    /// the user.
+    #[must_use]
+    pub fn allow_origin(&self, allow_credentials: bool) -> AllowOrigin {
+        let origins = self.clone();
+        AllowOrigin::predicate(move |origin, _| origins.allows(origin, allow_credentials))
+    }
+
+    fn allows(&self, origin: &HeaderValue, allow_credentials: bool) -> bool {
+        let origins = self.origins.load();
+        if origins.is_empty() {
+            return !allow_credentials;
+        }
+        origins.contains(origin)
+    }
+}
+
+fn parse_origins(origins: &[String]) -> Vec<HeaderValue> {
+    origins
+        .iter()
+        .filter_map(|origin| {
+            HeaderValue::from_str(origin)
+                .inspect_err(|_| tracing::warn!("Ignoring invalid CORS origin `{origin}`"))
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload() {
+        let app = HeaderValue::from_static("https://app.example.com");
+        let other = HeaderValue::from_static("https://other.example.com");
+
+        let origins = CorsOrigins::default();
+        assert!(origins.allows(&other, false));
+
+        origins.reload(&["https://app.example.com".to_string()]);
+        assert!(origins.allows(&app, false));
+        assert!(!origins.allows(&other, false));
+    }
+
+    #[test]
+    fn test_no_origin_is_reflected_with_credentials() {
+        let app = HeaderValue::from_static("https://app.example.com");
+
+        let origins = CorsOrigins::default();
+        assert!(!origins.allows(&app, true));
+
+        origins.reload(&["https://app.example.com".to_string()]);
+        assert!(origins.allows(&app, true));
+    }
+}
@@ -0,0 +1,193 @@
+//! Faults injected into API responses, for testing how frontends and SDKs
+//! cope with a misbehaving backend.
+//!
+//! Each [`FaultRule`] whose route is the request path or one of its leading
+//! segments is rolled separately: latency
+//! delays the request and lets it through, an injected error or a dropped
+//! connection replaces the response, and malformed JSON cuts the handler's
+//! response body. Admin routes are never faulted, so that injection can be
+//! turned off through `PUT /api/v1/admin/fault-injection` at any time.
+
+use std::{io, sync::Arc};
+
+use arc_swap::ArcSwap;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use mpc_backend_mock_core::config::{Fault, FaultInjectionConfig, FaultRule};
+use rand::Rng;
+
+use super::is_under_route;
+use crate::web::{controller::Error, ServiceState};
+
+/// Faults injected into the responses, none by default
+///
+/// The rules can be replaced while serving, see [`FaultInjector::reload`],
+/// and turned on or off, see [`FaultInjector::set_enabled`].
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    config: Arc<ArcSwap<FaultInjectionConfig>>,
+}
+
+impl FaultInjector {
+    #[must_use]
+    pub fn new(config: FaultInjectionConfig) -> Self {
+        Self { config: Arc::new(ArcSwap::from_pointee(config)) }
+    }
+
+    /// Faults injected into the following requests
+    #[must_use]
+    pub fn config(&self) -> FaultInjectionConfig {
+        FaultInjectionConfig::clone(&self.config.load())
+    }
+
+    /// Inject the faults of `config` into the following requests
+    pub fn reload(&self, config: FaultInjectionConfig) { self.config.store(Arc::new(config)); }
+
+    /// Turn the injection on or off, keeping the rules
+    pub fn set_enabled(&self, enable: bool) {
+        let _previous =
+            self.config.rcu(|config| FaultInjectionConfig { enable, rules: config.rules.clone() });
+    }
+
+    /// Faults to inject into a request of `path`, in the order of the rules
+    fn roll(&self, path: &str) -> Vec<Fault> {
+        let config = self.config.load();
+        if !config.enable || is_admin_route(path) {
+            return Vec::new();
+        }
+
+        let mut rng = rand::thread_rng();
+        config
+            .rules
+            .iter()
+            .filter(|rule| is_under_route(path, &rule.route))
+            .filter(|rule| rng.gen::<f64>() * 100.0 < rule.percentage)
+            .map(|FaultRule { fault, .. }| *fault)
+            .collect()
+    }
+}
+
+/// Fault injection middleware
+pub async fn fault_injection_middleware(
+    State(service_state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let faults = service_state.fault_injector.roll(request.uri().path());
+
+    let mut malformed_json = false;
+    for fault in faults {
+        tracing::debug!("Injecting {fault:?} into {} {}", request.method(), request.uri());
+        match fault {
+            Fault::Latency(latency) => tokio::time::sleep(latency).await,
+            Fault::Error(status) => {
+                let status =
+                    StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(Error::InjectedFault { status });
+            }
+            Fault::DropConnection => return Ok(dropped_connection()),
+            Fault::MalformedJson => malformed_json = true,
+        }
+    }
+
+    let response = next.run(request).await;
+    if malformed_json {
+        return Ok(cut_json_body(response).await);
+    }
+
+    Ok(response)
+}
+
+/// `/api/<version>/admin/*`
+fn is_admin_route(path: &str) -> bool {
+    path.strip_prefix("/api/").and_then(|path| path.split('/').nth(1)) == Some("admin")
+}
+
+/// Response whose body fails, the server closes the connection once the
+/// headers are sent
+fn dropped_connection() -> Response {
+    let body = Body::from_stream(futures::stream::once(async {
+        Err::<Bytes, _>(io::Error::new(io::ErrorKind::ConnectionAborted, "injected fault"))
+    }));
+
+    Response::new(body)
+}
+
+/// `response` with the first half of its JSON body, other bodies are kept
+async fn cut_json_body(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body.slice(..body.len() / 2),
+        Err(err) => {
+            tracing::warn!("Failed to read the response body to cut, error: {err}");
+            Bytes::new()
+        }
+    };
+    drop(parts.headers.remove(header::CONTENT_LENGTH));
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_roll() {
+        let injector = FaultInjector::new(FaultInjectionConfig {
+            enable: true,
+            rules: vec![
+                FaultRule {
+                    route: "/api".to_string(),
+                    percentage: 100.0,
+                    fault: Fault::Latency(Duration::from_millis(10)),
+                },
+                FaultRule {
+                    route: "/api/v1/bitcoin".to_string(),
+                    percentage: 100.0,
+                    fault: Fault::Error(503),
+                },
+                FaultRule {
+                    route: "/api/v1/solana".to_string(),
+                    percentage: 0.0,
+                    fault: Fault::DropConnection,
+                },
+            ],
+        });
+
+        assert_eq!(
+            injector.roll("/api/v1/bitcoin/utxos"),
+            [Fault::Latency(Duration::from_millis(10)), Fault::Error(503)]
+        );
+        assert_eq!(
+            injector.roll("/api/v1/solana/balance/x"),
+            [Fault::Latency(Duration::from_millis(10))]
+        );
+        assert!(injector.roll("/api/v1/admin/fault-injection").is_empty());
+        // rules match whole path segments
+        assert_eq!(
+            injector.roll("/api/v1/bitcoin-history"),
+            [Fault::Latency(Duration::from_millis(10))]
+        );
+
+        injector.set_enabled(false);
+        assert!(injector.roll("/api/v1/bitcoin/utxos").is_empty());
+        assert_eq!(injector.config().rules.len(), 3);
+    }
+}
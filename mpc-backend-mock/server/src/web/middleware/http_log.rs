@@ -0,0 +1,244 @@
+//! Request and response bodies of chosen routes, logged for debugging
+//! integrations when `web.debug_http` is enabled.
+//!
+//! The values of the redacted fields are replaced in JSON bodies and query
+//! strings, at any depth, before anything is logged. Only bodies of a known
+//! size up to `max_body_bytes` are read, so that event streams, WebSockets and
+//! uploads pass through untouched. The latest exchanges are kept in a ring
+//! buffer, served by `GET /api/v1/admin/http-log`. Admin routes are never
+//! logged.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Instant,
+};
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use mpc_backend_mock_core::config::DebugHttpConfig;
+use serde_json::Value;
+
+use super::is_under_route;
+use crate::{entity::HttpExchange, web::ServiceState};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Logged HTTP exchanges, nothing is logged by default
+#[derive(Clone, Default)]
+pub struct HttpLog {
+    config: Arc<DebugHttpConfig>,
+    exchanges: Arc<Mutex<VecDeque<HttpExchange>>>,
+}
+
+impl HttpLog {
+    #[must_use]
+    pub fn new(config: DebugHttpConfig) -> Self {
+        let exchanges = VecDeque::with_capacity(config.capacity);
+        Self { config: Arc::new(config), exchanges: Arc::new(Mutex::new(exchanges)) }
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool { self.config.enable }
+
+    /// Logged exchanges, newest first
+    #[must_use]
+    pub fn exchanges(&self) -> Vec<HttpExchange> { self.lock().iter().rev().cloned().collect() }
+
+    /// Whether the exchanges of `path` are logged
+    fn logs(&self, path: &str) -> bool {
+        self.config.enable
+            && !is_admin_route(path)
+            && (self.config.routes.is_empty()
+                || self.config.routes.iter().any(|route| is_under_route(path, route)))
+    }
+
+    fn record(&self, exchange: HttpExchange) {
+        let mut exchanges = self.lock();
+        while exchanges.len() >= self.config.capacity.max(1) {
+            let _exchange = exchanges.pop_front();
+        }
+        exchanges.push_back(exchange);
+        drop(exchanges);
+    }
+
+    fn is_redacted(&self, name: &str) -> bool {
+        self.config.redacted_fields.iter().any(|field| field.eq_ignore_ascii_case(name))
+    }
+
+    /// `value` with the values of the redacted fields replaced
+    fn redact(&self, value: Value) -> Value {
+        match value {
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| {
+                        let field = if self.is_redacted(&name) {
+                            Value::String(REDACTED.to_string())
+                        } else {
+                            self.redact(field)
+                        };
+                        (name, field)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|item| self.redact(item)).collect())
+            }
+            value => value,
+        }
+    }
+
+    /// `query` with the values of the redacted parameters replaced
+    fn redact_query(&self, query: &str) -> String {
+        query
+            .split('&')
+            .map(|parameter| match parameter.split_once('=') {
+                Some((name, _)) if self.is_redacted(name) => format!("{name}={REDACTED}"),
+                _ => parameter.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Read the body if it is small enough to log, returns the body to pass on
+    /// and the logged version of it
+    async fn capture(&self, headers: &HeaderMap, body: Body) -> (Body, Option<Value>) {
+        let size = body.size_hint().exact();
+        if size == Some(0) {
+            return (body, None);
+        }
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .unwrap_or("unknown content type")
+            .to_string();
+        let Some(size) = size.and_then(|size| usize::try_from(size).ok()) else {
+            return (body, Some(Value::String(format!("[streamed {content_type}]"))));
+        };
+        if size > self.config.max_body_size {
+            return (body, Some(Value::String(format!("[{size} bytes of {content_type}]"))));
+        }
+
+        let bytes = match axum::body::to_bytes(body, size).await {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                tracing::warn!("Failed to read the body to log, error: {err}");
+                return (Body::empty(), None);
+            }
+        };
+        let logged = if content_type.starts_with("application/json") {
+            serde_json::from_slice(&bytes).map_or_else(
+                |_| Value::String(format!("[{size} bytes of malformed JSON]")),
+                |value| self.redact(value),
+            )
+        } else {
+            Value::String(format!("[{size} bytes of {content_type}]"))
+        };
+
+        (Body::from(bytes), Some(logged))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<HttpExchange>> {
+        // exchanges are pushed and popped whole, a poisoned lock is still usable
+        self.exchanges.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Log the sanitized bodies of the requests to the routes of [`HttpLog`]
+pub async fn http_log_middleware(
+    State(service_state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let http_log = &service_state.http_log;
+    if !http_log.logs(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let started_at = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().map(|query| http_log.redact_query(query));
+
+    let (parts, body) = request.into_parts();
+    let (body, request_body) = http_log.capture(&parts.headers, body).await;
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let (parts, body) = response.into_parts();
+    let (body, response_body) = http_log.capture(&parts.headers, body).await;
+    let exchange = HttpExchange {
+        recorded_at: chrono::Utc::now(),
+        method,
+        path,
+        query,
+        status: parts.status.as_u16(),
+        duration_ms: u64::try_from(started_at.elapsed().as_millis()).unwrap_or(u64::MAX),
+        request_body,
+        response_body,
+    };
+    tracing::debug!(?exchange, "HTTP exchange");
+    http_log.record(exchange);
+
+    Response::from_parts(parts, body)
+}
+
+/// `/api/<version>/admin/*`
+fn is_admin_route(path: &str) -> bool {
+    path.strip_prefix("/api/").and_then(|path| path.split('/').nth(1)) == Some("admin")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn http_log() -> HttpLog {
+        HttpLog::new(DebugHttpConfig {
+            enable: true,
+            routes: vec!["/api/v1/auth".to_string()],
+            redacted_fields: vec!["password".to_string(), "access_token".to_string()],
+            capacity: 2,
+            max_body_size: 1024,
+        })
+    }
+
+    #[test]
+    fn test_redact() {
+        let http_log = http_log();
+
+        assert_eq!(
+            http_log.redact(json!({
+                "email": "user@example.com",
+                "Password": "secret",
+                "sessions": [{ "access_token": "eyJ", "expires_in": 300 }],
+            })),
+            json!({
+                "email": "user@example.com",
+                "Password": REDACTED,
+                "sessions": [{ "access_token": REDACTED, "expires_in": 300 }],
+            })
+        );
+        assert_eq!(
+            http_log.redact_query("access_token=eyJ&page=2"),
+            format!("access_token={REDACTED}&page=2")
+        );
+    }
+
+    #[test]
+    fn test_logs() {
+        let http_log = http_log();
+
+        assert!(http_log.logs("/api/v1/auth/login"));
+        assert!(!http_log.logs("/api/v1/authorizations"));
+        assert!(!http_log.logs("/api/v1/users"));
+        assert!(!http_log.logs("/api/v1/admin/http-log"));
+        assert!(!HttpLog::default().logs("/api/v1/auth/login"));
+    }
+}
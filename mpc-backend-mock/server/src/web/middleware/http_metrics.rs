@@ -0,0 +1,406 @@
+//! HTTP request latency metrics and the latency SLO report built from them.
+//!
+//! Every request routed by the API router is observed in the
+//! `http_request_duration_seconds` histogram. Since the histogram is
+//! cumulative, snapshots of it are taken every [`SNAPSHOT_INTERVAL`] and the
+//! report is the difference between now and the oldest snapshot in
+//! [`SLO_WINDOW`].
+//!
+//! A request carrying a sampled W3C `traceparent`, i.e. traced by an
+//! OpenTelemetry client or proxy in front of the server, is also recorded as
+//! the exemplar of its bucket. Exemplars are exported when the metrics are
+//! scraped in the OpenMetrics format, linking a latency spike to its trace.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{core::Collector, proto::Metric, HistogramOpts, HistogramVec, DEFAULT_BUCKETS};
+use snafu::ResultExt;
+use zeus_metrics::{DefaultMetrics, Exemplars, Metrics as _};
+
+use crate::{
+    entity::{RouteSlo, SloReport},
+    error::{self, Result},
+    web::ServiceState,
+};
+
+/// How often the histogram is snapshotted
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Length of the rolling window the SLO report covers
+pub const SLO_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+
+const TRACEPARENT: &str = "traceparent";
+
+/// Requests and errors of one route, buckets are cumulative as in the
+/// histogram
+#[derive(Clone, Debug, Default)]
+struct RouteCounts {
+    count: u64,
+    errors: u64,
+    buckets: Vec<u64>,
+}
+
+impl RouteCounts {
+    fn saturating_sub(&self, baseline: &Self) -> Self {
+        let buckets = self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| count.saturating_sub(baseline.buckets.get(i).copied().unwrap_or(0)))
+            .collect();
+
+        Self {
+            count: self.count.saturating_sub(baseline.count),
+            errors: self.errors.saturating_sub(baseline.errors),
+            buckets,
+        }
+    }
+}
+
+/// Counts per `(method, route)`
+type RouteCountsMap = BTreeMap<(String, String), RouteCounts>;
+
+#[derive(Debug)]
+struct Snapshot {
+    taken_at: Instant,
+    routes: RouteCountsMap,
+}
+
+/// HTTP request latency metrics
+#[derive(Clone, Debug)]
+pub struct HttpMetrics {
+    requests: HistogramVec,
+    exemplars: Exemplars,
+    snapshots: Arc<Mutex<VecDeque<Snapshot>>>,
+}
+
+impl HttpMetrics {
+    /// # Errors
+    ///
+    /// Returns an error if the histogram cannot be registered
+    pub fn new(metrics: &DefaultMetrics) -> Result<Self> {
+        let requests = HistogramVec::new(
+            HistogramOpts::new(REQUEST_DURATION, "HTTP request latency in seconds"),
+            &["method", "route", "status"],
+        )
+        .context(error::CreateHttpMetricsSnafu)?;
+
+        metrics.register(Box::new(requests.clone()))?;
+
+        Ok(Self {
+            requests,
+            exemplars: metrics.exemplars().unwrap_or_default(),
+            snapshots: Arc::default(),
+        })
+    }
+
+    fn observe(
+        &self,
+        method: &str,
+        route: &str,
+        status: StatusCode,
+        elapsed: Duration,
+        trace_id: Option<&str>,
+    ) {
+        let seconds = elapsed.as_secs_f64();
+        self.requests.with_label_values(&[method, route, status.as_str()]).observe(seconds);
+
+        if let Some(trace_id) = trace_id {
+            self.exemplars.observe(
+                REQUEST_DURATION,
+                &[("method", method), ("route", route), ("status", status.as_str())],
+                DEFAULT_BUCKETS,
+                seconds,
+                trace_id,
+            );
+        }
+    }
+
+    /// Snapshot the histogram every [`SNAPSHOT_INTERVAL`], forever
+    pub async fn record_snapshots(self) {
+        let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+        loop {
+            let _instant = interval.tick().await;
+            self.record_snapshot();
+        }
+    }
+
+    fn record_snapshot(&self) {
+        let now = Instant::now();
+        let snapshot = Snapshot { taken_at: now, routes: self.route_counts() };
+
+        let window_start = now.checked_sub(SLO_WINDOW);
+        let mut snapshots = self.lock();
+        snapshots.push_back(snapshot);
+
+        // keep the newest snapshot taken before the window as the baseline
+        while snapshots.get(1).is_some_and(|snapshot| {
+            window_start.is_some_and(|window_start| snapshot.taken_at <= window_start)
+        }) {
+            let _snapshot = snapshots.pop_front();
+        }
+        drop(snapshots);
+    }
+
+    /// Latency percentiles and error rate per route over the rolling window
+    #[must_use]
+    pub fn slo_report(&self) -> SloReport {
+        let current = self.route_counts();
+        let (baseline, window) = self.lock().front().map_or_else(
+            || (RouteCountsMap::new(), Duration::ZERO),
+            |snapshot| (snapshot.routes.clone(), snapshot.taken_at.elapsed()),
+        );
+        let zero = RouteCounts::default();
+
+        let routes = current
+            .into_iter()
+            .filter_map(|(key, counts)| {
+                let counts = counts.saturating_sub(baseline.get(&key).unwrap_or(&zero));
+                if counts.count == 0 {
+                    return None;
+                }
+
+                let percentile_ms = |q| {
+                    quantile(DEFAULT_BUCKETS, &counts.buckets, counts.count, q)
+                        .map(|seconds| seconds * 1000.0)
+                };
+                Some(RouteSlo {
+                    p50_ms: percentile_ms(0.50),
+                    p95_ms: percentile_ms(0.95),
+                    p99_ms: percentile_ms(0.99),
+                    error_rate: ratio(counts.errors, counts.count),
+                    request_count: counts.count,
+                    method: key.0,
+                    route: key.1,
+                })
+            })
+            .collect();
+
+        SloReport { window_seconds: window.as_secs(), routes }
+    }
+
+    fn route_counts(&self) -> RouteCountsMap {
+        let mut routes = RouteCountsMap::new();
+        for family in self.requests.collect() {
+            for metric in family.get_metric() {
+                let histogram = metric.get_histogram();
+                let counts = routes
+                    .entry((
+                        label(metric, "method").to_string(),
+                        label(metric, "route").to_string(),
+                    ))
+                    .or_default();
+
+                counts.count += histogram.get_sample_count();
+                if label(metric, "status").starts_with('5') {
+                    counts.errors += histogram.get_sample_count();
+                }
+
+                let buckets = histogram.get_bucket();
+                counts.buckets.resize(buckets.len(), 0);
+                for (total, bucket) in counts.buckets.iter_mut().zip(buckets) {
+                    *total += bucket.get_cumulative_count();
+                }
+            }
+        }
+        routes
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Snapshot>> {
+        // snapshots are pushed and popped whole, a poisoned lock is still usable
+        self.snapshots.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Record the latency of every routed request in [`HttpMetrics`]
+pub async fn http_metrics_middleware(
+    State(service_state): State<ServiceState>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let route = matched_path.as_ref().map_or("unmatched", MatchedPath::as_str).to_string();
+    let trace_id = sampled_trace_id(request.headers()).map(ToString::to_string);
+
+    let started_at = Instant::now();
+    let response = next.run(request).await;
+
+    service_state.http_metrics.observe(
+        method.as_str(),
+        &route,
+        response.status(),
+        started_at.elapsed(),
+        trace_id.as_deref(),
+    );
+
+    response
+}
+
+/// Trace ID of a sampled W3C `traceparent`,
+/// `<version>-<trace-id>-<parent-id>-<flags>`
+fn sampled_trace_id(headers: &HeaderMap) -> Option<&str> {
+    let traceparent = headers.get(TRACEPARENT)?.to_str().ok()?;
+    let mut fields = traceparent.trim().split('-');
+    let (version, trace_id, parent_id, flags) =
+        (fields.next()?, fields.next()?, fields.next()?, fields.next()?);
+
+    let is_hex = |field: &str, len: usize| {
+        field.len() == len
+            && field.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    };
+    let is_zero = |field: &str| field.bytes().all(|b| b == b'0');
+    if !is_hex(version, 2)
+        || version == "ff"
+        || (version == "00" && fields.next().is_some())
+        || !is_hex(trace_id, 32)
+        || is_zero(trace_id)
+        || !is_hex(parent_id, 16)
+        || is_zero(parent_id)
+        || !is_hex(flags, 2)
+    {
+        return None;
+    }
+
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    (flags & 0x01 == 0x01).then_some(trace_id)
+}
+
+fn label<'a>(metric: &'a Metric, name: &str) -> &'a str {
+    metric
+        .get_label()
+        .iter()
+        .find(|label| label.get_name() == name)
+        .map_or("", |label| label.get_value())
+}
+
+// SAFETY: allow: request counts are far below 2^52, the conversion is exact
+#[allow(clippy::cast_precision_loss)]
+const fn as_f64(value: u64) -> f64 { value as f64 }
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        as_f64(numerator) / as_f64(denominator)
+    }
+}
+
+/// Estimate the `q`-quantile from cumulative bucket counts, interpolating
+/// linearly inside the bucket like Prometheus' `histogram_quantile`
+fn quantile(bounds: &[f64], buckets: &[u64], count: u64, q: f64) -> Option<f64> {
+    if count == 0 {
+        return None;
+    }
+
+    let rank = q * as_f64(count);
+    let mut lower_bound = 0.0;
+    let mut lower_count = 0;
+    for (&upper_bound, &upper_count) in bounds.iter().zip(buckets) {
+        if as_f64(upper_count) >= rank {
+            let fraction = (rank - as_f64(lower_count)) / as_f64(upper_count - lower_count);
+            return Some((upper_bound - lower_bound).mul_add(fraction, lower_bound));
+        }
+        lower_bound = upper_bound;
+        lower_count = upper_count;
+    }
+
+    // the quantile falls into the `+Inf` bucket
+    Some(lower_bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantile() {
+        let bounds = [0.1, 0.2, 0.4];
+
+        assert_eq!(quantile(&bounds, &[0, 0, 0], 0, 0.5), None);
+
+        // 10 requests, all between 0.1s and 0.2s
+        let p50 = quantile(&bounds, &[0, 10, 10], 10, 0.5).unwrap();
+        assert!((p50 - 0.15).abs() < 1e-9);
+
+        // 1 of 100 requests is slower than the largest bucket
+        let p99 = quantile(&bounds, &[50, 90, 99], 100, 0.995).unwrap();
+        assert!((p99 - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slo_report_is_relative_to_baseline() {
+        let metrics = HttpMetrics::new(&DefaultMetrics::new()).unwrap();
+        metrics.observe("GET", "/api/v1/users", StatusCode::OK, Duration::from_millis(3), None);
+        metrics.record_snapshot();
+
+        metrics.observe("GET", "/api/v1/users", StatusCode::OK, Duration::from_millis(3), None);
+        metrics.observe(
+            "GET",
+            "/api/v1/users",
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Duration::from_millis(3),
+            None,
+        );
+
+        let report = metrics.slo_report();
+        assert_eq!(report.routes.len(), 1);
+        let route = &report.routes[0];
+        assert_eq!(route.request_count, 2);
+        assert!((route.error_rate - 0.5).abs() < 1e-9);
+        assert!(route.p50_ms.unwrap() <= 5.0);
+    }
+
+    #[test]
+    fn test_sampled_trace_id() {
+        let trace_id = |traceparent: &'static str| {
+            let mut headers = HeaderMap::new();
+            drop(headers.insert(TRACEPARENT, traceparent.parse().unwrap()));
+            sampled_trace_id(&headers).map(ToString::to_string)
+        };
+
+        assert_eq!(
+            trace_id("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        // not sampled
+        assert_eq!(trace_id("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00"), None);
+        assert_eq!(trace_id("00-00000000000000000000000000000000-00f067aa0ba902b7-01"), None);
+        assert_eq!(trace_id("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01"), None);
+        assert_eq!(trace_id("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"), None);
+        assert_eq!(trace_id("00-4bf92f3577b34da6a3ce929d0e0e4736-01"), None);
+    }
+
+    #[test]
+    fn test_exemplar_of_traced_request() {
+        let default_metrics = DefaultMetrics::new();
+        let metrics = HttpMetrics::new(&default_metrics).unwrap();
+        metrics.observe(
+            "GET",
+            "/api/v1/users",
+            StatusCode::OK,
+            Duration::from_millis(3),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736"),
+        );
+
+        let encoded = zeus_metrics::encode_openmetrics(
+            &zeus_metrics::Metrics::gather(&default_metrics),
+            zeus_metrics::Metrics::exemplars(&default_metrics).as_ref(),
+        );
+        assert!(
+            encoded.contains("le=\"0.005\"} 1 # {trace_id=\"4bf92f3577b34da6a3ce929d0e0e4736\"}")
+        );
+    }
+}
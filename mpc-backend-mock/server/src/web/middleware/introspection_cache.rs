@@ -0,0 +1,121 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+use super::auth::Claims;
+use crate::store::Store;
+
+/// Claims of tokens introspected as active, so that Keycloak is not asked on
+/// every request
+///
+/// Entries are keyed by the SHA-256 of the token, tokens are never stored. An
+/// entry lives for the configured TTL, or until the token expires if that is
+/// sooner, so a revoked token is accepted for at most the TTL.
+#[derive(Clone)]
+pub struct IntrospectionCache {
+    store: Arc<dyn Store>,
+    ttl: Duration,
+}
+
+impl IntrospectionCache {
+    /// A zero `ttl` disables the cache
+    #[must_use]
+    pub fn new(store: Arc<dyn Store>, ttl: Duration) -> Self { Self { store, ttl } }
+
+    pub(super) async fn get(&self, token: &str) -> Option<Claims> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+
+        let data = match self.store.get(&cache_key(token)).await {
+            Ok(data) => data?,
+            Err(err) => {
+                tracing::warn!("Failed to read cached introspection result, error: {err}");
+                return None;
+            }
+        };
+
+        serde_json::from_slice::<Claims>(&data)
+            .inspect_err(|err| tracing::warn!("Ignoring malformed introspection result: {err}"))
+            .ok()
+            .filter(|claims| claims.exp > Utc::now().timestamp())
+    }
+
+    pub(super) async fn insert(&self, token: &str, claims: &Claims) {
+        let expires_in =
+            u64::try_from(claims.exp.saturating_sub(Utc::now().timestamp())).unwrap_or(0);
+        let ttl = self.ttl.min(Duration::from_secs(expires_in));
+        if ttl.is_zero() {
+            return;
+        }
+
+        let data = match serde_json::to_vec(claims) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::warn!("Failed to serialize introspection result, error: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = self.store.set(&cache_key(token), &data, ttl).await {
+            tracing::warn!("Failed to cache introspection result, error: {err}");
+        }
+    }
+}
+
+fn cache_key(token: &str) -> String {
+    format!("introspection:{}", hex::encode(Sha256::digest(token.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    fn claims(exp: i64) -> Claims {
+        Claims {
+            sub: "7f1b2c3d-0000-0000-0000-000000000000".to_string(),
+            iat: 0,
+            exp,
+            aud: None,
+            iss: None,
+            email: None,
+            preferred_username: Some("user".to_string()),
+            email_verified: None,
+            realm_access: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_until_ttl() {
+        let cache =
+            IntrospectionCache::new(Arc::new(MemoryStore::default()), Duration::from_secs(30));
+        let exp = Utc::now().timestamp() + 300;
+
+        assert!(cache.get("token").await.is_none());
+        cache.insert("token", &claims(exp)).await;
+
+        let cached = cache.get("token").await.unwrap();
+        assert_eq!(cached.exp, exp);
+        assert_eq!(cached.preferred_username.as_deref(), Some("user"));
+        assert!(cache.get("other-token").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_not_cached() {
+        let cache =
+            IntrospectionCache::new(Arc::new(MemoryStore::default()), Duration::from_secs(30));
+
+        cache.insert("token", &claims(Utc::now().timestamp() - 1)).await;
+        assert!(cache.get("token").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let cache = IntrospectionCache::new(Arc::new(MemoryStore::default()), Duration::ZERO);
+
+        cache.insert("token", &claims(Utc::now().timestamp() + 300)).await;
+        assert!(cache.get("token").await.is_none());
+    }
+}
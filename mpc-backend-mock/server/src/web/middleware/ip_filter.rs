@@ -0,0 +1,72 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use mpc_backend_mock_core::config::IpAccessList;
+
+use crate::web::{controller::Error, ServiceState};
+
+/// Client IP allow/deny list enforced on admin routes
+#[derive(Clone, Debug)]
+pub struct AdminIpFilter {
+    trusted_proxies: Arc<[IpNet]>,
+    access_list: Arc<IpAccessList>,
+}
+
+impl AdminIpFilter {
+    #[must_use]
+    pub fn new(trusted_proxies: Vec<IpNet>, access_list: IpAccessList) -> Self {
+        Self { trusted_proxies: trusted_proxies.into(), access_list: Arc::new(access_list) }
+    }
+
+    /// Resolve the client IP, only honoring forwarding headers set by
+    /// trusted proxies
+    #[must_use]
+    pub fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let ConnectInfo(addr) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+        Some(zeus_axum::get_trusted_request_ip(request.headers(), addr, &self.trusted_proxies))
+    }
+
+    #[must_use]
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool { self.access_list.is_allowed(ip) }
+}
+
+/// Client IP of the request, resolved with the trusted proxy configuration
+///
+/// Set by [`request_id_middleware`](super::request_id_middleware) on every
+/// request it is resolved for, and always on admin routes.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+/// Admin IP filter middleware
+///
+/// Rejects requests whose client IP is not in the configured admin allowlist.
+/// This is enforced independently of authentication.
+pub async fn admin_ip_filter_middleware(
+    State(service_state): State<ServiceState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let filter = &service_state.admin_ip_filter;
+
+    let Some(ip) = filter.client_ip(&request) else {
+        tracing::warn!("Rejecting admin request without connection info");
+        return Err(Error::AdminAccessDenied { ip: None });
+    };
+
+    if !filter.is_allowed(&ip) {
+        tracing::warn!("Rejecting admin request from {ip}");
+        return Err(Error::AdminAccessDenied { ip: Some(ip) });
+    }
+
+    drop(request.extensions_mut().insert(ClientIp(ip)));
+
+    Ok(next.run(request).await)
+}
@@ -1,21 +1,48 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
 
+use arc_swap::ArcSwap;
 use jsonwebtoken::jwk::{Jwk, JwkSet};
+use mpc_backend_mock_core::config::CircuitBreakerConfig;
 use snafu::{ResultExt, Snafu};
-use tokio::sync::RwLock;
+
+use crate::{
+    circuit_breaker::{BreakerOpen, CircuitBreaker},
+    dependency_metrics::DependencyMetrics,
+    store::Store,
+};
+
+/// Attempts to fetch the JWKS before giving up
+const FETCH_ATTEMPTS: u32 = 3;
+
+/// Wait before the second attempt, doubled after every failed attempt
+const FETCH_INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Shortest time between two refreshes caused by an unknown `kid`, so tokens
+/// with made-up key ids cannot make every request hit Keycloak
+const MIN_UNKNOWN_KID_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
 
 /// JWKS client for fetching and caching public keys from Keycloak
+///
+/// Keys are cached in the [`Store`], so replicas sharing a Redis fetch them
+/// once. A failing store only costs a fetch from Keycloak.
+///
+/// A `kid` missing from the cached keys triggers one immediate refresh, so a
+/// key rotated in Keycloak is picked up without waiting for the cache to
+/// expire. When Keycloak cannot be reached, the last fetched keys keep being
+/// served, and fetches are skipped while the circuit breaker is open.
 #[derive(Clone)]
 pub struct JwksClient {
     jwks_url: String,
     http_client: reqwest::Client,
-    cache: Arc<RwLock<JwksCache>>,
-}
-
-/// Cached JWKS data
-struct JwksCache {
-    jwks: Option<JwkSet>,
-    last_fetch: Option<std::time::Instant>,
+    store: Arc<dyn Store>,
+    cache_ttl: Arc<ArcSwap<Duration>>,
+    circuit_breaker: CircuitBreaker,
+    dependency_metrics: DependencyMetrics,
+    last_fetched: Arc<RwLock<Option<JwkSet>>>,
+    last_unknown_kid_refresh: Arc<Mutex<Option<Instant>>>,
 }
 
 impl JwksClient {
@@ -24,7 +51,14 @@ impl JwksClient {
     /// # Arguments
     /// * `keycloak_url` - Base Keycloak URL (e.g., <http://localhost:8080>)
     /// * `realm` - Keycloak realm name (e.g., "mpc")
-    pub fn new(keycloak_url: &str, realm: &str) -> Result<Self, JwksError> {
+    /// * `store` - Store the fetched keys are cached in
+    /// * `cache_ttl` - How long fetched keys are served from the cache
+    pub fn new(
+        keycloak_url: &str,
+        realm: &str,
+        store: Arc<dyn Store>,
+        cache_ttl: Duration,
+    ) -> Result<Self, JwksError> {
         let jwks_url = format!("{keycloak_url}/realms/{realm}/protocol/openid-connect/certs");
 
         let http_client = reqwest::Client::builder()
@@ -35,49 +69,130 @@ impl JwksClient {
         Ok(Self {
             jwks_url,
             http_client,
-            cache: Arc::new(RwLock::new(JwksCache { jwks: None, last_fetch: None })),
+            store,
+            cache_ttl: Arc::new(ArcSwap::from_pointee(cache_ttl)),
+            circuit_breaker: CircuitBreaker::new("JWKS", &CircuitBreakerConfig::default()),
+            dependency_metrics: DependencyMetrics::default(),
+            last_fetched: Arc::new(RwLock::new(None)),
+            last_unknown_kid_refresh: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Fetch the keys through `circuit_breaker` instead of a breaker with the
+    /// default configuration
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Record the latency of every fetch attempt in `dependency_metrics`
+    #[must_use]
+    pub fn with_dependency_metrics(mut self, dependency_metrics: DependencyMetrics) -> Self {
+        self.dependency_metrics = dependency_metrics;
+        self
+    }
+
+    /// Cache keys fetched from now on for `cache_ttl`, keys already cached
+    /// keep their expiry
+    pub fn reload_cache_ttl(&self, cache_ttl: Duration) {
+        self.cache_ttl.store(Arc::new(cache_ttl));
+    }
+
     /// Get a JWK by key ID (kid)
     ///
-    /// This method will fetch from cache if available and fresh (< 5 minutes
-    /// old), otherwise it will fetch fresh JWKS from Keycloak
+    /// This method will fetch from cache if available and fresh, otherwise it
+    /// will fetch fresh JWKS from Keycloak
     pub async fn get_jwk(&self, kid: &str) -> Result<Jwk, JwksError> {
+        let key_not_found = || JwksError::KeyNotFound { kid: kid.to_string() };
+
         // Check cache first
-        let cache = self.cache.read().await;
-        if let Some(ref jwks) = cache.jwks {
-            if let Some(last_fetch) = cache.last_fetch {
-                // Cache is valid for 5 minutes
-                if last_fetch.elapsed() < Duration::from_secs(300) {
-                    if let Some(jwk) = jwks.find(kid) {
-                        tracing::debug!("Found JWK in cache for kid: {}", kid);
-                        return Ok(jwk.clone());
-                    }
-                }
+        let cached = self.cached_jwks().await;
+        if let Some(jwk) = cached.as_ref().and_then(|jwks| jwks.find(kid)) {
+            tracing::debug!("Found JWK in cache for kid: {}", kid);
+            return Ok(jwk.clone());
+        }
+
+        // The key may have been rotated since the keys were cached
+        if cached.is_some() {
+            if !self.begin_unknown_kid_refresh() {
+                return Err(key_not_found());
             }
+            tracing::info!("Key with kid '{kid}' not in cached JWKS, refreshing");
         }
-        drop(cache);
 
         // Fetch fresh JWKS
+        let jwks = match self.fetch_and_cache().await {
+            Ok(jwks) => jwks,
+            Err(err) => {
+                let last_fetched =
+                    self.last_fetched.read().unwrap_or_else(PoisonError::into_inner).clone();
+                let Some(jwks) = last_fetched else { return Err(err) };
+                tracing::warn!("Serving last fetched JWKS, error: {err}");
+                jwks
+            }
+        };
+
+        jwks.find(kid).cloned().ok_or_else(key_not_found)
+    }
+
+    /// Force refresh the JWKS cache
+    pub async fn refresh(&self) -> Result<(), JwksError> { self.fetch_and_cache().await.map(drop) }
+
+    /// Whether a refresh for an unknown `kid` may start now, recording it if
+    /// so
+    fn begin_unknown_kid_refresh(&self) -> bool {
+        let mut last_refresh =
+            self.last_unknown_kid_refresh.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if last_refresh.is_some_and(|at| at.elapsed() < MIN_UNKNOWN_KID_REFRESH_INTERVAL) {
+            return false;
+        }
+        *last_refresh = Some(Instant::now());
+        true
+    }
+
+    async fn fetch_and_cache(&self) -> Result<JwkSet, JwksError> {
         tracing::info!("Fetching fresh JWKS from {}", self.jwks_url);
-        let jwks = self.fetch_jwks().await?;
+        let jwks = self
+            .circuit_breaker
+            .call(self.fetch_jwks_with_retry(), JwksError::is_transient)
+            .await?;
+
+        self.cache_jwks(&jwks).await;
+        *self.last_fetched.write().unwrap_or_else(PoisonError::into_inner) = Some(jwks.clone());
+
+        Ok(jwks)
+    }
 
-        // Find the key
-        let jwk = jwks.find(kid).ok_or(JwksError::KeyNotFound { kid: kid.to_string() })?;
-        let jwk_clone = jwk.clone();
+    /// Fetch JWKS from Keycloak, retrying with exponential backoff while
+    /// Keycloak is unreachable or failing
+    async fn fetch_jwks_with_retry(&self) -> Result<JwkSet, JwksError> {
+        let mut backoff = FETCH_INITIAL_BACKOFF;
 
-        // Update cache
-        let mut cache = self.cache.write().await;
-        cache.jwks = Some(jwks);
-        cache.last_fetch = Some(std::time::Instant::now());
-        drop(cache);
+        for attempt in 1..FETCH_ATTEMPTS {
+            match self.fetch_jwks().await {
+                Err(err) if err.is_transient() => {
+                    tracing::warn!(
+                        "Failed to fetch JWKS (attempt {attempt}/{FETCH_ATTEMPTS}), retrying in \
+                         {backoff:?}, error: {err}"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
 
-        Ok(jwk_clone)
+        self.fetch_jwks().await
     }
 
     /// Fetch JWKS from Keycloak
     async fn fetch_jwks(&self) -> Result<JwkSet, JwksError> {
+        self.dependency_metrics.observe("keycloak", "fetch_jwks", self.request_jwks()).await
+    }
+
+    async fn request_jwks(&self) -> Result<JwkSet, JwksError> {
         let response = self.http_client.get(&self.jwks_url).send().await.context(FetchJwksSnafu)?;
 
         if !response.status().is_success() {
@@ -93,16 +208,35 @@ impl JwksClient {
         Ok(jwks)
     }
 
-    /// Force refresh the JWKS cache
-    pub async fn refresh(&self) -> Result<(), JwksError> {
-        let jwks = self.fetch_jwks().await?;
+    fn cache_key(&self) -> String { format!("jwks:{}", self.jwks_url) }
 
-        let mut cache = self.cache.write().await;
-        cache.jwks = Some(jwks);
-        cache.last_fetch = Some(std::time::Instant::now());
-        drop(cache);
+    async fn cached_jwks(&self) -> Option<JwkSet> {
+        match self.store.get(&self.cache_key()).await {
+            Ok(data) => data.and_then(|data| {
+                serde_json::from_slice(&data)
+                    .inspect_err(|err| tracing::warn!("Ignoring malformed cached JWKS: {err}"))
+                    .ok()
+            }),
+            Err(err) => {
+                tracing::warn!("Failed to read cached JWKS, error: {err}");
+                None
+            }
+        }
+    }
+
+    async fn cache_jwks(&self, jwks: &JwkSet) {
+        let data = match serde_json::to_vec(jwks) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::warn!("Failed to serialize JWKS, error: {err}");
+                return;
+            }
+        };
 
-        Ok(())
+        let cache_ttl = **self.cache_ttl.load();
+        if let Err(err) = self.store.set(&self.cache_key(), &data, cache_ttl).await {
+            tracing::warn!("Failed to cache JWKS, error: {err}");
+        }
     }
 }
 
@@ -128,6 +262,30 @@ pub enum JwksError {
     /// Key not found in JWKS
     #[snafu(display("Key with kid '{kid}' not found in JWKS"))]
     KeyNotFound { kid: String },
+
+    /// Keycloak is considered down, the fetch was not attempted
+    #[snafu(display("{dependency} is unavailable, JWKS fetch skipped"))]
+    Unavailable { dependency: &'static str, retry_after: Duration },
+}
+
+impl From<BreakerOpen> for JwksError {
+    fn from(BreakerOpen { dependency, retry_after }: BreakerOpen) -> Self {
+        Self::Unavailable { dependency, retry_after }
+    }
+}
+
+impl JwksError {
+    /// Whether fetching again later may succeed
+    const fn is_transient(&self) -> bool {
+        match self {
+            Self::FetchJwks { .. } => true,
+            Self::FetchFailed { status, .. } => *status >= 500,
+            Self::HttpClient { .. }
+            | Self::ParseJwks { .. }
+            | Self::KeyNotFound { .. }
+            | Self::Unavailable { .. } => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -136,11 +294,39 @@ mod tests {
 
     #[test]
     fn test_jwks_url_construction() {
-        let client =
-            JwksClient::new("http://localhost:8080", "mpc").expect("Failed to create client");
+        let client = JwksClient::new(
+            "http://localhost:8080",
+            "mpc",
+            Arc::new(crate::store::MemoryStore::default()),
+            Duration::from_secs(300),
+        )
+        .expect("Failed to create client");
         assert_eq!(
             client.jwks_url,
             "http://localhost:8080/realms/mpc/protocol/openid-connect/certs"
         );
     }
+
+    #[test]
+    fn test_unknown_kid_refresh_is_throttled() {
+        let client = JwksClient::new(
+            "http://localhost:8080",
+            "mpc",
+            Arc::new(crate::store::MemoryStore::default()),
+            Duration::from_secs(300),
+        )
+        .expect("Failed to create client");
+
+        assert!(client.begin_unknown_kid_refresh());
+        assert!(!client.begin_unknown_kid_refresh());
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        let failed = |status| JwksError::FetchFailed { status, url: String::new() };
+
+        assert!(failed(503).is_transient());
+        assert!(!failed(404).is_transient());
+        assert!(!JwksError::KeyNotFound { kid: "kid".to_string() }.is_transient());
+    }
 }
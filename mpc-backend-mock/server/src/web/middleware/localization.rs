@@ -0,0 +1,189 @@
+//! Translation of error messages into the language asked for by the
+//! `Accept-Language` header.
+//!
+//! Error responses carry a stable `code`, the message of a code is looked up in
+//! the catalog of the negotiated language. English is the language the
+//! messages are written in, it is used when no catalog matches or a catalog
+//! misses a code.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use axum::{
+    body::{self, Body},
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+/// Built-in catalogs, keyed by language tag
+const BUILT_IN_CATALOGS: [(&str, &str); 1] =
+    [("zh-TW", include_str!("../../../locales/zh-TW.yaml"))];
+
+static CATALOGS: LazyLock<MessageCatalogs> = LazyLock::new(|| {
+    MessageCatalogs::from_yaml(&BUILT_IN_CATALOGS).expect("built-in catalogs are valid; qed")
+});
+
+/// Error messages keyed by error code, per language
+#[derive(Debug, Default)]
+pub struct MessageCatalogs {
+    catalogs: Vec<(String, HashMap<String, String>)>,
+}
+
+impl MessageCatalogs {
+    /// Parse catalogs given as `(language tag, YAML mapping of code to
+    /// message)`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a catalog is not a mapping of strings
+    pub fn from_yaml(catalogs: &[(&str, &str)]) -> Result<Self, serde_yaml::Error> {
+        let catalogs = catalogs
+            .iter()
+            .map(|(language, yaml)| Ok(((*language).to_string(), serde_yaml::from_str(yaml)?)))
+            .collect::<Result<_, serde_yaml::Error>>()?;
+
+        Ok(Self { catalogs })
+    }
+
+    /// Pick the catalog for an `Accept-Language` header value, `None` means
+    /// English
+    ///
+    /// Languages are tried by descending quality, a language matches a catalog
+    /// of the same tag or, without a region, of the same primary language.
+    #[must_use]
+    pub fn negotiate(&self, accept_language: &str) -> Option<(&str, &HashMap<String, String>)> {
+        for language in preferred_languages(accept_language) {
+            let primary = primary_language(language);
+            if language == "*" || primary.eq_ignore_ascii_case("en") {
+                return None;
+            }
+
+            let exact = self.catalogs.iter().find(|(tag, _)| tag.eq_ignore_ascii_case(language));
+            // a language without region matches any region of it
+            let regional = || {
+                self.catalogs.iter().find(|(tag, _)| {
+                    language == primary && primary_language(tag).eq_ignore_ascii_case(primary)
+                })
+            };
+            if let Some((tag, messages)) = exact.or_else(regional) {
+                return Some((tag.as_str(), messages));
+            }
+        }
+        None
+    }
+}
+
+/// Translate the `message` of error responses into the language asked for by
+/// the `Accept-Language` header
+pub async fn localization_middleware(request: Request, next: Next) -> Response {
+    let catalog = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| CATALOGS.negotiate(value));
+
+    let response = next.run(request).await;
+
+    let Some((language, messages)) = catalog else {
+        return response;
+    };
+    if !(response.status().is_client_error() || response.status().is_server_error()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = body::to_bytes(body, usize::MAX).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+
+    let Some(localized) = translate_error(&bytes, messages) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    drop(parts.headers.remove(header::CONTENT_LENGTH));
+    if let Ok(language) = HeaderValue::from_str(language) {
+        drop(parts.headers.insert(header::CONTENT_LANGUAGE, language));
+    }
+    let _appended = parts.headers.append(header::VARY, HeaderValue::from_static("accept-language"));
+
+    Response::from_parts(parts, Body::from(localized))
+}
+
+/// Replace the `error.message` of an encapsulated error body, `None` if the
+/// body has no error code with a translation
+fn translate_error(body: &[u8], messages: &HashMap<String, String>) -> Option<Vec<u8>> {
+    let mut body: serde_json::Value = serde_json::from_slice(body).ok()?;
+
+    let error = body.get_mut("error")?.as_object_mut()?;
+    let message = messages.get(error.get("code")?.as_str()?)?;
+    let _previous = error.insert("message".to_string(), message.clone().into());
+
+    serde_json::to_vec(&body).ok()
+}
+
+/// Language tags of an `Accept-Language` header value, most preferred first,
+/// languages with `q=0` are left out
+fn preferred_languages(accept_language: &str) -> Vec<&str> {
+    let mut languages: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut params = entry.split(';').map(str::trim);
+            let language = params.next().filter(|language| !language.is_empty())?;
+            let quality = params
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.parse().ok())?;
+            (quality > 0.0).then_some((language, quality))
+        })
+        .collect();
+
+    // stable, so languages of equal quality keep their order
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages.into_iter().map(|(language, _)| language).collect()
+}
+
+/// Primary language subtag of a language tag, e.g. `zh` of `zh-TW`
+fn primary_language(tag: &str) -> &str { tag.split('-').next().unwrap_or(tag) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_languages() {
+        assert_eq!(
+            preferred_languages("en;q=0.5, zh-TW, ja;q=0.8, fr;q=0"),
+            vec!["zh-TW", "ja", "en"]
+        );
+        assert!(preferred_languages("").is_empty());
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let catalogs = &*CATALOGS;
+
+        assert_eq!(catalogs.negotiate("zh-tw").map(|(tag, _)| tag), Some("zh-TW"));
+        assert_eq!(catalogs.negotiate("zh").map(|(tag, _)| tag), Some("zh-TW"));
+        assert_eq!(catalogs.negotiate("ja, zh-TW;q=0.5").map(|(tag, _)| tag), Some("zh-TW"));
+        // English is preferred over the catalog
+        assert!(catalogs.negotiate("en-US, zh-TW;q=0.5").is_none());
+        assert!(catalogs.negotiate("zh-CN").is_none());
+        assert!(catalogs.negotiate("*").is_none());
+    }
+
+    #[test]
+    fn test_translate_error() {
+        let (_, messages) = CATALOGS.negotiate("zh-TW").unwrap();
+        let body = serde_json::json!({
+            "_status": 404,
+            "error": { "type": "NOT_FOUND", "code": "USER_NOT_FOUND", "message": "User not found" }
+        });
+
+        let translated = translate_error(&serde_json::to_vec(&body).unwrap(), messages).unwrap();
+        let translated: serde_json::Value = serde_json::from_slice(&translated).unwrap();
+
+        assert_eq!(translated["error"]["message"], messages["USER_NOT_FOUND"]);
+        assert_eq!(translated["error"]["code"], "USER_NOT_FOUND");
+        assert!(translate_error(br#"{"error":{"code":"UNKNOWN"}}"#, messages).is_none());
+    }
+}
@@ -1,5 +1,51 @@
+pub mod audit;
 pub mod auth;
+pub mod cors;
+pub mod fault_injection;
+pub mod http_log;
+pub mod http_metrics;
+pub mod introspection_cache;
+pub mod ip_filter;
 pub mod jwks;
+pub mod localization;
+pub mod rate_limit;
+pub mod realm;
+pub mod request_id;
 
-pub use auth::{jwt_auth_middleware, AuthUser};
+pub use audit::audit_admin_middleware;
+pub use auth::{
+    admin_role_middleware, jwt_auth_middleware, optional_jwt_auth_middleware, AuthUser,
+};
+pub use cors::CorsOrigins;
+pub use fault_injection::{fault_injection_middleware, FaultInjector};
+pub use http_log::{http_log_middleware, HttpLog};
+pub use http_metrics::{http_metrics_middleware, HttpMetrics};
+pub use introspection_cache::IntrospectionCache;
+pub use ip_filter::{admin_ip_filter_middleware, AdminIpFilter, ClientIp};
 pub use jwks::JwksClient;
+pub use localization::localization_middleware;
+pub use rate_limit::{ip_rate_limit_middleware, user_rate_limit_middleware, RateLimiter};
+pub use realm::{Realm, Realms};
+pub use request_id::{request_id_middleware, RequestId};
+
+/// Whether `path` is `route` or under it, `/api/v1/user` matches neither
+/// `/api/v1/users` nor `/api/v1/users/me`
+fn is_under_route(path: &str, route: &str) -> bool {
+    path.strip_prefix(route.trim_end_matches('/'))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_under_route() {
+        assert!(is_under_route("/api/v1/users", "/api/v1/users"));
+        assert!(is_under_route("/api/v1/users/me", "/api/v1/users"));
+        assert!(is_under_route("/api/v1/users/me", "/api/v1/users/"));
+        assert!(is_under_route("/api/v1/users", "/"));
+        assert!(!is_under_route("/api/v1/users", "/api/v1/user"));
+        assert!(!is_under_route("/api/v1/usersx/me", "/api/v1/users"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Token bucket rate limits per client IP and per authenticated user, and of
+//! the logins per client IP.
+//!
+//! Buckets live in the [`Store`], so they are shared by every replica when
+//! Redis is configured. Requests are let through when the store fails, an
+//! unreachable Redis must not take the API down.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use arc_swap::ArcSwap;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use mpc_backend_mock_core::config::{RateLimit, RateLimitConfig};
+
+use super::AuthUser;
+use crate::{
+    store::Store,
+    web::{controller::Error, ServiceState},
+};
+
+/// Per-IP and per-user request rate limits
+///
+/// The limits can be replaced while serving, see [`RateLimiter::reload`].
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<ArcSwap<RateLimitConfig>>,
+    trusted_proxies: Arc<[IpNet]>,
+    store: Arc<dyn Store>,
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(
+        trusted_proxies: Vec<IpNet>,
+        config: RateLimitConfig,
+        store: Arc<dyn Store>,
+    ) -> Self {
+        Self {
+            config: Arc::new(ArcSwap::from_pointee(config)),
+            trusted_proxies: trusted_proxies.into(),
+            store,
+        }
+    }
+
+    /// Limits applied to requests from now on
+    #[must_use]
+    pub fn config(&self) -> RateLimitConfig { **self.config.load() }
+
+    /// Apply `config` to the following requests, buckets keep their tokens
+    pub fn reload(&self, config: RateLimitConfig) { self.config.store(Arc::new(config)); }
+
+    /// Take a token from the login bucket of `ip`, returning how long to wait
+    /// when it is empty
+    pub async fn acquire_login(&self, ip: IpAddr) -> Option<Duration> {
+        let config = self.config();
+        if !config.enable {
+            return None;
+        }
+
+        self.acquire(&format!("rate_limit:login:{ip}"), &config.login_per_ip).await
+    }
+
+    /// Resolve the client IP, only honoring forwarding headers set by
+    /// trusted proxies
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let ConnectInfo(addr) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+        Some(zeus_axum::get_trusted_request_ip(request.headers(), addr, &self.trusted_proxies))
+    }
+
+    /// Take a token from the bucket `key`, returning how long to wait when it
+    /// is empty
+    async fn acquire(&self, key: &str, limit: &RateLimit) -> Option<Duration> {
+        match self.store.acquire_token(key, limit).await {
+            Ok(retry_after) => retry_after,
+            Err(err) => {
+                tracing::warn!("Skipping rate limit of `{key}`, error: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// Per-IP rate limit middleware
+///
+/// Requests without connection info are not limited.
+pub async fn ip_rate_limit_middleware(
+    State(service_state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let limiter = &service_state.rate_limiter;
+    let config = limiter.config();
+
+    if config.enable {
+        if let Some(ip) = limiter.client_ip(&request) {
+            let key = format!("rate_limit:ip:{ip}");
+            if let Some(retry_after) = limiter.acquire(&key, &config.per_ip).await {
+                tracing::debug!("Rate limiting requests from {ip}");
+                return Err(Error::RateLimited { retry_after });
+            }
+        }
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Per-user rate limit middleware
+///
+/// Must run after [`jwt_auth_middleware`](super::jwt_auth_middleware), which
+/// provides the [`AuthUser`].
+pub async fn user_rate_limit_middleware(
+    State(service_state): State<ServiceState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let limiter = &service_state.rate_limiter;
+    let config = limiter.config();
+
+    if config.enable {
+        if let Some(user) = request.extensions().get::<AuthUser>() {
+            let user_id = user.keycloak_user_id;
+            let key = format!("rate_limit:user:{user_id}");
+            if let Some(retry_after) = limiter.acquire(&key, &config.per_user).await {
+                tracing::debug!("Rate limiting requests from user {user_id}");
+                return Err(Error::RateLimited { retry_after });
+            }
+        }
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use super::JwksClient;
+use crate::keycloak_client::KeycloakClient;
+
+/// Keycloak realm whose tokens are accepted
+#[derive(Clone)]
+pub struct Realm {
+    name: String,
+
+    /// `iss` claim of the tokens of the realm
+    issuer: String,
+
+    jwks_client: JwksClient,
+
+    /// Client of the backend in the realm, introspects its tokens
+    keycloak_client: Arc<KeycloakClient>,
+
+    primary: bool,
+}
+
+impl Realm {
+    #[must_use]
+    pub const fn new(
+        name: String,
+        issuer: String,
+        jwks_client: JwksClient,
+        keycloak_client: Arc<KeycloakClient>,
+    ) -> Self {
+        Self { name, issuer, jwks_client, keycloak_client, primary: false }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &str { &self.name }
+
+    #[inline]
+    #[must_use]
+    pub fn issuer(&self) -> &str { &self.issuer }
+
+    #[inline]
+    #[must_use]
+    pub const fn jwks_client(&self) -> &JwksClient { &self.jwks_client }
+
+    #[inline]
+    #[must_use]
+    pub const fn keycloak_client(&self) -> &Arc<KeycloakClient> { &self.keycloak_client }
+
+    /// Realm stored on the users of this realm, `None` for the primary realm
+    #[must_use]
+    pub fn user_realm(&self) -> Option<&str> { (!self.primary).then_some(self.name.as_str()) }
+}
+
+/// Keycloak realms whose tokens are accepted, a token is validated by the
+/// realm named by its issuer
+///
+/// The primary realm is the one users log in to and are created in, its users
+/// are stored without a realm. The users of the other realms only bring tokens
+/// issued by their realm, and only see the rows stored with their realm.
+#[derive(Clone)]
+pub struct Realms {
+    /// The primary realm comes first
+    realms: Arc<Vec<Realm>>,
+}
+
+impl Realms {
+    /// Accept the tokens of the `primary` realm only
+    #[must_use]
+    pub fn new(mut primary: Realm) -> Self {
+        primary.primary = true;
+        Self { realms: Arc::new(vec![primary]) }
+    }
+
+    /// Accept the tokens of `realm` too
+    #[must_use]
+    pub fn with_realm(mut self, mut realm: Realm) -> Self {
+        realm.primary = false;
+        Arc::make_mut(&mut self.realms).push(realm);
+        self
+    }
+
+    #[must_use]
+    pub fn primary(&self) -> &Realm { &self.realms[0] }
+
+    /// Realm whose tokens carry `issuer` in their `iss` claim
+    #[must_use]
+    pub fn by_issuer(&self, issuer: &str) -> Option<&Realm> {
+        self.realms.iter().find(|realm| realm.issuer == issuer)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Realm> { self.realms.iter() }
+}
@@ -0,0 +1,154 @@
+//! `X-Request-Id` propagation and access logging.
+//!
+//! The request id sent by the client is kept when it is a plain token,
+//! otherwise a UUID is generated. The id is echoed in the `X-Request-Id`
+//! response header, is a field of the span the request is handled in and is
+//! added to error bodies as `error.request_id`. One `access_log` event is
+//! emitted per request once the response is ready.
+
+use std::time::Instant;
+
+use axum::{
+    body::{self, Body},
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use super::{AuthUser, ClientIp};
+use crate::web::ServiceState;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest request id accepted from clients
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the request, available as a request extension
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+/// Request id and access log middleware
+///
+/// Must run inside the compression layer, error bodies are rewritten before
+/// they are compressed.
+pub async fn request_id_middleware(
+    State(service_state): State<ServiceState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map_or_else(|| Uuid::new_v4().to_string(), ToString::to_string);
+    let header_value = HeaderValue::from_str(&request_id).ok();
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let ip = service_state.admin_ip_filter.client_ip(&request);
+
+    if let Some(ref value) = header_value {
+        drop(request.headers_mut().insert(X_REQUEST_ID.clone(), value.clone()));
+    }
+    drop(request.extensions_mut().insert(RequestId(request_id.clone())));
+    if let Some(ip) = ip {
+        drop(request.extensions_mut().insert(ClientIp(ip)));
+    }
+
+    let span = tracing::info_span!("request", request_id = %request_id);
+    let started_at = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    let latency = started_at.elapsed();
+
+    let mut response = if response.status().is_client_error() || response.status().is_server_error()
+    {
+        add_request_id_to_error(response, &request_id).await
+    } else {
+        response
+    };
+    if let Some(value) = header_value {
+        drop(response.headers_mut().insert(X_REQUEST_ID.clone(), value));
+    }
+
+    let status = response.status().as_u16();
+    let user_id = response.extensions().get::<AuthUser>().map(|user| user.keycloak_user_id);
+    span.in_scope(|| {
+        tracing::info!(
+            target: "access_log",
+            %method,
+            path,
+            status,
+            latency_ms = latency.as_secs_f64() * 1000.0,
+            ip = ip.map(tracing::field::display),
+            user_id = user_id.map(tracing::field::display),
+            "{method} {path} {status}"
+        );
+    });
+
+    response
+}
+
+/// Visible ASCII only, so that the id is a valid header value and cannot
+/// forge log lines
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+async fn add_request_id_to_error(response: Response, request_id: &str) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = body::to_bytes(body, usize::MAX).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+
+    let Some(body) = insert_request_id(&bytes, request_id) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    drop(parts.headers.remove(header::CONTENT_LENGTH));
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Add `request_id` to the `error` object of an encapsulated error body, `None`
+/// if the body is not one
+fn insert_request_id(body: &[u8], request_id: &str) -> Option<Vec<u8>> {
+    let mut body: serde_json::Value = serde_json::from_slice(body).ok()?;
+
+    let error = body.get_mut("error")?.as_object_mut()?;
+    let _previous = error.insert("request_id".to_string(), request_id.into());
+
+    serde_json::to_vec(&body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_request_id() {
+        assert!(is_valid_request_id("4f1c2d3e-request.1"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("with space"));
+        assert!(!is_valid_request_id("line\nbreak"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[test]
+    fn test_insert_request_id() {
+        let body = serde_json::json!({
+            "_status": 404,
+            "error": { "type": "NOT_FOUND", "code": "USER_NOT_FOUND", "message": "User not found" }
+        });
+
+        let body = insert_request_id(&serde_json::to_vec(&body).unwrap(), "abc").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["error"]["request_id"], "abc");
+        assert_eq!(body["error"]["code"], "USER_NOT_FOUND");
+        assert!(insert_request_id(br#"{"data":{}}"#, "abc").is_none());
+        assert!(insert_request_id(b"not json", "abc").is_none());
+    }
+}
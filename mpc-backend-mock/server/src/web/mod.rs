@@ -1,16 +1,29 @@
+mod business_metrics;
 pub mod controller;
+mod docs;
 pub mod error;
 pub mod extractor;
 pub mod middleware;
 
-use std::{future::Future, net::SocketAddr, sync::Arc};
+use std::{
+    future::{Future, IntoFuture},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 
 use axum::{
     extract::Request, http, response::IntoResponse, routing, Extension, Json, Router, ServiceExt,
 };
-use eris_bitcoin_rpc_client::Client as BitcoinRpcClient;
-use keycloak::{KeycloakAdmin, KeycloakServiceAccountAdminTokenRetriever};
-use mpc_backend_mock_core::ServerInfo;
+use futures::FutureExt;
+use mpc_backend_mock_core::{
+    config::{
+        ActivationConfig, BitcoinConfig, KeyGenerationConfig, LoginLockoutConfig, SessionConfig,
+        SigningConfig, TransactionRetryConfig,
+    },
+    ServerInfo,
+};
+use notification::NotificationClient;
 use snafu::ResultExt;
 use sqlx::PgPool;
 use tokio::net::TcpListener;
@@ -20,44 +33,94 @@ use tower_http::{
 };
 use utoipa::OpenApi;
 use zeus_axum::{json_response, response::EncapsulatedJsonError};
+use zeus_metrics::MetricsHandle;
 use zpl_rpc_client::RpcClient as ZplRpcClient;
 
 pub use self::{controller::ApiDoc, error::Error};
-use crate::{keycloak_client::KeycloakClient, service::UserManagementService};
+use crate::{
+    chain_state::ChainStateCache,
+    circuit_breaker::CircuitBreakers,
+    event::EventBus,
+    keycloak_client::KeycloakClient,
+    reload::ConfigReloader,
+    service::{
+        AnnotationService, ApiDriftService, AuditService, AuthService, BitcoinChain,
+        BitcoinService, CircuitBreakingBitcoinChain, CircuitBreakingUserDirectory, EventOutbox,
+        KeyService, LoginLockoutService, NotificationService, QueryMetrics, SessionService,
+        SigningService, SolanaChain, SolanaService, TransactionService, UserDirectory,
+        UserManagementService, WalletService, WebhookService,
+    },
+    store::Store,
+    task::TaskRegistry,
+};
 
+/// Serve the API, and Swagger UI under `/docs` when `docs_ui` is set, until
+/// `shutdown_signal`, then stop accepting connections and wait up to
+/// `grace_period` for in-flight requests
 pub async fn new_api_server<ShutdownSignal>(
     socket_address: SocketAddr,
     service_state: ServiceState,
     server_info: ServerInfo,
+    docs_ui: bool,
     shutdown_signal: ShutdownSignal,
+    grace_period: Duration,
 ) -> Result<(), Error>
 where
     ShutdownSignal: Future<Output = ()> + Send + 'static,
 {
-    let middleware_stack =
-        ServiceBuilder::new().layer(TraceLayer::new_for_http()).layer(CompressionLayer::new());
+    // request ids are outside the trace span so that it inherits the id, and
+    // inside compression so that error bodies can be amended
+    let middleware_stack = ServiceBuilder::new()
+        .layer(CompressionLayer::new())
+        .layer(axum::middleware::from_fn_with_state(
+            service_state.clone(),
+            middleware::request_id_middleware,
+        ))
+        .layer(TraceLayer::new_for_http());
 
     let router = {
-        let router = Router::new()
+        let mut router = Router::new()
             // For GKE load balancer default health check
             .route("/", routing::get(controller::server_info))
             .route(
                 "/openapi.json",
                 routing::get(openapi_json),
             )
-            .merge(controller::api_v1_router(&service_state))
-            .layer(Extension(server_info))
-            .layer(middleware_stack)
-            .fallback(fallback);
+            .merge(controller::api_router(&service_state));
+        if docs_ui {
+            router = router.merge(docs::router());
+        }
+        let router =
+            router.layer(Extension(server_info)).layer(middleware_stack).fallback(fallback);
         let router = NormalizePathLayer::trim_trailing_slash().layer(router);
         ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(router)
     };
 
     let listener = TcpListener::bind(&socket_address).await.context(error::BindTcpServerSnafu)?;
-    axum::serve(listener, router)
-        .with_graceful_shutdown(shutdown_signal)
-        .await
-        .map_err(|err| Error::ServeHttpServer { message: err.to_string() })
+    let shutdown_signal = shutdown_signal.shared();
+    let drain_deadline = {
+        let shutdown_signal = shutdown_signal.clone();
+        async move {
+            shutdown_signal.await;
+            tokio::time::sleep(grace_period).await;
+        }
+    };
+    let serve = axum::serve(listener, router).with_graceful_shutdown(shutdown_signal);
+
+    // new connections are refused as soon as the signal fires, in-flight
+    // requests are given the grace period
+    tokio::select! {
+        result = serve.into_future() => {
+            result.map_err(|err| Error::ServeHttpServer { message: err.to_string() })
+        }
+        () = drain_deadline => {
+            tracing::warn!(
+                "In-flight HTTP requests did not finish within {grace_period:?}, no longer \
+                 waiting for them"
+            );
+            Ok(())
+        }
+    }
 }
 
 // SAFETY: `axum` handler must be async
@@ -67,6 +130,7 @@ async fn fallback(uri: http::Uri) -> axum::response::Response {
         status: http::StatusCode::NOT_FOUND,
         error: zeus_axum::response::Error {
             type_: zeus_axum::response::ErrorType::NotFound,
+            code: "ROUTE_NOT_FOUND".to_string(),
             message: format!("No route for {uri}"),
             additional_fields: indexmap::IndexMap::default(),
         }
@@ -77,38 +141,288 @@ async fn openapi_json() -> Json<utoipa::openapi::OpenApi> { Json(ApiDoc::openapi
 
 #[derive(Clone)]
 pub struct ServiceState {
-    pub bitcoin_rpc_client: BitcoinRpcClient,
+    pub bitcoin_chain: Arc<dyn BitcoinChain>,
+    pub solana_chain: Arc<dyn SolanaChain>,
+    /// Chain heights refreshed in the background, never refreshed unless set
+    /// with [`ServiceState::with_chain_state`]
+    pub chain_state: ChainStateCache,
     pub zpl_rpc_client: ZplRpcClient,
     pub user_management_service: UserManagementService,
-    pub jwks_client: middleware::JwksClient,
-    pub keycloak_client: Option<Arc<KeycloakClient>>,
+    pub bitcoin_service: BitcoinService,
+    pub solana_service: SolanaService,
+    pub transaction_service: TransactionService,
+    pub signing_service: SigningService,
+    pub key_service: KeyService,
+    pub auth_service: AuthService,
+    pub session_service: SessionService,
+    pub login_lockout_service: LoginLockoutService,
+    pub api_drift_service: ApiDriftService,
+    pub audit_service: AuditService,
+    pub annotation_service: AnnotationService,
+    pub wallet_service: WalletService,
+    pub notification_service: NotificationService,
+    pub webhook_service: WebhookService,
+    /// Realms whose tokens are accepted
+    pub realms: middleware::Realms,
+    pub keycloak_client: Arc<KeycloakClient>,
     pub jwt_validation_method: mpc_backend_mock_core::config::JwtValidationMethod,
+    pub introspection_cache: middleware::IntrospectionCache,
+    pub admin_ip_filter: middleware::AdminIpFilter,
+    pub background_tasks: TaskRegistry,
+    pub http_metrics: middleware::HttpMetrics,
+    pub event_bus: EventBus,
+    pub rate_limiter: middleware::RateLimiter,
+    pub circuit_breakers: CircuitBreakers,
+    pub cors_origins: middleware::CorsOrigins,
+    /// Faults injected into API responses, none unless set with
+    /// [`ServiceState::with_fault_injector`]
+    pub fault_injector: middleware::FaultInjector,
+    /// Request and response bodies logged for debugging, none unless set with
+    /// [`ServiceState::with_http_log`]
+    pub http_log: middleware::HttpLog,
+    /// Serve the GraphQL API under `/api/graphql`, off unless set with
+    /// [`ServiceState::with_graphql`]
+    pub graphql: bool,
+    /// Unset when the configuration cannot be reloaded, e.g. in tests
+    pub config_reloader: Option<ConfigReloader>,
+    /// Creates the business metrics, not exported unless set with
+    /// [`ServiceState::with_metrics`]
+    pub metrics: MetricsHandle,
 }
 
 impl ServiceState {
-    /// Create a new service state
+    /// Create a new service state, calling Keycloak and the Bitcoin RPC
+    /// endpoint through `circuit_breakers`
     #[allow(clippy::too_many_arguments)]
     #[must_use]
     pub fn new(
         database: PgPool,
-        bitcoin_rpc_client: &BitcoinRpcClient,
+        bitcoin_chain: Arc<dyn BitcoinChain>,
+        bitcoin_config: &BitcoinConfig,
+        solana_chain: Arc<dyn SolanaChain>,
         zpl_rpc_client: ZplRpcClient,
-        jwks_client: middleware::JwksClient,
-        keycloak_admin: Arc<KeycloakAdmin<KeycloakServiceAccountAdminTokenRetriever>>,
-        keycloak_realm: String,
-        keycloak_client: Option<Arc<KeycloakClient>>,
+        realms: middleware::Realms,
+        user_directory: Arc<dyn UserDirectory>,
+        notification_client: Arc<dyn NotificationClient>,
+        activation_config: &ActivationConfig,
+        keycloak_client: Arc<KeycloakClient>,
         jwt_validation_method: mpc_backend_mock_core::config::JwtValidationMethod,
+        introspection_cache: middleware::IntrospectionCache,
+        admin_ip_filter: middleware::AdminIpFilter,
+        background_tasks: TaskRegistry,
+        http_metrics: middleware::HttpMetrics,
+        event_bus: EventBus,
+        query_metrics: QueryMetrics,
+        rate_limiter: middleware::RateLimiter,
+        circuit_breakers: CircuitBreakers,
     ) -> Self {
-        let user_management_service =
-            UserManagementService::new(database, keycloak_admin, keycloak_realm);
+        let bitcoin_chain: Arc<dyn BitcoinChain> = Arc::new(CircuitBreakingBitcoinChain::new(
+            bitcoin_chain,
+            circuit_breakers.bitcoin.clone(),
+        ));
+        let user_directory: Arc<dyn UserDirectory> = Arc::new(CircuitBreakingUserDirectory::new(
+            user_directory,
+            circuit_breakers.keycloak.clone(),
+        ));
+        let bitcoin_service =
+            BitcoinService::new(database.clone(), Arc::clone(&bitcoin_chain), bitcoin_config);
+        let transaction_service = TransactionService::new(
+            database.clone(),
+            Arc::clone(&solana_chain),
+            event_bus.clone(),
+            query_metrics.clone(),
+        );
+        let solana_service = SolanaService::new(Arc::clone(&solana_chain));
+        let signing_service = SigningService::new(database.clone());
+        let key_service = KeyService::new(database.clone());
+        let api_drift_service = ApiDriftService::new(database.clone());
+        let audit_service = AuditService::new(database.clone());
+        let annotation_service = AnnotationService::new(database.clone());
+        let wallet_service = WalletService::new(database.clone());
+        let notification_service = NotificationService::new(database.clone(), notification_client);
+        let webhook_service = WebhookService::new(database.clone());
+        let session_service = SessionService::new(database.clone());
+        let login_lockout_service =
+            LoginLockoutService::new(database.clone(), Arc::clone(&user_directory));
+        let user_management_service = UserManagementService::new(
+            database,
+            user_directory,
+            activation_config,
+            event_bus.clone(),
+            query_metrics,
+        );
+        let auth_service =
+            AuthService::new(keycloak_client.clone(), circuit_breakers.keycloak.clone());
 
         Self {
-            bitcoin_rpc_client: bitcoin_rpc_client.clone(),
+            bitcoin_chain,
+            solana_chain,
+            chain_state: ChainStateCache::default(),
             zpl_rpc_client,
             user_management_service,
-            jwks_client,
+            bitcoin_service,
+            solana_service,
+            transaction_service,
+            signing_service,
+            key_service,
+            auth_service,
+            session_service,
+            login_lockout_service,
+            api_drift_service,
+            audit_service,
+            annotation_service,
+            wallet_service,
+            notification_service,
+            webhook_service,
+            realms,
             keycloak_client,
             jwt_validation_method,
+            introspection_cache,
+            admin_ip_filter,
+            background_tasks,
+            http_metrics,
+            event_bus,
+            rate_limiter,
+            circuit_breakers,
+            cors_origins: middleware::CorsOrigins::default(),
+            fault_injector: middleware::FaultInjector::default(),
+            http_log: middleware::HttpLog::default(),
+            graphql: false,
+            config_reloader: None,
+            metrics: MetricsHandle::default(),
         }
     }
+
+    /// Allow browsers from `cors_origins` only, instead of any origin
+    #[must_use]
+    pub fn with_cors_origins(mut self, cors_origins: middleware::CorsOrigins) -> Self {
+        self.cors_origins = cors_origins;
+        self
+    }
+
+    /// Inject the faults of `fault_injector` into API responses
+    #[must_use]
+    pub fn with_fault_injector(mut self, fault_injector: middleware::FaultInjector) -> Self {
+        self.fault_injector = fault_injector;
+        self
+    }
+
+    /// Log the bodies of the routes of `http_log`
+    #[must_use]
+    pub fn with_http_log(mut self, http_log: middleware::HttpLog) -> Self {
+        self.http_log = http_log;
+        self
+    }
+
+    /// Serve the GraphQL API under `/api/graphql` if `enable` is set
+    #[must_use]
+    pub const fn with_graphql(mut self, enable: bool) -> Self {
+        self.graphql = enable;
+        self
+    }
+
+    /// Look users up and list them on the read replicas `replicas`, falling
+    /// back to the primary
+    #[must_use]
+    pub fn with_read_replicas(mut self, replicas: Vec<PgPool>) -> Self {
+        self.user_management_service = self.user_management_service.with_read_replicas(replicas);
+        self
+    }
+
+    /// Retry the transactions of the user management aborted by a
+    /// serialization failure or a deadlock as configured by
+    /// `transaction_retry`
+    #[must_use]
+    pub fn with_transaction_retry(mut self, transaction_retry: TransactionRetryConfig) -> Self {
+        self.user_management_service =
+            self.user_management_service.with_transaction_retry(transaction_retry);
+        self
+    }
+
+    /// Let `POST /api/v1/auth/login` set session cookies as configured by
+    /// `session_config`
+    #[must_use]
+    pub fn with_session_config(mut self, session_config: SessionConfig) -> Self {
+        self.session_service = self.session_service.with_config(session_config);
+        self
+    }
+
+    /// Lock emails after repeated failed logins of `POST /api/v1/auth/login`
+    /// as configured by `login_lockout_config`
+    #[must_use]
+    pub fn with_login_lockout_config(mut self, login_lockout_config: LoginLockoutConfig) -> Self {
+        self.login_lockout_service = self.login_lockout_service.with_config(login_lockout_config);
+        self
+    }
+
+    /// Simulate the MPC signing sessions as configured by `signing_config`
+    #[must_use]
+    pub fn with_signing_config(mut self, signing_config: SigningConfig) -> Self {
+        self.signing_service = self.signing_service.with_config(signing_config);
+        self
+    }
+
+    /// Simulate the key generation as configured by `key_generation_config`
+    #[must_use]
+    pub fn with_key_generation_config(
+        mut self,
+        key_generation_config: KeyGenerationConfig,
+    ) -> Self {
+        self.key_service = self.key_service.with_config(key_generation_config);
+        self
+    }
+
+    /// Record the domain events of the users and transactions in
+    /// `event_outbox`
+    #[must_use]
+    pub fn with_event_outbox(mut self, event_outbox: EventOutbox) -> Self {
+        self.user_management_service = self.user_management_service.with_event_outbox(event_outbox);
+        self.transaction_service = self.transaction_service.with_event_outbox(event_outbox);
+        self
+    }
+
+    /// Manage the users of `realm` in `directory`, through the Keycloak circuit
+    /// breaker like the directory of the primary realm
+    #[must_use]
+    pub fn with_realm_directory(mut self, realm: &str, directory: Arc<dyn UserDirectory>) -> Self {
+        let directory = Arc::new(CircuitBreakingUserDirectory::new(
+            directory,
+            self.circuit_breakers.keycloak.clone(),
+        ));
+        self.user_management_service =
+            self.user_management_service.with_realm_directory(realm, directory);
+        self
+    }
+
+    /// Cache the Bitcoin fee estimates and mempool in `store`, shared by the
+    /// replicas when it is Redis
+    #[must_use]
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.bitcoin_service = self.bitcoin_service.with_store(store);
+        self
+    }
+
+    /// Serve the chain heights from `chain_state`, refreshed by the
+    /// background worker
+    #[must_use]
+    pub fn with_chain_state(mut self, chain_state: ChainStateCache) -> Self {
+        self.bitcoin_service = self.bitcoin_service.with_chain_state(chain_state.clone());
+        self.chain_state = chain_state;
+        self
+    }
+
+    /// Create the business metrics with `metrics`, e.g. in the registry
+    /// exported by the metrics server
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: MetricsHandle) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Serve `POST /api/v1/admin/reload` with `config_reloader`
+    #[must_use]
+    pub fn with_config_reloader(mut self, config_reloader: ConfigReloader) -> Self {
+        self.config_reloader = Some(config_reloader);
+        self
+    }
 }
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use snafu::ResultExt;
+
+use crate::{
+    service::UserManagementService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Delete activation tokens and email change requests which expired, used
+/// ones included
+pub struct ExpireActivationTokensJob {
+    user_management_service: UserManagementService,
+}
+
+impl ExpireActivationTokensJob {
+    const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+    #[must_use]
+    pub const fn new(user_management_service: UserManagementService) -> Self {
+        Self { user_management_service }
+    }
+}
+
+#[async_trait]
+impl Job for ExpireActivationTokensJob {
+    fn name(&self) -> &'static str { "expire_activation_tokens" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let deleted = self
+            .user_management_service
+            .delete_expired_activation_tokens()
+            .await
+            .context(error::ExpireActivationTokensSnafu)?;
+
+        if deleted > 0 {
+            tracing::info!("Deleted {deleted} expired activation tokens");
+        }
+
+        let deleted = self
+            .user_management_service
+            .delete_expired_email_change_requests()
+            .await
+            .context(error::ExpireActivationTokensSnafu)?;
+        if deleted > 0 {
+            tracing::info!("Deleted {deleted} expired email change requests");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use prometheus::{IntGauge, IntGaugeVec, Opts};
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    chain_state::ChainStateCache,
+    entity::{ChainHeight, Event},
+    error as crate_error,
+    event::EventBus,
+    service::{BitcoinChain, SolanaChain},
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Read the Bitcoin block height and the Solana slot into the
+/// [`ChainStateCache`]
+///
+/// The block height is exported as the `bitcoin_block_height` gauge and
+/// [`Event::BitcoinBlockAdvanced`] is published when it increases. The age of
+/// each cached value is exported as `chain_state_staleness_seconds`, so a node
+/// which stopped answering shows up even though the cache keeps serving its
+/// last value.
+pub struct RefreshChainStateJob {
+    bitcoin_chain: Arc<dyn BitcoinChain>,
+    solana_chain: Arc<dyn SolanaChain>,
+    cache: ChainStateCache,
+    interval: Duration,
+    event_bus: EventBus,
+    block_height: IntGauge,
+    staleness: IntGaugeVec,
+}
+
+impl RefreshChainStateJob {
+    /// # Errors
+    ///
+    /// Returns an error if the gauges cannot be registered
+    pub fn new(
+        bitcoin_chain: Arc<dyn BitcoinChain>,
+        solana_chain: Arc<dyn SolanaChain>,
+        cache: ChainStateCache,
+        interval: Duration,
+        event_bus: EventBus,
+        metrics: &DefaultMetrics,
+    ) -> crate_error::Result<Self> {
+        let block_height =
+            IntGauge::new("bitcoin_block_height", "Latest Bitcoin block height seen by the server")
+                .context(crate_error::CreateWorkerMetricsSnafu)?;
+        metrics.register(Box::new(block_height.clone()))?;
+        let staleness = IntGaugeVec::new(
+            Opts::new(
+                "chain_state_staleness_seconds",
+                "Seconds since the cached height of the chain was last refreshed",
+            ),
+            &["chain"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+        metrics.register(Box::new(staleness.clone()))?;
+
+        Ok(Self {
+            bitcoin_chain,
+            solana_chain,
+            cache,
+            interval,
+            event_bus,
+            block_height,
+            staleness,
+        })
+    }
+
+    fn record_bitcoin_block_height(&self, block_height: u64) {
+        self.cache.set_bitcoin_block_height(block_height);
+
+        let height = i64::try_from(block_height).unwrap_or(i64::MAX);
+        let previous_height = self.block_height.get();
+        if height != previous_height {
+            tracing::debug!("Bitcoin block height is {height}");
+            self.block_height.set(height);
+        }
+        // the gauge starts at zero, so the first read is published as well
+        if height > previous_height {
+            self.event_bus.publish(Event::BitcoinBlockAdvanced { height: block_height });
+        }
+    }
+
+    fn record_staleness(&self, chain: &str, cached: Option<ChainHeight>) {
+        // a value never read is as stale as the process is old, the gauge is
+        // left unset instead so it does not look like a fresh value
+        if let Some(cached) = cached {
+            let age = (Utc::now() - cached.updated_at).num_seconds().max(0);
+            self.staleness.with_label_values(&[chain]).set(age);
+        }
+    }
+}
+
+#[async_trait]
+impl Job for RefreshChainStateJob {
+    fn name(&self) -> &'static str { "refresh_chain_state" }
+
+    fn interval(&self) -> Duration { self.interval }
+
+    async fn run(&self) -> Result<()> {
+        let (block_height, slot) =
+            tokio::join!(self.bitcoin_chain.get_block_count(), self.solana_chain.get_slot());
+
+        if let Ok(block_height) = block_height {
+            self.record_bitcoin_block_height(block_height);
+        }
+        if let Ok(slot) = slot {
+            self.cache.set_solana_slot(slot);
+        }
+
+        self.record_staleness("bitcoin", self.cache.bitcoin_block_height());
+        self.record_staleness("solana", self.cache.solana_slot());
+
+        // the Solana slot is refreshed even while the Bitcoin node is down,
+        // the run still fails so the outage shows in the job metrics
+        let _block_height = block_height.context(error::GetBitcoinBlockCountSnafu)?;
+        let _slot = slot.context(error::GetSolanaSlotSnafu)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use snafu::Snafu;
+
+use crate::web::middleware::jwks::JwksError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Failed to refresh JWKS cache of realm `{realm}`, error: {source}"))]
+    RefreshJwks { realm: String, source: JwksError },
+
+    #[snafu(display("Failed to get Bitcoin block count, error: {source}"))]
+    GetBitcoinBlockCount { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to get Solana slot, error: {source}"))]
+    GetSolanaSlot { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to expire activation tokens, error: {source}"))]
+    ExpireActivationTokens { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to expire sessions, error: {source}"))]
+    ExpireSessions { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to unlock expired logins, error: {source}"))]
+    UnlockExpiredLogins { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to snapshot wallet balances, error: {source}"))]
+    SnapshotWalletBalances { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to dispatch notifications, error: {source}"))]
+    DispatchNotifications { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to deliver webhook events, error: {source}"))]
+    DeliverWebhooks { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to reconcile users, error: {source}"))]
+    ReconcileUsers { source: crate::service::error::Error },
+
+    #[snafu(display("Failed to publish events, error: {source}"))]
+    PublishEvents { source: crate::service::error::Error },
+}
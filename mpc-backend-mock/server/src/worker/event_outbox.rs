@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use prometheus::IntCounter;
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    error as crate_error,
+    service::EventOutboxService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Publish the events written to the outbox since the last run, see
+/// [`EventOutboxService::publish_pending`], counting them in
+/// `events_published_total`
+pub struct PublishEventsJob {
+    event_outbox_service: EventOutboxService,
+    published: IntCounter,
+}
+
+impl PublishEventsJob {
+    /// Short, so consumers learn about a change within seconds
+    const INTERVAL: Duration = Duration::from_secs(2);
+
+    /// # Errors
+    ///
+    /// Returns an error if the publishing metrics cannot be registered
+    pub fn new(
+        event_outbox_service: EventOutboxService,
+        metrics: &DefaultMetrics,
+    ) -> crate_error::Result<Self> {
+        let published =
+            IntCounter::new("events_published_total", "Number of domain events published")
+                .context(crate_error::CreateWorkerMetricsSnafu)?;
+        metrics.register(Box::new(published.clone()))?;
+
+        Ok(Self { event_outbox_service, published })
+    }
+}
+
+#[async_trait]
+impl Job for PublishEventsJob {
+    fn name(&self) -> &'static str { "publish_events" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let published =
+            self.event_outbox_service.publish_pending().await.context(error::PublishEventsSnafu)?;
+
+        self.published.inc_by(published);
+        if published > 0 {
+            tracing::debug!("Published {published} events");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use snafu::ResultExt;
+
+use crate::{
+    web::middleware::Realms,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Refresh the JWKS cache of every realm before it expires, so token
+/// validation does not wait for Keycloak
+pub struct RefreshJwksJob {
+    realms: Realms,
+    interval: Duration,
+}
+
+impl RefreshJwksJob {
+    /// `interval` should be shorter than the time the cache stays fresh
+    #[must_use]
+    pub const fn new(realms: Realms, interval: Duration) -> Self { Self { realms, interval } }
+}
+
+#[async_trait]
+impl Job for RefreshJwksJob {
+    fn name(&self) -> &'static str { "refresh_jwks" }
+
+    fn interval(&self) -> Duration { self.interval }
+
+    /// A realm whose keys cannot be fetched does not keep the others from
+    /// being refreshed, the last failure is returned
+    async fn run(&self) -> Result<()> {
+        let mut result = Ok(());
+        for realm in self.realms.iter() {
+            if let Err(err) = realm.jwks_client().refresh().await {
+                result = Err(err).context(error::RefreshJwksSnafu { realm: realm.name() });
+            }
+        }
+        result
+    }
+}
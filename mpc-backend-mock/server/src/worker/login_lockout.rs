@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use snafu::ResultExt;
+
+use crate::{
+    service::LoginLockoutService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Lift the login lockouts which ended and forget failed logins whose failure
+/// window has passed
+pub struct UnlockExpiredLoginsJob {
+    login_lockout_service: LoginLockoutService,
+}
+
+impl UnlockExpiredLoginsJob {
+    /// Often enough that a Keycloak account is not disabled for long after
+    /// its lockout ended
+    const INTERVAL: Duration = Duration::from_secs(60);
+
+    #[must_use]
+    pub const fn new(login_lockout_service: LoginLockoutService) -> Self {
+        Self { login_lockout_service }
+    }
+}
+
+#[async_trait]
+impl Job for UnlockExpiredLoginsJob {
+    fn name(&self) -> &'static str { "unlock_expired_logins" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let unlocked = self
+            .login_lockout_service
+            .unlock_expired()
+            .await
+            .context(error::UnlockExpiredLoginsSnafu)?;
+
+        if unlocked > 0 {
+            tracing::info!("Unlocked {unlocked} emails whose lockout ended");
+        }
+        Ok(())
+    }
+}
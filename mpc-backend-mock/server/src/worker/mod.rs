@@ -0,0 +1,214 @@
+//! Background worker running periodic [`Job`]s.
+//!
+//! The worker is spawned on the [`LifecycleManager`](sigfinn::LifecycleManager)
+//! alongside the HTTP server. Every job runs on its own interval, a failed or
+//! panicked run is logged and counted and the job runs again on the next tick.
+//! On shutdown, runs in progress are allowed to finish before the worker exits.
+
+mod activation_token;
+mod chain_state;
+pub mod error;
+mod event_outbox;
+mod jwks;
+mod login_lockout;
+mod notification;
+mod session;
+mod snapshot;
+mod user_reconciliation;
+mod wallet;
+mod webhook;
+
+use std::{
+    panic::AssertUnwindSafe,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::FutureExt;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts};
+use sigfinn::Shutdown;
+use snafu::ResultExt;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use zeus_metrics::DefaultMetrics;
+
+pub use self::{
+    activation_token::ExpireActivationTokensJob, chain_state::RefreshChainStateJob,
+    event_outbox::PublishEventsJob, jwks::RefreshJwksJob, login_lockout::UnlockExpiredLoginsJob,
+    notification::DispatchNotificationsJob, session::ExpireSessionsJob,
+    snapshot::PublishServerSnapshotJob, user_reconciliation::ReconcileUsersJob,
+    wallet::SnapshotWalletBalancesJob, webhook::DeliverWebhooksJob,
+};
+use crate::error::{self as crate_error, Result};
+
+/// Periodic background job
+#[async_trait]
+pub trait Job: Send + Sync {
+    /// Name used in logs and as the `job` metric label
+    fn name(&self) -> &'static str;
+
+    /// Time between the starts of two runs, a run taking longer delays the
+    /// next one
+    fn interval(&self) -> Duration;
+
+    /// Run the job once
+    async fn run(&self) -> error::Result<()>;
+}
+
+#[derive(Clone, Debug)]
+struct JobMetrics {
+    runs: IntCounterVec,
+    duration: HistogramVec,
+    last_success: IntGaugeVec,
+}
+
+impl JobMetrics {
+    fn new(metrics: &DefaultMetrics) -> Result<Self> {
+        let runs = IntCounterVec::new(
+            Opts::new("worker_job_runs_total", "Number of worker job runs by result"),
+            &["job", "result"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new("worker_job_duration_seconds", "Duration of worker job runs"),
+            &["job"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+        let last_success = IntGaugeVec::new(
+            Opts::new(
+                "worker_job_last_success_timestamp_seconds",
+                "Unix time of the last successful worker job run",
+            ),
+            &["job"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+
+        metrics.register(Box::new(runs.clone()))?;
+        metrics.register(Box::new(duration.clone()))?;
+        metrics.register(Box::new(last_success.clone()))?;
+
+        Ok(Self { runs, duration, last_success })
+    }
+}
+
+/// Runs [`Job`]s until shutdown
+pub struct Worker {
+    jobs: Vec<Arc<dyn Job>>,
+    metrics: JobMetrics,
+}
+
+impl Worker {
+    /// # Errors
+    ///
+    /// Returns an error if the job metrics cannot be registered
+    pub fn new(metrics: &DefaultMetrics) -> Result<Self> {
+        Ok(Self { jobs: Vec::new(), metrics: JobMetrics::new(metrics)? })
+    }
+
+    /// Add a job to run
+    #[must_use]
+    pub fn with_job<J>(mut self, job: J) -> Self
+    where
+        J: Job + 'static,
+    {
+        self.jobs.push(Arc::new(job));
+        self
+    }
+
+    /// Run all jobs until `shutdown` resolves and the runs in progress finish
+    pub async fn run(self, shutdown: Shutdown) {
+        let cancellation = CancellationToken::new();
+
+        let jobs = futures::future::join_all(
+            self.jobs
+                .into_iter()
+                .map(|job| run_job(job, self.metrics.clone(), cancellation.clone())),
+        );
+        let cancel = async {
+            shutdown.await;
+            cancellation.cancel();
+        };
+
+        let (_jobs, ()) = tokio::join!(jobs, cancel);
+    }
+}
+
+async fn run_job(job: Arc<dyn Job>, metrics: JobMetrics, cancellation: CancellationToken) {
+    let name = job.name();
+    let mut interval = tokio::time::interval(job.interval());
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            () = cancellation.cancelled() => break,
+            _instant = interval.tick() => {}
+        }
+
+        // a run in progress is not interrupted by shutdown
+        let started_at = Instant::now();
+        let result = AssertUnwindSafe(job.run()).catch_unwind().await;
+        metrics.duration.with_label_values(&[name]).observe(started_at.elapsed().as_secs_f64());
+
+        let result_label = match result {
+            Ok(Ok(())) => {
+                metrics.last_success.with_label_values(&[name]).set(Utc::now().timestamp());
+                "success"
+            }
+            Ok(Err(err)) => {
+                tracing::warn!("Job `{name}` failed, error: {err}");
+                "failure"
+            }
+            Err(_panic) => {
+                tracing::error!("Job `{name}` panicked");
+                "panic"
+            }
+        };
+        metrics.runs.with_label_values(&[name, result_label]).inc();
+    }
+
+    tracing::debug!("Job `{name}` is stopped on shutdown");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+
+    struct CountingJob(Arc<AtomicU64>);
+
+    #[async_trait]
+    impl Job for CountingJob {
+        fn name(&self) -> &'static str { "counting" }
+
+        fn interval(&self) -> Duration { Duration::from_secs(1) }
+
+        async fn run(&self) -> error::Result<()> {
+            let _previous = self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_job_runs_on_interval_until_cancelled() {
+        let metrics = JobMetrics::new(&DefaultMetrics::new()).unwrap();
+        let runs = Arc::new(AtomicU64::new(0));
+        let cancellation = CancellationToken::new();
+
+        let handle = tokio::spawn(run_job(
+            Arc::new(CountingJob(Arc::clone(&runs))),
+            metrics.clone(),
+            cancellation.clone(),
+        ));
+
+        // the first tick is immediate
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        assert_eq!(runs.load(Ordering::Relaxed), 3);
+
+        cancellation.cancel();
+        handle.await.unwrap();
+        assert_eq!(metrics.runs.with_label_values(&["counting", "success"]).get(), 3);
+    }
+}
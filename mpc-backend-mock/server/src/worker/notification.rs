@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use prometheus::{IntCounterVec, IntGaugeVec, Opts};
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    error as crate_error,
+    service::NotificationService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Send the queued notifications whose next attempt is due, see
+/// [`NotificationService::dispatch_due`], and export the number of
+/// notifications left pending as the `notification_outbox_depth` gauge
+pub struct DispatchNotificationsJob {
+    notification_service: NotificationService,
+    dispatched: IntCounterVec,
+    outbox_depth: IntGaugeVec,
+}
+
+impl DispatchNotificationsJob {
+    /// Short, so an activation email goes out within seconds of the user being
+    /// created
+    const INTERVAL: Duration = Duration::from_secs(5);
+
+    /// # Errors
+    ///
+    /// Returns an error if the dispatch metrics cannot be registered
+    pub fn new(
+        notification_service: NotificationService,
+        metrics: &DefaultMetrics,
+    ) -> crate_error::Result<Self> {
+        let dispatched = IntCounterVec::new(
+            Opts::new(
+                "notifications_dispatched_total",
+                "Number of notification delivery attempts by result",
+            ),
+            &["result"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+        metrics.register(Box::new(dispatched.clone()))?;
+        let outbox_depth = metrics.handle().gauge(
+            "notification_outbox_depth",
+            "Number of notifications waiting for their first or next attempt",
+            &[],
+        )?;
+
+        Ok(Self { notification_service, dispatched, outbox_depth })
+    }
+}
+
+#[async_trait]
+impl Job for DispatchNotificationsJob {
+    fn name(&self) -> &'static str { "dispatch_notifications" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let dispatch = self
+            .notification_service
+            .dispatch_due()
+            .await
+            .context(error::DispatchNotificationsSnafu)?;
+
+        self.dispatched.with_label_values(&["sent"]).inc_by(dispatch.sent);
+        self.dispatched.with_label_values(&["skipped"]).inc_by(dispatch.skipped);
+        self.dispatched.with_label_values(&["retried"]).inc_by(dispatch.retried);
+        self.dispatched.with_label_values(&["dead_lettered"]).inc_by(dispatch.dead_lettered);
+
+        if dispatch.sent > 0 {
+            tracing::debug!("Sent {} queued notifications", dispatch.sent);
+        }
+
+        let pending = self
+            .notification_service
+            .pending_count()
+            .await
+            .context(error::DispatchNotificationsSnafu)?;
+        self.outbox_depth.with_label_values(&[]).set(i64::try_from(pending).unwrap_or(i64::MAX));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use snafu::ResultExt;
+
+use crate::{
+    service::SessionService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Delete cookie sessions which expired
+pub struct ExpireSessionsJob {
+    session_service: SessionService,
+}
+
+impl ExpireSessionsJob {
+    const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+    #[must_use]
+    pub const fn new(session_service: SessionService) -> Self { Self { session_service } }
+}
+
+#[async_trait]
+impl Job for ExpireSessionsJob {
+    fn name(&self) -> &'static str { "expire_sessions" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let deleted =
+            self.session_service.delete_expired().await.context(error::ExpireSessionsSnafu)?;
+
+        if deleted > 0 {
+            tracing::info!("Deleted {deleted} expired sessions");
+        }
+        Ok(())
+    }
+}
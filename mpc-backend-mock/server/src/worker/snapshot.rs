@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    chain_state::ChainStateCache,
+    entity::ServerSnapshot,
+    event::EventBus,
+    worker::{error::Result, Job},
+};
+
+/// Publish a [`ServerSnapshot`] for the clients subscribed to
+/// `/api/v1/events`
+///
+/// The chain heights are taken from the [`ChainStateCache`], a stale height is
+/// left out of the snapshot, so the uptime keeps being published while a chain
+/// cannot be reached.
+pub struct PublishServerSnapshotJob {
+    chain_state: ChainStateCache,
+    block_number_to_confirm: u64,
+    start_time: DateTime<Utc>,
+    event_bus: EventBus,
+}
+
+impl PublishServerSnapshotJob {
+    const INTERVAL: Duration = Duration::from_secs(10);
+
+    #[must_use]
+    pub fn new(
+        chain_state: ChainStateCache,
+        block_number_to_confirm: u64,
+        start_time: DateTime<Utc>,
+        event_bus: EventBus,
+    ) -> Self {
+        Self { chain_state, block_number_to_confirm, start_time, event_bus }
+    }
+}
+
+#[async_trait]
+impl Job for PublishServerSnapshotJob {
+    fn name(&self) -> &'static str { "publish_server_snapshot" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let bitcoin_block_height = self.chain_state.fresh_bitcoin_block_height();
+        let solana_slot = self.chain_state.fresh_solana_slot();
+
+        let taken_at = Utc::now();
+        self.event_bus.publish_snapshot(ServerSnapshot {
+            bitcoin_block_height,
+            bitcoin_confirmed_height: bitcoin_block_height
+                .and_then(|height| confirmed_height(height, self.block_number_to_confirm)),
+            solana_slot,
+            uptime_seconds: u64::try_from((taken_at - self.start_time).num_seconds()).unwrap_or(0),
+            taken_at,
+        });
+
+        Ok(())
+    }
+}
+
+/// Highest block whose outputs have `block_number_to_confirm` confirmations,
+/// the block at the tip has one
+const fn confirmed_height(block_height: u64, block_number_to_confirm: u64) -> Option<u64> {
+    // an output in the tip counts as confirmed even when no confirmation is
+    // required
+    let confirmations = if block_number_to_confirm == 0 { 1 } else { block_number_to_confirm };
+    block_height.saturating_add(1).checked_sub(confirmations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmed_height() {
+        assert_eq!(confirmed_height(100, 6), Some(95));
+        assert_eq!(confirmed_height(100, 1), Some(100));
+        assert_eq!(confirmed_height(100, 0), Some(100));
+        assert_eq!(confirmed_height(4, 6), None);
+    }
+}
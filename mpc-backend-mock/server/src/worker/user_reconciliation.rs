@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use prometheus::{IntCounterVec, IntGauge, Opts};
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    error as crate_error,
+    service::UserManagementService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Repair drift between the Keycloak accounts and the users of the database,
+/// see [`UserManagementService::reconcile_users`]
+pub struct ReconcileUsersJob {
+    user_management_service: UserManagementService,
+    repairs: IntCounterVec,
+    users_without_account: IntGauge,
+}
+
+impl ReconcileUsersJob {
+    /// Longer than any user creation takes, so a creation in progress is not
+    /// taken for drift
+    const GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+    const INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+    /// # Errors
+    ///
+    /// Returns an error if the reconciliation metrics cannot be registered
+    pub fn new(
+        user_management_service: UserManagementService,
+        metrics: &DefaultMetrics,
+    ) -> crate_error::Result<Self> {
+        let repairs = IntCounterVec::new(
+            Opts::new(
+                "user_reconciliation_repairs_total",
+                "Number of Keycloak accounts repaired by the user reconciliation by kind",
+            ),
+            &["kind"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+        let users_without_account = IntGauge::new(
+            "user_reconciliation_users_without_account",
+            "Number of users without a Keycloak account found by the last user reconciliation",
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+
+        metrics.register(Box::new(repairs.clone()))?;
+        metrics.register(Box::new(users_without_account.clone()))?;
+
+        Ok(Self { user_management_service, repairs, users_without_account })
+    }
+}
+
+#[async_trait]
+impl Job for ReconcileUsersJob {
+    fn name(&self) -> &'static str { "reconcile_users" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let reconciliation = self
+            .user_management_service
+            .reconcile_users(Self::GRACE_PERIOD)
+            .await
+            .context(error::ReconcileUsersSnafu)?;
+
+        self.repairs
+            .with_label_values(&["deleted_account"])
+            .inc_by(reconciliation.deleted_accounts);
+        self.repairs
+            .with_label_values(&["toggled_account"])
+            .inc_by(reconciliation.toggled_accounts);
+        self.users_without_account
+            .set(i64::try_from(reconciliation.users_without_account.len()).unwrap_or(i64::MAX));
+
+        if reconciliation.deleted_accounts > 0 || reconciliation.toggled_accounts > 0 {
+            tracing::info!(
+                "Reconciled users, deleted {} and toggled {} Keycloak accounts",
+                reconciliation.deleted_accounts,
+                reconciliation.toggled_accounts
+            );
+        }
+        Ok(())
+    }
+}
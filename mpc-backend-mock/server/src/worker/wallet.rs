@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use snafu::ResultExt;
+
+use crate::{
+    service::WalletService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Snapshot the balance of every wallet into the daily balance history
+pub struct SnapshotWalletBalancesJob {
+    wallet_service: WalletService,
+}
+
+impl SnapshotWalletBalancesJob {
+    /// Snapshots of the same day replace each other, so the last run of a day
+    /// records its end of day balance
+    const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+    #[must_use]
+    pub const fn new(wallet_service: WalletService) -> Self { Self { wallet_service } }
+}
+
+#[async_trait]
+impl Job for SnapshotWalletBalancesJob {
+    fn name(&self) -> &'static str { "snapshot_wallet_balances" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let snapshotted = self
+            .wallet_service
+            .snapshot_balances()
+            .await
+            .context(error::SnapshotWalletBalancesSnafu)?;
+
+        tracing::debug!("Snapshotted the balance of {snapshotted} wallets");
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use prometheus::{IntCounterVec, Opts};
+use snafu::ResultExt;
+use zeus_metrics::DefaultMetrics;
+
+use crate::{
+    error as crate_error,
+    service::WebhookService,
+    worker::{
+        error::{self, Result},
+        Job,
+    },
+};
+
+/// Post the queued webhook events whose next attempt is due, see
+/// [`WebhookService::deliver_due`]
+pub struct DeliverWebhooksJob {
+    webhook_service: WebhookService,
+    delivered: IntCounterVec,
+}
+
+impl DeliverWebhooksJob {
+    /// Short, so that consumers hear of an event within seconds
+    const INTERVAL: Duration = Duration::from_secs(5);
+
+    /// # Errors
+    ///
+    /// Returns an error if the delivery metrics cannot be registered
+    pub fn new(
+        webhook_service: WebhookService,
+        metrics: &DefaultMetrics,
+    ) -> crate_error::Result<Self> {
+        let delivered = IntCounterVec::new(
+            Opts::new("webhook_deliveries_total", "Number of webhook delivery attempts by result"),
+            &["result"],
+        )
+        .context(crate_error::CreateWorkerMetricsSnafu)?;
+        metrics.register(Box::new(delivered.clone()))?;
+
+        Ok(Self { webhook_service, delivered })
+    }
+}
+
+#[async_trait]
+impl Job for DeliverWebhooksJob {
+    fn name(&self) -> &'static str { "deliver_webhooks" }
+
+    fn interval(&self) -> Duration { Self::INTERVAL }
+
+    async fn run(&self) -> Result<()> {
+        let dispatch =
+            self.webhook_service.deliver_due().await.context(error::DeliverWebhooksSnafu)?;
+
+        self.delivered.with_label_values(&["delivered"]).inc_by(dispatch.delivered);
+        self.delivered.with_label_values(&["retried"]).inc_by(dispatch.retried);
+        self.delivered.with_label_values(&["dead_lettered"]).inc_by(dispatch.dead_lettered);
+
+        if dispatch.delivered > 0 {
+            tracing::debug!("Delivered {} webhook events", dispatch.delivered);
+        }
+
+        Ok(())
+    }
+}
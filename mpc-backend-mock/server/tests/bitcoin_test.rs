@@ -0,0 +1,57 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+use uuid::Uuid;
+
+/// Helper to create the test server against the mock Bitcoin chain, returns
+/// the bearer token of a user
+async fn create_test_server() -> (TestEnv, TestServer, HeaderValue) {
+    let env = TestEnv::start_with_fake_keycloak().await;
+
+    let email = format!("bitcoin-test-{}@example.com", Uuid::new_v4());
+    let token = env.fake_keycloak().access_token(&Uuid::new_v4(), &email);
+    let bearer = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, bearer)
+}
+
+#[tokio::test]
+async fn test_fee_estimates_are_cached() {
+    let (_env, server, bearer) = create_test_server().await;
+
+    let response =
+        server.get("/api/v1/bitcoin/fees").add_header(header::AUTHORIZATION, bearer.clone()).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    let estimates = body["data"]["estimates"].as_array().unwrap();
+    let targets: Vec<_> = estimates.iter().map(|e| e["target_blocks"].as_u64().unwrap()).collect();
+    assert_eq!(targets, [1, 3, 6, 12, 24, 144]);
+    assert_eq!(estimates[0]["sat_per_vbyte"], 20.0);
+    assert_eq!(estimates[5]["sat_per_vbyte"], 1.0);
+
+    // served from the cache until it expires
+    let response =
+        server.get("/api/v1/bitcoin/fees").add_header(header::AUTHORIZATION, bearer.clone()).await;
+    let cached: serde_json::Value = response.json();
+    assert_eq!(cached["data"]["fetched_at"], body["data"]["fetched_at"]);
+
+    let response = server.get("/api/v1/bitcoin/fees").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_mempool() {
+    let (_env, server, bearer) = create_test_server().await;
+
+    let response = server
+        .get("/api/v1/bitcoin/mempool")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["transaction_count"], 1_500);
+    assert_eq!(body["data"]["vsize"], 750_000);
+    assert_eq!(body["data"]["min_fee_sat_per_vbyte"], 1.0);
+    assert!(body["data"]["fetched_at"].is_string());
+}
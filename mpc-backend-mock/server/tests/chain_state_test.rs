@@ -0,0 +1,26 @@
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+
+#[tokio::test]
+async fn test_chain_state_is_served_from_the_cache() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+
+    // nothing was read yet
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert!(body["data"]["bitcoin_block_height"].is_null());
+    assert!(body["data"]["solana_slot"].is_null());
+
+    env.service_state().chain_state.set_bitcoin_block_height(870_123);
+    env.service_state().chain_state.set_solana_slot(300_000_000);
+
+    let response = server.get("/api/v1/meta/chain-state").await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["bitcoin_block_height"]["value"], 870_123);
+    assert_eq!(body["data"]["bitcoin_block_height"]["stale"], false);
+    assert!(body["data"]["bitcoin_block_height"]["updated_at"].is_string());
+    assert_eq!(body["data"]["solana_slot"]["value"], 300_000_000);
+}
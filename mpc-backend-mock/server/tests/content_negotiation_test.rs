@@ -0,0 +1,45 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+
+const MSGPACK: &str = "application/msgpack";
+
+#[tokio::test]
+async fn test_responses_are_msgpack_when_accepted() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    env.service_state().chain_state.set_bitcoin_block_height(870_123);
+
+    let response = server
+        .get("/api/v1/meta/chain-state")
+        .add_header(header::ACCEPT, HeaderValue::from_static(MSGPACK))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.header(header::CONTENT_TYPE), MSGPACK);
+    assert!(response.headers().get_all(header::VARY).iter().any(|vary| vary == "accept"));
+    let body: serde_json::Value = rmp_serde::from_slice(response.as_bytes()).unwrap();
+    assert_eq!(body["_status"], 200);
+    assert_eq!(body["data"]["bitcoin_block_height"]["value"], 870_123);
+
+    // JSON stays the default
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.header(header::CONTENT_TYPE), "application/json");
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["bitcoin_block_height"]["value"], 870_123);
+}
+
+#[tokio::test]
+async fn test_error_bodies_stay_json() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+
+    let response = server
+        .get("/api/v1/transactions/not-a-uuid")
+        .add_header(header::AUTHORIZATION, HeaderValue::from_static("Bearer invalid"))
+        .add_header(header::ACCEPT, HeaderValue::from_static(MSGPACK))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.header(header::CONTENT_TYPE), "application/json");
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INVALID_TOKEN");
+}
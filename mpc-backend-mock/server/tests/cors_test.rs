@@ -0,0 +1,64 @@
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum_test::{TestResponse, TestServer};
+use mpc_backend_mock_test_support::TestEnv;
+
+const ORIGIN: &str = "https://app.example.com";
+
+/// Helper to create the test server, any origin is allowed
+async fn create_test_server() -> (TestEnv, TestServer) {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server)
+}
+
+/// Preflight of a browser about to send `method` to `path` from [`ORIGIN`]
+async fn preflight(server: &TestServer, path: &str, method: &Method) -> TestResponse {
+    server
+        .method(Method::OPTIONS, path)
+        .add_header(header::ORIGIN, HeaderValue::from_static(ORIGIN))
+        .add_header(
+            header::ACCESS_CONTROL_REQUEST_METHOD,
+            HeaderValue::from_str(method.as_str()).unwrap(),
+        )
+        .add_header(
+            header::ACCESS_CONTROL_REQUEST_HEADERS,
+            HeaderValue::from_static("authorization, content-type"),
+        )
+        .await
+}
+
+/// Asserts that the preflight allows `method` from [`ORIGIN`]
+fn assert_allows(response: &TestResponse, method: &Method) {
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.header(header::ACCESS_CONTROL_ALLOW_ORIGIN), ORIGIN);
+    let allowed_methods = response.header(header::ACCESS_CONTROL_ALLOW_METHODS);
+    let allowed_methods = allowed_methods.to_str().unwrap();
+    assert!(
+        allowed_methods.split(',').any(|allowed| allowed.trim() == method.as_str()),
+        "{method} is not in `{allowed_methods}`"
+    );
+}
+
+#[tokio::test]
+async fn test_preflight_of_profile_update() {
+    let (_env, server) = create_test_server().await;
+
+    let response = preflight(&server, "/api/v1/users/me", &Method::PATCH).await;
+    assert_allows(&response, &Method::PATCH);
+}
+
+#[tokio::test]
+async fn test_preflight_of_preferences_replacement() {
+    let (_env, server) = create_test_server().await;
+
+    let response = preflight(&server, "/api/v1/users/me/preferences", &Method::PUT).await;
+    assert_allows(&response, &Method::PUT);
+}
+
+#[tokio::test]
+async fn test_preflight_of_fault_injection_toggle() {
+    let (_env, server) = create_test_server().await;
+
+    let response = preflight(&server, "/api/v1/admin/fault-injection", &Method::PUT).await;
+    assert_allows(&response, &Method::PUT);
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use mpc_backend_mock_server::BreakerOpen;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+
+#[derive(Debug)]
+enum RpcError {
+    Down,
+    Open,
+}
+
+impl From<BreakerOpen> for RpcError {
+    fn from(_: BreakerOpen) -> Self { Self::Open }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Down => write!(f, "Bitcoin RPC is down"),
+            Self::Open => write!(f, "Bitcoin RPC breaker is open"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_open_breaker_degrades_its_routes() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let breaker = env.service_state().circuit_breakers.bitcoin.clone();
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+
+    let response = server.get("/api/v1/admin/dependencies").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    let names: Vec<_> =
+        body["data"].as_array().unwrap().iter().map(|d| d["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Postgres", "Bitcoin RPC", "Solana", "Keycloak", "JWKS", "Gmail", "KMS"]);
+    assert_eq!(body["data"][1]["state"], "closed");
+    assert_eq!(body["data"][1]["degraded_routes"], json!([]));
+
+    // the default breaker opens after 5 consecutive failures
+    for _ in 0..5 {
+        let result: Result<(), RpcError> =
+            breaker.call(async { Err(RpcError::Down) }, |_| true).await;
+        assert!(result.is_err());
+    }
+
+    let response = server.get("/api/v1/admin/dependencies").await;
+    let body: serde_json::Value = response.json();
+    let bitcoin = &body["data"][1];
+    assert_eq!(bitcoin["state"], "open");
+    assert_eq!(bitcoin["last_error"], "Bitcoin RPC is down");
+    assert!(bitcoin["last_failed_at"].is_string());
+    assert_eq!(
+        bitcoin["degraded_routes"],
+        json!(["/bitcoin/balance", "/bitcoin/fees", "/bitcoin/mempool", "/bitcoin/utxos"])
+    );
+
+    // the other dependencies are not affected
+    assert_eq!(body["data"][3]["state"], "closed");
+    assert_eq!(body["data"][3]["last_error"], serde_json::Value::Null);
+    assert_eq!(body["data"][0]["state"], "untracked");
+}
@@ -0,0 +1,133 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+use notification::Notification;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Helper to create the test server and an active user with a Keycloak
+/// account, returns the Keycloak ID of the user and its bearer token
+async fn create_test_server() -> (TestEnv, TestServer, Uuid, HeaderValue) {
+    let env = TestEnv::start_with_fake_keycloak().await;
+
+    let email = format!("email-change-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = env.fake_keycloak().add_user(&email, "correct-horse-42");
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+    let token = env.fake_keycloak().access_token(&keycloak_user_id, &email);
+    let bearer = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, keycloak_user_id, bearer)
+}
+
+/// Dispatch the due notifications, returns the token of the last email change
+/// link sent to `email`
+async fn email_change_token(env: &TestEnv, email: &str) -> Option<String> {
+    let _dispatch = env.service_state().notification_service.dispatch_due().await.unwrap();
+
+    env.notifications().emails().iter().rev().find_map(|email_sent| match email_sent.notification {
+        Notification::EmailChangeVerification { ref recipients, ref link, .. }
+            if recipients.to.iter().any(|to| to == email) =>
+        {
+            link.split_once("token=").map(|(_, token)| token.to_string())
+        }
+        _ => None,
+    })
+}
+
+#[tokio::test]
+async fn test_email_change_is_applied_once_confirmed() {
+    let (env, server, keycloak_user_id, bearer) = create_test_server().await;
+    let new_email = format!("email-change-new-{}@example.com", Uuid::new_v4());
+
+    let response = server
+        .post("/api/v1/users/me/email")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email": new_email }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let token = email_change_token(&env, &new_email).await.expect("verification link was sent");
+
+    // nothing changes until the token is confirmed
+    let response =
+        server.get("/api/v1/users/me").add_header(header::AUTHORIZATION, bearer.clone()).await;
+    let body: serde_json::Value = response.json();
+    assert_ne!(body["data"]["email"], new_email.as_str());
+
+    let response =
+        server.post("/api/v1/users/email/confirm").json(&json!({ "token": token })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["email"], new_email.as_str());
+
+    let account = env.fake_keycloak().user(&keycloak_user_id).unwrap();
+    assert_eq!(account.email.as_deref(), Some(new_email.as_str()));
+    assert_eq!(account.email_verified, Some(true));
+
+    let response =
+        server.get("/api/v1/users/me").add_header(header::AUTHORIZATION, bearer.clone()).await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["email"], new_email.as_str());
+
+    // the token is single-use
+    let response =
+        server.post("/api/v1/users/email/confirm").json(&json!({ "token": token })).await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INVALID_EMAIL_CHANGE_TOKEN");
+}
+
+#[tokio::test]
+async fn test_email_change_to_taken_or_current_email_is_rejected() {
+    let (env, server, keycloak_user_id, bearer) = create_test_server().await;
+
+    // taken in Keycloak only
+    let taken_email = format!("email-change-taken-{}@example.com", Uuid::new_v4());
+    let _taken_user_id = env.fake_keycloak().add_user(&taken_email, "correct-horse-42");
+    let response = server
+        .post("/api/v1/users/me/email")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email": taken_email }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    let current_email = env.fake_keycloak().user(&keycloak_user_id).unwrap().email.unwrap();
+    let response = server
+        .post("/api/v1/users/me/email")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email": current_email }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INVALID_EMAIL_CHANGE");
+
+    // taken by a user created after the change was requested, the change is
+    // rolled back and Keycloak keeps the current email
+    let new_email = format!("email-change-race-{}@example.com", Uuid::new_v4());
+    let response = server
+        .post("/api/v1/users/me/email")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email": new_email }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let token = email_change_token(&env, &new_email).await.expect("verification link was sent");
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&new_email)
+            .bind(Uuid::new_v4())
+            .execute(env.pool())
+            .await
+            .unwrap();
+
+    let response =
+        server.post("/api/v1/users/email/confirm").json(&json!({ "token": token })).await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    let account = env.fake_keycloak().user(&keycloak_user_id).unwrap();
+    assert_eq!(account.email, Some(current_email));
+}
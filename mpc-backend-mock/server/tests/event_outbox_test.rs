@@ -0,0 +1,132 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use async_trait::async_trait;
+use events::{EventPublisher, Message};
+use mpc_backend_mock_server::{
+    EventOutbox, EventOutboxService, MemoryUserDirectory, UserManagementService,
+};
+use mpc_backend_mock_test_support::TestEnv;
+use uuid::Uuid;
+
+/// Publisher keeping the published messages, rejecting them while `failing`
+#[derive(Default)]
+struct RecordingPublisher {
+    messages: Mutex<Vec<(String, String, serde_json::Value)>>,
+    failing: AtomicBool,
+}
+
+impl RecordingPublisher {
+    fn kinds(&self) -> Vec<String> {
+        self.messages.lock().unwrap().iter().map(|(_, kind, _)| kind.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl EventPublisher for RecordingPublisher {
+    async fn publish(&self, message: &Message<'_>) -> Result<(), events::Error> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(events::Error::PublishKafka {
+                topic: "events".to_string(),
+                source: rdkafka::error::KafkaError::Canceled,
+            });
+        }
+        self.messages.lock().unwrap().push((
+            message.id.to_string(),
+            message.kind.to_string(),
+            serde_json::from_slice(message.payload).unwrap(),
+        ));
+        Ok(())
+    }
+}
+
+fn create_service(env: &TestEnv) -> UserManagementService {
+    env.user_management_service(Arc::new(MemoryUserDirectory::default()))
+        .with_event_outbox(EventOutbox::new(true))
+}
+
+#[tokio::test]
+async fn test_publish_user_events_once() {
+    let env = TestEnv::start().await;
+    let service = create_service(&env);
+    let publisher = Arc::new(RecordingPublisher::default());
+    let outbox = EventOutboxService::new(
+        env.pool().clone(),
+        Arc::clone(&publisher) as Arc<dyn EventPublisher>,
+        "test",
+        1,
+    );
+
+    let email = format!("events_{}@example.com", Uuid::new_v4().simple());
+    let user = service.create_user(&email, None, None).await.unwrap();
+    let _user_id = service.delete_user_by_email(&email).await.unwrap();
+
+    // batches of one event are published until the outbox is drained
+    assert_eq!(outbox.publish_pending().await.unwrap(), 2);
+    assert_eq!(publisher.kinds(), ["user_created", "user_deleted"]);
+
+    let messages = publisher.messages.lock().unwrap().clone();
+    let (event_id, _kind, envelope) = &messages[0];
+    assert_eq!(envelope["id"], *event_id);
+    assert_eq!(envelope["aggregate_id"], user.id.to_string());
+    assert_eq!(envelope["data"]["type"], "user_created");
+    assert_eq!(envelope["data"]["email"], email);
+
+    // the offset is past both events
+    assert_eq!(outbox.publish_pending().await.unwrap(), 0);
+    assert_eq!(publisher.kinds().len(), 2);
+}
+
+#[tokio::test]
+async fn test_failed_event_is_published_again() {
+    let env = TestEnv::start().await;
+    let service = create_service(&env);
+    let publisher = Arc::new(RecordingPublisher::default());
+    let outbox = EventOutboxService::new(
+        env.pool().clone(),
+        Arc::clone(&publisher) as Arc<dyn EventPublisher>,
+        "test",
+        10,
+    );
+
+    let email = format!("events_{}@example.com", Uuid::new_v4().simple());
+    let _user = service.create_user(&email, None, None).await.unwrap();
+
+    publisher.failing.store(true, Ordering::SeqCst);
+    assert!(outbox.publish_pending().await.is_err());
+    assert!(publisher.kinds().is_empty());
+
+    publisher.failing.store(false, Ordering::SeqCst);
+    assert_eq!(outbox.publish_pending().await.unwrap(), 1);
+    assert_eq!(publisher.kinds(), ["user_created"]);
+
+    // another publisher keeps an offset of its own
+    let other = Arc::new(RecordingPublisher::default());
+    let other_outbox = EventOutboxService::new(
+        env.pool().clone(),
+        Arc::clone(&other) as Arc<dyn EventPublisher>,
+        "other",
+        10,
+    );
+    assert_eq!(other_outbox.publish_pending().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_disabled_outbox_records_nothing() {
+    let env = TestEnv::start().await;
+    let service = create_service(&env).with_event_outbox(EventOutbox::new(false));
+    let publisher = Arc::new(RecordingPublisher::default());
+    let outbox = EventOutboxService::new(
+        env.pool().clone(),
+        Arc::clone(&publisher) as Arc<dyn EventPublisher>,
+        "test",
+        10,
+    );
+
+    let email = format!("events_{}@example.com", Uuid::new_v4().simple());
+    let _user = service.create_user(&email, None, None).await.unwrap();
+
+    assert_eq!(outbox.publish_pending().await.unwrap(), 0);
+}
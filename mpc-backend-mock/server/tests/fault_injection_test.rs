@@ -0,0 +1,66 @@
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::{Fault, FaultInjectionConfig, FaultRule};
+use mpc_backend_mock_server::FaultInjector;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+
+/// Helper to create the test server, injecting `fault` into every request of
+/// `/api/v1/meta/chain-state` once fault injection is turned on
+async fn create_test_server(fault: Fault) -> (TestEnv, TestServer) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let fault_injector = FaultInjector::new(FaultInjectionConfig {
+        enable: false,
+        rules: vec![FaultRule {
+            route: "/api/v1/meta/chain-state".to_string(),
+            percentage: 100.0,
+            fault,
+        }],
+    });
+    let service_state = env.service_state().clone().with_fault_injector(fault_injector);
+    *env.service_state_mut() = service_state;
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server)
+}
+
+#[tokio::test]
+async fn test_injected_errors_are_toggled_at_runtime() {
+    let (_env, server) = create_test_server(Fault::Error(503)).await;
+
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response =
+        server.put("/api/v1/admin/fault-injection").json(&json!({ "enable": true })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["enable"], true);
+    assert_eq!(body["data"]["rules"][0]["fault"], json!({ "type": "error", "status": 503 }));
+
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INJECTED_FAULT");
+    assert_eq!(body["error"]["retryable"], true);
+
+    // admin routes are never faulted
+    let response = server.get("/api/v1/admin/fault-injection").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response =
+        server.put("/api/v1/admin/fault-injection").json(&json!({ "enable": false })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_malformed_json() {
+    let (env, server) = create_test_server(Fault::MalformedJson).await;
+    env.service_state().fault_injector.set_enabled(true);
+
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(serde_json::from_str::<serde_json::Value>(&response.text()).is_err());
+}
@@ -0,0 +1,97 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Helper to create the test server serving GraphQL and an active user,
+/// returns the email and the bearer token of the user
+async fn create_test_server() -> (TestEnv, TestServer, String, HeaderValue) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let service_state = env.service_state().clone().with_graphql(true);
+    *env.service_state_mut() = service_state;
+
+    let email = format!("graphql-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = Uuid::new_v4();
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+    let token = env.fake_keycloak().access_token(&keycloak_user_id, &email);
+    let bearer = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, email, bearer)
+}
+
+#[tokio::test]
+async fn test_queries_use_the_bearer_token() {
+    let (_env, server, email, bearer) = create_test_server().await;
+
+    let response = server
+        .post("/api/graphql")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "query": "{ me { id email isActive } }" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["me"]["email"], email);
+    assert_eq!(body["data"]["me"]["isActive"], true);
+
+    // Service errors carry the code of the REST error
+    let response = server
+        .post("/api/graphql")
+        .add_header(header::AUTHORIZATION, bearer)
+        .json(&json!({
+            "query": "query($id: UUID!) { transaction(id: $id) { id status } }",
+            "variables": { "id": Uuid::new_v4() },
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["errors"][0]["extensions"]["code"], "TRANSACTION_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_anonymous_requests_run_public_operations_only() {
+    let (_env, server, _email, _bearer) = create_test_server().await;
+
+    let response = server.post("/api/graphql").json(&json!({ "query": "{ me { id } }" })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["errors"][0]["extensions"]["code"], "MISSING_TOKEN");
+
+    let email = format!("graphql-test-{}@example.com", Uuid::new_v4());
+    let response = server
+        .post("/api/graphql")
+        .json(&json!({
+            "query": "mutation($email: String!) { createUser(email: $email) { email isActive } }",
+            "variables": { "email": email },
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert!(body.get("errors").is_none(), "{body}");
+    assert_eq!(body["data"]["createUser"]["email"], email);
+    assert_eq!(body["data"]["createUser"]["isActive"], false);
+
+    // An invalid token is rejected before the operation runs
+    let response = server
+        .post("/api/graphql")
+        .add_header(header::AUTHORIZATION, HeaderValue::from_static("Bearer invalid"))
+        .json(&json!({ "query": "{ me { id } }" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_graphql_is_off_by_default() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+
+    let response = server.post("/api/graphql").json(&json!({ "query": "{ me { id } }" })).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
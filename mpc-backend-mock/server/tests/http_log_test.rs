@@ -0,0 +1,75 @@
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::DebugHttpConfig;
+use mpc_backend_mock_server::HttpLog;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+
+/// Helper to create the test server, logging the login and activation
+/// requests
+async fn create_test_server() -> (TestEnv, TestServer) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let http_log = HttpLog::new(DebugHttpConfig {
+        enable: true,
+        routes: vec!["/api/v1/auth/login".to_string(), "/api/v1/users/activate".to_string()],
+        redacted_fields: vec!["password".to_string(), "token".to_string()],
+        capacity: 10,
+        max_body_size: 64 * 1024,
+    });
+    let service_state = env.service_state().clone().with_http_log(http_log);
+    *env.service_state_mut() = service_state;
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server)
+}
+
+#[tokio::test]
+async fn test_bodies_of_chosen_routes_are_logged_redacted() {
+    let (_env, server) = create_test_server().await;
+
+    let _response = server
+        .post("/api/v1/auth/login")
+        .json(&json!({ "email": "http-log-test@example.com", "password": "hunter2" }))
+        .await;
+    let _response =
+        server.post("/api/v1/users/activate").json(&json!({ "token": "activation-token" })).await;
+    // other routes are not logged
+    let response = server.get("/api/v1/meta/chain-state").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server.get("/api/v1/admin/http-log").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["enable"], true);
+
+    let exchanges = body["data"]["exchanges"].as_array().unwrap();
+    assert_eq!(exchanges.len(), 2);
+    assert_eq!(exchanges[0]["method"], "POST");
+    assert_eq!(exchanges[0]["path"], "/api/v1/users/activate");
+    assert_eq!(exchanges[0]["request_body"], json!({ "token": "[REDACTED]" }));
+    assert!(exchanges[0]["response_body"]["error"]["code"].is_string());
+
+    assert_eq!(exchanges[1]["path"], "/api/v1/auth/login");
+    assert_eq!(
+        exchanges[1]["request_body"],
+        json!({ "email": "http-log-test@example.com", "password": "[REDACTED]" })
+    );
+    assert!(!body.to_string().contains("hunter2"));
+    assert!(!body.to_string().contains("activation-token"));
+}
+
+#[tokio::test]
+async fn test_nothing_is_logged_by_default() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+
+    let _response = server
+        .post("/api/v1/auth/login")
+        .json(&json!({ "email": "http-log-test@example.com", "password": "hunter2" }))
+        .await;
+
+    let response = server.get("/api/v1/admin/http-log").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"], json!({ "enable": false, "exchanges": [] }));
+}
@@ -0,0 +1,159 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::KeycloakRealmConfig;
+use mpc_backend_mock_server::{keycloak_client::KeycloakClient, JwksClient, MemoryStore, Realm};
+use mpc_backend_mock_test_support::TestEnv;
+use uuid::Uuid;
+
+const PARTNER_REALM: &str = "partner";
+
+const PARTNER_ISSUER: &str = "http://localhost:8080/realms/partner";
+
+/// Helper to create the test server and an active user with a Keycloak
+/// account, returns the ID of the user
+///
+/// The tokens of the `partner` realm are accepted besides those of the primary
+/// realm.
+async fn create_test_server() -> (TestEnv, TestServer, Uuid) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    accept_partner_realm(&mut env);
+
+    let email = format!("impersonation-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = env.fake_keycloak().add_user(&email, "correct-horse-42");
+    let user_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true) RETURNING id",
+    )
+    .bind(&email)
+    .bind(keycloak_user_id)
+    .fetch_one(env.pool())
+    .await
+    .unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, user_id)
+}
+
+fn accept_partner_realm(env: &mut TestEnv) {
+    let fake_keycloak = env.fake_keycloak();
+    fake_keycloak.add_realm(PARTNER_REALM);
+
+    let keycloak_config = env.keycloak_config().for_realm(&KeycloakRealmConfig {
+        realm: PARTNER_REALM.to_string(),
+        client_id: env.keycloak_config().client_id.clone(),
+        client_secret: env.keycloak_config().client_secret.clone(),
+        issuer: PARTNER_ISSUER.to_string(),
+    });
+    let jwks_client = JwksClient::new(
+        fake_keycloak.base_url(),
+        PARTNER_REALM,
+        Arc::new(MemoryStore::default()),
+        Duration::from_secs(300),
+    )
+    .expect("Failed to create JWKS client");
+    let keycloak_client =
+        KeycloakClient::new(keycloak_config).expect("Failed to create Keycloak client");
+    let realms = env.service_state().realms.clone().with_realm(Realm::new(
+        PARTNER_REALM.to_string(),
+        PARTNER_ISSUER.to_string(),
+        jwks_client,
+        Arc::new(keycloak_client),
+    ));
+    env.service_state_mut().realms = realms;
+}
+
+/// Bearer token of a Keycloak user of the primary realm holding the realm
+/// `roles`
+fn bearer_token(env: &TestEnv, roles: &[&str]) -> HeaderValue {
+    issued_bearer_token(env, &env.keycloak_config().issuer, roles)
+}
+
+/// Bearer token of a Keycloak user holding the realm `roles`, issued by
+/// `issuer`
+fn issued_bearer_token(env: &TestEnv, issuer: &str, roles: &[&str]) -> HeaderValue {
+    let now = chrono::Utc::now().timestamp();
+    let token = env.fake_keycloak().sign(&serde_json::json!({
+        "sub": Uuid::new_v4().to_string(),
+        "iat": now,
+        "exp": now + 300,
+        "iss": issuer,
+        "aud": "account",
+        "preferred_username": "support@example.com",
+        "realm_access": { "roles": roles },
+    }));
+
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_impersonates_user() {
+    let (env, server, user_id) = create_test_server().await;
+
+    let response = server
+        .post(&format!("/api/v1/admin/users/{user_id}/impersonate"))
+        .add_header(header::AUTHORIZATION, bearer_token(&env, &["admin"]))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["user_id"], user_id.to_string());
+    assert!(body["data"].get("refresh_token").is_none());
+    let access_token = body["data"]["access_token"].as_str().unwrap();
+
+    // the token acts as the user
+    let response = server
+        .get("/api/v1/users/me")
+        .add_header(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {access_token}")).unwrap(),
+        )
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["id"], user_id.to_string());
+
+    let response = server
+        .get("/api/v1/admin/audit-logs")
+        .add_query_param("action", "admin.user_impersonated")
+        .add_query_param("target_id", user_id)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    let entries = body["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["payload"]["admin_username"], "support@example.com");
+}
+
+#[tokio::test]
+async fn test_impersonation_requires_admin_role() {
+    let (env, server, user_id) = create_test_server().await;
+    let path = format!("/api/v1/admin/users/{user_id}/impersonate");
+
+    let response = server.post(&path).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response =
+        server.post(&path).add_header(header::AUTHORIZATION, bearer_token(&env, &["user"])).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INSUFFICIENT_PERMISSIONS");
+
+    let response = server
+        .post(&format!("/api/v1/admin/users/{}/impersonate", Uuid::new_v4()))
+        .add_header(header::AUTHORIZATION, bearer_token(&env, &["admin"]))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_of_another_realm_cannot_impersonate() {
+    let (env, server, user_id) = create_test_server().await;
+
+    let response = server
+        .post(&format!("/api/v1/admin/users/{user_id}/impersonate"))
+        .add_header(header::AUTHORIZATION, issued_bearer_token(&env, PARTNER_ISSUER, &["admin"]))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INSUFFICIENT_PERMISSIONS");
+}
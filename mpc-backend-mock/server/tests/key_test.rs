@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::KeyGenerationConfig;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Helper to create an active user, returns its bearer token
+async fn create_user(env: &TestEnv) -> HeaderValue {
+    let email = format!("key-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = Uuid::new_v4();
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+    let token = env.fake_keycloak().access_token(&keycloak_user_id, &email);
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+async fn test_keys_are_deterministic_per_user_and_chain() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    let bearer = create_user(&env).await;
+
+    let response = server
+        .post("/api/v1/keys")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "chain": "bitcoin" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let bitcoin_key: serde_json::Value = response.json();
+    assert_eq!(bitcoin_key["data"]["curve"], "secp256k1");
+    assert_eq!(bitcoin_key["data"]["public_key"].as_str().unwrap().len(), 66);
+
+    // generating again returns the existing key
+    let response = server
+        .post("/api/v1/keys")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "chain": "bitcoin" }))
+        .await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["id"], bitcoin_key["data"]["id"]);
+
+    let response = server
+        .post("/api/v1/keys")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "chain": "solana" }))
+        .await;
+    let solana_key: serde_json::Value = response.json();
+    assert_eq!(solana_key["data"]["curve"], "ed25519");
+    assert_ne!(solana_key["data"]["id"], bitcoin_key["data"]["id"]);
+
+    let id = bitcoin_key["data"]["id"].as_str().unwrap();
+    let response =
+        server.get(&format!("/api/v1/keys/{id}")).add_header(header::AUTHORIZATION, bearer).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["public_key"], bitcoin_key["data"]["public_key"]);
+
+    // keys of other users are not found
+    let other_bearer = create_user(&env).await;
+    let response = server
+        .get(&format!("/api/v1/keys/{id}"))
+        .add_header(header::AUTHORIZATION, other_bearer)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "KEY_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_simulated_key_generation_failure() {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let service_state =
+        env.service_state().clone().with_key_generation_config(KeyGenerationConfig {
+            simulate_delay: Duration::from_millis(50),
+            simulate_failure: true,
+        });
+    *env.service_state_mut() = service_state;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    let bearer = create_user(&env).await;
+
+    let response = server
+        .post("/api/v1/keys")
+        .add_header(header::AUTHORIZATION, bearer)
+        .json(&json!({ "chain": "solana" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "KEY_GENERATION_FAILED");
+}
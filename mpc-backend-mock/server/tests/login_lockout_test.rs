@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::LoginLockoutConfig;
+use mpc_backend_mock_server::entity::LoginRequest;
+use mpc_backend_mock_test_support::TestEnv;
+use uuid::Uuid;
+
+const PASSWORD: &str = "correct-horse-42";
+
+/// Helper to create the test server, locking an email after three failed
+/// logins until it is unlocked, and an active user who logs in with
+/// [`PASSWORD`]
+async fn create_test_server() -> (TestEnv, TestServer, String, Uuid) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let login_lockout_config = LoginLockoutConfig {
+        enable: true,
+        max_failed_attempts: 3,
+        failure_window: Duration::from_secs(15 * 60),
+        lockout_duration: None,
+    };
+    let service_state = env.service_state().clone().with_login_lockout_config(login_lockout_config);
+    *env.service_state_mut() = service_state;
+
+    let email = format!("lockout-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = env.fake_keycloak().add_user(&email, PASSWORD);
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, email, keycloak_user_id)
+}
+
+fn login_request(email: &str, password: &str) -> LoginRequest {
+    LoginRequest { email: email.parse().unwrap(), password: password.to_string(), session: false }
+}
+
+fn is_enabled(env: &TestEnv, keycloak_user_id: &Uuid) -> bool {
+    env.fake_keycloak().user(keycloak_user_id).unwrap().enabled == Some(true)
+}
+
+#[tokio::test]
+async fn test_failed_logins_lock_the_account_until_unlocked() {
+    let (env, server, email, keycloak_user_id) = create_test_server().await;
+
+    for _ in 0..3 {
+        let response =
+            server.post("/api/v1/auth/login").json(&login_request(&email, "wrong-password")).await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+    assert!(!is_enabled(&env, &keycloak_user_id));
+
+    // the right password does not help while the account is locked
+    let response = server.post("/api/v1/auth/login").json(&login_request(&email, PASSWORD)).await;
+    assert_eq!(response.status_code(), StatusCode::LOCKED);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "ACCOUNT_LOCKED");
+
+    let response = server
+        .get("/api/v1/admin/audit-logs")
+        .add_query_param("action", "auth.account_locked")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert!(body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|entry| entry["payload"]["email"] == email.as_str()));
+
+    let response = server.get("/api/v1/admin/login-lockouts").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    let lockout = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|lockout| lockout["email"] == email.as_str())
+        .expect("the email is locked");
+    assert_eq!(lockout["failed_attempts"], 3);
+    assert_eq!(lockout["last_failed_ip"], "127.0.0.1");
+    assert!(lockout["locked_until"].is_null());
+
+    let response = server.delete(&format!("/api/v1/admin/login-lockouts/{email}")).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(is_enabled(&env, &keycloak_user_id));
+
+    let response = server.post("/api/v1/auth/login").json(&login_request(&email, PASSWORD)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server.delete(&format!("/api/v1/admin/login-lockouts/{email}")).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "LOGIN_ATTEMPT_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_successful_login_resets_failed_logins() {
+    let (env, server, email, keycloak_user_id) = create_test_server().await;
+
+    for password in ["wrong-password", "wrong-password", PASSWORD, "wrong-password"] {
+        let _response =
+            server.post("/api/v1/auth/login").json(&login_request(&email, password)).await;
+    }
+
+    assert!(is_enabled(&env, &keycloak_user_id));
+    let response = server.post("/api/v1/auth/login").json(&login_request(&email, PASSWORD)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
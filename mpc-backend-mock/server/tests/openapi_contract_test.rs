@@ -0,0 +1,345 @@
+//! Contract tests generated from the OpenAPI document: every documented
+//! operation is requested and must answer with a documented status and a body
+//! of the documented schema, and no undocumented method is served at the
+//! documented paths. The routes missing from the document are caught by the
+//! unit tests of the versioned router.
+
+use axum::{
+    http::{header, HeaderValue, Method, StatusCode},
+    Extension,
+};
+use axum_test::{TestResponse, TestServer};
+use mpc_backend_mock_core::{Capabilities, ServerInfo};
+use mpc_backend_mock_server::ApiDoc;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::{json, Value};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+/// Methods of the operations of a path item, as keyed in the document
+const METHODS: [(&str, Method); 5] = [
+    ("get", Method::GET),
+    ("put", Method::PUT),
+    ("post", Method::POST),
+    ("delete", Method::DELETE),
+    ("patch", Method::PATCH),
+];
+
+/// Operation of the document, with its path and method
+struct Operation<'a> {
+    path: &'a str,
+    method: Method,
+    spec: &'a Value,
+}
+
+impl Operation<'_> {
+    fn parameters(&self, location: &'static str) -> impl Iterator<Item = &Value> {
+        self.spec["parameters"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(move |parameter| parameter["in"] == location)
+    }
+
+    /// Whether the operation takes input which the placeholders may not
+    /// satisfy, so that a rejection of the request is expected
+    fn takes_input(&self) -> bool {
+        self.spec.get("requestBody").is_some() || self.parameters("query").next().is_some()
+    }
+
+    /// Whether the response is not a complete body, which is exercised by the
+    /// tests of the operation instead
+    fn is_long_lived(&self) -> bool {
+        let responses = &self.spec["responses"];
+        responses.get("101").is_some()
+            || responses
+                .as_object()
+                .into_iter()
+                .flatten()
+                .any(|(_, response)| response["content"].get("text/event-stream").is_some())
+    }
+
+    /// URI of the operation, with placeholders for its path parameters and
+    /// required query parameters
+    fn uri(&self) -> String {
+        let path = self.parameters("path").fold(self.path.to_string(), |path, parameter| {
+            let name = parameter["name"].as_str().unwrap_or_default();
+            path.replace(&format!("{{{name}}}"), &placeholder(parameter))
+        });
+        let query = self
+            .parameters("query")
+            .filter(|parameter| parameter["required"] == true)
+            .map(|parameter| {
+                format!(
+                    "{}={}",
+                    parameter["name"].as_str().unwrap_or_default(),
+                    placeholder(parameter)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if query.is_empty() {
+            path
+        } else {
+            format!("{path}?{}", query.join("&"))
+        }
+    }
+}
+
+/// Value of a parameter which is well formed but refers to nothing
+fn placeholder(parameter: &Value) -> String {
+    let schema = &parameter["schema"];
+    match (parameter["name"].as_str().unwrap_or_default(), schema["format"].as_str()) {
+        (_, Some("uuid")) => Uuid::new_v4().to_string(),
+        (_, Some("date")) => chrono::Utc::now().date_naive().to_string(),
+        (_, Some("date-time")) => chrono::Utc::now().to_rfc3339(),
+        ("email", _) => "contract-test@example.com".to_string(),
+        ("pubkey", _) => "11111111111111111111111111111111".to_string(),
+        _ if schema_types(schema).contains(&"integer") => "1".to_string(),
+        _ if schema_types(schema).contains(&"boolean") => "false".to_string(),
+        _ => "placeholder".to_string(),
+    }
+}
+
+fn schema_types(schema: &Value) -> Vec<&str> {
+    match schema["type"] {
+        Value::String(ref type_) => vec![type_.as_str()],
+        Value::Array(ref types) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Check that `value` conforms to `schema`, resolving references against the
+/// components of `document`, returns the violation with its location
+fn validate(document: &Value, schema: &Value, value: &Value, location: &str) -> Result<(), String> {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        return validate(document, &document["components"]["schemas"][name], value, location);
+    }
+    if let Some(schemas) = schema["allOf"].as_array() {
+        for schema in schemas {
+            validate(document, schema, value, location)?;
+        }
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(schemas) = schema[key].as_array() {
+            if !schemas.iter().any(|schema| validate(document, schema, value, location).is_ok()) {
+                return Err(format!("`{location}` matches none of the `{key}` schemas: {value}"));
+            }
+        }
+    }
+    if let Some(variants) = schema["enum"].as_array() {
+        if !variants.contains(value) {
+            return Err(format!("`{location}` is not one of {variants:?}: {value}"));
+        }
+    }
+
+    let types = schema_types(schema);
+    let type_ = match *value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(ref number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    if !types.is_empty()
+        && !types.contains(&type_)
+        && !(type_ == "integer" && types.contains(&"number"))
+    {
+        return Err(format!("`{location}` is {type_} rather than {types:?}: {value}"));
+    }
+
+    match *value {
+        Value::Array(ref items) => {
+            for (index, item) in items.iter().enumerate() {
+                validate(document, &schema["items"], item, &format!("{location}/{index}"))?;
+            }
+        }
+        Value::Object(ref fields) => {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let required = required.as_str().unwrap_or_default();
+                if !fields.contains_key(required) {
+                    return Err(format!("`{location}` lacks the required `{required}`: {value}"));
+                }
+            }
+            for (name, field) in fields {
+                let location = format!("{location}/{name}");
+                match (schema["properties"].get(name), &schema["additionalProperties"]) {
+                    (Some(property), _) => validate(document, property, field, &location)?,
+                    (None, Value::Bool(false)) => {
+                        return Err(format!("`{location}` is not a documented property"));
+                    }
+                    (None, additional @ Value::Object(_)) => {
+                        validate(document, additional, field, &location)?;
+                    }
+                    (None, _) => {}
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Check the response of `operation` against the document
+fn check_response(document: &Value, operation: &Operation<'_>, response: &TestResponse) {
+    let name = format!("{} {}", operation.method, operation.path);
+    let status = response.status_code();
+    let body = response.as_bytes();
+
+    assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{name} is documented but not served");
+    // the handlers answer their 404s with an error body, the router without
+    assert!(
+        status != StatusCode::NOT_FOUND || !body.is_empty(),
+        "{name} is documented but not served"
+    );
+
+    let Some(documented) = operation.spec["responses"].get(status.as_str()) else {
+        // the placeholders may be rejected before the handler runs
+        assert!(
+            operation.takes_input()
+                && matches!(status, StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY),
+            "{name} answered with the undocumented status {status}: {}",
+            response.text()
+        );
+        return;
+    };
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+    let Some(schema) = documented["content"]["application/json"].get("schema") else {
+        return;
+    };
+    assert!(is_json, "{name} answered {status} without the documented JSON body");
+
+    let body: Value = serde_json::from_slice(body).expect("Body is not JSON");
+    // success bodies are encapsulated, the schema documents their data
+    let (value, location) = if status.is_success() && body.get("_status").is_some() {
+        (&body["data"], "/data")
+    } else {
+        (&body, "")
+    };
+    if let Err(violation) = validate(document, schema, value, location) {
+        panic!("{name} answered {status} with a body breaking its schema: {violation}");
+    }
+}
+
+/// Bearer token of an active user of the database holding the admin role, so
+/// that every operation gets past authentication
+async fn admin_bearer(env: &TestEnv) -> HeaderValue {
+    let email = format!("contract-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = Uuid::new_v4();
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+
+    let now = chrono::Utc::now().timestamp();
+    let token = env.fake_keycloak().sign(&json!({
+        "sub": keycloak_user_id.to_string(),
+        "iat": now,
+        "exp": now + 300,
+        "iss": env.keycloak_config().issuer,
+        "aud": "account",
+        "email": email,
+        "preferred_username": email,
+        "realm_access": { "roles": ["admin"] },
+    }));
+
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+/// Test server with the server info the full server provides
+fn create_test_server(env: &TestEnv) -> TestServer {
+    let server_info = ServerInfo {
+        version: "0.0.0".to_string(),
+        branch: "main".to_string(),
+        commit_hash: "0000000".to_string(),
+        bitcoin_network: "regtest".to_string(),
+        solana_cluster: "localnet".to_string(),
+        start_time: chrono::Utc::now(),
+        capabilities: Capabilities::new(),
+    };
+
+    TestServer::new(env.router().layer(Extension(server_info)))
+        .expect("Failed to create test server")
+}
+
+/// Operations of the document, in path order
+fn operations(document: &Value) -> Vec<Operation<'_>> {
+    document["paths"]
+        .as_object()
+        .expect("Document has paths")
+        .iter()
+        .flat_map(|(path, item)| {
+            METHODS.iter().filter_map(move |(key, method)| {
+                item.get(*key).map(|spec| Operation { path, method: method.clone(), spec })
+            })
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_documented_operations_honor_their_contract() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = create_test_server(&env);
+    let bearer = admin_bearer(&env).await;
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+    let operations = operations(&document);
+    assert!(!operations.is_empty());
+    for operation in operations.iter().filter(|operation| !operation.is_long_lived()) {
+        let mut request = server
+            .method(operation.method.clone(), &operation.uri())
+            .add_header(header::AUTHORIZATION, bearer.clone());
+        if operation.spec.get("requestBody").is_some() {
+            request = request.json(&json!({}));
+        }
+
+        check_response(&document, operation, &request.await);
+    }
+}
+
+#[tokio::test]
+async fn test_undocumented_methods_are_not_served() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = create_test_server(&env);
+    let bearer = admin_bearer(&env).await;
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+    for (path, item) in document["paths"].as_object().expect("Document has paths") {
+        // placeholders which are well formed for any path parameter
+        let uri = path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') {
+                    Uuid::new_v4().to_string()
+                } else {
+                    segment.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        for (key, method) in METHODS.iter().filter(|(key, _)| item.get(*key).is_none()) {
+            let response = server
+                .method(method.clone(), &uri)
+                .add_header(header::AUTHORIZATION, bearer.clone())
+                .await;
+            assert_eq!(
+                response.status_code(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {path} is served but not documented",
+                key.to_uppercase()
+            );
+        }
+    }
+}
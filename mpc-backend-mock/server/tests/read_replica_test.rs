@@ -0,0 +1,50 @@
+use std::{sync::Arc, time::Duration};
+
+use mpc_backend_mock_server::MemoryUserDirectory;
+use mpc_backend_mock_test_support::TestEnv;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_reads_fall_back_to_primary_when_replica_is_down() {
+    let env = TestEnv::start().await;
+
+    // nothing listens on the discard port
+    let unreachable_replica = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(500))
+        .connect_lazy_with(PgConnectOptions::new().host("127.0.0.1").port(9));
+    let service = env
+        .user_management_service(Arc::new(MemoryUserDirectory::default()))
+        .with_read_replicas(vec![unreachable_replica]);
+
+    let email = format!("replica_{}@example.com", Uuid::new_v4().simple());
+    let created = service.create_user(&email, None, None).await.unwrap();
+
+    let user = service.get_user_by_id(created.id).await.unwrap();
+    assert_eq!(user.email, email);
+    let user = service.get_user_by_email(email.clone()).await.unwrap();
+    assert_eq!(user.id, created.id);
+}
+
+#[tokio::test]
+async fn test_reads_use_read_only_replica() {
+    let env = TestEnv::start().await;
+
+    // the primary in a read-only session stands in for a replica
+    let replica = PgPoolOptions::new().connect_lazy_with(
+        env.pool()
+            .connect_options()
+            .as_ref()
+            .clone()
+            .options([("default_transaction_read_only", "on")]),
+    );
+    let service = env
+        .user_management_service(Arc::new(MemoryUserDirectory::default()))
+        .with_read_replicas(vec![replica]);
+
+    let email = format!("replica_{}@example.com", Uuid::new_v4().simple());
+    let created = service.create_user(&email, None, None).await.unwrap();
+
+    let user = service.get_user_by_keycloak_id(&created.keycloak_user_id, None).await.unwrap();
+    assert_eq!(user.id, created.id);
+}
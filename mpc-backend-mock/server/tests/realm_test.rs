@@ -0,0 +1,226 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::KeycloakRealmConfig;
+use mpc_backend_mock_server::{
+    keycloak_client::KeycloakClient, JwksClient, MemoryStore, MemoryUserDirectory, Realm,
+};
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+use uuid::Uuid;
+
+const PARTNER_REALM: &str = "partner";
+
+const PARTNER_ISSUER: &str = "http://localhost:8080/realms/partner";
+
+/// Helper to create the test server, accepting the tokens of the `partner`
+/// realm besides those of the primary realm and managing its users in memory
+async fn create_test_server() -> (TestEnv, TestServer) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let fake_keycloak = env.fake_keycloak();
+    fake_keycloak.add_realm(PARTNER_REALM);
+
+    let keycloak_config = env.keycloak_config().for_realm(&KeycloakRealmConfig {
+        realm: PARTNER_REALM.to_string(),
+        client_id: env.keycloak_config().client_id.clone(),
+        client_secret: env.keycloak_config().client_secret.clone(),
+        issuer: PARTNER_ISSUER.to_string(),
+    });
+    let jwks_client = JwksClient::new(
+        fake_keycloak.base_url(),
+        PARTNER_REALM,
+        Arc::new(MemoryStore::default()),
+        Duration::from_secs(300),
+    )
+    .expect("Failed to create JWKS client");
+    let keycloak_client =
+        KeycloakClient::new(keycloak_config).expect("Failed to create Keycloak client");
+    let realms = env.service_state().realms.clone().with_realm(Realm::new(
+        PARTNER_REALM.to_string(),
+        PARTNER_ISSUER.to_string(),
+        jwks_client,
+        Arc::new(keycloak_client),
+    ));
+    let mut service_state = env
+        .service_state()
+        .clone()
+        .with_realm_directory(PARTNER_REALM, Arc::new(MemoryUserDirectory::default()));
+    service_state.realms = realms;
+    *env.service_state_mut() = service_state;
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server)
+}
+
+/// Create a user with `email` in `realm`, `None` for the primary realm,
+/// returns its Keycloak user ID
+async fn create_user(server: &TestServer, email: &str, realm: Option<&str>) -> Uuid {
+    let response =
+        server.post("/api/v1/users").json(&json!({ "email": email, "realm": realm })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["user"]["email"], email);
+    assert_eq!(body["data"]["user"].get("realm").and_then(|realm| realm.as_str()), realm);
+
+    body["data"]["user"]["keycloak_user_id"].as_str().unwrap().parse().unwrap()
+}
+
+fn unique_email() -> String { format!("realm-test-{}@example.com", Uuid::new_v4()) }
+
+/// Bearer token of `keycloak_user_id` issued by `issuer`
+fn bearer_token(env: &TestEnv, keycloak_user_id: &Uuid, email: &str, issuer: &str) -> HeaderValue {
+    let now = chrono::Utc::now().timestamp();
+    let token = env.fake_keycloak().sign(&serde_json::json!({
+        "sub": keycloak_user_id.to_string(),
+        "iat": now,
+        "exp": now + 300,
+        "iss": issuer,
+        "aud": "account",
+        "email": email,
+        "preferred_username": email,
+        "email_verified": true,
+    }));
+
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+async fn test_users_are_scoped_to_the_realm_of_their_token() {
+    let (env, server) = create_test_server().await;
+    let partner_email = unique_email();
+    let partner_user_id = create_user(&server, &partner_email, Some(PARTNER_REALM)).await;
+    let primary_email = unique_email();
+    let primary_user_id = create_user(&server, &primary_email, None).await;
+    let primary_issuer = env.keycloak_config().issuer.clone();
+
+    let response = server
+        .get("/api/v1/users/me")
+        .add_header(
+            header::AUTHORIZATION,
+            bearer_token(&env, &partner_user_id, &partner_email, PARTNER_ISSUER),
+        )
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["email"], partner_email.as_str());
+    assert_eq!(body["data"]["realm"], PARTNER_REALM);
+
+    let response = server
+        .get("/api/v1/users/me")
+        .add_header(
+            header::AUTHORIZATION,
+            bearer_token(&env, &primary_user_id, &primary_email, &primary_issuer),
+        )
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert!(body["data"].get("realm").is_none());
+
+    // a user is not found through the tokens of another realm
+    for (user_id, email, issuer) in [
+        (&partner_user_id, &partner_email, primary_issuer.as_str()),
+        (&primary_user_id, &primary_email, PARTNER_ISSUER),
+    ] {
+        let response = server
+            .get("/api/v1/users/me")
+            .add_header(header::AUTHORIZATION, bearer_token(&env, user_id, email, issuer))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["error"]["code"], "USER_NOT_FOUND");
+    }
+}
+
+#[tokio::test]
+async fn test_token_of_unknown_issuer_is_rejected() {
+    let (env, server) = create_test_server().await;
+    let email = unique_email();
+    let keycloak_user_id = create_user(&server, &email, None).await;
+
+    let response = server
+        .get("/api/v1/users/me")
+        .add_header(
+            header::AUTHORIZATION,
+            bearer_token(&env, &keycloak_user_id, &email, "http://localhost:8080/realms/other"),
+        )
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INVALID_TOKEN");
+}
+
+#[tokio::test]
+async fn test_emails_are_unique_within_a_realm() {
+    let (_env, server) = create_test_server().await;
+    let email = unique_email();
+    let partner_user_id = create_user(&server, &email, Some(PARTNER_REALM)).await;
+    let primary_user_id = create_user(&server, &email, None).await;
+    assert_ne!(partner_user_id, primary_user_id);
+
+    let response =
+        server.post("/api/v1/users").json(&json!({ "email": email, "realm": PARTNER_REALM })).await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "USER_ALREADY_EXISTS");
+
+    let response = server
+        .post("/api/v1/users")
+        .json(&json!({ "email": unique_email(), "realm": "unknown" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "UNKNOWN_REALM");
+}
+
+#[tokio::test]
+async fn test_users_are_listed_in_the_realm_of_their_token() {
+    let (env, server) = create_test_server().await;
+    let email = unique_email();
+    let partner_user_id = create_user(&server, &email, Some(PARTNER_REALM)).await;
+    let primary_user_id = create_user(&server, &email, None).await;
+    let primary_issuer = env.keycloak_config().issuer.clone();
+
+    for (user_id, issuer, realm) in [
+        (&partner_user_id, PARTNER_ISSUER, Some(PARTNER_REALM)),
+        (&primary_user_id, primary_issuer.as_str(), None),
+    ] {
+        let response = server
+            .get("/api/v1/users")
+            .add_query_param("email_like", &email)
+            .add_header(header::AUTHORIZATION, bearer_token(&env, user_id, &email, issuer))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        let users = body["data"].as_array().unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0]["keycloak_user_id"], user_id.to_string());
+        assert_eq!(users[0].get("realm").and_then(|realm| realm.as_str()), realm);
+        assert_eq!(body["_metadata"]["totalCount"], 1);
+    }
+}
+
+#[tokio::test]
+async fn test_password_is_not_changed_for_another_realm() {
+    let (env, server) = create_test_server().await;
+    let email = unique_email();
+    let partner_user_id = create_user(&server, &email, Some(PARTNER_REALM)).await;
+    // an account of the primary realm with the same email, whose password
+    // must not be checked in place of the partner one
+    let _primary_user_id = env.fake_keycloak().add_user(&email, "current-password-42");
+
+    let response = server
+        .post("/api/v1/users/me/password")
+        .add_header(
+            header::AUTHORIZATION,
+            bearer_token(&env, &partner_user_id, &email, PARTNER_ISSUER),
+        )
+        .json(&json!({
+            "current_password": "current-password-42",
+            "new_password": "correct-horse-battery-42",
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "PASSWORD_MANAGED_BY_REALM");
+}
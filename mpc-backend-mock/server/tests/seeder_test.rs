@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use mpc_backend_mock_server::{Fixtures, MemoryUserDirectory, SeedReport, SeedService};
+use mpc_backend_mock_test_support::TestEnv;
+use solana_sdk::signature::Signature;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_seed_fixtures_is_idempotent() {
+    let env = TestEnv::start().await;
+    let directory = MemoryUserDirectory::default();
+    let service = SeedService::new(env.pool().clone(), Arc::new(directory.clone()));
+
+    let marker = Uuid::new_v4();
+    // signatures are unique across the tests
+    let mut signature = [1; 64];
+    signature[..16].copy_from_slice(marker.as_bytes());
+    let signature = Signature::from(signature);
+    let marker = marker.simple();
+    let fixtures: Fixtures = serde_yaml::from_str(&format!(
+        r"
+users:
+  - email: Alice_{marker}@example.com
+    password: Passw0rd!
+    bitcoin_addresses:
+      - tb1q{marker}
+    transactions:
+      - signature: {signature}
+        status: finalized
+        slot: 42
+  - email: bob_{marker}@example.com
+    active: false
+"
+    ))
+    .unwrap();
+
+    let report = service.seed(&fixtures).await.unwrap();
+    assert_eq!(
+        report,
+        SeedReport {
+            users_created: 2,
+            wallets_created: 1,
+            transactions_created: 1,
+            ..SeedReport::default()
+        }
+    );
+
+    let (keycloak_user_id, is_active): (Uuid, bool) =
+        sqlx::query_as("SELECT keycloak_user_id, is_active FROM users WHERE email = $1")
+            .bind(format!("alice_{marker}@example.com"))
+            .fetch_one(env.pool())
+            .await
+            .unwrap();
+    assert!(is_active);
+    let account = directory.get(&keycloak_user_id).unwrap();
+    assert!(account.enabled);
+    assert!(account.email_verified);
+    assert_eq!(account.password.as_deref(), Some("Passw0rd!"));
+
+    let (status, slot): (String, Option<i64>) =
+        sqlx::query_as("SELECT status::TEXT, slot FROM transactions WHERE signature = $1")
+            .bind(signature.to_string())
+            .fetch_one(env.pool())
+            .await
+            .unwrap();
+    assert_eq!(status, "finalized");
+    assert_eq!(slot, Some(42));
+
+    // seeding again finds everything in place
+    let report = service.seed(&fixtures).await.unwrap();
+    assert_eq!(
+        report,
+        SeedReport {
+            users_skipped: 2,
+            wallets_skipped: 1,
+            transactions_skipped: 1,
+            ..SeedReport::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_seed_rejects_invalid_fixtures_before_writing() {
+    let env = TestEnv::start().await;
+    let directory = MemoryUserDirectory::default();
+    let service = SeedService::new(env.pool().clone(), Arc::new(directory));
+
+    let email = format!("seed_{}@example.com", Uuid::new_v4().simple());
+    let fixtures: Fixtures = serde_yaml::from_str(&format!(
+        r"
+users:
+  - email: {email}
+  - email: carol@example.com
+    transactions:
+      - signature: not-a-signature
+"
+    ))
+    .unwrap();
+
+    let err = service.seed(&fixtures).await.unwrap_err();
+    assert!(err.to_string().contains("not-a-signature"), "{err}");
+
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email = $1")
+        .bind(&email)
+        .fetch_one(env.pool())
+        .await
+        .unwrap();
+    assert_eq!(users, 0);
+}
@@ -0,0 +1,106 @@
+use axum::http::{header, HeaderName, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_core::config::SessionConfig;
+use mpc_backend_mock_server::entity::LoginRequest;
+use mpc_backend_mock_test_support::TestEnv;
+use uuid::Uuid;
+
+const PASSWORD: &str = "correct-horse-42";
+
+/// Helper to create the test server, with sessions over plain HTTP if
+/// `enable` is set, and an active user who logs in with [`PASSWORD`]
+async fn create_test_server(enable: bool) -> (TestEnv, TestServer, String) {
+    let mut env = TestEnv::start_with_fake_keycloak().await;
+    let session_config = SessionConfig { enable, secure: false, ..SessionConfig::default() };
+    let service_state = env.service_state().clone().with_session_config(session_config);
+    *env.service_state_mut() = service_state;
+
+    let email = format!("session-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = env.fake_keycloak().add_user(&email, PASSWORD);
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, email)
+}
+
+fn login_request(email: &str, session: bool) -> LoginRequest {
+    LoginRequest { email: email.parse().unwrap(), password: PASSWORD.to_string(), session }
+}
+
+#[tokio::test]
+async fn test_session_cookie_authenticates_requests() {
+    let (_env, server, email) = create_test_server(true).await;
+
+    let response = server.post("/api/v1/auth/login").json(&login_request(&email, true)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let session_cookie = response.cookie("mpc_session");
+    let csrf_cookie = response.cookie("mpc_session_csrf");
+    assert_eq!(session_cookie.http_only(), Some(true));
+    assert_ne!(csrf_cookie.http_only(), Some(true));
+    let cookies = HeaderValue::from_str(&format!(
+        "mpc_session={}; mpc_session_csrf={}",
+        session_cookie.value(),
+        csrf_cookie.value()
+    ))
+    .unwrap();
+
+    let response = server.get("/api/v1/users/me").add_header(header::COOKIE, cookies.clone()).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.text().contains(&email));
+
+    // mutating requests must echo the CSRF token
+    let response =
+        server.post("/api/v1/auth/logout").add_header(header::COOKIE, cookies.clone()).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server
+        .post("/api/v1/auth/logout")
+        .add_header(header::COOKIE, cookies.clone())
+        .add_header(
+            HeaderName::from_static("x-csrf-token"),
+            session_cookie.value().parse().unwrap(),
+        )
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .post("/api/v1/auth/logout")
+        .add_header(header::COOKIE, cookies.clone())
+        .add_header(HeaderName::from_static("x-csrf-token"), csrf_cookie.value().parse().unwrap())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    // the session is over
+    let response = server.get("/api/v1/users/me").add_header(header::COOKIE, cookies).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_login_without_session_sets_no_cookie() {
+    let (_env, server, email) = create_test_server(true).await;
+
+    let response = server.post("/api/v1/auth/login").json(&login_request(&email, false)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.maybe_cookie("mpc_session").is_none());
+}
+
+#[tokio::test]
+async fn test_session_requires_sessions_enabled() {
+    let (_env, server, email) = create_test_server(false).await;
+
+    let response = server.post("/api/v1/auth/login").json(&login_request(&email, true)).await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("SESSIONS_DISABLED"));
+
+    // the cookie is ignored while sessions are disabled
+    let response = server
+        .get("/api/v1/users/me")
+        .add_header(header::COOKIE, HeaderValue::from_static("mpc_session=forged"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,114 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Helper to create the test server and an active user, returns its bearer
+/// token
+async fn create_test_server() -> (TestEnv, TestServer, HeaderValue) {
+    let env = TestEnv::start_with_fake_keycloak().await;
+
+    let email = format!("signing-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = Uuid::new_v4();
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+    let token = env.fake_keycloak().access_token(&keycloak_user_id, &email);
+    let bearer = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, bearer)
+}
+
+#[tokio::test]
+async fn test_signing_session_moves_through_the_rounds() {
+    let (_env, server, bearer) = create_test_server().await;
+
+    let response = server
+        .post("/api/v1/signing/sessions")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "payload": "DEADBEEF", "round_duration_milliseconds": 60_000 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["status"], "round1");
+    assert_eq!(body["data"]["payload"], "deadbeef");
+    assert!(body["data"]["signature"].is_null());
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let response = server
+        .get(&format!("/api/v1/signing/sessions/{id}"))
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["status"], "round1");
+
+    // without delay the rounds are over right away
+    let response = server
+        .post("/api/v1/signing/sessions")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "payload": "deadbeef", "round_duration_milliseconds": 0 }))
+        .await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["status"], "complete");
+    let signature = body["data"]["signature"].as_str().unwrap().to_string();
+    assert_eq!(signature.len(), 128);
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let response = server
+        .get(&format!("/api/v1/signing/sessions/{id}"))
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["signature"], signature.as_str());
+
+    let response = server
+        .get(&format!("/api/v1/signing/sessions/{}", Uuid::new_v4()))
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "SIGNING_SESSION_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_signing_session_failure_injection() {
+    let (_env, server, bearer) = create_test_server().await;
+
+    let response = server
+        .post("/api/v1/signing/sessions")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({
+            "payload": "deadbeef",
+            "round_duration_milliseconds": 0,
+            "fail_at": "round2"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["status"], "failed");
+    assert_eq!(body["data"]["error"], "Simulated failure of a party in round 2");
+    assert!(body["data"]["signature"].is_null());
+
+    let response = server
+        .post("/api/v1/signing/sessions")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "payload": "not hex" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "INVALID_SIGNING_SESSION");
+
+    let response = server
+        .post("/api/v1/signing/sessions")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "payload": "deadbeef", "fail_at": "complete" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
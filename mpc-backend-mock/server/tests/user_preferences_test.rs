@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::{header, HeaderValue, StatusCode},
+    routing, Json, Router,
+};
+use axum_test::TestServer;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Helper to create the test server and an active user, returns the bearer
+/// token of the user
+async fn create_test_server() -> (TestEnv, TestServer, String, HeaderValue) {
+    let env = TestEnv::start_with_fake_keycloak().await;
+
+    let email = format!("preferences-test-{}@example.com", Uuid::new_v4());
+    let keycloak_user_id = Uuid::new_v4();
+    let _result =
+        sqlx::query("INSERT INTO users (email, keycloak_user_id, is_active) VALUES ($1, $2, true)")
+            .bind(&email)
+            .bind(keycloak_user_id)
+            .execute(env.pool())
+            .await
+            .unwrap();
+    let token = env.fake_keycloak().access_token(&keycloak_user_id, &email);
+    let bearer = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    (env, server, email, bearer)
+}
+
+/// Queue an activation email to `email` and dispatch the due notifications
+async fn notify(env: &TestEnv, email: &str) {
+    let payload = json!({
+        "type": "activation_email",
+        "to": [email],
+        "link": "http://localhost:3000/activate?token=abc",
+    });
+    let _result = sqlx::query(
+        "INSERT INTO notification_outbox (kind, recipient, payload) VALUES ($1, $2, $3)",
+    )
+    .bind("activation_email")
+    .bind(email)
+    .bind(payload)
+    .execute(env.pool())
+    .await
+    .unwrap();
+
+    let _dispatch = env.service_state().notification_service.dispatch_due().await.unwrap();
+}
+
+async fn notification_status(env: &TestEnv, email: &str) -> String {
+    sqlx::query_scalar("SELECT status::TEXT FROM notification_outbox WHERE recipient = $1")
+        .bind(email)
+        .fetch_one(env.pool())
+        .await
+        .unwrap()
+}
+
+/// Start a webhook recording the bodies posted to it, returns its URL
+async fn start_webhook() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    async fn record(
+        State(bodies): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+        Json(body): Json<serde_json::Value>,
+    ) -> StatusCode {
+        bodies.lock().unwrap().push(body);
+        StatusCode::NO_CONTENT
+    }
+
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let router =
+        Router::new().route("/hook", routing::post(record)).with_state(Arc::clone(&bodies));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let _handle = tokio::spawn(async move { axum::serve(listener, router).await });
+
+    (format!("http://{address}/hook"), bodies)
+}
+
+#[tokio::test]
+async fn test_preferences_default_until_replaced() {
+    let (_env, server, _email, bearer) = create_test_server().await;
+
+    let response = server
+        .get("/api/v1/users/me/preferences")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["email_enabled"], true);
+    assert!(body["data"]["webhook_url"].is_null());
+    assert!(body["data"].get("updated_at").is_none());
+
+    let response = server
+        .put("/api/v1/users/me/preferences")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email_enabled": false, "locale": "zh-TW" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get("/api/v1/users/me/preferences")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["email_enabled"], false);
+    assert_eq!(body["data"]["locale"], "zh-TW");
+    assert!(body["data"]["updated_at"].is_string());
+
+    for invalid in [
+        json!({ "email_enabled": true, "webhook_url": "ftp://example.com/hook" }),
+        json!({ "email_enabled": true, "locale": "zh_TW" }),
+    ] {
+        let response = server
+            .put("/api/v1/users/me/preferences")
+            .add_header(header::AUTHORIZATION, bearer.clone())
+            .json(&invalid)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["error"]["code"], "INVALID_USER_PREFERENCES");
+    }
+}
+
+#[tokio::test]
+async fn test_dispatcher_follows_preferences() {
+    let (env, server, email, bearer) = create_test_server().await;
+    let (webhook_url, webhook_bodies) = start_webhook().await;
+
+    // emails off and no webhook, the notification is skipped
+    let response = server
+        .put("/api/v1/users/me/preferences")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email_enabled": false }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    notify(&env, &email).await;
+    assert_eq!(notification_status(&env, &email).await, "skipped");
+    assert!(env.notifications().emails().is_empty());
+
+    // the webhook is posted the notification in the preferred locale
+    let _result = sqlx::query("DELETE FROM notification_outbox").execute(env.pool()).await;
+    let response = server
+        .put("/api/v1/users/me/preferences")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "email_enabled": true, "webhook_url": webhook_url, "locale": "zh-TW" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    notify(&env, &email).await;
+    assert_eq!(notification_status(&env, &email).await, "sent");
+
+    let emails = env.notifications().emails();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].notification.locale(), Some("zh-TW"));
+    let webhook_bodies = webhook_bodies.lock().unwrap();
+    assert_eq!(webhook_bodies.len(), 1);
+    assert_eq!(webhook_bodies[0]["type"], "activation_email");
+    assert_eq!(webhook_bodies[0]["locale"], "zh-TW");
+}
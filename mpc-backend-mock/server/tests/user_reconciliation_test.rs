@@ -0,0 +1,51 @@
+use std::{sync::Arc, time::Duration};
+
+use mpc_backend_mock_server::{MemoryUserDirectory, UserDirectory};
+use mpc_backend_mock_test_support::TestEnv;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_reconcile_users_repairs_drift() {
+    let env = TestEnv::start().await;
+    let directory = MemoryUserDirectory::default();
+    let service = env.user_management_service(Arc::new(directory.clone()));
+
+    // an account left behind by a failed creation
+    let orphaned_account = directory
+        .create_user(&format!("orphan_{}@example.com", Uuid::new_v4().simple()), None, None)
+        .await
+        .unwrap();
+
+    // a soft-deleted user whose account was enabled again
+    let deleted_email = format!("reconcile_{}@example.com", Uuid::new_v4().simple());
+    let deleted_user = service.create_user(&deleted_email, None, None).await.unwrap();
+    let _user_id = service.delete_user_by_email(&deleted_email).await.unwrap();
+    directory.set_enabled(&deleted_user.keycloak_user_id, true).await.unwrap();
+
+    // a user whose account is gone
+    let missing_email = format!("reconcile_{}@example.com", Uuid::new_v4().simple());
+    let missing_user = service.create_user(&missing_email, None, None).await.unwrap();
+    directory.delete(&missing_user.keycloak_user_id).await.unwrap();
+
+    let reconciliation = service.reconcile_users(Duration::ZERO).await.unwrap();
+    assert_eq!(reconciliation.deleted_accounts, 1);
+    assert_eq!(reconciliation.toggled_accounts, 1);
+    assert!(reconciliation.users_without_account.contains(&missing_user.id));
+    assert!(!reconciliation.users_without_account.contains(&deleted_user.id));
+
+    assert_eq!(directory.get(&orphaned_account), None);
+    assert!(!directory.get(&deleted_user.keycloak_user_id).unwrap().enabled);
+
+    // the repaired accounts are in step now
+    let reconciliation = service.reconcile_users(Duration::ZERO).await.unwrap();
+    assert_eq!(reconciliation.deleted_accounts, 0);
+    assert_eq!(reconciliation.toggled_accounts, 0);
+
+    // users created within the grace period are not reported
+    let reconciliation = service.reconcile_users(Duration::from_secs(60 * 60)).await.unwrap();
+    assert!(!reconciliation.users_without_account.contains(&missing_user.id));
+
+    for email in [deleted_email, missing_email] {
+        let _emails = service.delete_users_by_pattern(&email, None, None, false).await.unwrap();
+    }
+}
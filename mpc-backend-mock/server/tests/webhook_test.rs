@@ -0,0 +1,196 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    routing, Router,
+};
+use axum_test::TestServer;
+use mpc_backend_mock_server::webhook_signature;
+use mpc_backend_mock_test_support::TestEnv;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Requests posted to a webhook, with its headers and raw body
+type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+/// Start a webhook recording the requests posted to it and answering with
+/// `status`, returns its URL
+async fn start_webhook(status: StatusCode) -> (String, Received) {
+    async fn record(
+        State((status, received)): State<(StatusCode, Received)>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        received.lock().unwrap().push((headers, body));
+        status
+    }
+
+    let received = Received::default();
+    let router = Router::new()
+        .route("/hook", routing::post(record))
+        .with_state((status, Arc::clone(&received)));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let _handle = tokio::spawn(async move { axum::serve(listener, router).await });
+
+    (format!("http://{address}/hook"), received)
+}
+
+/// Create an inactive user through the API, returns its activation token and
+/// the bearer token of the user
+async fn create_user(env: &TestEnv, server: &TestServer, email: &str) -> (String, HeaderValue) {
+    let response = server.post("/api/v1/users").json(&json!({ "email": email })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let _dispatch = env.service_state().notification_service.dispatch_due().await.unwrap();
+    let activation_token = env.activation_token(email).expect("No activation email sent");
+
+    let keycloak_user_id: Uuid =
+        sqlx::query_scalar("SELECT keycloak_user_id FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_one(env.pool())
+            .await
+            .unwrap();
+    let token = env.fake_keycloak().access_token(&keycloak_user_id, email);
+
+    (activation_token, HeaderValue::from_str(&format!("Bearer {token}")).unwrap())
+}
+
+/// Register a webhook of the user subscribing to `user.activated`, returns its
+/// ID and secret
+async fn create_webhook(server: &TestServer, bearer: &HeaderValue, url: &str) -> (String, String) {
+    let response = server
+        .post("/api/v1/webhooks")
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .json(&json!({ "url": url, "event_types": ["user.activated"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["url"], url);
+    assert_eq!(body["data"]["event_types"], json!(["user.activated"]));
+
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+    let secret = body["data"]["secret"].as_str().unwrap().to_string();
+    assert!(secret.starts_with("whsec_"));
+    (id, secret)
+}
+
+#[tokio::test]
+async fn test_webhook_receives_signed_events() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    let email = format!("webhook-test-{}@example.com", Uuid::new_v4());
+    let (activation_token, bearer) = create_user(&env, &server, &email).await;
+
+    let (url, received) = start_webhook(StatusCode::NO_CONTENT).await;
+    let (webhook_id, secret) = create_webhook(&server, &bearer, &url).await;
+
+    let response =
+        server.post("/api/v1/users/activate").json(&json!({ "token": activation_token })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let dispatch = env.service_state().webhook_service.deliver_due().await.unwrap();
+    assert_eq!(dispatch.delivered, 1);
+
+    let (headers, body) = received.lock().unwrap().pop().expect("No event posted");
+    assert_eq!(headers["x-webhook-event"], "user.activated");
+    assert_eq!(headers["x-webhook-signature"], webhook_signature(&secret, &body).as_str());
+    let event: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(event["type"], "user.activated");
+    assert_eq!(event["data"]["email"], email);
+
+    // Nothing is left to deliver
+    let dispatch = env.service_state().webhook_service.deliver_due().await.unwrap();
+    assert_eq!(dispatch.delivered, 0);
+
+    let response = server
+        .get(&format!("/api/v1/webhooks/{webhook_id}/deliveries"))
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"][0]["id"], event["id"]);
+    assert_eq!(body["data"][0]["status"], "delivered");
+    assert_eq!(body["data"][0]["attempts"], 1);
+    assert_eq!(body["data"][0]["response_status"], 204);
+}
+
+#[tokio::test]
+async fn test_failed_webhook_delivery_is_retried() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    let email = format!("webhook-test-{}@example.com", Uuid::new_v4());
+    let (activation_token, bearer) = create_user(&env, &server, &email).await;
+
+    let (url, received) = start_webhook(StatusCode::INTERNAL_SERVER_ERROR).await;
+    let (webhook_id, _secret) = create_webhook(&server, &bearer, &url).await;
+
+    let response =
+        server.post("/api/v1/users/activate").json(&json!({ "token": activation_token })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let dispatch = env.service_state().webhook_service.deliver_due().await.unwrap();
+    assert_eq!(dispatch.retried, 1);
+    assert_eq!(received.lock().unwrap().len(), 1);
+
+    // The next attempt is not due yet
+    let dispatch = env.service_state().webhook_service.deliver_due().await.unwrap();
+    assert_eq!(dispatch.retried, 0);
+
+    let response = server
+        .get(&format!("/api/v1/webhooks/{webhook_id}/deliveries"))
+        .add_header(header::AUTHORIZATION, bearer.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"][0]["status"], "pending");
+    assert_eq!(body["data"][0]["attempts"], 1);
+    assert_eq!(body["data"][0]["response_status"], 500);
+    assert!(body["data"][0]["last_error"].as_str().unwrap().contains("500"));
+}
+
+#[tokio::test]
+async fn test_webhook_validation_and_ownership() {
+    let env = TestEnv::start_with_fake_keycloak().await;
+    let server = TestServer::new(env.router()).expect("Failed to create test server");
+    let (_token, bearer) =
+        create_user(&env, &server, &format!("webhook-test-{}@example.com", Uuid::new_v4())).await;
+    let (_token, other_bearer) =
+        create_user(&env, &server, &format!("webhook-test-{}@example.com", Uuid::new_v4())).await;
+
+    for request in [
+        json!({ "url": "ftp://example.com/hook", "event_types": ["user.activated"] }),
+        json!({ "url": "https://example.com/hook", "event_types": [] }),
+    ] {
+        let response = server
+            .post("/api/v1/webhooks")
+            .add_header(header::AUTHORIZATION, bearer.clone())
+            .json(&request)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["error"]["code"], "INVALID_WEBHOOK");
+    }
+
+    let (webhook_id, _secret) =
+        create_webhook(&server, &bearer, "https://example.com/hooks/mpc").await;
+
+    // The deliveries of a webhook are only listed to its owner
+    let response = server
+        .get(&format!("/api/v1/webhooks/{webhook_id}/deliveries"))
+        .add_header(header::AUTHORIZATION, other_bearer)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"]["code"], "WEBHOOK_NOT_FOUND");
+
+    let response = server
+        .get(&format!("/api/v1/webhooks/{webhook_id}/deliveries"))
+        .add_header(header::AUTHORIZATION, bearer)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"], json!([]));
+}
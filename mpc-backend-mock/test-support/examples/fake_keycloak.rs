@@ -0,0 +1,24 @@
+//! Serve a [`FakeKeycloak`] where the Keycloak of the docker compose setup
+//! listens, to run the backend offline.
+//!
+//! The realm has the test user of `setup-keycloak.sh`, `test@example.com`
+//! with the password `test123`.
+
+use std::{io, net::SocketAddr};
+
+use mpc_backend_mock_test_support::FakeKeycloak;
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let keycloak = FakeKeycloak::bind(SocketAddr::from(([127, 0, 0, 1], 8080))).await?;
+    let user_id = keycloak.add_user("test@example.com", "test123");
+
+    let config = keycloak.keycloak_config();
+    println!("Fake Keycloak listening on {}", keycloak.base_url());
+    println!("  realm:         {}", config.realm);
+    println!("  client_id:     {}", config.client_id);
+    println!("  client_secret: {}", config.client_secret);
+    println!("  test user:     test@example.com / test123 ({user_id})");
+
+    tokio::signal::ctrl_c().await
+}
@@ -0,0 +1,237 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State as AxumState},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use keycloak::types::{CredentialRepresentation, UserRepresentation};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{check_realm, oidc::Claims, SigningKey, State, ISSUER};
+
+/// Page size when the request does not set `max`, as in Keycloak
+const DEFAULT_MAX: usize = 100;
+
+pub fn router() -> Router<Arc<State>> {
+    Router::new()
+        .route("/admin/realms/:realm/users", routing::get(list_users).post(create_user))
+        .route(
+            "/admin/realms/:realm/users/:id",
+            routing::get(get_user).put(update_user).delete(delete_user),
+        )
+        .route("/admin/realms/:realm/users/:id/reset-password", routing::put(reset_password))
+}
+
+/// Users ordered by username, filtered like Keycloak does with `username`,
+/// `email` and `search` matching substrings unless `exact` is set
+async fn list_users(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(realm): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    if let Err(response) = authorize(&state, &realm, &headers) {
+        return response;
+    }
+
+    let exact = query.get("exact").is_some_and(|exact| exact == "true");
+    let matches_filter = |value: Option<&String>, filter: &str| {
+        let value = value.map(|value| value.to_lowercase()).unwrap_or_default();
+        let filter = filter.to_lowercase();
+        if exact {
+            value == filter
+        } else {
+            value.contains(&filter)
+        }
+    };
+    let first = query.get("first").and_then(|first| first.parse().ok()).unwrap_or(0);
+    let max = query.get("max").and_then(|max| max.parse().ok()).unwrap_or(DEFAULT_MAX);
+
+    let mut users = state
+        .accounts()
+        .values()
+        .map(|account| account.user.clone())
+        .filter(|user| {
+            query
+                .get("username")
+                .is_none_or(|filter| matches_filter(user.username.as_ref(), filter))
+                && query
+                    .get("email")
+                    .is_none_or(|filter| matches_filter(user.email.as_ref(), filter))
+                && query.get("search").is_none_or(|filter| {
+                    matches_filter(user.username.as_ref(), filter)
+                        || matches_filter(user.email.as_ref(), filter)
+                })
+                && query
+                    .get("enabled")
+                    .is_none_or(|enabled| user.enabled.unwrap_or(false).to_string() == *enabled)
+        })
+        .collect::<Vec<_>>();
+    users.sort_by(|a, b| a.username.cmp(&b.username));
+
+    Json(users.into_iter().skip(first).take(max).collect::<Vec<_>>()).into_response()
+}
+
+/// Create the user, answering with its URL in the `Location` header
+async fn create_user(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(realm): Path<String>,
+    headers: HeaderMap,
+    Json(mut user): Json<UserRepresentation>,
+) -> Response {
+    if let Err(response) = authorize(&state, &realm, &headers) {
+        return response;
+    }
+
+    // Keycloak stores both lowercased
+    user.username = user.username.or_else(|| user.email.clone()).map(|u| u.to_lowercase());
+    user.email = user.email.map(|email| email.to_lowercase());
+    let Some(username) = user.username.clone() else {
+        return admin_error(StatusCode::BAD_REQUEST, "User name is missing");
+    };
+    let exists = state.accounts().values().any(|account| {
+        account.user.username.as_deref() == Some(&username)
+            || (user.email.is_some() && account.user.email == user.email)
+    });
+    if exists {
+        return admin_error(StatusCode::CONFLICT, "User exists with same username or email");
+    }
+
+    let password = user.credentials.take().and_then(|credentials| {
+        credentials.into_iter().find(|c| c.type_.as_deref() == Some("password"))?.value
+    });
+    user.enabled = Some(user.enabled.unwrap_or(false));
+    let id = state.insert(user, password);
+
+    let location = format!("{}/admin/realms/{realm}/users/{id}", state.base_url);
+    (StatusCode::CREATED, [(header::LOCATION, location)]).into_response()
+}
+
+async fn get_user(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((realm, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(response) = authorize(&state, &realm, &headers) {
+        return response;
+    }
+
+    let user = Uuid::parse_str(&id)
+        .ok()
+        .and_then(|id| state.accounts().get(&id).map(|account| account.user.clone()));
+    match user {
+        Some(user) => Json(user).into_response(),
+        None => user_not_found(),
+    }
+}
+
+/// Replace the fields set in the body, leaving the others as they are
+async fn update_user(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((realm, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(update): Json<UserRepresentation>,
+) -> Response {
+    if let Err(response) = authorize(&state, &realm, &headers) {
+        return response;
+    }
+
+    let mut accounts = state.accounts();
+    let Some(id) = Uuid::parse_str(&id).ok().filter(|id| accounts.contains_key(id)) else {
+        return user_not_found();
+    };
+    let email = update.email.as_deref().map(str::to_lowercase);
+    let email_taken = email.is_some()
+        && accounts.iter().any(|(other_id, other)| *other_id != id && other.user.email == email);
+    if email_taken {
+        return admin_error(StatusCode::CONFLICT, "User exists with same email");
+    }
+    let Some(account) = accounts.get_mut(&id) else {
+        return user_not_found();
+    };
+    let user = &mut account.user;
+    user.username =
+        update.username.map(|username| username.to_lowercase()).or(user.username.take());
+    user.email = email.or(user.email.take());
+    user.enabled = update.enabled.or(user.enabled);
+    user.email_verified = update.email_verified.or(user.email_verified);
+    user.first_name = update.first_name.or(user.first_name.take());
+    user.last_name = update.last_name.or(user.last_name.take());
+    user.attributes = update.attributes.or(user.attributes.take());
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn delete_user(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((realm, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(response) = authorize(&state, &realm, &headers) {
+        return response;
+    }
+
+    let Some(id) = Uuid::parse_str(&id).ok().filter(|id| state.accounts().remove(id).is_some())
+    else {
+        return user_not_found();
+    };
+    state.refresh_tokens().retain(|_, user_id| *user_id != id);
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Set the password, only an empty one is rejected as the realm has no
+/// password policy
+async fn reset_password(
+    AxumState(state): AxumState<Arc<State>>,
+    Path((realm, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(credential): Json<CredentialRepresentation>,
+) -> Response {
+    if let Err(response) = authorize(&state, &realm, &headers) {
+        return response;
+    }
+
+    let Some(password) = credential.value.filter(|password| !password.is_empty()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalidPasswordMinLengthMessage" })),
+        )
+            .into_response();
+    };
+
+    let mut accounts = state.accounts();
+    let Some(account) = Uuid::parse_str(&id).ok().and_then(|id| accounts.get_mut(&id)) else {
+        return user_not_found();
+    };
+    account.password = Some(password);
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Only the service account of the backend client may call the admin API
+fn authorize(state: &State, realm: &str, headers: &HeaderMap) -> Result<(), Response> {
+    check_realm(realm)?;
+
+    let claims = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| SigningKey::get().verify::<Claims>(token, ISSUER));
+    match claims {
+        None => Err(admin_error(StatusCode::UNAUTHORIZED, "HTTP 401 Unauthorized")),
+        Some(claims) if claims.sub != state.service_account_id.to_string() => {
+            Err(admin_error(StatusCode::FORBIDDEN, "HTTP 403 Forbidden"))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn user_not_found() -> Response { admin_error(StatusCode::NOT_FOUND, "User not found") }
+
+fn admin_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "errorMessage": message }))).into_response()
+}
@@ -0,0 +1,75 @@
+use std::sync::LazyLock;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rsa::{pkcs1::EncodeRsaPrivateKey, traits::PublicKeyParts, RsaPrivateKey};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::oidc::AUDIENCE;
+
+/// Generated once per process and shared by every fake, generating an RSA key
+/// takes a while
+static KEY: LazyLock<SigningKey> = LazyLock::new(SigningKey::generate);
+
+const BITS: usize = 2048;
+
+/// RS256 key the tokens are signed with, published in the JWKS
+pub struct SigningKey {
+    kid: String,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    modulus: String,
+    exponent: String,
+}
+
+impl SigningKey {
+    pub fn get() -> &'static Self { &KEY }
+
+    fn generate() -> Self {
+        let private_key =
+            RsaPrivateKey::new(&mut rand::thread_rng(), BITS).expect("Failed to generate RSA key");
+        let der = private_key.to_pkcs1_der().expect("Failed to encode RSA key");
+        let modulus = URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be());
+        let exponent = URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be());
+        let decoding_key =
+            DecodingKey::from_rsa_components(&modulus, &exponent).expect("is an RSA key; qed");
+
+        Self {
+            kid: Uuid::new_v4().to_string(),
+            encoding_key: EncodingKey::from_rsa_der(der.as_bytes()),
+            decoding_key,
+            modulus,
+            exponent,
+        }
+    }
+
+    /// JWKS holding the public key, as served by the `certs` endpoint
+    pub fn jwks(&self) -> Value {
+        json!({
+            "keys": [{
+                "kid": self.kid,
+                "kty": "RSA",
+                "alg": "RS256",
+                "use": "sig",
+                "n": self.modulus,
+                "e": self.exponent,
+            }]
+        })
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> String {
+        let header = Header { kid: Some(self.kid.clone()), ..Header::new(Algorithm::RS256) };
+        jsonwebtoken::encode(&header, claims, &self.encoding_key).expect("is serializable; qed")
+    }
+
+    /// Claims of `token` if it is signed with this key, unexpired and issued
+    /// by `issuer`
+    pub fn verify<T: DeserializeOwned>(&self, token: &str, issuer: &str) -> Option<T> {
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[issuer]);
+        validation.set_audience(&[AUDIENCE]);
+        jsonwebtoken::decode(token, &self.decoding_key, &validation).ok().map(|data| data.claims)
+    }
+}
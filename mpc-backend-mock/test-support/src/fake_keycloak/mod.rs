@@ -0,0 +1,249 @@
+//! In-process stand-in for Keycloak.
+//!
+//! [`FakeKeycloak`] serves the parts of Keycloak the backend calls: the JWKS,
+//! the token endpoint with the client credentials, password, refresh token and
+//! token exchange grants, token introspection and the admin users API. Tokens
+//! are signed with RS256 like those of a real realm, so they pass the JWT
+//! middleware.
+//!
+//! Tokens are issued by `http://localhost:8080/realms/mpc`, the issuer of
+//! [`FakeKeycloak::keycloak_config`], whichever address the fake listens on.
+
+mod admin;
+mod key;
+mod oidc;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use keycloak::types::UserRepresentation;
+use mpc_backend_mock_core::config::{JwtValidationMethod, KeycloakConfig};
+use serde::Serialize;
+use serde_json::json;
+use tokio::{net::TcpListener, task::JoinHandle};
+use uuid::Uuid;
+
+use self::{key::SigningKey, oidc::Claims};
+use crate::{CLIENT_ID, CLIENT_SECRET, REALM};
+
+/// Issuer of the tokens, the one of the Keycloak of the docker compose setup
+const ISSUER: &str = "http://localhost:8080/realms/mpc";
+
+/// Username of the service account of the backend client
+const SERVICE_ACCOUNT_USERNAME: &str = "service-account-mpc-backend-service";
+
+/// Keycloak realm served over HTTP from the test process, stopped when dropped
+pub struct FakeKeycloak {
+    base_url: String,
+    state: Arc<State>,
+    server: JoinHandle<()>,
+}
+
+impl FakeKeycloak {
+    /// Serve the fake on an ephemeral port of the loopback address
+    ///
+    /// # Panics
+    ///
+    /// Panics if no port can be bound
+    pub async fn start() -> Self {
+        Self::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .expect("Failed to start fake Keycloak")
+    }
+
+    /// Serve the fake on `address`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `address` cannot be bound
+    pub async fn bind(address: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(State {
+            base_url: base_url.clone(),
+            service_account_id: Uuid::new_v4(),
+            accounts: Mutex::new(BTreeMap::new()),
+            refresh_tokens: Mutex::new(HashMap::new()),
+            additional_realms: Mutex::new(BTreeSet::new()),
+        });
+
+        let router = Router::new()
+            .route("/health/ready", routing::get(|| async { Json(json!({ "status": "UP" })) }))
+            .merge(oidc::router())
+            .merge(admin::router())
+            .with_state(Arc::clone(&state));
+        let server = tokio::spawn(async move {
+            let _result = axum::serve(listener, router).await;
+        });
+
+        Ok(Self { base_url, state, server })
+    }
+
+    /// Base URL of the fake, e.g. `http://127.0.0.1:35141`
+    #[must_use]
+    pub fn base_url(&self) -> &str { &self.base_url }
+
+    /// Configuration of the backend service client, validating tokens with the
+    /// JWKS
+    #[must_use]
+    pub fn keycloak_config(&self) -> KeycloakConfig {
+        KeycloakConfig {
+            server_url: self.base_url.clone(),
+            realm: REALM.to_string(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: CLIENT_SECRET.to_string(),
+            issuer: ISSUER.to_string(),
+            verify_ssl: false,
+            jwt_validation_method: JwtValidationMethod::Jwks,
+            introspection_cache_ttl: Duration::ZERO,
+            jwks_cache_ttl: Duration::from_secs(300),
+            jwks_refresh_interval: Duration::from_secs(240),
+            additional_realms: Vec::new(),
+        }
+    }
+
+    /// Serve the JWKS of `realm` as well, whose tokens are signed with the same
+    /// key by [`FakeKeycloak::sign`], the other endpoints stay with the `mpc`
+    /// realm
+    pub fn add_realm(&self, realm: &str) {
+        let _added = self.state.additional_realms().insert(realm.to_string());
+    }
+
+    /// Add an enabled user with a verified email, who logs in with `password`
+    pub fn add_user(&self, email: &str, password: &str) -> Uuid {
+        let email = email.to_lowercase();
+        let user = UserRepresentation {
+            username: Some(email.clone()),
+            email: Some(email),
+            enabled: Some(true),
+            email_verified: Some(true),
+            ..Default::default()
+        };
+        self.state.insert(user, Some(password.to_string()))
+    }
+
+    /// Account of `user_id`, as returned by the admin API
+    #[must_use]
+    pub fn user(&self, user_id: &Uuid) -> Option<UserRepresentation> {
+        self.state.accounts().get(user_id).map(|account| account.user.clone())
+    }
+
+    /// Access token of `user_id`, as issued on login
+    #[must_use]
+    pub fn access_token(&self, user_id: &Uuid, email: &str) -> String {
+        SigningKey::get().sign(&Claims::new(user_id, email, Some(email)))
+    }
+
+    /// Sign `claims` with the key of the JWKS, e.g. expired or incomplete ones
+    #[must_use]
+    pub fn sign<T: Serialize>(&self, claims: &T) -> String { SigningKey::get().sign(claims) }
+
+    /// Issuer the tokens are signed for
+    #[must_use]
+    pub const fn issuer(&self) -> &'static str { ISSUER }
+}
+
+impl Drop for FakeKeycloak {
+    fn drop(&mut self) { self.server.abort(); }
+}
+
+/// Realm shared by the handlers
+struct State {
+    base_url: String,
+    service_account_id: Uuid,
+    accounts: Mutex<BTreeMap<Uuid, Account>>,
+
+    /// User each outstanding refresh token was issued to
+    refresh_tokens: Mutex<HashMap<String, Uuid>>,
+
+    /// Realms whose JWKS is served besides the one of the `mpc` realm
+    additional_realms: Mutex<BTreeSet<String>>,
+}
+
+struct Account {
+    user: UserRepresentation,
+    password: Option<String>,
+}
+
+impl State {
+    fn accounts(&self) -> MutexGuard<'_, BTreeMap<Uuid, Account>> {
+        self.accounts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn refresh_tokens(&self) -> MutexGuard<'_, HashMap<String, Uuid>> {
+        self.refresh_tokens.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn additional_realms(&self) -> MutexGuard<'_, BTreeSet<String>> {
+        self.additional_realms.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Store `user` under a new ID, which is returned
+    fn insert(&self, mut user: UserRepresentation, password: Option<String>) -> Uuid {
+        let id = Uuid::new_v4();
+        user.id = Some(id.to_string());
+        user.created_timestamp = Some(chrono::Utc::now().timestamp_millis());
+        let _previous = self.accounts().insert(id, Account { user, password });
+        id
+    }
+
+    /// Enabled user with `username`, or `username` as email, and `password`
+    fn authenticate(&self, username: &str, password: &str) -> Option<Uuid> {
+        let username = username.to_lowercase();
+        self.accounts().iter().find_map(|(id, account)| {
+            let user = &account.user;
+            let matches = user.username.as_deref() == Some(&username)
+                || user.email.as_deref() == Some(&username);
+            (matches && user.enabled == Some(true) && account.password.as_deref() == Some(password))
+                .then_some(*id)
+        })
+    }
+
+    /// Claims of an access token of `user_id`, unless the user is gone or
+    /// disabled
+    fn claims(&self, user_id: &Uuid) -> Option<Claims> {
+        let accounts = self.accounts();
+        let user = &accounts.get(user_id)?.user;
+        if user.enabled != Some(true) {
+            return None;
+        }
+        let mut claims = Claims::new(
+            user_id,
+            user.username.as_deref().unwrap_or_default(),
+            user.email.as_deref(),
+        );
+        claims.email_verified = user.email_verified;
+        Some(claims)
+    }
+
+    fn issue_refresh_token(&self, user_id: Uuid) -> String {
+        let refresh_token = Uuid::new_v4().simple().to_string();
+        let _previous = self.refresh_tokens().insert(refresh_token.clone(), user_id);
+        refresh_token
+    }
+
+    /// User of `refresh_token`, which is used up as refresh tokens rotate
+    fn redeem_refresh_token(&self, refresh_token: &str) -> Option<Uuid> {
+        self.refresh_tokens().remove(refresh_token)
+    }
+}
+
+/// Reject requests for another realm than the one served
+fn check_realm(realm: &str) -> Result<(), Response> {
+    if realm == REALM {
+        Ok(())
+    } else {
+        Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Realm does not exist" })))
+            .into_response())
+    }
+}
@@ -0,0 +1,238 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, State as AxumState},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Form, Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::{check_realm, SigningKey, State, ISSUER, SERVICE_ACCOUNT_USERNAME};
+use crate::{CLIENT_ID, CLIENT_SECRET};
+
+/// Audience of the access tokens, the one the JWT middleware expects
+pub const AUDIENCE: &str = "account";
+
+const ACCESS_TOKEN_LIFESPAN_SECONDS: i64 = 300;
+
+const REFRESH_TOKEN_LIFESPAN_SECONDS: i64 = 1800;
+
+const SCOPE: &str = "openid email profile";
+
+const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+/// Claims of the access tokens
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Claims {
+    pub sub: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+    pub aud: String,
+    pub typ: String,
+    pub azp: String,
+    pub scope: String,
+    pub preferred_username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+}
+
+impl Claims {
+    /// Claims of an access token of `subject` issued now
+    pub fn new(subject: &Uuid, username: &str, email: Option<&str>) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            sub: subject.to_string(),
+            iat: now,
+            exp: now + ACCESS_TOKEN_LIFESPAN_SECONDS,
+            iss: ISSUER.to_string(),
+            aud: AUDIENCE.to_string(),
+            typ: "Bearer".to_string(),
+            azp: CLIENT_ID.to_string(),
+            scope: SCOPE.to_string(),
+            preferred_username: username.to_string(),
+            email: email.map(ToString::to_string),
+            email_verified: email.map(|_| true),
+        }
+    }
+}
+
+pub fn router() -> Router<Arc<State>> {
+    Router::new()
+        .route("/realms/:realm/protocol/openid-connect/certs", routing::get(certs))
+        .route("/realms/:realm/protocol/openid-connect/token", routing::post(token))
+        .route("/realms/:realm/protocol/openid-connect/token/introspect", routing::post(introspect))
+}
+
+async fn certs(AxumState(state): AxumState<Arc<State>>, Path(realm): Path<String>) -> Response {
+    if !state.additional_realms().contains(&realm) {
+        if let Err(response) = check_realm(&realm) {
+            return response;
+        }
+    }
+    Json(SigningKey::get().jwks()).into_response()
+}
+
+async fn token(
+    AxumState(state): AxumState<Arc<State>>,
+    Path(realm): Path<String>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    if let Err(response) = check_realm(&realm) {
+        return response;
+    }
+    if !is_backend_client(&headers, &form) {
+        return oauth_error(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized_client",
+            "Invalid client credentials",
+        );
+    }
+
+    match form.get("grant_type").map(String::as_str) {
+        Some("client_credentials") => {
+            let claims = Claims::new(&state.service_account_id, SERVICE_ACCOUNT_USERNAME, None);
+            token_response(&claims, None)
+        }
+        Some("password") => {
+            let (Some(username), Some(password)) = (form.get("username"), form.get("password"))
+            else {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_request",
+                    "Missing parameter: username",
+                );
+            };
+            match state.authenticate(username, password).and_then(|id| state.claims(&id)) {
+                Some(claims) => tokens_of(&state, &claims),
+                None => oauth_error(
+                    StatusCode::UNAUTHORIZED,
+                    "invalid_grant",
+                    "Invalid user credentials",
+                ),
+            }
+        }
+        Some("refresh_token") => {
+            let claims = form
+                .get("refresh_token")
+                .and_then(|refresh_token| state.redeem_refresh_token(refresh_token))
+                .and_then(|id| state.claims(&id));
+            match claims {
+                Some(claims) => tokens_of(&state, &claims),
+                None => {
+                    oauth_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid refresh token")
+                }
+            }
+        }
+        Some(TOKEN_EXCHANGE_GRANT_TYPE) => {
+            // direct naked impersonation, the backend client may impersonate
+            // every user
+            let claims = form
+                .get("requested_subject")
+                .and_then(|subject| subject.parse().ok())
+                .and_then(|id| state.claims(&id));
+            match claims {
+                Some(claims) => token_response(&claims, None),
+                None => oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_request",
+                    "Requested subject not found",
+                ),
+            }
+        }
+        _ => {
+            oauth_error(StatusCode::BAD_REQUEST, "unsupported_grant_type", "Unsupported grant_type")
+        }
+    }
+}
+
+async fn introspect(
+    Path(realm): Path<String>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    if let Err(response) = check_realm(&realm) {
+        return response;
+    }
+    if !is_backend_client(&headers, &form) {
+        return oauth_error(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized_client",
+            "Invalid client credentials",
+        );
+    }
+
+    let claims =
+        form.get("token").and_then(|token| SigningKey::get().verify::<Claims>(token, ISSUER));
+    let Some(claims) = claims else {
+        return Json(json!({ "active": false })).into_response();
+    };
+
+    Json(json!({
+        "active": true,
+        "sub": claims.sub,
+        "iat": claims.iat,
+        "exp": claims.exp,
+        "iss": claims.iss,
+        "aud": claims.aud,
+        "client_id": claims.azp,
+        "username": claims.preferred_username,
+        "email": claims.email,
+        "scope": claims.scope,
+        "token_type": claims.typ,
+    }))
+    .into_response()
+}
+
+/// Access and refresh token of the user of `claims`
+fn tokens_of(state: &State, claims: &Claims) -> Response {
+    let user_id = claims.sub.parse().expect("is a user ID; qed");
+    token_response(claims, Some(state.issue_refresh_token(user_id)))
+}
+
+fn token_response(claims: &Claims, refresh_token: Option<String>) -> Response {
+    let mut body = json!({
+        "access_token": SigningKey::get().sign(claims),
+        "expires_in": ACCESS_TOKEN_LIFESPAN_SECONDS,
+        "refresh_expires_in": 0,
+        "token_type": "Bearer",
+        "not-before-policy": 0,
+        "scope": claims.scope,
+    });
+    if let Some(refresh_token) = refresh_token {
+        body["refresh_token"] = Value::from(refresh_token);
+        body["refresh_expires_in"] = Value::from(REFRESH_TOKEN_LIFESPAN_SECONDS);
+    }
+    Json(body).into_response()
+}
+
+/// Whether the request authenticates as the backend client, in the form or
+/// with HTTP Basic authentication
+fn is_backend_client(headers: &HeaderMap, form: &HashMap<String, String>) -> bool {
+    let basic = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|credentials| STANDARD.decode(credentials).ok())
+        .and_then(|credentials| String::from_utf8(credentials).ok());
+    let (client_id, client_secret) = match basic.as_deref().and_then(|c| c.split_once(':')) {
+        Some((client_id, client_secret)) => (Some(client_id), Some(client_secret)),
+        None => (
+            form.get("client_id").map(String::as_str),
+            form.get("client_secret").map(String::as_str),
+        ),
+    };
+
+    client_id == Some(CLIENT_ID) && client_secret == Some(CLIENT_SECRET)
+}
+
+fn oauth_error(status: StatusCode, error: &str, description: &str) -> Response {
+    (status, Json(json!({ "error": error, "error_description": description }))).into_response()
+}
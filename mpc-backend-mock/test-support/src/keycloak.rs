@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use mpc_backend_mock_core::config::{JwtValidationMethod, KeycloakConfig};
+use testcontainers::{
+    core::{IntoContainerPort, WaitFor},
+    runners::AsyncRunner,
+    ContainerAsync, GenericImage, ImageExt,
+};
+
+use crate::{CLIENT_ID, CLIENT_SECRET, REALM};
+
+const IMAGE: &str = "quay.io/keycloak/keycloak";
+
+/// Same version as in `dev-support/test-environments/docker-compose`
+const TAG: &str = "26.4.7";
+
+const PORT: u16 = 8080;
+
+/// The `mpc` realm with the backend service account, imported on startup
+const REALM_EXPORT: &[u8] = include_bytes!("../realm.json");
+
+/// Start Keycloak with the `mpc` realm, returning the configuration to reach
+/// it as the backend service client
+pub async fn start() -> (ContainerAsync<GenericImage>, KeycloakConfig) {
+    let container = GenericImage::new(IMAGE, TAG)
+        .with_exposed_port(PORT.tcp())
+        .with_wait_for(WaitFor::message_on_stdout("Listening on:"))
+        .with_cmd(["start-dev", "--import-realm"])
+        .with_copy_to("/opt/keycloak/data/import/mpc-realm.json", REALM_EXPORT.to_vec())
+        // the JVM takes a while to boot on CI runners
+        .with_startup_timeout(Duration::from_secs(180))
+        .start()
+        .await
+        .expect("Failed to start Keycloak container");
+
+    let host = container.get_host().await.expect("Failed to get Keycloak host");
+    let port = container.get_host_port_ipv4(PORT).await.expect("Failed to get Keycloak port");
+    let server_url = format!("http://{host}:{port}");
+    let config = KeycloakConfig {
+        issuer: format!("{server_url}/realms/{REALM}"),
+        server_url,
+        realm: REALM.to_string(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: CLIENT_SECRET.to_string(),
+        verify_ssl: false,
+        jwt_validation_method: JwtValidationMethod::Jwks,
+        introspection_cache_ttl: Duration::ZERO,
+        jwks_cache_ttl: Duration::from_secs(300),
+        jwks_refresh_interval: Duration::from_secs(240),
+        additional_realms: Vec::new(),
+    };
+
+    (container, config)
+}
@@ -0,0 +1,284 @@
+//! Throwaway Postgres and Keycloak for the integration tests.
+//!
+//! [`TestEnv::start`] runs both in containers, applies the migrations and
+//! imports the `mpc` realm with the `mpc-backend-service` client, so the tests
+//! only need Docker. The containers are removed when the [`TestEnv`] is
+//! dropped.
+//!
+//! [`TestEnv::start_with_fake_keycloak`] replaces the Keycloak container with
+//! a [`FakeKeycloak`] served from the test process, which signs real RS256
+//! tokens for the JWT middleware.
+
+mod fake_keycloak;
+mod keycloak;
+mod postgres;
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{extract::connect_info::MockConnectInfo, Router};
+use eris_bitcoin_ext::WellKnownNetwork as BitcoinNetwork;
+use eris_bitcoin_rpc_client::Authentication as BitcoinRpcAuthentication;
+use mpc_backend_mock_core::config::{
+    ActivationConfig, BitcoinConfig, IpAccessList, KeycloakConfig, RateLimit, RateLimitConfig,
+};
+use mpc_backend_mock_server::{
+    controller, keycloak_client::KeycloakClient, AdminIpFilter, ChainStateCache, CircuitBreakers,
+    EventBus, HttpMetrics, IntrospectionCache, JwksClient, KeycloakUserDirectory, MockBitcoinChain,
+    MockSolanaChain, QueryMetrics, RateLimiter, Realm, Realms, ServiceState, TaskRegistry,
+    UserDirectory, UserManagementService,
+};
+use notification::{
+    capture::{self, MemoryStore},
+    Notification,
+};
+use sqlx::PgPool;
+use testcontainers::{ContainerAsync, GenericImage};
+use testcontainers_modules::postgres::Postgres;
+use zeus_metrics::DefaultMetrics;
+
+pub use self::fake_keycloak::FakeKeycloak;
+
+const REALM: &str = "mpc";
+
+const CLIENT_ID: &str = "mpc-backend-service";
+
+const CLIENT_SECRET: &str = "test-secret";
+
+/// Postgres and Keycloak containers, with the service state of the API
+/// backed by them
+pub struct TestEnv {
+    pool: PgPool,
+    keycloak_config: KeycloakConfig,
+    notifications: Arc<MemoryStore>,
+    service_state: ServiceState,
+
+    // dropped last, which removes the containers
+    _postgres: ContainerAsync<Postgres>,
+    keycloak: Keycloak,
+}
+
+enum Keycloak {
+    Container(ContainerAsync<GenericImage>),
+    Fake(FakeKeycloak),
+}
+
+impl TestEnv {
+    /// Start the containers and create the service state
+    ///
+    /// Notifications are captured in [`TestEnv::notifications`] and the
+    /// loopback client may reach the admin routes, which the tests use to
+    /// clean up after themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a container cannot be started, e.g. Docker is not running,
+    /// or the service state cannot be created
+    pub async fn start() -> Self {
+        let ((postgres, pool), (keycloak, keycloak_config)) =
+            tokio::join!(postgres::start(), keycloak::start());
+        Self::new(postgres, pool, Keycloak::Container(keycloak), keycloak_config).await
+    }
+
+    /// Start the Postgres container and a [`FakeKeycloak`], then create the
+    /// service state as [`TestEnv::start`] does
+    ///
+    /// # Panics
+    ///
+    /// Panics if a container cannot be started, e.g. Docker is not running,
+    /// or the service state cannot be created
+    pub async fn start_with_fake_keycloak() -> Self {
+        let ((postgres, pool), fake_keycloak) =
+            tokio::join!(postgres::start(), FakeKeycloak::start());
+        let keycloak_config = fake_keycloak.keycloak_config();
+        Self::new(postgres, pool, Keycloak::Fake(fake_keycloak), keycloak_config).await
+    }
+
+    async fn new(
+        postgres: ContainerAsync<Postgres>,
+        pool: PgPool,
+        keycloak: Keycloak,
+        keycloak_config: KeycloakConfig,
+    ) -> Self {
+        let notifications = Arc::new(MemoryStore::default());
+        let notification_client =
+            capture::Client::new(capture::Config::default(), Arc::clone(&notifications) as _)
+                .expect("Failed to create capture notification client");
+
+        let keycloak_client =
+            KeycloakClient::new(keycloak_config.clone()).expect("Failed to create Keycloak client");
+        let keycloak_admin =
+            keycloak_client.get_admin_client().await.expect("Failed to create Keycloak admin");
+        let jwks_client = JwksClient::new(
+            &keycloak_config.server_url,
+            &keycloak_config.realm,
+            Arc::new(mpc_backend_mock_server::MemoryStore::default()),
+            keycloak_config.jwks_cache_ttl,
+        )
+        .expect("Failed to create JWKS client");
+        let keycloak_client = Arc::new(keycloak_client);
+        let realms = Realms::new(Realm::new(
+            keycloak_config.realm.clone(),
+            keycloak_config.issuer.clone(),
+            jwks_client,
+            Arc::clone(&keycloak_client),
+        ));
+
+        let service_state = ServiceState::new(
+            pool.clone(),
+            Arc::new(MockBitcoinChain),
+            &bitcoin_config(),
+            Arc::new(MockSolanaChain::default()),
+            zpl_rpc_client(),
+            realms,
+            Arc::new(KeycloakUserDirectory::new(
+                Arc::new(keycloak_admin),
+                keycloak_config.realm.clone(),
+            )),
+            Arc::new(notification_client),
+            &activation_config(),
+            keycloak_client,
+            keycloak_config.jwt_validation_method.clone(),
+            IntrospectionCache::new(
+                Arc::new(mpc_backend_mock_server::MemoryStore::default()),
+                keycloak_config.introspection_cache_ttl,
+            ),
+            AdminIpFilter::new(
+                Vec::new(),
+                IpAccessList {
+                    allow: vec!["127.0.0.1/32".parse().expect("is valid; qed")],
+                    deny: Vec::new(),
+                },
+            ),
+            TaskRegistry::default(),
+            HttpMetrics::new(&DefaultMetrics::new()).expect("Failed to create HTTP metrics"),
+            EventBus::new(),
+            QueryMetrics::new(&DefaultMetrics::new()).expect("Failed to create query metrics"),
+            RateLimiter::new(
+                Vec::new(),
+                RateLimitConfig {
+                    enable: false,
+                    per_ip: RateLimit { requests_per_minute: 0, burst: 0 },
+                    per_user: RateLimit { requests_per_minute: 0, burst: 0 },
+                    login_per_ip: RateLimit { requests_per_minute: 0, burst: 0 },
+                },
+                Arc::new(mpc_backend_mock_server::MemoryStore::default()),
+            ),
+            CircuitBreakers::default(),
+        )
+        // nothing refreshes the cache, tests set the heights they need
+        .with_chain_state(ChainStateCache::new(Duration::from_secs(10)));
+
+        Self { pool, keycloak_config, notifications, service_state, _postgres: postgres, keycloak }
+    }
+
+    /// Router of the API, requests come from the loopback address
+    #[must_use]
+    pub fn router(&self) -> Router {
+        controller::api_router(&self.service_state)
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))))
+    }
+
+    /// Pool of the migrated test database
+    #[must_use]
+    pub const fn pool(&self) -> &PgPool { &self.pool }
+
+    /// Configuration of the backend service client of the Keycloak
+    #[must_use]
+    pub const fn keycloak_config(&self) -> &KeycloakConfig { &self.keycloak_config }
+
+    /// Fake Keycloak of an environment started with
+    /// [`TestEnv::start_with_fake_keycloak`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if the environment runs a Keycloak container instead
+    #[must_use]
+    pub const fn fake_keycloak(&self) -> &FakeKeycloak {
+        match &self.keycloak {
+            Keycloak::Fake(fake_keycloak) => fake_keycloak,
+            Keycloak::Container(_) => panic!("test environment runs a Keycloak container"),
+        }
+    }
+
+    /// Notifications sent by the API
+    #[must_use]
+    pub const fn notifications(&self) -> &Arc<MemoryStore> { &self.notifications }
+
+    /// Token of the last activation link sent to `email`
+    #[must_use]
+    pub fn activation_token(&self, email: &str) -> Option<String> {
+        self.notifications.emails().iter().rev().find_map(|email_sent| {
+            match email_sent.notification {
+                Notification::ActivationEmail { ref recipients, ref link, .. }
+                    if recipients.to.iter().any(|to| to == email) =>
+                {
+                    link.split_once("token=").map(|(_, token)| token.to_string())
+                }
+                _ => None,
+            }
+        })
+    }
+
+    #[must_use]
+    pub const fn service_state(&self) -> &ServiceState { &self.service_state }
+
+    /// Service state to adjust before calling [`TestEnv::router`], e.g. to
+    /// replace the admin allowlist
+    pub const fn service_state_mut(&mut self) -> &mut ServiceState { &mut self.service_state }
+
+    /// User management service of the test database with the accounts in
+    /// `directory`, for the tests calling the service without the API
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query metrics cannot be created
+    #[must_use]
+    pub fn user_management_service(
+        &self,
+        directory: Arc<dyn UserDirectory>,
+    ) -> UserManagementService {
+        UserManagementService::new(
+            self.pool.clone(),
+            directory,
+            &activation_config(),
+            EventBus::new(),
+            QueryMetrics::new(&DefaultMetrics::new()).expect("Failed to create query metrics"),
+        )
+    }
+}
+
+fn activation_config() -> ActivationConfig {
+    ActivationConfig {
+        url: "http://localhost:3000/activate".to_string(),
+        email_change_url: "http://localhost:3000/confirm-email".to_string(),
+        token_ttl: Duration::from_secs(60),
+    }
+}
+
+fn bitcoin_config() -> BitcoinConfig {
+    BitcoinConfig {
+        endpoint: eris_bitcoin_rpc_client::RpcEndpoint {
+            endpoint: "http://localhost:8332".parse().expect("is valid; qed"),
+            indexer_endpoint: None,
+            authentication: BitcoinRpcAuthentication::default(),
+            support_quicknode_blockbook: false,
+            network: BitcoinNetwork::Regtest,
+        },
+        block_number_to_confirm: 6,
+        fee_cache_ttl: Duration::from_secs(5),
+        mock: true,
+    }
+}
+
+/// Client of the ZPL programs on devnet, never called as the chains are mocked
+fn zpl_rpc_client() -> zpl_rpc_client::RpcClient {
+    zpl_rpc_client::RpcClient::new(
+        zpl_rpc_client::Endpoint::devnet(),
+        solana_sdk::commitment_config::CommitmentConfig::confirmed(),
+        None,
+        zpl_rpc_client::config::TransactionSimulation {
+            enable: false,
+            send_failed_solana_transaction: false,
+        },
+    )
+}
@@ -0,0 +1,30 @@
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt};
+use testcontainers_modules::postgres::Postgres;
+
+/// Same version as in `dev-support/test-environments/docker-compose`
+const TAG: &str = "17.4";
+
+const PORT: u16 = 5432;
+
+/// Start Postgres, returning a pool to its database with the migrations
+/// applied
+pub async fn start() -> (ContainerAsync<Postgres>, PgPool) {
+    let container = Postgres::default()
+        .with_tag(TAG)
+        .start()
+        .await
+        .expect("Failed to start Postgres container");
+
+    let host = container.get_host().await.expect("Failed to get Postgres host");
+    let port = container.get_host_port_ipv4(PORT).await.expect("Failed to get Postgres port");
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&format!("postgres://postgres:postgres@{host}:{port}/postgres"))
+        .await
+        .expect("Failed to connect to test database");
+
+    sqlx::migrate!("../server/migrations").run(&pool).await.expect("Failed to run migrations");
+
+    (container, pool)
+}